@@ -0,0 +1,381 @@
+//! Copies of journal files taken before the app writes to them, so a write
+//! can be undone
+//!
+//! Each file backed up gets a directory of its own in the backups
+//! directory, named after the file and a hash of its full path. It holds a
+//! `source` file with that path and one copy per write, named by the time
+//! the copy was taken so the oldest sort first.
+
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Copies kept per file unless the settings say otherwise
+pub const DEFAULT_KEEP: usize = 10;
+
+/// Where a backup directory keeps the path of the file it's for
+const SOURCE_FILE: &str = "source";
+
+/// One copy of a file, as it was before a write
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    /// What [`BackupManager::restore`] takes to put this copy back
+    pub id: String,
+    /// The file it's a copy of
+    pub file: String,
+    /// When it was taken, in milliseconds since the Unix epoch
+    pub created_ms: u64,
+    /// Its size in bytes
+    pub size: u64,
+}
+
+/// Takes, lists and restores backups in one directory, keeping a limited
+/// number per file
+#[derive(Debug, Clone)]
+pub struct BackupManager {
+    dir: PathBuf,
+    keep: Arc<AtomicUsize>,
+}
+
+impl BackupManager {
+    /// Keep backups in `dir`, created on first use, at most `keep` per file
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep: Arc::new(AtomicUsize::new(keep)),
+        }
+    }
+
+    /// Change how many backups are kept per file; 0 stops taking them.
+    /// Files with more are trimmed on their next backup.
+    pub fn set_keep(&self, keep: usize) {
+        self.keep.store(keep, Ordering::Relaxed);
+    }
+
+    /// Copy `path` into the backups, dropping its oldest copies beyond the
+    /// limit
+    ///
+    /// A file that doesn't exist yet has nothing to back up, and neither
+    /// does any file while the limit is 0.
+    pub fn back_up(&self, path: &Path) -> io::Result<Option<Backup>> {
+        let keep = self.keep.load(Ordering::Relaxed);
+        if keep == 0 {
+            return Ok(None);
+        }
+        let path = canonical(path);
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let dir_name = backup_dir_name(&path);
+        let dir = self.dir.join(&dir_name);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(SOURCE_FILE), path.to_string_lossy().as_bytes())?;
+
+        // Copies taken within the same millisecond still get their own name
+        let file_name = file_name(&path);
+        let mut created_ms = now_ms();
+        while dir.join(copy_name(created_ms, &file_name)).exists() {
+            created_ms += 1;
+        }
+        let name = copy_name(created_ms, &file_name);
+        fs::write(dir.join(&name), &contents)?;
+
+        let copies = copies(&dir)?;
+        for (old, _) in copies.iter().take(copies.len().saturating_sub(keep)) {
+            fs::remove_file(dir.join(old))?;
+        }
+
+        Ok(Some(Backup {
+            id: format!("{}/{}", dir_name, name),
+            file: path.to_string_lossy().into_owned(),
+            created_ms,
+            size: contents.len() as u64,
+        }))
+    }
+
+    /// The backups of `path`, newest first
+    pub fn list(&self, path: &Path) -> io::Result<Vec<Backup>> {
+        let path = canonical(path);
+        let dir_name = backup_dir_name(&path);
+        let dir = self.dir.join(&dir_name);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let file = path.to_string_lossy().into_owned();
+        let mut backups = Vec::new();
+        for (name, created_ms) in copies(&dir)?.into_iter().rev() {
+            backups.push(Backup {
+                size: fs::metadata(dir.join(&name))?.len(),
+                id: format!("{}/{}", dir_name, name),
+                file: file.clone(),
+                created_ms,
+            });
+        }
+        Ok(backups)
+    }
+
+    /// Put the backup with `id` back in place of its file, backing up what
+    /// the file holds now first so the restore can be undone too
+    pub fn restore(&self, id: &str) -> io::Result<Backup> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, format!("no backup {}", id));
+        let (dir_name, name) = id.split_once('/').ok_or_else(invalid)?;
+        if !is_plain_name(dir_name) || !is_plain_name(name) || name == SOURCE_FILE {
+            return Err(invalid());
+        }
+        let created_ms = created_ms(name).ok_or_else(invalid)?;
+        let dir = self.dir.join(dir_name);
+        let contents = match fs::read(dir.join(name)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(invalid()),
+            Err(e) => return Err(e),
+        };
+        let file = PathBuf::from(fs::read_to_string(dir.join(SOURCE_FILE))?);
+
+        self.back_up(&file)?;
+        replace_file(&file, &contents)?;
+        Ok(Backup {
+            id: id.to_string(),
+            file: file.to_string_lossy().into_owned(),
+            created_ms,
+            size: contents.len() as u64,
+        })
+    }
+
+    /// A hook for the library's writes that backs up each file before it
+    /// changes
+    pub fn write_hook(&self) -> hledger_lib::WriteHook {
+        let manager = self.clone();
+        hledger_lib::WriteHook::new(move |path| {
+            manager.back_up(path)?;
+            Ok(())
+        })
+    }
+}
+
+/// The path with symlinks resolved, as the library writes it, or as given
+/// if it doesn't exist
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "journal".to_string())
+}
+
+/// `NAME-HASH`: readable, and apart from other files with the same name
+fn backup_dir_name(path: &Path) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in path.to_string_lossy().as_bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{}-{:016x}", file_name(path), hash)
+}
+
+/// Zero-padded so names sort by time
+fn copy_name(created_ms: u64, file_name: &str) -> String {
+    format!("{:013}-{}", created_ms, file_name)
+}
+
+fn created_ms(copy_name: &str) -> Option<u64> {
+    copy_name.split_once('-')?.0.parse().ok()
+}
+
+/// The copies in a backup directory with their times, oldest first
+fn copies(dir: &Path) -> io::Result<Vec<(String, u64)>> {
+    let mut copies = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if let Some(created_ms) = created_ms(&name) {
+            copies.push((name, created_ms));
+        }
+    }
+    copies.sort();
+    Ok(copies)
+}
+
+/// A single path component, so an id can't point outside the backups
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Write through a temporary file and a rename, like the library's writes,
+/// keeping the file's permissions
+fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.restore",
+        file_name(path),
+        std::process::id()
+    ));
+    let result = (|| {
+        fs::write(&tmp, contents)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&tmp, metadata.permissions())?;
+        }
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hledger_lib::{PostingBuilder, TransactionBuilder, WriteOptions};
+
+    /// A journal and a backups directory in the temp dir, removed when
+    /// dropped
+    struct Sandbox {
+        root: PathBuf,
+        journal: PathBuf,
+    }
+
+    impl Sandbox {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "hledger-gui-backups-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            let journal = root.join("main.journal");
+            Sandbox { root, journal }
+        }
+
+        fn manager(&self, keep: usize) -> BackupManager {
+            BackupManager::new(self.root.join("backups"), keep)
+        }
+
+        fn write(&self, contents: &str) {
+            fs::write(&self.journal, contents).unwrap();
+        }
+
+        fn read(&self) -> String {
+            fs::read_to_string(&self.journal).unwrap()
+        }
+    }
+
+    impl Drop for Sandbox {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    #[test]
+    fn test_keeps_newest_copies() {
+        let sandbox = Sandbox::new("rotation");
+        let manager = sandbox.manager(3);
+        for i in 1..=5 {
+            sandbox.write(&format!("; version {}\n", i));
+            manager.back_up(&sandbox.journal).unwrap().unwrap();
+        }
+
+        let backups = manager.list(&sandbox.journal).unwrap();
+        assert_eq!(backups.len(), 3);
+        assert!(backups[0].created_ms > backups[1].created_ms);
+        let contents: Vec<String> = backups
+            .iter()
+            .map(|backup| {
+                let path = sandbox.root.join("backups").join(&backup.id);
+                fs::read_to_string(path).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            contents,
+            ["; version 5\n", "; version 4\n", "; version 3\n"]
+        );
+
+        // A lower limit applies from the next backup
+        manager.set_keep(1);
+        manager.back_up(&sandbox.journal).unwrap();
+        assert_eq!(manager.list(&sandbox.journal).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_nothing_to_back_up() {
+        let sandbox = Sandbox::new("nothing");
+        assert_eq!(sandbox.manager(3).back_up(&sandbox.journal).unwrap(), None);
+
+        sandbox.write("; exists\n");
+        let off = sandbox.manager(0);
+        assert_eq!(off.back_up(&sandbox.journal).unwrap(), None);
+        assert!(off.list(&sandbox.journal).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_is_undoable() {
+        let sandbox = Sandbox::new("restore");
+        let manager = sandbox.manager(10);
+        sandbox.write("; before\n");
+        let backup = manager.back_up(&sandbox.journal).unwrap().unwrap();
+        sandbox.write("; after a bad edit\n");
+
+        let restored = manager.restore(&backup.id).unwrap();
+        assert_eq!(restored, backup);
+        assert_eq!(sandbox.read(), "; before\n");
+
+        // What the restore replaced is the newest backup now
+        let backups = manager.list(&sandbox.journal).unwrap();
+        assert_eq!(backups.len(), 2);
+        manager.restore(&backups[0].id).unwrap();
+        assert_eq!(sandbox.read(), "; after a bad edit\n");
+    }
+
+    #[test]
+    fn test_restore_rejects_unknown_ids() {
+        let sandbox = Sandbox::new("ids");
+        let manager = sandbox.manager(10);
+        sandbox.write("; journal\n");
+        let backup = manager.back_up(&sandbox.journal).unwrap().unwrap();
+        let dir_name = backup.id.split_once('/').unwrap().0;
+
+        for id in [
+            "no-slash".to_string(),
+            format!("{}/{}", dir_name, SOURCE_FILE),
+            format!("{}/../../main.journal", dir_name),
+            format!("../{}", backup.id),
+            format!("{}/0000000000001-main.journal", dir_name),
+        ] {
+            let err = manager.restore(&id).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", id);
+        }
+        assert_eq!(sandbox.read(), "; journal\n");
+    }
+
+    #[test]
+    fn test_write_hook_backs_up() {
+        let sandbox = Sandbox::new("hook");
+        let manager = sandbox.manager(10);
+        sandbox.write("2024-01-01 opening\n    assets:cash  $100\n    equity\n");
+
+        let txn = TransactionBuilder::new("2024-02-01".parse().unwrap(), "groceries")
+            .posting(PostingBuilder::new("expenses:food").amount("20".parse().unwrap(), "$"))
+            .posting(PostingBuilder::new("assets:cash"));
+        let options = WriteOptions::new().before_write(manager.write_hook());
+        hledger_lib::append_transaction(&sandbox.journal, &txn, &options).unwrap();
+
+        let backups = manager.list(&sandbox.journal).unwrap();
+        assert_eq!(backups.len(), 1);
+        manager.restore(&backups[0].id).unwrap();
+        assert!(!sandbox.read().contains("groceries"));
+    }
+}
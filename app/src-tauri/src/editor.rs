@@ -0,0 +1,157 @@
+//! Opening a journal at a line in the user's editor
+//!
+//! The editor is a command template from the settings, such as
+//! `code -g {file}:{line}` or `$EDITOR +{line} {file}`. It is split into
+//! arguments here and run directly, never through a shell, so a journal
+//! path can't inject anything into the command.
+
+use std::path::Path;
+
+/// The program and arguments for `template` opening `file` at `line`
+///
+/// Arguments are split on whitespace, with single or double quotes for
+/// ones containing spaces. `{file}` and `{line}` are replaced in any
+/// argument; a template without `{file}` gets the file as its last
+/// argument. A program written as `$NAME` is read from that environment
+/// variable, through `env`.
+pub fn editor_command(
+    template: &str,
+    file: &str,
+    line: u32,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<(String, Vec<String>), String> {
+    let mut words = split_words(template)?;
+    if words.is_empty() {
+        return Err("The editor command is empty".to_string());
+    }
+    if !words.iter().any(|word| word.contains("{file}")) {
+        words.push("{file}".to_string());
+    }
+
+    let line = line.to_string();
+    let mut words = words
+        .into_iter()
+        .map(|word| word.replace("{file}", file).replace("{line}", &line));
+    let program = words.next().unwrap_or_default();
+    let program = match program.strip_prefix('$') {
+        Some(name) => env(name)
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| format!("${} is not set", name))?,
+        None => program,
+    };
+    Ok((program, words.collect()))
+}
+
+/// Split `template` into words, honouring quotes but nothing else a shell
+/// would interpret
+fn split_words(template: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in template.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unclosed quote in editor command: {}", template));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Whether `file` is one of `journal_files`, compared by canonical path so
+/// `..` or a symlink can't name some other file
+pub fn is_journal_file(file: &str, journal_files: &[String]) -> bool {
+    let Ok(file) = Path::new(file).canonicalize() else {
+        return false;
+    };
+    journal_files
+        .iter()
+        .filter_map(|journal| Path::new(journal).canonicalize().ok())
+        .any(|journal| journal == file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_editor_command_templates() {
+        assert_eq!(
+            editor_command("code -g {file}:{line}", "/books/main.journal", 12, no_env).unwrap(),
+            (
+                "code".to_string(),
+                vec!["-g".to_string(), "/books/main.journal:12".to_string()]
+            )
+        );
+
+        // The file goes last when the template doesn't place it
+        let (program, args) = editor_command("vim +{line}", "main.journal", 3, no_env).unwrap();
+        assert_eq!(program, "vim");
+        assert_eq!(args, ["+3", "main.journal"]);
+
+        let (program, args) = editor_command(
+            "'/Applications/My Editor' --line {line}",
+            "a.journal",
+            1,
+            no_env,
+        )
+        .unwrap();
+        assert_eq!(program, "/Applications/My Editor");
+        assert_eq!(args, ["--line", "1", "a.journal"]);
+    }
+
+    #[test]
+    fn test_editor_command_from_environment() {
+        let env = |name: &str| (name == "EDITOR").then(|| "nano".to_string());
+        let (program, args) = editor_command("$EDITOR +{line}", "a.journal", 7, env).unwrap();
+        assert_eq!(program, "nano");
+        assert_eq!(args, ["+7", "a.journal"]);
+
+        assert!(editor_command("$VISUAL", "a.journal", 7, env).is_err());
+    }
+
+    #[test]
+    fn test_file_names_stay_one_argument() {
+        let file = "/books/my journal; rm -rf ~.journal";
+        let (_, args) = editor_command("code -g {file}:{line}", file, 2, no_env).unwrap();
+        assert_eq!(args, ["-g", &format!("{}:2", file)]);
+    }
+
+    #[test]
+    fn test_editor_command_errors() {
+        assert!(editor_command("   ", "a.journal", 1, no_env).is_err());
+        assert!(editor_command("code 'unclosed", "a.journal", 1, no_env).is_err());
+    }
+
+    #[test]
+    fn test_is_journal_file() {
+        let dir = std::env::temp_dir().join(format!("editor-files-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let journal = dir.join("main.journal");
+        std::fs::write(&journal, "").unwrap();
+        let journals = [journal.to_string_lossy().into_owned()];
+
+        let indirect = dir.join("sub/../main.journal");
+        assert!(is_journal_file(&indirect.to_string_lossy(), &journals));
+        assert!(!is_journal_file("/etc/passwd", &journals));
+        assert!(!is_journal_file(
+            &dir.join("missing.journal").to_string_lossy(),
+            &journals
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
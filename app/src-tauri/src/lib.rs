@@ -1,60 +1,680 @@
-use std::sync::{Arc, Mutex};
-use tauri::{Manager, State};
+mod backups;
+mod editor;
+mod settings;
+mod watcher;
+
+use hledger_lib::AppError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use tauri::{Emitter, Manager, State};
 use tauri_plugin_dialog::DialogExt;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 struct AppState {
     hledger_path: Arc<Mutex<Option<String>>>,
+    /// Journal files the picker offers, as saved in the settings store;
+    /// commands act on the journals in `open_journals` instead
+    journal_files: Arc<Mutex<Vec<String>>>,
+    /// Journals open in some window, by the id `open_journal` gave them
+    open_journals: Arc<Mutex<HashMap<String, OpenJournal>>>,
+    /// Number of journals opened so far, for the next id
+    journals_opened: Arc<AtomicU64>,
+    /// Names to complete in the transaction form, per journal
+    suggestions: Arc<hledger_lib::SuggestionCache>,
+    /// What the journal picker shows about each file, until it changes
+    journal_metadata: Arc<hledger_lib::JournalMetadataCache>,
+    /// Reports started with a request id, until they finish
+    running_reports: Arc<Mutex<HashMap<String, hledger_lib::CancelToken>>>,
+    /// Why the settings store couldn't be read at startup, if it couldn't
+    config_error: Arc<Mutex<Option<String>>>,
+    /// Report options used where a command's options leave them out
+    report_defaults: Arc<Mutex<hledger_lib::ReportDefaults>>,
+    /// Copies of files taken before each write; unset if the app has no
+    /// data directory to keep them in
+    backups: Arc<OnceLock<backups::BackupManager>>,
+}
+
+/// A journal open in some window, and what the app keeps for it
+struct OpenJournal {
+    /// The files read together, as with one `-f` per file
+    paths: Vec<String>,
+    /// Reports already read, until the journal changes
+    report_cache: Arc<hledger_lib::ReportCache>,
+    /// Report options for this journal alone, in place of the saved
+    /// defaults
+    report_defaults: Option<hledger_lib::ReportDefaults>,
+    /// Watch over the journal and every file it includes; `None` if it
+    /// couldn't be started
+    watcher: Option<watcher::JournalWatcher>,
+}
+
+impl AppState {
+    /// Forget the reports any open journal read from `path`
+    fn invalidate_reports(&self, path: impl AsRef<std::path::Path>) {
+        for journal in lock(&self.open_journals).values() {
+            journal.report_cache.invalidate(&path);
+        }
+    }
+
+    /// Forget every open journal's reports
+    fn clear_reports(&self) {
+        for journal in lock(&self.open_journals).values() {
+            journal.report_cache.clear();
+        }
+    }
+
+    /// A hook backing up each file before a write changes it, if backups
+    /// are available
+    fn backup_hook(&self) -> Option<hledger_lib::WriteHook> {
+        self.backups.get().map(backups::BackupManager::write_hook)
+    }
+
+    /// The open journals as saved for the next launch
+    fn saved_journals(&self) -> Vec<settings::SavedJournal> {
+        let mut saved: Vec<_> = lock(&self.open_journals)
+            .values()
+            .map(|journal| settings::SavedJournal {
+                paths: journal.paths.clone(),
+                report_defaults: journal.report_defaults.clone(),
+            })
+            .collect();
+        saved.sort_by(|a, b| a.paths.cmp(&b.paths));
+        saved
+    }
+}
+
+/// Lock `mutex` even if a command panicked while holding it
+///
+/// Each lock guards a value that's replaced or updated in a single step, so
+/// a panic can't leave it half-changed, and one failed command shouldn't
+/// make every later one fail too.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Turn a library error into the error the UI receives, its message saying
+/// what failed
+///
+/// A missing or non-executable binary gets a pointer to the hledger path
+/// setting, since that's the only thing the user can do about it.
+fn command_error(action: &str, e: hledger_lib::HLedgerError) -> AppError {
+    use hledger_lib::HLedgerError;
+    match e {
+        HLedgerError::HLedgerNotFound { .. } | HLedgerError::HLedgerNotExecutable { .. } => {
+            let mut error = AppError::from(e);
+            error
+                .message
+                .push_str(". Check the hledger path in Settings.");
+            error
+        }
+        HLedgerError::Cancelled => AppError::from(e),
+        _ => AppError::from(e).context(format_args!("Failed to {}", action)),
+    }
+}
+
+/// Run a report on a blocking thread with the configured hledger path,
+/// keeping the UI and `cancel_report` responsive meanwhile
+///
+/// With a `request_id` the run can be cancelled until it finishes, which
+/// kills hledger and fails the command with the cancelled message.
+async fn run_report<T, F>(
+    state: &AppState,
+    request_id: Option<String>,
+    action: &str,
+    run: F,
+) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&AppState, Option<&str>) -> hledger_lib::Result<T> + Send + 'static,
+{
+    let hledger_path = lock(&state.hledger_path).clone();
+    let token = hledger_lib::CancelToken::new();
+    if let Some(id) = &request_id {
+        lock(&state.running_reports).insert(id.clone(), token.clone());
+    }
+
+    let worker_state = state.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        hledger_lib::with_cancel_token(&token, || run(&worker_state, hledger_path.as_deref()))
+    })
+    .await;
+
+    if let Some(id) = &request_id {
+        lock(&state.running_reports).remove(id);
+    }
+    result
+        .map_err(|e| format!("Failed to {}: {}", action, e))?
+        .map_err(|e| command_error(action, e))
+}
+
+/// Stop the report started with `request_id`; `false` if it isn't running
+#[tauri::command]
+fn cancel_report(request_id: String, state: State<'_, AppState>) -> bool {
+    match lock(&state.running_reports).get(&request_id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
 }
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
 #[tauri::command]
 async fn set_hledger_path(
-    _app: tauri::AppHandle,
+    app: tauri::AppHandle,
     path: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
+    settings::save_hledger_path(&app, &path)?;
+
     // Update state
-    let mut hledger_path = state.hledger_path.lock().unwrap();
+    let mut hledger_path = lock(&state.hledger_path);
     *hledger_path = Some(path.clone());
 
     Ok(())
 }
 
 #[tauri::command]
-fn get_hledger_path(state: State<'_, AppState>) -> Result<Option<String>, String> {
-    let hledger_path = state.hledger_path.lock().unwrap();
+fn get_hledger_path(state: State<'_, AppState>) -> Result<Option<String>, AppError> {
+    let hledger_path = lock(&state.hledger_path);
     Ok(hledger_path.clone())
 }
 
+/// A working hledger, as `test_hledger_path` found it
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestedHledger {
+    /// What `--version` printed
+    version: String,
+    path: String,
+    source: hledger_lib::HledgerSource,
+}
+
+/// Run `hledger --version` from `path`, or without one from the hledger
+/// reports would run (the bundled one, else `PATH`'s), and say which ran
 #[tauri::command]
-fn test_hledger_path(path: String) -> Result<String, String> {
-    let output = std::process::Command::new(&path)
+fn test_hledger_path(path: Option<String>) -> Result<TestedHledger, AppError> {
+    let sidecar = hledger_lib::command_config().sidecar;
+    let resolved = hledger_lib::resolve_hledger(path.as_deref(), sidecar.as_deref())?;
+    let output = std::process::Command::new(&resolved.path)
         .arg("--version")
         .output()
         .map_err(|e| format!("Failed to execute hledger: {}", e))?;
 
     if !output.status.success() {
-        return Err("hledger command failed".to_string());
+        return Err("hledger command failed".into());
     }
 
     let version = String::from_utf8_lossy(&output.stdout);
-    Ok(version.trim().to_string())
+    Ok(TestedHledger {
+        version: version.trim().to_string(),
+        path: resolved.path.to_string_lossy().into_owned(),
+        source: resolved.source,
+    })
+}
+
+/// Where the bundler puts the hledger sidecar: beside the app's own
+/// executable, without the target triple it was built with
+fn sidecar_path() -> Option<std::path::PathBuf> {
+    let exe = tauri::utils::platform::current_exe().ok()?;
+    let name = if cfg!(windows) {
+        "hledger.exe"
+    } else {
+        "hledger"
+    };
+    Some(exe.parent()?.join(name))
+}
+
+/// Look for an installed hledger to prefill the path setting; `None` if
+/// none was found
+#[tauri::command]
+async fn detect_hledger() -> Result<Option<hledger_lib::DetectedHledger>, AppError> {
+    // Probing runs several binaries, so keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(hledger_lib::detect_hledger)
+        .await
+        .map_err(|e| format!("Failed to detect hledger: {}", e).into())
+}
+
+/// Longest the startup health check may take
+const HEALTH_CHECK_BUDGET: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Check hledger, the files of open journal `journal_id` and the saved
+/// settings in one go, for the status shown at startup; without an id, no
+/// journal is checked
+#[tauri::command]
+async fn health_check(
+    journal_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::HealthReport, AppError> {
+    use hledger_lib::{HealthCode, HealthItem, HealthStatus};
+    let hledger_path = lock(&state.hledger_path).clone();
+    let journal_files = match journal_id {
+        Some(journal_id) => journal_paths(&state, &journal_id)?,
+        None => Vec::new(),
+    };
+    let config_error = lock(&state.config_error).clone();
+
+    let mut report = tauri::async_runtime::spawn_blocking(move || {
+        hledger_lib::health_check(hledger_path.as_deref(), &journal_files, HEALTH_CHECK_BUDGET)
+    })
+    .await
+    .map_err(|e| format!("Failed to run health check: {}", e))?;
+
+    report.push(match config_error {
+        None => HealthItem::new(
+            HealthCode::ConfigLoaded,
+            HealthStatus::Ok,
+            "Settings were loaded",
+        ),
+        Some(e) => HealthItem::new(
+            HealthCode::ConfigFailed,
+            HealthStatus::Error,
+            format!("Couldn't read {}: {}", settings::STORE_FILE, e),
+        ),
+    });
+    Ok(report)
 }
 
+/// Remember the journal files the picker offers, for this session and the
+/// next
 #[tauri::command]
-async fn select_journal_files(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+fn set_journal_files(
+    app: tauri::AppHandle,
+    files: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    settings::save_journal_paths(&app, &files)?;
+    *lock(&state.journal_files) = files;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_editor_command(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    Ok(settings::load_editor_command(&app)?)
+}
+
+/// Save the command "open in editor" runs, e.g. `code -g {file}:{line}` or
+/// `$EDITOR +{line} {file}`; an empty one reveals files in the file manager
+/// instead
+#[tauri::command]
+fn set_editor_command(app: tauri::AppHandle, command: Option<String>) -> Result<(), AppError> {
+    let command = command.filter(|command| !command.trim().is_empty());
+    if let Some(command) = &command {
+        // Catch a malformed template now rather than on first use
+        editor::editor_command(command, "journal", 1, |_| Some("editor".to_string()))?;
+    }
+    Ok(settings::save_editor_command(&app, command.as_deref())?)
+}
+
+/// Open `file` at `line` with the saved editor command, or reveal it in the
+/// file manager if there is none
+///
+/// Only the files of open journal `journal_id` and the files they include
+/// can be opened.
+#[tauri::command]
+async fn open_in_editor(
+    app: tauri::AppHandle,
+    journal_id: String,
+    file: String,
+    line: u32,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let journals = journal_paths(&state, &journal_id)?;
+    let allowed = run_report(&state, None, "open in editor", move |_, hledger_path| {
+        let mut files = Vec::new();
+        for journal in &journals {
+            files.extend(hledger_lib::get_files(hledger_path, Some(journal))?);
+        }
+        Ok(files)
+    })
+    .await?;
+    if !editor::is_journal_file(&file, &allowed) {
+        return Err(format!("{} is not one of the journal's files", file).into());
+    }
+
+    match settings::load_editor_command(&app)? {
+        Some(template) => {
+            let (program, args) =
+                editor::editor_command(&template, &file, line, |name| std::env::var(name).ok())?;
+            std::process::Command::new(&program)
+                .args(&args)
+                .spawn()
+                .map_err(|e| format!("Failed to start editor {}: {}", program, e))?;
+        }
+        None => tauri_plugin_opener::reveal_item_in_dir(&file)
+            .map_err(|e| format!("Failed to show {}: {}", file, e))?,
+    }
+    Ok(())
+}
+
+/// Each of `paths` with the files it reads, itself first
+///
+/// A journal hledger can't read is still watched, so fixing it is noticed.
+fn journal_files_of(hledger_path: Option<&str>, paths: &[String]) -> Vec<watcher::JournalFiles> {
+    paths
+        .iter()
+        .map(|path| {
+            let mut files = vec![path.into()];
+            match hledger_lib::get_files(hledger_path, Some(path)) {
+                Ok(included) => files.extend(included.into_iter().map(Into::into)),
+                Err(e) => eprintln!("Warning: watching only {}: {}", path, e),
+            }
+            watcher::JournalFiles {
+                journal: path.clone(),
+                files,
+            }
+        })
+        .collect()
+}
+
+/// Payload of `journal-changed`: the change, and the open journal whose
+/// files it's in
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalChanged {
+    journal_id: String,
+    #[serde(flatten)]
+    change: watcher::JournalChange,
+}
+
+/// Watch `journals`, the files of open journal `journal_id`, dropping its
+/// cached reports and emitting `journal-changed` whenever one changes
+fn watch_open_journal(
+    app: &tauri::AppHandle,
+    journal_id: &str,
+    journals: &[watcher::JournalFiles],
+    report_cache: Arc<hledger_lib::ReportCache>,
+) -> Result<watcher::JournalWatcher, String> {
+    let app = app.clone();
+    let journal_id = journal_id.to_string();
+    let suggestions = app.state::<AppState>().suggestions.clone();
+    watcher::JournalWatcher::new(journals, move |change| {
+        report_cache.invalidate(&change.path);
+        suggestions.mark_stale();
+        // Off the watcher's thread, since it may replace this watch
+        let (app, id) = (app.clone(), journal_id.clone());
+        std::thread::spawn(move || follow_includes(&app, &id));
+        let event = JournalChanged {
+            journal_id: journal_id.clone(),
+            change,
+        };
+        if let Err(e) = app.emit(watcher::CHANGE_EVENT, event) {
+            eprintln!("Warning: couldn't send journal change: {}", e);
+        }
+    })
+}
+
+/// Restart open journal `journal_id`'s watch if it now includes different
+/// files, so added includes are watched and removed ones dropped
+fn follow_includes(app: &tauri::AppHandle, journal_id: &str) {
+    let state = app.state::<AppState>();
+    let hledger_path = lock(&state.hledger_path).clone();
+    let paths = match lock(&state.open_journals).get(journal_id) {
+        Some(journal) => journal.paths.clone(),
+        None => return,
+    };
+    let current = journal_files_of(hledger_path.as_deref(), &paths);
+    let report_cache = match lock(&state.open_journals).get(journal_id) {
+        Some(OpenJournal {
+            watcher: Some(watcher),
+            report_cache,
+            ..
+        }) if current.iter().any(|j| watcher.includes_changed(j)) => report_cache.clone(),
+        _ => return,
+    };
+    match watch_open_journal(app, journal_id, &current, report_cache) {
+        Ok(watcher) => {
+            // Unless the journal was closed meanwhile
+            if let Some(journal) = lock(&state.open_journals).get_mut(journal_id) {
+                journal.watcher = Some(watcher);
+            }
+        }
+        Err(e) => eprintln!("Warning: couldn't watch the journal's new includes: {}", e),
+    }
+}
+
+/// Open a journal reading `paths` and watch its files, returning its id; a
+/// journal already open with the same paths keeps its id
+fn open_journal_paths(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    paths: Vec<String>,
+    report_defaults: Option<hledger_lib::ReportDefaults>,
+) -> Result<String, String> {
+    let open = lock(&state.open_journals)
+        .iter()
+        .find(|(_, journal)| journal.paths == paths)
+        .map(|(id, _)| id.clone());
+    if let Some(journal_id) = open {
+        return Ok(journal_id);
+    }
+    // Fail now rather than on every report
+    hledger_lib::combined_journal(&paths)
+        .map_err(|e| format!("Failed to read the selected journals: {}", e))?;
+
+    let number = state.journals_opened.fetch_add(1, Ordering::Relaxed) + 1;
+    let journal_id = format!("journal-{}", number);
+    let hledger_path = lock(&state.hledger_path).clone();
+    let journals = journal_files_of(hledger_path.as_deref(), &paths);
+    let report_cache = Arc::new(hledger_lib::ReportCache::default());
+    // Reports still work unwatched, only without noticing outside edits
+    let watcher = watch_open_journal(app, &journal_id, &journals, report_cache.clone())
+        .map_err(|e| eprintln!("Warning: not watching {}: {}", paths.join(", "), e))
+        .ok();
+    lock(&state.open_journals).insert(
+        journal_id.clone(),
+        OpenJournal {
+            paths,
+            report_cache,
+            report_defaults,
+            watcher,
+        },
+    );
+    Ok(journal_id)
+}
+
+/// Open a journal reading `paths` together, or hledger's default journal
+/// when there are none, and return the id report commands take for it
+///
+/// Its files are watched until it's closed, with `journal-changed` events
+/// naming the id, and it's reopened on the next launch unless closed
+/// first. Opening the same paths again gives the same id.
+#[tauri::command]
+fn open_journal(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<String, AppError> {
+    let journal_id = open_journal_paths(&app, &state, paths, None)?;
+    settings::save_open_journals(&app, &state.saved_journals())?;
+    Ok(journal_id)
+}
+
+/// Stop watching the journal and forget its reports; closing a journal
+/// that isn't open does nothing
+#[tauri::command]
+fn close_journal(
+    app: tauri::AppHandle,
+    journal_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let closed = lock(&state.open_journals).remove(&journal_id);
+    if closed.is_some() {
+        settings::save_open_journals(&app, &state.saved_journals())?;
+    }
+    Ok(())
+}
+
+/// An open journal, as `get_open_journals` lists it
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenJournalInfo {
+    journal_id: String,
+    paths: Vec<String>,
+}
+
+/// The open journals, including those reopened from the last session
+#[tauri::command]
+fn get_open_journals(state: State<'_, AppState>) -> Vec<OpenJournalInfo> {
+    let mut open: Vec<_> = lock(&state.open_journals)
+        .iter()
+        .map(|(journal_id, journal)| OpenJournalInfo {
+            journal_id: journal_id.clone(),
+            paths: journal.paths.clone(),
+        })
+        .collect();
+    open.sort_by(|a, b| a.paths.cmp(&b.paths));
+    open
+}
+
+/// The remembered journal files, each flagged with whether it still
+/// exists; missing ones are kept for the user to remove or relocate
+#[tauri::command]
+fn get_journal_files(state: State<'_, AppState>) -> Vec<settings::JournalFile> {
+    settings::journal_files(&lock(&state.journal_files))
+}
+
+/// The journal hledger reads when it isn't given one (`LEDGER_FILE`, else
+/// `~/.hledger.journal`), to offer when no journal is selected
+///
+/// Report commands read it when given `<default>` as their journal file, or
+/// none at all.
+#[tauri::command]
+fn get_default_journal() -> Option<hledger_lib::DefaultJournal> {
+    hledger_lib::default_journal()
+}
+
+/// Size, modification time, transaction count, date span, commodities and
+/// included files of a journal, for the file picker
+///
+/// Figures hledger can't give quickly (a very large file, a journal that
+/// doesn't parse) are left out with a note; results are reused until the
+/// file changes.
+#[tauri::command]
+async fn get_journal_metadata(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::JournalMetadata, AppError> {
+    run_report(
+        &state,
+        None,
+        "read journal metadata",
+        move |state, hledger_path| state.journal_metadata.get(hledger_path, &path),
+    )
+    .await
+}
+
+/// Check that a journal can be read before running reports on it, with
+/// hledger's named `checks` (e.g. `accounts`) on top of the basic ones
+///
+/// Problems with the journal come back as issues for the UI to show
+/// inline; only a failure to run hledger is an error.
+#[tauri::command]
+async fn validate_journal(
+    journal_file: String,
+    checks: Vec<String>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::CheckIssue>, AppError> {
+    run_report(
+        &state,
+        request_id,
+        "validate journal",
+        move |_, hledger_path| {
+            hledger_lib::get_check(hledger_path, Some(journal_file.as_str()), &checks)
+        },
+    )
+    .await
+}
+
+/// Whether a file dropped on the window should be added as a journal: it
+/// must have a journal's extension, be text, and be readable by hledger
+/// within a few seconds
+#[tauri::command]
+async fn validate_dropped_file(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::DroppedFile, AppError> {
+    run_report(
+        &state,
+        None,
+        "check dropped file",
+        move |_, hledger_path| hledger_lib::validate_dropped_file(hledger_path, &path),
+    )
+    .await
+}
+
+/// The text encoding of `path`, for files hledger can't read as they are
+#[tauri::command]
+fn detect_journal_encoding(path: String) -> Result<hledger_lib::JournalEncoding, AppError> {
+    let path = std::path::Path::new(&path);
+    Ok(hledger_lib::detect_journal_encoding(path)?)
+}
+
+/// Rewrite `path` in UTF-8 after backing it up, returning the encoding it
+/// was in
+#[tauri::command]
+async fn convert_journal_to_utf8(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::JournalEncoding, AppError> {
+    run_report(&state, None, "convert to UTF-8", move |state, _| {
+        let options = hledger_lib::WriteOptions {
+            before_write: state.backup_hook(),
+            ..hledger_lib::WriteOptions::default()
+        };
+        let path = std::path::Path::new(&path);
+        let converted = hledger_lib::convert_journal_to_utf8(path, &options)?;
+        state.invalidate_reports(path);
+        Ok(converted)
+    })
+    .await
+}
+
+/// Write a starter journal at `path` and add it to the journal files,
+/// returning the files written
+#[tauri::command]
+async fn create_journal(
+    app: tauri::AppHandle,
+    path: String,
+    template: hledger_lib::JournalTemplate,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, AppError> {
+    let main = path.clone();
+    let files = run_report(&state, None, "create journal", move |_, hledger_path| {
+        let options = hledger_lib::WriteOptions {
+            hledger_path: hledger_path.map(str::to_string),
+            ..hledger_lib::WriteOptions::default()
+        };
+        hledger_lib::create_journal(std::path::Path::new(&path), &template, &options)
+    })
+    .await?;
+
+    let mut journals = lock(&state.journal_files).clone();
+    if !journals.contains(&main) {
+        journals.push(main);
+        settings::save_journal_paths(&app, &journals)?;
+        *lock(&state.journal_files) = journals;
+    }
+    Ok(files)
+}
+
+#[tauri::command]
+async fn select_journal_files(app: tauri::AppHandle) -> Result<Vec<String>, AppError> {
     use std::sync::mpsc;
 
     let (tx, rx) = mpsc::channel();
 
     app.dialog()
         .file()
-        .add_filter("Journal Files", &["journal", "ledger", "hledger", "dat"])
+        .add_filter("Journal Files", hledger_lib::JOURNAL_EXTENSIONS)
         .set_title("Select hledger Journal Files")
         .pick_files(move |file_paths| {
-            tx.send(file_paths).unwrap();
+            // Nothing is left to tell if the command stopped waiting
+            let _ = tx.send(file_paths);
         });
 
     match rx.recv() {
@@ -68,104 +688,1405 @@ async fn select_journal_files(app: tauri::AppHandle) -> Result<Vec<String>, Stri
             Ok(vec![])
         }
         Err(_) => {
-            println!("Error receiving file selection");
-            Ok(vec![])
+            Err("Failed to select journal files: the file dialog closed without an answer".into())
         }
     }
 }
 
+/// What a report reads for an open journal, copied out of the state so
+/// the report doesn't hold its lock
+struct ReportJournal {
+    /// The journal file hledger is given; `None` for its default journal
+    file: Option<String>,
+    cache: Arc<hledger_lib::ReportCache>,
+    /// The journal's own report defaults, else the saved ones
+    defaults: hledger_lib::ReportDefaults,
+}
+
+impl ReportJournal {
+    fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// A report's options: the fields the call set, over the report
+    /// defaults
+    fn options<O>(&self, options: serde_json::Value) -> Result<O, AppError>
+    where
+        O: Default + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        hledger_lib::merge_options(&self.defaults, options)
+            .map_err(|e| command_error("read report options", e))
+    }
+}
+
+/// Run `report`, reading it afresh for a command called with `cache: false`:
+/// its cached copy is replaced, and the journal's other reports stay cached
+fn read_report<T>(cache: Option<bool>, report: impl FnOnce() -> T) -> T {
+    if cache == Some(false) {
+        hledger_lib::with_refresh(report)
+    } else {
+        report()
+    }
+}
+
+/// The files open journal `journal_id` reads together
+fn journal_paths(state: &AppState, journal_id: &str) -> Result<Vec<String>, AppError> {
+    lock(&state.open_journals)
+        .get(journal_id)
+        .map(|journal| journal.paths.clone())
+        .ok_or_else(|| format!("No open journal {}", journal_id).into())
+}
+
+/// The open journal `journal_id`, for a report command
+///
+/// Its paths are read together, as with one `-f` per file; none reads
+/// hledger's default journal (`LEDGER_FILE`, else `~/.hledger.journal`).
+fn report_journal(state: &AppState, journal_id: &str) -> Result<ReportJournal, AppError> {
+    let (paths, cache, defaults) = {
+        let journals = lock(&state.open_journals);
+        let journal = journals
+            .get(journal_id)
+            .ok_or_else(|| format!("No open journal {}", journal_id))?;
+        (
+            journal.paths.clone(),
+            journal.report_cache.clone(),
+            journal.report_defaults.clone(),
+        )
+    };
+    let file = hledger_lib::combined_journal(&paths)
+        .map_err(|e| format!("Failed to read the selected journals: {}", e))?;
+    let defaults = defaults.unwrap_or_else(|| lock(&state.report_defaults).clone());
+    Ok(ReportJournal {
+        file,
+        cache,
+        defaults,
+    })
+}
+
+/// The report defaults of open journal `journal_id`, or the saved ones
+/// without an id
 #[tauri::command]
-fn get_accounts(
-    journal_file: String,
-    options: hledger_lib::AccountsOptions,
+fn get_report_defaults(
+    journal_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Vec<String>, String> {
-    let hledger_path = state.hledger_path.lock().unwrap();
-    let path_ref = hledger_path.as_ref().map(|s| s.as_str());
+) -> Result<hledger_lib::ReportDefaults, AppError> {
+    match journal_id {
+        Some(journal_id) => Ok(report_journal(&state, &journal_id)?.defaults),
+        None => Ok(lock(&state.report_defaults).clone()),
+    }
+}
 
-    let file_ref = Some(journal_file.as_str());
-    match hledger_lib::get_accounts(path_ref, file_ref, &options) {
-        Ok(accounts) => Ok(accounts),
-        Err(e) => Err(format!("Failed to get accounts: {}", e)),
+/// Save the options reports use unless a call sets them, for this session
+/// and the next: for open journal `journal_id` alone, or without an id for
+/// every journal that has none of its own
+#[tauri::command]
+fn set_report_defaults(
+    app: tauri::AppHandle,
+    journal_id: Option<String>,
+    defaults: hledger_lib::ReportDefaults,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let Some(journal_id) = journal_id else {
+        settings::save_report_defaults(&app, &defaults)?;
+        *lock(&state.report_defaults) = defaults;
+        return Ok(());
+    };
+    match lock(&state.open_journals).get_mut(&journal_id) {
+        Some(journal) => journal.report_defaults = Some(defaults),
+        None => return Err(format!("No open journal {}", journal_id).into()),
     }
+    Ok(settings::save_open_journals(&app, &state.saved_journals())?)
 }
 
 #[tauri::command]
-fn get_balance(
-    journal_file: String,
-    options: hledger_lib::BalanceOptions,
+async fn get_accounts(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::AccountsOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get accounts",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_accounts_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_accounts_detailed(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::AccountInfo>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::AccountsOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get accounts",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_accounts_detailed_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_account_tree(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::AccountNode>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::AccountsOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get accounts",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_account_tree_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+/// The account tree with each account's balance, from one balance report
+#[tauri::command]
+async fn get_account_balance_tree(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::AccountBalanceNode>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::BalanceOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get account balances",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal.cache.get_account_balance_tree_cached(
+                    hledger_path,
+                    journal.file(),
+                    &options,
+                )
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn find_account(
+    journal_id: String,
+    pattern: String,
+    options: serde_json::Value,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::AccountsOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "find account",
+        move |_, hledger_path| {
+            hledger_lib::find_account(hledger_path, journal.file(), &pattern, &options)
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_balance(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::BalanceReport, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::BalanceOptions = journal.options(options)?;
+    run_report(&state, request_id, "get balance", move |_, hledger_path| {
+        read_report(cache, || {
+            journal
+                .cache
+                .get_balance_cached(hledger_path, journal.file(), &options)
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_balancesheet(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<hledger_lib::BalanceReport, String> {
-    let hledger_path = state.hledger_path.lock().unwrap();
-    let path_ref = hledger_path.as_ref().map(|s| s.as_str());
+) -> Result<hledger_lib::BalanceSheetReport, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::BalanceSheetOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get balancesheet",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_balancesheet_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_budget_report(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::BudgetReport, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::BudgetOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get budget report",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_budget_report_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
 
-    let file_ref = Some(journal_file.as_str());
-    match hledger_lib::get_balance(path_ref, file_ref, &options) {
-        Ok(balance) => Ok(balance),
-        Err(e) => Err(format!("Failed to get balance: {}", e)),
+#[tauri::command]
+async fn verify_assertions(
+    journal_id: String,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::AssertionFailure>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    run_report(
+        &state,
+        request_id,
+        "verify balance assertions",
+        move |_, hledger_path| hledger_lib::verify_assertions(hledger_path, journal.file()),
+    )
+    .await
+}
+
+/// The journal's problems, located so the editor can open them; a failing
+/// balance assertion comes with its asserted and calculated balances
+#[tauri::command]
+async fn get_journal_problems(
+    journal_id: String,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::JournalProblem>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    run_report(
+        &state,
+        request_id,
+        "check journal",
+        move |_, hledger_path| hledger_lib::get_journal_problems(hledger_path, journal.file()),
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_prices(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::MarketPrice>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::PricesOptions = journal.options(options)?;
+    run_report(&state, request_id, "get prices", move |_, hledger_path| {
+        read_report(cache, || {
+            journal
+                .cache
+                .get_prices_cached(hledger_path, journal.file(), &options)
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn top_spending_accounts(
+    journal_id: String,
+    n: usize,
+    options: serde_json::Value,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::AccountTotal>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::TopSpendingOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get top spending accounts",
+        move |_, hledger_path| {
+            hledger_lib::top_spending_accounts(hledger_path, journal.file(), n, &options)
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_flow_data(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::FlowGraph, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::FlowOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get flow data",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_flow_data_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_networth_series(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::NetworthPoint>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::NetworthOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get net worth",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_networth_series_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+/// Display strings for amounts in the journal's own commodity styles, which
+/// are learned from one print of the journal and cached with its reports
+#[tauri::command]
+async fn format_amounts(
+    journal_id: String,
+    amounts: Vec<hledger_lib::commands::balance::Amount>,
+    options: Option<hledger_lib::FormatOptions>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options = options.unwrap_or_default();
+    run_report(
+        &state,
+        request_id,
+        "format amounts",
+        move |_, hledger_path| {
+            let styles = journal
+                .cache
+                .get_commodity_styles_cached(hledger_path, journal.file())?;
+            Ok(hledger_lib::format_amounts(&amounts, &styles, &options))
+        },
+    )
+    .await
+}
+
+/// Every tag with how many transactions and postings use it, and their
+/// sums, overall and per value
+#[tauri::command]
+async fn get_tag_usage(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::TagUsage>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::TagsOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get tag usage",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_tag_usage_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_timeseries(
+    journal_id: String,
+    spec: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::Series>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let spec: hledger_lib::TimeseriesSpec = journal.options(spec)?;
+    run_report(
+        &state,
+        request_id,
+        "get time series",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_timeseries_cached(hledger_path, journal.file(), &spec)
+            })
+        },
+    )
+    .await
+}
+
+/// The income statement for `period_a` and `period_b` side by side, one
+/// row per account with its change between them
+#[tauri::command]
+async fn compare_periods(
+    journal_id: String,
+    period_a: String,
+    period_b: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::ComparisonRow>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::IncomeStatementOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "compare periods",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal.cache.compare_periods_cached(
+                    hledger_path,
+                    journal.file(),
+                    &period_a,
+                    &period_b,
+                    &options,
+                )
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_cashflow(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::CashflowReport, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::CashflowOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get cashflow",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_cashflow_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_incomestatement(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::IncomeStatementReport, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::IncomeStatementOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get incomestatement",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_incomestatement_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn get_print(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::PrintReport, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::PrintOptions = journal.options(options)?;
+    run_report(&state, request_id, "get print", move |_, hledger_path| {
+        read_report(cache, || {
+            journal
+                .cache
+                .get_print_cached(hledger_path, journal.file(), &options)
+        })
+    })
+    .await
+}
+
+/// One page of the print report, so the transactions table can load
+/// incrementally instead of receiving the whole journal at once
+#[tauri::command]
+async fn get_print_page(
+    journal_id: String,
+    options: serde_json::Value,
+    page: hledger_lib::PageSpec,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::PrintPage, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::PrintOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get print page",
+        move |_, hledger_path| {
+            hledger_lib::get_print_paged(hledger_path, journal.file(), &options, page)
+        },
+    )
+    .await
+}
+
+/// Transactions due in the next `horizon_days` days: future-dated ones and
+/// those the journal's periodic rules generate
+#[tauri::command]
+async fn get_upcoming_transactions(
+    journal_id: String,
+    horizon_days: u32,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::UpcomingTransaction>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    run_report(
+        &state,
+        request_id,
+        "get upcoming transactions",
+        move |_, hledger_path| {
+            journal.cache.get_upcoming_transactions_cached(
+                hledger_path,
+                journal.file(),
+                None,
+                horizon_days,
+            )
+        },
+    )
+    .await
+}
+
+/// Transactions matching every word of `query`, latest first, with the
+/// ranges to highlight; the search index is kept until the journal changes
+#[tauri::command]
+async fn search_transactions(
+    journal_id: String,
+    query: String,
+    limit: usize,
+    offset: usize,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::SearchResults, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    run_report(
+        &state,
+        request_id,
+        "search transactions",
+        move |_, hledger_path| {
+            journal.cache.search_transactions_cached(
+                hledger_path,
+                journal.file(),
+                &query,
+                limit,
+                offset,
+            )
+        },
+    )
+    .await
+}
+
+/// Run several reports (accounts, balance sheet, income statement,
+/// cashflow...) at once rather than one invoke after another
+///
+/// Responses come back in request order, with any warnings hledger printed
+/// for a non-blocking banner; the first failure fails the whole command.
+#[tauri::command]
+async fn get_reports(
+    journal_id: String,
+    requests: Vec<hledger_lib::ReportRequest>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::ReportOutput<Vec<hledger_lib::ReportResponse>>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    // Warnings are collected per thread, so the batch runs on one blocking
+    // thread (its reports still run in parallel) instead of tokio's pool
+    run_report(&state, request_id, "get reports", move |_, hledger_path| {
+        hledger_lib::with_warnings(|| {
+            hledger_lib::run_reports(hledger_path, journal.file(), requests)
+                .into_iter()
+                .collect::<hledger_lib::Result<Vec<_>>>()
+        })
+    })
+    .await
+}
+
+/// The home screen's summary of `month` (`YYYY-MM`, the current month when
+/// omitted): net worth, income and expenses against last month, and the
+/// transaction count
+///
+/// A report that fails only marks its section unavailable.
+#[tauri::command]
+async fn get_dashboard(
+    journal_id: String,
+    month: Option<String>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::DashboardSummary, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    run_report(
+        &state,
+        request_id,
+        "load dashboard",
+        move |_, hledger_path| {
+            hledger_lib::dashboard_summary(
+                hledger_path,
+                journal.file(),
+                month.as_deref(),
+                &journal.defaults,
+            )
+        },
+    )
+    .await
+}
+
+/// Up to `limit` account, payee, commodity or tag names matching
+/// `prefix`, most used first, for completing as the user types
+///
+/// Never waits for hledger: the names come from an index of the journal
+/// that is built in the background on first use and after it changes, with
+/// `refreshing` set meanwhile so the form can ask again.
+#[tauri::command]
+fn get_suggestions(
+    journal_id: String,
+    kind: hledger_lib::SuggestionKind,
+    prefix: String,
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::Suggestions, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let hledger_path = lock(&state.hledger_path).clone();
+    Ok(state.suggestions.suggest(
+        hledger_path.as_deref(),
+        journal.file(),
+        kind,
+        &prefix,
+        limit,
+    ))
+}
+
+/// A template for the transaction form from the latest transaction with a
+/// description like `description`: its accounts, amounts and tags, and when
+/// it happened
+///
+/// Answers from the same background index as `get_suggestions`, so it never
+/// waits for hledger.
+#[tauri::command]
+fn suggest_transaction(
+    journal_id: String,
+    description: String,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::TransactionSuggestion, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let hledger_path = lock(&state.hledger_path).clone();
+    Ok(state
+        .suggestions
+        .suggest_transaction(hledger_path.as_deref(), journal.file(), &description))
+}
+
+/// Payee and monthly totals of the transactions `options` selects, so the
+/// dashboard doesn't have to ship the whole print report over IPC
+#[tauri::command]
+async fn summarize_transactions(
+    journal_id: String,
+    options: serde_json::Value,
+    account_filter: Option<String>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::TransactionSummary, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let mut options: hledger_lib::PrintOptions = journal.options(options)?;
+    // Inferred amounts are only in the report when shown explicitly
+    options.explicit = true;
+    run_report(
+        &state,
+        request_id,
+        "summarize transactions",
+        move |_, hledger_path| {
+            let transactions = hledger_lib::get_print(hledger_path, journal.file(), &options)?;
+            Ok(hledger_lib::summarize_transactions(
+                &transactions,
+                account_filter.as_deref(),
+            ))
+        },
+    )
+    .await
+}
+
+/// Count, date span, totals and usual account of each payee, so the
+/// merchants screen doesn't need the transactions themselves
+#[tauri::command]
+async fn get_payee_stats(
+    journal_id: String,
+    options: serde_json::Value,
+    cache: Option<bool>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::PayeeStats>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let options: hledger_lib::PayeeStatsOptions = journal.options(options)?;
+    run_report(
+        &state,
+        request_id,
+        "get payee stats",
+        move |_, hledger_path| {
+            read_report(cache, || {
+                journal
+                    .cache
+                    .get_payee_stats_cached(hledger_path, journal.file(), &options)
+            })
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+async fn find_duplicate_candidates(
+    journal_id: String,
+    options: serde_json::Value,
+    tolerance: hledger_lib::DuplicateTolerance,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<hledger_lib::DuplicateGroup>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let mut options: hledger_lib::PrintOptions = journal.options(options)?;
+    // Inferred amounts are only in the report when shown explicitly
+    options.explicit = true;
+    run_report(
+        &state,
+        request_id,
+        "find duplicate transactions",
+        move |_, hledger_path| {
+            let transactions = hledger_lib::get_print(hledger_path, journal.file(), &options)?;
+            Ok(hledger_lib::find_duplicate_candidates(
+                &transactions,
+                tolerance,
+            ))
+        },
+    )
+    .await
+}
+
+/// Options for a report named at runtime, as in `get_report_raw`, over the
+/// saved report defaults
+fn parse_options<T>(
+    defaults: &hledger_lib::ReportDefaults,
+    value: serde_json::Value,
+) -> Result<T, String>
+where
+    T: Default + serde::Serialize + serde::de::DeserializeOwned,
+{
+    hledger_lib::merge_options(defaults, value)
+        .map_err(|e| format!("Invalid report options: {}", e))
+}
+
+/// Run a report and return hledger's JSON as-is, for experimenting with
+/// fields the typed reports don't expose yet
+#[tauri::command]
+async fn get_report_raw(
+    journal_id: String,
+    kind: String,
+    options: serde_json::Value,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    let action = format!("get {} report", kind);
+    // Bad options and unknown kinds fail as they are, not as hledger errors
+    Ok(run_report(
+        &state,
+        request_id,
+        &action,
+        move |_, hledger_path| match raw_report(
+            hledger_path,
+            journal.file(),
+            &kind,
+            &journal.defaults,
+            options,
+        ) {
+            Ok(report) => report.map(Ok),
+            Err(e) => Ok(Err(e)),
+        },
+    )
+    .await??)
+}
+
+/// The raw report of `kind`; the outer error is for requests that can't be
+/// run at all
+fn raw_report(
+    path_ref: Option<&str>,
+    file_ref: Option<&str>,
+    kind: &str,
+    defaults: &hledger_lib::ReportDefaults,
+    options: serde_json::Value,
+) -> Result<hledger_lib::Result<serde_json::Value>, String> {
+    Ok(match kind {
+        "balance" => {
+            hledger_lib::get_balance_raw(path_ref, file_ref, &parse_options(defaults, options)?)
+        }
+        "balancesheet" => hledger_lib::get_balancesheet_raw(
+            path_ref,
+            file_ref,
+            &parse_options(defaults, options)?,
+        ),
+        "incomestatement" => hledger_lib::get_incomestatement_raw(
+            path_ref,
+            file_ref,
+            &parse_options(defaults, options)?,
+        ),
+        "cashflow" => hledger_lib::get_cashflow_raw(
+            path_ref,
+            file_ref.map(std::path::Path::new),
+            parse_options(defaults, options)?,
+        ),
+        "print" => {
+            hledger_lib::get_print_raw(path_ref, file_ref, &parse_options(defaults, options)?)
+        }
+        _ => return Err(format!("Unknown report kind: {}", kind)),
+    })
+}
+
+/// Contents of an exported report: the table for CSV and TSV, with one
+/// column per period and the totals for multi-period reports, or the report
+/// itself for JSON
+fn export_contents<R: serde::Serialize>(
+    report: &R,
+    table: impl FnOnce() -> hledger_lib::Result<hledger_lib::CsvReport>,
+    format: hledger_lib::ExportFormat,
+) -> hledger_lib::Result<String> {
+    use hledger_lib::ExportFormat;
+    match format {
+        ExportFormat::Csv => table()?.to_csv_string(),
+        ExportFormat::Tsv => table()?.to_tsv_string(),
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(report)?),
     }
 }
 
+/// Run a report (or reuse the cached one) and save it where the user picks,
+/// as CSV, TSV or JSON
+///
+/// The dialog suggests a name for the report and its period, e.g.
+/// `incomestatement-2024-06.csv`. Returns the saved path, or `None` when the
+/// dialog was cancelled.
 #[tauri::command]
-fn get_balancesheet(
-    journal_file: String,
-    options: hledger_lib::BalanceSheetOptions,
+async fn export_report(
+    app: tauri::AppHandle,
+    kind: String,
+    journal_id: String,
+    options: serde_json::Value,
+    format: hledger_lib::ExportFormat,
     state: State<'_, AppState>,
-) -> Result<hledger_lib::BalanceSheetReport, String> {
-    let hledger_path = state.hledger_path.lock().unwrap();
-    let path_ref = hledger_path.as_ref().map(|s| s.as_str());
+) -> Result<Option<String>, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    use std::sync::mpsc;
 
-    let file_ref = Some(journal_file.as_str());
-    match hledger_lib::get_balancesheet(path_ref, file_ref, &options) {
-        Ok(balancesheet) => Ok(balancesheet),
-        Err(e) => Err(format!("Failed to get balancesheet: {}", e)),
+    if ![
+        "balance",
+        "balancesheet",
+        "incomestatement",
+        "cashflow",
+        "print",
+    ]
+    .contains(&kind.as_str())
+    {
+        return Err(format!("Unknown report kind: {}", kind).into());
+    }
+    let report_kind = kind.clone();
+    let (contents, dates) = run_report(&state, None, "export report", move |_, hledger_path| {
+        let (cache, defaults) = (&journal.cache, &journal.defaults);
+        let file_ref = journal.file();
+        match report_kind.as_str() {
+            "balance" => {
+                let report = cache.get_balance_cached(
+                    hledger_path,
+                    file_ref,
+                    &hledger_lib::merge_options(defaults, options)?,
+                )?;
+                let dates = match &report {
+                    hledger_lib::BalanceReport::Periodic(periodic) => periodic.dates.clone(),
+                    hledger_lib::BalanceReport::Simple(_) => Vec::new(),
+                };
+                let contents = export_contents(&report, || Ok(report.to_csv_report()), format)?;
+                Ok((contents, dates))
+            }
+            "balancesheet" => {
+                let report = cache.get_balancesheet_cached(
+                    hledger_path,
+                    file_ref,
+                    &hledger_lib::merge_options(defaults, options)?,
+                )?;
+                let contents = export_contents(&report, || Ok(report.to_csv_report()), format)?;
+                Ok((contents, report.dates))
+            }
+            "incomestatement" => {
+                let report = cache.get_incomestatement_cached(
+                    hledger_path,
+                    file_ref,
+                    &hledger_lib::merge_options(defaults, options)?,
+                )?;
+                let contents = export_contents(&report, || Ok(report.to_csv_report()), format)?;
+                Ok((contents, report.dates))
+            }
+            "cashflow" => {
+                let report = cache.get_cashflow_cached(
+                    hledger_path,
+                    file_ref,
+                    &hledger_lib::merge_options(defaults, options)?,
+                )?;
+                let contents = export_contents(&report, || Ok(report.to_csv_report()), format)?;
+                Ok((contents, report.dates))
+            }
+            _ => {
+                let options: hledger_lib::PrintOptions =
+                    hledger_lib::merge_options(defaults, options)?;
+                let report = cache.get_print_cached(hledger_path, file_ref, &options)?;
+                // hledger's own CSV has a row per posting, which the typed
+                // report doesn't lay out
+                let contents = export_contents(
+                    &report,
+                    || hledger_lib::get_print_csv(hledger_path, file_ref, &options),
+                    format,
+                )?;
+                Ok((contents, Vec::new()))
+            }
+        }
+    })
+    .await?;
+
+    let (tx, rx) = mpsc::channel();
+    let extension = format.extension();
+    app.dialog()
+        .file()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(hledger_lib::export_file_name(&kind, &dates, format))
+        .set_title("Export Report")
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+
+    match rx.recv() {
+        Ok(Some(file_path)) => {
+            let path = file_path
+                .into_path()
+                .map_err(|e| format!("Failed to export report: {}", e))?;
+            std::fs::write(&path, contents)
+                .map_err(|e| format!("Failed to export report: {}", e))?;
+            Ok(Some(path.to_string_lossy().into_owned()))
+        }
+        Ok(None) => Ok(None),
+        Err(_) => Err("Failed to export report: the file dialog closed without an answer".into()),
     }
 }
 
+/// Writes aren't given a request id: stopping one halfway would leave the
+/// journal to be checked by hand
 #[tauri::command]
-fn get_incomestatement(
+async fn append_transaction(
     journal_file: String,
-    options: hledger_lib::IncomeStatementOptions,
+    transaction: hledger_lib::TransactionBuilder,
+    mut options: hledger_lib::WriteOptions,
     state: State<'_, AppState>,
-) -> Result<hledger_lib::IncomeStatementReport, String> {
-    let hledger_path = state.hledger_path.lock().unwrap();
-    let path_ref = hledger_path.as_ref().map(|s| s.as_str());
+) -> Result<hledger_lib::WriteReceipt, AppError> {
+    run_report(
+        &state,
+        None,
+        "add transaction",
+        move |state, hledger_path| {
+            if options.hledger_path.is_none() {
+                options.hledger_path = hledger_path.map(str::to_string);
+            }
+            options.before_write = state.backup_hook();
+            let path = std::path::Path::new(&journal_file);
+            let receipt = hledger_lib::append_transaction(path, &transaction, &options)?;
+            state.invalidate_reports(path);
+            Ok(receipt)
+        },
+    )
+    .await
+}
 
-    let file_ref = Some(journal_file.as_str());
-    match hledger_lib::get_incomestatement(path_ref, file_ref, &options) {
-        Ok(incomestatement) => Ok(incomestatement),
-        Err(e) => Err(format!("Failed to get incomestatement: {}", e)),
+#[tauri::command]
+async fn replace_transaction(
+    journal_file: String,
+    span: hledger_lib::SourceSpan,
+    transaction: hledger_lib::TransactionBuilder,
+    mut options: hledger_lib::WriteOptions,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    run_report(
+        &state,
+        None,
+        "edit transaction",
+        move |state, hledger_path| {
+            if options.hledger_path.is_none() {
+                options.hledger_path = hledger_path.map(str::to_string);
+            }
+            options.before_write = state.backup_hook();
+            let path = std::path::Path::new(&journal_file);
+            let replacement = hledger_lib::Replacement::Transaction(&transaction);
+            hledger_lib::replace_transaction(path, &span, replacement, &options)?;
+            state.invalidate_reports(path);
+            Ok(())
+        },
+    )
+    .await
+}
+
+/// The backups of `path` taken before the app wrote to it, newest first
+#[tauri::command]
+fn list_backups(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<backups::Backup>, AppError> {
+    let backups = state.backups.get().ok_or("Backups aren't available")?;
+    backups
+        .list(std::path::Path::new(&path))
+        .map_err(|e| format!("Failed to list backups: {}", e).into())
+}
+
+/// Put a backup back in place of its file, after backing up what the file
+/// holds now
+#[tauri::command]
+fn restore_backup(
+    backup_id: String,
+    state: State<'_, AppState>,
+) -> Result<backups::Backup, AppError> {
+    let backups = state.backups.get().ok_or("Backups aren't available")?;
+    let backup = backups
+        .restore(&backup_id)
+        .map_err(|e| format!("Failed to restore backup: {}", e))?;
+    state.invalidate_reports(&backup.file);
+    Ok(backup)
+}
+
+#[tauri::command]
+fn get_backup_limit(app: tauri::AppHandle) -> Result<usize, AppError> {
+    Ok(settings::load_backup_limit(&app)?)
+}
+
+/// Save how many backups to keep per file; 0 stops taking them
+#[tauri::command]
+fn set_backup_limit(
+    app: tauri::AppHandle,
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    settings::save_backup_limit(&app, limit)?;
+    if let Some(backups) = state.backups.get() {
+        backups.set_keep(limit);
     }
+    Ok(())
+}
+
+/// The transactions importing `files` into the journal would add
+#[tauri::command]
+async fn preview_import(
+    journal_file: String,
+    files: Vec<String>,
+    options: hledger_lib::ImportOptions,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::PrintReport, AppError> {
+    run_report(
+        &state,
+        request_id,
+        "preview import",
+        move |_, hledger_path| {
+            let files: Vec<&str> = files.iter().map(|f| f.as_str()).collect();
+            hledger_lib::preview_import(hledger_path, Some(journal_file.as_str()), &files, &options)
+        },
+    )
+    .await
 }
 
 #[tauri::command]
-fn get_print(
+async fn import_files(
     journal_file: String,
-    options: hledger_lib::PrintOptions,
+    files: Vec<String>,
+    mut options: hledger_lib::ImportOptions,
     state: State<'_, AppState>,
-) -> Result<hledger_lib::PrintReport, String> {
-    let hledger_path = state.hledger_path.lock().unwrap();
-    let path_ref = hledger_path.as_ref().map(|s| s.as_str());
+) -> Result<String, AppError> {
+    run_report(&state, None, "import", move |state, hledger_path| {
+        options.before_write = state.backup_hook();
+        let files: Vec<&str> = files.iter().map(|f| f.as_str()).collect();
+        let output =
+            hledger_lib::import_files(hledger_path, Some(journal_file.as_str()), &files, &options)?;
+        if !options.dry_run {
+            state.invalidate_reports(&journal_file);
+        }
+        Ok(output)
+    })
+    .await
+}
 
-    let file_ref = Some(journal_file.as_str());
-    match hledger_lib::get_print(path_ref, file_ref, &options) {
-        Ok(print_report) => Ok(print_report),
-        Err(e) => Err(format!("Failed to get print: {}", e)),
+/// The header and first `max_rows` rows of a CSV file, for choosing how
+/// to import it
+#[tauri::command]
+async fn preview_csv(
+    path: String,
+    delimiter: char,
+    has_header: bool,
+    max_rows: usize,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::CsvPreview, AppError> {
+    run_report(&state, None, "preview CSV", move |_, _| {
+        hledger_lib::preview_csv(std::path::Path::new(&path), delimiter, has_header, max_rows)
+    })
+    .await
+}
+
+#[tauri::command]
+fn guess_csv_mapping(
+    headers: Vec<String>,
+    sample_rows: Vec<Vec<String>>,
+) -> hledger_lib::CsvMapping {
+    hledger_lib::guess_csv_mapping(&headers, &sample_rows)
+}
+
+/// Import `csv_path` into the main file of open journal `journal_id` with
+/// `rules`, or only preview what would be imported with `dry_run`
+#[tauri::command]
+async fn run_csv_import(
+    journal_id: String,
+    csv_path: String,
+    rules: hledger_lib::CsvRules,
+    dry_run: bool,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::CsvImportOutcome, AppError> {
+    let journal_file = lock(&state.open_journals)
+        .get(&journal_id)
+        .and_then(|journal| journal.paths.first().cloned())
+        .ok_or_else(|| format!("No open journal {}", journal_id))?;
+    run_report(&state, None, "import CSV", move |state, hledger_path| {
+        let options = hledger_lib::ImportOptions {
+            dry_run,
+            before_write: state.backup_hook(),
+            ..hledger_lib::ImportOptions::default()
+        };
+        let outcome = hledger_lib::import_csv(
+            hledger_path,
+            Some(journal_file.as_str()),
+            &csv_path,
+            &rules,
+            &options,
+        )?;
+        if !dry_run {
+            state.invalidate_reports(&journal_file);
+        }
+        Ok(outcome)
+    })
+    .await
+}
+
+/// Copy the journal's transactions into the SQLite database at `db_path`
+/// for querying with other tools, returning how many rows were written
+#[tauri::command]
+async fn export_journal_to_sqlite(
+    journal_id: String,
+    db_path: String,
+    state: State<'_, AppState>,
+) -> Result<hledger_lib::ExportStats, AppError> {
+    let journal = report_journal(&state, &journal_id)?;
+    run_report(&state, None, "export to SQLite", move |_, hledger_path| {
+        let transactions = journal.cache.get_print_cached(
+            hledger_path,
+            journal.file(),
+            &hledger_lib::PrintOptions::new(),
+        )?;
+        hledger_lib::export_to_sqlite(&transactions, std::path::Path::new(&db_path))
+    })
+    .await
+}
+
+/// Drop cached reports read from `path`, or every cached report when `None`,
+/// e.g. after the journal was edited outside the app
+#[tauri::command]
+fn invalidate_report_cache(path: Option<String>, state: State<'_, AppState>) {
+    match path {
+        Some(path) => state.invalidate_reports(path),
+        None => state.clear_reports(),
+    }
+}
+
+/// Forget every cached report
+#[tauri::command]
+fn clear_report_cache(state: State<'_, AppState>) {
+    state.clear_reports();
+}
+
+#[tauri::command]
+fn file_fingerprint(file: String) -> Result<hledger_lib::FileFingerprint, AppError> {
+    match hledger_lib::file_fingerprint(std::path::Path::new(&file)) {
+        Ok(fingerprint) => Ok(fingerprint),
+        Err(e) => Err(command_error("read journal", e)),
     }
 }
 
+#[tauri::command]
+fn compute_running_balances(
+    transactions: Vec<hledger_lib::PrintTransaction>,
+    account_query: String,
+) -> Vec<hledger_lib::RunningBalanceEntry> {
+    hledger_lib::compute_running_balances(&transactions, &account_query)
+}
+
+#[tauri::command]
+fn render_queries(queries: Vec<hledger_lib::Query>) -> Vec<String> {
+    queries.iter().map(|query| query.to_arg()).collect()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let app_state = AppState {
-        hledger_path: Arc::new(Mutex::new(None)),
-    };
-
     tauri::Builder::default()
-        .manage(app_state)
+        .manage(AppState::default())
         .setup(|app| {
+            // Run the bundled hledger when no path is set; builds without
+            // one, like development builds, use PATH's
+            if let Some(sidecar) = sidecar_path() {
+                hledger_lib::set_command_config(hledger_lib::command_config().sidecar(sidecar));
+            }
+
             // Load config on startup
             let state = app.state::<AppState>();
-            // TODO: Load hledger path from config file and update state
+            // An unreadable store only costs the saved path, not the launch
+            let config_failed = |e: String| {
+                eprintln!("Warning: couldn't read {}: {}", settings::STORE_FILE, e);
+                *lock(&state.config_error) = Some(e);
+            };
+            match settings::load_hledger_path(app.handle(), |path| {
+                test_hledger_path(Some(path.into()))
+                    .map(|tested| tested.version)
+                    .map_err(|e| e.message)
+            }) {
+                Ok(hledger_path) => *lock(&state.hledger_path) = hledger_path,
+                Err(e) => config_failed(e),
+            }
+            match settings::load_journal_paths(app.handle()) {
+                Ok(paths) => *lock(&state.journal_files) = paths,
+                Err(e) => config_failed(e),
+            }
+            match settings::load_report_defaults(app.handle()) {
+                Ok(defaults) => *lock(&state.report_defaults) = defaults,
+                Err(e) => config_failed(e),
+            }
+            let backup_limit = settings::load_backup_limit(app.handle()).unwrap_or_else(|e| {
+                config_failed(e);
+                backups::DEFAULT_KEEP
+            });
+            match app.path().app_data_dir() {
+                Ok(dir) => {
+                    let manager = backups::BackupManager::new(dir.join("backups"), backup_limit);
+                    let _ = state.backups.set(manager);
+                }
+                Err(e) => eprintln!("Warning: writes won't be backed up: {}", e),
+            }
+            // After the hledger path, which finds the journals' includes
+            match settings::load_open_journals(app.handle()) {
+                Ok(saved) => {
+                    for journal in saved {
+                        let paths = journal.paths.join(", ");
+                        if let Err(e) = open_journal_paths(
+                            app.handle(),
+                            &state,
+                            journal.paths,
+                            journal.report_defaults,
+                        ) {
+                            eprintln!("Warning: couldn't reopen {}: {}", paths, e);
+                        }
+                    }
+                }
+                Err(e) => config_failed(e),
+            }
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -173,15 +2094,157 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .invoke_handler(tauri::generate_handler![
             select_journal_files,
+            validate_journal,
+            validate_dropped_file,
+            detect_journal_encoding,
+            convert_journal_to_utf8,
+            create_journal,
+            set_journal_files,
+            get_journal_files,
+            get_default_journal,
+            get_journal_metadata,
+            open_journal,
+            close_journal,
+            get_open_journals,
             set_hledger_path,
             get_hledger_path,
             test_hledger_path,
+            detect_hledger,
+            get_editor_command,
+            set_editor_command,
+            open_in_editor,
+            health_check,
             get_accounts,
+            get_accounts_detailed,
+            get_account_tree,
+            get_account_balance_tree,
+            find_account,
             get_balance,
             get_balancesheet,
+            get_networth_series,
+            get_tag_usage,
+            get_timeseries,
+            format_amounts,
+            get_budget_report,
             get_incomestatement,
-            get_print
+            compare_periods,
+            get_cashflow,
+            get_print,
+            get_print_page,
+            search_transactions,
+            get_upcoming_transactions,
+            get_reports,
+            get_dashboard,
+            get_suggestions,
+            suggest_transaction,
+            cancel_report,
+            summarize_transactions,
+            get_payee_stats,
+            find_duplicate_candidates,
+            get_report_raw,
+            export_report,
+            append_transaction,
+            replace_transaction,
+            preview_import,
+            import_files,
+            preview_csv,
+            guess_csv_mapping,
+            run_csv_import,
+            list_backups,
+            restore_backup,
+            get_backup_limit,
+            set_backup_limit,
+            export_journal_to_sqlite,
+            invalidate_report_cache,
+            clear_report_cache,
+            get_report_defaults,
+            set_report_defaults,
+            verify_assertions,
+            get_journal_problems,
+            get_prices,
+            top_spending_accounts,
+            get_flow_data,
+            file_fingerprint,
+            compute_running_balances,
+            render_queries
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_command_error() {
+        use hledger_lib::{ErrorCode, HLedgerError};
+
+        let e = command_error(
+            "get balance",
+            HLedgerError::HLedgerNotFound {
+                attempted: "/opt/hledger".to_string(),
+            },
+        );
+        assert_eq!(e.code, ErrorCode::HledgerNotFound);
+        assert!(e.message.ends_with("Check the hledger path in Settings."));
+
+        let e = command_error("get balance", HLedgerError::Cancelled);
+        assert_eq!(e.message, "HLedger command was cancelled");
+
+        let e = command_error("get balance", HLedgerError::ParseError("bad".to_string()));
+        assert_eq!(e.code, ErrorCode::ParseError);
+        assert_eq!(e.message, "Failed to get balance: Parse error: bad");
+    }
+
+    #[test]
+    fn test_reports_run_concurrently() {
+        let state = AppState::default();
+        let slow = |_: &AppState, _: Option<&str>| -> hledger_lib::Result<()> {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(())
+        };
+
+        // Started back to back, as two tabs loading at once would
+        let start = Instant::now();
+        let run = |id: &str| {
+            let (state, id) = (state.clone(), id.to_string());
+            tauri::async_runtime::spawn(async move {
+                run_report(&state, Some(id), "get report", slow).await
+            })
+        };
+        let (first, second) = (run("first"), run("second"));
+        let (first, second) =
+            tauri::async_runtime::block_on(async { (first.await.unwrap(), second.await.unwrap()) });
+        assert!(first.is_ok() && second.is_ok());
+        assert!(start.elapsed() < Duration::from_millis(900));
+        assert!(state.running_reports.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_commands_survive_a_poisoned_lock() {
+        let state = AppState::default();
+        *state.hledger_path.lock().unwrap() = Some("/opt/hledger".to_string());
+        let panicking = state.clone();
+        let _ = std::thread::spawn(move || {
+            let _path = panicking.hledger_path.lock().unwrap();
+            let _reports = panicking.running_reports.lock().unwrap();
+            panic!("a command panicked while holding the locks");
+        })
+        .join();
+        assert!(state.hledger_path.is_poisoned());
+
+        let path = |_: &AppState, path: Option<&str>| -> hledger_lib::Result<Option<String>> {
+            Ok(path.map(str::to_string))
+        };
+        let result = tauri::async_runtime::block_on(run_report(
+            &state,
+            Some("after".to_string()),
+            "get report",
+            path,
+        ));
+        assert_eq!(result.unwrap().as_deref(), Some("/opt/hledger"));
+        assert!(lock(&state.running_reports).is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+//! Settings kept between launches in the app's `settings.json` store
+
+use crate::backups::DEFAULT_KEEP;
+use hledger_lib::ReportDefaults;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+/// Store file, in the app's data directory
+pub const STORE_FILE: &str = "settings.json";
+
+const HLEDGER_PATH_KEY: &str = "hledgerPath";
+const JOURNALS_KEY: &str = "journals";
+const REPORT_DEFAULTS_KEY: &str = "reportDefaults";
+const EDITOR_COMMAND_KEY: &str = "editorCommand";
+const OPEN_JOURNALS_KEY: &str = "openJournals";
+const BACKUP_LIMIT_KEY: &str = "backupLimit";
+
+/// What the store held for the hledger path at startup
+#[derive(Debug, PartialEq)]
+pub enum StoredHledgerPath {
+    /// Nothing saved yet
+    Missing,
+    /// A path that still runs hledger
+    Valid(String),
+    /// A saved value that no longer works, e.g. hledger was uninstalled
+    Invalid { value: Value, reason: String },
+}
+
+/// Check the stored hledger path with `test`, which runs the binary and
+/// fails with a reason if it can't
+pub fn check_hledger_path(
+    stored: Option<Value>,
+    test: impl FnOnce(&str) -> Result<String, String>,
+) -> StoredHledgerPath {
+    let path = match &stored {
+        None | Some(Value::Null) => return StoredHledgerPath::Missing,
+        Some(Value::String(path)) if path.trim().is_empty() => return StoredHledgerPath::Missing,
+        Some(Value::String(path)) => path.clone(),
+        Some(value) => {
+            return StoredHledgerPath::Invalid {
+                value: value.clone(),
+                reason: "not a path".to_string(),
+            }
+        }
+    };
+    match test(&path) {
+        Ok(_) => StoredHledgerPath::Valid(path),
+        Err(reason) => StoredHledgerPath::Invalid {
+            value: Value::String(path),
+            reason,
+        },
+    }
+}
+
+/// Read the saved hledger path, dropping it from the store if it no longer
+/// works so the user is asked again
+pub fn load_hledger_path<R: Runtime>(
+    app: &AppHandle<R>,
+    test: impl FnOnce(&str) -> Result<String, String>,
+) -> Result<Option<String>, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    match check_hledger_path(store.get(HLEDGER_PATH_KEY), test) {
+        StoredHledgerPath::Missing => Ok(None),
+        StoredHledgerPath::Valid(path) => Ok(Some(path)),
+        StoredHledgerPath::Invalid { value, reason } => {
+            eprintln!(
+                "Warning: forgetting saved hledger path {}: {}",
+                value, reason
+            );
+            store.delete(HLEDGER_PATH_KEY);
+            store.save().map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+    }
+}
+
+/// Save the hledger path for the next launch
+pub fn save_hledger_path<R: Runtime>(app: &AppHandle<R>, path: &str) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(HLEDGER_PATH_KEY, path);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// A saved journal path, and whether there's still a file there
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalFile {
+    pub path: String,
+    pub exists: bool,
+}
+
+/// The journal paths in a stored `journals` value, skipping anything that
+/// isn't a path
+pub fn journal_paths(stored: Option<Value>) -> Vec<String> {
+    match stored {
+        Some(Value::Array(values)) => values
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::String(path) => Some(path),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Each path with whether it exists, keeping the ones that don't so the
+/// user can decide what to do about them
+pub fn journal_files(paths: &[String]) -> Vec<JournalFile> {
+    paths
+        .iter()
+        .map(|path| JournalFile {
+            path: path.clone(),
+            exists: Path::new(path).is_file(),
+        })
+        .collect()
+}
+
+/// Read the saved journal paths, missing files included
+pub fn load_journal_paths<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<String>, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    Ok(journal_paths(store.get(JOURNALS_KEY)))
+}
+
+/// Save the selected journal paths for the next launch
+pub fn save_journal_paths<R: Runtime>(app: &AppHandle<R>, paths: &[String]) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(JOURNALS_KEY, paths);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// A journal open when the app last ran, to open again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedJournal {
+    pub paths: Vec<String>,
+    /// Its own report defaults, if it had any
+    #[serde(default)]
+    pub report_defaults: Option<ReportDefaults>,
+}
+
+/// The journals in a stored `openJournals` value, skipping any that can't
+/// be read
+pub fn saved_journals(stored: Option<Value>) -> Vec<SavedJournal> {
+    match stored {
+        Some(Value::Array(values)) => values
+            .into_iter()
+            .filter_map(|value| match serde_json::from_value(value) {
+                Ok(journal) => Some(journal),
+                Err(e) => {
+                    eprintln!("Warning: not reopening a saved journal: {}", e);
+                    None
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Read the journals to open again
+pub fn load_open_journals<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<SavedJournal>, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    Ok(saved_journals(store.get(OPEN_JOURNALS_KEY)))
+}
+
+/// Save the open journals for the next launch
+pub fn save_open_journals<R: Runtime>(
+    app: &AppHandle<R>,
+    journals: &[SavedJournal],
+) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(journals).map_err(|e| e.to_string())?;
+    store.set(OPEN_JOURNALS_KEY, value);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// The report defaults in a stored `reportDefaults` value; none if there
+/// isn't one or it can't be read, e.g. after a downgrade
+pub fn report_defaults(stored: Option<Value>) -> ReportDefaults {
+    match stored {
+        None | Some(Value::Null) => ReportDefaults::default(),
+        Some(value) => serde_json::from_value(value).unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring saved report defaults: {}", e);
+            ReportDefaults::default()
+        }),
+    }
+}
+
+/// Read the saved report defaults
+pub fn load_report_defaults<R: Runtime>(app: &AppHandle<R>) -> Result<ReportDefaults, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    Ok(report_defaults(store.get(REPORT_DEFAULTS_KEY)))
+}
+
+/// Save the report defaults for the next launch
+pub fn save_report_defaults<R: Runtime>(
+    app: &AppHandle<R>,
+    defaults: &ReportDefaults,
+) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(defaults).map_err(|e| e.to_string())?;
+    store.set(REPORT_DEFAULTS_KEY, value);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// The editor command template saved for "open in editor", if any
+pub fn load_editor_command<R: Runtime>(app: &AppHandle<R>) -> Result<Option<String>, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    Ok(match store.get(EDITOR_COMMAND_KEY) {
+        Some(Value::String(command)) if !command.trim().is_empty() => Some(command),
+        _ => None,
+    })
+}
+
+/// Save the editor command template; `None` goes back to revealing files
+/// in the file manager
+pub fn save_editor_command<R: Runtime>(
+    app: &AppHandle<R>,
+    command: Option<&str>,
+) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    match command {
+        Some(command) => store.set(EDITOR_COMMAND_KEY, command),
+        None => {
+            store.delete(EDITOR_COMMAND_KEY);
+        }
+    }
+    store.save().map_err(|e| e.to_string())
+}
+
+/// The number of backups to keep per file in a stored `backupLimit`
+/// value; the default if there isn't one or it isn't a count
+pub fn backup_limit(stored: Option<Value>) -> usize {
+    stored
+        .as_ref()
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_KEEP, |limit| limit as usize)
+}
+
+/// Read how many backups to keep per file
+pub fn load_backup_limit<R: Runtime>(app: &AppHandle<R>) -> Result<usize, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    Ok(backup_limit(store.get(BACKUP_LIMIT_KEY)))
+}
+
+/// Save how many backups to keep per file
+pub fn save_backup_limit<R: Runtime>(app: &AppHandle<R>, limit: usize) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(BACKUP_LIMIT_KEY, limit);
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn works(_: &str) -> Result<String, String> {
+        Ok("hledger 1.32.3".to_string())
+    }
+
+    fn not_found(path: &str) -> Result<String, String> {
+        Err(format!(
+            "HLedger executable not found: no file at '{}'",
+            path
+        ))
+    }
+
+    #[test]
+    fn test_check_hledger_path_missing() {
+        assert_eq!(check_hledger_path(None, works), StoredHledgerPath::Missing);
+        assert_eq!(
+            check_hledger_path(Some(Value::Null), works),
+            StoredHledgerPath::Missing
+        );
+        assert_eq!(
+            check_hledger_path(Some(json!("  ")), works),
+            StoredHledgerPath::Missing
+        );
+    }
+
+    #[test]
+    fn test_check_hledger_path_valid() {
+        assert_eq!(
+            check_hledger_path(Some(json!("/usr/local/bin/hledger")), works),
+            StoredHledgerPath::Valid("/usr/local/bin/hledger".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_hledger_path_invalid() {
+        match check_hledger_path(Some(json!("/opt/gone/hledger")), not_found) {
+            StoredHledgerPath::Invalid { value, reason } => {
+                assert_eq!(value, json!("/opt/gone/hledger"));
+                assert!(reason.contains("/opt/gone/hledger"));
+            }
+            other => panic!("Expected Invalid, got {:?}", other),
+        }
+
+        // A value of the wrong type is never run
+        let result = check_hledger_path(Some(json!(42)), |_| panic!("shouldn't be tested"));
+        assert!(matches!(result, StoredHledgerPath::Invalid { .. }));
+    }
+
+    #[test]
+    fn test_journal_paths() {
+        assert!(journal_paths(None).is_empty());
+        assert!(journal_paths(Some(json!("main.journal"))).is_empty());
+        assert_eq!(
+            journal_paths(Some(json!(["main.journal", 3, null, "2024.journal"]))),
+            vec!["main.journal", "2024.journal"]
+        );
+    }
+
+    #[test]
+    fn test_journal_files_keeps_missing_files() {
+        let existing =
+            std::env::temp_dir().join(format!("journals-{}.journal", std::process::id()));
+        std::fs::write(&existing, "").unwrap();
+        let existing = existing.to_string_lossy().into_owned();
+
+        let files = journal_files(&[existing.clone(), "/nonexistent/moved.journal".to_string()]);
+        std::fs::remove_file(&existing).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                JournalFile {
+                    path: existing,
+                    exists: true,
+                },
+                JournalFile {
+                    path: "/nonexistent/moved.journal".to_string(),
+                    exists: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_defaults() {
+        assert_eq!(report_defaults(None), ReportDefaults::default());
+        assert_eq!(
+            report_defaults(Some(json!("monthly"))),
+            ReportDefaults::default()
+        );
+
+        let defaults = report_defaults(Some(json!({
+            "interval": { "type": "Monthly" },
+            "tree": true,
+            "depth": 2,
+            "statuses": ["Cleared"],
+        })));
+        assert_eq!(defaults.list_mode, Some(hledger_lib::ListMode::Tree));
+        assert_eq!(defaults.depth, Some(2));
+        assert_eq!(defaults.value_mode, None);
+    }
+
+    #[test]
+    fn test_saved_journals() {
+        assert!(saved_journals(None).is_empty());
+        assert!(saved_journals(Some(json!({ "paths": [] }))).is_empty());
+
+        let journals = saved_journals(Some(json!([
+            { "paths": ["personal.journal"] },
+            { "paths": "business.journal" },
+            { "paths": ["a.journal", "b.journal"], "reportDefaults": { "depth": 2 } },
+        ])));
+        assert_eq!(journals.len(), 2);
+        assert_eq!(journals[0].paths, ["personal.journal"]);
+        assert_eq!(journals[0].report_defaults, None);
+        assert_eq!(journals[1].paths, ["a.journal", "b.journal"]);
+        assert_eq!(journals[1].report_defaults.as_ref().unwrap().depth, Some(2));
+    }
+
+    #[test]
+    fn test_backup_limit() {
+        assert_eq!(backup_limit(None), DEFAULT_KEEP);
+        assert_eq!(backup_limit(Some(json!(3))), 3);
+        assert_eq!(backup_limit(Some(json!(0))), 0);
+        assert_eq!(backup_limit(Some(json!(-1))), DEFAULT_KEEP);
+        assert_eq!(backup_limit(Some(json!("5"))), DEFAULT_KEEP);
+    }
+}
@@ -0,0 +1,358 @@
+//! Watching the selected journals, and the files they include, for edits
+//! made outside the app
+//!
+//! Each file's directory is watched rather than the file itself: editors
+//! that save by writing a new file and renaming it over the old one would
+//! otherwise leave the watch on the replaced file, and every later save
+//! would go unnoticed. A file read by several selected journals is watched
+//! once, and its changes name every journal that reads it.
+
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Event emitted to the frontend for each change
+pub const CHANGE_EVENT: &str = "journal-changed";
+
+/// How long a file must be quiet before its changes are reported, so a
+/// save that touches it several times is one change
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// What happened to a watched file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChangeKind {
+    /// New contents, including a new file written or renamed into place
+    Modify,
+    Remove,
+    /// Moved away; the path no longer has a file unless one is put back
+    Rename,
+}
+
+/// Payload of [`CHANGE_EVENT`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JournalChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// The selected journals that read the file, as they were selected
+    pub journals: Vec<String>,
+}
+
+/// A selected journal and the files it reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalFiles {
+    pub journal: String,
+    /// The journal itself and every file it includes
+    pub files: Vec<PathBuf>,
+}
+
+impl JournalFiles {
+    /// The files as [`watch_path`] names them, without repeats
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        self.files
+            .iter()
+            .filter_map(|file| watch_path(file))
+            .filter(|file| seen.insert(file.clone()))
+            .collect()
+    }
+}
+
+/// A running watch; dropping it stops watching
+pub struct JournalWatcher {
+    _debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+    /// Each journal with its files as watched
+    journals: Vec<JournalFiles>,
+}
+
+impl JournalWatcher {
+    /// Watch the files of `journals`, calling `on_change` from the
+    /// watcher's thread for each change to one of them
+    pub fn new(
+        journals: &[JournalFiles],
+        on_change: impl Fn(JournalChange) + Send + 'static,
+    ) -> Result<Self, String> {
+        let journals: Vec<JournalFiles> = journals
+            .iter()
+            .map(|journal| JournalFiles {
+                journal: journal.journal.clone(),
+                files: journal.watch_paths(),
+            })
+            .collect();
+        let readers = readers(&journals);
+        let files: Vec<PathBuf> = readers.keys().cloned().collect();
+
+        let handle_events = move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                for event in events {
+                    changes(&event.kind, &event.paths, &readers)
+                        .into_iter()
+                        .for_each(&on_change);
+                }
+            }
+            Err(errors) => {
+                for e in errors {
+                    eprintln!("Warning: journal watcher error: {}", e);
+                }
+            }
+        };
+        let mut debouncer = new_debouncer(DEBOUNCE, None, handle_events)
+            .map_err(|e| format!("Failed to start watching journal files: {}", e))?;
+
+        let dirs: HashSet<&Path> = files.iter().filter_map(|f| f.parent()).collect();
+        for dir in dirs {
+            debouncer
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+        }
+
+        Ok(Self {
+            _debouncer: debouncer,
+            journals,
+        })
+    }
+
+    /// The selected journals being watched
+    pub fn journals(&self) -> Vec<String> {
+        self.journals.iter().map(|j| j.journal.clone()).collect()
+    }
+
+    /// Whether `journal` now reads different files than when the watch
+    /// started, e.g. after an `include` was added or removed
+    pub fn includes_changed(&self, journal: &JournalFiles) -> bool {
+        let watched = self.journals.iter().find(|j| j.journal == journal.journal);
+        watched.map(|j| &j.files) != Some(&journal.watch_paths())
+    }
+}
+
+/// The journals reading each file
+fn readers(journals: &[JournalFiles]) -> HashMap<PathBuf, Vec<String>> {
+    let mut readers: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for journal in journals {
+        for file in &journal.files {
+            let names = readers.entry(file.clone()).or_default();
+            if !names.contains(&journal.journal) {
+                names.push(journal.journal.clone());
+            }
+        }
+    }
+    readers
+}
+
+/// `file` with its directory canonicalized, which is how events name it
+///
+/// The file itself may be a symlink or missing for a moment mid-save, so
+/// only the directory is resolved. `None` if the directory doesn't exist.
+pub fn watch_path(file: &Path) -> Option<PathBuf> {
+    let name = file.file_name()?;
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    Some(dir.canonicalize().ok()?.join(name))
+}
+
+/// The changes to watched files an event stands for, given the journals
+/// reading each watched file
+pub fn changes(
+    kind: &EventKind,
+    paths: &[PathBuf],
+    readers: &HashMap<PathBuf, Vec<String>>,
+) -> Vec<JournalChange> {
+    let change = |path: &PathBuf, kind: ChangeKind| {
+        readers.get(path).map(|journals| JournalChange {
+            path: path.to_string_lossy().into_owned(),
+            kind,
+            journals: journals.clone(),
+        })
+    };
+    match kind {
+        // A rename names both ends: the old file went away, and whatever was
+        // renamed onto a watched path (an editor's temporary file) is its
+        // new contents
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => [
+            change(&paths[0], ChangeKind::Rename),
+            change(&paths[1], ChangeKind::Modify),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths
+            .iter()
+            .filter_map(|p| change(p, ChangeKind::Modify))
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(_)) => paths
+            .iter()
+            .filter_map(|p| change(p, ChangeKind::Rename))
+            .collect(),
+        EventKind::Modify(_) | EventKind::Create(_) => paths
+            .iter()
+            .filter_map(|p| change(p, ChangeKind::Modify))
+            .collect(),
+        EventKind::Remove(_) => paths
+            .iter()
+            .filter_map(|p| change(p, ChangeKind::Remove))
+            .collect(),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify_debouncer_full::notify::event::{CreateKind, DataChange, RemoveKind};
+    use std::fs;
+    use std::sync::mpsc;
+
+    /// Each path read by the journal `main.journal`
+    fn watched(paths: &[&str]) -> HashMap<PathBuf, Vec<String>> {
+        paths
+            .iter()
+            .map(|path| (PathBuf::from(path), vec!["main.journal".to_string()]))
+            .collect()
+    }
+
+    fn change(path: &str, kind: ChangeKind) -> JournalChange {
+        JournalChange {
+            path: path.to_string(),
+            kind,
+            journals: vec!["main.journal".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_changes_to_watched_files_only() {
+        let watched = watched(&["/books/main.journal"]);
+        let modify = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        assert_eq!(
+            changes(&modify, &[PathBuf::from("/books/main.journal")], &watched),
+            vec![change("/books/main.journal", ChangeKind::Modify)]
+        );
+        assert!(changes(&modify, &[PathBuf::from("/books/notes.txt")], &watched).is_empty());
+        assert_eq!(
+            changes(
+                &EventKind::Remove(RemoveKind::File),
+                &[PathBuf::from("/books/main.journal")],
+                &watched
+            ),
+            vec![change("/books/main.journal", ChangeKind::Remove)]
+        );
+    }
+
+    #[test]
+    fn test_changes_for_renames() {
+        let watched = watched(&["/books/main.journal"]);
+        let both = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+
+        // Saved by renaming a temporary file over the journal
+        let saved = [
+            PathBuf::from("/books/.main.journal.swp"),
+            PathBuf::from("/books/main.journal"),
+        ];
+        assert_eq!(
+            changes(&both, &saved, &watched),
+            vec![change("/books/main.journal", ChangeKind::Modify)]
+        );
+
+        // Moved away
+        let moved = [
+            PathBuf::from("/books/main.journal"),
+            PathBuf::from("/archive/main.journal"),
+        ];
+        assert_eq!(
+            changes(&both, &moved, &watched),
+            vec![change("/books/main.journal", ChangeKind::Rename)]
+        );
+
+        let from = EventKind::Modify(ModifyKind::Name(RenameMode::From));
+        assert_eq!(
+            changes(&from, &[PathBuf::from("/books/main.journal")], &watched),
+            vec![change("/books/main.journal", ChangeKind::Rename)]
+        );
+        assert_eq!(
+            changes(
+                &EventKind::Create(CreateKind::File),
+                &[PathBuf::from("/books/main.journal")],
+                &watched
+            ),
+            vec![change("/books/main.journal", ChangeKind::Modify)]
+        );
+    }
+
+    #[test]
+    fn test_watcher_sees_replaced_file() {
+        let dir = std::env::temp_dir().join(format!("journal-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let journal = dir.join("main.journal");
+        fs::write(&journal, "2024-01-01 opening\n").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let selected = JournalFiles {
+            journal: "main.journal".to_string(),
+            files: vec![journal.clone()],
+        };
+        let watcher = JournalWatcher::new(&[selected], move |change| {
+            let _ = tx.send(change);
+        })
+        .unwrap();
+        let watched = watch_path(&journal).unwrap().to_string_lossy().into_owned();
+
+        // Replace the journal the way many editors save
+        let temp = dir.join(".main.journal.tmp");
+        fs::write(&temp, "2024-01-02 edited\n").unwrap();
+        fs::rename(&temp, &journal).unwrap();
+        let first = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(first.path, watched);
+
+        // The next save is still seen
+        while rx.recv_timeout(Duration::from_millis(500)).is_ok() {}
+        fs::write(&journal, "2024-01-03 edited again\n").unwrap();
+        let second = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(second, change(&watched, ChangeKind::Modify));
+
+        drop(watcher);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_shared_files_name_every_reader() {
+        let dir = std::env::temp_dir().join(format!("journal-readers-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (main, other, shared) = (
+            dir.join("main.journal"),
+            dir.join("other.journal"),
+            dir.join("2024.journal"),
+        );
+        let journals = [
+            JournalFiles {
+                journal: "main.journal".to_string(),
+                files: vec![main.clone(), shared.clone(), shared.clone()],
+            },
+            JournalFiles {
+                journal: "other.journal".to_string(),
+                files: vec![other.clone(), shared.clone()],
+            },
+        ];
+        let watched: Vec<JournalFiles> = journals
+            .iter()
+            .map(|j| JournalFiles {
+                journal: j.journal.clone(),
+                files: j.watch_paths(),
+            })
+            .collect();
+        assert_eq!(watched[0].files.len(), 2);
+
+        let readers = readers(&watched);
+        assert_eq!(readers.len(), 3);
+        let shared = watch_path(&shared).unwrap();
+        let modify = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        let seen = changes(&modify, &[shared], &readers);
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].journals, ["main.journal", "other.journal"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
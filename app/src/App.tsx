@@ -1,4 +1,6 @@
 import type { DateValue } from "@internationalized/date";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useEffect, useState } from "react";
 
 import { AccountsTab } from "@/components/AccountsTab";
@@ -11,6 +13,7 @@ import { IncomeStatementTab } from "@/components/IncomeStatementTab";
 import { PrintTab } from "@/components/PrintTab";
 import { Tab, TabList, TabPanel, Tabs } from "@/components/ui/tabs";
 import VerificationTab from "@/components/VerificationTab";
+import type { HealthReport, JournalChange, TestedHledger } from "@/types/hledger.types";
 import { loadConfig, saveLastSelectedFile } from "@/utils/configStore";
 
 function App() {
@@ -23,6 +26,11 @@ function App() {
   const [currencyMode, setCurrencyMode] = useState("original");
   const [configDialogOpen, setConfigDialogOpen] = useState(false);
   const [journalFiles, setJournalFiles] = useState<string[]>([]);
+  // Id of the selected journal once open, which the report commands take
+  const [journalId, setJournalId] = useState("");
+  // Bumped when the journal changes on disk, to remount the tabs and reload
+  const [journalRevision, setJournalRevision] = useState(0);
+  const [health, setHealth] = useState<HealthReport | null>(null);
 
   // Load journal files from store on mount
   useEffect(() => {
@@ -31,8 +39,15 @@ function App() {
         const store = await loadConfig();
         setJournalFiles(store.journalFiles);
 
-        // If no hledger path or journal files are configured, automatically open the dialog
-        if (store.hledgerPath == null || store.journalFiles.length === 0) {
+        // If no hledger path or journal files are configured, automatically open the dialog;
+        // the hledger bundled with the app stands in for a path
+        const bundled =
+          store.hledgerPath == null &&
+          (await invoke<TestedHledger>("test_hledger_path", {}).then(
+            (tested) => tested.source === "Sidecar",
+            () => false,
+          ));
+        if ((store.hledgerPath == null && !bundled) || store.journalFiles.length === 0) {
           setConfigDialogOpen(true);
         }
         // If we have a last selected file, use it
@@ -53,6 +68,41 @@ function App() {
     loadJournalFilesFromStore();
   }, []);
 
+  // Check hledger and the settings, and the journal once it is open
+  useEffect(() => {
+    invoke<HealthReport>("health_check", { journalId: journalId || null }).then(setHealth).catch(console.error);
+  }, [journalId]);
+
+  // Open the selected journal, which watches it and its includes for edits
+  // made elsewhere
+  useEffect(() => {
+    if (!selectedJournalFile) {
+      return;
+    }
+    invoke<string>("open_journal", { paths: [selectedJournalFile] })
+      .then(setJournalId)
+      .catch(console.error);
+  }, [selectedJournalFile]);
+
+  // Reload on changes to the open journal, and close it once another is
+  // selected
+  useEffect(() => {
+    if (!journalId) {
+      return;
+    }
+    const unlisten = listen<JournalChange>("journal-changed", (event) => {
+      if (event.payload.journalId !== journalId) {
+        return;
+      }
+      console.log(`Journal ${event.payload.kind}: ${event.payload.path}`);
+      setJournalRevision((revision) => revision + 1);
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+      invoke("close_journal", { journalId }).catch(console.error);
+    };
+  }, [journalId]);
+
   // Save selected file to store when it changes
   useEffect(() => {
     if (selectedJournalFile) {
@@ -88,6 +138,21 @@ function App() {
       {/* Main Content */}
       <div className="ml-80 p-8 pt-6">
         <div className="max-w-5xl mx-auto w-full">
+          {health && !health.ok && (
+            <div className="mb-4 rounded-md border border-destructive/40 p-3 text-sm">
+              {health.items
+                .filter((item) => item.status !== "Ok")
+                .map((item, i) => (
+                  <p
+                    key={i}
+                    className={item.status === "Error" ? "text-destructive" : "text-muted-foreground"}
+                  >
+                    {item.file && `${item.file}: `}
+                    {item.message}
+                  </p>
+                ))}
+            </div>
+          )}
           <Tabs>
             <TabList aria-label="hledger data views" className="w-fit">
               <Tab id="dashboard">Dashboard</Tab>
@@ -110,46 +175,49 @@ function App() {
             </TabList>
 
             <TabPanel id="dashboard">
-              <DashboardTab searchQuery={searchQuery} dateRange={dateRange} selectedJournalFile={selectedJournalFile} />
+              <DashboardTab key={journalRevision} searchQuery={searchQuery} dateRange={dateRange} journalId={journalId} />
             </TabPanel>
 
             <TabPanel id="verification">
-              <VerificationTab selectedJournalFile={selectedJournalFile} />
+              <VerificationTab key={journalRevision} journalId={journalId} />
             </TabPanel>
 
             <TabPanel id="accounts">
-              <AccountsTab searchQuery={searchQuery} dateRange={dateRange} selectedJournalFile={selectedJournalFile} />
+              <AccountsTab key={journalRevision} searchQuery={searchQuery} dateRange={dateRange} journalId={journalId} />
             </TabPanel>
 
             <TabPanel id="balances">
               <BalancesTab
+                key={journalRevision}
                 searchQuery={searchQuery}
                 dateRange={dateRange}
-                selectedJournalFile={selectedJournalFile}
+                journalId={journalId}
                 currencyMode={currencyMode}
               />
             </TabPanel>
 
             <TabPanel id="balancesheet">
               <BalanceSheetTab
+                key={journalRevision}
                 searchQuery={searchQuery}
                 dateRange={dateRange}
-                selectedJournalFile={selectedJournalFile}
+                journalId={journalId}
                 currencyMode={currencyMode}
               />
             </TabPanel>
 
             <TabPanel id="incomestatement">
               <IncomeStatementTab
+                key={journalRevision}
                 searchQuery={searchQuery}
                 dateRange={dateRange}
-                selectedJournalFile={selectedJournalFile}
+                journalId={journalId}
                 currencyMode={currencyMode}
               />
             </TabPanel>
 
             <TabPanel id="print">
-              <PrintTab searchQuery={searchQuery} dateRange={dateRange} selectedJournalFile={selectedJournalFile} />
+              <PrintTab key={journalRevision} searchQuery={searchQuery} dateRange={dateRange} journalId={journalId} />
             </TabPanel>
           </Tabs>
         </div>
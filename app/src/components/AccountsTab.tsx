@@ -7,10 +7,10 @@ import { useCallback, useEffect, useState } from "react";
 interface AccountsTabProps {
   searchQuery: string;
   dateRange: { start: DateValue; end: DateValue } | null;
-  selectedJournalFile: string;
+  journalId: string;
 }
 
-export function AccountsTab({ searchQuery, dateRange, selectedJournalFile }: AccountsTabProps) {
+export function AccountsTab({ searchQuery, dateRange, journalId }: AccountsTabProps) {
   const [accounts, setAccounts] = useState<string[]>([]);
 
   const fetchAccounts = useCallback(
@@ -30,7 +30,7 @@ export function AccountsTab({ searchQuery, dateRange, selectedJournalFile }: Acc
 
       try {
         const accountsList = await invoke<string[]>("get_accounts", {
-          journalFile: selectedJournalFile,
+          journalId,
           options,
         });
         setAccounts(accountsList);
@@ -39,10 +39,10 @@ export function AccountsTab({ searchQuery, dateRange, selectedJournalFile }: Acc
         setAccounts([]);
       }
     },
-    [selectedJournalFile],
+    [journalId],
   );
 
-  // Fetch accounts when searchQuery, dateRange, or selectedJournalFile changes
+  // Fetch accounts when searchQuery, dateRange, or journalId changes
   useEffect(() => {
     fetchAccounts(searchQuery, dateRange);
   }, [searchQuery, dateRange, fetchAccounts]);
@@ -6,6 +6,7 @@ import {
   type BalanceSheetReport,
   type BalanceSheetSubreport,
   createDefaultBalanceSheetOptions,
+  intervalFromPeriodMode,
 } from "@/types/hledger.types";
 import type { DateValue } from "@internationalized/date";
 import { invoke } from "@tauri-apps/api/core";
@@ -16,11 +17,11 @@ import { useCallback, useEffect, useMemo, useState } from "react";
 interface BalanceSheetTabProps {
   searchQuery: string;
   dateRange: { start: DateValue; end: DateValue } | null;
-  selectedJournalFile: string;
+  journalId: string;
   currencyMode: string;
 }
 
-export function BalanceSheetTab({ searchQuery, dateRange, selectedJournalFile, currencyMode }: BalanceSheetTabProps) {
+export function BalanceSheetTab({ searchQuery, dateRange, journalId, currencyMode }: BalanceSheetTabProps) {
   const [balanceSheetData, setBalanceSheetData] = useState<BalanceSheetReport | null>(null);
   const [balanceDisplayMode, setBalanceDisplayMode] = useState<string>("flat");
   const [periodMode, setPeriodMode] = useState<string>("none");
@@ -140,33 +141,10 @@ export function BalanceSheetTab({ searchQuery, dateRange, selectedJournalFile, c
       }
 
       // Set period mode
-      switch (periodMode) {
-        case "daily":
-          options.daily = true;
-          break;
-        case "weekly":
-          options.weekly = true;
-          break;
-        case "monthly":
-          options.monthly = true;
-          break;
-        case "quarterly":
-          options.quarterly = true;
-          break;
-        case "yearly":
-          options.yearly = true;
-          break;
-        // "none" or default - no period flags set
-      }
+      options.interval = intervalFromPeriodMode(periodMode);
 
       // Set tree/flat display mode
-      if (balanceDisplayMode === "tree") {
-        options.tree = true;
-        options.flat = false;
-      } else {
-        options.flat = true;
-        options.tree = false;
-      }
+      options.listMode = balanceDisplayMode === "tree" ? "Tree" : "Flat";
 
       // Set depth option
       if (depthMode !== "none") {
@@ -180,7 +158,7 @@ export function BalanceSheetTab({ searchQuery, dateRange, selectedJournalFile, c
 
       try {
         const balanceSheetReport = await invoke<BalanceSheetReport>("get_balancesheet", {
-          journalFile: selectedJournalFile,
+          journalId,
           options,
         });
 
@@ -190,7 +168,7 @@ export function BalanceSheetTab({ searchQuery, dateRange, selectedJournalFile, c
         setBalanceSheetData(null);
       }
     },
-    [balanceDisplayMode, periodMode, depthMode, currencyMode, selectedJournalFile],
+    [balanceDisplayMode, periodMode, depthMode, currencyMode, journalId],
   );
 
   // Handle balance display mode selection
@@ -300,10 +278,10 @@ export function BalanceSheetTab({ searchQuery, dateRange, selectedJournalFile, c
                         )}
                         {hasChildAccounts ? (
                           <button onClick={() => toggleAccount(row.account)} className="hover:underline text-left">
-                            {row.display_name || row.account}
+                            {row.displayName || row.account}
                           </button>
                         ) : (
-                          row.display_name || row.account
+                          row.displayName || row.account
                         )}
                       </div>
 
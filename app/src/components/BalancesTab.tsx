@@ -8,6 +8,7 @@ import {
   type PeriodicBalance,
   type SimpleBalance,
   createDefaultBalanceOptions,
+  intervalFromPeriodMode,
 } from "@/types/hledger.types";
 import type { DateValue } from "@internationalized/date";
 import { invoke } from "@tauri-apps/api/core";
@@ -18,11 +19,11 @@ import { useCallback, useEffect, useMemo, useState } from "react";
 interface BalancesTabProps {
   searchQuery: string;
   dateRange: { start: DateValue; end: DateValue } | null;
-  selectedJournalFile: string;
+  journalId: string;
   currencyMode: string;
 }
 
-export function BalancesTab({ searchQuery, dateRange, selectedJournalFile, currencyMode }: BalancesTabProps) {
+export function BalancesTab({ searchQuery, dateRange, journalId, currencyMode }: BalancesTabProps) {
   const [balances, setBalances] = useState<BalanceAccount[]>([]);
   const [periodicData, setPeriodicData] = useState<PeriodicBalance | null>(null);
   const [balanceDisplayMode, setBalanceDisplayMode] = useState<string>("flat");
@@ -171,33 +172,10 @@ export function BalancesTab({ searchQuery, dateRange, selectedJournalFile, curre
       }
 
       // Set period mode
-      switch (periodMode) {
-        case "daily":
-          options.daily = true;
-          break;
-        case "weekly":
-          options.weekly = true;
-          break;
-        case "monthly":
-          options.monthly = true;
-          break;
-        case "quarterly":
-          options.quarterly = true;
-          break;
-        case "yearly":
-          options.yearly = true;
-          break;
-        // "none" or default - no period flags set
-      }
+      options.interval = intervalFromPeriodMode(periodMode);
 
       // Set tree/flat display mode
-      if (balanceDisplayMode === "tree") {
-        options.tree = true;
-        options.flat = false;
-      } else {
-        options.flat = true;
-        options.tree = false;
-      }
+      options.listMode = balanceDisplayMode === "tree" ? "Tree" : "Flat";
 
       // Set depth option
       if (depthMode !== "none") {
@@ -211,7 +189,7 @@ export function BalancesTab({ searchQuery, dateRange, selectedJournalFile, curre
 
       try {
         const balanceReport = await invoke<BalanceReport>("get_balance", {
-          journalFile: selectedJournalFile,
+          journalId,
           options,
         });
 
@@ -220,16 +198,15 @@ export function BalancesTab({ searchQuery, dateRange, selectedJournalFile, curre
         setPeriodicData(null);
 
         // Extract accounts from the balance report
-        // Check if it's a SimpleBalance (has accounts property) or PeriodicBalance (has dates/rows properties)
-        if ("accounts" in balanceReport) {
-          const simpleBalance = balanceReport as SimpleBalance;
+        if (balanceReport.type === "Simple") {
+          const simpleBalance: SimpleBalance = balanceReport;
           // Filter out accounts that have only zero amounts
           const accountsWithBalances = simpleBalance.accounts.filter((account) =>
             account.amounts.some((amount) => Number.parseFloat(amount.quantity) !== 0),
           );
           setBalances(accountsWithBalances);
-        } else if ("dates" in balanceReport && "rows" in balanceReport) {
-          const periodicBalance = balanceReport as PeriodicBalance;
+        } else {
+          const periodicBalance: PeriodicBalance = balanceReport;
           // Filter out rows that have only zero amounts across all periods
           const filteredRows = periodicBalance.rows.filter((row) =>
             row.amounts.some((periodAmounts) =>
@@ -247,7 +224,7 @@ export function BalancesTab({ searchQuery, dateRange, selectedJournalFile, curre
         setPeriodicData(null);
       }
     },
-    [balanceDisplayMode, periodMode, depthMode, currencyMode, selectedJournalFile],
+    [balanceDisplayMode, periodMode, depthMode, currencyMode, journalId],
   );
 
   // Handle balance display mode selection
@@ -446,10 +423,10 @@ export function BalancesTab({ searchQuery, dateRange, selectedJournalFile, curre
                                     onClick={() => toggleAccount(row.account)}
                                     className="hover:underline text-left"
                                   >
-                                    {row.display_name || row.account}
+                                    {row.displayName || row.account}
                                   </button>
                                 ) : (
-                                  row.display_name || row.account
+                                  row.displayName || row.account
                                 )}
                               </div>
 
@@ -524,10 +501,10 @@ export function BalancesTab({ searchQuery, dateRange, selectedJournalFile, curre
                                   onClick={() => toggleAccount(balance.name)}
                                   className="hover:underline text-left"
                                 >
-                                  {balance.display_name || balance.name}
+                                  {balance.displayName || balance.name}
                                 </button>
                               ) : (
-                                balance.display_name || balance.name
+                                balance.displayName || balance.name
                               )}
                             </span>
                             <div className="flex flex-col items-end">
@@ -1,6 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
 import { CheckCircle, Loader2, Plus, Trash2, XCircle } from "lucide-react";
-import { useEffect, useState } from "react";
+import { useEffect, useRef, useState } from "react";
 
 import { Button } from "@/components/ui/button";
 import {
@@ -14,6 +15,15 @@ import {
 } from "@/components/ui/dialog";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
+import {
+  type CheckIssue,
+  type DefaultJournal,
+  type DetectedHledger,
+  type DroppedFile,
+  type JournalMetadata,
+  type TestedHledger,
+  errorMessage,
+} from "@/types/hledger.types";
 import { loadConfig, removeJournalFile, saveHledgerPath, saveJournalFiles } from "@/utils/configStore";
 
 interface ConfigDialogProps {
@@ -37,6 +47,25 @@ export function ConfigDialog({
   const [isTestingPath, setIsTestingPath] = useState(false);
   const [hledgerVersion, setHledgerVersion] = useState<string | null>(null);
   const [pathError, setPathError] = useState<string | null>(null);
+  // Problems found checking newly added journal files, by path
+  const [journalIssues, setJournalIssues] = useState<Record<string, CheckIssue[]>>({});
+  // Size, span and counts of each configured file, by path
+  const [metadata, setMetadata] = useState<Record<string, JournalMetadata>>({});
+  // hledger's own default journal, offered while no files are configured
+  const [defaultJournal, setDefaultJournal] = useState<DefaultJournal | null>(null);
+  // Files last dropped on the window that weren't added, with why
+  const [rejectedDrops, setRejectedDrops] = useState<Record<string, DroppedFile>>({});
+
+  useEffect(() => {
+    if (open && journalFiles.length === 0) {
+      invoke<DefaultJournal | null>("get_default_journal")
+        .then(setDefaultJournal)
+        .catch((error) => {
+          console.error("Failed to find the default journal:", error);
+          setDefaultJournal(null);
+        });
+    }
+  }, [open, journalFiles.length]);
 
   // Load hledger path from store when dialog opens
   useEffect(() => {
@@ -44,7 +73,29 @@ export function ConfigDialog({
       async function loadHledgerPathFromStore() {
         try {
           const store = await loadConfig();
-          setHledgerPath(store.hledgerPath || "");
+          if (store.hledgerPath) {
+            setHledgerPath(store.hledgerPath);
+            return;
+          }
+
+          // First run: the hledger bundled with the app needs no setting
+          setHledgerPath("");
+          const current = await invoke<TestedHledger>("test_hledger_path", {}).catch(() => null);
+          if (current?.source === "Sidecar") {
+            setHledgerVersion(`${current.version} (bundled with the app)`);
+            setPathError(null);
+            return;
+          }
+
+          // Otherwise prefill with an installed hledger if one can be found
+          const detected = await invoke<DetectedHledger | null>("detect_hledger");
+          if (detected) {
+            setHledgerPath(detected.path);
+            setHledgerVersion(detected.version);
+            setPathError(null);
+            await saveHledgerPath(detected.path);
+            await invoke("set_hledger_path", { path: detected.path });
+          }
         } catch (error) {
           console.error("Failed to load hledger path from store:", error);
           setHledgerPath("");
@@ -54,6 +105,16 @@ export function ConfigDialog({
     }
   }, [open]);
 
+  useEffect(() => {
+    if (open) {
+      for (const file of journalFiles) {
+        invoke<JournalMetadata>("get_journal_metadata", { path: file })
+          .then((fileMetadata) => setMetadata((current) => ({ ...current, [file]: fileMetadata })))
+          .catch((error) => console.error("Failed to read journal metadata:", error));
+      }
+    }
+  }, [open, journalFiles]);
+
   // Test hledger path functionality
   const testHledgerPath = async (path: string) => {
     if (!path.trim()) {
@@ -67,14 +128,14 @@ export function ConfigDialog({
     setHledgerVersion(null);
 
     try {
-      const version = await invoke<string>("test_hledger_path", { path });
-      setHledgerVersion(version);
+      const tested = await invoke<TestedHledger>("test_hledger_path", { path });
+      setHledgerVersion(tested.version);
       setPathError(null);
 
       // Sync to Tauri backend state
       await invoke("set_hledger_path", { path });
     } catch (error) {
-      setPathError(error as string);
+      setPathError(errorMessage(error));
       setHledgerVersion(null);
     } finally {
       setIsTestingPath(false);
@@ -105,13 +166,35 @@ export function ConfigDialog({
     try {
       const files = await invoke<string[]>("select_journal_files");
       console.log("Selected files:", files);
+      await addFiles(files);
+    } catch (error) {
+      console.error("Failed to select files:", error);
+    }
+  };
 
+  const addFiles = async (files: string[]) => {
+    try {
       if (files && files.length > 0) {
         // Merge new files with existing ones (avoid duplicates)
         const existingFiles = new Set(journalFiles);
         const newFiles = files.filter((file) => !existingFiles.has(file));
         const updatedFiles = [...journalFiles, ...newFiles];
 
+        // Check the new files parse before any report runs on them
+        const checked = await Promise.all(
+          newFiles.map(async (file): Promise<[string, CheckIssue[]]> => {
+            try {
+              return [file, await invoke<CheckIssue[]>("validate_journal", { journalFile: file, checks: [] })];
+            } catch (error) {
+              return [
+                file,
+                [{ kind: "Unreadable", severity: "Error", file, line: null, column: null, message: errorMessage(error) }],
+              ];
+            }
+          }),
+        );
+        setJournalIssues((issues) => ({ ...issues, ...Object.fromEntries(checked) }));
+
         // Save the updated files to the store
         await saveJournalFiles(updatedFiles);
 
@@ -124,10 +207,37 @@ export function ConfigDialog({
         }
       }
     } catch (error) {
-      console.error("Failed to select files:", error);
+      console.error("Failed to add files:", error);
     }
   };
 
+  // Add the files dropped on the window that look like journals, and show
+  // why the others weren't
+  const addFilesRef = useRef(addFiles);
+  addFilesRef.current = addFiles;
+  useEffect(() => {
+    const unlisten = getCurrentWebview().onDragDropEvent(async (event) => {
+      if (event.payload.type !== "drop") {
+        return;
+      }
+      const checked = await Promise.all(
+        event.payload.paths.map(async (path): Promise<[string, DroppedFile]> => {
+          try {
+            return [path, await invoke<DroppedFile>("validate_dropped_file", { path })];
+          } catch (error) {
+            return [path, { accepted: false, reason: errorMessage(error), transactionCount: null, issue: null, encoding: null }];
+          }
+        }),
+      );
+      setRejectedDrops(Object.fromEntries(checked.filter(([, dropped]) => !dropped.accepted)));
+      onOpenChange(true);
+      await addFilesRef.current(checked.filter(([, dropped]) => dropped.accepted).map(([path]) => path));
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [onOpenChange]);
+
   return (
     <Dialog open={open} onOpenChange={onOpenChange}>
       <DialogContent className="sm:max-w-[600px]">
@@ -205,6 +315,22 @@ export function ConfigDialog({
                         <p className="text-xs text-muted-foreground truncate" title={file}>
                           {file}
                         </p>
+                        {metadata[file] && (
+                          <p className="text-xs text-muted-foreground truncate" title={metadata[file].note ?? undefined}>
+                            {describeMetadata(metadata[file])}
+                          </p>
+                        )}
+                        {journalIssues[file]?.map((issue, i) => (
+                          <p
+                            key={i}
+                            className={`text-xs whitespace-pre-wrap ${
+                              issue.severity === "Error" ? "text-destructive" : "text-muted-foreground"
+                            }`}
+                          >
+                            {issue.line != null && `${issue.file ?? file}:${issue.line}: `}
+                            {issue.message}
+                          </p>
+                        ))}
                       </div>
                       <Button
                         size="sm"
@@ -239,11 +365,26 @@ export function ConfigDialog({
                 </div>
               </div>
             ) : (
-              <div className="text-center py-4 text-muted-foreground">
+              <div className="text-center py-4 text-muted-foreground space-y-2">
                 <p className="text-sm">No files configured</p>
+                {defaultJournal?.readable && (
+                  <Button variant="outline" size="sm" onClick={() => addFiles([defaultJournal.path])}>
+                    Use {defaultJournal.path}
+                    {defaultJournal.source === "LedgerFile" ? " (LEDGER_FILE)" : ""}
+                  </Button>
+                )}
               </div>
             )}
 
+            {Object.entries(rejectedDrops).map(([path, dropped]) => (
+              <p key={path} className="text-xs text-destructive whitespace-pre-wrap" title={path}>
+                {`Not added: ${getFileName(path)}`}
+                {dropped.issue?.line != null &&
+                  `:${dropped.issue.line}${dropped.issue.column != null ? `:${dropped.issue.column}` : ""}`}
+                {`: ${dropped.reason}`}
+              </p>
+            ))}
+
             <Button onClick={handleAddFiles}>
               <Plus className="h-4 w-4 mr-2" />
               Add files
@@ -260,3 +401,22 @@ export function ConfigDialog({
     </Dialog>
   );
 }
+
+/** One line about a journal: size, transactions, date span and includes */
+function describeMetadata(metadata: JournalMetadata): string {
+  const parts = [`${(metadata.size / 1024).toFixed(0)} KB`];
+  const stats = metadata.stats;
+  if (stats) {
+    parts.push(`${stats.transactionCount} transactions`);
+    if (stats.firstDate && stats.lastDate) {
+      parts.push(`${stats.firstDate} to ${stats.lastDate}`);
+    }
+    if (stats.commodities.length > 0) {
+      parts.push(stats.commodities.join(", "));
+    }
+  }
+  if (metadata.includedFiles && metadata.includedFiles.length > 0) {
+    parts.push(`includes ${metadata.includedFiles.length} files`);
+  }
+  return parts.join(" · ");
+}
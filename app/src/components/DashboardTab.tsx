@@ -21,10 +21,10 @@ import { Bar, BarChart, CartesianGrid, LabelList, ReferenceLine, XAxis } from "r
 interface DashboardTabProps {
   searchQuery: string;
   dateRange: { start: DateValue; end: DateValue } | null;
-  selectedJournalFile: string;
+  journalId: string;
 }
 
-export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
+export function DashboardTab({ journalId }: DashboardTabProps) {
   const [balanceSheetData, setBalanceSheetData] = useState<BalanceSheetReport | null>(null);
   const [incomeStatementData, setIncomeStatementData] = useState<IncomeStatementReport | null>(null);
   const [prevIncomeStatementData, setPrevIncomeStatementData] = useState<IncomeStatementReport | null>(null);
@@ -38,8 +38,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
     options.depth = 1;
 
     // Keep it flat for simple display
-    options.flat = true;
-    options.tree = false;
+    options.listMode = "Flat";
 
     // Convert to dollars
     options.exchange = "$";
@@ -48,7 +47,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
 
     try {
       const balanceSheetReport = await invoke<BalanceSheetReport>("get_balancesheet", {
-        journalFile: selectedJournalFile,
+        journalId,
         options,
       });
 
@@ -57,7 +56,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       console.error("Failed to fetch balance sheet:", error);
       setBalanceSheetData(null);
     }
-  }, [selectedJournalFile]);
+  }, [journalId]);
 
   const fetchLastMonthExpenses = useCallback(async () => {
     const options = createDefaultIncomeStatementOptions();
@@ -85,12 +84,11 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
     options.depth = 1;
 
     // Keep it flat for simple display
-    options.flat = true;
-    options.tree = false;
+    options.listMode = "Flat";
 
     try {
       const incomeStatementReport = await invoke<IncomeStatementReport>("get_incomestatement", {
-        journalFile: selectedJournalFile,
+        journalId,
         options,
       });
 
@@ -99,7 +97,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       console.error("Failed to fetch income statement:", error);
       setIncomeStatementData(null);
     }
-  }, [selectedJournalFile]);
+  }, [journalId]);
 
   const fetchPreviousMonthExpenses = useCallback(async () => {
     const options = createDefaultIncomeStatementOptions();
@@ -127,12 +125,11 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
     options.depth = 1;
 
     // Keep it flat for simple display
-    options.flat = true;
-    options.tree = false;
+    options.listMode = "Flat";
 
     try {
       const incomeStatementReport = await invoke<IncomeStatementReport>("get_incomestatement", {
-        journalFile: selectedJournalFile,
+        journalId,
         options,
       });
 
@@ -141,7 +138,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       console.error("Failed to fetch previous month income statement:", error);
       setPrevIncomeStatementData(null);
     }
-  }, [selectedJournalFile]);
+  }, [journalId]);
 
   const fetchYearlyMonthlyExpenses = useCallback(async () => {
     const options = createDefaultIncomeStatementOptions();
@@ -165,7 +162,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
     options.end = formatDate(todayPlusOne);
 
     // Set monthly period to get data for each month
-    options.monthly = true;
+    options.interval = { type: "Monthly" };
 
     // Set depth to 2 to get expense categories
     options.depth = 2;
@@ -174,12 +171,11 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
     options.drop = 1;
 
     // Keep it flat for simple display
-    options.flat = true;
-    options.tree = false;
+    options.listMode = "Flat";
 
     try {
       const incomeStatementReport = await invoke<IncomeStatementReport>("get_incomestatement", {
-        journalFile: selectedJournalFile,
+        journalId,
         options,
       });
 
@@ -188,14 +184,14 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       console.error("Failed to fetch last 12 months expenses:", error);
       setYearlyExpensesData(null);
     }
-  }, [selectedJournalFile]);
+  }, [journalId]);
 
   const fetchHistoricalNetWorth = useCallback(async () => {
     const options = createDefaultBalanceSheetOptions();
 
     // No date range - get all historical data
     // Set monthly period to get data for each month
-    options.monthly = true;
+    options.interval = { type: "Monthly" };
 
     // Set depth to 2 for assets/liabilities breakdown
     options.depth = 2;
@@ -204,15 +200,14 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
     options.drop = 1;
 
     // Keep it flat for simple display
-    options.flat = true;
-    options.tree = false;
+    options.listMode = "Flat";
 
     // Convert to dollars
     options.exchange = "$";
 
     try {
       const balanceSheetReport = await invoke<BalanceSheetReport>("get_balancesheet", {
-        journalFile: selectedJournalFile,
+        journalId,
         options,
       });
       setHistoricalNetWorthData(balanceSheetReport);
@@ -220,11 +215,11 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       console.error("Failed to fetch historical net worth:", error);
       setHistoricalNetWorthData(null);
     }
-  }, [selectedJournalFile]);
+  }, [journalId]);
 
   // Fetch net worth and expenses only when journal file changes
   useEffect(() => {
-    if (selectedJournalFile) {
+    if (journalId) {
       fetchNetWorth();
       fetchLastMonthExpenses();
       fetchPreviousMonthExpenses();
@@ -237,7 +232,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
     fetchPreviousMonthExpenses,
     fetchYearlyMonthlyExpenses,
     fetchHistoricalNetWorth,
-    selectedJournalFile,
+    journalId,
   ]);
 
   // Extract net worth from balance sheet data
@@ -294,7 +289,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       const categories: { [key: string]: number } = {};
 
       expensesSubreport.rows.forEach((row) => {
-        let categoryName = row.display_name || row.account || "Unknown";
+        let categoryName = row.displayName || row.account || "Unknown";
 
         // Remove "expenses:" prefix if present
         if (categoryName.startsWith("expenses:")) {
@@ -348,7 +343,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       const assetCategories: { [key: string]: number } = {};
       if (assetsSubreport?.rows) {
         assetsSubreport.rows.forEach((row) => {
-          let categoryName = row.display_name || row.account || "Unknown";
+          let categoryName = row.displayName || row.account || "Unknown";
 
           // Remove "assets:" prefix if present
           if (categoryName.startsWith("assets:")) {
@@ -367,7 +362,7 @@ export function DashboardTab({ selectedJournalFile }: DashboardTabProps) {
       const liabilityCategories: { [key: string]: number } = {};
       if (liabilitiesSubreport?.rows) {
         liabilitiesSubreport.rows.forEach((row) => {
-          let categoryName = row.display_name || row.account || "Unknown";
+          let categoryName = row.displayName || row.account || "Unknown";
 
           // Remove "liabilities:" prefix if present
           if (categoryName.startsWith("liabilities:")) {
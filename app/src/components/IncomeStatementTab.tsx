@@ -6,6 +6,7 @@ import {
   type IncomeStatementReport,
   type IncomeStatementSubreport,
   createDefaultIncomeStatementOptions,
+  intervalFromPeriodMode,
 } from "@/types/hledger.types";
 import type { DateValue } from "@internationalized/date";
 import { invoke } from "@tauri-apps/api/core";
@@ -16,14 +17,14 @@ import { useCallback, useEffect, useMemo, useState } from "react";
 interface IncomeStatementTabProps {
   searchQuery: string;
   dateRange: { start: DateValue; end: DateValue } | null;
-  selectedJournalFile: string;
+  journalId: string;
   currencyMode: string;
 }
 
 export function IncomeStatementTab({
   searchQuery,
   dateRange,
-  selectedJournalFile,
+  journalId,
   currencyMode,
 }: IncomeStatementTabProps) {
   const [incomeStatementData, setIncomeStatementData] = useState<IncomeStatementReport | null>(null);
@@ -145,33 +146,10 @@ export function IncomeStatementTab({
       }
 
       // Set period mode
-      switch (periodMode) {
-        case "daily":
-          options.daily = true;
-          break;
-        case "weekly":
-          options.weekly = true;
-          break;
-        case "monthly":
-          options.monthly = true;
-          break;
-        case "quarterly":
-          options.quarterly = true;
-          break;
-        case "yearly":
-          options.yearly = true;
-          break;
-        // "none" or default - no period flags set
-      }
+      options.interval = intervalFromPeriodMode(periodMode);
 
       // Set tree/flat display mode
-      if (balanceDisplayMode === "tree") {
-        options.tree = true;
-        options.flat = false;
-      } else {
-        options.flat = true;
-        options.tree = false;
-      }
+      options.listMode = balanceDisplayMode === "tree" ? "Tree" : "Flat";
 
       // Set depth option
       if (depthMode !== "none") {
@@ -185,7 +163,7 @@ export function IncomeStatementTab({
 
       try {
         const incomeStatementReport = await invoke<IncomeStatementReport>("get_incomestatement", {
-          journalFile: selectedJournalFile,
+          journalId,
           options,
         });
 
@@ -195,7 +173,7 @@ export function IncomeStatementTab({
         setIncomeStatementData(null);
       }
     },
-    [balanceDisplayMode, periodMode, depthMode, currencyMode, selectedJournalFile],
+    [balanceDisplayMode, periodMode, depthMode, currencyMode, journalId],
   );
 
   // Handle balance display mode selection
@@ -305,10 +283,10 @@ export function IncomeStatementTab({
                         )}
                         {hasChildAccounts ? (
                           <button onClick={() => toggleAccount(row.account)} className="hover:underline text-left">
-                            {row.display_name || row.account}
+                            {row.displayName || row.account}
                           </button>
                         ) : (
-                          row.display_name || row.account
+                          row.displayName || row.account
                         )}
                       </div>
 
@@ -1,61 +1,93 @@
 import type { DateValue } from "@internationalized/date";
 import { invoke } from "@tauri-apps/api/core";
-import { File } from "lucide-react";
+import { ExternalLink, File } from "lucide-react";
 import { useCallback, useEffect, useState } from "react";
 
 import { Button } from "@/components/ui/button";
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
-import { type PrintReport, type PrintTransaction, createDefaultPrintOptions } from "@/types/hledger.types";
+import { type PageSpec, type PrintPage, type PrintTransaction, createDefaultPrintOptions } from "@/types/hledger.types";
 
 interface PrintTabProps {
   searchQuery: string;
   dateRange: { start: DateValue; end: DateValue } | null;
-  selectedJournalFile: string;
+  journalId: string;
 }
 
-export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintTabProps) {
+/** Transactions fetched per page, so long journals load incrementally */
+const PAGE_SIZE = 200;
+
+export function PrintTab({ searchQuery, dateRange, journalId }: PrintTabProps) {
   const [transactions, setTransactions] = useState<PrintTransaction[]>([]);
+  const [total, setTotal] = useState(0);
   const [loading, setLoading] = useState(false);
+  const [loadingMore, setLoadingMore] = useState(false);
+
+  const buildOptions = useCallback((query: string, customRange: { start: DateValue; end: DateValue } | null) => {
+    const options = createDefaultPrintOptions();
+
+    // Add the search query if provided
+    if (query.trim()) {
+      options.queries = [query];
+    }
+
+    // Add date range if provided
+    if (customRange) {
+      options.begin = customRange.start.toString();
+      options.end = customRange.end.add({ days: 1 }).toString();
+    }
+    return options;
+  }, []);
+
+  const fetchPage = useCallback(
+    async (offset: number, query: string, customRange: { start: DateValue; end: DateValue } | null) => {
+      const page: PageSpec = { offset, limit: PAGE_SIZE, order: "OldestFirst" };
+      return invoke<PrintPage>("get_print_page", {
+        journalId,
+        options: buildOptions(query, customRange),
+        page,
+      });
+    },
+    [journalId, buildOptions],
+  );
 
   const fetchTransactions = useCallback(
     async (query = "", customRange: { start: DateValue; end: DateValue } | null = null) => {
-      if (!selectedJournalFile) {
+      if (!journalId) {
         setTransactions([]);
+        setTotal(0);
         return;
       }
 
       setLoading(true);
-      const options = createDefaultPrintOptions();
-
-      // Add the search query if provided
-      if (query.trim()) {
-        options.queries = [query];
-      }
-
-      // Add date range if provided
-      if (customRange) {
-        options.begin = customRange.start.toString();
-        options.end = customRange.end.add({ days: 1 }).toString();
-      }
-
       try {
-        const printReport = await invoke<PrintReport>("get_print", {
-          journalFile: selectedJournalFile,
-          options,
-        });
-
-        setTransactions(printReport);
+        const printPage = await fetchPage(0, query, customRange);
+        setTransactions(printPage.transactions);
+        setTotal(printPage.total);
       } catch (error) {
         console.error("Failed to fetch transactions:", error);
         setTransactions([]);
+        setTotal(0);
       } finally {
         setLoading(false);
       }
     },
-    [selectedJournalFile],
+    [journalId, fetchPage],
   );
 
-  // Fetch transactions when searchQuery, dateRange, or selectedJournalFile changes
+  const loadMore = async () => {
+    setLoadingMore(true);
+    try {
+      const printPage = await fetchPage(transactions.length, searchQuery, dateRange);
+      setTransactions((current) => [...current, ...printPage.transactions]);
+      setTotal(printPage.total);
+    } catch (error) {
+      console.error("Failed to fetch more transactions:", error);
+    } finally {
+      setLoadingMore(false);
+    }
+  };
+
+  // Fetch transactions when searchQuery, dateRange, or journalId changes
   useEffect(() => {
     fetchTransactions(searchQuery, dateRange);
   }, [searchQuery, dateRange, fetchTransactions]);
@@ -65,8 +97,8 @@ export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintT
   };
 
   const copySourceLocation = async (transaction: PrintTransaction) => {
-    if (transaction.source_positions && transaction.source_positions.length > 0) {
-      const sourcePosition = transaction.source_positions[0];
+    if (transaction.sourcePositions && transaction.sourcePositions.length > 0) {
+      const sourcePosition = transaction.sourcePositions[0];
       const sourceLocation = `${sourcePosition.file}:${sourcePosition.line}`;
       try {
         await navigator.clipboard.writeText(sourceLocation);
@@ -76,6 +108,17 @@ export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintT
     }
   };
 
+  const openInEditor = async (transaction: PrintTransaction) => {
+    if (transaction.sourcePositions && transaction.sourcePositions.length > 0) {
+      const sourcePosition = transaction.sourcePositions[0];
+      try {
+        await invoke("open_in_editor", { journalId, file: sourcePosition.file, line: sourcePosition.line });
+      } catch (error) {
+        console.error("Failed to open the journal in an editor:", error);
+      }
+    }
+  };
+
   return (
     <Card>
       <CardHeader>
@@ -92,7 +135,8 @@ export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintT
             <div className="space-y-4">
               <div className="flex items-center justify-between">
                 <p className="text-sm text-muted-foreground">
-                  {transactions.length} {transactions.length !== 1 ? "transactions" : "transaction"}
+                  {transactions.length < total && `${transactions.length} of `}
+                  {total} {total !== 1 ? "transactions" : "transaction"}
                 </p>
               </div>
 
@@ -115,7 +159,7 @@ export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintT
                             {transaction.code && (
                               <span className="text-xs font-mono text-muted-foreground">({transaction.code})</span>
                             )}
-                            {transaction.source_positions && transaction.source_positions.length > 0 && (
+                            {transaction.sourcePositions && transaction.sourcePositions.length > 0 && (
                               <Button
                                 variant="ghost"
                                 size="sm"
@@ -126,6 +170,17 @@ export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintT
                                 <File className="h-3 w-3" />
                               </Button>
                             )}
+                            {transaction.sourcePositions && transaction.sourcePositions.length > 0 && (
+                              <Button
+                                variant="ghost"
+                                size="sm"
+                                onClick={() => openInEditor(transaction)}
+                                className="h-6 w-6 p-0"
+                                title="Open in editor"
+                              >
+                                <ExternalLink className="h-3 w-3" />
+                              </Button>
+                            )}
                           </div>
                         </div>
                         {transaction.comment && <p className="text-xs text-muted-foreground">{transaction.comment}</p>}
@@ -164,9 +219,9 @@ export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintT
                                     ))}
                                   </div>
                                 )}
-                                {posting.balance_assertion && (
+                                {posting.balanceAssertion && (
                                   <div className="text-xs text-muted-foreground mt-1">
-                                    = {formatAmount(posting.balance_assertion.amount)}
+                                    = {formatAmount(posting.balanceAssertion.amount)}
                                   </div>
                                 )}
                               </div>
@@ -178,6 +233,14 @@ export function PrintTab({ searchQuery, dateRange, selectedJournalFile }: PrintT
                   ))}
                 </div>
               </div>
+
+              {transactions.length < total && (
+                <div className="flex justify-center">
+                  <Button variant="outline" size="sm" onClick={loadMore} disabled={loadingMore}>
+                    {loadingMore ? "Loading..." : `Load ${Math.min(PAGE_SIZE, total - transactions.length)} more`}
+                  </Button>
+                </div>
+              )}
             </div>
           ) : (
             <div className="flex justify-center items-center py-8">
@@ -9,22 +9,22 @@ import { invoke } from "@tauri-apps/api/core";
 import { useCallback, useEffect, useState } from "react";
 
 interface VerificationTabProps {
-  selectedJournalFile: string;
+  journalId: string;
 }
 
-export default function VerificationTab({ selectedJournalFile }: VerificationTabProps) {
+export default function VerificationTab({ journalId }: VerificationTabProps) {
   const [tempBalances, setTempBalances] = useState<BalanceReport | null>(null);
   const [uncategorizedTransactions, setUncategorizedTransactions] = useState<PrintReport | null>(null);
 
   const fetchTempBalances = useCallback(async () => {
-    if (!selectedJournalFile) return;
+    if (!journalId) return;
 
     const options = createDefaultBalanceOptions();
     options.queries = ["temp"];
 
     try {
       const balanceReport = await invoke<BalanceReport>("get_balance", {
-        journalFile: selectedJournalFile,
+        journalId,
         options,
       });
       setTempBalances(balanceReport);
@@ -32,17 +32,17 @@ export default function VerificationTab({ selectedJournalFile }: VerificationTab
       console.error("Failed to fetch temp balances:", error);
       setTempBalances(null);
     }
-  }, [selectedJournalFile]);
+  }, [journalId]);
 
   const fetchUncategorizedTransactions = useCallback(async () => {
-    if (!selectedJournalFile) return;
+    if (!journalId) return;
 
     const options = createDefaultPrintOptions();
     options.queries = ["expenses:uncat", "expenses:unknown"];
 
     try {
       const printReport = await invoke<PrintReport>("get_print", {
-        journalFile: selectedJournalFile,
+        journalId,
         options,
       });
       setUncategorizedTransactions(printReport);
@@ -50,7 +50,7 @@ export default function VerificationTab({ selectedJournalFile }: VerificationTab
       console.error("Failed to fetch uncategorized transactions:", error);
       setUncategorizedTransactions(null);
     }
-  }, [selectedJournalFile]);
+  }, [journalId]);
 
   useEffect(() => {
     fetchTempBalances();
@@ -66,7 +66,7 @@ export default function VerificationTab({ selectedJournalFile }: VerificationTab
       );
     }
 
-    if ("accounts" in tempBalances) {
+    if (tempBalances.type === "Simple") {
       const simpleBalance = tempBalances;
       const accountsWithBalances = simpleBalance.accounts.filter((account) =>
         account.amounts.some((amount) => Number.parseFloat(amount.quantity) !== 0),
@@ -88,7 +88,7 @@ export default function VerificationTab({ selectedJournalFile }: VerificationTab
                 key={index}
                 className="flex justify-between items-start text-sm hover:bg-muted-foreground/10 rounded px-2 py-1"
               >
-                <span className="flex-1 mr-2">{balance.display_name || balance.name}</span>
+                <span className="flex-1 mr-2">{balance.displayName || balance.name}</span>
                 <div className="flex flex-col items-end">
                   {balance.amounts
                     .filter((amount) => Number.parseFloat(amount.quantity) !== 0)
@@ -1,50 +1,95 @@
-// Import and re-export all generated types from hledger-lib
-import type { AccountsOptions } from "../../../hledger-lib/bindings/AccountsOptions.ts";
-import type { Amount } from "../../../hledger-lib/bindings/Amount.ts";
-import type { BalanceAccount } from "../../../hledger-lib/bindings/BalanceAccount.ts";
-import type { BalanceOptions } from "../../../hledger-lib/bindings/BalanceOptions.ts";
-import type { BalanceReport } from "../../../hledger-lib/bindings/BalanceReport.ts";
-import type { BalanceSheetOptions } from "../../../hledger-lib/bindings/BalanceSheetOptions.ts";
-import type { BalanceSheetReport } from "../../../hledger-lib/bindings/BalanceSheetReport.ts";
-import type { BalanceSheetSubreport } from "../../../hledger-lib/bindings/BalanceSheetSubreport.ts";
-import type { IncomeStatementOptions } from "../../../hledger-lib/bindings/IncomeStatementOptions.ts";
-import type { IncomeStatementReport } from "../../../hledger-lib/bindings/IncomeStatementReport.ts";
-import type { IncomeStatementSubreport } from "../../../hledger-lib/bindings/IncomeStatementSubreport.ts";
-import type { PeriodDate } from "../../../hledger-lib/bindings/PeriodDate.ts";
-import type { PeriodicBalance } from "../../../hledger-lib/bindings/PeriodicBalance.ts";
-import type { PeriodicBalanceRow } from "../../../hledger-lib/bindings/PeriodicBalanceRow.ts";
-import type { Price } from "../../../hledger-lib/bindings/Price.ts";
-import type { PrintOptions } from "../../../hledger-lib/bindings/PrintOptions.ts";
-import type { PrintTransaction } from "../../../hledger-lib/bindings/PrintTransaction.ts";
-import type { PrintPosting } from "../../../hledger-lib/bindings/PrintPosting.ts";
-import type { PrintAmount } from "../../../hledger-lib/bindings/PrintAmount.ts";
-import type { SimpleBalance } from "../../../hledger-lib/bindings/SimpleBalance.ts";
-
-// PrintReport is a type alias in Rust, so we define it here
-export type PrintReport = PrintTransaction[];
-
-export type {
+// Import and re-export all generated types from the hledger-lib bindings barrel
+import type {
   AccountsOptions,
+  AppError,
   BalanceOptions,
-  BalanceReport,
   BalanceSheetOptions,
-  BalanceSheetReport,
-  BalanceSheetSubreport,
+  BudgetOptions,
+  CashflowOptions,
+  FlowOptions,
+  HledgerSource,
   IncomeStatementOptions,
-  IncomeStatementReport,
-  IncomeStatementSubreport,
-  SimpleBalance,
-  PeriodicBalance,
-  PeriodicBalanceRow,
-  PeriodDate,
-  BalanceAccount,
-  Amount,
-  Price,
+  NetworthOptions,
+  PricesOptions,
   PrintOptions,
   PrintTransaction,
-  PrintPosting,
-  PrintAmount,
-};
+  ReportInterval,
+  TopSpendingOptions,
+  WriteOptions,
+} from "../../../hledger-lib/bindings/index.ts";
+
+export type * from "../../../hledger-lib/bindings/index.ts";
+
+// PrintReport is a type alias in Rust, so we define it here
+export type PrintReport = PrintTransaction[];
+
+// A working hledger, as returned by the test_hledger_path command
+export interface TestedHledger {
+  version: string;
+  path: string;
+  source: HledgerSource;
+}
+
+// A remembered journal path, as returned by the get_journal_files command
+export interface JournalFile {
+  path: string;
+  exists: boolean;
+}
+
+// Journal path open_journal reads as hledger's default journal
+// (LEDGER_FILE, else ~/.hledger.journal), running hledger without -f
+export const DEFAULT_JOURNAL = "<default>";
+
+// An open journal, as returned by the get_open_journals command
+export interface OpenJournal {
+  journalId: string;
+  paths: string[];
+}
+
+// Payload of the journal-changed event sent for each open journal
+export interface JournalChange {
+  // The open journal the changed file belongs to
+  journalId: string;
+  path: string;
+  kind: "Modify" | "Remove" | "Rename";
+  // The journal's paths that read the changed file
+  journals: string[];
+}
+
+/**
+ * Map a period picker value ("none", "daily", ... "yearly") to the report
+ * interval sent to hledger
+ */
+export function intervalFromPeriodMode(mode: string): ReportInterval {
+  switch (mode) {
+    case "daily":
+      return { type: "Daily" };
+    case "weekly":
+      return { type: "Weekly" };
+    case "monthly":
+      return { type: "Monthly" };
+    case "quarterly":
+      return { type: "Quarterly" };
+    case "yearly":
+      return { type: "Yearly" };
+    default:
+      return { type: "None" };
+  }
+}
+
+/**
+ * The message to show for a rejected invoke: commands fail with an AppError,
+ * anything else is shown as it is
+ */
+export function errorMessage(error: unknown): string {
+  if (typeof error === "object" && error !== null && "message" in error) {
+    return String((error as AppError).message);
+  }
+  return String(error);
+}
+
+// Report kinds accepted by the get_report_raw command
+export type ReportKind = "balance" | "balancesheet" | "incomestatement" | "cashflow" | "print";
 
 // Utility functions for creating default instances
 
@@ -65,13 +110,16 @@ export function createDefaultAccountsOptions(): AccountsOptions {
     depth: null,
     begin: null,
     end: null,
+    uncheckedDates: false,
     period: null,
     unmarked: false,
     pending: false,
     cleared: false,
     real: false,
     empty: false,
+    ignoreAssertions: false,
     queries: [],
+    timeout: null,
   };
 }
 
@@ -88,40 +136,37 @@ export function createDefaultBalanceOptions(): BalanceOptions {
     change: false,
     cumulative: false,
     historical: false,
-    flat: true,
-    tree: false,
+    listMode: null,
     drop: null,
     declared: false,
     average: false,
-    row_total: false,
-    summary_only: false,
-    no_total: false,
-    no_elide: false,
-    sort_amount: false,
+    rowTotal: false,
+    summaryOnly: false,
+    noTotal: false,
+    noElide: false,
+    sortAmount: false,
     percent: false,
     related: false,
     invert: false,
     transpose: false,
     layout: null,
-    daily: false,
-    weekly: false,
-    monthly: false,
-    quarterly: false,
-    yearly: false,
-    period: null,
+    interval: { type: "None" },
     begin: null,
     end: null,
+    uncheckedDates: false,
     depth: null,
     unmarked: false,
     pending: false,
     cleared: false,
     real: false,
     empty: false,
+    ignoreAssertions: false,
     cost: false,
     market: false,
     exchange: null,
     value: null,
     queries: [],
+    timeout: null,
   };
 }
 
@@ -136,37 +181,136 @@ export function createDefaultBalanceSheetOptions(): BalanceSheetOptions {
     change: false,
     cumulative: false,
     historical: true,
-    flat: true,
-    tree: false,
+    listMode: null,
     drop: null,
     declared: false,
     average: false,
-    row_total: false,
-    summary_only: false,
-    no_total: false,
-    no_elide: false,
-    sort_amount: false,
+    rowTotal: false,
+    summaryOnly: false,
+    noTotal: false,
+    noElide: false,
+    sortAmount: false,
     percent: false,
     layout: null,
-    daily: false,
-    weekly: false,
-    monthly: false,
-    quarterly: false,
-    yearly: false,
-    period: null,
+    interval: { type: "None" },
     begin: null,
     end: null,
+    uncheckedDates: false,
     depth: null,
     unmarked: false,
     pending: false,
     cleared: false,
     real: false,
     empty: false,
+    ignoreAssertions: false,
     cost: false,
     market: false,
     exchange: null,
     value: null,
     queries: [],
+    timeout: null,
+  };
+}
+
+/**
+ * Create a new CashflowOptions object with default values
+ */
+export function createDefaultCashflowOptions(): CashflowOptions {
+  return {
+    sum: true,
+    valuechange: false,
+    gain: false,
+    budget: false,
+    change: true,
+    cumulative: false,
+    historical: false,
+    listMode: null,
+    drop: null,
+    declared: false,
+    average: false,
+    rowTotal: false,
+    summaryOnly: false,
+    noTotal: false,
+    noElide: false,
+    format: null,
+    sortAmount: false,
+    percent: false,
+    layout: null,
+    baseUrl: null,
+    begin: null,
+    end: null,
+    uncheckedDates: false,
+    interval: { type: "None" },
+    depth: null,
+    empty: false,
+    ignoreAssertions: false,
+    query: [],
+    timeout: null,
+  };
+}
+
+/**
+ * Create a new BudgetOptions object with default values
+ */
+export function createDefaultBudgetOptions(): BudgetOptions {
+  return {
+    budget: null,
+    interval: { type: "Monthly" },
+    begin: null,
+    end: null,
+    uncheckedDates: false,
+    depth: null,
+    empty: false,
+    queries: [],
+    timeout: null,
+  };
+}
+
+/**
+ * Create a new NetworthOptions object with default values
+ */
+export function createDefaultNetworthOptions(): NetworthOptions {
+  return {
+    interval: { type: "None" },
+    begin: null,
+    end: null,
+    uncheckedDates: false,
+    cost: false,
+    market: false,
+    exchange: null,
+    queries: [],
+    timeout: null,
+  };
+}
+
+/**
+ * Create a new PricesOptions object with default values
+ */
+export function createDefaultPricesOptions(): PricesOptions {
+  return {
+    inferMarketPrices: false,
+    begin: null,
+    end: null,
+    uncheckedDates: false,
+    queries: [],
+    timeout: null,
+  };
+}
+
+/**
+ * Create a new FlowOptions object with default values
+ */
+export function createDefaultFlowOptions(): FlowOptions {
+  return {
+    depth: null,
+    period: null,
+    begin: null,
+    end: null,
+    uncheckedDates: false,
+    commodity: null,
+    convert: false,
+    queries: [],
+    timeout: null,
   };
 }
 
@@ -181,37 +325,34 @@ export function createDefaultIncomeStatementOptions(): IncomeStatementOptions {
     change: true,
     cumulative: false,
     historical: false,
-    flat: true,
-    tree: false,
+    listMode: null,
     drop: null,
     declared: false,
     average: false,
-    row_total: false,
-    summary_only: false,
-    no_total: false,
-    no_elide: false,
-    sort_amount: false,
+    rowTotal: false,
+    summaryOnly: false,
+    noTotal: false,
+    noElide: false,
+    sortAmount: false,
     percent: false,
     layout: null,
-    daily: false,
-    weekly: false,
-    monthly: false,
-    quarterly: false,
-    yearly: false,
-    period: null,
+    interval: { type: "None" },
     begin: null,
     end: null,
+    uncheckedDates: false,
     depth: null,
     unmarked: false,
     pending: false,
     cleared: false,
     real: false,
     empty: false,
+    ignoreAssertions: false,
     cost: false,
     market: false,
     exchange: null,
     value: null,
     queries: [],
+    timeout: null,
   };
 }
 
@@ -221,17 +362,56 @@ export function createDefaultIncomeStatementOptions(): IncomeStatementOptions {
 export function createDefaultPrintOptions(): PrintOptions {
   return {
     explicit: false,
-    show_costs: false,
+    showCosts: false,
     round: null,
     new: false,
-    match_desc: null,
+    matchDesc: null,
     begin: null,
     end: null,
+    uncheckedDates: false,
     unmarked: false,
     pending: false,
     cleared: false,
     real: false,
     empty: false,
+    ignoreAssertions: false,
     queries: [],
+    timeout: null,
+  };
+}
+
+/**
+ * Create a new TopSpendingOptions object with default values
+ */
+export function createDefaultTopSpendingOptions(): TopSpendingOptions {
+  return {
+    accounts: null,
+    depth: null,
+    period: null,
+    begin: null,
+    end: null,
+    uncheckedDates: false,
+    commodity: null,
+    convert: false,
+    queries: [],
+    timeout: null,
+  };
+}
+
+/**
+ * Create a new WriteOptions object with default values
+ */
+export function createDefaultWriteOptions(): WriteOptions {
+  return {
+    validate: true,
+    hledgerPath: null,
+    style: {
+      indent: 4,
+      minGap: 2,
+      amountColumn: null,
+      commodityStyles: {},
+    },
+    expectUnchanged: null,
+    timeout: null,
   };
 }
@@ -1,3 +1,4 @@
+import { invoke } from "@tauri-apps/api/core";
 import { load } from "@tauri-apps/plugin-store";
 
 interface AppConfig {
@@ -29,6 +30,7 @@ export async function saveHledgerPath(path: string): Promise<void> {
 export async function saveJournalFiles(files: string[]): Promise<void> {
   const store = await load(STORE_FILE, { autoSave: true });
   await store.set("journalFiles", files);
+  await invoke("set_journal_files", { files });
   console.log("Saved journal files:", files);
 }
 
@@ -43,6 +45,7 @@ export async function removeJournalFile(fileToRemove: string): Promise<string[]>
   const currentFiles = (await store.get<string[]>("journalFiles")) || [];
   const updatedFiles = currentFiles.filter((file) => file !== fileToRemove);
   await store.set("journalFiles", updatedFiles);
+  await invoke("set_journal_files", { files: updatedFiles });
   console.log("Removed file:", fileToRemove, "Updated files:", updatedFiles);
   return updatedFiles;
 }
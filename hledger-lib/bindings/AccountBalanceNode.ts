@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * An account and its subaccounts, with their balances
+ */
+export type AccountBalanceNode = { 
+/**
+ * Last part of the account name, e.g. `checking`
+ */
+name: string, 
+/**
+ * Full account name, e.g. `assets:bank:checking`
+ */
+fullName: string, 
+/**
+ * The account's own balance; zero for parents hledger didn't list
+ */
+balance: MultiAmount, 
+/**
+ * `balance` plus the totals of all subaccounts
+ */
+total: MultiAmount, children: Array<AccountBalanceNode>, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountType } from "./AccountType";
+import type { SourcePosition } from "./SourcePosition";
+
+/**
+ * An account with the details `--types` and `--positions` add
+ */
+export type AccountInfo = { 
+/**
+ * Full account name
+ */
+name: string, 
+/**
+ * Declared or inferred type (with `types`)
+ */
+accountType: AccountType | null, 
+/**
+ * Where the account directive is (with `positions`, for declared accounts)
+ */
+declaredAt: SourcePosition | null, };
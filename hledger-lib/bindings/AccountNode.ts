@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An account and its subaccounts
+ */
+export type AccountNode = { 
+/**
+ * Last part of the account name, e.g. `checking`
+ */
+name: string, 
+/**
+ * Full account name, e.g. `assets:bank:checking`
+ */
+fullName: string, children: Array<AccountNode>, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Amount } from "./Amount";
+
+/**
+ * One account's spending, or the remainder after the top accounts
+ */
+export type AccountTotal = { 
+/**
+ * Full account name, or `other` for the remainder
+ */
+account: string, 
+/**
+ * Total in the ranking commodity
+ */
+amount: Amount, 
+/**
+ * Whether this is the aggregated remainder of the accounts below the top N
+ */
+isOther: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * hledger account type
+ */
+export type AccountType = "Asset" | "Liability" | "Equity" | "Revenue" | "Expense" | "Cash" | "Conversion" | "Other";
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * An account with its balance, as one balance report gives both
+ */
+export type AccountWithBalance = { 
+/**
+ * Full account name
+ */
+name: string, 
+/**
+ * The account's own postings, without its subaccounts' (unless
+ * `depth` clips them into it)
+ */
+balance: MultiAmount, };
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeout } from "./Timeout";
 
 /**
  * Options for the accounts command
@@ -33,7 +34,8 @@ positions: boolean,
  */
 directives: boolean, 
 /**
- * Find the first account matched by the pattern
+ * Find the first account matched by this pattern (a case-insensitive
+ * infix regex); see [`find_account`]
  */
 find: string | null, 
 /**
@@ -52,6 +54,10 @@ begin: string | null,
  * End date filter (exclusive: transactions before this date)
  */
 end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
 /**
  * Period expression
  */
@@ -76,7 +82,11 @@ real: boolean,
  * Show zero items (normally hidden)
  */
 empty: boolean, 
+/**
+ * Skip balance assertions, e.g. to see the report while one fails
+ */
+ignoreAssertions: boolean, 
 /**
  * Query patterns to filter accounts
  */
-queries: Array<string>, };
+queries: Array<string>, timeout: Timeout | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Comparison used by an amount query
+ */
+export type AmountOp = "Eq" | "Lt" | "Le" | "Gt" | "Ge";
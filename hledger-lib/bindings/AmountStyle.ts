@@ -1,6 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DigitGroups } from "./DigitGroups";
+import type { Precision } from "./Precision";
 
 /**
  * Amount display style
  */
-export type AmountStyle = { commodity_side: string, commodity_spaced: boolean, decimal_mark: string | null, digit_groups: string | null, precision: number, rounding: string, };
+export type AmountStyle = { commoditySide: string, commoditySpaced: boolean, decimalMark: string | null, digitGroups: DigitGroups | null, precision: Precision, rounding: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorCode } from "./ErrorCode";
+import type { ErrorDetails } from "./ErrorDetails";
+
+/**
+ * An error as the app's frontend receives it: a code to decide what to
+ * offer (the hledger path setting, a jump to the journal line), a message
+ * to show and whatever details the error carried
+ */
+export type AppError = { code: ErrorCode, message: string, details: ErrorDetails | null, };
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Amount } from "./Amount";
+
+/**
+ * A balance assertion that did not hold
+ */
+export type AssertionFailure = { 
+/**
+ * File containing the assertion
+ */
+file: string, 
+/**
+ * Line of the posting with the assertion (1-based)
+ */
+line: number, 
+/**
+ * Account the assertion is about
+ */
+account: string, 
+/**
+ * Balance the journal asserts
+ */
+expected: Amount, 
+/**
+ * Balance hledger calculated at that point
+ */
+actual: Amount, 
+/**
+ * `expected - actual`: what the balance is short by
+ */
+diff: Amount, };
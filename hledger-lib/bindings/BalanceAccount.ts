@@ -12,7 +12,7 @@ name: string,
 /**
  * Display name (may be shortened in tree mode)
  */
-display_name: string, 
+displayName: string, 
 /**
  * Indentation level (for tree display)
  */
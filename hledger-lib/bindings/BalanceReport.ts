@@ -4,5 +4,9 @@ import type { SimpleBalance } from "./SimpleBalance";
 
 /**
  * Unified balance report that can be either simple or periodic
+ *
+ * Serialized with a `type` of `simple` or `periodic` next to the report's
+ * fields. Reports serialized before the tag was added, with no `type`,
+ * still deserialize.
  */
-export type BalanceReport = SimpleBalance | PeriodicBalance;
+export type BalanceReport = { "type": "Simple" } & SimpleBalance | { "type": "Periodic" } & PeriodicBalance;
@@ -1,4 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ListMode } from "./ListMode";
+import type { ReportInterval } from "./ReportInterval";
+import type { Timeout } from "./Timeout";
 
 /**
  * Options for the balancesheet command
@@ -29,13 +32,10 @@ cumulative: boolean,
  */
 historical: boolean, 
 /**
- * Show accounts as flat list (default)
+ * Show accounts as a flat list or a tree; `None` leaves it to hledger
+ * (flat, unless a config file says otherwise)
  */
-flat: boolean, 
-/**
- * Show accounts as tree
- */
-tree: boolean, 
+listMode: ListMode | null, 
 /**
  * Omit N leading account name parts
  */
@@ -51,23 +51,23 @@ average: boolean,
 /**
  * Show row total column
  */
-row_total: boolean, 
+rowTotal: boolean, 
 /**
  * Display only row summaries
  */
-summary_only: boolean, 
+summaryOnly: boolean, 
 /**
  * Omit the final total row
  */
-no_total: boolean, 
+noTotal: boolean, 
 /**
  * Don't squash boring parent accounts
  */
-no_elide: boolean, 
+noElide: boolean, 
 /**
  * Sort by amount instead of account name
  */
-sort_amount: boolean, 
+sortAmount: boolean, 
 /**
  * Express values as percentage of column total
  */
@@ -77,29 +77,9 @@ percent: boolean,
  */
 layout: string | null, 
 /**
- * Daily periods
- */
-daily: boolean, 
-/**
- * Weekly periods
- */
-weekly: boolean, 
-/**
- * Monthly periods
- */
-monthly: boolean, 
-/**
- * Quarterly periods
+ * How the report is split into periods
  */
-quarterly: boolean, 
-/**
- * Yearly periods
- */
-yearly: boolean, 
-/**
- * Custom period
- */
-period: string | null, 
+interval: ReportInterval, 
 /**
  * Begin date (inclusive: transactions on or after this date)
  */
@@ -108,6 +88,10 @@ begin: string | null,
  * End date (exclusive: transactions before this date)
  */
 end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
 /**
  * Limit depth of accounts shown
  */
@@ -132,6 +116,10 @@ real: boolean,
  * Show zero items
  */
 empty: boolean, 
+/**
+ * Skip balance assertions, e.g. to see the report while one fails
+ */
+ignoreAssertions: boolean, 
 /**
  * Convert to cost basis
  */
@@ -147,4 +135,4 @@ exchange: string | null,
 /**
  * Detailed value conversion
  */
-value: string | null, queries: Array<string>, };
+value: string | null, queries: Array<string>, timeout: Timeout | null, };
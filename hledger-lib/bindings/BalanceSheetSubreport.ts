@@ -27,4 +27,4 @@ totals: PeriodicBalanceRow | null,
  * - Assets: true (increases net worth)
  * - Liabilities: false (decreases net worth)
  */
-increases_total: boolean, };
+increasesTotal: boolean, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * Actual and goal amounts for one account in one period
+ */
+export type BudgetCell = { 
+/**
+ * What was actually posted
+ */
+actual: MultiAmount, 
+/**
+ * The budget goal, if the account has one in this period
+ */
+goal: MultiAmount | null, 
+/**
+ * Goal minus actual
+ */
+remaining: MultiAmount | null, 
+/**
+ * Actual as a percentage of the goal, rounded to two decimal places
+ *
+ * Only given when the goal is a non-zero amount in a single commodity
+ * and the actual is in that same commodity.
+ */
+percent: string | null, };
@@ -0,0 +1,36 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReportInterval } from "./ReportInterval";
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for the budget report (`balance --budget`)
+ */
+export type BudgetOptions = { 
+/**
+ * Only use periodic rules whose description matches this pattern
+ */
+budget: string | null, 
+/**
+ * How the report is split into periods
+ */
+interval: ReportInterval, 
+/**
+ * Begin date (inclusive: transactions on or after this date)
+ */
+begin: string | null, 
+/**
+ * End date (exclusive: transactions before this date)
+ */
+end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
+/**
+ * Limit depth of accounts shown
+ */
+depth: number | null, 
+/**
+ * Show accounts without budget goals or activity too
+ */
+empty: boolean, queries: Array<string>, timeout: Timeout | null, };
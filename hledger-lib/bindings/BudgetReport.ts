@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetRow } from "./BudgetRow";
+import type { PeriodDate } from "./PeriodDate";
+
+/**
+ * Budget-vs-actual report
+ */
+export type BudgetReport = { 
+/**
+ * Period date ranges
+ */
+dates: Array<PeriodDate>, 
+/**
+ * Account rows
+ */
+rows: Array<BudgetRow>, 
+/**
+ * Totals across all accounts
+ */
+totals: BudgetRow | null, };
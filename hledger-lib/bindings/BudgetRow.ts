@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetCell } from "./BudgetCell";
+
+/**
+ * One account's budget performance across the report periods
+ */
+export type BudgetRow = { 
+/**
+ * Account name (`<unbudgeted>` collects accounts without goals)
+ */
+account: string, 
+/**
+ * One cell per report period
+ */
+cells: Array<BudgetCell>, };
@@ -1,4 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ListMode } from "./ListMode";
+import type { ReportInterval } from "./ReportInterval";
+import type { Timeout } from "./Timeout";
 
 /**
  * Options for the cashflow command
@@ -33,13 +36,10 @@ cumulative: boolean,
  */
 historical: boolean, 
 /**
- * Show accounts as a flat list (default)
+ * Show accounts as a flat list or a tree; `None` leaves it to hledger
+ * (flat, unless a config file says otherwise)
  */
-flat: boolean, 
-/**
- * Show accounts as a tree
- */
-tree: boolean, 
+listMode: ListMode | null, 
 /**
  * Omit N leading account name parts in flat mode
  */
@@ -55,19 +55,19 @@ average: boolean,
 /**
  * Show a row total column
  */
-row_total: boolean, 
+rowTotal: boolean, 
 /**
  * Display only row summaries
  */
-summary_only: boolean, 
+summaryOnly: boolean, 
 /**
  * Omit the final total row
  */
-no_total: boolean, 
+noTotal: boolean, 
 /**
  * Don't squash boring parent accounts in tree mode
  */
-no_elide: boolean, 
+noElide: boolean, 
 /**
  * Use custom line format
  */
@@ -75,7 +75,7 @@ format: string | null,
 /**
  * Sort by amount instead of account code/name
  */
-sort_amount: boolean, 
+sortAmount: boolean, 
 /**
  * Express values in percentage of each column's total
  */
@@ -87,7 +87,7 @@ layout: string | null,
 /**
  * Base URL for hledger-web hyperlinks
  */
-base_url: string | null, 
+baseUrl: string | null, 
 /**
  * Start date
  */
@@ -97,29 +97,13 @@ begin: string | null,
  */
 end: string | null, 
 /**
- * Reporting period
- */
-period: string | null, 
-/**
- * Report by day
- */
-daily: boolean, 
-/**
- * Report by week
+ * Pass `begin`/`end` to hledger without validating them first
  */
-weekly: boolean, 
+uncheckedDates: boolean, 
 /**
- * Report by month
+ * How the report is split into periods
  */
-monthly: boolean, 
-/**
- * Report by quarter
- */
-quarterly: boolean, 
-/**
- * Report by year
- */
-yearly: boolean, 
+interval: ReportInterval, 
 /**
  * Depth limit for accounts
  */
@@ -128,7 +112,11 @@ depth: number | null,
  * Show empty/zero accounts
  */
 empty: boolean, 
+/**
+ * Skip balance assertions, e.g. to see the report while one fails
+ */
+ignoreAssertions: boolean, 
 /**
  * Account query patterns
  */
-query: Array<string>, };
+query: Array<string>, timeout: Timeout | null, };
@@ -16,4 +16,4 @@ data: PeriodicBalance,
 /**
  * Whether this subreport increases the overall total (always true for cashflow)
  */
-increases_total: boolean, };
+increasesTotal: boolean, };
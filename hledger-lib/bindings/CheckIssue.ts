@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckIssueKind } from "./CheckIssueKind";
+import type { CheckSeverity } from "./CheckSeverity";
+
+/**
+ * A problem found by [`get_check`]
+ */
+export type CheckIssue = { kind: CheckIssueKind, severity: CheckSeverity, 
+/**
+ * File the problem is in, when known
+ */
+file: string | null, 
+/**
+ * Line of the problem (1-based), when known
+ */
+line: number | null, column: number | null, message: string, };
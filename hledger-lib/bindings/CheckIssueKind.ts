@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What kind of problem a [`CheckIssue`] is
+ */
+export type CheckIssueKind = "Unreadable" | "ParseError" | "CheckFailed" | "Warning";
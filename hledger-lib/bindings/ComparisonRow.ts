@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * One account's change in each period
+ */
+export type ComparisonRow = { account: string, 
+/**
+ * The change in the first period; zero if the account wasn't in it
+ */
+a: MultiAmount, 
+/**
+ * The change in the second period; zero if the account wasn't in it
+ */
+b: MultiAmount, 
+/**
+ * `b` less `a`
+ */
+delta: MultiAmount, 
+/**
+ * `delta` as a percentage of `a`, to two decimal places; `None` when
+ * `a` is zero or either period holds more than one commodity
+ */
+pctChange: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A field assignment such as `account2 expenses:food`
+ */
+export type CsvAssignment = { field: string, value: string, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CsvAssignment } from "./CsvAssignment";
+
+/**
+ * An `if` block: assignments applied to rows any of the matchers match
+ */
+export type CsvCondition = { 
+/**
+ * Regexes matched against the whole row, or against one field when
+ * written `%field REGEX`
+ */
+matchers: Array<string>, 
+/**
+ * Field assignments for matching rows
+ */
+assignments: Array<CsvAssignment>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PrintTransaction } from "./PrintTransaction";
+
+/**
+ * What [`import_csv`] did
+ */
+export type CsvImportOutcome = { "type": "Preview", "value": Array<PrintTransaction> } | { "type": "Imported", "value": number };
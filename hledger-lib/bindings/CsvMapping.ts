@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Which columns (counted from 0) hold what, as guessed from a preview
+ */
+export type CsvMapping = { date: number | null, 
+/**
+ * strptime-style format every sample date reads with, for the
+ * `date-format` rule
+ */
+dateFormat: string | null, description: number | null, 
+/**
+ * A single signed amount column
+ */
+amount: number | null, 
+/**
+ * Money in, when amounts are split over two columns
+ */
+amountIn: number | null, 
+/**
+ * Money out, when amounts are split over two columns
+ */
+amountOut: number | null, 
+/**
+ * `,` when the amounts use a decimal comma
+ */
+decimalMark: string | null, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The start of a CSV file
+ */
+export type CsvPreview = { 
+/**
+ * The first record when the file has a header, otherwise empty
+ */
+headers: Array<string>, 
+/**
+ * Up to the requested number of records after the header; rows may
+ * differ in length
+ */
+rows: Array<Array<string>>, 
+/**
+ * Records in the whole file, not counting the header
+ */
+totalRows: number, };
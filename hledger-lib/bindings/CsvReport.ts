@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A report as a table of text cells
+ */
+export type CsvReport = { 
+/**
+ * The first record
+ */
+headers: Array<string>, 
+/**
+ * Every other record; rows may differ in length from the headers
+ */
+rows: Array<Array<string>>, };
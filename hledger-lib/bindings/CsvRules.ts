@@ -0,0 +1,63 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CsvCondition } from "./CsvCondition";
+
+/**
+ * An hledger CSV rules file, describing how rows of a bank's CSV export
+ * become transactions
+ *
+ * Values like `amount_in` are hledger field assignments, so they may refer
+ * to CSV fields by name or number (`%credit`, `%4`) and mix in literal text.
+ * Render with [`CsvRules::to_rules_string`]; see the hledger manual's CSV
+ * section for what each rule does.
+ */
+export type CsvRules = { 
+/**
+ * Header lines to skip at the top of the file
+ */
+skip: number | null, 
+/**
+ * Field separator when it isn't a comma, e.g. `;` or `TAB`
+ */
+separator: string | null, 
+/**
+ * A name for each CSV column, in order; empty for unused columns.
+ * Names hledger knows (`date`, `description`, `amount`, ...) are
+ * assigned directly
+ */
+fields: Array<string>, 
+/**
+ * strptime-style format of the date field, e.g. `%d/%m/%Y`
+ */
+dateFormat: string | null, 
+/**
+ * Decimal mark of amounts, when it's `,`
+ */
+decimalMark: string | null, 
+/**
+ * Rows are newest first, so same-day transactions should be reversed
+ */
+newestFirst: boolean, 
+/**
+ * Commodity for amounts that have none
+ */
+currency: string | null, 
+/**
+ * The account the CSV is for, e.g. `assets:bank:checking`
+ */
+account1: string | null, 
+/**
+ * The other account, when no conditional rule sets one
+ */
+account2: string | null, 
+/**
+ * Amount flowing into account1
+ */
+amountIn: string | null, 
+/**
+ * Amount flowing out of account1
+ */
+amountOut: string | null, 
+/**
+ * `if` blocks, applied in order
+ */
+conditions: Array<CsvCondition>, };
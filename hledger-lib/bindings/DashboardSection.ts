@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A part of the dashboard that comes from one report
+ */
+export type DashboardSection = "NetWorth" | "IncomeExpenses" | "Transactions";
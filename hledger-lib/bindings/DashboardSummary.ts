@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MonthTotals } from "./MonthTotals";
+import type { MultiAmount } from "./MultiAmount";
+import type { UnavailableSection } from "./UnavailableSection";
+
+/**
+ * Summary of one month; a figure is `None` when its section is
+ * unavailable
+ */
+export type DashboardSummary = { 
+/**
+ * The month summarised, as `YYYY-MM`
+ */
+month: string, 
+/**
+ * Net worth at the end of the month
+ */
+netWorth: MultiAmount | null, thisMonth: MonthTotals | null, lastMonth: MonthTotals | null, 
+/**
+ * This month's net less last month's
+ */
+netChange: MultiAmount | null, 
+/**
+ * Transactions dated in the month
+ */
+transactionCount: number | null, unavailable: Array<UnavailableSection>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DefaultJournalSource } from "./DefaultJournalSource";
+
+/**
+ * The journal hledger reads when it isn't given `-f`
+ */
+export type DefaultJournal = { path: string, source: DefaultJournalSource, 
+/**
+ * Whether the file exists and can be opened
+ */
+readable: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where hledger's default journal was found
+ */
+export type DefaultJournalSource = "LedgerFile" | "Home";
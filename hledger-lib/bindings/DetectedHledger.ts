@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An installed hledger found by [`detect_hledger`]
+ */
+export type DetectedHledger = { path: string, 
+/**
+ * What `--version` printed, e.g. `hledger 1.32.3, linux-x86_64`
+ */
+version: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Digit group style, e.g. `1,000,000` or the Indian `10,00,000`
+ */
+export type DigitGroups = { 
+/**
+ * Character placed between digit groups
+ */
+separator: string, 
+/**
+ * Group sizes counting from the decimal mark leftwards; the last size repeats
+ */
+sizes: Array<number>, };
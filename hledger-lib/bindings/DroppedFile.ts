@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckIssue } from "./CheckIssue";
+import type { JournalEncoding } from "./JournalEncoding";
+
+/**
+ * Whether a file dropped on the window can be added as a journal
+ */
+export type DroppedFile = { accepted: boolean, 
+/**
+ * Why the file was turned away, or why an accepted one wasn't checked
+ */
+reason: string | null, 
+/**
+ * Transactions hledger read from it; `None` when it didn't read it
+ */
+transactionCount: number | null, 
+/**
+ * hledger's complaint about the file, with its line and column when
+ * known
+ */
+issue: CheckIssue | null, 
+/**
+ * The file's encoding when hledger can't read it, for the app to offer
+ * converting it to UTF-8; `None` for UTF-8
+ */
+encoding: JournalEncoding | null, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceSpan } from "./SourceSpan";
+
+/**
+ * A transaction that may duplicate the others in its group
+ */
+export type DuplicateCandidate = { 
+/**
+ * The transaction's index in the journal
+ */
+index: number, date: string, description: string, 
+/**
+ * Where the transaction is written, for deleting it
+ */
+sourceSpan: SourceSpan | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DuplicateCandidate } from "./DuplicateCandidate";
+
+/**
+ * Transactions that look like copies of each other
+ */
+export type DuplicateGroup = { 
+/**
+ * At least two transactions, in date then index order
+ */
+transactions: Array<DuplicateCandidate>, };
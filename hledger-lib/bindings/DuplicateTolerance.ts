@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How closely two transactions must match to be reported as duplicates
+ */
+export type DuplicateTolerance = { 
+/**
+ * Days the dates may differ by; 0 requires the same date
+ */
+dateDays: number, 
+/**
+ * Character edits the normalized descriptions may differ by; `None`
+ * requires them to be equal
+ */
+maxEditDistance: number | null, };
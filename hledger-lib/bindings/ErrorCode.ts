@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What kind of failure an [`AppError`] is, one per [`HLedgerError`]
+ * variant plus the app's own failures
+ */
+export type ErrorCode = "FileNotFound" | "Io" | "CommandFailed" | "JournalError" | "AssertionFailed" | "InvalidDate" | "InvalidTransaction" | "InvalidCsvRules" | "CsvDelimiter" | "CsvDate" | "FileChanged" | "UnsupportedFeature" | "UnsupportedByBackend" | "Http" | "Sqlite" | "Timeout" | "Cancelled" | "HledgerNotFound" | "HledgerNotExecutable" | "InvalidUtf8" | "Json" | "Csv" | "ParseError" | "Other";
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where and how an [`AppError`] happened, as far as the error knows
+ */
+export type ErrorDetails = { 
+/**
+ * The journal, or other file, the error is about
+ */
+file: string | null, line: number | null, column: number | null, 
+/**
+ * Journal lines hledger quoted around the error
+ */
+excerpt: string | null, 
+/**
+ * The hledger command line that failed
+ */
+command: string | null, exitCode: number | null, stderr: string | null, 
+/**
+ * The hledger binary that was looked for or couldn't run
+ */
+hledgerPath: string | null, 
+/**
+ * How long the command ran before it was stopped
+ */
+elapsedMs: bigint | null, };
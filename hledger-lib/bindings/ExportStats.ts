@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Rows written by [`export_to_sqlite`]
+ */
+export type ExportStats = { transactions: number, postings: number, amounts: number, tags: number, };
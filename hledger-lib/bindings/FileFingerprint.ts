@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Identifies a file's contents, to detect edits made behind our back
+ *
+ * Content is compared by length and hash rather than modification time,
+ * which editors and sync tools don't always update.
+ */
+export type FileFingerprint = { len: number, 
+/**
+ * FNV-1a hash of the contents, in hex
+ */
+hash: string, };
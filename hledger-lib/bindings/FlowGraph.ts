@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FlowLink } from "./FlowLink";
+import type { FlowNode } from "./FlowNode";
+
+/**
+ * Income → budget → expense flows, ready for a Sankey diagram
+ */
+export type FlowGraph = { nodes: Array<FlowNode>, links: Array<FlowLink>, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Amount } from "./Amount";
+
+/**
+ * A flow of money between two nodes, always positive
+ */
+export type FlowLink = { 
+/**
+ * Id of the node the money comes from
+ */
+source: string, 
+/**
+ * Id of the node the money goes to
+ */
+target: string, amount: Amount, };
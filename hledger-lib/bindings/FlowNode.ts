@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FlowNodeKind } from "./FlowNodeKind";
+
+/**
+ * A node in the flow graph
+ */
+export type FlowNode = { 
+/**
+ * Unique id: the full account name, or `budget`, `savings` or `deficit`
+ */
+id: string, 
+/**
+ * Text to show on the node
+ */
+label: string, kind: FlowNodeKind, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a node in the flow graph stands for
+ */
+export type FlowNodeKind = "Income" | "Budget" | "Expense" | "Savings" | "Deficit";
@@ -0,0 +1,37 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for [`get_flow_data`]
+ */
+export type FlowOptions = { 
+/**
+ * Account depth of the income and expense nodes; deeper accounts roll
+ * up (2 when `None`)
+ */
+depth: number | null, 
+/**
+ * Period expression, e.g. `2024-03` or `thisyear`
+ */
+period: string | null, 
+/**
+ * Begin date (inclusive: transactions on or after this date)
+ */
+begin: string | null, 
+/**
+ * End date (exclusive: transactions before this date)
+ */
+end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
+/**
+ * Commodity of the flows; the one used by the most accounts when `None`
+ */
+commodity: string | null, 
+/**
+ * Convert other commodities with the journal's market prices instead of
+ * leaving them out
+ */
+convert: boolean, queries: Array<string>, timeout: Timeout | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How [`format_amounts`] renders amounts
+ */
+export type FormatOptions = { 
+/**
+ * Abbreviate thousands, millions and billions (`$1.2k`), e.g. for chart
+ * axis labels
+ */
+compact: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An installed hledger's version, e.g. 1.32.3
+ */
+export type HLedgerVersion = { major: number, minor: number, patch: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a [`HealthItem`] found, for the app to act on
+ */
+export type HealthCode = "HledgerFound" | "HledgerNotFound" | "HledgerTooOld" | "HledgerVersionUnknown" | "NoJournalSelected" | "JournalMissing" | "JournalUnreadable" | "JournalParses" | "JournalInvalid" | "JournalCheckTimedOut" | "ConfigLoaded" | "ConfigFailed";
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthCode } from "./HealthCode";
+import type { HealthStatus } from "./HealthStatus";
+
+/**
+ * One finding of a health check
+ */
+export type HealthItem = { code: HealthCode, status: HealthStatus, message: string, 
+/**
+ * The journal the item is about, if any
+ */
+file: string | null, };
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HLedgerVersion } from "./HLedgerVersion";
+import type { HealthItem } from "./HealthItem";
+import type { HledgerSource } from "./HledgerSource";
+
+/**
+ * Result of [`health_check`]
+ */
+export type HealthReport = { 
+/**
+ * Whether no item is an error
+ */
+ok: boolean, 
+/**
+ * The binary that would run, if one was found
+ */
+hledgerPath: string | null, 
+/**
+ * Where `hledger_path` comes from
+ */
+hledgerSource: HledgerSource | null, hledgerVersion: HLedgerVersion | null, meetsMinimumVersion: boolean, items: Array<HealthItem>, };
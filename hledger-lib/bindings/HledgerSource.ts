@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where the hledger that runs comes from
+ */
+export type HledgerSource = "Sidecar" | "Custom" | "Path";
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CsvRules } from "./CsvRules";
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for the import command
+ */
+export type ImportOptions = { 
+/**
+ * Rules to read the files with, written to a temporary rules file for
+ * the run; takes precedence over `rules_file`
+ */
+rules: CsvRules | null, 
+/**
+ * Rules file to read the files with; hledger looks for `FILE.rules`
+ * next to each file when neither this nor `rules` is set
+ */
+rulesFile: string | null, 
+/**
+ * Only show what would be imported, writing nothing
+ */
+dryRun: boolean, 
+/**
+ * Mark everything in the files as imported without adding it
+ */
+catchup: boolean, timeout: Timeout | null, };
@@ -27,4 +27,4 @@ totals: PeriodicBalanceRow | null,
  * - Revenues: true (increases net income)
  * - Expenses: false (decreases net income)
  */
-increases_total: boolean, };
+increasesTotal: boolean, };
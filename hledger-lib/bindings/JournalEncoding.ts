@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The text encoding of a file
+ */
+export type JournalEncoding = { 
+/**
+ * The encoding's WHATWG name: `UTF-8`, `UTF-16LE`, `UTF-16BE` or
+ * `windows-1252` (which covers Latin-1)
+ */
+name: string, 
+/**
+ * Whether the file starts with a byte order mark
+ */
+bom: boolean, };
@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JournalEncoding } from "./JournalEncoding";
+import type { JournalStats } from "./JournalStats";
+
+/**
+ * A journal file's size and age, and what hledger finds in it
+ */
+export type JournalMetadata = { path: string, size: number, 
+/**
+ * Last modification, in milliseconds since the Unix epoch
+ */
+modifiedMs: number | null, 
+/**
+ * `None` when unknown: see `note`
+ */
+stats: JournalStats | null, 
+/**
+ * Files the journal includes, directly or through others; `None` when
+ * unknown
+ */
+includedFiles: Array<string> | null, 
+/**
+ * Why `stats` or `included_files` is unknown
+ */
+note: string | null, 
+/**
+ * The file's encoding when hledger can't read it, for the app to offer
+ * converting it to UTF-8; `None` for UTF-8
+ */
+encoding: JournalEncoding | null, };
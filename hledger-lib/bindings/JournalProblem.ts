@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Amount } from "./Amount";
+import type { CheckIssueKind } from "./CheckIssueKind";
+
+/**
+ * A problem with the journal, located for the editor to open at
+ */
+export type JournalProblem = { kind: CheckIssueKind, 
+/**
+ * File the problem is in, when known
+ */
+file: string | null, 
+/**
+ * Line of the problem (1-based), when known
+ */
+line: number | null, column: number | null, message: string, 
+/**
+ * For a failing balance assertion, the balance the journal asserts
+ */
+expected: Amount | null, 
+/**
+ * For a failing balance assertion, the balance hledger calculated
+ */
+actual: Amount | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Summary figures for a journal, from `hledger stats`
+ */
+export type JournalStats = { transactionCount: number, 
+/**
+ * Date of the first transaction; `None` without transactions
+ */
+firstDate: string | null, 
+/**
+ * Date of the last transaction; `None` without transactions
+ */
+lastDate: string | null, accountCount: number, payeeCount: number, commodities: Array<string>, };
@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AmountStyle } from "./AmountStyle";
+
+/**
+ * Layout used when rendering transactions as journal text
+ */
+export type JournalStyle = { 
+/**
+ * Spaces before each posting
+ */
+indent: number, 
+/**
+ * Minimum spaces between an account name and its amount
+ */
+minGap: number, 
+/**
+ * Right-align amounts to end at least at this column (hledger's own
+ * output aligns them just past the longest account name)
+ */
+amountColumn: number | null, 
+/**
+ * Display style per commodity, e.g. taken from a print report; other
+ * commodities get a default style
+ */
+commodityStyles: { [key in string]?: AmountStyle }, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JournalLayout } from "./JournalLayout";
+import type { OpeningBalance } from "./OpeningBalance";
+
+/**
+ * What goes into a new journal
+ */
+export type JournalTemplate = { 
+/**
+ * Commodity amounts are in unless they say otherwise, e.g. `$` or
+ * `EUR`; declared with a `commodity` directive
+ */
+currency: string, 
+/**
+ * Balances to start with, booked against
+ * [`OPENING_BALANCES_ACCOUNT`]; none leaves out the transaction
+ */
+openingBalances: Array<OpeningBalance>, 
+/**
+ * Date of the opening balances; today when `None`
+ */
+openingDate: string | null, layout: JournalLayout, };
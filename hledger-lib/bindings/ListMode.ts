@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a balance report lists its accounts
+ *
+ * Options hold an `Option<ListMode>`: `None` passes neither flag, leaving it
+ * to hledger (flat, unless a config file says otherwise). The lowercase
+ * names it once had are still read, as saved settings may hold them.
+ */
+export type ListMode = "Flat" | "Tree";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Amount } from "./Amount";
+
+/**
+ * A market price: one unit of `commodity` was worth `price` on `date`
+ */
+export type MarketPrice = { date: string, commodity: string, price: Amount, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * Activity in one calendar month
+ */
+export type MonthSummary = { year: number, 
+/**
+ * 1 to 12
+ */
+month: number, 
+/**
+ * Number of transactions with a matching posting in the month
+ */
+txnCount: number, 
+/**
+ * Sum of the matching postings
+ */
+total: MultiAmount, };
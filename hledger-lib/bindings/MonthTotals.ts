@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * Income and expenses over one month
+ */
+export type MonthTotals = { 
+/**
+ * Revenues, positive as hledger shows them
+ */
+income: MultiAmount, expenses: MultiAmount, 
+/**
+ * Income less expenses
+ */
+net: MultiAmount, };
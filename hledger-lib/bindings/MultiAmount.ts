@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A sum of amounts in possibly several commodities
+ *
+ * Quantities are kept per commodity; commodities that net to zero are dropped,
+ * so a `MultiAmount` is zero exactly when it holds no commodities. Prices are
+ * not tracked. Serializes as an object mapping commodity to decimal string,
+ * e.g. `{"$": "120.50", "EUR": "-3"}`.
+ */
+export type MultiAmount = Record<string, string>;
@@ -0,0 +1,39 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReportInterval } from "./ReportInterval";
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for the net worth series
+ *
+ * Balances are always historical (from the start of the journal). With no
+ * interval chosen the series is monthly.
+ */
+export type NetworthOptions = { 
+/**
+ * How the series is split into periods (monthly when `None`)
+ */
+interval: ReportInterval, 
+/**
+ * Begin date (inclusive: transactions on or after this date)
+ */
+begin: string | null, 
+/**
+ * End date (exclusive: transactions before this date)
+ */
+end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
+/**
+ * Convert to cost basis
+ */
+cost: boolean, 
+/**
+ * Convert to market value at period end
+ */
+market: boolean, 
+/**
+ * Convert to specific commodity
+ */
+exchange: string | null, queries: Array<string>, timeout: Timeout | null, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * Net worth at the end of one period
+ */
+export type NetworthPoint = { 
+/**
+ * Last day of the period (inclusive)
+ */
+date: string, 
+/**
+ * Total assets
+ */
+assets: MultiAmount, 
+/**
+ * Total liabilities, positive when money is owed
+ */
+liabilities: MultiAmount, 
+/**
+ * Assets minus liabilities
+ */
+netWorth: MultiAmount, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An account's balance when the journal starts
+ */
+export type OpeningBalance = { account: string, quantity: string, 
+/**
+ * The template's currency when `None`
+ */
+commodity: string | null, };
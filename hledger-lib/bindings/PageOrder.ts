@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Order a [`PageSpec`] counts transactions in
+ */
+export type PageOrder = "OldestFirst" | "NewestFirst";
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PageOrder } from "./PageOrder";
+
+/**
+ * Which transactions of a print report to return, for loading long
+ * journals a page at a time
+ */
+export type PageSpec = { 
+/**
+ * Transactions to skip, counted in `order`
+ */
+offset: number, 
+/**
+ * Most transactions to return
+ */
+limit: number, order: PageOrder, };
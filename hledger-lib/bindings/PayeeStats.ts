@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * What one payee's transactions add up to
+ */
+export type PayeeStats = { payee: string, transactionCount: number, firstDate: string, lastDate: string, 
+/**
+ * Sum of the expense and income postings, signed as hledger shows them
+ * (spending positive, income negative)
+ */
+total: MultiAmount, 
+/**
+ * `total` per transaction, rounded to the total's decimal places
+ */
+average: MultiAmount, 
+/**
+ * The expense or income account posted to most often; `None` when the
+ * payee has no such postings
+ */
+topAccount: string | null, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for [`get_payee_stats`]
+ */
+export type PayeeStatsOptions = { 
+/**
+ * Leave out payees with fewer transactions than this
+ */
+minCount: number, 
+/**
+ * Begin date (inclusive: transactions on or after this date)
+ */
+begin: string | null, 
+/**
+ * End date (exclusive: transactions before this date)
+ */
+end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, queries: Array<string>, timeout: Timeout | null, };
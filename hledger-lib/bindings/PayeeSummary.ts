@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * Activity for one payee
+ */
+export type PayeeSummary = { payee: string, 
+/**
+ * Number of transactions with a matching posting
+ */
+txnCount: number, 
+/**
+ * Sum of the matching postings
+ */
+total: MultiAmount, };
@@ -5,10 +5,10 @@
  */
 export type PeriodDate = { 
 /**
- * Start date (ISO format)
+ * Start date (ISO format, inclusive)
  */
 start: string, 
 /**
- * End date (ISO format)
+ * End date (ISO format, exclusive)
  */
 end: string, };
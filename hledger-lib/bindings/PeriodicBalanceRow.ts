@@ -10,9 +10,13 @@ export type PeriodicBalanceRow = {
  */
 account: string, 
 /**
- * Display name
+ * Display name (leaf part of the account in tree mode)
  */
-display_name: string, 
+displayName: string, 
+/**
+ * Indentation level (for tree display)
+ */
+indent: number, 
 /**
  * Amounts for each period
  */
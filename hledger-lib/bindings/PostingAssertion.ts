@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A balance assertion on a posting, e.g. `= $100` or `==* $100`
+ */
+export type PostingAssertion = { commodity: string, quantity: string, 
+/**
+ * Assert the balance of all commodities (`==`), not just this one
+ */
+total: boolean, 
+/**
+ * Include subaccounts in the asserted balance (`=*`)
+ */
+inclusive: boolean, };
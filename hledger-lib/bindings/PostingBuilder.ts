@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PostingAssertion } from "./PostingAssertion";
+import type { Price } from "./Price";
+import type { Status } from "./Status";
+
+/**
+ * A posting of a [`TransactionBuilder`]
+ */
+export type PostingBuilder = { account: string, status: Status, 
+/**
+ * Amount quantity; `None` leaves the amount for hledger to infer
+ */
+quantity: string | null, commodity: string, price: Price | null, assertion: PostingAssertion | null, comment: string | null, tags: Array<[string, string]>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Number of decimal digits an amount is displayed with
+ */
+export type Precision = { "type": "Digits", "digits": number } | { "type": "Natural" };
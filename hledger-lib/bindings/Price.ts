@@ -1,9 +1,14 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PriceKind } from "./PriceKind";
 
 /**
  * Price information for amounts
  */
 export type Price = { 
+/**
+ * Whether the price is per unit (`@`) or for the whole amount (`@@`)
+ */
+kind: PriceKind, 
 /**
  * Price commodity
  */
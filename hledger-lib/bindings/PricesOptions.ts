@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for the prices command
+ */
+export type PricesOptions = { 
+/**
+ * Also list prices inferred from transaction costs
+ */
+inferMarketPrices: boolean, 
+/**
+ * Begin date (inclusive: prices on or after this date)
+ */
+begin: string | null, 
+/**
+ * End date (exclusive: prices before this date)
+ */
+end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
+/**
+ * Commodity patterns, e.g. `cur:GOOG`
+ */
+queries: Array<string>, timeout: Timeout | null, };
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeout } from "./Timeout";
 
 /**
  * Options for the print command
@@ -11,7 +12,7 @@ explicit: boolean,
 /**
  * Show transaction prices even with conversion postings
  */
-show_costs: boolean, 
+showCosts: boolean, 
 /**
  * Rounding mode: none, soft, hard, all
  */
@@ -23,7 +24,17 @@ new: boolean,
 /**
  * Fuzzy search for transaction by description
  */
-match_desc: string | null, 
+matchDesc: string | null, 
+/**
+ * Include transactions generated by periodic rules (`~ monthly ...`),
+ * up to `end` or six months after today
+ */
+forecast: boolean, 
+/**
+ * Date hledger takes as today (`YYYY-MM-DD`), for forecasts and
+ * relative dates; the system date when `None`
+ */
+today: string | null, 
 /**
  * Begin date (inclusive)
  */
@@ -32,6 +43,10 @@ begin: string | null,
  * End date (exclusive)
  */
 end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
 /**
  * Include only unmarked transactions
  */
@@ -51,4 +66,8 @@ real: boolean,
 /**
  * Show empty accounts
  */
-empty: boolean, queries: Array<string>, };
+empty: boolean, 
+/**
+ * Skip balance assertions, e.g. to see the report while one fails
+ */
+ignoreAssertions: boolean, queries: Array<string>, timeout: Timeout | null, };
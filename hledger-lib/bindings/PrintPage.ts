@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PrintTransaction } from "./PrintTransaction";
+
+/**
+ * One page of a print report
+ */
+export type PrintPage = { 
+/**
+ * How many transactions the whole report has
+ */
+total: number, 
+/**
+ * The page's transactions, in the page's order
+ */
+transactions: Array<PrintTransaction>, };
@@ -5,4 +5,4 @@ import type { PrintAmount } from "./PrintAmount";
 /**
  * Posting structure
  */
-export type PrintPosting = { account: string, amounts: Array<PrintAmount>, status: string, comment: string, tags: Array<[string, string]>, posting_type: string, date: string | null, date2: string | null, balance_assertion: BalanceAssertion | null, original: PrintPosting | null, transaction_index: string, };
+export type PrintPosting = { account: string, amounts: Array<PrintAmount>, status: string, comment: string, tags: Array<[string, string]>, postingType: string, date: string | null, date2: string | null, balanceAssertion: BalanceAssertion | null, original: PrintPosting | null, transactionIndex: string, };
@@ -1,8 +1,13 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { PrintPosting } from "./PrintPosting";
 import type { SourcePosition } from "./SourcePosition";
+import type { SourceSpan } from "./SourceSpan";
 
 /**
  * Transaction structure
  */
-export type PrintTransaction = { index: number, date: string, date2: string | null, status: string, code: string, description: string, comment: string, tags: Array<[string, string]>, postings: Array<PrintPosting>, preceding_comment: string, source_positions: Array<SourcePosition>, };
+export type PrintTransaction = { index: number, date: string, date2: string | null, status: string, code: string, description: string, comment: string, tags: Array<[string, string]>, postings: Array<PrintPosting>, precedingComment: string, sourcePositions: Array<SourcePosition>, 
+/**
+ * Span from the first to the last source position, if hledger gave any
+ */
+sourceSpan: SourceSpan | null, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AmountOp } from "./AmountOp";
+import type { Status } from "./Status";
+
+/**
+ * A single hledger query term, rendered to a command-line argument with
+ * [`Query::to_arg`]
+ *
+ * Text values of `Account`, `Description`, `Payee`, `Note` and `Code` are
+ * regular expressions, as in hledger. `Currency` matches a commodity symbol
+ * literally.
+ */
+export type Query = { "type": "Account", "value": string } | { "type": "Description", "value": string } | { "type": "Payee", "value": string } | { "type": "Note", "value": string } | { "type": "Tag", "value": { name: string, value: string | null, } } | { "type": "Amount", "value": { op: AmountOp, value: string, } } | { "type": "Currency", "value": string } | { "type": "Status", "value": Status } | { "type": "Date", "value": string } | { "type": "Code", "value": string } | { "type": "Not", "value": Query } | { "type": "Expr", "value": string };
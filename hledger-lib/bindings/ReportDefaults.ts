@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ListMode } from "./ListMode";
+import type { ReportInterval } from "./ReportInterval";
+import type { Status } from "./Status";
+import type { ValueMode } from "./ValueMode";
+
+/**
+ * Report settings to use unless a call sets them; `None` leaves the
+ * report's own default
+ */
+export type ReportDefaults = { interval: ReportInterval | null, 
+/**
+ * Accounts as a tree or a flat list; the `tree` boolean it replaced is
+ * still read from saved settings
+ */
+listMode: ListMode | null, depth: number | null, 
+/**
+ * Only postings with one of these statuses; empty means all
+ */
+statuses: Array<Status>, valueMode: ValueMode | null, 
+/**
+ * Convert amounts to this commodity (`--exchange`)
+ */
+commodity: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a report is split into periods
+ */
+export type ReportInterval = { "type": "None" } | { "type": "Daily" } | { "type": "Weekly" } | { "type": "Monthly" } | { "type": "Quarterly" } | { "type": "Yearly" } | { "type": "Custom", "value": string };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A report with the warnings raised while producing it, so the app can
+ * show them without treating the report as failed
+ */
+export type ReportOutput<T> = { value: T, warnings: Array<string>, };
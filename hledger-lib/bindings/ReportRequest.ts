@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountsOptions } from "./AccountsOptions";
+import type { BalanceOptions } from "./BalanceOptions";
+import type { BalanceSheetOptions } from "./BalanceSheetOptions";
+import type { BudgetOptions } from "./BudgetOptions";
+import type { CashflowOptions } from "./CashflowOptions";
+import type { IncomeStatementOptions } from "./IncomeStatementOptions";
+import type { PricesOptions } from "./PricesOptions";
+import type { PrintOptions } from "./PrintOptions";
+
+/**
+ * One report to run, with its options
+ */
+export type ReportRequest = { "type": "Accounts", "value": AccountsOptions } | { "type": "Balance", "value": BalanceOptions } | { "type": "BalanceSheet", "value": BalanceSheetOptions } | { "type": "IncomeStatement", "value": IncomeStatementOptions } | { "type": "Cashflow", "value": CashflowOptions } | { "type": "Print", "value": PrintOptions } | { "type": "Prices", "value": PricesOptions } | { "type": "Budget", "value": BudgetOptions };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BalanceReport } from "./BalanceReport";
+import type { BalanceSheetReport } from "./BalanceSheetReport";
+import type { BudgetReport } from "./BudgetReport";
+import type { CashflowReport } from "./CashflowReport";
+import type { IncomeStatementReport } from "./IncomeStatementReport";
+import type { MarketPrice } from "./MarketPrice";
+import type { PrintTransaction } from "./PrintTransaction";
+
+/**
+ * The report a [`ReportRequest`] produced, of the same kind
+ */
+export type ReportResponse = { "type": "Accounts", "value": Array<string> } | { "type": "Balance", "value": BalanceReport } | { "type": "BalanceSheet", "value": BalanceSheetReport } | { "type": "IncomeStatement", "value": IncomeStatementReport } | { "type": "Cashflow", "value": CashflowReport } | { "type": "Print", "value": Array<PrintTransaction> } | { "type": "Prices", "value": Array<MarketPrice> } | { "type": "Budget", "value": BudgetReport };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * One posting in a register view, with the running total after it
+ */
+export type RunningBalanceEntry = { 
+/**
+ * Posting date (the transaction's unless the posting has its own)
+ */
+date: string, 
+/**
+ * Index of the transaction in the journal
+ */
+transactionIndex: number, description: string, account: string, 
+/**
+ * The posting's amount
+ */
+change: MultiAmount, 
+/**
+ * Sum of this and all earlier matching postings
+ */
+balance: MultiAmount, };
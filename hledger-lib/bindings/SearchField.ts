@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The part of a transaction a query matched
+ */
+export type SearchField = "Description" | "Comment" | "PostingComment" | "Tag" | "Account";
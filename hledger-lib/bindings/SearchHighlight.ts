@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchField } from "./SearchField";
+
+/**
+ * Where a query term was found
+ */
+export type SearchHighlight = { field: SearchField, 
+/**
+ * Which posting or tag, for fields a transaction has several of; 0
+ * otherwise
+ */
+item: number, 
+/**
+ * Byte offset of the first matched byte in the field's text
+ */
+start: number, 
+/**
+ * Byte offset just past the match
+ */
+end: number, };
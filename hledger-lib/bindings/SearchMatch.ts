@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PrintTransaction } from "./PrintTransaction";
+import type { SearchHighlight } from "./SearchHighlight";
+
+/**
+ * A transaction that matched, with every place its terms were found
+ */
+export type SearchMatch = { 
+/**
+ * The transaction, with its source span for jumping to it
+ */
+transaction: PrintTransaction, 
+/**
+ * Non-overlapping ranges, in field order
+ */
+highlights: Array<SearchHighlight>, };
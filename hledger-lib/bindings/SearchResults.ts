@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchMatch } from "./SearchMatch";
+
+/**
+ * One page of matches
+ */
+export type SearchResults = { matches: Array<SearchMatch>, 
+/**
+ * Matches across all pages
+ */
+total: number, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SeriesPoint } from "./SeriesPoint";
+
+/**
+ * An account's values in one commodity, with a point for every period
+ */
+export type Series = { 
+/**
+ * Full account name
+ */
+account: string, commodity: string, points: Array<SeriesPoint>, };
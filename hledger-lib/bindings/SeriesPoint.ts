@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One account's value in one period
+ */
+export type SeriesPoint = { 
+/**
+ * First day of the period
+ */
+date: string, value: string, };
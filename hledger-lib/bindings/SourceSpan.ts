@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where a transaction sits in its source file
+ *
+ * The end position is exclusive: hledger reports it as the start of the
+ * line following the transaction's last posting.
+ */
+export type SourceSpan = { file: string, startLine: number, startColumn: number, endLine: number, endColumn: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Clearing status of a transaction or posting
+ */
+export type Status = "Unmarked" | "Pending" | "Cleared";
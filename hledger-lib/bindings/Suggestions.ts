@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Suggestions for one query
+ */
+export type Suggestions = { values: Array<string>, 
+/**
+ * The index is being rebuilt, so `values` may be out of date; asking
+ * again shortly gives the fresh ones
+ */
+refreshing: boolean, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+import type { TagValueUsage } from "./TagValueUsage";
+
+/**
+ * How much a tag is used, overall and per value
+ */
+export type TagUsage = { name: string, 
+/**
+ * Transactions with the tag on themselves or any of their postings
+ */
+transactionCount: number, 
+/**
+ * Postings with the tag, their own or their transaction's
+ */
+postingCount: number, 
+/**
+ * Sum of the postings with the tag
+ */
+total: MultiAmount, 
+/**
+ * Sorted by value
+ */
+values: Array<TagValueUsage>, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MultiAmount } from "./MultiAmount";
+
+/**
+ * How much one tag value is used
+ */
+export type TagValueUsage = { 
+/**
+ * Empty for the tag used without a value
+ */
+value: string, transactionCount: number, postingCount: number, 
+/**
+ * Sum of the postings with this value
+ */
+total: MultiAmount, };
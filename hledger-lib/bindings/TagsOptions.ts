@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for the tags command
+ */
+export type TagsOptions = { 
+/**
+ * Only tags whose name matches this regular expression
+ */
+pattern: string | null, 
+/**
+ * Begin date (inclusive: transactions on or after this date)
+ */
+begin: string | null, 
+/**
+ * End date (exclusive: transactions before this date)
+ */
+end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, 
+/**
+ * Query patterns selecting the transactions whose tags are listed
+ */
+queries: Array<string>, timeout: Timeout | null, };
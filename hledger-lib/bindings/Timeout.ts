@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How long hledger may run before it is killed and the call fails with
+ * `HLedgerError::Timeout`
+ *
+ * Options hold an `Option<Timeout>`: `None` lets hledger run as long as it
+ * needs. Serialized as whole milliseconds.
+ */
+export type Timeout = number;
@@ -0,0 +1,40 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Accumulation } from "./Accumulation";
+import type { ReportInterval } from "./ReportInterval";
+import type { Timeout } from "./Timeout";
+import type { ValueMode } from "./ValueMode";
+
+/**
+ * What [`get_timeseries`] charts
+ */
+export type TimeseriesSpec = { 
+/**
+ * Query terms as hledger takes them, e.g. `^expenses` `depth:2`; each
+ * account matched is a series
+ */
+query: Array<string>, 
+/**
+ * How the series is split into periods (monthly when `None`)
+ */
+interval: ReportInterval, accumulation: Accumulation, 
+/**
+ * How amounts are valued; as recorded when `None`
+ */
+valueMode: ValueMode | null, 
+/**
+ * Commodity of every series, other commodities being converted with the
+ * journal's market prices; one series per commodity when `None`
+ */
+commodity: string | null, 
+/**
+ * Begin date (inclusive: transactions on or after this date)
+ */
+begin: string | null, 
+/**
+ * End date (exclusive: transactions before this date)
+ */
+end: string | null, 
+/**
+ * Pass `begin`/`end` to hledger without validating them first
+ */
+uncheckedDates: boolean, timeout: Timeout | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PostingBuilder } from "./PostingBuilder";
+import type { Status } from "./Status";
+
+/**
+ * A transaction to be written as journal text
+ */
+export type TransactionBuilder = { date: string, status: Status, code: string | null, description: string, comment: string | null, tags: Array<[string, string]>, postings: Array<PostingBuilder>, };
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TransactionBuilder } from "./TransactionBuilder";
+
+/**
+ * A transaction to start the form from, copied from the latest one with
+ * a similar description
+ */
+export type TransactionSuggestion = { 
+/**
+ * Its description, tags, and postings with their accounts, amounts and
+ * tags; `None` when no description matches
+ */
+template: TransactionBuilder | null, 
+/**
+ * Date of the transaction the template was copied from
+ */
+lastDate: string | null, 
+/**
+ * The index is being rebuilt, so the template may be out of date
+ */
+refreshing: boolean, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MonthSummary } from "./MonthSummary";
+import type { PayeeSummary } from "./PayeeSummary";
+
+/**
+ * Both groupings of the same transactions
+ */
+export type TransactionSummary = { byPayee: Array<PayeeSummary>, byMonth: Array<MonthSummary>, };
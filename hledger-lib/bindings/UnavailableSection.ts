@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AppError } from "./AppError";
+import type { DashboardSection } from "./DashboardSection";
+
+/**
+ * Why a section of the dashboard is missing
+ */
+export type UnavailableSection = { section: DashboardSection, error: AppError, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PrintTransaction } from "./PrintTransaction";
+
+/**
+ * A transaction dated after today
+ */
+export type UpcomingTransaction = { transaction: PrintTransaction, 
+/**
+ * Period expression of the periodic rule that generated it, e.g.
+ * `monthly from 2024-01-01`; `None` for one recorded in the journal
+ */
+rule: string | null, };
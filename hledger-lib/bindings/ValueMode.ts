@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How amounts are valued when the caller doesn't say
+ *
+ * The lowercase names it once had are still read, as saved settings may
+ * hold them.
+ */
+export type ValueMode = "Cost" | "Market";
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileFingerprint } from "./FileFingerprint";
+import type { JournalStyle } from "./JournalStyle";
+import type { Timeout } from "./Timeout";
+
+/**
+ * Options for writing transactions to a journal file
+ */
+export type WriteOptions = { 
+/**
+ * Run `hledger check` on the journal after writing, restoring the
+ * previous contents if it fails
+ */
+validate: boolean, 
+/**
+ * hledger binary used for validation (found on PATH when unset)
+ */
+hledgerPath: string | null, 
+/**
+ * Layout of the rendered transaction
+ */
+style: JournalStyle, 
+/**
+ * Refuse to write if the file no longer matches this fingerprint, taken
+ * with [`file_fingerprint`] when the journal was last read
+ */
+expectUnchanged: FileFingerprint | null, 
+/**
+ * Limit on the hledger run validating the changed journal
+ */
+timeout: Timeout | null, };
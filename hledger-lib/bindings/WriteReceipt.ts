@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where a write put the new text
+ */
+export type WriteReceipt = { 
+/**
+ * The file that was written (symlinks resolved)
+ */
+file: string, 
+/**
+ * Byte offset of the first written byte
+ */
+startOffset: bigint, 
+/**
+ * Byte offset just past the last written byte
+ */
+endOffset: bigint, 
+/**
+ * First line of the written text (1-based)
+ */
+startLine: number, 
+/**
+ * Last line of the written text (1-based, inclusive)
+ */
+endLine: number, };
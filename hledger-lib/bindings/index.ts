@@ -0,0 +1,137 @@
+// This file was generated by the `export_bindings` test in hledger-lib. Do not edit this file manually.
+export type { AccountBalanceNode } from "./AccountBalanceNode";
+export type { AccountInfo } from "./AccountInfo";
+export type { AccountNode } from "./AccountNode";
+export type { AccountTotal } from "./AccountTotal";
+export type { AccountType } from "./AccountType";
+export type { AccountWithBalance } from "./AccountWithBalance";
+export type { AccountsOptions } from "./AccountsOptions";
+export type { Accumulation } from "./Accumulation";
+export type { Amount } from "./Amount";
+export type { AmountOp } from "./AmountOp";
+export type { AmountStyle } from "./AmountStyle";
+export type { AppError } from "./AppError";
+export type { AssertionFailure } from "./AssertionFailure";
+export type { BalanceAccount } from "./BalanceAccount";
+export type { BalanceAssertion } from "./BalanceAssertion";
+export type { BalanceOptions } from "./BalanceOptions";
+export type { BalanceReport } from "./BalanceReport";
+export type { BalanceSheetOptions } from "./BalanceSheetOptions";
+export type { BalanceSheetReport } from "./BalanceSheetReport";
+export type { BalanceSheetSubreport } from "./BalanceSheetSubreport";
+export type { BudgetCell } from "./BudgetCell";
+export type { BudgetOptions } from "./BudgetOptions";
+export type { BudgetReport } from "./BudgetReport";
+export type { BudgetRow } from "./BudgetRow";
+export type { CashflowOptions } from "./CashflowOptions";
+export type { CashflowReport } from "./CashflowReport";
+export type { CashflowSubreport } from "./CashflowSubreport";
+export type { CheckIssue } from "./CheckIssue";
+export type { CheckIssueKind } from "./CheckIssueKind";
+export type { CheckSeverity } from "./CheckSeverity";
+export type { ComparisonRow } from "./ComparisonRow";
+export type { CsvAssignment } from "./CsvAssignment";
+export type { CsvCondition } from "./CsvCondition";
+export type { CsvImportOutcome } from "./CsvImportOutcome";
+export type { CsvMapping } from "./CsvMapping";
+export type { CsvPreview } from "./CsvPreview";
+export type { CsvReport } from "./CsvReport";
+export type { CsvRules } from "./CsvRules";
+export type { DashboardSection } from "./DashboardSection";
+export type { DashboardSummary } from "./DashboardSummary";
+export type { DefaultJournal } from "./DefaultJournal";
+export type { DefaultJournalSource } from "./DefaultJournalSource";
+export type { DetectedHledger } from "./DetectedHledger";
+export type { DigitGroups } from "./DigitGroups";
+export type { DroppedFile } from "./DroppedFile";
+export type { DuplicateCandidate } from "./DuplicateCandidate";
+export type { DuplicateGroup } from "./DuplicateGroup";
+export type { DuplicateTolerance } from "./DuplicateTolerance";
+export type { ErrorCode } from "./ErrorCode";
+export type { ErrorDetails } from "./ErrorDetails";
+export type { ExportFormat } from "./ExportFormat";
+export type { ExportStats } from "./ExportStats";
+export type { FileFingerprint } from "./FileFingerprint";
+export type { FlowGraph } from "./FlowGraph";
+export type { FlowLink } from "./FlowLink";
+export type { FlowNode } from "./FlowNode";
+export type { FlowNodeKind } from "./FlowNodeKind";
+export type { FlowOptions } from "./FlowOptions";
+export type { FormatOptions } from "./FormatOptions";
+export type { HLedgerVersion } from "./HLedgerVersion";
+export type { HealthCode } from "./HealthCode";
+export type { HealthItem } from "./HealthItem";
+export type { HealthReport } from "./HealthReport";
+export type { HealthStatus } from "./HealthStatus";
+export type { HledgerSource } from "./HledgerSource";
+export type { ImportOptions } from "./ImportOptions";
+export type { IncomeStatementOptions } from "./IncomeStatementOptions";
+export type { IncomeStatementReport } from "./IncomeStatementReport";
+export type { IncomeStatementSubreport } from "./IncomeStatementSubreport";
+export type { JournalEncoding } from "./JournalEncoding";
+export type { JournalLayout } from "./JournalLayout";
+export type { JournalMetadata } from "./JournalMetadata";
+export type { JournalProblem } from "./JournalProblem";
+export type { JournalStats } from "./JournalStats";
+export type { JournalStyle } from "./JournalStyle";
+export type { JournalTemplate } from "./JournalTemplate";
+export type { ListMode } from "./ListMode";
+export type { MarketPrice } from "./MarketPrice";
+export type { MonthSummary } from "./MonthSummary";
+export type { MonthTotals } from "./MonthTotals";
+export type { MultiAmount } from "./MultiAmount";
+export type { NetworthOptions } from "./NetworthOptions";
+export type { NetworthPoint } from "./NetworthPoint";
+export type { OpeningBalance } from "./OpeningBalance";
+export type { PageOrder } from "./PageOrder";
+export type { PageSpec } from "./PageSpec";
+export type { PayeeStats } from "./PayeeStats";
+export type { PayeeStatsOptions } from "./PayeeStatsOptions";
+export type { PayeeSummary } from "./PayeeSummary";
+export type { PeriodDate } from "./PeriodDate";
+export type { PeriodicBalance } from "./PeriodicBalance";
+export type { PeriodicBalanceRow } from "./PeriodicBalanceRow";
+export type { PostingAssertion } from "./PostingAssertion";
+export type { PostingBuilder } from "./PostingBuilder";
+export type { Precision } from "./Precision";
+export type { Price } from "./Price";
+export type { PriceKind } from "./PriceKind";
+export type { PricesOptions } from "./PricesOptions";
+export type { PrintAmount } from "./PrintAmount";
+export type { PrintOptions } from "./PrintOptions";
+export type { PrintPage } from "./PrintPage";
+export type { PrintPosting } from "./PrintPosting";
+export type { PrintTransaction } from "./PrintTransaction";
+export type { Query } from "./Query";
+export type { ReportDefaults } from "./ReportDefaults";
+export type { ReportInterval } from "./ReportInterval";
+export type { ReportOutput } from "./ReportOutput";
+export type { ReportRequest } from "./ReportRequest";
+export type { ReportResponse } from "./ReportResponse";
+export type { RunningBalanceEntry } from "./RunningBalanceEntry";
+export type { SearchField } from "./SearchField";
+export type { SearchHighlight } from "./SearchHighlight";
+export type { SearchMatch } from "./SearchMatch";
+export type { SearchResults } from "./SearchResults";
+export type { Series } from "./Series";
+export type { SeriesPoint } from "./SeriesPoint";
+export type { SimpleBalance } from "./SimpleBalance";
+export type { SourcePosition } from "./SourcePosition";
+export type { SourceSpan } from "./SourceSpan";
+export type { Status } from "./Status";
+export type { SuggestionKind } from "./SuggestionKind";
+export type { Suggestions } from "./Suggestions";
+export type { TagUsage } from "./TagUsage";
+export type { TagValueUsage } from "./TagValueUsage";
+export type { TagsOptions } from "./TagsOptions";
+export type { Timeout } from "./Timeout";
+export type { TimeseriesSpec } from "./TimeseriesSpec";
+export type { TopSpendingOptions } from "./TopSpendingOptions";
+export type { TransactionBuilder } from "./TransactionBuilder";
+export type { TransactionSuggestion } from "./TransactionSuggestion";
+export type { TransactionSummary } from "./TransactionSummary";
+export type { UnavailableSection } from "./UnavailableSection";
+export type { UpcomingTransaction } from "./UpcomingTransaction";
+export type { ValueMode } from "./ValueMode";
+export type { WriteOptions } from "./WriteOptions";
+export type { WriteReceipt } from "./WriteReceipt";
@@ -7,7 +7,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Simple Balance ===");
     let options = BalanceOptions::new();
 
-    match get_balance(Some("tests/fixtures/test.journal"), &options) {
+    match get_balance(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => match report {
             BalanceReport::Simple(simple) => {
                 println!("Found {} accounts:", simple.accounts.len());
@@ -30,7 +30,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Monthly Balance ===");
     let options = BalanceOptions::new().monthly();
 
-    match get_balance(Some("tests/fixtures/test.journal"), &options) {
+    match get_balance(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => match report {
             BalanceReport::Simple(_) => {
                 println!("Unexpected simple report for periodic balance");
@@ -53,7 +53,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Tree Mode with Depth ===");
     let options = BalanceOptions::new().tree().depth(2);
 
-    match get_balance(Some("tests/fixtures/test.journal"), &options) {
+    match get_balance(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => match report {
             BalanceReport::Simple(simple) => {
                 println!("Tree mode accounts:");
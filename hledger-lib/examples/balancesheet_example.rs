@@ -7,7 +7,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Simple Balance Sheet ===");
     let options = BalanceSheetOptions::new();
 
-    match get_balancesheet(Some("tests/fixtures/test.journal"), &options) {
+    match get_balancesheet(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => {
             println!("Balance Sheet Title: {}", report.title);
             println!("Found {} periods:", report.dates.len());
@@ -56,7 +56,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Monthly Balance Sheet ===");
     let options = BalanceSheetOptions::new().monthly();
 
-    match get_balancesheet(Some("tests/fixtures/test.journal"), &options) {
+    match get_balancesheet(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => {
             println!("Monthly Balance Sheet Title: {}", report.title);
             println!("Found {} periods:", report.dates.len());
@@ -79,7 +79,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Tree Mode Balance Sheet with Depth ===");
     let options = BalanceSheetOptions::new().tree().depth(2);
 
-    match get_balancesheet(Some("tests/fixtures/test.journal"), &options) {
+    match get_balancesheet(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => {
             println!("Tree mode balance sheet:");
             for subreport in &report.subreports {
@@ -101,7 +101,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Historical Balance Sheet ===");
     let options = BalanceSheetOptions::new().historical();
 
-    match get_balancesheet(Some("tests/fixtures/test.journal"), &options) {
+    match get_balancesheet(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => {
             println!("Historical balance sheet:");
             for subreport in &report.subreports {
@@ -121,7 +121,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Balance Sheet with Query Filter ===");
     let options = BalanceSheetOptions::new().query("assets");
 
-    match get_balancesheet(Some("tests/fixtures/test.journal"), &options) {
+    match get_balancesheet(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(report) => {
             println!("Filtered balance sheet (assets only):");
             for subreport in &report.subreports {
@@ -4,7 +4,7 @@ use std::path::Path;
 fn main() {
     // Basic cashflow statement
     let options = CashflowOptions::new();
-    match get_cashflow(Some(Path::new("test.journal")), options) {
+    match get_cashflow(None, Some(Path::new("test.journal")), options) {
         Ok(report) => {
             println!("Cashflow Report: {}", report.title);
             println!("Number of periods: {}", report.dates.len());
@@ -34,7 +34,7 @@ fn main() {
         .begin("2024-01-01")
         .end("2024-12-31");
 
-    match get_cashflow(Some(Path::new("test.journal")), options) {
+    match get_cashflow(None, Some(Path::new("test.journal")), options) {
         Ok(report) => {
             println!("\n\nMonthly Cashflow Report: {}", report.title);
             // Process the report...
@@ -45,7 +45,7 @@ fn main() {
     // Cashflow with custom query
     let options = CashflowOptions::new().query("bank").empty().row_total();
 
-    match get_cashflow(Some(Path::new("test.journal")), options) {
+    match get_cashflow(None, Some(Path::new("test.journal")), options) {
         Ok(report) => {
             println!("\n\nFiltered Cashflow Report: {}", report.title);
             // Process the report...
@@ -1,12 +1,11 @@
 use hledger_lib::{get_balance, BalanceOptions};
 
 fn main() {
-    let mut options = BalanceOptions::default();
-    options.flat = true;
+    let options = BalanceOptions::new().flat();
 
     println!("Running balance with options: {:?}", options);
 
-    match get_balance(None, &options) {
+    match get_balance(None, None, &options) {
         Ok(balance) => {
             match balance {
                 hledger_lib::BalanceReport::Simple(simple) => {
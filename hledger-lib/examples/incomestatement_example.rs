@@ -4,7 +4,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 1: Simple income statement
     println!("=== Simple Income Statement ===");
     let options = IncomeStatementOptions::new();
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)?;
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)?;
 
     println!("Report: {}", report.title);
     println!(
@@ -12,13 +12,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         report
             .dates
             .first()
-            .map(|d| &d.start)
-            .unwrap_or(&"".to_string()),
+            .map(|d| d.start.to_string())
+            .unwrap_or_default(),
         report
             .dates
             .first()
-            .map(|d| &d.end)
-            .unwrap_or(&"".to_string())
+            .map(|d| d.end.to_string())
+            .unwrap_or_default()
     );
 
     for subreport in &report.subreports {
@@ -43,7 +43,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .row_total()
         .average();
 
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)?;
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)?;
 
     println!("Report: {}", report.title);
 
@@ -67,7 +67,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n\n=== Quarterly Income Statement (Depth 2) ===");
     let options = IncomeStatementOptions::new().quarterly().depth(2).empty();
 
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)?;
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)?;
 
     println!("Report: {}", report.title);
 
@@ -85,7 +85,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .end("2024-01-31")
         .sort_amount();
 
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)?;
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)?;
 
     println!("Report: {}", report.title);
 
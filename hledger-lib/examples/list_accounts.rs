@@ -3,6 +3,7 @@ use hledger_lib::{get_accounts, AccountsOptions};
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Get accounts from the test journal
     let accounts = get_accounts(
+        None,
         Some("tests/fixtures/test.journal"),
         &AccountsOptions::default(),
     )?;
@@ -7,7 +7,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Basic Print ===");
     let options = PrintOptions::new();
 
-    match get_print(Some("tests/fixtures/test.journal"), &options) {
+    match get_print(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(transactions) => {
             println!("Found {} transactions:", transactions.len());
             for txn in &transactions {
@@ -21,29 +21,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 for posting in &txn.postings {
                     print!("    {} ", posting.account);
                     for amount in &posting.amounts {
-                        println!(
-                            "{}{} {}",
-                            if amount.style.commodity_side == "L" {
-                                &amount.commodity
-                            } else {
-                                ""
-                            },
-                            amount.quantity,
-                            if amount.style.commodity_side == "R" {
-                                &amount.commodity
-                            } else {
-                                ""
-                            }
-                        );
-                        if let Some(price) = &amount.price {
-                            println!("      @ {}{}", price.commodity, price.quantity);
-                        }
+                        println!("{}", amount.format());
                     }
                     if let Some(assertion) = &posting.balance_assertion {
-                        println!(
-                            "      = {}{}",
-                            assertion.amount.commodity, assertion.amount.quantity
-                        );
+                        println!("      = {}", assertion.amount.format());
                     }
                 }
             }
@@ -55,7 +36,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Print with Date Range ===");
     let options = PrintOptions::new().begin("2024-01-01").end("2024-01-06");
 
-    match get_print(Some("tests/fixtures/test.journal"), &options) {
+    match get_print(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(transactions) => {
             println!("Found {} transactions in date range:", transactions.len());
             for txn in &transactions {
@@ -69,7 +50,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Print Expense Transactions ===");
     let options = PrintOptions::new().query("expenses");
 
-    match get_print(Some("tests/fixtures/test.journal"), &options) {
+    match get_print(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(transactions) => {
             println!("Found {} expense transactions:", transactions.len());
             for txn in &transactions {
@@ -96,7 +77,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Print with Explicit Amounts ===");
     let options = PrintOptions::new().explicit();
 
-    match get_print(Some("tests/fixtures/test.journal"), &options) {
+    match get_print(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(transactions) => {
             if let Some(txn) = transactions.first() {
                 println!("First transaction with explicit amounts:");
@@ -117,7 +98,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Transaction Details ===");
     let options = PrintOptions::new();
 
-    match get_print(Some("tests/fixtures/test.journal"), &options) {
+    match get_print(None, Some("tests/fixtures/test.journal"), &options) {
         Ok(transactions) => {
             for txn in transactions.iter().take(1) {
                 println!("Transaction #{}", txn.index);
@@ -3,7 +3,7 @@ use hledger_lib::{get_balance, BalanceOptions, BalanceReport};
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Simple balance report
     let options = BalanceOptions::new();
-    let report = get_balance(Some("tests/fixtures/test.journal"), &options)?;
+    let report = get_balance(None, Some("tests/fixtures/test.journal"), &options)?;
 
     match report {
         BalanceReport::Simple(balance) => {
@@ -25,7 +25,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Monthly balance report
     let options = BalanceOptions::new().monthly().row_total().average();
-    let report = get_balance(Some("tests/fixtures/test.journal"), &options)?;
+    let report = get_balance(None, Some("tests/fixtures/test.journal"), &options)?;
 
     match report {
         BalanceReport::Periodic(balance) => {
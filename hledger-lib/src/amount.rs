@@ -0,0 +1,803 @@
+use crate::commands::balance::{Amount, Price, PriceKind};
+use crate::commands::print::{AmountStyle, DigitGroups, Precision, PrintAmount};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use ts_rs::TS;
+
+/// A sum of amounts in possibly several commodities
+///
+/// Quantities are kept per commodity; commodities that net to zero are dropped,
+/// so a `MultiAmount` is zero exactly when it holds no commodities. Prices are
+/// not tracked. Serializes as an object mapping commodity to decimal string,
+/// e.g. `{"$": "120.50", "EUR": "-3"}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct MultiAmount(#[ts(type = "Record<string, string>")] BTreeMap<String, Decimal>);
+
+impl MultiAmount {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a single amount, ignoring its price
+    pub fn add_amount(&mut self, amount: &Amount) {
+        self.add_quantity(&amount.commodity, amount.quantity);
+    }
+
+    /// Add a quantity of the given commodity
+    pub fn add_quantity(&mut self, commodity: &str, quantity: Decimal) {
+        let total = self.get(commodity) + quantity;
+        if total.is_zero() {
+            self.0.remove(commodity);
+        } else {
+            self.0.insert(commodity.to_string(), total);
+        }
+    }
+
+    /// Quantity held in the given commodity (zero if absent)
+    pub fn get(&self, commodity: &str) -> Decimal {
+        self.0.get(commodity).copied().unwrap_or(Decimal::ZERO)
+    }
+
+    /// Flip the sign of every quantity
+    pub fn negate(&self) -> Self {
+        Self(self.0.iter().map(|(c, q)| (c.clone(), -*q)).collect())
+    }
+
+    /// True when every commodity nets to zero
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Commodities and quantities, ordered by commodity
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Decimal)> {
+        self.0.iter().map(|(c, q)| (c.as_str(), *q))
+    }
+
+    /// Convert back to amounts, one per non-zero commodity
+    pub fn to_amounts(&self) -> Vec<Amount> {
+        self.iter()
+            .map(|(commodity, quantity)| Amount {
+                commodity: commodity.to_string(),
+                quantity,
+                price: None,
+            })
+            .collect()
+    }
+}
+
+impl From<&[Amount]> for MultiAmount {
+    fn from(amounts: &[Amount]) -> Self {
+        sum_amounts(amounts)
+    }
+}
+
+impl From<MultiAmount> for Vec<Amount> {
+    fn from(multi: MultiAmount) -> Self {
+        multi.to_amounts()
+    }
+}
+
+impl<'a> FromIterator<&'a Amount> for MultiAmount {
+    fn from_iter<I: IntoIterator<Item = &'a Amount>>(iter: I) -> Self {
+        let mut multi = MultiAmount::new();
+        for amount in iter {
+            multi.add_amount(amount);
+        }
+        multi
+    }
+}
+
+impl AddAssign<&MultiAmount> for MultiAmount {
+    fn add_assign(&mut self, other: &MultiAmount) {
+        for (commodity, quantity) in other.iter() {
+            self.add_quantity(commodity, quantity);
+        }
+    }
+}
+
+impl SubAssign<&MultiAmount> for MultiAmount {
+    fn sub_assign(&mut self, other: &MultiAmount) {
+        for (commodity, quantity) in other.iter() {
+            self.add_quantity(commodity, -quantity);
+        }
+    }
+}
+
+impl Add for MultiAmount {
+    type Output = MultiAmount;
+
+    fn add(mut self, other: MultiAmount) -> MultiAmount {
+        self += &other;
+        self
+    }
+}
+
+impl Sub for MultiAmount {
+    type Output = MultiAmount;
+
+    fn sub(mut self, other: MultiAmount) -> MultiAmount {
+        self -= &other;
+        self
+    }
+}
+
+impl std::iter::Sum for MultiAmount {
+    fn sum<I: Iterator<Item = MultiAmount>>(iter: I) -> Self {
+        iter.fold(MultiAmount::new(), |acc, multi| acc + multi)
+    }
+}
+
+/// Sum amounts per commodity
+pub fn sum_amounts<'a>(amounts: impl IntoIterator<Item = &'a Amount>) -> MultiAmount {
+    amounts.into_iter().collect()
+}
+
+impl PrintAmount {
+    /// Render the amount as hledger would display it, e.g. `$1,234.50` or
+    /// `2 AAPL @ $150`
+    pub fn format(&self) -> String {
+        format_amount(
+            &self.commodity,
+            self.quantity,
+            &self.style,
+            self.price.as_ref(),
+        )
+    }
+}
+
+impl Amount {
+    /// Render the amount as a display string
+    ///
+    /// Balance reports carry no style, so callers can pass one (e.g. taken from
+    /// a print report); otherwise a default style for the commodity is used.
+    pub fn format(&self, style: Option<&AmountStyle>) -> String {
+        let default_style;
+        let style = match style {
+            Some(style) => style,
+            None => {
+                default_style = default_style_for(&self.commodity);
+                &default_style
+            }
+        };
+        format_amount(&self.commodity, self.quantity, style, self.price.as_ref())
+    }
+
+    /// Render the amount briefly, for chart axes and the like: thousands,
+    /// millions and billions are abbreviated (`$1.2k`, `3,5M €`) and at most
+    /// one decimal digit is kept
+    ///
+    /// The style gives the commodity's placement and decimal mark; its
+    /// precision and digit groups are ignored, as is the price.
+    pub fn format_compact(&self, style: Option<&AmountStyle>) -> String {
+        let default_style;
+        let style = match style {
+            Some(style) => style,
+            None => {
+                default_style = default_style_for(&self.commodity);
+                &default_style
+            }
+        };
+
+        let magnitude = self.quantity.abs();
+        let (quantity, suffix) = [
+            (Decimal::from(1_000_000_000), "B"),
+            (Decimal::from(1_000_000), "M"),
+            (Decimal::from(1_000), "k"),
+        ]
+        .into_iter()
+        .find(|(unit, _)| magnitude >= *unit)
+        .map_or((self.quantity, ""), |(unit, suffix)| {
+            (self.quantity / unit, suffix)
+        });
+        let compact_style = AmountStyle {
+            digit_groups: None,
+            precision: Precision::Natural,
+            ..style.clone()
+        };
+        let number = format_quantity(quantity.round_dp(1).normalize(), &compact_style);
+        place_commodity(&self.commodity, &format!("{}{}", number, suffix), style)
+    }
+}
+
+/// Arithmetic on single-commodity amounts
+///
+/// Prices are not part of the arithmetic: unary operations keep the price as
+/// it is, and sums drop it since the price of a combined amount is unknown.
+impl Amount {
+    /// True when the quantity is zero
+    pub fn is_zero(&self) -> bool {
+        self.quantity.is_zero()
+    }
+
+    /// Amount with the absolute quantity
+    pub fn abs(&self) -> Amount {
+        Amount {
+            quantity: self.quantity.abs(),
+            ..self.clone()
+        }
+    }
+
+    /// -1, 0 or 1 depending on the sign of the quantity
+    pub fn signum(&self) -> Decimal {
+        if self.quantity.is_zero() {
+            Decimal::ZERO
+        } else if self.quantity.is_sign_negative() {
+            Decimal::NEGATIVE_ONE
+        } else {
+            Decimal::ONE
+        }
+    }
+
+    /// Sum of two amounts, or `None` if their commodities differ
+    pub fn try_add(&self, other: &Amount) -> Option<Amount> {
+        (self.commodity == other.commodity).then(|| Amount {
+            commodity: self.commodity.clone(),
+            quantity: self.quantity + other.quantity,
+            price: None,
+        })
+    }
+
+    /// Difference of two amounts, or `None` if their commodities differ
+    pub fn try_sub(&self, other: &Amount) -> Option<Amount> {
+        self.try_add(&-other)
+    }
+}
+
+impl Neg for Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Amount {
+        Amount {
+            quantity: -self.quantity,
+            ..self
+        }
+    }
+}
+
+impl Neg for &Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Amount {
+        -self.clone()
+    }
+}
+
+/// Amounts are only ordered within a commodity; amounts in different
+/// commodities, or equal quantities with different prices, are incomparable.
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Amount) -> Option<Ordering> {
+        if self.commodity != other.commodity {
+            return None;
+        }
+        match self.quantity.cmp(&other.quantity) {
+            Ordering::Equal if self.price != other.price => None,
+            ordering => Some(ordering),
+        }
+    }
+}
+
+/// Fallback style when hledger did not report one: symbols such as `$` go on
+/// the left unspaced, alphabetic commodities on the right after a space, and
+/// the quantity is shown with its natural precision.
+pub(crate) fn default_style_for(commodity: &str) -> AmountStyle {
+    let is_word = commodity.chars().any(|c| c.is_alphabetic());
+    AmountStyle {
+        commodity_side: if is_word { "R" } else { "L" }.to_string(),
+        commodity_spaced: is_word,
+        decimal_mark: Some(".".to_string()),
+        digit_groups: None,
+        precision: Precision::Natural,
+        rounding: "NoRounding".to_string(),
+    }
+}
+
+pub(crate) fn format_amount(
+    commodity: &str,
+    quantity: Decimal,
+    style: &AmountStyle,
+    price: Option<&Price>,
+) -> String {
+    let mut out = place_commodity(commodity, &format_quantity(quantity, style), style);
+
+    if let Some(price) = price {
+        let marker = match price.kind {
+            PriceKind::Unit => "@",
+            PriceKind::Total => "@@",
+        };
+        let price_style = default_style_for(&price.commodity);
+        out.push_str(&format!(
+            " {} {}",
+            marker,
+            format_amount(&price.commodity, price.quantity, &price_style, None)
+        ));
+    }
+
+    out
+}
+
+/// The formatted number with the commodity on the style's side
+fn place_commodity(commodity: &str, number: &str, style: &AmountStyle) -> String {
+    let commodity = quote_commodity(commodity);
+    let space = if style.commodity_spaced && !commodity.is_empty() {
+        " "
+    } else {
+        ""
+    };
+
+    if style.commodity_side == "R" {
+        format!("{}{}{}", number, space, commodity)
+    } else {
+        format!("{}{}{}", commodity, space, number)
+    }
+}
+
+/// Parse an amount as hledger displays it in messages, e.g. `$-1,234.50`,
+/// `-50 EUR` or `5 "ACME Inc"`
+///
+/// A number with both `.` and `,` uses the last one as decimal mark. With
+/// only one kind, a mark appearing once is the decimal mark (as hledger
+/// assumes without a commodity directive) and a repeated one groups digits.
+/// A cost after `@` is ignored.
+pub(crate) fn parse_amount_text(text: &str) -> Option<Amount> {
+    let text = text.split(" @").next().unwrap_or(text).trim();
+
+    // Quoted commodities may contain digits and spaces, so take them out first
+    let (quoted, rest) = match text.find('"') {
+        Some(open) => {
+            let close = open + 1 + text[open + 1..].find('"')?;
+            let rest = format!("{}{}", &text[..open], &text[close + 1..]);
+            (Some(text[open + 1..close].to_string()), rest)
+        }
+        None => (None, text.to_string()),
+    };
+
+    let first_digit = rest.find(|c: char| c.is_ascii_digit())?;
+    let last_digit = rest.rfind(|c: char| c.is_ascii_digit())?;
+    let (before, number, after) = (
+        &rest[..first_digit],
+        &rest[first_digit..=last_digit],
+        &rest[last_digit + 1..],
+    );
+    let negative = before.contains('-');
+    let commodity = match quoted {
+        Some(commodity) => commodity,
+        None => format!("{}{}", before.replace(['-', '+'], ""), after)
+            .trim()
+            .to_string(),
+    };
+
+    let digits: String = number
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'')
+        .collect();
+    let decimal_mark = match (digits.rfind('.'), digits.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(if dot > comma { '.' } else { ',' }),
+        (Some(_), None) if digits.matches('.').count() == 1 => Some('.'),
+        (None, Some(_)) if digits.matches(',').count() == 1 => Some(','),
+        _ => None,
+    };
+    let mut normalized: String = digits
+        .chars()
+        .filter(|&c| c.is_ascii_digit() || Some(c) == decimal_mark)
+        .map(|c| if c.is_ascii_digit() { c } else { '.' })
+        .collect();
+    if negative {
+        normalized.insert(0, '-');
+    }
+
+    Some(Amount {
+        commodity,
+        quantity: normalized.parse().ok()?,
+        price: None,
+    })
+}
+
+/// Format a quantity with the style's precision, decimal mark and digit groups
+pub fn format_quantity(quantity: Decimal, style: &AmountStyle) -> String {
+    let quantity = match style.precision {
+        Precision::Digits(digits) => {
+            let mut rounded = quantity.round_dp(digits.into());
+            rounded.rescale(digits.into());
+            rounded
+        }
+        Precision::Natural => quantity,
+    };
+
+    let text = quantity.abs().to_string();
+    let (integer, fraction) = match text.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (text.as_str(), None),
+    };
+
+    let mut out = String::new();
+    if quantity.is_sign_negative() && !quantity.is_zero() {
+        out.push('-');
+    }
+    match &style.digit_groups {
+        Some(groups) => out.push_str(&group_digits(integer, groups)),
+        None => out.push_str(integer),
+    }
+    if let Some(fraction) = fraction {
+        out.push_str(style.decimal_mark.as_deref().unwrap_or("."));
+        out.push_str(fraction);
+    }
+    out
+}
+
+/// Insert group separators into the integer digits, counting from the right
+fn group_digits(digits: &str, groups: &DigitGroups) -> String {
+    let Some(&last_size) = groups.sizes.last() else {
+        return digits.to_string();
+    };
+
+    let chars: Vec<char> = digits.chars().collect();
+    let mut parts: Vec<String> = Vec::new();
+    let mut end = chars.len();
+    let mut sizes = groups.sizes.iter().copied();
+
+    while end > 0 {
+        let size = sizes.next().unwrap_or(last_size).max(1) as usize;
+        let start = end.saturating_sub(size);
+        parts.push(chars[start..end].iter().collect());
+        end = start;
+    }
+
+    parts.reverse();
+    parts.join(&groups.separator.to_string())
+}
+
+/// Quote commodity symbols containing characters hledger would quote
+fn quote_commodity(commodity: &str) -> String {
+    let needs_quotes = commodity
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_ascii_digit() || "-+.,;:@*/=\"'()[]{}".contains(c));
+    if needs_quotes {
+        format!("\"{}\"", commodity)
+    } else {
+        commodity.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(commodity: &str, quantity: Decimal) -> Amount {
+        Amount {
+            commodity: commodity.to_string(),
+            quantity,
+            price: None,
+        }
+    }
+
+    #[test]
+    fn export_bindings() {
+        MultiAmount::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_sum_mixed_commodities() {
+        let amounts = vec![
+            amount("$", Decimal::new(10050, 2)),
+            amount("EUR", Decimal::new(20, 0)),
+            amount("$", Decimal::new(-50, 2)),
+            amount("GOOG", Decimal::new(2, 0)),
+        ];
+
+        let total = sum_amounts(&amounts);
+        assert_eq!(total.get("$"), Decimal::new(100, 0));
+        assert_eq!(total.get("EUR"), Decimal::new(20, 0));
+        assert_eq!(total.get("GOOG"), Decimal::new(2, 0));
+        assert_eq!(total.get("CHF"), Decimal::ZERO);
+
+        let commodities: Vec<&str> = total.iter().map(|(c, _)| c).collect();
+        assert_eq!(commodities, vec!["$", "EUR", "GOOG"]);
+    }
+
+    #[test]
+    fn test_zero_elision() {
+        let amounts = vec![
+            amount("$", Decimal::new(20, 0)),
+            amount("EUR", Decimal::new(5, 0)),
+            amount("$", Decimal::new(-2000, 2)),
+        ];
+
+        let total = MultiAmount::from(amounts.as_slice());
+        assert!(!total.is_zero());
+        let back: Vec<Amount> = total.into();
+        assert_eq!(back.len(), 1);
+        assert_eq!(back[0].commodity, "EUR");
+
+        let empty = sum_amounts(&[]);
+        assert!(empty.is_zero());
+        assert!(empty.to_amounts().is_empty());
+    }
+
+    #[test]
+    fn test_add_sub_negate() {
+        let a = sum_amounts(&[
+            amount("$", Decimal::new(30, 0)),
+            amount("EUR", Decimal::new(1, 0)),
+        ]);
+        let b = sum_amounts(&[amount("$", Decimal::new(10, 0))]);
+
+        let sum = a.clone() + b.clone();
+        assert_eq!(sum.get("$"), Decimal::new(40, 0));
+        assert_eq!(sum.get("EUR"), Decimal::new(1, 0));
+
+        let diff = a.clone() - b;
+        assert_eq!(diff.get("$"), Decimal::new(20, 0));
+
+        let negated = a.negate();
+        assert_eq!(negated.get("$"), Decimal::new(-30, 0));
+        assert_eq!(negated.get("EUR"), Decimal::new(-1, 0));
+        assert!((a.clone() + negated).is_zero());
+        assert!((a.clone() - a).is_zero());
+    }
+
+    #[test]
+    fn test_sum_of_multi_amounts() {
+        let rows = vec![
+            sum_amounts(&[amount("$", Decimal::new(1, 0))]),
+            sum_amounts(&[
+                amount("$", Decimal::new(2, 0)),
+                amount("EUR", Decimal::new(3, 0)),
+            ]),
+        ];
+        let total: MultiAmount = rows.into_iter().sum();
+        assert_eq!(total.get("$"), Decimal::new(3, 0));
+        assert_eq!(total.get("EUR"), Decimal::new(3, 0));
+    }
+
+    fn style(
+        side: &str,
+        spaced: bool,
+        mark: &str,
+        groups: Option<(char, Vec<u8>)>,
+        precision: Precision,
+    ) -> AmountStyle {
+        AmountStyle {
+            commodity_side: side.to_string(),
+            commodity_spaced: spaced,
+            decimal_mark: Some(mark.to_string()),
+            digit_groups: groups.map(|(separator, sizes)| DigitGroups { separator, sizes }),
+            precision,
+            rounding: "NoRounding".to_string(),
+        }
+    }
+
+    fn print_amount(commodity: &str, quantity: Decimal, style: AmountStyle) -> PrintAmount {
+        PrintAmount {
+            commodity: commodity.to_string(),
+            quantity,
+            price: None,
+            style,
+        }
+    }
+
+    #[test]
+    fn test_format_left_commodity_with_groups() {
+        let amount = print_amount(
+            "$",
+            Decimal::new(12345, 1),
+            style("L", false, ".", Some((',', vec![3])), Precision::Digits(2)),
+        );
+        assert_eq!(amount.format(), "$1,234.50");
+
+        let amount = print_amount(
+            "$",
+            Decimal::new(-30050, 2),
+            style("L", false, ".", None, Precision::Digits(2)),
+        );
+        assert_eq!(amount.format(), "$-300.50");
+    }
+
+    #[test]
+    fn test_format_right_commodity_spaced() {
+        let amount = print_amount(
+            "kr",
+            Decimal::new(123450, 2),
+            style("R", true, ",", Some((' ', vec![3])), Precision::Digits(2)),
+        );
+        assert_eq!(amount.format(), "1 234,50 kr");
+    }
+
+    #[test]
+    fn test_format_indian_grouping() {
+        let amount = print_amount(
+            "INR",
+            Decimal::new(100000000, 2),
+            style(
+                "L",
+                true,
+                ".",
+                Some((',', vec![3, 2])),
+                Precision::Digits(2),
+            ),
+        );
+        assert_eq!(amount.format(), "INR 10,00,000.00");
+    }
+
+    #[test]
+    fn test_format_natural_precision() {
+        let amount = print_amount(
+            "BTC",
+            Decimal::new(123456, 6),
+            style("R", true, ".", None, Precision::Natural),
+        );
+        assert_eq!(amount.format(), "0.123456 BTC");
+
+        // Fixed precision rounds and pads instead
+        let amount = print_amount(
+            "BTC",
+            Decimal::new(123456, 6),
+            style("R", true, ".", None, Precision::Digits(3)),
+        );
+        assert_eq!(amount.format(), "0.123 BTC");
+    }
+
+    #[test]
+    fn test_format_with_prices() {
+        let mut amount = print_amount(
+            "AAPL",
+            Decimal::new(2, 0),
+            style("R", true, ".", None, Precision::Digits(0)),
+        );
+        amount.price = Some(Price {
+            kind: PriceKind::Unit,
+            commodity: "$".to_string(),
+            quantity: Decimal::new(150, 0),
+        });
+        assert_eq!(amount.format(), "2 AAPL @ $150");
+
+        amount.price = Some(Price {
+            kind: PriceKind::Total,
+            commodity: "USD".to_string(),
+            quantity: Decimal::new(300, 0),
+        });
+        assert_eq!(amount.format(), "2 AAPL @@ 300 USD");
+    }
+
+    #[test]
+    fn test_format_balance_amount() {
+        let usd = amount("$", Decimal::new(2050, 2));
+        assert_eq!(usd.format(None), "$20.50");
+
+        let goog = amount("GOOG", Decimal::new(2, 0));
+        assert_eq!(goog.format(None), "2 GOOG");
+
+        let styled = style("L", false, ".", Some((',', vec![3])), Precision::Digits(2));
+        let big = amount("$", Decimal::new(1234567, 0));
+        assert_eq!(big.format(Some(&styled)), "$1,234,567.00");
+
+        let quoted = amount("AAPL 2024", Decimal::new(1, 0));
+        assert_eq!(quoted.format(None), "1 \"AAPL 2024\"");
+    }
+
+    #[test]
+    fn test_format_compact() {
+        let compact = |commodity, quantity: &str, style: Option<&AmountStyle>| {
+            amount(commodity, quantity.parse().unwrap()).format_compact(style)
+        };
+        assert_eq!(compact("$", "1234.50", None), "$1.2k");
+        assert_eq!(compact("$", "-12000", None), "$-12k");
+        assert_eq!(compact("$", "999.96", None), "$1000");
+        assert_eq!(compact("$", "2500000", None), "$2.5M");
+        assert_eq!(compact("$", "7000000000", None), "$7B");
+        assert_eq!(compact("$", "0.25", None), "$0.2");
+        assert_eq!(compact("USD", "1500", None), "1.5k USD");
+
+        let euro = style("R", true, ",", Some((' ', vec![3])), Precision::Digits(2));
+        assert_eq!(compact("€", "3456789.10", Some(&euro)), "3,5M €");
+    }
+
+    #[test]
+    fn test_amount_neg_abs_signum() {
+        let loss = amount("$", Decimal::new(-30050, 2));
+        assert_eq!((-&loss).quantity, Decimal::new(30050, 2));
+        assert_eq!((-loss.clone()).commodity, "$");
+        assert_eq!(loss.abs().quantity, Decimal::new(30050, 2));
+        assert_eq!(loss.signum(), Decimal::NEGATIVE_ONE);
+        assert_eq!(loss.abs().signum(), Decimal::ONE);
+
+        let zero = amount("$", Decimal::new(0, 2));
+        assert!(zero.is_zero());
+        assert_eq!(zero.signum(), Decimal::ZERO);
+        assert!((-&zero).is_zero());
+        assert!(!loss.is_zero());
+    }
+
+    #[test]
+    fn test_amount_neg_keeps_price() {
+        let mut shares = amount("AAPL", Decimal::new(2, 0));
+        shares.price = Some(Price {
+            kind: PriceKind::Unit,
+            commodity: "$".to_string(),
+            quantity: Decimal::new(150, 0),
+        });
+        let sold = -&shares;
+        assert_eq!(sold.quantity, Decimal::new(-2, 0));
+        assert_eq!(sold.price, shares.price);
+    }
+
+    #[test]
+    fn test_amount_try_add() {
+        let a = amount("$", Decimal::new(1050, 2));
+        let b = amount("$", Decimal::new(-2, 0));
+        let eur = amount("EUR", Decimal::new(5, 0));
+
+        let sum = a.try_add(&b).unwrap();
+        assert_eq!(sum.quantity, Decimal::new(850, 2));
+        assert_eq!(sum.commodity, "$");
+        assert!(a.try_add(&eur).is_none());
+
+        let delta = a.try_sub(&b).unwrap();
+        assert_eq!(delta.quantity, Decimal::new(1250, 2));
+        assert!(a.try_sub(&eur).is_none());
+
+        // Prices never survive a sum
+        let mut priced = amount("$", Decimal::new(1, 0));
+        priced.price = Some(Price {
+            kind: PriceKind::Total,
+            commodity: "EUR".to_string(),
+            quantity: Decimal::new(1, 0),
+        });
+        assert_eq!(priced.try_add(&a).unwrap().price, None);
+    }
+
+    #[test]
+    fn test_amount_ordering() {
+        let small = amount("$", Decimal::new(-5, 0));
+        let large = amount("$", Decimal::new(20, 0));
+        assert!(small < large);
+        assert!(large > small);
+
+        // Differing precisions compare by value
+        let a = amount("$", Decimal::new(150, 2));
+        let b = amount("$", Decimal::new(15, 1));
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
+        assert_eq!(a, b);
+
+        // Different commodities are incomparable
+        let eur = amount("EUR", Decimal::new(1, 0));
+        assert_eq!(large.partial_cmp(&eur), None);
+    }
+
+    #[test]
+    fn test_serialized_form() {
+        let total = sum_amounts(&[
+            amount("$", Decimal::new(12050, 2)),
+            amount("EUR", Decimal::new(-3, 0)),
+        ]);
+        let json = serde_json::to_value(&total).unwrap();
+        assert_eq!(json, serde_json::json!({"$": "120.50", "EUR": "-3"}));
+
+        let roundtrip: MultiAmount = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, total);
+    }
+
+    #[test]
+    fn test_parse_amount_text() {
+        let parse = |text| parse_amount_text(text).unwrap();
+        assert_eq!(parse("$100"), amount("$", Decimal::new(100, 0)));
+        assert_eq!(parse("$-1,234.50"), amount("$", Decimal::new(-123450, 2)));
+        assert_eq!(parse("-$12"), amount("$", Decimal::new(-12, 0)));
+        assert_eq!(parse("-50 EUR"), amount("EUR", Decimal::new(-50, 0)));
+        assert_eq!(
+            parse("1.234,56 EUR"),
+            amount("EUR", Decimal::new(123456, 2))
+        );
+        assert_eq!(
+            parse("1 000 000 SEK"),
+            amount("SEK", Decimal::new(1000000, 0))
+        );
+        assert_eq!(parse("0"), amount("", Decimal::ZERO));
+        assert_eq!(parse("5 \"ACME 2\""), amount("ACME 2", Decimal::new(5, 0)));
+        assert_eq!(parse("2 AAPL @ $150"), amount("AAPL", Decimal::new(2, 0)));
+        assert!(parse_amount_text("$").is_none());
+    }
+}
@@ -0,0 +1,371 @@
+use super::Backend;
+use crate::commands::accounts::AccountsOptions;
+use crate::commands::balance::{BalanceOptions, BalanceReport};
+use crate::commands::balancesheet::{BalanceSheetOptions, BalanceSheetReport};
+use crate::commands::cashflow::{CashflowOptions, CashflowReport};
+use crate::commands::incomestatement::{IncomeStatementOptions, IncomeStatementReport};
+use crate::commands::json::{from_value, JsonAmount};
+use crate::commands::prices::{MarketPrice, PricesOptions};
+use crate::commands::print::{parse_print_reader, PrintOptions, PrintReport};
+use crate::timeout::Timeout;
+use crate::{HLedgerError, Result};
+use chrono::NaiveDate;
+use reqwest::blocking::{Client, Response};
+use std::time::Duration;
+
+/// Name used for this backend in `UnsupportedByBackend` errors
+const BACKEND_NAME: &str = "hledger-web";
+
+/// Credentials sent with every request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpAuth {
+    /// HTTP basic authentication
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    /// An `Authorization: Bearer` token
+    Bearer(String),
+}
+
+/// Reads a journal served by `hledger-web --serve-api`
+///
+/// hledger-web's JSON API only lists account names, transactions and market
+/// prices, all of them unfiltered. Those are mapped to the same structs the
+/// subprocess backend returns; account depth is applied locally. Options that
+/// would need hledger to filter (queries, dates, status) and every other
+/// report fail with `UnsupportedByBackend`.
+#[derive(Debug, Clone)]
+pub struct HttpBackend {
+    /// Where hledger-web is listening, e.g. `http://localhost:5000`
+    pub base_url: String,
+    pub auth: Option<HttpAuth>,
+    client: Client,
+}
+
+// Implementation for builder pattern
+impl HttpBackend {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            auth: None,
+            client: Client::new(),
+        }
+    }
+
+    pub fn auth(mut self, auth: HttpAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// GET an API path, failing on transport errors and non-2xx responses
+    fn get(&self, path: &str, timeout: Option<Timeout>) -> Result<Response> {
+        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), path);
+        let timeout = timeout.map(Duration::from);
+        let mut request = self.client.get(&url);
+        match &self.auth {
+            Some(HttpAuth::Basic { username, password }) => {
+                request = request.basic_auth(username, password.as_ref());
+            }
+            Some(HttpAuth::Bearer(token)) => request = request.bearer_auth(token),
+            None => {}
+        }
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+
+        let response = request.send().map_err(|e| match timeout {
+            Some(elapsed) if e.is_timeout() => HLedgerError::Timeout { elapsed },
+            _ => HLedgerError::Http {
+                url: url.clone(),
+                message: e.to_string(),
+            },
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(HLedgerError::Http {
+                url,
+                message: format!("server responded with {}", status),
+            });
+        }
+        Ok(response)
+    }
+}
+
+impl Backend for HttpBackend {
+    fn get_accounts(&self, options: &AccountsOptions) -> Result<Vec<String>> {
+        let filtered = options.used
+            || options.declared
+            || options.unused
+            || options.undeclared
+            || options.types
+            || options.positions
+            || options.directives
+            || options.find.is_some()
+            || options.drop.is_some()
+            || options.begin.is_some()
+            || options.end.is_some()
+            || options.period.is_some()
+            || options.unmarked
+            || options.pending
+            || options.cleared
+            || options.real
+            || options.empty
+            || !options.queries.is_empty();
+        if filtered {
+            return Err(unsupported("filtering account names"));
+        }
+
+        let names: Vec<String> =
+            serde_json::from_reader(self.get("accountnames", options.timeout)?)?;
+        Ok(match options.depth {
+            Some(depth) => clip_accounts(names, depth),
+            None => names,
+        })
+    }
+
+    fn get_print(&self, options: &PrintOptions) -> Result<PrintReport> {
+        let filtered = options.round.is_some()
+            || options.new
+            || options.match_desc.is_some()
+            || options.begin.is_some()
+            || options.end.is_some()
+            || options.unmarked
+            || options.pending
+            || options.cleared
+            || options.real
+            || options.empty
+            || !options.queries.is_empty();
+        if filtered {
+            return Err(unsupported("filtering transactions"));
+        }
+
+        parse_print_reader(self.get("transactions", options.timeout)?)
+    }
+
+    fn get_prices(&self, options: &PricesOptions) -> Result<Vec<MarketPrice>> {
+        if options.infer_market_prices {
+            return Err(unsupported("inferring market prices"));
+        }
+        if options.begin.is_some() || options.end.is_some() || !options.queries.is_empty() {
+            return Err(unsupported("filtering market prices"));
+        }
+
+        let value: serde_json::Value =
+            serde_json::from_reader(self.get("prices", options.timeout)?)?;
+        value
+            .as_array()
+            .ok_or_else(|| HLedgerError::ParseError("Prices should be an array".to_string()))?
+            .iter()
+            .map(parse_price_directive)
+            .collect()
+    }
+
+    fn get_balance(&self, _options: &BalanceOptions) -> Result<BalanceReport> {
+        Err(unsupported("the balance report"))
+    }
+
+    fn get_balancesheet(&self, _options: &BalanceSheetOptions) -> Result<BalanceSheetReport> {
+        Err(unsupported("the balance sheet"))
+    }
+
+    fn get_incomestatement(
+        &self,
+        _options: &IncomeStatementOptions,
+    ) -> Result<IncomeStatementReport> {
+        Err(unsupported("the income statement"))
+    }
+
+    fn get_cashflow(&self, _options: &CashflowOptions) -> Result<CashflowReport> {
+        Err(unsupported("the cashflow report"))
+    }
+}
+
+fn unsupported(operation: &str) -> HLedgerError {
+    HLedgerError::UnsupportedByBackend {
+        backend: BACKEND_NAME.to_string(),
+        operation: operation.to_string(),
+    }
+}
+
+/// Account names cut to `depth` parts, keeping the first of each
+fn clip_accounts(names: Vec<String>, depth: u32) -> Vec<String> {
+    let mut clipped: Vec<String> = Vec::with_capacity(names.len());
+    for name in names {
+        let name: String = name
+            .split(':')
+            .take(depth as usize)
+            .collect::<Vec<_>>()
+            .join(":");
+        if !clipped.contains(&name) {
+            clipped.push(name);
+        }
+    }
+    clipped
+}
+
+/// Parse a `{"pddate", "pdcommodity", "pdamount"}` price directive
+fn parse_price_directive(value: &serde_json::Value) -> Result<MarketPrice> {
+    let field = |name: &str| {
+        value
+            .get(name)
+            .ok_or_else(|| HLedgerError::ParseError(format!("Price directive is missing {}", name)))
+    };
+
+    let date = field("pddate")?
+        .as_str()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .ok_or_else(|| HLedgerError::ParseError("Invalid price date".to_string()))?;
+    let commodity = field("pdcommodity")?
+        .as_str()
+        .ok_or_else(|| HLedgerError::ParseError("Invalid price commodity".to_string()))?
+        .to_string();
+    let price = from_value::<JsonAmount>(field("pdamount")?)?.into_amount()?;
+
+    Ok(MarketPrice {
+        date,
+        commodity,
+        price,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serve one canned JSON response and hand back the request it got
+    fn serve_once(body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_accounts_over_http() {
+        let (url, server) =
+            serve_once(r#"["assets:bank:checking","assets:bank:savings","expenses:food"]"#);
+        let backend = HttpBackend::new(url).auth(HttpAuth::Bearer("secret".to_string()));
+
+        let names = backend
+            .get_accounts(&AccountsOptions::new().depth(2))
+            .unwrap();
+        assert_eq!(names, vec!["assets:bank", "expenses:food"]);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /accountnames "));
+        assert!(request.contains("authorization: Bearer secret"));
+    }
+
+    #[test]
+    fn test_error_status_is_reported() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            stream
+                .write_all(
+                    b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+                .unwrap();
+        });
+
+        let err = HttpBackend::new(url)
+            .get_print(&PrintOptions::new())
+            .unwrap_err();
+        server.join().unwrap();
+        match err {
+            HLedgerError::Http { url, message } => {
+                assert!(url.ends_with("/transactions"));
+                assert!(message.contains("401"));
+            }
+            other => panic!("expected Http error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unsupported_requests_fail_without_connecting() {
+        // Nothing listens here; these must fail before making a request
+        let backend = HttpBackend::new("http://127.0.0.1:9");
+
+        for err in [
+            backend.get_balance(&BalanceOptions::new()).unwrap_err(),
+            backend
+                .get_print(&PrintOptions::new().query("expenses"))
+                .unwrap_err(),
+            backend
+                .get_accounts(&AccountsOptions::new().begin("2024-01-01"))
+                .unwrap_err(),
+            backend
+                .get_prices(&PricesOptions::new().infer_market_prices())
+                .unwrap_err(),
+        ] {
+            assert!(
+                matches!(&err, HLedgerError::UnsupportedByBackend { backend, .. } if backend == "hledger-web"),
+                "unexpected error: {:?}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_price_directive() {
+        let value = serde_json::json!({
+            "pddate": "2024-01-15",
+            "pdcommodity": "GOOG",
+            "pdamount": {
+                "acommodity": "$",
+                "aquantity": {"decimalMantissa": 15000, "decimalPlaces": 2, "floatingPoint": 150},
+                "aprice": null,
+                "astyle": {},
+            },
+        });
+        let price = parse_price_directive(&value).unwrap();
+        assert_eq!(price.date, NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+        assert_eq!(price.commodity, "GOOG");
+        assert_eq!(price.price.commodity, "$");
+        assert_eq!(price.price.quantity, Decimal::new(15000, 2));
+
+        assert!(parse_price_directive(&serde_json::json!({"pddate": "2024-01-15"})).is_err());
+    }
+
+    #[test]
+    fn test_clip_accounts() {
+        let names = vec![
+            "assets".to_string(),
+            "assets:bank:checking".to_string(),
+            "assets:cash".to_string(),
+            "expenses:food:groceries".to_string(),
+        ];
+        assert_eq!(clip_accounts(names.clone(), 1), vec!["assets", "expenses"]);
+        assert_eq!(
+            clip_accounts(names, 2),
+            vec!["assets", "assets:bank", "assets:cash", "expenses:food"]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Where reports come from: a local `hledger` binary, or (with the `http`
+//! feature) a `hledger-web` server
+
+#[cfg(feature = "http")]
+mod http;
+
+#[cfg(feature = "http")]
+pub use http::{HttpAuth, HttpBackend};
+
+use crate::commands::accounts::{get_accounts, AccountsOptions};
+use crate::commands::balance::{get_balance, BalanceOptions, BalanceReport};
+use crate::commands::balancesheet::{get_balancesheet, BalanceSheetOptions, BalanceSheetReport};
+use crate::commands::cashflow::{get_cashflow, CashflowOptions, CashflowReport};
+use crate::commands::incomestatement::{
+    get_incomestatement, IncomeStatementOptions, IncomeStatementReport,
+};
+use crate::commands::prices::{get_prices, MarketPrice, PricesOptions};
+use crate::commands::print::{get_print, PrintOptions, PrintReport};
+use crate::Result;
+use std::path::Path;
+
+/// A source of hledger data
+///
+/// Every method returns the same typed structs as the free functions of the
+/// same name. A backend that can't produce a report, or can't apply some of
+/// the options, fails with `HLedgerError::UnsupportedByBackend`.
+pub trait Backend {
+    fn get_accounts(&self, options: &AccountsOptions) -> Result<Vec<String>>;
+    fn get_print(&self, options: &PrintOptions) -> Result<PrintReport>;
+    fn get_prices(&self, options: &PricesOptions) -> Result<Vec<MarketPrice>>;
+    fn get_balance(&self, options: &BalanceOptions) -> Result<BalanceReport>;
+    fn get_balancesheet(&self, options: &BalanceSheetOptions) -> Result<BalanceSheetReport>;
+    fn get_incomestatement(
+        &self,
+        options: &IncomeStatementOptions,
+    ) -> Result<IncomeStatementReport>;
+    fn get_cashflow(&self, options: &CashflowOptions) -> Result<CashflowReport>;
+}
+
+/// Runs a local `hledger` binary for each request, as the free functions do
+#[derive(Debug, Default, Clone)]
+pub struct SubprocessBackend {
+    /// The binary to run; `hledger` on PATH when `None`
+    pub hledger_path: Option<String>,
+    /// The journal to read; hledger's default (`LEDGER_FILE`) when `None`
+    pub journal_file: Option<String>,
+}
+
+// Implementation for builder pattern
+impl SubprocessBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn hledger_path(mut self, path: impl Into<String>) -> Self {
+        self.hledger_path = Some(path.into());
+        self
+    }
+
+    pub fn journal_file(mut self, file: impl Into<String>) -> Self {
+        self.journal_file = Some(file.into());
+        self
+    }
+}
+
+impl Backend for SubprocessBackend {
+    fn get_accounts(&self, options: &AccountsOptions) -> Result<Vec<String>> {
+        get_accounts(
+            self.hledger_path.as_deref(),
+            self.journal_file.as_deref(),
+            options,
+        )
+    }
+
+    fn get_print(&self, options: &PrintOptions) -> Result<PrintReport> {
+        get_print(
+            self.hledger_path.as_deref(),
+            self.journal_file.as_deref(),
+            options,
+        )
+    }
+
+    fn get_prices(&self, options: &PricesOptions) -> Result<Vec<MarketPrice>> {
+        get_prices(
+            self.hledger_path.as_deref(),
+            self.journal_file.as_deref(),
+            options,
+        )
+    }
+
+    fn get_balance(&self, options: &BalanceOptions) -> Result<BalanceReport> {
+        get_balance(
+            self.hledger_path.as_deref(),
+            self.journal_file.as_deref(),
+            options,
+        )
+    }
+
+    fn get_balancesheet(&self, options: &BalanceSheetOptions) -> Result<BalanceSheetReport> {
+        get_balancesheet(
+            self.hledger_path.as_deref(),
+            self.journal_file.as_deref(),
+            options,
+        )
+    }
+
+    fn get_incomestatement(
+        &self,
+        options: &IncomeStatementOptions,
+    ) -> Result<IncomeStatementReport> {
+        get_incomestatement(
+            self.hledger_path.as_deref(),
+            self.journal_file.as_deref(),
+            options,
+        )
+    }
+
+    fn get_cashflow(&self, options: &CashflowOptions) -> Result<CashflowReport> {
+        get_cashflow(
+            self.hledger_path.as_deref(),
+            self.journal_file.as_deref().map(Path::new),
+            options.clone(),
+        )
+    }
+}
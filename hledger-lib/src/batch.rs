@@ -0,0 +1,362 @@
+//! Running several reports against one journal at once, e.g. to load a
+//! dashboard without waiting for each hledger run in turn
+
+use crate::cancel::{current_token, with_cancel_token, CancelToken};
+use crate::commands::accounts::{get_accounts, AccountsOptions};
+use crate::commands::balance::{get_balance, BalanceOptions, BalanceReport};
+use crate::commands::balancesheet::{get_balancesheet, BalanceSheetOptions, BalanceSheetReport};
+use crate::commands::budget::{get_budget_report, BudgetOptions, BudgetReport};
+use crate::commands::cashflow::{get_cashflow, CashflowOptions, CashflowReport};
+use crate::commands::incomestatement::{
+    get_incomestatement, IncomeStatementOptions, IncomeStatementReport,
+};
+use crate::commands::prices::{get_prices, MarketPrice, PricesOptions};
+use crate::commands::print::{get_print, PrintOptions, PrintReport};
+use crate::warnings::{collect_warnings, warn};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use ts_rs::TS;
+
+/// One report to run, with its options
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type", content = "value")]
+pub enum ReportRequest {
+    Accounts(AccountsOptions),
+    Balance(BalanceOptions),
+    BalanceSheet(BalanceSheetOptions),
+    IncomeStatement(IncomeStatementOptions),
+    Cashflow(CashflowOptions),
+    Print(PrintOptions),
+    Prices(PricesOptions),
+    Budget(BudgetOptions),
+}
+
+/// The report a [`ReportRequest`] produced, of the same kind
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type", content = "value")]
+pub enum ReportResponse {
+    Accounts(Vec<String>),
+    Balance(BalanceReport),
+    BalanceSheet(BalanceSheetReport),
+    IncomeStatement(IncomeStatementReport),
+    Cashflow(CashflowReport),
+    Print(PrintReport),
+    Prices(Vec<MarketPrice>),
+    Budget(BudgetReport),
+}
+
+impl ReportRequest {
+    /// Run the report, as its free function would
+    pub fn run(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+    ) -> Result<ReportResponse> {
+        Ok(match self {
+            Self::Accounts(options) => {
+                ReportResponse::Accounts(get_accounts(hledger_path, journal_file, options)?)
+            }
+            Self::Balance(options) => {
+                ReportResponse::Balance(get_balance(hledger_path, journal_file, options)?)
+            }
+            Self::BalanceSheet(options) => {
+                ReportResponse::BalanceSheet(get_balancesheet(hledger_path, journal_file, options)?)
+            }
+            Self::IncomeStatement(options) => ReportResponse::IncomeStatement(get_incomestatement(
+                hledger_path,
+                journal_file,
+                options,
+            )?),
+            Self::Cashflow(options) => ReportResponse::Cashflow(get_cashflow(
+                hledger_path,
+                journal_file.map(Path::new),
+                options.clone(),
+            )?),
+            Self::Print(options) => {
+                ReportResponse::Print(get_print(hledger_path, journal_file, options)?)
+            }
+            Self::Prices(options) => {
+                ReportResponse::Prices(get_prices(hledger_path, journal_file, options)?)
+            }
+            Self::Budget(options) => {
+                ReportResponse::Budget(get_budget_report(hledger_path, journal_file, options)?)
+            }
+        })
+    }
+}
+
+/// How many hledger runs to have going at once by default: one per core,
+/// but at least two
+pub fn default_parallelism() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .max(2)
+}
+
+/// Run every request against the same journal, [`default_parallelism`] at a
+/// time, returning the results in request order
+///
+/// A failing report doesn't stop the others.
+pub fn run_reports(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    requests: Vec<ReportRequest>,
+) -> Vec<Result<ReportResponse>> {
+    run_reports_with_parallelism(hledger_path, journal_file, requests, default_parallelism())
+}
+
+/// [`run_reports`] with at most `parallelism` hledger runs at once (at least
+/// one)
+pub fn run_reports_with_parallelism(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    requests: Vec<ReportRequest>,
+    parallelism: usize,
+) -> Vec<Result<ReportResponse>> {
+    run_all(requests, parallelism, |request| {
+        request.run(hledger_path, journal_file)
+    })
+}
+
+/// Apply `f` to every item on up to `parallelism` scoped threads, keeping
+/// the results in item order
+///
+/// Warnings raised on the worker threads are raised again on the calling
+/// thread, in item order, so its collector sees them, and the workers watch
+/// the caller's cancel token.
+fn run_all<T, R, F>(items: Vec<T>, parallelism: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let count = items.len();
+    let workers = parallelism.max(1).min(count);
+    if workers <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    // Workers take the next unclaimed item until none are left
+    let items: Vec<Mutex<Option<T>>> = items.into_iter().map(|i| Mutex::new(Some(i))).collect();
+    // Each result with the warnings raised producing it
+    type Slot<R> = Mutex<Option<(R, Vec<String>)>>;
+    let results: Vec<Slot<R>> = (0..count).map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+    let token = current_token();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= count {
+                    break;
+                }
+                let item = items[i].lock().unwrap().take().unwrap();
+                let result = collect_warnings(|| watching(token.as_ref(), || f(item)));
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    results
+        .into_iter()
+        .map(|result| {
+            let (result, warnings) = result.into_inner().unwrap().unwrap();
+            warnings.into_iter().for_each(warn);
+            result
+        })
+        .collect()
+}
+
+/// Run `f` under `token`, if there is one
+fn watching<R>(token: Option<&CancelToken>, f: impl FnOnce() -> R) -> R {
+    match token {
+        Some(token) => with_cancel_token(token, f),
+        None => f(),
+    }
+}
+
+/// [`run_reports`] for async callers: each hledger run goes on tokio's
+/// blocking pool, at most `parallelism` at once (at least one)
+///
+/// Must be awaited inside a tokio runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_reports_async(
+    hledger_path: Option<String>,
+    journal_file: Option<String>,
+    requests: Vec<ReportRequest>,
+    parallelism: usize,
+) -> Vec<Result<ReportResponse>> {
+    let hledger_path = std::sync::Arc::new(hledger_path);
+    let journal_file = std::sync::Arc::new(journal_file);
+    run_all_async(requests, parallelism, move |request| {
+        request.run(hledger_path.as_deref(), journal_file.as_deref())
+    })
+    .await
+}
+
+/// Async counterpart of [`run_all`]; a panic in `f` is resumed in the caller
+#[cfg(feature = "tokio")]
+async fn run_all_async<T, R, F>(items: Vec<T>, parallelism: usize, f: F) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    let permits = Arc::new(Semaphore::new(parallelism.max(1)));
+    let f = Arc::new(f);
+    let handles: Vec<_> = items
+        .into_iter()
+        .map(|item| {
+            let permits = permits.clone();
+            let f = f.clone();
+            tokio::spawn(async move {
+                let _permit = permits.acquire_owned().await.unwrap();
+                tokio::task::spawn_blocking(move || f(item)).await
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        match handle.await {
+            Ok(Ok(result)) => results.push(result),
+            Ok(Err(e)) | Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HLedgerError;
+    use std::time::Duration;
+
+    #[test]
+    fn export_bindings() {
+        ReportRequest::export_all().unwrap();
+        ReportResponse::export_all().unwrap();
+    }
+
+    /// Records how many calls overlap at most
+    #[derive(Default)]
+    struct Overlap {
+        running: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    impl Overlap {
+        fn run(&self, n: u64) -> u64 {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(now, Ordering::SeqCst);
+            // Later items finish sooner, so completion order differs from
+            // item order
+            thread::sleep(Duration::from_millis(40 - n * 5));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            n * 10
+        }
+    }
+
+    #[test]
+    fn test_run_all_bounds_parallelism_and_keeps_order() {
+        let overlap = Overlap::default();
+        let results = run_all((0..6).collect(), 3, |n| overlap.run(n));
+        assert_eq!(results, vec![0, 10, 20, 30, 40, 50]);
+        assert_eq!(overlap.max.load(Ordering::SeqCst), 3);
+
+        let overlap = Overlap::default();
+        assert_eq!(run_all(vec![1, 2], 0, |n| overlap.run(n)), vec![10, 20]);
+        assert_eq!(overlap.max.load(Ordering::SeqCst), 1);
+
+        assert!(run_all(Vec::<u64>::new(), 4, |n| n).is_empty());
+    }
+
+    #[test]
+    fn test_run_all_forwards_worker_warnings() {
+        let (results, warnings) = collect_warnings(|| {
+            run_all(vec![1, 2, 3], 3, |n| {
+                warn(format!("item {}", n));
+                n
+            })
+        });
+        assert_eq!(results, vec![1, 2, 3]);
+        assert_eq!(warnings, vec!["item 1", "item 2", "item 3"]);
+    }
+
+    #[test]
+    fn test_default_parallelism_is_at_least_two() {
+        assert!(default_parallelism() >= 2);
+    }
+
+    #[test]
+    fn test_run_reports_returns_each_result_in_order() {
+        let results = run_reports(
+            Some("/nonexistent/hledger"),
+            None,
+            vec![
+                ReportRequest::Accounts(AccountsOptions::new()),
+                ReportRequest::Balance(BalanceOptions::new().begin("not a date")),
+                ReportRequest::Cashflow(CashflowOptions::new()),
+            ],
+        );
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            results[0],
+            Err(HLedgerError::HLedgerNotFound { .. })
+        ));
+        assert!(matches!(results[1], Err(HLedgerError::InvalidDate { .. })));
+        assert!(matches!(
+            results[2],
+            Err(HLedgerError::HLedgerNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_request_serialization() {
+        let request = ReportRequest::Print(PrintOptions::new().explicit());
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["type"], "Print");
+        assert_eq!(json["value"]["explicit"], true);
+
+        let parsed: ReportRequest = serde_json::from_value(json).unwrap();
+        assert!(matches!(parsed, ReportRequest::Print(options) if options.explicit));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_run_all_async_bounds_parallelism_and_keeps_order() {
+        let overlap = std::sync::Arc::new(Overlap::default());
+        let counter = overlap.clone();
+        let results = run_all_async((0..6).collect(), 2, move |n| counter.run(n)).await;
+        assert_eq!(results, vec![0, 10, 20, 30, 40, 50]);
+        assert_eq!(overlap.max.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_run_reports_async_returns_each_result() {
+        let results = run_reports_async(
+            Some("/nonexistent/hledger".to_string()),
+            None,
+            vec![
+                ReportRequest::Prices(PricesOptions::new()),
+                ReportRequest::Accounts(AccountsOptions::new()),
+            ],
+            default_parallelism(),
+        )
+        .await;
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|r| matches!(r, Err(HLedgerError::HLedgerNotFound { .. }))));
+    }
+}
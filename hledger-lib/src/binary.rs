@@ -0,0 +1,618 @@
+//! Finding the hledger executable from what a user typed or pasted
+//!
+//! Settings fields get paths like `"C:\Program Files\hledger\hledger.exe"`
+//! (quotes included, from Explorer's "Copy as path"), paths without the
+//! `.exe`, and bare names to look up on `PATH`. [`resolve_hledger_binary`]
+//! turns all of these into the file to run, or says why there is none.
+//! [`detect_hledger`] looks for an installed hledger when there's no
+//! setting yet, and [`resolve_hledger`] picks between a setting, the
+//! hledger an app ships as a sidecar, and `PATH`.
+
+use crate::config::run_command;
+use crate::timeout::Timeout;
+use crate::version::HLedgerVersion;
+use crate::{HLedgerError, Result};
+use serde::{Deserialize, Serialize};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use ts_rs::TS;
+
+/// How long a candidate gets to answer `--version`
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Find the hledger executable named by `path_or_name`
+///
+/// Surrounding whitespace and one pair of matching quotes are ignored. A
+/// value with a directory part (including UNC paths like
+/// `\\server\share\hledger.exe`) is checked as is; a bare name is looked up
+/// on `PATH`. On Windows a name without an extension also matches
+/// `NAME.exe`, which is preferred.
+///
+/// Fails with `HLedgerNotFound` if no such file exists, and with
+/// `HLedgerNotExecutable` if it exists but can't be run.
+pub fn resolve_hledger_binary(path_or_name: &str) -> Result<PathBuf> {
+    let input = clean_input(path_or_name);
+    let windows = cfg!(windows);
+
+    if is_bare_name(input) {
+        let path_var = std::env::var_os("PATH").unwrap_or_default();
+        return find_on_path(input, &path_var, windows).ok_or_else(|| {
+            HLedgerError::HLedgerNotFound {
+                attempted: input.to_string(),
+            }
+        });
+    }
+
+    let candidates = candidates(Path::new(input), windows);
+    let Some(found) = candidates.into_iter().find(|c| c.is_file()) else {
+        return Err(HLedgerError::HLedgerNotFound {
+            attempted: input.to_string(),
+        });
+    };
+    if !is_executable(&found) {
+        return Err(HLedgerError::HLedgerNotExecutable {
+            path: found.to_string_lossy().into_owned(),
+        });
+    }
+    Ok(found)
+}
+
+/// Where the hledger that runs comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum HledgerSource {
+    /// Shipped with the app, run when no path is configured
+    Sidecar,
+    /// A configured path
+    Custom,
+    /// `hledger`, or a configured bare name, found on `PATH`
+    Path,
+}
+
+/// The hledger [`resolve_hledger`] found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedHledger {
+    pub path: PathBuf,
+    pub source: HledgerSource,
+}
+
+/// Find the hledger to run: `hledger_path` if one is configured, else the
+/// app's `sidecar` if it's there, else `hledger` on `PATH`
+///
+/// A sidecar that's missing or can't be run, as on architectures the app
+/// doesn't ship hledger for, is passed over silently. Fails as
+/// [`resolve_hledger_binary`] does when the binary chosen isn't there.
+pub fn resolve_hledger(
+    hledger_path: Option<&str>,
+    sidecar: Option<&Path>,
+) -> Result<ResolvedHledger> {
+    if let Some(path) = hledger_path {
+        let source = if is_bare_name(clean_input(path)) {
+            HledgerSource::Path
+        } else {
+            HledgerSource::Custom
+        };
+        return Ok(ResolvedHledger {
+            path: resolve_hledger_binary(path)?,
+            source,
+        });
+    }
+    if let Some(sidecar) = usable_sidecar(sidecar) {
+        return Ok(ResolvedHledger {
+            path: sidecar.to_path_buf(),
+            source: HledgerSource::Sidecar,
+        });
+    }
+    Ok(ResolvedHledger {
+        path: resolve_hledger_binary("hledger")?,
+        source: HledgerSource::Path,
+    })
+}
+
+/// `sidecar`, if there's an executable file there
+pub(crate) fn usable_sidecar(sidecar: Option<&Path>) -> Option<&Path> {
+    sidecar.filter(|path| path.is_file() && is_executable(path))
+}
+
+/// Trim whitespace and one pair of matching surrounding quotes
+pub(crate) fn clean_input(input: &str) -> &str {
+    let input = input.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = input
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner.trim();
+        }
+    }
+    input
+}
+
+/// Whether `input` is a name to look up on `PATH` rather than a path
+fn is_bare_name(input: &str) -> bool {
+    !input.is_empty() && !input.contains(['/', '\\']) && Path::new(input).components().count() == 1
+}
+
+/// Files `path` could mean, most likely first
+fn candidates(path: &Path, windows: bool) -> Vec<PathBuf> {
+    if windows && path.extension().is_none() {
+        let mut exe = OsString::from(path.as_os_str());
+        exe.push(".exe");
+        vec![PathBuf::from(exe), path.to_path_buf()]
+    } else {
+        vec![path.to_path_buf()]
+    }
+}
+
+/// The first executable file called `name` in the directories of `path_var`
+fn find_on_path(name: &str, path_var: &OsStr, windows: bool) -> Option<PathBuf> {
+    std::env::split_paths(path_var)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .flat_map(|dir| candidates(&dir.join(name), windows))
+        .find(|candidate| candidate.is_file() && is_executable(candidate))
+}
+
+/// An installed hledger found by [`detect_hledger`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DetectedHledger {
+    pub path: String,
+    /// What `--version` printed, e.g. `hledger 1.32.3, linux-x86_64`
+    pub version: String,
+}
+
+/// Look for an installed hledger, for prefilling the path setting
+///
+/// Checks the `HLEDGER` environment variable, then `hledger` on `PATH`,
+/// then the usual install locations (Homebrew, `/usr/local/bin`,
+/// `~/.local/bin`, ghcup, stack and cabal bin directories, and on Windows
+/// `%LOCALAPPDATA%\Programs`). The first one whose `--version` works is
+/// returned; `None` if none does.
+pub fn detect_hledger() -> Option<DetectedHledger> {
+    let candidates = detection_candidates(|key| std::env::var_os(key), cfg!(windows));
+    first_working(candidates, probe_version)
+}
+
+/// Files to try, in order, given the environment `env`
+fn detection_candidates(env: impl Fn(&str) -> Option<OsString>, windows: bool) -> Vec<PathBuf> {
+    let name = "hledger";
+    let path_dirs: Vec<PathBuf> = env("PATH")
+        .map(|path_var| {
+            std::env::split_paths(&path_var)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut files = Vec::new();
+
+    if let Some(value) = env("HLEDGER") {
+        let value = value.to_string_lossy();
+        let value = clean_input(&value);
+        if is_bare_name(value) {
+            for dir in &path_dirs {
+                files.extend(candidates(&dir.join(value), windows));
+            }
+        } else if !value.is_empty() {
+            files.extend(candidates(Path::new(value), windows));
+        }
+    }
+    for dir in &path_dirs {
+        files.extend(candidates(&dir.join(name), windows));
+    }
+    for dir in install_dirs(&env, windows) {
+        files.extend(candidates(&dir.join(name), windows));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    files
+}
+
+/// Where installers and Haskell build tools put hledger, most likely first
+fn install_dirs(env: &impl Fn(&str) -> Option<OsString>, windows: bool) -> Vec<PathBuf> {
+    let under = |key: &str, parts: &[&str]| {
+        env(key).filter(|v| !v.is_empty()).map(|base| {
+            parts
+                .iter()
+                .fold(PathBuf::from(base), |dir, part| dir.join(part))
+        })
+    };
+    if windows {
+        [
+            under("LOCALAPPDATA", &["Programs", "hledger"]),
+            under("LOCALAPPDATA", &["Programs"]),
+            under("APPDATA", &["local", "bin"]),
+            under("APPDATA", &["cabal", "bin"]),
+            Some(PathBuf::from(r"C:\ghcup\bin")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    } else {
+        [
+            Some(PathBuf::from("/opt/homebrew/bin")),
+            Some(PathBuf::from("/usr/local/bin")),
+            under("HOME", &[".local", "bin"]),
+            under("HOME", &[".ghcup", "bin"]),
+            under("HOME", &[".cabal", "bin"]),
+            Some(PathBuf::from("/usr/bin")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// The first of `candidates` that `probe` gets a version from
+fn first_working(
+    candidates: impl IntoIterator<Item = PathBuf>,
+    mut probe: impl FnMut(&Path) -> Option<String>,
+) -> Option<DetectedHledger> {
+    candidates.into_iter().find_map(|path| {
+        let version = probe(&path)?;
+        Some(DetectedHledger {
+            path: path.to_string_lossy().into_owned(),
+            version,
+        })
+    })
+}
+
+/// `path --version`, if `path` is an hledger that runs
+fn probe_version(path: &Path) -> Option<String> {
+    if !path.is_file() || !is_executable(path) {
+        return None;
+    }
+    let mut cmd = Command::new(path);
+    cmd.arg("--version");
+    let output = run_command(&mut cmd, Some(Timeout(PROBE_TIMEOUT))).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    HLedgerVersion::parse(&version).map(|_| version)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A fresh directory under the system temp dir, removed on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        /// Create an empty file in the directory, executable on Unix
+        fn executable(&self, name: &str) -> PathBuf {
+            let path = self.0.join(name);
+            fs::write(&path, "").unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            }
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_clean_input() {
+        assert_eq!(clean_input("  hledger \n"), "hledger");
+        assert_eq!(
+            clean_input(r#""C:\Program Files\hledger\hledger.exe""#),
+            r"C:\Program Files\hledger\hledger.exe"
+        );
+        assert_eq!(
+            clean_input("'/opt/my tools/hledger'"),
+            "/opt/my tools/hledger"
+        );
+        // Unbalanced quotes are left alone
+        assert_eq!(clean_input(r#""/opt/hledger"#), r#""/opt/hledger"#);
+        assert_eq!(clean_input(r#"'/opt/hledger""#), r#"'/opt/hledger""#);
+    }
+
+    #[test]
+    fn test_is_bare_name() {
+        assert!(is_bare_name("hledger"));
+        assert!(is_bare_name("hledger.exe"));
+        assert!(!is_bare_name("./hledger"));
+        assert!(!is_bare_name("/usr/bin/hledger"));
+        assert!(!is_bare_name(r"C:\hledger\hledger.exe"));
+        assert!(!is_bare_name(r"\\server\share\hledger.exe"));
+        assert!(!is_bare_name(""));
+    }
+
+    #[test]
+    fn test_candidates() {
+        assert_eq!(
+            candidates(Path::new("/usr/bin/hledger"), false),
+            vec![PathBuf::from("/usr/bin/hledger")]
+        );
+        assert_eq!(
+            candidates(Path::new("hledger"), true),
+            vec![PathBuf::from("hledger.exe"), PathBuf::from("hledger")]
+        );
+        assert_eq!(
+            candidates(Path::new("hledger.exe"), true),
+            vec![PathBuf::from("hledger.exe")]
+        );
+        assert_eq!(
+            candidates(Path::new(r"\\server\share\hledger"), true)[0],
+            PathBuf::from(r"\\server\share\hledger.exe")
+        );
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let dir = TempDir::new("resolve-hledger path");
+        let binary = dir.executable(if cfg!(windows) {
+            "hledger.exe"
+        } else {
+            "hledger"
+        });
+
+        let quoted = format!("\"{}\"", binary.display());
+        assert_eq!(resolve_hledger_binary(&quoted).unwrap(), binary);
+
+        let missing = dir.0.join("missing-hledger");
+        match resolve_hledger_binary(missing.to_str().unwrap()) {
+            Err(HLedgerError::HLedgerNotFound { attempted }) => {
+                assert!(attempted.ends_with("missing-hledger"))
+            }
+            other => panic!("Expected HLedgerNotFound, got {:?}", other),
+        }
+
+        // A directory is not the binary
+        assert!(matches!(
+            resolve_hledger_binary(dir.0.to_str().unwrap()),
+            Err(HLedgerError::HLedgerNotFound { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_not_executable() {
+        let dir = TempDir::new("resolve-hledger-mode");
+        let path = dir.0.join("hledger");
+        fs::write(&path, "").unwrap();
+        assert!(matches!(
+            resolve_hledger_binary(path.to_str().unwrap()),
+            Err(HLedgerError::HLedgerNotExecutable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_on_path() {
+        let empty = TempDir::new("resolve-hledger-empty");
+        let dir = TempDir::new("resolve-hledger-bin");
+        let binary = dir.executable(if cfg!(windows) {
+            "hledger.exe"
+        } else {
+            "hledger"
+        });
+
+        let path_var = std::env::join_paths([&empty.0, &dir.0]).unwrap();
+        assert_eq!(
+            find_on_path("hledger", &path_var, cfg!(windows)),
+            Some(binary)
+        );
+        assert_eq!(find_on_path("ledger", &path_var, cfg!(windows)), None);
+        assert_eq!(find_on_path("hledger", OsStr::new(""), cfg!(windows)), None);
+    }
+
+    #[test]
+    fn test_resolve_missing_name() {
+        match resolve_hledger_binary("surely-not-an-installed-hledger") {
+            Err(HLedgerError::HLedgerNotFound { attempted }) => {
+                assert_eq!(attempted, "surely-not-an-installed-hledger")
+            }
+            other => panic!("Expected HLedgerNotFound, got {:?}", other),
+        }
+    }
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| OsString::from(v))
+        }
+    }
+
+    #[test]
+    fn test_detection_candidates_order() {
+        let path_var = std::env::join_paths(["/nix/bin", "/usr/local/bin"]).unwrap();
+        let path_var = path_var.to_str().unwrap();
+        let vars = [
+            ("HLEDGER", "'/opt/hledger-1.40/hledger'"),
+            ("PATH", path_var),
+            ("HOME", "/home/ana"),
+        ];
+        assert_eq!(
+            detection_candidates(env(&vars), false),
+            [
+                "/opt/hledger-1.40/hledger",
+                "/nix/bin/hledger",
+                "/usr/local/bin/hledger",
+                "/opt/homebrew/bin/hledger",
+                "/home/ana/.local/bin/hledger",
+                "/home/ana/.ghcup/bin/hledger",
+                "/home/ana/.cabal/bin/hledger",
+                "/usr/bin/hledger",
+            ]
+            .map(PathBuf::from)
+        );
+
+        // A bare name in HLEDGER is looked up on PATH, ahead of `hledger`
+        let vars = [("HLEDGER", "hledger-dev"), ("PATH", "/nix/bin")];
+        let candidates = detection_candidates(env(&vars), false);
+        assert_eq!(
+            candidates[..2],
+            ["/nix/bin/hledger-dev", "/nix/bin/hledger"].map(PathBuf::from)
+        );
+        assert!(!candidates.iter().any(|c| c.starts_with("/home")));
+    }
+
+    #[test]
+    fn test_detection_candidates_windows() {
+        let vars = [
+            ("LOCALAPPDATA", r"C:\Users\ana\AppData\Local"),
+            ("APPDATA", r"C:\Users\ana\AppData\Roaming"),
+        ];
+        let candidates = detection_candidates(env(&vars), true);
+        // Each directory is tried with `.exe` first
+        assert_eq!(
+            candidates[0],
+            PathBuf::from(r"C:\Users\ana\AppData\Local")
+                .join("Programs")
+                .join("hledger")
+                .join("hledger.exe")
+        );
+        assert_eq!(
+            candidates[1],
+            PathBuf::from(r"C:\Users\ana\AppData\Local")
+                .join("Programs")
+                .join("hledger")
+                .join("hledger")
+        );
+        assert!(candidates.contains(&PathBuf::from(r"C:\ghcup\bin").join("hledger.exe")));
+        assert!(!candidates.iter().any(|c| c.starts_with("/usr")));
+    }
+
+    #[test]
+    fn test_first_working() {
+        let candidates = [
+            "/opt/broken/hledger",
+            "/usr/local/bin/hledger",
+            "/usr/bin/hledger",
+        ]
+        .map(PathBuf::from);
+        let mut probed = Vec::new();
+        let detected = first_working(candidates.clone(), |path| {
+            probed.push(path.to_path_buf());
+            (path != Path::new("/opt/broken/hledger")).then(|| "hledger 1.32.3".to_string())
+        });
+        assert_eq!(
+            detected,
+            Some(DetectedHledger {
+                path: "/usr/local/bin/hledger".to_string(),
+                version: "hledger 1.32.3".to_string(),
+            })
+        );
+        // Stops at the first that works
+        assert_eq!(probed, candidates[..2]);
+
+        assert_eq!(first_working(candidates, |_| None), None);
+    }
+
+    #[test]
+    fn test_probe_version_skips_non_executables() {
+        let dir = TempDir::new("detect-hledger");
+        assert_eq!(probe_version(&dir.0.join("hledger")), None);
+        assert_eq!(probe_version(&dir.0), None);
+    }
+
+    #[test]
+    fn test_resolve_hledger_sources() {
+        let dir = TempDir::new("resolve-hledger-sidecar");
+        let sidecar = dir.executable("hledger-sidecar");
+        let custom = dir.executable("hledger-custom");
+
+        let resolved = resolve_hledger(None, Some(&sidecar)).unwrap();
+        assert_eq!(resolved.path, sidecar);
+        assert_eq!(resolved.source, HledgerSource::Sidecar);
+
+        // A configured path wins over the sidecar
+        let resolved = resolve_hledger(custom.to_str(), Some(&sidecar)).unwrap();
+        assert_eq!(resolved.path, custom);
+        assert_eq!(resolved.source, HledgerSource::Custom);
+
+        // A missing sidecar falls back to PATH, which fails only if there's
+        // no hledger there either
+        let missing = dir.0.join("hledger-missing");
+        match resolve_hledger(None, Some(&missing)) {
+            Ok(resolved) => assert_eq!(resolved.source, HledgerSource::Path),
+            Err(e) => assert!(matches!(e, HLedgerError::HLedgerNotFound { .. })),
+        }
+        assert!(matches!(
+            resolve_hledger(Some("surely-not-an-installed-hledger"), Some(&sidecar)),
+            Err(HLedgerError::HLedgerNotFound { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unexecutable_sidecar_is_skipped() {
+        let dir = TempDir::new("resolve-hledger-sidecar-mode");
+        let path = dir.0.join("hledger");
+        fs::write(&path, "").unwrap();
+        assert_eq!(usable_sidecar(Some(&path)), None);
+        assert_eq!(usable_sidecar(None), None);
+    }
+
+    #[test]
+    fn export_bindings() {
+        DetectedHledger::export_all().unwrap();
+        HledgerSource::export_all().unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_resolve_appends_exe_on_windows() {
+        let dir = TempDir::new("resolve hledger exe");
+        let binary = dir.executable("hledger.exe");
+
+        let without_exe = dir.0.join("hledger");
+        assert_eq!(
+            resolve_hledger_binary(without_exe.to_str().unwrap()).unwrap(),
+            binary
+        );
+        // Forward slashes work too
+        let forward = without_exe.to_str().unwrap().replace('\\', "/");
+        assert_eq!(
+            resolve_hledger_binary(&forward).unwrap(),
+            PathBuf::from(format!("{}.exe", forward))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_path_parts() {
+        assert_eq!(
+            candidates(Path::new(r"C:\Program Files\hledger.v1\hledger"), true),
+            vec![
+                PathBuf::from(r"C:\Program Files\hledger.v1\hledger.exe"),
+                PathBuf::from(r"C:\Program Files\hledger.v1\hledger"),
+            ]
+        );
+        assert!(!is_bare_name("C:hledger.exe"));
+        assert!(Path::new(r"\\server\share\hledger.exe").is_absolute());
+    }
+}
@@ -0,0 +1,249 @@
+//! Writes `bindings/index.ts`, re-exporting every type exported to
+//! TypeScript so the app can import them all from one path
+
+use crate::amount::MultiAmount;
+use crate::batch::{ReportRequest, ReportResponse};
+use crate::binary::{DetectedHledger, HledgerSource};
+use crate::commands::accounts::{
+    AccountBalanceNode, AccountInfo, AccountNode, AccountType, AccountWithBalance, AccountsOptions,
+};
+use crate::commands::balance::{
+    Amount, BalanceAccount, BalanceOptions, BalanceReport, PeriodDate, PeriodicBalance,
+    PeriodicBalanceRow, Price, PriceKind, SimpleBalance,
+};
+use crate::commands::balancesheet::{
+    BalanceSheetOptions, BalanceSheetReport, BalanceSheetSubreport,
+};
+use crate::commands::budget::{BudgetCell, BudgetOptions, BudgetReport, BudgetRow};
+use crate::commands::cashflow::{CashflowOptions, CashflowReport, CashflowSubreport};
+use crate::commands::check::{
+    AssertionFailure, CheckIssue, CheckIssueKind, CheckSeverity, JournalProblem,
+};
+use crate::commands::flow::{FlowGraph, FlowLink, FlowNode, FlowNodeKind, FlowOptions};
+use crate::commands::import::ImportOptions;
+use crate::commands::incomestatement::{
+    IncomeStatementOptions, IncomeStatementReport, IncomeStatementSubreport,
+};
+use crate::commands::networth::{NetworthOptions, NetworthPoint};
+use crate::commands::prices::{MarketPrice, PricesOptions};
+use crate::commands::print::{
+    AmountStyle, BalanceAssertion, DigitGroups, PageOrder, PageSpec, Precision, PrintAmount,
+    PrintOptions, PrintPage, PrintPosting, PrintTransaction, SourcePosition, SourceSpan,
+};
+use crate::commands::spending::{AccountTotal, TopSpendingOptions};
+use crate::commands::stats::JournalStats;
+use crate::commands::tags::{TagUsage, TagValueUsage, TagsOptions};
+use crate::commands::timeseries::{Accumulation, Series, SeriesPoint, TimeseriesSpec};
+use crate::compare::ComparisonRow;
+use crate::csv_import::{CsvImportOutcome, CsvMapping, CsvPreview};
+use crate::csv_report::{CsvReport, ExportFormat};
+use crate::csv_rules::{CsvAssignment, CsvCondition, CsvRules};
+use crate::dashboard::{DashboardSection, DashboardSummary, MonthTotals, UnavailableSection};
+use crate::defaults::{ReportDefaults, ValueMode};
+use crate::duplicates::{DuplicateCandidate, DuplicateGroup, DuplicateTolerance};
+use crate::encoding::JournalEncoding;
+use crate::error::{AppError, ErrorCode, ErrorDetails};
+use crate::format::FormatOptions;
+use crate::health::{HealthCode, HealthItem, HealthReport, HealthStatus};
+use crate::interval::ReportInterval;
+use crate::journals::{DefaultJournal, DefaultJournalSource};
+use crate::list_mode::ListMode;
+use crate::metadata::{DroppedFile, JournalMetadata};
+use crate::payees::{PayeeStats, PayeeStatsOptions};
+use crate::query::{AmountOp, Query, Status};
+use crate::register::RunningBalanceEntry;
+use crate::scaffold::{JournalLayout, JournalTemplate, OpeningBalance};
+use crate::search::{SearchField, SearchHighlight, SearchMatch, SearchResults};
+use crate::sqlite::ExportStats;
+use crate::suggest::{SuggestionKind, Suggestions, TransactionSuggestion};
+use crate::summary::{MonthSummary, PayeeSummary, TransactionSummary};
+use crate::timeout::Timeout;
+use crate::transaction::{JournalStyle, PostingAssertion, PostingBuilder, TransactionBuilder};
+use crate::upcoming::UpcomingTransaction;
+use crate::version::HLedgerVersion;
+use crate::warnings::ReportOutput;
+use crate::write::{FileFingerprint, WriteOptions, WriteReceipt};
+use std::collections::BTreeSet;
+use std::fs;
+use ts_rs::TS;
+
+const HEADER: &str = "// This file was generated by the `export_bindings` test in hledger-lib. Do not edit this file manually.\n";
+
+/// Export each type and return the names the barrel should re-export
+macro_rules! export_types {
+    ($($ty:ty),* $(,)?) => {{
+        let mut names = BTreeSet::new();
+        $(
+            <$ty>::export_all().unwrap();
+            names.insert(<$ty>::ident());
+        )*
+        names
+    }};
+}
+
+#[test]
+fn export_bindings() {
+    let names = export_types!(
+        AccountBalanceNode,
+        AccountInfo,
+        AccountNode,
+        AccountTotal,
+        AccountType,
+        AccountWithBalance,
+        AccountsOptions,
+        Accumulation,
+        Amount,
+        AmountOp,
+        AmountStyle,
+        AppError,
+        AssertionFailure,
+        BalanceAccount,
+        BalanceAssertion,
+        BalanceOptions,
+        BalanceReport,
+        BalanceSheetOptions,
+        BalanceSheetReport,
+        BalanceSheetSubreport,
+        BudgetCell,
+        BudgetOptions,
+        BudgetReport,
+        BudgetRow,
+        CashflowOptions,
+        CashflowReport,
+        CashflowSubreport,
+        CheckIssue,
+        CheckIssueKind,
+        CheckSeverity,
+        ComparisonRow,
+        CsvAssignment,
+        CsvCondition,
+        CsvImportOutcome,
+        CsvMapping,
+        CsvPreview,
+        CsvReport,
+        CsvRules,
+        DashboardSection,
+        DashboardSummary,
+        DefaultJournal,
+        DefaultJournalSource,
+        DetectedHledger,
+        DigitGroups,
+        DroppedFile,
+        DuplicateCandidate,
+        DuplicateGroup,
+        DuplicateTolerance,
+        ErrorCode,
+        ErrorDetails,
+        ExportFormat,
+        ExportStats,
+        FileFingerprint,
+        FlowGraph,
+        FlowLink,
+        FlowNode,
+        FlowNodeKind,
+        FlowOptions,
+        FormatOptions,
+        HLedgerVersion,
+        HealthCode,
+        HealthItem,
+        HealthReport,
+        HealthStatus,
+        HledgerSource,
+        ImportOptions,
+        IncomeStatementOptions,
+        IncomeStatementReport,
+        IncomeStatementSubreport,
+        JournalEncoding,
+        JournalLayout,
+        JournalMetadata,
+        JournalProblem,
+        JournalStats,
+        JournalStyle,
+        JournalTemplate,
+        ListMode,
+        MarketPrice,
+        MonthSummary,
+        MonthTotals,
+        MultiAmount,
+        NetworthOptions,
+        NetworthPoint,
+        OpeningBalance,
+        PageOrder,
+        PageSpec,
+        PayeeStats,
+        PayeeStatsOptions,
+        PayeeSummary,
+        PeriodDate,
+        PeriodicBalance,
+        PeriodicBalanceRow,
+        PostingAssertion,
+        PostingBuilder,
+        Precision,
+        Price,
+        PriceKind,
+        PricesOptions,
+        PrintAmount,
+        PrintOptions,
+        PrintPage,
+        PrintPosting,
+        PrintTransaction,
+        Query,
+        ReportDefaults,
+        ReportInterval,
+        ReportOutput<()>,
+        ReportRequest,
+        ReportResponse,
+        RunningBalanceEntry,
+        SearchField,
+        SearchHighlight,
+        SearchMatch,
+        SearchResults,
+        Series,
+        SeriesPoint,
+        SimpleBalance,
+        SourcePosition,
+        SourceSpan,
+        Status,
+        SuggestionKind,
+        Suggestions,
+        TagUsage,
+        TagValueUsage,
+        TagsOptions,
+        Timeout,
+        TimeseriesSpec,
+        TopSpendingOptions,
+        TransactionBuilder,
+        TransactionSuggestion,
+        TransactionSummary,
+        UnavailableSection,
+        UpcomingTransaction,
+        ValueMode,
+        WriteOptions,
+        WriteReceipt,
+    );
+
+    let index = Amount::default_output_path()
+        .unwrap()
+        .with_file_name("index.ts");
+    let dir = index.parent().unwrap();
+
+    // Every generated file must be listed above, or the barrel would miss it
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path == index {
+            continue;
+        }
+        let stem = path.file_stem().unwrap().to_string_lossy().into_owned();
+        assert!(
+            names.contains(&stem),
+            "{} is not listed in the bindings barrel",
+            path.display()
+        );
+    }
+
+    let mut contents = HEADER.to_string();
+    for name in &names {
+        contents.push_str(&format!("export type {{ {name} }} from \"./{name}\";\n"));
+    }
+    fs::write(&index, contents).unwrap();
+}
@@ -0,0 +1,908 @@
+use crate::cancel::{check_cancelled, current_token};
+use crate::commands::accounts::{
+    get_account_balance_tree, get_account_tree, get_accounts, get_accounts_detailed,
+    AccountBalanceNode, AccountInfo, AccountNode, AccountsOptions,
+};
+use crate::commands::balance::{get_balance, BalanceOptions, BalanceReport};
+use crate::commands::balancesheet::{get_balancesheet, BalanceSheetOptions, BalanceSheetReport};
+use crate::commands::budget::{get_budget_report, BudgetOptions, BudgetReport};
+use crate::commands::cashflow::{get_cashflow, CashflowOptions, CashflowReport};
+use crate::commands::files::get_files;
+use crate::commands::flow::{get_flow_data, FlowGraph, FlowOptions};
+use crate::commands::incomestatement::{
+    get_incomestatement, IncomeStatementOptions, IncomeStatementReport,
+};
+use crate::commands::networth::{get_networth_series, NetworthOptions, NetworthPoint};
+use crate::commands::prices::{get_prices, MarketPrice, PricesOptions};
+use crate::commands::print::{get_print, PrintOptions, PrintReport};
+use crate::commands::tags::{get_tag_usage, TagUsage, TagsOptions};
+use crate::commands::timeseries::{get_timeseries, Series, TimeseriesSpec};
+use crate::compare::{compare_periods, ComparisonRow};
+use crate::format::{get_commodity_styles, CommodityStyles};
+use crate::journals::journal_file_paths;
+use crate::payees::{get_payee_stats, PayeeStats, PayeeStatsOptions};
+use crate::search::{SearchIndex, SearchResults};
+use crate::upcoming::{get_upcoming_transactions, UpcomingTransaction};
+use crate::Result;
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime};
+
+thread_local! {
+    static REFRESH: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with the reports it gets from a [`ReportCache`] on this thread
+/// read afresh
+///
+/// Their cached copies are skipped rather than dropped, and each new result
+/// replaces its own entry, so the cache's other reports stay cached.
+pub fn with_refresh<T>(f: impl FnOnce() -> T) -> T {
+    let outer = REFRESH.with(|refresh| refresh.replace(true));
+    // Restores the outer setting even if `f` panics
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            REFRESH.with(|refresh| refresh.set(self.0));
+        }
+    }
+    let _restore = Restore(outer);
+    f()
+}
+
+/// Lists the files a journal reads, given the hledger path and journal file
+type FileLister = dyn Fn(Option<&str>, Option<&str>) -> Result<Vec<String>> + Send + Sync;
+
+/// Modification time and size of a file, or `None` if it can't be read
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// Which journal, read by which hledger
+type JournalKey = (Option<String>, Option<String>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    kind: &'static str,
+    options_hash: u64,
+    journal: JournalKey,
+}
+
+impl CacheKey {
+    /// The key for a report: options are hashed in their serialized form,
+    /// so equal options give the same key however they were built
+    fn new<O: Serialize>(
+        kind: &'static str,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &O,
+    ) -> Result<Self> {
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(options)?.hash(&mut hasher);
+        Ok(Self {
+            kind,
+            options_hash: hasher.finish(),
+            journal: (
+                hledger_path.map(str::to_string),
+                journal_file.map(str::to_string),
+            ),
+        })
+    }
+}
+
+/// A report being run, which identical requests wait for instead of running
+/// it again
+#[derive(Default)]
+struct InFlight {
+    done: Mutex<bool>,
+    finished: Condvar,
+}
+
+impl InFlight {
+    /// Wait until the run finishes, or until this thread's runs are
+    /// cancelled
+    fn wait(&self) -> Result<()> {
+        let token = current_token();
+        let mut done = self.done.lock().unwrap();
+        while !*done {
+            check_cancelled(token.as_ref())?;
+            done = self
+                .finished
+                .wait_timeout(done, Duration::from_millis(50))
+                .unwrap()
+                .0;
+        }
+        Ok(())
+    }
+}
+
+/// Ends an in-flight run when dropped, however the run went, waking those
+/// waiting for it
+struct Flight<'a> {
+    in_flight: &'a Mutex<HashMap<CacheKey, Arc<InFlight>>>,
+    key: &'a CacheKey,
+}
+
+impl Drop for Flight<'_> {
+    fn drop(&mut self) {
+        let flight = self.in_flight.lock().unwrap().remove(self.key);
+        if let Some(flight) = flight {
+            *flight.done.lock().unwrap() = true;
+            flight.finished.notify_all();
+        }
+    }
+}
+
+struct CacheEntry {
+    key: CacheKey,
+    files: Vec<(PathBuf, FileStamp)>,
+    report: Arc<dyn Any + Send + Sync>,
+}
+
+/// Parsed reports kept until one of the files they were read from changes
+///
+/// Entries are keyed on the report kind, a hash of the serialized options,
+/// the hledger path and the journal file, and are only reused while the
+/// modification time and size of the journal and every file it includes
+/// (from [`get_files`]) are unchanged. The file list itself is reused on the
+/// same terms, so a hit costs a few `stat` calls and no hledger run. Once
+/// `capacity` reports are cached the least recently used is dropped.
+///
+/// A request for a report that is already being run waits for that run
+/// rather than starting hledger again.
+///
+/// Changes within the filesystem's timestamp resolution that keep the size
+/// can go unnoticed; a file watcher should call [`ReportCache::invalidate`].
+pub struct ReportCache {
+    capacity: usize,
+    /// Least recently used first
+    entries: Mutex<VecDeque<CacheEntry>>,
+    /// Reports being run, by the key they'll be cached under
+    in_flight: Mutex<HashMap<CacheKey, Arc<InFlight>>>,
+    file_lists: Mutex<HashMap<JournalKey, Vec<(PathBuf, FileStamp)>>>,
+    list_files: Box<FileLister>,
+}
+
+impl Default for ReportCache {
+    /// A cache of 32 reports
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+impl std::fmt::Debug for ReportCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReportCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl ReportCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            file_lists: Mutex::new(HashMap::new()),
+            list_files: Box::new(get_files),
+        }
+    }
+
+    /// Use `list_files` instead of `hledger files` to find the files a
+    /// journal reads
+    pub fn file_lister(
+        mut self,
+        list_files: impl Fn(Option<&str>, Option<&str>) -> Result<Vec<String>> + Send + Sync + 'static,
+    ) -> Self {
+        self.list_files = Box::new(list_files);
+        self
+    }
+
+    /// Number of reports cached
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every report read from `path`
+    pub fn invalidate(&self, path: impl AsRef<Path>) {
+        let path = canonical(path.as_ref());
+        let reads = |files: &[(PathBuf, FileStamp)]| files.iter().any(|(file, _)| *file == path);
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|entry| !reads(&entry.files));
+        self.file_lists
+            .lock()
+            .unwrap()
+            .retain(|_, files| !reads(files));
+    }
+
+    /// Forget every report
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        self.file_lists.lock().unwrap().clear();
+    }
+
+    /// The cached report for these arguments, or the result of `run`, which
+    /// is cached when it succeeds
+    ///
+    /// `kind` tells apart reports that take the same options type. While the
+    /// same report is being run for another caller this waits for it and
+    /// returns its result; if that run fails, this one runs the report
+    /// itself, so a cancelled or failed caller never hands its error on.
+    /// Under [`with_refresh`] the cached report and the journal's file list
+    /// are not used, and a run in progress is waited out and then repeated.
+    pub fn get_or_run<O, T>(
+        &self,
+        kind: &'static str,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &O,
+        run: impl FnOnce() -> Result<T>,
+    ) -> Result<T>
+    where
+        O: Serialize,
+        T: Clone + Send + Sync + 'static,
+    {
+        let key = CacheKey::new(kind, hledger_path, journal_file, options)?;
+        let refresh = REFRESH.with(Cell::get);
+        loop {
+            if !refresh {
+                if let Some(report) = self.lookup::<T>(&key) {
+                    return Ok(report);
+                }
+            }
+            let running = {
+                let mut in_flight = self.in_flight.lock().unwrap();
+                match in_flight.get(&key) {
+                    Some(flight) => Some(flight.clone()),
+                    None => {
+                        in_flight.insert(key.clone(), Arc::default());
+                        None
+                    }
+                }
+            };
+            match running {
+                Some(flight) => flight.wait()?,
+                None => break,
+            }
+        }
+        let _flight = Flight {
+            in_flight: &self.in_flight,
+            key: &key,
+        };
+
+        // Stamp the files before running, so edits made while hledger reads
+        // them make the entry stale rather than silently cached
+        let files = self.journal_files(&key.journal, refresh)?;
+        let report = run()?;
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| entry.key != key);
+        entries.push_back(CacheEntry {
+            key: key.clone(),
+            files,
+            report: Arc::new(report.clone()),
+        });
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+        Ok(report)
+    }
+
+    /// A fresh cached report, marked as most recently used; stale entries
+    /// are dropped
+    fn lookup<T: Clone + 'static>(&self, key: &CacheKey) -> Option<T> {
+        let mut entries = self.entries.lock().unwrap();
+        let position = entries.iter().position(|entry| entry.key == *key)?;
+        let entry = entries.remove(position)?;
+        if !is_fresh(&entry.files) {
+            return None;
+        }
+        let report = entry.report.downcast_ref::<T>().cloned();
+        entries.push_back(entry);
+        report
+    }
+
+    /// The journal's files with their current stamps, listing them again
+    /// only when one has changed or `relist` is set
+    fn journal_files(
+        &self,
+        journal: &JournalKey,
+        relist: bool,
+    ) -> Result<Vec<(PathBuf, FileStamp)>> {
+        if let Some(files) = self.file_lists.lock().unwrap().get(journal) {
+            if !relist && is_fresh(files) {
+                return Ok(files.clone());
+            }
+        }
+
+        let (hledger_path, journal_file) = journal;
+        let mut paths: Vec<PathBuf> =
+            (self.list_files)(hledger_path.as_deref(), journal_file.as_deref())?
+                .iter()
+                .map(|file| canonical(Path::new(file)))
+                .collect();
+        // The named files go first, whether or not hledger listed them
+        let mut named: Vec<PathBuf> = journal_file
+            .iter()
+            .flat_map(|file| journal_file_paths(file))
+            .map(|file| canonical(Path::new(file)))
+            .filter(|file| !paths.contains(file))
+            .collect();
+        named.append(&mut paths);
+        let paths = named;
+
+        let files: Vec<_> = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+        self.file_lists
+            .lock()
+            .unwrap()
+            .insert(journal.clone(), files.clone());
+        Ok(files)
+    }
+
+    /// [`get_accounts`], cached
+    pub fn get_accounts_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &AccountsOptions,
+    ) -> Result<Vec<String>> {
+        self.get_or_run("accounts", hledger_path, journal_file, options, || {
+            get_accounts(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_accounts_detailed`], cached
+    pub fn get_accounts_detailed_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &AccountsOptions,
+    ) -> Result<Vec<AccountInfo>> {
+        self.get_or_run(
+            "accounts_detailed",
+            hledger_path,
+            journal_file,
+            options,
+            || get_accounts_detailed(hledger_path, journal_file, options),
+        )
+    }
+
+    /// [`get_account_tree`], cached
+    pub fn get_account_tree_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &AccountsOptions,
+    ) -> Result<Vec<AccountNode>> {
+        self.get_or_run("account_tree", hledger_path, journal_file, options, || {
+            get_account_tree(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_account_balance_tree`], cached
+    pub fn get_account_balance_tree_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &BalanceOptions,
+    ) -> Result<Vec<AccountBalanceNode>> {
+        self.get_or_run(
+            "account_balance_tree",
+            hledger_path,
+            journal_file,
+            options,
+            || get_account_balance_tree(hledger_path, journal_file, options),
+        )
+    }
+
+    /// [`get_balance`], cached
+    pub fn get_balance_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &BalanceOptions,
+    ) -> Result<BalanceReport> {
+        self.get_or_run("balance", hledger_path, journal_file, options, || {
+            get_balance(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_balancesheet`], cached
+    pub fn get_balancesheet_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &BalanceSheetOptions,
+    ) -> Result<BalanceSheetReport> {
+        self.get_or_run("balancesheet", hledger_path, journal_file, options, || {
+            get_balancesheet(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_budget_report`], cached
+    pub fn get_budget_report_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &BudgetOptions,
+    ) -> Result<BudgetReport> {
+        self.get_or_run("budget", hledger_path, journal_file, options, || {
+            get_budget_report(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_cashflow`], cached
+    pub fn get_cashflow_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &CashflowOptions,
+    ) -> Result<CashflowReport> {
+        self.get_or_run("cashflow", hledger_path, journal_file, options, || {
+            get_cashflow(hledger_path, journal_file.map(Path::new), options.clone())
+        })
+    }
+
+    /// [`get_flow_data`], cached
+    pub fn get_flow_data_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &FlowOptions,
+    ) -> Result<FlowGraph> {
+        self.get_or_run("flow", hledger_path, journal_file, options, || {
+            get_flow_data(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_incomestatement`], cached
+    pub fn get_incomestatement_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &IncomeStatementOptions,
+    ) -> Result<IncomeStatementReport> {
+        self.get_or_run(
+            "incomestatement",
+            hledger_path,
+            journal_file,
+            options,
+            || get_incomestatement(hledger_path, journal_file, options),
+        )
+    }
+
+    /// [`get_networth_series`], cached
+    pub fn get_networth_series_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &NetworthOptions,
+    ) -> Result<Vec<NetworthPoint>> {
+        self.get_or_run("networth", hledger_path, journal_file, options, || {
+            get_networth_series(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_payee_stats`], cached
+    pub fn get_payee_stats_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &PayeeStatsOptions,
+    ) -> Result<Vec<PayeeStats>> {
+        self.get_or_run("payees", hledger_path, journal_file, options, || {
+            get_payee_stats(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_prices`], cached
+    pub fn get_prices_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &PricesOptions,
+    ) -> Result<Vec<MarketPrice>> {
+        self.get_or_run("prices", hledger_path, journal_file, options, || {
+            get_prices(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`compare_periods`], cached
+    pub fn compare_periods_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        period_a: &str,
+        period_b: &str,
+        options: &IncomeStatementOptions,
+    ) -> Result<Vec<ComparisonRow>> {
+        let key = (period_a, period_b, options);
+        self.get_or_run("compare", hledger_path, journal_file, &key, || {
+            compare_periods(hledger_path, journal_file, period_a, period_b, options)
+        })
+    }
+
+    /// [`get_print`], cached
+    pub fn get_print_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &PrintOptions,
+    ) -> Result<PrintReport> {
+        self.get_or_run("print", hledger_path, journal_file, options, || {
+            get_print(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_commodity_styles`], cached
+    pub fn get_commodity_styles_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+    ) -> Result<CommodityStyles> {
+        self.get_or_run("styles", hledger_path, journal_file, &(), || {
+            get_commodity_styles(hledger_path, journal_file)
+        })
+    }
+
+    /// [`get_tag_usage`], cached
+    pub fn get_tag_usage_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        options: &TagsOptions,
+    ) -> Result<Vec<TagUsage>> {
+        self.get_or_run("tags", hledger_path, journal_file, options, || {
+            get_tag_usage(hledger_path, journal_file, options)
+        })
+    }
+
+    /// [`get_timeseries`], cached
+    pub fn get_timeseries_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        spec: &TimeseriesSpec,
+    ) -> Result<Vec<Series>> {
+        self.get_or_run("timeseries", hledger_path, journal_file, spec, || {
+            get_timeseries(hledger_path, journal_file, spec)
+        })
+    }
+
+    /// [`get_upcoming_transactions`], cached for the day
+    pub fn get_upcoming_transactions_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        today: Option<NaiveDate>,
+        horizon_days: u32,
+    ) -> Result<Vec<UpcomingTransaction>> {
+        let today = today.unwrap_or_else(|| Local::now().date_naive());
+        let key = (today, horizon_days);
+        self.get_or_run("upcoming", hledger_path, journal_file, &key, || {
+            get_upcoming_transactions(hledger_path, journal_file, Some(today), horizon_days)
+        })
+    }
+
+    /// [`search_transactions`](crate::search::search_transactions), with the
+    /// index cached rather than each query's results
+    pub fn search_transactions_cached(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        query: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<SearchResults> {
+        let index = self.get_or_run("search", hledger_path, journal_file, &(), || {
+            let transactions =
+                self.get_print_cached(hledger_path, journal_file, &PrintOptions::new())?;
+            Ok(Arc::new(SearchIndex::new(transactions)))
+        })?;
+        Ok(index.search(query, limit, offset))
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn stamp(path: &Path) -> FileStamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn is_fresh(files: &[(PathBuf, FileStamp)]) -> bool {
+    files.iter().all(|(path, old)| stamp(path) == *old)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defaults::{merge_options, ReportDefaults};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A journal in its own temp directory, removed on drop
+    struct TempJournal {
+        dir: PathBuf,
+        file: PathBuf,
+    }
+
+    impl TempJournal {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("hledger-cache-{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let file = dir.join("main.journal");
+            fs::write(&file, "2024-01-01 a\n    a  $1\n    b\n").unwrap();
+            Self { dir, file }
+        }
+
+        fn path(&self) -> &str {
+            self.file.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempJournal {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    /// A cache whose file lister returns just the journal, and a counter of
+    /// how often the lister ran
+    fn cache(capacity: usize) -> (ReportCache, Arc<AtomicUsize>) {
+        let listed = Arc::new(AtomicUsize::new(0));
+        let counter = listed.clone();
+        let cache = ReportCache::new(capacity).file_lister(move |_, journal| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(journal.into_iter().map(str::to_string).collect())
+        });
+        (cache, listed)
+    }
+
+    /// Fetch through the cache with a runner that counts its invocations
+    fn fetch(
+        cache: &ReportCache,
+        journal: &TempJournal,
+        options: &PrintOptions,
+        runs: &AtomicUsize,
+    ) -> Vec<String> {
+        cache
+            .get_or_run("test", None, Some(journal.path()), options, || {
+                let n = runs.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(vec![format!("run {}", n)])
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cache_key_derivation() {
+        let key = |kind, hledger: Option<&str>, journal: Option<&str>, options: &PrintOptions| {
+            CacheKey::new(kind, hledger, journal, options).unwrap()
+        };
+        let options = PrintOptions::new();
+        let base = key("print", None, Some("a.journal"), &options);
+
+        // Equal options built separately give the same key
+        assert_eq!(
+            base,
+            key("print", None, Some("a.journal"), &PrintOptions::new())
+        );
+        assert_ne!(
+            base,
+            key(
+                "print",
+                None,
+                Some("a.journal"),
+                &PrintOptions::new().explicit()
+            )
+        );
+        assert_ne!(base, key("balance", None, Some("a.journal"), &options));
+        assert_ne!(base, key("print", None, Some("b.journal"), &options));
+        assert_ne!(base, key("print", None, None, &options));
+        assert_ne!(
+            base,
+            key("print", Some("/opt/hledger"), Some("a.journal"), &options)
+        );
+    }
+
+    #[test]
+    fn test_legacy_options_are_another_report() {
+        let journal = TempJournal::new("legacy");
+        let (cache, _) = cache(8);
+        let runs = AtomicUsize::new(0);
+        let fetch = |explicit| -> Vec<String> {
+            let options: BalanceOptions =
+                merge_options(&ReportDefaults::default(), explicit).unwrap();
+            cache
+                .get_or_run("balance", None, Some(journal.path()), &options, || {
+                    let n = runs.fetch_add(1, Ordering::SeqCst) + 1;
+                    Ok(vec![format!("run {}", n)])
+                })
+                .unwrap()
+        };
+
+        // The legacy fields are folded into the options, so into the key
+        let legacy = serde_json::json!({ "monthly": true, "tree": true });
+        assert_eq!(fetch(legacy.clone()), vec!["run 1"]);
+        assert_eq!(fetch(serde_json::json!({})), vec!["run 2"]);
+        assert_eq!(fetch(legacy), vec!["run 1"]);
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_kinds_with_same_options_are_separate() {
+        let journal = TempJournal::new("kinds");
+        let (cache, _) = cache(8);
+        let options = PrintOptions::new();
+
+        let first: Vec<String> = cache
+            .get_or_run("first", None, Some(journal.path()), &options, || {
+                Ok(vec!["first".to_string()])
+            })
+            .unwrap();
+        let second: Vec<String> = cache
+            .get_or_run("second", None, Some(journal.path()), &options, || {
+                Ok(vec!["second".to_string()])
+            })
+            .unwrap();
+        assert_eq!(
+            (first, second),
+            (vec!["first".to_string()], vec!["second".to_string()])
+        );
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_invalidate_by_included_file() {
+        let journal = TempJournal::new("include");
+        let included = journal.dir.join("2023.journal");
+        fs::write(&included, "").unwrap();
+        let listed = included.to_string_lossy().into_owned();
+        let cache = ReportCache::new(8).file_lister(move |_, journal| {
+            Ok(journal
+                .into_iter()
+                .map(str::to_string)
+                .chain([listed.clone()])
+                .collect())
+        });
+        let runs = AtomicUsize::new(0);
+        let options = PrintOptions::new();
+
+        fetch(&cache, &journal, &options, &runs);
+        cache.invalidate(journal.dir.join("unrelated.journal"));
+        assert_eq!(cache.len(), 1);
+        cache.invalidate(&included);
+        assert!(cache.is_empty());
+        assert_eq!(fetch(&cache, &journal, &options, &runs), vec!["run 2"]);
+    }
+
+    #[test]
+    fn test_unchanged_files_do_not_rerun() {
+        let journal = TempJournal::new("unchanged");
+        let (cache, listed) = cache(8);
+        let runs = AtomicUsize::new(0);
+        let options = PrintOptions::new();
+
+        assert_eq!(fetch(&cache, &journal, &options, &runs), vec!["run 1"]);
+        assert_eq!(fetch(&cache, &journal, &options, &runs), vec!["run 1"]);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(listed.load(Ordering::SeqCst), 1);
+
+        // Other options are another report, but the file list is reused
+        let explicit = PrintOptions::new().explicit();
+        assert_eq!(fetch(&cache, &journal, &explicit, &runs), vec!["run 2"]);
+        assert_eq!(listed.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_changed_file_reruns() {
+        let journal = TempJournal::new("changed");
+        let (cache, listed) = cache(8);
+        let runs = AtomicUsize::new(0);
+        let options = PrintOptions::new();
+
+        fetch(&cache, &journal, &options, &runs);
+        fs::write(&journal.file, "2024-01-01 a\n    a  $10\n    b\n").unwrap();
+        assert_eq!(fetch(&cache, &journal, &options, &runs), vec!["run 2"]);
+        // The file list is refreshed too, since an include may have changed
+        assert_eq!(listed.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_refresh_replaces_only_its_report() {
+        let journal = TempJournal::new("refresh");
+        let (cache, listed) = cache(8);
+        let runs = AtomicUsize::new(0);
+        let options = PrintOptions::new();
+        let explicit = PrintOptions::new().explicit();
+
+        fetch(&cache, &journal, &options, &runs);
+        fetch(&cache, &journal, &explicit, &runs);
+        let refreshed = with_refresh(|| fetch(&cache, &journal, &options, &runs));
+        assert_eq!(refreshed, vec!["run 3"]);
+        assert_eq!(listed.load(Ordering::SeqCst), 2);
+
+        // The new result is cached in place of the old, beside the other report
+        assert_eq!(cache.len(), 2);
+        assert_eq!(fetch(&cache, &journal, &options, &runs), vec!["run 3"]);
+        assert_eq!(fetch(&cache, &journal, &explicit, &runs), vec!["run 2"]);
+    }
+
+    #[test]
+    fn test_invalidate_forgets_reports_from_file() {
+        let journal = TempJournal::new("invalidate");
+        let other = TempJournal::new("invalidate-other");
+        let (cache, _) = cache(8);
+        let runs = AtomicUsize::new(0);
+        let options = PrintOptions::new();
+
+        fetch(&cache, &journal, &options, &runs);
+        fetch(&cache, &other, &options, &runs);
+        cache.invalidate(&journal.file);
+        assert_eq!(cache.len(), 1);
+
+        assert_eq!(fetch(&cache, &journal, &options, &runs), vec!["run 3"]);
+        assert_eq!(fetch(&cache, &other, &options, &runs), vec!["run 2"]);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let journal = TempJournal::new("lru");
+        let (cache, _) = cache(2);
+        let runs = AtomicUsize::new(0);
+        let first = PrintOptions::new();
+        let second = PrintOptions::new().explicit();
+        let third = PrintOptions::new().show_costs();
+
+        fetch(&cache, &journal, &first, &runs);
+        fetch(&cache, &journal, &second, &runs);
+        // Using the first report makes the second the least recently used
+        fetch(&cache, &journal, &first, &runs);
+        fetch(&cache, &journal, &third, &runs);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+        assert_eq!(fetch(&cache, &journal, &first, &runs), vec!["run 1"]);
+        assert_eq!(fetch(&cache, &journal, &second, &runs), vec!["run 4"]);
+    }
+
+    #[test]
+    fn test_failures_are_not_cached() {
+        let journal = TempJournal::new("failure");
+        let (cache, _) = cache(8);
+        let options = PrintOptions::new();
+
+        let result: Result<Vec<String>> =
+            cache.get_or_run("test", None, Some(journal.path()), &options, || {
+                Err(crate::HLedgerError::ParseError("boom".to_string()))
+            });
+        assert!(result.is_err());
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+//! Stopping hledger runs from another thread, e.g. when the user gives up on
+//! a slow report
+//!
+//! Runs started while [`with_cancel_token`] runs its closure watch the
+//! token: once it is cancelled, a running hledger is killed and the run
+//! fails with `HLedgerError::Cancelled`, and no further run starts. As with
+//! warnings, reports run by [`crate::run_reports`] on its worker threads
+//! watch the caller's token too.
+
+use crate::{HLedgerError, Result};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// A flag shared between the thread running reports and whoever may cancel
+/// them; clones share the flag
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the runs watching this token; there is no undoing it
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Run `f` with the hledger runs it starts on this thread watching `token`
+///
+/// Scopes nest: the innermost token is the one watched.
+pub fn with_cancel_token<T>(token: &CancelToken, f: impl FnOnce() -> T) -> T {
+    let outer = CURRENT.with(|c| c.borrow_mut().replace(token.clone()));
+    // Restores the outer token even if `f` panics
+    struct Restore(Option<Option<CancelToken>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            if let Some(outer) = self.0.take() {
+                CURRENT.with(|c| *c.borrow_mut() = outer);
+            }
+        }
+    }
+    let _restore = Restore(Some(outer));
+    f()
+}
+
+/// The token runs on this thread watch, if any
+pub(crate) fn current_token() -> Option<CancelToken> {
+    CURRENT.with(|c| c.borrow().clone())
+}
+
+/// Fail with `Cancelled` if `token` has been cancelled
+pub(crate) fn check_cancelled(token: Option<&CancelToken>) -> Result<()> {
+    match token {
+        Some(token) if token.is_cancelled() => Err(HLedgerError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_cancel_token_scopes() {
+        assert!(current_token().is_none());
+        let outer = CancelToken::new();
+        let inner = CancelToken::new();
+        with_cancel_token(&outer, || {
+            with_cancel_token(&inner, || {
+                inner.cancel();
+                assert!(current_token().unwrap().is_cancelled());
+            });
+            assert!(!current_token().unwrap().is_cancelled());
+        });
+        assert!(current_token().is_none());
+    }
+
+    #[test]
+    fn test_check_cancelled() {
+        let token = CancelToken::new();
+        assert!(check_cancelled(None).is_ok());
+        assert!(check_cancelled(Some(&token)).is_ok());
+        token.clone().cancel();
+        assert!(matches!(
+            check_cancelled(Some(&token)),
+            Err(HLedgerError::Cancelled)
+        ));
+    }
+}
@@ -1,10 +1,28 @@
+use crate::amount::{sum_amounts, MultiAmount};
+use crate::commands::balance::{get_balance, BalanceOptions, BalanceReport};
+use crate::commands::print::SourcePosition;
+use crate::config::{
+    add_journal_args, add_query_args, check_success, run_command, run_command_piped,
+};
+use crate::dates::validate_date_options;
+use crate::error::parse_location_header;
+use crate::interval::ReportInterval;
+use crate::list_mode::ListMode;
+use crate::query::Query;
+use crate::timeout::Timeout;
 use crate::{get_hledger_command, HLedgerError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::Command;
+use std::time::Duration;
 use ts_rs::TS;
 
 /// Options for the accounts command
 #[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct AccountsOptions {
     /// Show only accounts used by transactions
     pub used: bool,
@@ -20,7 +38,8 @@ pub struct AccountsOptions {
     pub positions: bool,
     /// Show as account directives, for use in journals
     pub directives: bool,
-    /// Find the first account matched by the pattern
+    /// Find the first account matched by this pattern (a case-insensitive
+    /// infix regex); see [`find_account`]
     pub find: Option<String>,
 
     /// Flat mode: omit N leading account name parts
@@ -31,6 +50,8 @@ pub struct AccountsOptions {
     pub begin: Option<String>,
     /// End date filter (exclusive: transactions before this date)
     pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
     /// Period expression
     pub period: Option<String>,
     /// Include only unmarked postings/transactions
@@ -43,8 +64,11 @@ pub struct AccountsOptions {
     pub real: bool,
     /// Show zero items (normally hidden)
     pub empty: bool,
+    /// Skip balance assertions, e.g. to see the report while one fails
+    pub ignore_assertions: bool,
     /// Query patterns to filter accounts
     pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
 }
 
 impl AccountsOptions {
@@ -112,6 +136,11 @@ impl AccountsOptions {
         self
     }
 
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
     pub fn period(mut self, period: impl Into<String>) -> Self {
         self.period = Some(period.into());
         self
@@ -142,15 +171,120 @@ impl AccountsOptions {
         self
     }
 
+    pub fn ignore_assertions(mut self) -> Self {
+        self.ignore_assertions = true;
+        self
+    }
+
     pub fn query(mut self, query: impl Into<String>) -> Self {
         self.queries.push(query.into());
         self
     }
 
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
     pub fn queries(mut self, queries: Vec<String>) -> Self {
         self.queries = queries;
         self
     }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+}
+
+/// An account with the details `--types` and `--positions` add
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct AccountInfo {
+    /// Full account name
+    pub name: String,
+    /// Declared or inferred type (with `types`)
+    pub account_type: Option<AccountType>,
+    /// Where the account directive is (with `positions`, for declared accounts)
+    pub declared_at: Option<SourcePosition>,
+}
+
+/// An account and its subaccounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct AccountNode {
+    /// Last part of the account name, e.g. `checking`
+    pub name: String,
+    /// Full account name, e.g. `assets:bank:checking`
+    pub full_name: String,
+    pub children: Vec<AccountNode>,
+}
+
+/// An account with its balance, as one balance report gives both
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct AccountWithBalance {
+    /// Full account name
+    pub name: String,
+    /// The account's own postings, without its subaccounts' (unless
+    /// `depth` clips them into it)
+    pub balance: MultiAmount,
+}
+
+/// An account and its subaccounts, with their balances
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct AccountBalanceNode {
+    /// Last part of the account name, e.g. `checking`
+    pub name: String,
+    /// Full account name, e.g. `assets:bank:checking`
+    pub full_name: String,
+    /// The account's own balance; zero for parents hledger didn't list
+    pub balance: MultiAmount,
+    /// `balance` plus the totals of all subaccounts
+    pub total: MultiAmount,
+    pub children: Vec<AccountBalanceNode>,
+}
+
+/// hledger account type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum AccountType {
+    Asset,
+    Liability,
+    Equity,
+    Revenue,
+    Expense,
+    /// Liquid assets, a subtype of `Asset`
+    Cash,
+    /// Currency conversion postings, a subtype of `Equity`
+    Conversion,
+    /// A type this library doesn't know
+    Other,
+}
+
+impl AccountType {
+    /// Read a type as hledger writes it: a code such as `A`, or a name
+    fn parse(text: &str) -> AccountType {
+        match text.to_lowercase().as_str() {
+            "a" | "asset" | "assets" => AccountType::Asset,
+            "l" | "liability" | "liabilities" => AccountType::Liability,
+            "e" | "equity" => AccountType::Equity,
+            "r" | "revenue" | "revenues" | "income" => AccountType::Revenue,
+            "x" | "expense" | "expenses" => AccountType::Expense,
+            "c" | "cash" => AccountType::Cash,
+            "v" | "conversion" => AccountType::Conversion,
+            _ => AccountType::Other,
+        }
+    }
 }
 
 /// Get account names from the hledger journal with specified options
@@ -159,11 +293,232 @@ pub fn get_accounts(
     journal_file: Option<&str>,
     options: &AccountsOptions,
 ) -> Result<Vec<String>> {
-    let mut cmd = get_hledger_command(hledger_path);
+    let mut cmd = accounts_command(hledger_path, journal_file, options)?;
+    account_names(&mut cmd, None, options.timeout)
+}
+
+/// Get account names for journal text that isn't on disk
+///
+/// The text is piped to `hledger -f -`, as for [`crate::get_balance_from_text`].
+pub fn get_accounts_from_text(
+    hledger_path: Option<&str>,
+    journal_text: &str,
+    options: &AccountsOptions,
+) -> Result<Vec<String>> {
+    let mut cmd = accounts_command(hledger_path, Some("-"), options)?;
+    account_names(&mut cmd, Some(journal_text), options.timeout)
+}
+
+/// Run an accounts command and return its non-empty lines
+fn account_names(
+    cmd: &mut Command,
+    stdin: Option<&str>,
+    timeout: Option<Timeout>,
+) -> Result<Vec<String>> {
+    let output = run_command_piped(cmd, stdin, timeout, |stdout| {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    })?;
+
+    check_success(cmd, output.status, &output.stderr)?;
+
+    let stdout = String::from_utf8(output.stdout?)?;
+    let accounts = stdout
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Ok(accounts)
+}
+
+/// Find the first account matching `pattern`, or `None` if there is none
+///
+/// Other filters in `options` still apply. hledger exits with an error when
+/// nothing matches; only that case becomes `Ok(None)`.
+pub fn find_account(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    pattern: &str,
+    options: &AccountsOptions,
+) -> Result<Option<String>> {
+    let options = AccountsOptions {
+        find: Some(pattern.to_string()),
+        ..options.clone()
+    };
+    match get_accounts(hledger_path, journal_file, &options) {
+        Ok(accounts) => Ok(accounts.into_iter().next()),
+        Err(HLedgerError::CommandFailed { stderr, .. }) if is_no_match(&stderr) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether hledger's error says `--find` matched nothing
+fn is_no_match(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    ["no account", "could not find", "not found", "no matching"]
+        .iter()
+        .any(|phrase| stderr.contains(phrase))
+}
+
+/// Get accounts with their types and declaration positions
+///
+/// Details are only filled in for the flags set in `options`: `types` for
+/// `account_type` and `positions` for `declared_at`.
+pub fn get_accounts_detailed(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &AccountsOptions,
+) -> Result<Vec<AccountInfo>> {
+    let mut cmd = accounts_command(hledger_path, journal_file, options)?;
+    let output = run_command(&mut cmd, options.timeout)?;
+
+    check_success(&cmd, output.status, &output.stderr)?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_account_line)
+        .collect())
+}
+
+/// Get accounts as a tree following the `:`-separated name hierarchy
+///
+/// Parents that hledger didn't list (e.g. `assets:bank` when only
+/// `assets:bank:checking` is used) get nodes of their own. Siblings keep the
+/// order hledger listed them in, which puts declared accounts first, in
+/// declaration order.
+pub fn get_account_tree(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &AccountsOptions,
+) -> Result<Vec<AccountNode>> {
+    let accounts = get_accounts_detailed(hledger_path, journal_file, options)?;
+    Ok(account_tree(accounts.iter().map(|info| info.name.as_str())))
+}
 
-    if let Some(file) = journal_file {
-        cmd.arg("-f").arg(file);
+/// Get every account with its balance from a single `hledger balance` run
+///
+/// Cheaper than [`get_accounts`] plus [`get_balance`] when both are
+/// needed. The report is run flat, with `--empty` so accounts that net to
+/// zero are listed too, and as a single period whatever the interval;
+/// dates, queries, valuation, `declared` and the other options apply as
+/// for [`get_balance`].
+pub fn get_accounts_with_balances(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &BalanceOptions,
+) -> Result<Vec<AccountWithBalance>> {
+    let options = BalanceOptions {
+        list_mode: Some(ListMode::Flat),
+        empty: true,
+        interval: ReportInterval::default(),
+        budget: None,
+        layout: None,
+        transpose: false,
+        ..options.clone()
+    };
+    match get_balance(hledger_path, journal_file, &options)? {
+        BalanceReport::Simple(report) => Ok(report
+            .accounts
+            .into_iter()
+            .map(|account| AccountWithBalance {
+                balance: sum_amounts(&account.amounts),
+                name: account.name,
+            })
+            .collect()),
+        BalanceReport::Periodic(_) => Err(HLedgerError::ParseError(
+            "Expected a single-period balance report".to_string(),
+        )),
     }
+}
+
+/// Get the account tree with balances from a single `hledger balance` run;
+/// see [`get_accounts_with_balances`] and [`account_balance_tree`]
+pub fn get_account_balance_tree(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &BalanceOptions,
+) -> Result<Vec<AccountBalanceNode>> {
+    let accounts = get_accounts_with_balances(hledger_path, journal_file, options)?;
+    Ok(account_balance_tree(&accounts))
+}
+
+/// Arrange `accounts` as a tree as [`get_account_tree`] does, each node
+/// totalling its subaccounts
+pub fn account_balance_tree(accounts: &[AccountWithBalance]) -> Vec<AccountBalanceNode> {
+    let balances: HashMap<&str, &MultiAmount> = accounts
+        .iter()
+        .map(|account| (account.name.as_str(), &account.balance))
+        .collect();
+    account_tree(accounts.iter().map(|account| account.name.as_str()))
+        .into_iter()
+        .map(|node| with_balances(node, &balances))
+        .collect()
+}
+
+fn with_balances(node: AccountNode, balances: &HashMap<&str, &MultiAmount>) -> AccountBalanceNode {
+    let children: Vec<AccountBalanceNode> = node
+        .children
+        .into_iter()
+        .map(|child| with_balances(child, balances))
+        .collect();
+    let balance = balances
+        .get(node.full_name.as_str())
+        .map(|balance| (*balance).clone())
+        .unwrap_or_default();
+    let mut total = balance.clone();
+    for child in &children {
+        total += &child.total;
+    }
+    AccountBalanceNode {
+        name: node.name,
+        full_name: node.full_name,
+        balance,
+        total,
+        children,
+    }
+}
+
+fn account_tree<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<AccountNode> {
+    let mut roots: Vec<AccountNode> = Vec::new();
+    for name in names {
+        let mut siblings = &mut roots;
+        let mut end = 0;
+        for part in name.split(':') {
+            end += part.len();
+            let index = match siblings.iter().position(|node| node.name == part) {
+                Some(index) => index,
+                None => {
+                    siblings.push(AccountNode {
+                        name: part.to_string(),
+                        full_name: name[..end].to_string(),
+                        children: Vec::new(),
+                    });
+                    siblings.len() - 1
+                }
+            };
+            siblings = &mut siblings[index].children;
+            end += 1;
+        }
+    }
+    roots
+}
+
+/// Build the hledger command for the accounts report
+fn accounts_command(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &AccountsOptions,
+) -> Result<Command> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
+    }
+
+    let mut cmd = get_hledger_command(hledger_path);
+
+    add_journal_args(&mut cmd, journal_file);
 
     cmd.arg("accounts");
 
@@ -189,8 +544,8 @@ pub fn get_accounts(
     if options.directives {
         cmd.arg("--directives");
     }
-    if let Some(pattern) = &options.find {
-        cmd.arg("--find").arg(pattern);
+    if options.find.is_some() {
+        cmd.arg("--find");
     }
     // Always use flat format (default)
     cmd.arg("--flat");
@@ -230,36 +585,66 @@ pub fn get_accounts(
     if options.empty {
         cmd.arg("--empty");
     }
-
-    // Add query patterns
-    for query in &options.queries {
-        cmd.arg(query);
+    if options.ignore_assertions {
+        cmd.arg("--ignore-assertions");
     }
 
-    let output = cmd.output().map_err(|e| {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            HLedgerError::HLedgerNotFound
-        } else {
-            HLedgerError::Io(e)
+    // Add query patterns; `--find` searches with the first one
+    match &options.find {
+        Some(pattern) => {
+            let mut queries = vec![pattern.clone()];
+            queries.extend(options.queries.iter().cloned());
+            add_query_args(&mut cmd, &queries);
         }
-    })?;
+        None => add_query_args(&mut cmd, &options.queries),
+    }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(HLedgerError::CommandFailed {
-            code: output.status.code().unwrap_or(-1),
-            stderr: stderr.to_string(),
-        });
+    Ok(cmd)
+}
+
+/// Split a line such as `assets:cash    ; type: C, declared at a.journal:3:1`
+/// into the account name and its details
+///
+/// The name ends at a tab, at two spaces or at the `;` starting the details;
+/// account names can contain single spaces.
+fn parse_account_line(line: &str) -> AccountInfo {
+    let line = line.trim();
+    let name_end = [line.find('\t'), line.find("  "), line.find(" ;")]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(line.len());
+    let (name, details) = line.split_at(name_end);
+
+    AccountInfo {
+        name: name.to_string(),
+        account_type: detail_value(details, "type:").map(AccountType::parse),
+        declared_at: detail_value(details, "declared at").and_then(parse_position),
     }
+}
 
-    let stdout = String::from_utf8(output.stdout)?;
-    let accounts = stdout
-        .lines()
-        .map(|line| line.trim().to_string())
-        .filter(|line| !line.is_empty())
-        .collect();
+const DETAIL_KEYS: [&str; 2] = ["type:", "declared at"];
+
+/// The text after `key`, up to the next detail key
+fn detail_value<'a>(details: &'a str, key: &str) -> Option<&'a str> {
+    let start = details.find(key)? + key.len();
+    let rest = &details[start..];
+    let end = DETAIL_KEYS
+        .iter()
+        .filter_map(|other| rest.find(other))
+        .min()
+        .unwrap_or(rest.len());
+    let value = rest[..end].trim_matches(|c: char| c.is_whitespace() || c == ',' || c == ';');
+    (!value.is_empty()).then_some(value)
+}
 
-    Ok(accounts)
+fn parse_position(text: &str) -> Option<SourcePosition> {
+    let (file, line, column, rest) = parse_location_header(text)?;
+    rest.is_empty().then(|| SourcePosition {
+        file: file.to_string(),
+        line,
+        column: column.unwrap_or(1),
+    })
 }
 
 #[cfg(test)]
@@ -269,6 +654,11 @@ mod tests {
     #[test]
     fn export_bindings() {
         AccountsOptions::export_all().unwrap();
+        AccountInfo::export_all().unwrap();
+        AccountType::export_all().unwrap();
+        AccountNode::export_all().unwrap();
+        AccountWithBalance::export_all().unwrap();
+        AccountBalanceNode::export_all().unwrap();
     }
 
     #[test]
@@ -310,4 +700,204 @@ mod tests {
 
         assert_eq!(options.find, Some("assets".to_string()));
     }
+
+    #[test]
+    fn test_find_pattern_is_first_query() {
+        let options = AccountsOptions::new().query("depth:2").find("-checking");
+        let cmd = accounts_command(None, None, &options).unwrap();
+        let args: Vec<_> = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+
+        assert!(args.contains(&"--find".to_string()));
+        let separator = args.iter().position(|arg| arg == "--").unwrap();
+        assert_eq!(args[separator + 1..], ["-checking", "depth:2"]);
+    }
+
+    #[test]
+    fn test_no_match_detection() {
+        assert!(is_no_match(
+            "hledger: Error: could not find an account matching xyz\n"
+        ));
+        assert!(!is_no_match(
+            "hledger: Error: this regular expression could not be compiled: (\n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_account_line_plain() {
+        let info = parse_account_line("assets:bank:checking");
+        assert_eq!(info.name, "assets:bank:checking");
+        assert_eq!(info.account_type, None);
+        assert_eq!(info.declared_at, None);
+
+        // Single spaces belong to the name
+        let info = parse_account_line("expenses:eating out    ; type: X");
+        assert_eq!(info.name, "expenses:eating out");
+        assert_eq!(info.account_type, Some(AccountType::Expense));
+    }
+
+    #[test]
+    fn test_parse_account_line_types() {
+        let types = [
+            ("A", AccountType::Asset),
+            ("L", AccountType::Liability),
+            ("E", AccountType::Equity),
+            ("R", AccountType::Revenue),
+            ("X", AccountType::Expense),
+            ("C", AccountType::Cash),
+            ("V", AccountType::Conversion),
+            ("Cash", AccountType::Cash),
+            ("Q", AccountType::Other),
+        ];
+        for (code, expected) in types {
+            let info = parse_account_line(&format!("acct  ; type: {}", code));
+            assert_eq!(info.account_type, Some(expected), "type {}", code);
+        }
+    }
+
+    #[test]
+    fn test_parse_account_line_positions() {
+        let info = parse_account_line("assets:cash\t; declared at /home/user/main.journal:3:1");
+        assert_eq!(info.name, "assets:cash");
+        assert_eq!(
+            info.declared_at,
+            Some(SourcePosition {
+                file: "/home/user/main.journal".to_string(),
+                line: 3,
+                column: 1,
+            })
+        );
+
+        let info = parse_account_line(r"liabilities  ; declared at C:\books\main.journal:7");
+        let position = info.declared_at.unwrap();
+        assert_eq!(position.file, r"C:\books\main.journal");
+        assert_eq!(position.line, 7);
+        assert_eq!(position.column, 1);
+    }
+
+    #[test]
+    fn test_parse_account_line_types_and_positions() {
+        let info = parse_account_line(
+            "assets:bank:checking   ; type: C, declared at /books/2024, main.journal:4:1",
+        );
+        assert_eq!(info.name, "assets:bank:checking");
+        assert_eq!(info.account_type, Some(AccountType::Cash));
+        let position = info.declared_at.unwrap();
+        assert_eq!(position.file, "/books/2024, main.journal");
+        assert_eq!(position.line, 4);
+
+        let info = parse_account_line("income  ; declared at a.journal:9:1; type: R");
+        assert_eq!(info.account_type, Some(AccountType::Revenue));
+        assert_eq!(info.declared_at.unwrap().line, 9);
+    }
+
+    fn shape(nodes: &[AccountNode]) -> Vec<String> {
+        let mut out = Vec::new();
+        for node in nodes {
+            out.push(node.full_name.clone());
+            out.extend(shape(&node.children));
+        }
+        out
+    }
+
+    #[test]
+    fn test_account_tree_synthesizes_parents() {
+        let tree = account_tree(["assets:bank:checking", "expenses:food"]);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].name, "assets");
+        assert_eq!(tree[0].children[0].name, "bank");
+        assert_eq!(tree[0].children[0].full_name, "assets:bank");
+        assert_eq!(
+            tree[0].children[0].children[0].full_name,
+            "assets:bank:checking"
+        );
+        assert!(tree[0].children[0].children[0].children.is_empty());
+        assert_eq!(
+            shape(&tree),
+            [
+                "assets",
+                "assets:bank",
+                "assets:bank:checking",
+                "expenses",
+                "expenses:food"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_account_tree_keeps_listed_order() {
+        // Declared accounts come first in hledger's output, so `expenses`
+        // stays ahead of `assets` and a parent listed after its child keeps
+        // the child's position
+        let tree = account_tree([
+            "expenses:rent",
+            "assets:cash",
+            "expenses:food",
+            "expenses",
+            "assets",
+        ]);
+        assert_eq!(
+            shape(&tree),
+            [
+                "expenses",
+                "expenses:rent",
+                "expenses:food",
+                "assets",
+                "assets:cash"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_account_balance_tree_totals_subaccounts() {
+        use rust_decimal::Decimal;
+
+        let dollars = |quantity: i64| {
+            let mut amount = MultiAmount::new();
+            amount.add_quantity("$", Decimal::from(quantity));
+            amount
+        };
+        let account = |name: &str, balance: MultiAmount| AccountWithBalance {
+            name: name.to_string(),
+            balance,
+        };
+        let mut euros = MultiAmount::new();
+        euros.add_quantity("EUR", Decimal::from(5));
+        let accounts = [
+            account("assets", dollars(1)),
+            account("assets:bank:checking", dollars(100)),
+            account("assets:bank:savings", euros.clone()),
+            account("assets:cash", MultiAmount::new()),
+            account("expenses:food", dollars(-40)),
+        ];
+
+        let tree = account_balance_tree(&accounts);
+        assert_eq!(tree.len(), 2);
+        let assets = &tree[0];
+        assert_eq!(assets.balance, dollars(1));
+        assert_eq!(assets.total, dollars(101) + euros.clone());
+        // Not listed by hledger, so no balance of its own
+        let bank = &assets.children[0];
+        assert_eq!(bank.full_name, "assets:bank");
+        assert!(bank.balance.is_zero());
+        assert_eq!(bank.total, dollars(100) + euros);
+        assert!(assets.children[1].total.is_zero());
+        assert_eq!(tree[1].total, dollars(-40));
+    }
+
+    #[test]
+    fn test_account_tree_same_name_under_different_parents() {
+        let tree = account_tree(["assets:cash", "liabilities:cash", "assets:cash:wallet"]);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].children.len(), 1);
+        assert_eq!(
+            tree[0].children[0].children[0].full_name,
+            "assets:cash:wallet"
+        );
+        assert_eq!(tree[1].children[0].full_name, "liabilities:cash");
+        assert!(account_tree(std::iter::empty()).is_empty());
+    }
 }
@@ -1,6 +1,20 @@
-use crate::{get_hledger_command, HLedgerError, Result};
+use crate::commands::json::{JsonAmounts, JsonPeriodicReport};
+use crate::config::{add_journal_args, add_query_args, run_json_command, run_json_command_piped};
+use crate::csv_report::{run_csv_command, CsvReport};
+use crate::dates::validate_date_options;
+use crate::interval::{LegacyInterval, ReportInterval};
+use crate::list_mode::{LegacyListMode, ListMode};
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, Result};
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
+use serde::de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::process::Command;
+use std::time::Duration;
 use ts_rs::TS;
 
 /// Custom serde module for Decimal to/from string
@@ -26,7 +40,11 @@ mod decimal_string_serde {
 
 /// Options for the balance command
 #[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+// Derived as inherent functions, which the trait impls below wrap to fold in
+// the deprecated fields
+#[serde(rename_all = "camelCase", remote = "Self")]
+#[ts(rename_all = "camelCase")]
 pub struct BalanceOptions {
     // Calculation modes (mutually exclusive)
     /// Show sum of posting amounts (default)
@@ -49,10 +67,9 @@ pub struct BalanceOptions {
     pub historical: bool,
 
     // List/tree modes
-    /// Show accounts as flat list (default)
-    pub flat: bool,
-    /// Show accounts as tree
-    pub tree: bool,
+    /// Show accounts as a flat list or a tree; `None` leaves it to hledger
+    /// (flat, unless a config file says otherwise)
+    pub list_mode: Option<ListMode>,
     /// Omit N leading account name parts
     pub drop: Option<u32>,
     /// Include non-parent declared accounts
@@ -87,24 +104,17 @@ pub struct BalanceOptions {
     pub layout: Option<String>,
 
     // Period selection
-    /// Daily periods
-    pub daily: bool,
-    /// Weekly periods
-    pub weekly: bool,
-    /// Monthly periods
-    pub monthly: bool,
-    /// Quarterly periods
-    pub quarterly: bool,
-    /// Yearly periods
-    pub yearly: bool,
-    /// Custom period
-    pub period: Option<String>,
+    /// How the report is split into periods
+    #[serde(default)]
+    pub interval: ReportInterval,
 
     // Date filters
     /// Begin date (inclusive: transactions on or after this date)
     pub begin: Option<String>,
     /// End date (exclusive: transactions before this date)
     pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
 
     // Other filters
     /// Limit depth of accounts shown
@@ -119,6 +129,8 @@ pub struct BalanceOptions {
     pub real: bool,
     /// Show zero items
     pub empty: bool,
+    /// Skip balance assertions, e.g. to see the report while one fails
+    pub ignore_assertions: bool,
 
     // Valuation options
     /// Convert to cost basis
@@ -132,11 +144,32 @@ pub struct BalanceOptions {
 
     // Query patterns
     pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+impl Serialize for BalanceOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        BalanceOptions::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BalanceOptions {
+    /// Reads the options, folding the deprecated interval and `tree`/`flat`
+    /// fields into `interval` and `list_mode`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        let mut options = BalanceOptions::deserialize(&json).map_err(de::Error::custom)?;
+        options.interval = LegacyInterval::resolve(options.interval, &json)?;
+        options.list_mode = LegacyListMode::resolve(options.list_mode, &json)?;
+        Ok(options)
+    }
 }
 
 /// Amount representation in balance reports
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct Amount {
     /// Commodity/currency symbol
     pub commodity: String,
@@ -149,9 +182,13 @@ pub struct Amount {
 }
 
 /// Price information for amounts
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct Price {
+    /// Whether the price is per unit (`@`) or for the whole amount (`@@`)
+    pub kind: PriceKind,
     /// Price commodity
     pub commodity: String,
     /// Price quantity
@@ -160,9 +197,21 @@ pub struct Price {
     pub quantity: Decimal,
 }
 
+/// Kind of price attached to an amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum PriceKind {
+    /// Price per unit, written `@`
+    Unit,
+    /// Price for the whole amount, written `@@`
+    Total,
+}
+
 /// Account information in balance report
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct BalanceAccount {
     /// Full account name
     pub name: String,
@@ -176,7 +225,9 @@ pub struct BalanceAccount {
 
 /// Simple balance report (single period)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct SimpleBalance {
     /// List of accounts with their balances
     pub accounts: Vec<BalanceAccount>,
@@ -186,22 +237,30 @@ pub struct SimpleBalance {
 
 /// Period date range
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct PeriodDate {
-    /// Start date (ISO format)
-    pub start: String,
-    /// End date (ISO format)
-    pub end: String,
+    /// Start date (ISO format, inclusive)
+    #[ts(type = "string")]
+    pub start: NaiveDate,
+    /// End date (ISO format, exclusive)
+    #[ts(type = "string")]
+    pub end: NaiveDate,
 }
 
 /// Row in periodic balance report
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct PeriodicBalanceRow {
     /// Account name
     pub account: String,
-    /// Display name
+    /// Display name (leaf part of the account in tree mode)
     pub display_name: String,
+    /// Indentation level (for tree display)
+    pub indent: u32,
     /// Amounts for each period
     pub amounts: Vec<Vec<Amount>>,
     /// Row total (if requested)
@@ -212,7 +271,9 @@ pub struct PeriodicBalanceRow {
 
 /// Periodic balance report (multiple periods)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct PeriodicBalance {
     /// Period date ranges
     pub dates: Vec<PeriodDate>,
@@ -223,9 +284,13 @@ pub struct PeriodicBalance {
 }
 
 /// Unified balance report that can be either simple or periodic
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
-#[serde(untagged)]
+///
+/// Serialized with a `type` of `simple` or `periodic` next to the report's
+/// fields. Reports serialized before the tag was added, with no `type`,
+/// still deserialize.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type")]
 pub enum BalanceReport {
     /// Simple single-period balance
     Simple(SimpleBalance),
@@ -233,6 +298,41 @@ pub enum BalanceReport {
     Periodic(PeriodicBalance),
 }
 
+impl<'de> Deserialize<'de> for BalanceReport {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        /// Tagged as now, or by the lowercase names the tags once had
+        #[derive(Deserialize)]
+        #[serde(tag = "type")]
+        enum Tagged {
+            #[serde(alias = "simple")]
+            Simple(SimpleBalance),
+            #[serde(alias = "periodic")]
+            Periodic(PeriodicBalance),
+        }
+
+        /// The tagged form, or the untagged one told apart by its fields
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum AnyForm {
+            Tagged(Tagged),
+            Simple(SimpleBalance),
+            Periodic(PeriodicBalance),
+        }
+
+        Ok(match AnyForm::deserialize(deserializer)? {
+            AnyForm::Tagged(Tagged::Simple(report)) | AnyForm::Simple(report) => {
+                BalanceReport::Simple(report)
+            }
+            AnyForm::Tagged(Tagged::Periodic(report)) | AnyForm::Periodic(report) => {
+                BalanceReport::Periodic(report)
+            }
+        })
+    }
+}
+
 // Implementation for builder pattern
 impl BalanceOptions {
     pub fn new() -> Self {
@@ -241,32 +341,37 @@ impl BalanceOptions {
 
     // Period options
     pub fn daily(mut self) -> Self {
-        self.daily = true;
+        self.interval = ReportInterval::Daily;
         self
     }
 
     pub fn weekly(mut self) -> Self {
-        self.weekly = true;
+        self.interval = ReportInterval::Weekly;
         self
     }
 
     pub fn monthly(mut self) -> Self {
-        self.monthly = true;
+        self.interval = ReportInterval::Monthly;
         self
     }
 
     pub fn quarterly(mut self) -> Self {
-        self.quarterly = true;
+        self.interval = ReportInterval::Quarterly;
         self
     }
 
     pub fn yearly(mut self) -> Self {
-        self.yearly = true;
+        self.interval = ReportInterval::Yearly;
         self
     }
 
     pub fn period(mut self, period: impl Into<String>) -> Self {
-        self.period = Some(period.into());
+        self.interval = ReportInterval::Custom(period.into());
+        self
+    }
+
+    pub fn interval(mut self, interval: ReportInterval) -> Self {
+        self.interval = interval;
         self
     }
 
@@ -283,14 +388,12 @@ impl BalanceOptions {
 
     // Display modes
     pub fn tree(mut self) -> Self {
-        self.tree = true;
-        self.flat = false;
+        self.list_mode = Some(ListMode::Tree);
         self
     }
 
     pub fn flat(mut self) -> Self {
-        self.flat = true;
-        self.tree = false;
+        self.list_mode = Some(ListMode::Flat);
         self
     }
 
@@ -321,6 +424,11 @@ impl BalanceOptions {
         self
     }
 
+    pub fn ignore_assertions(mut self) -> Self {
+        self.ignore_assertions = true;
+        self
+    }
+
     pub fn begin(mut self, date: impl Into<String>) -> Self {
         self.begin = Some(date.into());
         self
@@ -331,11 +439,21 @@ impl BalanceOptions {
         self
     }
 
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
     pub fn query(mut self, query: impl Into<String>) -> Self {
         self.queries.push(query.into());
         self
     }
 
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
     pub fn queries(mut self, queries: Vec<String>) -> Self {
         self.queries = queries;
         self
@@ -356,6 +474,11 @@ impl BalanceOptions {
         self.sort_amount = true;
         self
     }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
 }
 
 /// Get balance report from hledger
@@ -364,36 +487,87 @@ pub fn get_balance(
     journal_file: Option<&str>,
     options: &BalanceOptions,
 ) -> Result<BalanceReport> {
-    let mut cmd = get_hledger_command(hledger_path);
+    let mut cmd = balance_command(hledger_path, journal_file, options, "json")?;
+    balance_report(run_json_command(&mut cmd, options.timeout)?, options)
+}
+
+/// Get balance report for journal text that isn't on disk, e.g. edits to
+/// preview before saving
+///
+/// The text is piped to `hledger -f -`; see [`crate::overlay_journal`] for
+/// previewing pending transactions on top of a saved journal.
+pub fn get_balance_from_text(
+    hledger_path: Option<&str>,
+    journal_text: &str,
+    options: &BalanceOptions,
+) -> Result<BalanceReport> {
+    let mut cmd = balance_command(hledger_path, Some("-"), options, "json")?;
+    let json = run_json_command_piped(&mut cmd, Some(journal_text), options.timeout)?;
+    balance_report(json, options)
+}
+
+/// Convert hledger's balance JSON and apply the options hledger leaves to us
+fn balance_report(json: JsonBalanceReport, options: &BalanceOptions) -> Result<BalanceReport> {
+    match json.into_report()? {
+        BalanceReport::Periodic(mut periodic) => {
+            if options.list_mode == Some(ListMode::Tree) {
+                apply_tree_layout(&mut periodic.rows);
+            }
+            if options.no_total {
+                periodic.totals = None;
+            }
+            Ok(BalanceReport::Periodic(periodic))
+        }
+        report => Ok(report),
+    }
+}
+
+/// Get the balance report as hledger's JSON, untouched
+///
+/// For fields the typed [`BalanceReport`] doesn't model yet. The command is built
+/// exactly as for [`get_balance`].
+pub fn get_balance_raw(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &BalanceOptions,
+) -> Result<serde_json::Value> {
+    let mut cmd = balance_command(hledger_path, journal_file, options, "json")?;
+    run_json_command(&mut cmd, options.timeout)
+}
+
+/// Get the balance report as hledger's CSV (`-O csv`), one row per account
+///
+/// The command is built exactly as for [`get_balance`].
+pub fn get_balance_csv(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &BalanceOptions,
+) -> Result<CsvReport> {
+    let mut cmd = balance_command(hledger_path, journal_file, options, "csv")?;
+    run_csv_command(&mut cmd, options.timeout)
+}
 
-    if let Some(file) = journal_file {
-        cmd.arg("-f").arg(file);
+/// Build the hledger command for the balance report
+fn balance_command(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &BalanceOptions,
+    output_format: &str,
+) -> Result<Command> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
     }
 
+    let mut cmd = get_hledger_command(hledger_path);
+
+    add_journal_args(&mut cmd, journal_file);
+
     cmd.arg("balance");
 
-    // Always output JSON
-    cmd.arg("--output-format").arg("json");
+    cmd.arg("--output-format").arg(output_format);
 
     // Add period flags
-    if options.daily {
-        cmd.arg("--daily");
-    }
-    if options.weekly {
-        cmd.arg("--weekly");
-    }
-    if options.monthly {
-        cmd.arg("--monthly");
-    }
-    if options.quarterly {
-        cmd.arg("--quarterly");
-    }
-    if options.yearly {
-        cmd.arg("--yearly");
-    }
-    if let Some(period) = &options.period {
-        cmd.arg("--period").arg(period);
-    }
+    options.interval.add_args(&mut cmd);
 
     // Calculation modes
     if options.valuechange {
@@ -418,11 +592,7 @@ pub fn get_balance(
     }
 
     // List/tree modes
-    if options.tree {
-        cmd.arg("--tree");
-    } else {
-        cmd.arg("--flat");
-    }
+    ListMode::add_args(options.list_mode, &mut cmd);
 
     if let Some(n) = options.drop {
         cmd.arg(format!("--drop={}", n));
@@ -476,6 +646,9 @@ pub fn get_balance(
     if options.empty {
         cmd.arg("--empty");
     }
+    if options.ignore_assertions {
+        cmd.arg("--ignore-assertions");
+    }
 
     // Date filters
     if let Some(begin) = &options.begin {
@@ -514,322 +687,143 @@ pub fn get_balance(
     }
 
     // Query patterns
-    for query in &options.queries {
-        cmd.arg(query);
-    }
-
-    let output = cmd.output().map_err(|e| {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            HLedgerError::HLedgerNotFound
-        } else {
-            HLedgerError::Io(e)
-        }
-    })?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(HLedgerError::CommandFailed {
-            code: output.status.code().unwrap_or(-1),
-            stderr: stderr.to_string(),
-        });
-    }
+    add_query_args(&mut cmd, &options.queries);
 
-    let stdout = String::from_utf8(output.stdout)?;
-
-    // Parse the JSON output
-    let json_value: serde_json::Value = serde_json::from_str(&stdout)?;
-
-    // Determine if it's a periodic or simple balance based on structure
-    let report = if json_value.is_array() {
-        // Simple balance format: [accounts, totals]
-        parse_simple_balance(&json_value)?
-    } else if json_value.is_object() && json_value.get("prDates").is_some() {
-        // Periodic balance format with prDates, prRows, prTotals
-        parse_periodic_balance(&json_value)?
-    } else {
-        return Err(HLedgerError::ParseError(
-            "Unknown balance report format".to_string(),
-        ));
-    };
-
-    Ok(report)
+    Ok(cmd)
 }
 
-/// Parse simple balance format
-fn parse_simple_balance(value: &serde_json::Value) -> Result<BalanceReport> {
-    let array = value
-        .as_array()
-        .ok_or_else(|| HLedgerError::ParseError("Expected array for simple balance".to_string()))?;
-
-    if array.len() != 2 {
-        return Err(HLedgerError::ParseError(
-            "Simple balance should have 2 elements".to_string(),
-        ));
-    }
-
-    let accounts_json = &array[0];
-    let totals_json = &array[1];
-
-    let mut accounts = Vec::new();
-    if let Some(accounts_array) = accounts_json.as_array() {
-        for account_json in accounts_array {
-            let account = parse_balance_account(account_json)?;
-            accounts.push(account);
-        }
-    }
-
-    let totals = parse_amounts(totals_json)?;
-
-    Ok(BalanceReport::Simple(SimpleBalance { accounts, totals }))
-}
-
-/// Parse periodic balance format
-fn parse_periodic_balance(value: &serde_json::Value) -> Result<BalanceReport> {
-    // Parse dates
-    let dates_json = value.get("prDates").ok_or_else(|| {
-        HLedgerError::ParseError("Missing prDates in periodic balance".to_string())
-    })?;
-
-    let mut dates = Vec::new();
-    if let Some(dates_array) = dates_json.as_array() {
-        for date_pair in dates_array {
-            if let Some(pair) = date_pair.as_array() {
-                if pair.len() == 2 {
-                    let start = extract_date_from_tagged_value(&pair[0]);
-                    let end = extract_date_from_tagged_value(&pair[1]);
-                    dates.push(PeriodDate { start, end });
-                }
+/// Compute tree indentation and leaf display names for periodic rows
+///
+/// hledger only reports full account names in periodic JSON, so the tree is
+/// rebuilt from the rows themselves: each row is indented one level below the
+/// nearest ancestor that is also shown, and its display name is the remaining
+/// part of the account name. Elided (boring) parents therefore stay joined to
+/// their child, e.g. `bank:checking` under `assets`.
+pub(crate) fn apply_tree_layout(rows: &mut [PeriodicBalanceRow]) {
+    let accounts: Vec<String> = rows.iter().map(|row| row.account.clone()).collect();
+    let mut indents: Vec<u32> = Vec::with_capacity(rows.len());
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        let parent = (0..i)
+            .rev()
+            .find(|&j| row.account.starts_with(&format!("{}:", accounts[j])));
+
+        match parent {
+            Some(j) => {
+                row.indent = indents[j] + 1;
+                row.display_name = row.account[accounts[j].len() + 1..].to_string();
+            }
+            None => {
+                row.indent = 0;
+                row.display_name = row.account.clone();
             }
         }
+        indents.push(row.indent);
     }
+}
 
-    // Parse rows
-    let rows_json = value.get("prRows").ok_or_else(|| {
-        HLedgerError::ParseError("Missing prRows in periodic balance".to_string())
-    })?;
+/// hledger's balance JSON, read straight off its stdout
+///
+/// A single-period report is `[accounts, totals]`; a periodic one is an
+/// object with `prDates`, `prRows` and `prTotals`.
+pub(crate) enum JsonBalanceReport {
+    Simple(Vec<JsonBalanceAccount>, JsonAmounts),
+    Periodic(JsonPeriodicReport),
+}
 
-    let mut rows = Vec::new();
-    if let Some(rows_array) = rows_json.as_array() {
-        for row_json in rows_array {
-            let row = parse_periodic_row(row_json)?;
-            rows.push(row);
-        }
-    }
+impl<'de> Deserialize<'de> for JsonBalanceReport {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ReportVisitor;
 
-    // Parse totals
-    let totals = if let Some(totals_json) = value.get("prTotals") {
-        Some(parse_periodic_row(totals_json)?)
-    } else {
-        None
-    };
+        impl<'de> Visitor<'de> for ReportVisitor {
+            type Value = JsonBalanceReport;
 
-    Ok(BalanceReport::Periodic(PeriodicBalance {
-        dates,
-        rows,
-        totals,
-    }))
-}
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a simple or periodic balance report")
+            }
 
-/// Parse a balance account entry
-fn parse_balance_account(value: &serde_json::Value) -> Result<BalanceAccount> {
-    let array = value
-        .as_array()
-        .ok_or_else(|| HLedgerError::ParseError("Account should be an array".to_string()))?;
-
-    if array.len() < 4 {
-        return Err(HLedgerError::ParseError(
-            "Account array should have at least 4 elements".to_string(),
-        ));
-    }
-
-    let name = array[0].as_str().unwrap_or("").to_string();
-    let display_name = array[1].as_str().unwrap_or("").to_string();
-    let indent = array[2].as_u64().unwrap_or(0) as u32;
-    let amounts = parse_amounts(&array[3])?;
-
-    Ok(BalanceAccount {
-        name,
-        display_name,
-        indent,
-        amounts,
-    })
-}
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<JsonBalanceReport, A::Error> {
+                let invalid = || de::Error::custom("Simple balance should have 2 elements");
+                let accounts = seq.next_element()?.ok_or_else(invalid)?;
+                let totals = seq.next_element()?.ok_or_else(invalid)?;
+                if seq.next_element::<IgnoredAny>()?.is_some() {
+                    return Err(invalid());
+                }
+                Ok(JsonBalanceReport::Simple(accounts, totals))
+            }
 
-/// Parse amounts from JSON
-pub(crate) fn parse_amounts(value: &serde_json::Value) -> Result<Vec<Amount>> {
-    let mut amounts = Vec::new();
-
-    if let Some(amounts_array) = value.as_array() {
-        for amount_json in amounts_array {
-            if let Some(amount_obj) = amount_json.as_object() {
-                let commodity = amount_obj
-                    .get("acommodity")
-                    .and_then(|c| c.as_str())
-                    .unwrap_or("")
-                    .to_string();
-
-                let quantity = if let Some(q) = amount_obj.get("aquantity") {
-                    parse_decimal_from_json(q)?
-                } else {
-                    Decimal::ZERO
-                };
-
-                let price = if let Some(price_obj) = amount_obj.get("aprice") {
-                    parse_price(price_obj)?
-                } else {
-                    None
-                };
-
-                amounts.push(Amount {
-                    commodity,
-                    quantity,
-                    price,
-                });
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> std::result::Result<JsonBalanceReport, A::Error> {
+                let report = Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(JsonBalanceReport::Periodic(report))
             }
         }
-    }
-
-    Ok(amounts)
-}
 
-/// Parse price from JSON
-fn parse_price(value: &serde_json::Value) -> Result<Option<Price>> {
-    if let Some(price_obj) = value.as_object() {
-        // Handle the tagged price format with "contents" field
-        if let Some(amount_obj) = price_obj.get("contents").and_then(|a| a.as_object()) {
-            let commodity = amount_obj
-                .get("acommodity")
-                .and_then(|c| c.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let quantity = if let Some(q) = amount_obj.get("aquantity") {
-                parse_decimal_from_json(q)?
-            } else {
-                Decimal::ZERO
-            };
-
-            return Ok(Some(Price {
-                commodity,
-                quantity,
-            }));
-        }
-        // Legacy format fallback
-        if let Some(amount_obj) = price_obj.get("priceAmount").and_then(|a| a.as_object()) {
-            let commodity = amount_obj
-                .get("acommodity")
-                .and_then(|c| c.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let quantity = if let Some(q) = amount_obj.get("aquantity") {
-                parse_decimal_from_json(q)?
-            } else {
-                Decimal::ZERO
-            };
-
-            return Ok(Some(Price {
-                commodity,
-                quantity,
-            }));
-        }
+        deserializer.deserialize_any(ReportVisitor)
     }
-    Ok(None)
 }
 
-/// Parse a periodic balance row
-fn parse_periodic_row(value: &serde_json::Value) -> Result<PeriodicBalanceRow> {
-    let obj = value
-        .as_object()
-        .ok_or_else(|| HLedgerError::ParseError("Periodic row should be an object".to_string()))?;
-
-    // Extract account name
-    let account = obj
-        .get("prrName")
-        .and_then(|n| n.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let display_name = account.clone(); // For now, use same as account name
-
-    // Parse period amounts (prrAmounts is an array of arrays of amounts)
-    let mut amounts = Vec::new();
-    if let Some(amounts_array) = obj.get("prrAmounts").and_then(|a| a.as_array()) {
-        for period_amounts in amounts_array {
-            amounts.push(parse_amounts(period_amounts)?);
-        }
+impl JsonBalanceReport {
+    pub(crate) fn into_report(self) -> Result<BalanceReport> {
+        Ok(match self {
+            JsonBalanceReport::Simple(accounts, totals) => BalanceReport::Simple(SimpleBalance {
+                accounts: accounts
+                    .into_iter()
+                    .map(JsonBalanceAccount::into_account)
+                    .collect::<Result<_>>()?,
+                totals: totals.into_amounts()?,
+            }),
+            JsonBalanceReport::Periodic(report) => {
+                BalanceReport::Periodic(report.into_periodic_balance()?)
+            }
+        })
     }
-
-    // Parse total
-    let total = if let Some(total_amounts) = obj.get("prrTotal") {
-        Some(parse_amounts(total_amounts)?)
-    } else {
-        None
-    };
-
-    // Parse average
-    let average = if let Some(avg_amounts) = obj.get("prrAverage") {
-        Some(parse_amounts(avg_amounts)?)
-    } else {
-        None
-    };
-
-    Ok(PeriodicBalanceRow {
-        account,
-        display_name,
-        amounts,
-        total,
-        average,
-    })
 }
 
-/// Extract date from tagged value format
-pub(crate) fn extract_date_from_tagged_value(value: &serde_json::Value) -> String {
-    if let Some(obj) = value.as_object() {
-        if let Some(contents) = obj.get("contents").and_then(|c| c.as_str()) {
-            return contents.to_string();
-        }
-    }
-    // Fallback to empty string if format is unexpected
-    "".to_string()
-}
+/// A simple balance report's account: `[name, display name, indent, amounts]`
+#[derive(Deserialize)]
+pub(crate) struct JsonBalanceAccount(String, String, u32, JsonAmounts);
 
-/// Parse decimal from JSON value
-fn parse_decimal_from_json(value: &serde_json::Value) -> Result<Decimal> {
-    if let Some(obj) = value.as_object() {
-        // Handle decimal object format
-        if let Some(mantissa) = obj.get("decimalMantissa").and_then(|m| m.as_i64()) {
-            let places = obj
-                .get("decimalPlaces")
-                .and_then(|p| p.as_u64())
-                .unwrap_or(0) as u32;
-            return Ok(Decimal::new(mantissa, places));
-        }
-    } else if let Some(num) = value.as_f64() {
-        // Handle simple number
-        return Decimal::from_f64_retain(num)
-            .ok_or_else(|| HLedgerError::ParseError("Invalid decimal number".to_string()));
-    } else if let Some(s) = value.as_str() {
-        // Handle string number
-        return s
-            .parse()
-            .map_err(|_| HLedgerError::ParseError("Invalid decimal string".to_string()));
-    }
-
-    Err(HLedgerError::ParseError(
-        "Unknown decimal format".to_string(),
-    ))
+impl JsonBalanceAccount {
+    fn into_account(self) -> Result<BalanceAccount> {
+        let JsonBalanceAccount(name, display_name, indent, amounts) = self;
+        Ok(BalanceAccount {
+            name,
+            display_name,
+            indent,
+            amounts: amounts.into_amounts()?,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::json::from_value;
+    use crate::HLedgerError;
+
+    fn parse(value: &serde_json::Value) -> Result<BalanceReport> {
+        from_value::<JsonBalanceReport>(value)?.into_report()
+    }
+
+    fn periodic(value: &serde_json::Value) -> Result<PeriodicBalance> {
+        from_value::<JsonPeriodicReport>(value)?.into_periodic_balance()
+    }
 
     #[test]
     fn export_bindings() {
         BalanceOptions::export_all().unwrap();
         Amount::export_all().unwrap();
         Price::export_all().unwrap();
+        PriceKind::export_all().unwrap();
         BalanceAccount::export_all().unwrap();
         SimpleBalance::export_all().unwrap();
         PeriodDate::export_all().unwrap();
@@ -838,6 +832,99 @@ mod tests {
         BalanceReport::export_all().unwrap();
     }
 
+    #[test]
+    fn test_options_use_camel_case_keys() {
+        let options = BalanceOptions::new().row_total().no_total();
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(json["rowTotal"], true);
+        assert_eq!(json["noTotal"], true);
+        assert_eq!(json["uncheckedDates"], false);
+        assert!(json.get("row_total").is_none());
+
+        let options: BalanceOptions = serde_json::from_value(json).unwrap();
+        assert!(options.row_total);
+        assert!(options.no_total);
+
+        let account = BalanceAccount {
+            name: "assets:bank".to_string(),
+            display_name: "bank".to_string(),
+            indent: 1,
+            amounts: vec![],
+        };
+        let json = serde_json::to_value(&account).unwrap();
+        assert_eq!(json["displayName"], "bank");
+    }
+
+    #[test]
+    fn test_ignore_assertions_arg() {
+        let options = BalanceOptions::new().ignore_assertions();
+        let cmd = balance_command(None, None, &options, "json").unwrap();
+        assert!(cmd.get_args().any(|arg| arg == "--ignore-assertions"));
+
+        let cmd = balance_command(None, None, &BalanceOptions::new(), "json").unwrap();
+        assert!(!cmd.get_args().any(|arg| arg == "--ignore-assertions"));
+    }
+
+    #[test]
+    fn test_list_mode_flag_only_when_chosen() {
+        let args = |options: &BalanceOptions| -> Vec<String> {
+            balance_command(None, None, options, "json")
+                .unwrap()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        };
+        let default = args(&BalanceOptions::new());
+        assert!(!default.iter().any(|arg| arg == "--flat" || arg == "--tree"));
+        assert!(args(&BalanceOptions::new().flat()).contains(&"--flat".to_string()));
+        assert!(args(&BalanceOptions::new().tree()).contains(&"--tree".to_string()));
+
+        // Older frontends send both booleans, with `flat` set by default
+        let mut json = serde_json::to_value(BalanceOptions::new()).unwrap();
+        json["tree"] = true.into();
+        json["flat"] = false.into();
+        let options: BalanceOptions = serde_json::from_value(json).unwrap();
+        assert_eq!(options.list_mode, Some(ListMode::Tree));
+        assert!(args(&options).contains(&"--tree".to_string()));
+        let json = serde_json::to_value(&options).unwrap();
+        assert!(json.get("tree").is_none());
+        assert_eq!(json["listMode"], "Tree");
+    }
+
+    #[test]
+    fn test_options_accept_legacy_interval_fields() {
+        // As sent by a frontend written before `interval` existed
+        let mut json = serde_json::to_value(BalanceOptions::new().row_total()).unwrap();
+        json.as_object_mut().unwrap().remove("interval");
+        json["weekly"] = true.into();
+        let options: BalanceOptions = serde_json::from_value(json).unwrap();
+        assert_eq!(options.interval, ReportInterval::Weekly);
+        assert!(options.row_total);
+
+        // Folded in as they are read, so the options survive a round trip
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(json["interval"]["type"], "Weekly");
+        let roundtrip: BalanceOptions = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip.interval, ReportInterval::Weekly);
+
+        let cmd = balance_command(None, None, &options, "json").unwrap();
+        let args: Vec<_> = cmd.get_args().collect();
+        assert!(args.contains(&std::ffi::OsStr::new("--weekly")));
+
+        // The new field wins, and the old ones are never written back
+        let mut json = serde_json::to_value(BalanceOptions::new().quarterly()).unwrap();
+        json["daily"] = true.into();
+        let options: BalanceOptions = serde_json::from_value(json).unwrap();
+        let cmd = balance_command(None, None, &options, "json").unwrap();
+        let args: Vec<_> = cmd.get_args().collect();
+        assert!(args.contains(&std::ffi::OsStr::new("--quarterly")));
+        assert!(!args.contains(&std::ffi::OsStr::new("--daily")));
+
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(json["interval"]["type"], "Quarterly");
+        assert!(json.get("daily").is_none());
+    }
+
     #[test]
     fn test_balance_options_builder() {
         let options = BalanceOptions::new()
@@ -848,9 +935,8 @@ mod tests {
             .average()
             .query("expenses");
 
-        assert!(options.monthly);
-        assert!(options.tree);
-        assert!(!options.flat);
+        assert_eq!(options.interval, ReportInterval::Monthly);
+        assert_eq!(options.list_mode, Some(ListMode::Tree));
         assert_eq!(options.depth, Some(2));
         assert!(options.row_total);
         assert!(options.average);
@@ -858,46 +944,175 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_decimal() {
-        // Test decimal object format
+    fn test_period_date_serializes_as_iso_string() {
+        let period = PeriodDate {
+            start: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+        };
+        let json = serde_json::to_value(&period).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"start": "2024-01-01", "end": "2024-02-01"})
+        );
+
+        let roundtrip: PeriodDate = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip.start, period.start);
+        assert_eq!(roundtrip.end, period.end);
+    }
+
+    #[test]
+    fn test_parse_periodic_balance_with_totals() {
+        // hledger -f tests/fixtures/test.journal balance -M -O json expenses
         let json = serde_json::json!({
-            "decimalMantissa": 2000,
-            "decimalPlaces": 2
+            "prDates": [
+                [{"contents": "2024-01-01", "tag": "Exact"}, {"contents": "2024-02-01", "tag": "Exact"}]
+            ],
+            "prRows": [
+                {
+                    "prrName": "expenses:fees:brokerage",
+                    "prrAmounts": [[{"acommodity": "$", "aquantity": {"decimalMantissa": 50, "decimalPlaces": 2}}]],
+                    "prrTotal": [{"acommodity": "$", "aquantity": {"decimalMantissa": 50, "decimalPlaces": 2}}],
+                    "prrAverage": [{"acommodity": "$", "aquantity": {"decimalMantissa": 50, "decimalPlaces": 2}}]
+                },
+                {
+                    "prrName": "expenses:groceries",
+                    "prrAmounts": [[{"acommodity": "$", "aquantity": {"decimalMantissa": 2000, "decimalPlaces": 2}}]],
+                    "prrTotal": [{"acommodity": "$", "aquantity": {"decimalMantissa": 2000, "decimalPlaces": 2}}],
+                    "prrAverage": [{"acommodity": "$", "aquantity": {"decimalMantissa": 2000, "decimalPlaces": 2}}]
+                }
+            ],
+            "prTotals": {
+                "prrName": [],
+                "prrAmounts": [[{"acommodity": "$", "aquantity": {"decimalMantissa": 2050, "decimalPlaces": 2}}]],
+                "prrTotal": [{"acommodity": "$", "aquantity": {"decimalMantissa": 2050, "decimalPlaces": 2}}],
+                "prrAverage": [{"acommodity": "$", "aquantity": {"decimalMantissa": 2050, "decimalPlaces": 2}}]
+            }
         });
-        let decimal = parse_decimal_from_json(&json).unwrap();
-        assert_eq!(decimal, Decimal::new(2000, 2));
 
-        // Test simple number
-        let json = serde_json::json!(20.5);
-        let decimal = parse_decimal_from_json(&json).unwrap();
-        assert_eq!(decimal.to_string(), "20.5");
+        let report = periodic(&json).unwrap();
+        assert_eq!(report.dates.len(), 1);
+        assert_eq!(report.rows.len(), 2);
+        assert_eq!(report.rows[1].account, "expenses:groceries");
+
+        let totals = report.totals.unwrap();
+        assert_eq!(totals.account, "");
+        assert_eq!(totals.display_name, "");
+        assert_eq!(totals.amounts[0][0].quantity, Decimal::new(2050, 2));
+        assert_eq!(totals.total.unwrap()[0].quantity, Decimal::new(2050, 2));
+    }
+
+    fn row(account: &str) -> PeriodicBalanceRow {
+        PeriodicBalanceRow {
+            account: account.to_string(),
+            display_name: account.to_string(),
+            indent: 0,
+            amounts: Vec::new(),
+            total: None,
+            average: None,
+        }
+    }
 
-        // Test string number
-        let json = serde_json::json!("30.25");
-        let decimal = parse_decimal_from_json(&json).unwrap();
-        assert_eq!(decimal.to_string(), "30.25");
+    #[test]
+    fn test_apply_tree_layout() {
+        let mut rows = vec![
+            row("assets"),
+            row("assets:bank:checking"),
+            row("assets:investments"),
+            row("assets:investments:fidelity:cash"),
+            row("assets:investments:fidelity:goog"),
+            row("expenses"),
+            row("expenses:groceries"),
+            row("income:salary"),
+        ];
+        apply_tree_layout(&mut rows);
+
+        let layout: Vec<(u32, &str)> = rows
+            .iter()
+            .map(|r| (r.indent, r.display_name.as_str()))
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                (0, "assets"),
+                (1, "bank:checking"),
+                (1, "investments"),
+                (2, "fidelity:cash"),
+                (2, "fidelity:goog"),
+                (0, "expenses"),
+                (1, "groceries"),
+                (0, "income:salary"),
+            ]
+        );
+        // Full account names are preserved
+        assert_eq!(rows[3].account, "assets:investments:fidelity:cash");
     }
 
     #[test]
-    fn test_parse_amount() {
-        let json = serde_json::json!([{
-            "acommodity": "$",
-            "aquantity": {
-                "decimalMantissa": 10000,
-                "decimalPlaces": 2
-            },
-            "astyle": {
-                "ascommodityside": "L",
-                "ascommodityspaced": false,
-                "asdecimalmark": ".",
-                "asdigitgroups": null,
-                "asprecision": 2
-            }
-        }]);
+    fn test_apply_tree_layout_ignores_name_prefixes() {
+        // "assets:bank2" is a sibling of "assets:bank", not its child
+        let mut rows = vec![row("assets"), row("assets:bank"), row("assets:bank2")];
+        apply_tree_layout(&mut rows);
+        assert_eq!(rows[2].indent, 1);
+        assert_eq!(rows[2].display_name, "bank2");
+    }
 
-        let amounts = parse_amounts(&json).unwrap();
-        assert_eq!(amounts.len(), 1);
-        assert_eq!(amounts[0].commodity, "$");
-        assert_eq!(amounts[0].quantity, Decimal::new(10000, 2));
+    #[test]
+    fn test_parse_empty_journal_reports() {
+        // hledger bal -O json on an empty journal
+        let json = serde_json::json!([[], []]);
+        let BalanceReport::Simple(report) = parse(&json).unwrap() else {
+            panic!("Expected simple balance report");
+        };
+        assert!(report.accounts.is_empty());
+        assert!(report.totals.is_empty());
+
+        // hledger bal -M -O json on an empty journal
+        let json = serde_json::json!({
+            "prDates": [],
+            "prRows": [],
+            "prTotals": {"prrName": [], "prrAmounts": [], "prrTotal": [], "prrAverage": []}
+        });
+        let report = periodic(&json).unwrap();
+        assert!(report.dates.is_empty());
+        assert!(report.rows.is_empty());
+        assert!(report.totals.is_none());
+
+        // An object without the report's keys is not a report
+        for json in [
+            serde_json::json!({}),
+            serde_json::json!({"prDates": []}),
+            serde_json::json!({"prRows": []}),
+        ] {
+            assert!(matches!(parse(&json), Err(HLedgerError::ParseError(_))));
+        }
+    }
+
+    #[test]
+    fn test_balance_report_is_tagged() {
+        let simple = BalanceReport::Simple(SimpleBalance {
+            accounts: vec![],
+            totals: vec![],
+        });
+        let periodic = BalanceReport::Periodic(PeriodicBalance {
+            dates: vec![],
+            rows: vec![],
+            totals: None,
+        });
+        for (report, tag) in [(simple, "Simple"), (periodic, "Periodic")] {
+            let mut json = serde_json::to_value(&report).unwrap();
+            assert_eq!(json["type"], tag);
+            let parsed: BalanceReport = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(serde_json::to_value(parsed).unwrap(), json);
+
+            // Tagged by the old lowercase names
+            json["type"] = tag.to_lowercase().into();
+            let parsed: BalanceReport = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(serde_json::to_value(parsed).unwrap()["type"], tag);
+
+            // Untagged, as reports were cached before
+            json.as_object_mut().unwrap().remove("type");
+            let parsed: BalanceReport = serde_json::from_value(json).unwrap();
+            assert_eq!(serde_json::to_value(parsed).unwrap()["type"], tag);
+        }
     }
 }
@@ -0,0 +1,465 @@
+use crate::amount::MultiAmount;
+use crate::commands::balance::PeriodDate;
+use crate::commands::json::{parse_amounts, parse_period_dates, parse_row_name};
+use crate::config::{add_journal_args, add_query_args, run_json_command};
+use crate::dates::validate_date_options;
+use crate::interval::{LegacyInterval, ReportInterval};
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, HLedgerError, Result};
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::process::Command;
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for the budget report (`balance --budget`)
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+// Derived as inherent functions, which the trait impls below wrap to fold in
+// the deprecated fields
+#[serde(rename_all = "camelCase", remote = "Self")]
+#[ts(rename_all = "camelCase")]
+pub struct BudgetOptions {
+    /// Only use periodic rules whose description matches this pattern
+    pub budget: Option<String>,
+
+    // Period selection
+    /// How the report is split into periods
+    #[serde(default)]
+    pub interval: ReportInterval,
+
+    // Date filters
+    /// Begin date (inclusive: transactions on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: transactions before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    // Other filters
+    /// Limit depth of accounts shown
+    pub depth: Option<u32>,
+    /// Show accounts without budget goals or activity too
+    pub empty: bool,
+
+    // Query patterns
+    pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+impl Serialize for BudgetOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        BudgetOptions::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BudgetOptions {
+    /// Reads the options, folding the deprecated interval fields into
+    /// `interval`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        let mut options = BudgetOptions::deserialize(&json).map_err(de::Error::custom)?;
+        options.interval = LegacyInterval::resolve(options.interval, &json)?;
+        Ok(options)
+    }
+}
+
+/// Actual and goal amounts for one account in one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct BudgetCell {
+    /// What was actually posted
+    pub actual: MultiAmount,
+    /// The budget goal, if the account has one in this period
+    pub goal: Option<MultiAmount>,
+    /// Goal minus actual
+    pub remaining: Option<MultiAmount>,
+    /// Actual as a percentage of the goal, rounded to two decimal places
+    ///
+    /// Only given when the goal is a non-zero amount in a single commodity
+    /// and the actual is in that same commodity.
+    #[ts(type = "string | null")]
+    pub percent: Option<Decimal>,
+}
+
+/// One account's budget performance across the report periods
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct BudgetRow {
+    /// Account name (`<unbudgeted>` collects accounts without goals)
+    pub account: String,
+    /// One cell per report period
+    pub cells: Vec<BudgetCell>,
+}
+
+/// Budget-vs-actual report
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct BudgetReport {
+    /// Period date ranges
+    pub dates: Vec<PeriodDate>,
+    /// Account rows
+    pub rows: Vec<BudgetRow>,
+    /// Totals across all accounts
+    pub totals: Option<BudgetRow>,
+}
+
+impl BudgetCell {
+    /// Derive the remaining amount and percentage from actual and goal
+    pub fn new(actual: MultiAmount, goal: Option<MultiAmount>) -> Self {
+        let remaining = goal.clone().map(|goal| goal - actual.clone());
+        let percent = goal
+            .as_ref()
+            .and_then(|goal| percent_of_goal(&actual, goal));
+        Self {
+            actual,
+            goal,
+            remaining,
+            percent,
+        }
+    }
+}
+
+fn percent_of_goal(actual: &MultiAmount, goal: &MultiAmount) -> Option<Decimal> {
+    let mut goal_commodities = goal.iter();
+    let (commodity, goal_quantity) = goal_commodities.next()?;
+    if goal_commodities.next().is_some() || actual.iter().any(|(c, _)| c != commodity) {
+        return None;
+    }
+    let percent = actual.get(commodity) / goal_quantity * Decimal::ONE_HUNDRED;
+    Some(percent.round_dp(2))
+}
+
+// Implementation for builder pattern
+impl BudgetOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn budget(mut self, pattern: impl Into<String>) -> Self {
+        self.budget = Some(pattern.into());
+        self
+    }
+
+    pub fn daily(mut self) -> Self {
+        self.interval = ReportInterval::Daily;
+        self
+    }
+
+    pub fn weekly(mut self) -> Self {
+        self.interval = ReportInterval::Weekly;
+        self
+    }
+
+    pub fn monthly(mut self) -> Self {
+        self.interval = ReportInterval::Monthly;
+        self
+    }
+
+    pub fn quarterly(mut self) -> Self {
+        self.interval = ReportInterval::Quarterly;
+        self
+    }
+
+    pub fn yearly(mut self) -> Self {
+        self.interval = ReportInterval::Yearly;
+        self
+    }
+
+    pub fn period(mut self, period: impl Into<String>) -> Self {
+        self.interval = ReportInterval::Custom(period.into());
+        self
+    }
+
+    pub fn interval(mut self, interval: ReportInterval) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn depth(mut self, n: u32) -> Self {
+        self.depth = Some(n);
+        self
+    }
+
+    pub fn empty(mut self) -> Self {
+        self.empty = true;
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.queries.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+}
+
+/// Get actual, goal, remaining and percent used per account and period
+pub fn get_budget_report(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &BudgetOptions,
+) -> Result<BudgetReport> {
+    let mut cmd = budget_command(hledger_path, journal_file, options)?;
+    let json_value: serde_json::Value = run_json_command(&mut cmd, options.timeout)?;
+    parse_budget_report(&json_value)
+}
+
+/// Build the hledger command for the budget report
+fn budget_command(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &BudgetOptions,
+) -> Result<Command> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
+    }
+
+    let mut cmd = get_hledger_command(hledger_path);
+
+    add_journal_args(&mut cmd, journal_file);
+
+    cmd.arg("balance");
+
+    // Always output JSON
+    cmd.arg("--output-format").arg("json");
+
+    match &options.budget {
+        Some(pattern) => cmd.arg(format!("--budget={}", pattern)),
+        None => cmd.arg("--budget"),
+    };
+    cmd.arg("--flat");
+
+    // Add period flags
+    options.interval.add_args(&mut cmd);
+
+    // Add date filters
+    if let Some(begin) = &options.begin {
+        cmd.arg("--begin").arg(begin);
+    }
+    if let Some(end) = &options.end {
+        cmd.arg("--end").arg(end);
+    }
+
+    if let Some(depth) = options.depth {
+        cmd.arg("--depth").arg(depth.to_string());
+    }
+    if options.empty {
+        cmd.arg("--empty");
+    }
+
+    add_query_args(&mut cmd, &options.queries);
+
+    Ok(cmd)
+}
+
+/// Parse `balance --budget` JSON
+///
+/// It has the shape of a periodic balance report, except every amount is a
+/// `[actual, goal]` pair where either side may be null.
+fn parse_budget_report(value: &serde_json::Value) -> Result<BudgetReport> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| HLedgerError::ParseError("Expected object for budget report".to_string()))?;
+
+    let dates = parse_period_dates(obj.get("prDates"))?;
+
+    let mut rows = Vec::new();
+    if let Some(rows_array) = obj.get("prRows").and_then(|r| r.as_array()) {
+        for row in rows_array {
+            rows.push(parse_budget_row(row)?);
+        }
+    }
+
+    let totals = match obj.get("prTotals") {
+        Some(totals) if !totals.is_null() => Some(parse_budget_row(totals)?),
+        _ => None,
+    };
+
+    Ok(BudgetReport {
+        dates,
+        rows,
+        totals,
+    })
+}
+
+fn parse_budget_row(value: &serde_json::Value) -> Result<BudgetRow> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| HLedgerError::ParseError("Budget row should be an object".to_string()))?;
+
+    let account = parse_row_name(obj.get("prrName"));
+
+    let mut cells = Vec::new();
+    if let Some(cells_array) = obj.get("prrAmounts").and_then(|a| a.as_array()) {
+        for cell in cells_array {
+            cells.push(parse_budget_cell(cell)?);
+        }
+    }
+
+    Ok(BudgetRow { account, cells })
+}
+
+fn parse_budget_cell(value: &serde_json::Value) -> Result<BudgetCell> {
+    let pair = value
+        .as_array()
+        .filter(|pair| pair.len() == 2)
+        .ok_or_else(|| {
+            HLedgerError::ParseError("Budget cell should be an [actual, goal] pair".to_string())
+        })?;
+
+    let actual = parse_amounts(&pair[0])?;
+    let goal = if pair[1].is_null() {
+        None
+    } else {
+        Some(MultiAmount::from(parse_amounts(&pair[1])?.as_slice()))
+    };
+
+    Ok(BudgetCell::new(MultiAmount::from(actual.as_slice()), goal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        BudgetOptions::export_all().unwrap();
+        BudgetCell::export_all().unwrap();
+        BudgetRow::export_all().unwrap();
+        BudgetReport::export_all().unwrap();
+    }
+
+    fn dollars(quantity: &str) -> MultiAmount {
+        let mut amount = MultiAmount::new();
+        amount.add_quantity("$", quantity.parse().unwrap());
+        amount
+    }
+
+    #[test]
+    fn test_budget_cell_math() {
+        let cell = BudgetCell::new(dollars("450"), Some(dollars("500")));
+        assert_eq!(cell.remaining, Some(dollars("50")));
+        assert_eq!(cell.percent, Some("90".parse().unwrap()));
+
+        // Over budget
+        let cell = BudgetCell::new(dollars("620"), Some(dollars("500")));
+        assert_eq!(cell.remaining, Some(dollars("-120")));
+        assert_eq!(cell.percent, Some("124".parse().unwrap()));
+
+        // Income goals are negative, so progress is still a positive percentage
+        let cell = BudgetCell::new(dollars("-1000"), Some(dollars("-3000")));
+        assert_eq!(cell.percent, Some("33.33".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_budget_cell_without_usable_goal() {
+        let cell = BudgetCell::new(dollars("40"), None);
+        assert_eq!(cell.remaining, None);
+        assert_eq!(cell.percent, None);
+
+        // Nothing spent yet
+        let cell = BudgetCell::new(MultiAmount::new(), Some(dollars("200")));
+        assert_eq!(cell.remaining, Some(dollars("200")));
+        assert_eq!(cell.percent, Some(Decimal::ZERO));
+
+        // A zero goal has no meaningful percentage
+        let cell = BudgetCell::new(dollars("10"), Some(MultiAmount::new()));
+        assert_eq!(cell.percent, None);
+
+        // Mixed commodities can't be compared
+        let mut euros = MultiAmount::new();
+        euros.add_quantity("EUR", Decimal::TEN);
+        let cell = BudgetCell::new(euros, Some(dollars("200")));
+        assert_eq!(cell.percent, None);
+    }
+
+    #[test]
+    fn test_parse_budget_report() {
+        // Shape of `hledger bal --budget -M -O json` on tests/fixtures/budget.journal
+        let amount = |quantity: u64| {
+            serde_json::json!([{
+                "acommodity": "$",
+                "aquantity": {"decimalMantissa": quantity * 100, "decimalPlaces": 2, "floatingPoint": quantity as f64}
+            }])
+        };
+        let json = serde_json::json!({
+            "prDates": [
+                [{"tag": "Exact", "contents": "2024-01-01"}, {"tag": "Exact", "contents": "2024-02-01"}]
+            ],
+            "prRows": [
+                {
+                    "prrName": "expenses:food",
+                    "prrAmounts": [[amount(450), amount(500)]],
+                    "prrTotal": [amount(450), amount(500)],
+                    "prrAverage": [amount(450), amount(500)]
+                },
+                {
+                    "prrName": "<unbudgeted>",
+                    "prrAmounts": [[amount(30), null]],
+                    "prrTotal": [amount(30), null],
+                    "prrAverage": [amount(30), null]
+                }
+            ],
+            "prTotals": {
+                "prrName": [],
+                "prrAmounts": [[amount(480), amount(500)]],
+                "prrTotal": [amount(480), amount(500)],
+                "prrAverage": [amount(480), amount(500)]
+            }
+        });
+
+        let report = parse_budget_report(&json).unwrap();
+        assert_eq!(report.dates.len(), 1);
+        assert_eq!(report.rows.len(), 2);
+
+        let food = &report.rows[0];
+        assert_eq!(food.account, "expenses:food");
+        assert_eq!(food.cells[0].actual, dollars("450"));
+        assert_eq!(food.cells[0].remaining, Some(dollars("50")));
+
+        let unbudgeted = &report.rows[1].cells[0];
+        assert_eq!(unbudgeted.goal, None);
+
+        let totals = report.totals.unwrap();
+        assert_eq!(totals.account, "");
+        assert_eq!(totals.cells[0].percent, Some("96".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_parse_budget_cell_rejects_non_pairs() {
+        assert!(parse_budget_cell(&serde_json::json!([])).is_err());
+        assert!(parse_budget_cell(&serde_json::json!({"actual": []})).is_err());
+    }
+}
@@ -1,14 +1,29 @@
 use crate::commands::balance::{
-    extract_date_from_tagged_value, parse_amounts, PeriodDate, PeriodicBalance, PeriodicBalanceRow,
+    apply_tree_layout, PeriodDate, PeriodicBalance, PeriodicBalanceRow,
 };
-use crate::{get_hledger_command, HLedgerError, Result};
-use serde::{Deserialize, Serialize};
+use crate::commands::json::{json_error, period_dates, totals_row, JsonCompoundReport};
+use crate::config::{add_journal_args, add_query_args, run_json_command, run_json_command_piped};
+use crate::csv_report::{run_csv_command, CsvReport};
+use crate::dates::validate_date_options;
+use crate::interval::{LegacyInterval, ReportInterval};
+use crate::list_mode::{LegacyListMode, ListMode};
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, Result};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
 use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
 use ts_rs::TS;
 
 /// Options for the cashflow command
 #[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+// Derived as inherent functions, which the trait impls below wrap to fold in
+// the deprecated fields
+#[serde(rename_all = "camelCase", remote = "Self")]
+#[ts(rename_all = "camelCase")]
 pub struct CashflowOptions {
     /// Show sum of posting amounts (default)
     pub sum: bool,
@@ -24,10 +39,9 @@ pub struct CashflowOptions {
     pub cumulative: bool,
     /// Accumulate amounts from journal start to column end
     pub historical: bool,
-    /// Show accounts as a flat list (default)
-    pub flat: bool,
-    /// Show accounts as a tree
-    pub tree: bool,
+    /// Show accounts as a flat list or a tree; `None` leaves it to hledger
+    /// (flat, unless a config file says otherwise)
+    pub list_mode: Option<ListMode>,
     /// Omit N leading account name parts in flat mode
     pub drop: Option<u32>,
     /// Include non-parent declared accounts
@@ -56,35 +70,47 @@ pub struct CashflowOptions {
     pub begin: Option<String>,
     /// End date
     pub end: Option<String>,
-    /// Reporting period
-    pub period: Option<String>,
-    /// Report by day
-    pub daily: bool,
-    /// Report by week
-    pub weekly: bool,
-    /// Report by month
-    pub monthly: bool,
-    /// Report by quarter
-    pub quarterly: bool,
-    /// Report by year
-    pub yearly: bool,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+    /// How the report is split into periods
+    #[serde(default)]
+    pub interval: ReportInterval,
     /// Depth limit for accounts
     pub depth: Option<u32>,
     /// Show empty/zero accounts
     pub empty: bool,
+    /// Skip balance assertions, e.g. to see the report while one fails
+    pub ignore_assertions: bool,
     /// Account query patterns
     pub query: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+impl Serialize for CashflowOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        CashflowOptions::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CashflowOptions {
+    /// Reads the options, folding the deprecated interval and `tree`/`flat`
+    /// fields into `interval` and `list_mode`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        let mut options = CashflowOptions::deserialize(&json).map_err(de::Error::custom)?;
+        options.interval = LegacyInterval::resolve(options.interval, &json)?;
+        options.list_mode = LegacyListMode::resolve(options.list_mode, &json)?;
+        Ok(options)
+    }
 }
 
 impl CashflowOptions {
     /// Create new cashflow options with defaults
+    ///
+    /// `sum`, `change` and `flat` are left unset like in `default()`: they
+    /// are hledger's own defaults, so no flag is passed for them either way.
     pub fn new() -> Self {
-        Self {
-            sum: true,
-            change: true,
-            flat: true,
-            ..Default::default()
-        }
+        Self::default()
     }
 
     /// Enable valuechange mode
@@ -132,8 +158,13 @@ impl CashflowOptions {
 
     /// Enable tree mode
     pub fn tree(mut self) -> Self {
-        self.tree = true;
-        self.flat = false;
+        self.list_mode = Some(ListMode::Tree);
+        self
+    }
+
+    /// Enable flat mode
+    pub fn flat(mut self) -> Self {
+        self.list_mode = Some(ListMode::Flat);
         self
     }
 
@@ -149,6 +180,11 @@ impl CashflowOptions {
         self
     }
 
+    pub fn ignore_assertions(mut self) -> Self {
+        self.ignore_assertions = true;
+        self
+    }
+
     /// Set begin date
     pub fn begin(mut self, date: &str) -> Self {
         self.begin = Some(date.to_string());
@@ -161,59 +197,51 @@ impl CashflowOptions {
         self
     }
 
-    /// Set reporting period
+    /// Skip `begin`/`end` validation, for date expressions it doesn't know
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    /// Set a custom reporting period, e.g. `every 2 weeks` or `2024q1`
     pub fn period(mut self, period: &str) -> Self {
-        self.period = Some(period.to_string());
+        self.interval = ReportInterval::Custom(period.to_string());
+        self
+    }
+
+    /// Set the reporting interval
+    pub fn interval(mut self, interval: ReportInterval) -> Self {
+        self.interval = interval;
         self
     }
 
     /// Enable daily reporting
     pub fn daily(mut self) -> Self {
-        self.daily = true;
-        self.weekly = false;
-        self.monthly = false;
-        self.quarterly = false;
-        self.yearly = false;
+        self.interval = ReportInterval::Daily;
         self
     }
 
     /// Enable weekly reporting
     pub fn weekly(mut self) -> Self {
-        self.weekly = true;
-        self.daily = false;
-        self.monthly = false;
-        self.quarterly = false;
-        self.yearly = false;
+        self.interval = ReportInterval::Weekly;
         self
     }
 
     /// Enable monthly reporting
     pub fn monthly(mut self) -> Self {
-        self.monthly = true;
-        self.daily = false;
-        self.weekly = false;
-        self.quarterly = false;
-        self.yearly = false;
+        self.interval = ReportInterval::Monthly;
         self
     }
 
     /// Enable quarterly reporting
     pub fn quarterly(mut self) -> Self {
-        self.quarterly = true;
-        self.daily = false;
-        self.weekly = false;
-        self.monthly = false;
-        self.yearly = false;
+        self.interval = ReportInterval::Quarterly;
         self
     }
 
     /// Enable yearly reporting
     pub fn yearly(mut self) -> Self {
-        self.yearly = true;
-        self.daily = false;
-        self.weekly = false;
-        self.monthly = false;
-        self.quarterly = false;
+        self.interval = ReportInterval::Yearly;
         self
     }
 
@@ -223,6 +251,12 @@ impl CashflowOptions {
         self
     }
 
+    /// Add a typed query
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.query.push(query.to_arg());
+        self
+    }
+
     /// Show average column
     pub fn average(mut self) -> Self {
         self.average = true;
@@ -264,11 +298,19 @@ impl CashflowOptions {
         self.percent = true;
         self
     }
+
+    /// Give up on hledger after the given duration
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
 }
 
 /// The cashflow report structure
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct CashflowReport {
     /// Report title
     pub title: String,
@@ -282,7 +324,9 @@ pub struct CashflowReport {
 
 /// Cashflow subreport structure  
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct CashflowSubreport {
     /// The name of the subreport (always "Cash flows" for cashflow)
     pub name: String,
@@ -298,18 +342,88 @@ pub fn get_cashflow(
     journal_path: Option<&Path>,
     options: CashflowOptions,
 ) -> Result<CashflowReport> {
+    let mut cmd = cashflow_command(hledger_path, journal_path, &options, "json")?;
+    cashflow_report(run_json_command(&mut cmd, options.timeout)?, &options)
+}
+
+/// Get cashflow statement for journal text that isn't on disk
+///
+/// The text is piped to `hledger -f -`, as for [`crate::get_balance_from_text`].
+pub fn get_cashflow_from_text(
+    hledger_path: Option<&str>,
+    journal_text: &str,
+    options: CashflowOptions,
+) -> Result<CashflowReport> {
+    let mut cmd = cashflow_command(hledger_path, Some(Path::new("-")), &options, "json")?;
+    let json = run_json_command_piped(&mut cmd, Some(journal_text), options.timeout)?;
+    cashflow_report(json, &options)
+}
+
+/// Convert hledger's cashflow JSON and apply the options hledger leaves to us
+fn cashflow_report(json: JsonCompoundReport, options: &CashflowOptions) -> Result<CashflowReport> {
+    let mut report = parse_cashflow_report(json)?;
+    let tree = options.list_mode == Some(ListMode::Tree);
+    for subreport in &mut report.subreports {
+        if tree {
+            apply_tree_layout(&mut subreport.data.rows);
+        }
+        if options.no_total {
+            subreport.data.totals = None;
+        }
+    }
+    if options.no_total {
+        report.totals = None;
+    }
+
+    Ok(report)
+}
+
+/// Get the cashflow statement as hledger's JSON, untouched
+///
+/// For fields the typed [`CashflowReport`] doesn't model yet. The command is built
+/// exactly as for [`get_cashflow`].
+pub fn get_cashflow_raw(
+    hledger_path: Option<&str>,
+    journal_path: Option<&Path>,
+    options: CashflowOptions,
+) -> Result<serde_json::Value> {
+    let mut cmd = cashflow_command(hledger_path, journal_path, &options, "json")?;
+    run_json_command(&mut cmd, options.timeout)
+}
+
+/// Get the cashflow statement as hledger's CSV (`-O csv`), one row per cash account
+///
+/// The command is built exactly as for [`get_cashflow`].
+pub fn get_cashflow_csv(
+    hledger_path: Option<&str>,
+    journal_path: Option<&Path>,
+    options: CashflowOptions,
+) -> Result<CsvReport> {
+    let mut cmd = cashflow_command(hledger_path, journal_path, &options, "csv")?;
+    run_csv_command(&mut cmd, options.timeout)
+}
+
+/// Build the hledger command for the cashflow statement
+fn cashflow_command(
+    hledger_path: Option<&str>,
+    journal_path: Option<&Path>,
+    options: &CashflowOptions,
+    output_format: &str,
+) -> Result<Command> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
+    }
+
     let mut cmd = get_hledger_command(hledger_path);
 
     // Add journal file if provided
-    if let Some(path) = journal_path {
-        cmd.arg("-f").arg(path);
-    }
+    let journal_file = journal_path.map(Path::to_string_lossy);
+    add_journal_args(&mut cmd, journal_file.as_deref());
 
     // Add the cashflow command
     cmd.arg("cashflow");
 
-    // Always request JSON output
-    cmd.arg("--output-format").arg("json");
+    cmd.arg("--output-format").arg(output_format);
 
     // Add calculation mode flags (mutually exclusive)
     if options.valuechange {
@@ -329,11 +443,8 @@ pub fn get_cashflow(
     }
     // change is the default, no flag needed
 
-    // Add list/tree mode flags (mutually exclusive)
-    if options.tree {
-        cmd.arg("--tree");
-    }
-    // flat is the default, no flag needed
+    // Add list/tree mode flags; neither is passed unless one was chosen
+    ListMode::add_args(options.list_mode, &mut cmd);
 
     // Add other flags
     if let Some(drop) = options.drop {
@@ -393,22 +504,8 @@ pub fn get_cashflow(
         cmd.arg("--end").arg(end);
     }
 
-    if let Some(period) = &options.period {
-        cmd.arg("--period").arg(period);
-    }
-
-    // Add period flags (mutually exclusive)
-    if options.daily {
-        cmd.arg("--daily");
-    } else if options.weekly {
-        cmd.arg("--weekly");
-    } else if options.monthly {
-        cmd.arg("--monthly");
-    } else if options.quarterly {
-        cmd.arg("--quarterly");
-    } else if options.yearly {
-        cmd.arg("--yearly");
-    }
+    // Add period flags
+    options.interval.add_args(&mut cmd);
 
     // Add depth option
     if let Some(depth) = options.depth {
@@ -419,195 +516,39 @@ pub fn get_cashflow(
     if options.empty {
         cmd.arg("--empty");
     }
-
-    // Add query patterns
-    for pattern in &options.query {
-        cmd.arg(pattern);
+    if options.ignore_assertions {
+        cmd.arg("--ignore-assertions");
     }
 
-    // Execute command
-    let output = cmd.output().map_err(|e| match e.kind() {
-        std::io::ErrorKind::NotFound => HLedgerError::HLedgerNotFound,
-        _ => HLedgerError::Io(e),
-    })?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        let code = output.status.code().unwrap_or(-1);
-        return Err(HLedgerError::CommandFailed { code, stderr });
-    }
+    // Add query patterns
+    add_query_args(&mut cmd, &options.query);
 
-    // Parse the JSON output
-    let json_str = String::from_utf8_lossy(&output.stdout);
-    parse_cashflow(&json_str)
+    Ok(cmd)
 }
 
 /// Parse cashflow JSON output
 pub fn parse_cashflow(json_str: &str) -> Result<CashflowReport> {
-    let value: serde_json::Value = serde_json::from_str(json_str)?;
-    parse_cashflow_report(&value)
+    parse_cashflow_report(serde_json::from_str(json_str).map_err(json_error)?)
 }
 
-fn parse_cashflow_report(value: &serde_json::Value) -> Result<CashflowReport> {
-    use crate::commands::balance::extract_date_from_tagged_value;
-    let obj = value.as_object().ok_or_else(|| {
-        HLedgerError::ParseError("Expected JSON object for cashflow report".to_string())
-    })?;
-
-    // Extract title
-    let title = obj
-        .get("cbrTitle")
-        .and_then(|v| v.as_str())
-        .unwrap_or("Cashflow Statement")
-        .to_string();
-
-    // Parse dates
-    let dates_json = obj
-        .get("cbrDates")
-        .ok_or_else(|| HLedgerError::ParseError("Missing cbrDates in cashflow".to_string()))?;
-
-    let mut dates = Vec::new();
-    if let Some(dates_array) = dates_json.as_array() {
-        for date_pair in dates_array {
-            if let Some(pair) = date_pair.as_array() {
-                if pair.len() == 2 {
-                    let start = extract_date_from_tagged_value(&pair[0]);
-                    let end = extract_date_from_tagged_value(&pair[1]);
-                    dates.push(PeriodDate { start, end });
-                }
-            }
-        }
-    }
-
-    // Parse subreports
-    let subreports_json = obj
-        .get("cbrSubreports")
-        .ok_or_else(|| HLedgerError::ParseError("Missing cbrSubreports in cashflow".to_string()))?;
-
+/// Convert hledger's compound cashflow report
+fn parse_cashflow_report(json: JsonCompoundReport) -> Result<CashflowReport> {
     let mut subreports = Vec::new();
-    if let Some(subreports_array) = subreports_json.as_array() {
-        for subreport_entry in subreports_array {
-            if let Some(entry_array) = subreport_entry.as_array() {
-                if entry_array.len() >= 3 {
-                    let name = entry_array[0].as_str().unwrap_or("").to_string();
-                    let periodic_balance = parse_periodic_balance_value(&entry_array[1])?;
-                    let increases_total = entry_array[2].as_bool().unwrap_or(true);
-
-                    subreports.push(CashflowSubreport {
-                        name,
-                        data: periodic_balance,
-                        increases_total,
-                    });
-                }
-            }
-        }
+    for (name, report, increases_total) in json.subreports {
+        subreports.push(CashflowSubreport {
+            name,
+            data: report.into_periodic_balance()?,
+            increases_total,
+        });
     }
 
-    // Parse totals
-    let totals = obj
-        .get("cbrTotals")
-        .map(|v| parse_periodic_row_value(v))
-        .transpose()?;
-
     Ok(CashflowReport {
-        title,
-        dates,
+        title: json
+            .title
+            .unwrap_or_else(|| "Cashflow Statement".to_string()),
+        dates: period_dates(json.dates),
         subreports,
-        totals,
-    })
-}
-
-fn parse_periodic_balance_value(value: &serde_json::Value) -> Result<PeriodicBalance> {
-    let obj = value.as_object().ok_or_else(|| {
-        HLedgerError::ParseError("Expected object for periodic balance".to_string())
-    })?;
-
-    // Parse dates from prDates
-    let dates_json = obj.get("prDates").ok_or_else(|| {
-        HLedgerError::ParseError("Missing prDates in periodic balance".to_string())
-    })?;
-
-    let mut dates = Vec::new();
-    if let Some(dates_array) = dates_json.as_array() {
-        for date_pair in dates_array {
-            if let Some(pair) = date_pair.as_array() {
-                if pair.len() == 2 {
-                    let start = extract_date_from_tagged_value(&pair[0]);
-                    let end = extract_date_from_tagged_value(&pair[1]);
-                    dates.push(PeriodDate { start, end });
-                }
-            }
-        }
-    }
-
-    // Parse rows from prRows
-    let rows_json = obj.get("prRows").ok_or_else(|| {
-        HLedgerError::ParseError("Missing prRows in periodic balance".to_string())
-    })?;
-
-    let mut rows = Vec::new();
-    if let Some(rows_array) = rows_json.as_array() {
-        for row_value in rows_array {
-            let row = parse_periodic_row_value(row_value)?;
-            rows.push(row);
-        }
-    }
-
-    // Parse totals from prTotals
-    let totals = obj
-        .get("prTotals")
-        .map(|v| parse_periodic_row_value(v))
-        .transpose()?;
-
-    Ok(PeriodicBalance {
-        dates,
-        rows,
-        totals,
-    })
-}
-
-fn parse_periodic_row_value(value: &serde_json::Value) -> Result<PeriodicBalanceRow> {
-    let obj = value.as_object().ok_or_else(|| {
-        HLedgerError::ParseError("Expected object for periodic balance row".to_string())
-    })?;
-
-    // Get account name from prrName (can be string or empty array for totals)
-    let account = obj
-        .get("prrName")
-        .map(|v| {
-            if let Some(s) = v.as_str() {
-                s.to_string()
-            } else {
-                // For totals, prrName is an empty array
-                String::new()
-            }
-        })
-        .unwrap_or_else(|| String::new());
-    let display_name = account.clone(); // Use same name as display name
-
-    // Parse amounts from prrAmounts using existing function
-    let mut amounts = Vec::new();
-    if let Some(amounts_array) = obj.get("prrAmounts").and_then(|v| v.as_array()) {
-        for period_amounts in amounts_array {
-            amounts.push(parse_amounts(period_amounts)?);
-        }
-    }
-
-    // Parse total from prrTotal using existing function
-    let total = obj.get("prrTotal").map(|v| parse_amounts(v)).transpose()?;
-
-    // Parse average from prrAverage using existing function
-    let average = obj
-        .get("prrAverage")
-        .map(|v| parse_amounts(v))
-        .transpose()?;
-
-    Ok(PeriodicBalanceRow {
-        account,
-        display_name,
-        amounts,
-        total,
-        average,
+        totals: totals_row(json.totals)?,
     })
 }
 
@@ -625,15 +566,29 @@ mod tests {
             .begin("2024-01-01")
             .end("2024-12-31");
 
-        assert!(opts.monthly);
-        assert!(opts.tree);
-        assert!(!opts.flat);
+        assert_eq!(opts.interval, ReportInterval::Monthly);
+        assert_eq!(opts.list_mode, Some(ListMode::Tree));
         assert_eq!(opts.depth, Some(3));
         assert!(opts.empty);
         assert_eq!(opts.begin, Some("2024-01-01".to_string()));
         assert_eq!(opts.end, Some("2024-12-31".to_string()));
     }
 
+    #[test]
+    fn test_list_mode_flag_only_when_chosen() {
+        let args = |options: &CashflowOptions| -> Vec<String> {
+            cashflow_command(None, None, options, "json")
+                .unwrap()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        };
+        let default = args(&CashflowOptions::new());
+        assert!(!default.iter().any(|arg| arg == "--flat" || arg == "--tree"));
+        assert!(args(&CashflowOptions::new().flat()).contains(&"--flat".to_string()));
+        assert!(args(&CashflowOptions::new().tree()).contains(&"--tree".to_string()));
+    }
+
     #[test]
     fn test_calculation_modes_mutual_exclusion() {
         let opts = CashflowOptions::new().valuechange();
@@ -664,19 +619,24 @@ mod tests {
 
     #[test]
     fn test_period_flags_mutual_exclusion() {
-        let opts = CashflowOptions::new().monthly();
-        assert!(opts.monthly);
-        assert!(!opts.daily);
-        assert!(!opts.weekly);
-        assert!(!opts.quarterly);
-        assert!(!opts.yearly);
-
-        let opts = CashflowOptions::new().yearly();
-        assert!(opts.yearly);
-        assert!(!opts.daily);
-        assert!(!opts.weekly);
-        assert!(!opts.monthly);
-        assert!(!opts.quarterly);
+        let opts = CashflowOptions::new().daily().monthly();
+        assert_eq!(opts.interval, ReportInterval::Monthly);
+
+        let opts = CashflowOptions::new().period("2024").yearly();
+        assert_eq!(opts.interval, ReportInterval::Yearly);
+    }
+
+    #[test]
+    fn test_parse_cashflow_requires_report_keys() {
+        for json in [
+            "{}",
+            r#"{"cbrDates": [], "cbrSubreports": [["Cash flows", {}, true]]}"#,
+        ] {
+            assert!(matches!(
+                parse_cashflow(json),
+                Err(crate::HLedgerError::ParseError(_))
+            ));
+        }
     }
 
     #[test]
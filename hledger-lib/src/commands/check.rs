@@ -0,0 +1,516 @@
+use crate::amount::parse_amount_text;
+use crate::commands::balance::Amount;
+use crate::config::{add_journal_args, check_success, run_command};
+use crate::journals::journal_file_paths;
+use crate::warnings::collect_warnings;
+use crate::{get_hledger_command, HLedgerError, Result};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// A balance assertion that did not hold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct AssertionFailure {
+    /// File containing the assertion
+    pub file: String,
+    /// Line of the posting with the assertion (1-based)
+    pub line: u32,
+    /// Account the assertion is about
+    pub account: String,
+    /// Balance the journal asserts
+    pub expected: Amount,
+    /// Balance hledger calculated at that point
+    pub actual: Amount,
+    /// `expected - actual`: what the balance is short by
+    pub diff: Amount,
+}
+
+/// What kind of problem a [`CheckIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum CheckIssueKind {
+    /// The journal, or a file it includes, couldn't be opened
+    Unreadable,
+    /// hledger couldn't parse the journal
+    ParseError,
+    /// The journal parsed but a check failed, e.g. an unbalanced
+    /// transaction, a failing assertion or an undeclared account
+    CheckFailed,
+    /// Something hledger printed without failing
+    Warning,
+}
+
+/// How much a [`CheckIssue`] matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum CheckSeverity {
+    /// Reports on the journal would fail
+    Error,
+    /// Reports still work
+    Warning,
+}
+
+/// A problem found by [`get_check`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct CheckIssue {
+    pub kind: CheckIssueKind,
+    pub severity: CheckSeverity,
+    /// File the problem is in, when known
+    pub file: Option<String>,
+    /// Line of the problem (1-based), when known
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+/// A problem with the journal, located for the editor to open at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct JournalProblem {
+    pub kind: CheckIssueKind,
+    /// File the problem is in, when known
+    pub file: Option<String>,
+    /// Line of the problem (1-based), when known
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+    /// For a failing balance assertion, the balance the journal asserts
+    pub expected: Option<Amount>,
+    /// For a failing balance assertion, the balance hledger calculated
+    pub actual: Option<Amount>,
+}
+
+impl From<CheckIssue> for JournalProblem {
+    fn from(issue: CheckIssue) -> Self {
+        let failure = match (&issue.file, issue.line) {
+            (Some(file), Some(line)) if issue.kind == CheckIssueKind::CheckFailed => {
+                parse_assertion_failure(file, line, &issue.message)
+            }
+            _ => None,
+        };
+        let (expected, actual) = match failure {
+            Some(failure) => (Some(failure.expected), Some(failure.actual)),
+            None => (None, None),
+        };
+        JournalProblem {
+            kind: issue.kind,
+            file: issue.file,
+            line: issue.line,
+            column: issue.column,
+            message: issue.message,
+            expected,
+            actual,
+        }
+    }
+}
+
+/// Phrases in hledger's messages for a journal that parsed but failed a
+/// check, lowercased; any other journal error is a parse error
+const CHECK_FAILURE_MARKERS: &[&str] = &[
+    "checking is enabled",
+    "balance assertion failed",
+    "unbalanced",
+    "could not balance",
+    "postings' sum should be",
+];
+
+/// Check that the journal can be read, with hledger's `checks` (e.g.
+/// `accounts`, `ordereddates`) on top of the basic ones
+///
+/// Runs `hledger check`. Problems with the journal are returned as issues
+/// rather than errors; hledger stops at the first, so there is at most one
+/// error-severity issue, plus any warnings. A missing binary or a check
+/// name hledger doesn't know is still an error.
+pub fn get_check(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    checks: &[String],
+) -> Result<Vec<CheckIssue>> {
+    for file in journal_file.into_iter().flat_map(journal_file_paths) {
+        if let Err(e) = std::fs::File::open(file) {
+            return Ok(vec![CheckIssue {
+                kind: CheckIssueKind::Unreadable,
+                severity: CheckSeverity::Error,
+                file: Some(file.to_string()),
+                line: None,
+                column: None,
+                message: e.to_string(),
+            }]);
+        }
+    }
+
+    let mut cmd = get_hledger_command(hledger_path);
+    add_journal_args(&mut cmd, journal_file);
+    cmd.arg("check").args(checks);
+
+    let (result, warnings) = collect_warnings(|| {
+        let output = run_command(&mut cmd, None)?;
+        check_success(&cmd, output.status, &output.stderr)
+    });
+    let mut issues = match result {
+        Ok(()) => Vec::new(),
+        Err(e) => vec![issue_from_error(e)?],
+    };
+    issues.extend(warnings.into_iter().map(|message| {
+        CheckIssue {
+            kind: CheckIssueKind::Warning,
+            severity: CheckSeverity::Warning,
+            // hledger doesn't say which of several files a warning is about
+            file: journal_file
+                .filter(|file| journal_file_paths(file).count() == 1)
+                .map(str::to_string),
+            line: None,
+            column: None,
+            message,
+        }
+    }));
+    Ok(issues)
+}
+
+/// The issue a failed `hledger check` stands for, or the error itself if it
+/// isn't about the journal
+pub(crate) fn issue_from_error(error: HLedgerError) -> Result<CheckIssue> {
+    match error {
+        HLedgerError::JournalError {
+            file,
+            line,
+            column,
+            message,
+            ..
+        } => {
+            let lower = message.to_lowercase();
+            let kind = if CHECK_FAILURE_MARKERS.iter().any(|m| lower.contains(m)) {
+                CheckIssueKind::CheckFailed
+            } else {
+                CheckIssueKind::ParseError
+            };
+            Ok(CheckIssue {
+                kind,
+                severity: CheckSeverity::Error,
+                file: Some(file),
+                line: Some(line),
+                column,
+                message,
+            })
+        }
+        // An included file that can't be opened
+        HLedgerError::CommandFailed { stderr, .. } if stderr.contains("openFile:") => {
+            let message = stderr.trim();
+            let message = message
+                .strip_prefix("hledger: Error: ")
+                .or_else(|| message.strip_prefix("hledger: "))
+                .unwrap_or(message);
+            Ok(CheckIssue {
+                kind: CheckIssueKind::Unreadable,
+                severity: CheckSeverity::Error,
+                file: message
+                    .split_once(": openFile:")
+                    .map(|(file, _)| file.to_string()),
+                line: None,
+                column: None,
+                message: message.to_string(),
+            })
+        }
+        e => Err(e),
+    }
+}
+
+/// The journal's problems, with the asserted and calculated balances of a
+/// failing assertion
+///
+/// Runs `hledger check` through [`get_check`], so the same limits apply:
+/// at most one error-severity problem (hledger stops at the first), plus
+/// any warnings.
+pub fn get_journal_problems(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+) -> Result<Vec<JournalProblem>> {
+    let issues = get_check(hledger_path, journal_file, &[])?;
+    Ok(issues.into_iter().map(JournalProblem::from).collect())
+}
+
+/// Check the journal's balance assertions
+///
+/// Runs `hledger check`. hledger stops at the first failing assertion, so the
+/// result holds at most one failure; fix it and verify again to find the
+/// next. Journal errors other than assertion failures are returned as errors.
+pub fn verify_assertions(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+) -> Result<Vec<AssertionFailure>> {
+    let mut cmd = get_hledger_command(hledger_path);
+    add_journal_args(&mut cmd, journal_file);
+    cmd.arg("check");
+
+    let output = run_command(&mut cmd, None)?;
+    match check_success(&cmd, output.status, &output.stderr) {
+        Ok(()) => Ok(vec![]),
+        Err(HLedgerError::JournalError {
+            file,
+            line,
+            column,
+            message,
+            excerpt,
+        }) => match parse_assertion_failure(&file, line, &message) {
+            Some(failure) => Ok(vec![failure]),
+            None => Err(HLedgerError::JournalError {
+                file,
+                line,
+                column,
+                message,
+                excerpt,
+            }),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Read an assertion failure out of hledger's error message, which looks like
+///
+/// ```text
+/// Balance assertion failed in assets:checking
+/// In commodity "$" at this point, excluding subaccounts, ignoring costs,
+/// the asserted balance is:        $100
+/// but the calculated balance is:  $50
+/// (difference: $50)
+/// ```
+fn parse_assertion_failure(file: &str, line: u32, message: &str) -> Option<AssertionFailure> {
+    let mut account = None;
+    let mut commodity = None;
+    let mut expected = None;
+    let mut actual = None;
+
+    for l in message.lines().map(str::trim) {
+        let lower = l.to_lowercase();
+        if let Some(i) = lower.find("balance assertion failed in ") {
+            account = Some(
+                l[i + "balance assertion failed in ".len()..]
+                    .trim()
+                    .to_string(),
+            );
+        } else if let Some(i) = lower.find("commodity \"") {
+            let quoted = &l[i + "commodity \"".len()..];
+            commodity = quoted.find('"').map(|end| quoted[..end].to_string());
+        } else if lower.contains("asserted balance") {
+            expected = l.split_once(':').and_then(|(_, v)| parse_amount_text(v));
+        } else if lower.contains("calculated balance") {
+            actual = l.split_once(':').and_then(|(_, v)| parse_amount_text(v));
+        }
+    }
+
+    let (mut expected, mut actual) = (expected?, actual?);
+    // hledger shows a zero balance as a bare `0`, so take the commodity from
+    // its own line when there is one
+    if let Some(commodity) = commodity {
+        expected.commodity = commodity.clone();
+        actual.commodity = commodity;
+    }
+    let diff = expected.try_sub(&actual)?;
+
+    Some(AssertionFailure {
+        file: file.to_string(),
+        line,
+        account: account?,
+        expected,
+        actual,
+        diff,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn export_bindings() {
+        AssertionFailure::export_all().unwrap();
+        CheckIssue::export_all().unwrap();
+        JournalProblem::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_issue_from_error_kinds() {
+        let issue = issue_from_error(HLedgerError::from_failure(
+            "hledger check".into(),
+            1,
+            STDERR.into(),
+        ))
+        .unwrap();
+        assert_eq!(issue.kind, CheckIssueKind::CheckFailed);
+        assert_eq!(issue.severity, CheckSeverity::Error);
+        assert_eq!(issue.file.as_deref(), Some("/home/user/assertions.journal"));
+        assert_eq!(issue.line, Some(9));
+        assert_eq!(issue.column, Some(35));
+
+        let undeclared = "hledger: Error: /books/main.journal:4:
+  | 2024-01-02 coffee
+4 |     expenses:cofee     $3
+  |     ^^^^^^^^^^^^^^
+Strict account checking is enabled, and
+account \"expenses:cofee\" has not been declared.
+";
+        let issue = issue_from_error(HLedgerError::from_failure(
+            "hledger check".into(),
+            1,
+            undeclared.into(),
+        ))
+        .unwrap();
+        assert_eq!(issue.kind, CheckIssueKind::CheckFailed);
+
+        let bad_date = "hledger: Error: /books/main.journal:1:6:
+  |
+1 | 2024-13-01 bad date
+  |      ^^^^^
+invalid date: month must be 1-12
+";
+        let issue = issue_from_error(HLedgerError::from_failure(
+            "hledger check".into(),
+            1,
+            bad_date.into(),
+        ))
+        .unwrap();
+        assert_eq!(issue.kind, CheckIssueKind::ParseError);
+        assert_eq!(issue.message, "invalid date: month must be 1-12");
+    }
+
+    #[test]
+    fn test_issue_from_error_unreadable_include() {
+        let stderr = "hledger: Error: /books/2023.journal: openFile: does not exist (No such file or directory)\n";
+        let issue = issue_from_error(HLedgerError::from_failure(
+            "hledger check".into(),
+            1,
+            stderr.into(),
+        ))
+        .unwrap();
+        assert_eq!(issue.kind, CheckIssueKind::Unreadable);
+        assert_eq!(issue.file.as_deref(), Some("/books/2023.journal"));
+        assert!(issue.message.starts_with("/books/2023.journal: openFile"));
+
+        // Failures that aren't about the journal stay errors
+        let unknown = HLedgerError::from_failure(
+            "hledger check".into(),
+            1,
+            "hledger: Error: unknown check: speling\n".into(),
+        );
+        assert!(matches!(
+            issue_from_error(unknown),
+            Err(HLedgerError::CommandFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_check_unreadable_file() {
+        // The binary is never run for a file that can't be opened
+        let issues = get_check(
+            Some("/nonexistent/hledger"),
+            Some("/nonexistent/main.journal"),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, CheckIssueKind::Unreadable);
+        assert_eq!(issues[0].file.as_deref(), Some("/nonexistent/main.journal"));
+    }
+
+    const STDERR: &str = "\
+hledger: Error: /home/user/assertions.journal:9:35:
+  | 2024-01-31 * Statement
+9 |     assets:bank:checking         $0 = $800.00
+  |                                     ^^^^^^^^^
+
+Balance assertion failed in assets:bank:checking
+In commodity \"$\" at this point, excluding subaccounts, ignoring costs,
+the asserted balance is:        $800.00
+but the calculated balance is:  $750.00
+(difference: $50.00)
+To troubleshoot, check this account's running balance with assertions disabled, eg:
+hledger reg -I 'assets:bank:checking$' cur:'\\$'
+";
+
+    fn journal_error(stderr: &str) -> (String, u32, String) {
+        match HLedgerError::from_failure("hledger check".into(), 1, stderr.into()) {
+            HLedgerError::JournalError {
+                file,
+                line,
+                message,
+                ..
+            } => (file, line, message),
+            e => panic!("expected a journal error, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_parse_assertion_failure() {
+        let (file, line, message) = journal_error(STDERR);
+        let failure = parse_assertion_failure(&file, line, &message).unwrap();
+
+        assert_eq!(failure.file, "/home/user/assertions.journal");
+        assert_eq!(failure.line, 9);
+        assert_eq!(failure.account, "assets:bank:checking");
+        assert_eq!(failure.expected.commodity, "$");
+        assert_eq!(failure.expected.quantity, Decimal::new(800, 0));
+        assert_eq!(failure.actual.quantity, Decimal::new(750, 0));
+        assert_eq!(failure.diff.quantity, Decimal::new(50, 0));
+    }
+
+    #[test]
+    fn test_journal_problem_from_assertion_issue() {
+        let issue = issue_from_error(HLedgerError::from_failure(
+            "hledger check".into(),
+            1,
+            STDERR.into(),
+        ))
+        .unwrap();
+        let problem = JournalProblem::from(issue);
+        assert_eq!(problem.kind, CheckIssueKind::CheckFailed);
+        assert_eq!(
+            problem.file.as_deref(),
+            Some("/home/user/assertions.journal")
+        );
+        assert_eq!((problem.line, problem.column), (Some(9), Some(35)));
+        assert_eq!(problem.expected.unwrap().quantity, Decimal::new(800, 0));
+        assert_eq!(problem.actual.unwrap().quantity, Decimal::new(750, 0));
+
+        // Other problems have no balances
+        let problem = JournalProblem::from(CheckIssue {
+            kind: CheckIssueKind::ParseError,
+            severity: CheckSeverity::Error,
+            file: Some("/books/main.journal".into()),
+            line: Some(1),
+            column: Some(6),
+            message: "invalid date: month must be 1-12".into(),
+        });
+        assert_eq!((problem.expected, problem.actual), (None, None));
+    }
+
+    #[test]
+    fn test_parse_assertion_failure_zero_balance() {
+        let message = "\
+Balance assertion failed in liabilities:card
+In commodity \"EUR\" at this point, including subaccounts, ignoring costs,
+the asserted balance is:        0
+but the calculated balance is:  -25 EUR
+(difference: 25 EUR)";
+        let failure = parse_assertion_failure("card.journal", 4, message).unwrap();
+
+        assert_eq!(failure.expected.commodity, "EUR");
+        assert!(failure.expected.is_zero());
+        assert_eq!(failure.actual.quantity, Decimal::new(-25, 0));
+        assert_eq!(failure.diff.quantity, Decimal::new(25, 0));
+    }
+
+    #[test]
+    fn test_other_journal_errors_are_not_assertions() {
+        let message =
+            "could not balance this transaction:\nreal postings' sum should be 0 but is: $5";
+        assert!(parse_assertion_failure("a.journal", 3, message).is_none());
+    }
+}
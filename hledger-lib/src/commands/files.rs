@@ -0,0 +1,32 @@
+use crate::config::{add_journal_args, check_success, run_command};
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, Result};
+use std::time::Duration;
+
+/// List the journal file and every file it includes
+///
+/// Runs `hledger files`, which prints one path per line, main file first.
+pub fn get_files(hledger_path: Option<&str>, journal_file: Option<&str>) -> Result<Vec<String>> {
+    get_files_within(hledger_path, journal_file, None)
+}
+
+/// [`get_files`], killing hledger after `timeout`
+pub(crate) fn get_files_within(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<Vec<String>> {
+    let mut cmd = get_hledger_command(hledger_path);
+    add_journal_args(&mut cmd, journal_file);
+    cmd.arg("files");
+
+    let output = run_command(&mut cmd, timeout.map(Timeout))?;
+    check_success(&cmd, output.status, &output.stderr)?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
@@ -0,0 +1,512 @@
+use crate::commands::balance::Amount;
+use crate::commands::incomestatement::{
+    get_incomestatement, IncomeStatementOptions, IncomeStatementReport,
+};
+use crate::commands::spending::{most_common_commodity, quantity_in};
+use crate::conversion::ConversionTable;
+use crate::interval::ReportInterval;
+use crate::list_mode::ListMode;
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Id of the node every flow passes through
+const BUDGET_NODE: &str = "budget";
+/// Id of the node taking what is left of income after expenses
+const SAVINGS_NODE: &str = "savings";
+/// Id of the node covering expenses beyond income
+const DEFICIT_NODE: &str = "deficit";
+
+/// Options for [`get_flow_data`]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct FlowOptions {
+    /// Account depth of the income and expense nodes; deeper accounts roll
+    /// up (2 when `None`)
+    pub depth: Option<u32>,
+
+    // Period selection
+    /// Period expression, e.g. `2024-03` or `thisyear`
+    pub period: Option<String>,
+
+    // Date filters
+    /// Begin date (inclusive: transactions on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: transactions before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    // Amounts
+    /// Commodity of the flows; the one used by the most accounts when `None`
+    pub commodity: Option<String>,
+    /// Convert other commodities with the journal's market prices instead of
+    /// leaving them out
+    pub convert: bool,
+
+    // Query patterns
+    pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+/// What a node in the flow graph stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum FlowNodeKind {
+    /// A revenue account
+    Income,
+    /// The central node between income and expenses
+    Budget,
+    /// An expense account
+    Expense,
+    /// Income left over after expenses
+    Savings,
+    /// Expenses not covered by income
+    Deficit,
+}
+
+/// A node in the flow graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct FlowNode {
+    /// Unique id: the full account name, or `budget`, `savings` or `deficit`
+    pub id: String,
+    /// Text to show on the node
+    pub label: String,
+    pub kind: FlowNodeKind,
+}
+
+/// A flow of money between two nodes, always positive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct FlowLink {
+    /// Id of the node the money comes from
+    pub source: String,
+    /// Id of the node the money goes to
+    pub target: String,
+    pub amount: Amount,
+}
+
+/// Income → budget → expense flows, ready for a Sankey diagram
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct FlowGraph {
+    pub nodes: Vec<FlowNode>,
+    pub links: Vec<FlowLink>,
+}
+
+// Implementation for builder pattern
+impl FlowOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn period(mut self, period: impl Into<String>) -> Self {
+        self.period = Some(period.into());
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn commodity(mut self, commodity: impl Into<String>) -> Self {
+        self.commodity = Some(commodity.into());
+        self
+    }
+
+    pub fn convert(mut self) -> Self {
+        self.convert = true;
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.queries.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    /// The flat, depth-limited income statement the flows are built from
+    fn incomestatement_options(&self) -> IncomeStatementOptions {
+        IncomeStatementOptions {
+            list_mode: Some(ListMode::Flat),
+            depth: Some(self.depth.unwrap_or(2)),
+            interval: self
+                .period
+                .clone()
+                .map_or(ReportInterval::None, ReportInterval::Custom),
+            begin: self.begin.clone(),
+            end: self.end.clone(),
+            unchecked_dates: self.unchecked_dates,
+            queries: self.queries.clone(),
+            timeout: self.timeout,
+            ..IncomeStatementOptions::default()
+        }
+    }
+}
+
+/// Money flowing from income accounts through a central budget node to
+/// expense accounts
+///
+/// Built from a flat, depth-limited `hledger incomestatement`, summed over
+/// its periods. Revenues and expenses are both normally positive there and
+/// flow into and out of the budget node respectively; an account with the
+/// opposite sign (e.g. refunds exceeding spending) flows the other way. Any
+/// difference is balanced by a savings or deficit node. Amounts in other
+/// commodities are left out unless `convert` is set, in which case they are
+/// converted with the journal's latest market prices.
+pub fn get_flow_data(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &FlowOptions,
+) -> Result<FlowGraph> {
+    let report = get_incomestatement(
+        hledger_path,
+        journal_file,
+        &options.incomestatement_options(),
+    )?;
+    let table = if options.convert {
+        Some(ConversionTable::load(hledger_path, journal_file, None)?)
+    } else {
+        None
+    };
+    Ok(flow_graph(
+        &report,
+        options.commodity.as_deref(),
+        table.as_ref(),
+    ))
+}
+
+fn flow_graph(
+    report: &IncomeStatementReport,
+    commodity: Option<&str>,
+    table: Option<&ConversionTable>,
+) -> FlowGraph {
+    // Rows of each side with their amounts summed over the periods
+    let mut income = Vec::new();
+    let mut expenses = Vec::new();
+    for subreport in &report.subreports {
+        let side = if subreport.increases_total {
+            &mut income
+        } else {
+            &mut expenses
+        };
+        side.extend(
+            subreport
+                .rows
+                .iter()
+                .map(|row| (row.account.clone(), row.amounts.concat())),
+        );
+    }
+
+    let all_rows: Vec<_> = income.iter().chain(&expenses).cloned().collect();
+    let Some(target) = commodity
+        .map(str::to_string)
+        .or_else(|| most_common_commodity(&all_rows))
+    else {
+        return FlowGraph::default();
+    };
+
+    let mut graph = FlowGraph::default();
+    let mut link = |source: &str, target_node: &str, quantity: Decimal| {
+        graph.links.push(FlowLink {
+            source: source.to_string(),
+            target: target_node.to_string(),
+            amount: Amount {
+                commodity: target.clone(),
+                quantity,
+                price: None,
+            },
+        });
+    };
+
+    let mut income_nodes = Vec::new();
+    let mut expense_nodes = Vec::new();
+    let mut net = Decimal::ZERO;
+    for (account, amounts) in &income {
+        let quantity = quantity_in(amounts, &target, table);
+        if quantity.is_zero() {
+            continue;
+        }
+        if quantity > Decimal::ZERO {
+            link(account, BUDGET_NODE, quantity);
+        } else {
+            link(BUDGET_NODE, account, -quantity);
+        }
+        net += quantity;
+        income_nodes.push(account_node(account, FlowNodeKind::Income));
+    }
+    for (account, amounts) in &expenses {
+        let quantity = quantity_in(amounts, &target, table);
+        if quantity.is_zero() {
+            continue;
+        }
+        if quantity > Decimal::ZERO {
+            link(BUDGET_NODE, account, quantity);
+        } else {
+            link(account, BUDGET_NODE, -quantity);
+        }
+        net -= quantity;
+        expense_nodes.push(account_node(account, FlowNodeKind::Expense));
+    }
+
+    let balance_node = if net > Decimal::ZERO {
+        link(BUDGET_NODE, SAVINGS_NODE, net);
+        Some(special_node(SAVINGS_NODE, "Savings", FlowNodeKind::Savings))
+    } else if net < Decimal::ZERO {
+        link(DEFICIT_NODE, BUDGET_NODE, -net);
+        Some(special_node(DEFICIT_NODE, "Deficit", FlowNodeKind::Deficit))
+    } else {
+        None
+    };
+
+    if graph.links.is_empty() {
+        return graph;
+    }
+    graph.nodes = income_nodes;
+    graph
+        .nodes
+        .push(special_node(BUDGET_NODE, "Budget", FlowNodeKind::Budget));
+    graph.nodes.extend(expense_nodes);
+    graph.nodes.extend(balance_node);
+    graph
+}
+
+fn account_node(account: &str, kind: FlowNodeKind) -> FlowNode {
+    FlowNode {
+        id: account.to_string(),
+        label: account.to_string(),
+        kind,
+    }
+}
+
+fn special_node(id: &str, label: &str, kind: FlowNodeKind) -> FlowNode {
+    FlowNode {
+        id: id.to_string(),
+        label: label.to_string(),
+        kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::PeriodicBalanceRow;
+    use crate::commands::incomestatement::IncomeStatementSubreport;
+    use crate::commands::prices::MarketPrice;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn export_bindings() {
+        FlowOptions::export_all().unwrap();
+        FlowNodeKind::export_all().unwrap();
+        FlowNode::export_all().unwrap();
+        FlowLink::export_all().unwrap();
+        FlowGraph::export_all().unwrap();
+    }
+
+    fn amount(commodity: &str, quantity: &str) -> Amount {
+        Amount {
+            commodity: commodity.to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+        }
+    }
+
+    /// A row with one amount per period
+    fn row(account: &str, periods: &[(&str, &str)]) -> PeriodicBalanceRow {
+        PeriodicBalanceRow {
+            account: account.to_string(),
+            display_name: account.to_string(),
+            indent: 0,
+            amounts: periods.iter().map(|(c, q)| vec![amount(c, q)]).collect(),
+            total: None,
+            average: None,
+        }
+    }
+
+    fn report(
+        revenues: Vec<PeriodicBalanceRow>,
+        expenses: Vec<PeriodicBalanceRow>,
+    ) -> IncomeStatementReport {
+        let subreport = |name: &str, rows, increases_total| IncomeStatementSubreport {
+            name: name.to_string(),
+            dates: vec![],
+            rows,
+            totals: None,
+            increases_total,
+        };
+        IncomeStatementReport {
+            title: "Income Statement".to_string(),
+            dates: vec![],
+            subreports: vec![
+                subreport("Revenues", revenues, true),
+                subreport("Expenses", expenses, false),
+            ],
+            totals: None,
+        }
+    }
+
+    fn links(graph: &FlowGraph) -> Vec<(&str, &str, String)> {
+        graph
+            .links
+            .iter()
+            .map(|l| {
+                (
+                    l.source.as_str(),
+                    l.target.as_str(),
+                    l.amount.quantity.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_incomestatement_options() {
+        let options = FlowOptions::new().incomestatement_options();
+        assert_eq!(options.list_mode, Some(ListMode::Flat));
+        assert_eq!(options.depth, Some(2));
+        assert_eq!(options.interval, ReportInterval::None);
+
+        let options = FlowOptions::new()
+            .depth(1)
+            .period("2024q1")
+            .incomestatement_options();
+        assert_eq!(options.depth, Some(1));
+        assert_eq!(options.interval, ReportInterval::Custom("2024q1".into()));
+    }
+
+    #[test]
+    fn test_flow_graph_with_savings() {
+        let report = report(
+            vec![row("income:salary", &[("$", "3000"), ("$", "3000")])],
+            vec![
+                row("expenses:rent", &[("$", "1200"), ("$", "1200")]),
+                row("expenses:food", &[("$", "400"), ("$", "500")]),
+            ],
+        );
+        let graph = flow_graph(&report, None, None);
+
+        assert_eq!(
+            links(&graph),
+            vec![
+                ("income:salary", "budget", "6000".to_string()),
+                ("budget", "expenses:rent", "2400".to_string()),
+                ("budget", "expenses:food", "900".to_string()),
+                ("budget", "savings", "2700".to_string()),
+            ]
+        );
+        let kinds: Vec<FlowNodeKind> = graph.nodes.iter().map(|n| n.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                FlowNodeKind::Income,
+                FlowNodeKind::Budget,
+                FlowNodeKind::Expense,
+                FlowNodeKind::Expense,
+                FlowNodeKind::Savings,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flow_graph_reverses_negative_rows_and_adds_deficit() {
+        let report = report(
+            vec![row("income:salary", &[("$", "100")])],
+            vec![
+                row("expenses:rent", &[("$", "150")]),
+                // Refunds exceeding spending act as income
+                row("expenses:returns", &[("$", "-20")]),
+            ],
+        );
+        let graph = flow_graph(&report, Some("$"), None);
+
+        assert_eq!(
+            links(&graph),
+            vec![
+                ("income:salary", "budget", "100".to_string()),
+                ("budget", "expenses:rent", "150".to_string()),
+                ("expenses:returns", "budget", "20".to_string()),
+                ("deficit", "budget", "30".to_string()),
+            ]
+        );
+        assert_eq!(graph.nodes.last().unwrap().kind, FlowNodeKind::Deficit);
+    }
+
+    #[test]
+    fn test_flow_graph_converts_commodities() {
+        let prices = vec![MarketPrice {
+            date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            commodity: "EUR".to_string(),
+            price: amount("$", "2"),
+        }];
+        let table = ConversionTable::from_prices(prices, None);
+        let report = report(
+            vec![row("income:salary", &[("EUR", "100")])],
+            vec![row("expenses:food", &[("$", "200")])],
+        );
+
+        // Without conversion the EUR income is left out
+        let graph = flow_graph(&report, Some("$"), None);
+        assert_eq!(
+            links(&graph),
+            vec![
+                ("budget", "expenses:food", "200".to_string()),
+                ("deficit", "budget", "200".to_string()),
+            ]
+        );
+
+        let graph = flow_graph(&report, Some("$"), Some(&table));
+        assert_eq!(
+            links(&graph),
+            vec![
+                ("income:salary", "budget", "200".to_string()),
+                ("budget", "expenses:food", "200".to_string()),
+            ]
+        );
+        assert!(graph.nodes.iter().all(|n| n.kind != FlowNodeKind::Savings));
+    }
+
+    #[test]
+    fn test_flow_graph_empty_report() {
+        let graph = flow_graph(&report(vec![], vec![]), None, None);
+        assert_eq!(graph, FlowGraph::default());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::commands::print::{get_print_from_text, PrintOptions, PrintReport};
+use crate::config::{add_journal_args, check_success, run_command};
+use crate::csv_rules::CsvRules;
+use crate::timeout::Timeout;
+use crate::write::WriteHook;
+use crate::{get_hledger_command, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for the import command
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct ImportOptions {
+    /// Rules to read the files with, written to a temporary rules file for
+    /// the run; takes precedence over `rules_file`
+    pub rules: Option<CsvRules>,
+    /// Rules file to read the files with; hledger looks for `FILE.rules`
+    /// next to each file when neither this nor `rules` is set
+    pub rules_file: Option<String>,
+    /// Only show what would be imported, writing nothing
+    pub dry_run: bool,
+    /// Mark everything in the files as imported without adding it
+    pub catchup: bool,
+    pub timeout: Option<Timeout>,
+    /// Called with the journal before a real import adds to it, e.g. to
+    /// back it up
+    #[serde(skip)]
+    #[ts(skip)]
+    pub before_write: Option<WriteHook>,
+}
+
+// Implementation for builder pattern
+impl ImportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rules(mut self, rules: CsvRules) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    pub fn rules_file(mut self, path: impl Into<String>) -> Self {
+        self.rules_file = Some(path.into());
+        self
+    }
+
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    pub fn catchup(mut self) -> Self {
+        self.catchup = true;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    pub fn before_write(mut self, hook: WriteHook) -> Self {
+        self.before_write = Some(hook);
+        self
+    }
+}
+
+/// Import new transactions from `files` (usually bank CSV exports) into the
+/// journal, returning what hledger prints
+///
+/// hledger remembers the latest date imported from each file in a
+/// `.latest.FILE` next to it and skips anything older on later imports. A
+/// dry run prints the new transactions in journal format and writes
+/// nothing; see [`preview_import`] for them parsed.
+pub fn import_files(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    files: &[&str],
+    options: &ImportOptions,
+) -> Result<String> {
+    let rules_file = match &options.rules {
+        Some(rules) => Some(TempRulesFile::write(rules)?),
+        None => None,
+    };
+    let mut cmd = import_command(
+        hledger_path,
+        journal_file,
+        files,
+        options,
+        rules_file.as_ref().map(|f| f.0.as_path()),
+    );
+    // Dry runs and catch-ups leave the journal as it is
+    if !options.dry_run && !options.catchup {
+        if let Some(file) = journal_file {
+            WriteHook::call(options.before_write.as_ref(), Path::new(file))?;
+        }
+    }
+    let output = run_command(&mut cmd, options.timeout)?;
+    check_success(&cmd, output.status, &output.stderr)?;
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// The transactions importing `files` would add, without adding them
+///
+/// Runs a dry-run import and parses its output with hledger again. Source
+/// positions point into that output rather than the imported files.
+pub fn preview_import(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    files: &[&str],
+    options: &ImportOptions,
+) -> Result<PrintReport> {
+    let options = ImportOptions {
+        dry_run: true,
+        catchup: false,
+        ..options.clone()
+    };
+    let text = import_files(hledger_path, journal_file, files, &options)?;
+
+    let mut print_options = PrintOptions::new();
+    print_options.timeout = options.timeout;
+    get_print_from_text(hledger_path, &text, &print_options)
+}
+
+/// Build the hledger command for importing
+fn import_command(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    files: &[&str],
+    options: &ImportOptions,
+    generated_rules: Option<&Path>,
+) -> Command {
+    let mut cmd = get_hledger_command(hledger_path);
+
+    add_journal_args(&mut cmd, journal_file);
+
+    cmd.arg("import");
+
+    match (generated_rules, &options.rules_file) {
+        (Some(path), _) => {
+            cmd.arg("--rules-file").arg(path);
+        }
+        (None, Some(path)) => {
+            cmd.arg("--rules-file").arg(path);
+        }
+        (None, None) => {}
+    }
+    if options.dry_run {
+        cmd.arg("--dry-run");
+    }
+    if options.catchup {
+        cmd.arg("--catchup");
+    }
+
+    cmd.arg("--").args(files);
+    cmd
+}
+
+/// A rules file in the temp directory, removed on drop
+struct TempRulesFile(PathBuf);
+
+impl TempRulesFile {
+    fn write(rules: &CsvRules) -> Result<Self> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "hledger-import-{}-{}.rules",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        rules.write_to(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempRulesFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::command_line;
+    use crate::csv_rules::CsvCondition;
+
+    #[test]
+    fn export_bindings() {
+        ImportOptions::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_import_options_builder() {
+        let options = ImportOptions::new()
+            .rules_file("bank.rules")
+            .dry_run()
+            .catchup()
+            .timeout(Duration::from_secs(5));
+        assert_eq!(options.rules_file.as_deref(), Some("bank.rules"));
+        assert!(options.dry_run);
+        assert!(options.catchup);
+        assert_eq!(options.timeout, Some(Timeout(Duration::from_secs(5))));
+    }
+
+    #[test]
+    fn test_import_command() {
+        let options = ImportOptions::new().rules_file("bank.rules").dry_run();
+        let cmd = import_command(
+            None,
+            Some("main.journal"),
+            &["-statement.csv"],
+            &options,
+            None,
+        );
+        assert_eq!(
+            command_line(&cmd),
+            "hledger -f main.journal import --rules-file bank.rules --dry-run -- -statement.csv"
+        );
+
+        // Generated rules win over a rules file
+        let cmd = import_command(
+            None,
+            None,
+            &["a.csv"],
+            &options,
+            Some(Path::new("/tmp/gen.rules")),
+        );
+        assert_eq!(
+            command_line(&cmd),
+            "hledger import --rules-file /tmp/gen.rules --dry-run -- a.csv"
+        );
+    }
+
+    #[test]
+    fn test_temp_rules_file_is_removed() {
+        let rules = CsvRules::new()
+            .fields(["date", "description", "amount"])
+            .condition(CsvCondition::new("COFFEE").account2("expenses:coffee"));
+        let file = TempRulesFile::write(&rules).unwrap();
+        let path = file.0.clone();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            rules.to_rules_string().unwrap()
+        );
+
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_invalid_rules_fail_before_running() {
+        let options = ImportOptions::new().rules(CsvRules::new().account1("a\nb"));
+        let result = import_files(Some("/nonexistent/hledger"), None, &["a.csv"], &options);
+        assert!(matches!(
+            result,
+            Err(crate::HLedgerError::InvalidCsvRules(_))
+        ));
+    }
+}
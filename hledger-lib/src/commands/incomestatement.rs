@@ -1,11 +1,26 @@
-use crate::commands::balance::{PeriodDate, PeriodicBalanceRow};
-use crate::{get_hledger_command, HLedgerError, Result};
-use serde::{Deserialize, Serialize};
+use crate::commands::balance::{apply_tree_layout, PeriodDate, PeriodicBalanceRow};
+use crate::commands::json::{period_dates, totals_row, JsonCompoundReport};
+use crate::config::{add_journal_args, add_query_args, run_json_command, run_json_command_piped};
+use crate::csv_report::{run_csv_command, CsvReport};
+use crate::dates::validate_date_options;
+use crate::interval::{LegacyInterval, ReportInterval};
+use crate::list_mode::{LegacyListMode, ListMode};
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, Result};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::process::Command;
+use std::time::Duration;
 use ts_rs::TS;
 
 /// Options for the incomestatement command
 #[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+// Derived as inherent functions, which the trait impls below wrap to fold in
+// the deprecated fields
+#[serde(rename_all = "camelCase", remote = "Self")]
+#[ts(rename_all = "camelCase")]
 pub struct IncomeStatementOptions {
     // Calculation modes (mutually exclusive)
     /// Show sum of posting amounts (default)
@@ -24,10 +39,9 @@ pub struct IncomeStatementOptions {
     pub historical: bool,
 
     // List/tree modes
-    /// Show accounts as flat list (default)
-    pub flat: bool,
-    /// Show accounts as tree
-    pub tree: bool,
+    /// Show accounts as a flat list or a tree; `None` leaves it to hledger
+    /// (flat, unless a config file says otherwise)
+    pub list_mode: Option<ListMode>,
     /// Omit N leading account name parts
     pub drop: Option<u32>,
     /// Include non-parent declared accounts
@@ -54,24 +68,17 @@ pub struct IncomeStatementOptions {
     pub layout: Option<String>,
 
     // Period selection
-    /// Daily periods
-    pub daily: bool,
-    /// Weekly periods
-    pub weekly: bool,
-    /// Monthly periods
-    pub monthly: bool,
-    /// Quarterly periods
-    pub quarterly: bool,
-    /// Yearly periods
-    pub yearly: bool,
-    /// Custom period
-    pub period: Option<String>,
+    /// How the report is split into periods
+    #[serde(default)]
+    pub interval: ReportInterval,
 
     // Date filters
     /// Begin date (inclusive: transactions on or after this date)
     pub begin: Option<String>,
     /// End date (exclusive: transactions before this date)
     pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
 
     // Other filters
     /// Limit depth of accounts shown
@@ -86,6 +93,8 @@ pub struct IncomeStatementOptions {
     pub real: bool,
     /// Show zero items
     pub empty: bool,
+    /// Skip balance assertions, e.g. to see the report while one fails
+    pub ignore_assertions: bool,
 
     // Valuation options
     /// Convert to cost basis
@@ -99,11 +108,32 @@ pub struct IncomeStatementOptions {
 
     // Query patterns
     pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+impl Serialize for IncomeStatementOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        IncomeStatementOptions::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for IncomeStatementOptions {
+    /// Reads the options, folding the deprecated interval and `tree`/`flat`
+    /// fields into `interval` and `list_mode`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        let mut options = IncomeStatementOptions::deserialize(&json).map_err(de::Error::custom)?;
+        options.interval = LegacyInterval::resolve(options.interval, &json)?;
+        options.list_mode = LegacyListMode::resolve(options.list_mode, &json)?;
+        Ok(options)
+    }
 }
 
 /// A subreport in the income statement (Revenues, Expenses)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct IncomeStatementSubreport {
     /// The name of the subreport (e.g., "Revenues", "Expenses")
     pub name: String,
@@ -121,7 +151,9 @@ pub struct IncomeStatementSubreport {
 
 /// Income statement report structure
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct IncomeStatementReport {
     /// Report title
     pub title: String,
@@ -141,32 +173,37 @@ impl IncomeStatementOptions {
 
     // Period options
     pub fn daily(mut self) -> Self {
-        self.daily = true;
+        self.interval = ReportInterval::Daily;
         self
     }
 
     pub fn weekly(mut self) -> Self {
-        self.weekly = true;
+        self.interval = ReportInterval::Weekly;
         self
     }
 
     pub fn monthly(mut self) -> Self {
-        self.monthly = true;
+        self.interval = ReportInterval::Monthly;
         self
     }
 
     pub fn quarterly(mut self) -> Self {
-        self.quarterly = true;
+        self.interval = ReportInterval::Quarterly;
         self
     }
 
     pub fn yearly(mut self) -> Self {
-        self.yearly = true;
+        self.interval = ReportInterval::Yearly;
         self
     }
 
     pub fn period(mut self, period: impl Into<String>) -> Self {
-        self.period = Some(period.into());
+        self.interval = ReportInterval::Custom(period.into());
+        self
+    }
+
+    pub fn interval(mut self, interval: ReportInterval) -> Self {
+        self.interval = interval;
         self
     }
 
@@ -188,14 +225,12 @@ impl IncomeStatementOptions {
 
     // Display modes
     pub fn tree(mut self) -> Self {
-        self.tree = true;
-        self.flat = false;
+        self.list_mode = Some(ListMode::Tree);
         self
     }
 
     pub fn flat(mut self) -> Self {
-        self.flat = true;
-        self.tree = false;
+        self.list_mode = Some(ListMode::Flat);
         self
     }
 
@@ -226,6 +261,11 @@ impl IncomeStatementOptions {
         self
     }
 
+    pub fn ignore_assertions(mut self) -> Self {
+        self.ignore_assertions = true;
+        self
+    }
+
     pub fn begin(mut self, date: impl Into<String>) -> Self {
         self.begin = Some(date.into());
         self
@@ -236,11 +276,21 @@ impl IncomeStatementOptions {
         self
     }
 
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
     pub fn query(mut self, query: impl Into<String>) -> Self {
         self.queries.push(query.into());
         self
     }
 
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
     pub fn queries(mut self, queries: Vec<String>) -> Self {
         self.queries = queries;
         self
@@ -272,6 +322,11 @@ impl IncomeStatementOptions {
         self.gain = true;
         self
     }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
 }
 
 /// Get income statement report from hledger
@@ -280,36 +335,91 @@ pub fn get_incomestatement(
     journal_file: Option<&str>,
     options: &IncomeStatementOptions,
 ) -> Result<IncomeStatementReport> {
-    let mut cmd = get_hledger_command(hledger_path);
+    let mut cmd = incomestatement_command(hledger_path, journal_file, options, "json")?;
+    incomestatement_report(run_json_command(&mut cmd, options.timeout)?, options)
+}
 
-    if let Some(file) = journal_file {
-        cmd.arg("-f").arg(file);
+/// Get income statement report for journal text that isn't on disk
+///
+/// The text is piped to `hledger -f -`, as for [`crate::get_balance_from_text`].
+pub fn get_incomestatement_from_text(
+    hledger_path: Option<&str>,
+    journal_text: &str,
+    options: &IncomeStatementOptions,
+) -> Result<IncomeStatementReport> {
+    let mut cmd = incomestatement_command(hledger_path, Some("-"), options, "json")?;
+    let json = run_json_command_piped(&mut cmd, Some(journal_text), options.timeout)?;
+    incomestatement_report(json, options)
+}
+
+/// Convert hledger's income statement JSON and apply the options hledger leaves to us
+fn incomestatement_report(
+    json: JsonCompoundReport,
+    options: &IncomeStatementOptions,
+) -> Result<IncomeStatementReport> {
+    let mut report = parse_incomestatement_report(json)?;
+    let tree = options.list_mode == Some(ListMode::Tree);
+    for subreport in &mut report.subreports {
+        if tree {
+            apply_tree_layout(&mut subreport.rows);
+        }
+        if options.no_total {
+            subreport.totals = None;
+        }
+    }
+    if options.no_total {
+        report.totals = None;
+    }
+
+    Ok(report)
+}
+
+/// Get the income statement as hledger's JSON, untouched
+///
+/// For fields the typed [`IncomeStatementReport`] doesn't model yet. The command is built
+/// exactly as for [`get_incomestatement`].
+pub fn get_incomestatement_raw(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &IncomeStatementOptions,
+) -> Result<serde_json::Value> {
+    let mut cmd = incomestatement_command(hledger_path, journal_file, options, "json")?;
+    run_json_command(&mut cmd, options.timeout)
+}
+
+/// Get the income statement as hledger's CSV (`-O csv`), section by section
+///
+/// The command is built exactly as for [`get_incomestatement`].
+pub fn get_incomestatement_csv(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &IncomeStatementOptions,
+) -> Result<CsvReport> {
+    let mut cmd = incomestatement_command(hledger_path, journal_file, options, "csv")?;
+    run_csv_command(&mut cmd, options.timeout)
+}
+
+/// Build the hledger command for the income statement
+fn incomestatement_command(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &IncomeStatementOptions,
+    output_format: &str,
+) -> Result<Command> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
     }
 
+    let mut cmd = get_hledger_command(hledger_path);
+
+    add_journal_args(&mut cmd, journal_file);
+
     cmd.arg("incomestatement");
 
-    // Always output JSON
-    cmd.arg("--output-format").arg("json");
+    cmd.arg("--output-format").arg(output_format);
 
     // Add period flags
-    if options.daily {
-        cmd.arg("--daily");
-    }
-    if options.weekly {
-        cmd.arg("--weekly");
-    }
-    if options.monthly {
-        cmd.arg("--monthly");
-    }
-    if options.quarterly {
-        cmd.arg("--quarterly");
-    }
-    if options.yearly {
-        cmd.arg("--yearly");
-    }
-    if let Some(period) = &options.period {
-        cmd.arg("--period").arg(period);
-    }
+    options.interval.add_args(&mut cmd);
 
     // Calculation modes
     if options.valuechange {
@@ -331,11 +441,7 @@ pub fn get_incomestatement(
     }
 
     // List/tree modes
-    if options.tree {
-        cmd.arg("--tree");
-    } else {
-        cmd.arg("--flat");
-    }
+    ListMode::add_args(options.list_mode, &mut cmd);
 
     if let Some(n) = options.drop {
         cmd.arg(format!("--drop={}", n));
@@ -380,6 +486,9 @@ pub fn get_incomestatement(
     if options.empty {
         cmd.arg("--empty");
     }
+    if options.ignore_assertions {
+        cmd.arg("--ignore-assertions");
+    }
 
     // Date filters
     if let Some(begin) = &options.begin {
@@ -418,218 +527,30 @@ pub fn get_incomestatement(
     }
 
     // Query patterns
-    for query in &options.queries {
-        cmd.arg(query);
-    }
+    add_query_args(&mut cmd, &options.queries);
 
-    let output = cmd.output().map_err(|e| {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            HLedgerError::HLedgerNotFound
-        } else {
-            HLedgerError::Io(e)
-        }
-    })?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(HLedgerError::CommandFailed {
-            code: output.status.code().unwrap_or(-1),
-            stderr: stderr.to_string(),
-        });
-    }
-
-    let stdout = String::from_utf8(output.stdout)?;
-
-    // Parse the JSON output
-    let json_value: serde_json::Value = serde_json::from_str(&stdout)?;
-
-    parse_incomestatement_report(&json_value)
+    Ok(cmd)
 }
 
-/// Parse income statement report from JSON
-fn parse_incomestatement_report(value: &serde_json::Value) -> Result<IncomeStatementReport> {
-    use crate::commands::balance::extract_date_from_tagged_value;
-    let obj = value.as_object().ok_or_else(|| {
-        HLedgerError::ParseError("Expected object for income statement report".to_string())
-    })?;
-
-    // Parse title
-    let title = obj
-        .get("cbrTitle")
-        .and_then(|t| t.as_str())
-        .unwrap_or("Income Statement")
-        .to_string();
-
-    // Parse dates
-    let dates_json = obj.get("cbrDates").ok_or_else(|| {
-        HLedgerError::ParseError("Missing cbrDates in income statement".to_string())
-    })?;
-
-    let mut dates = Vec::new();
-    if let Some(dates_array) = dates_json.as_array() {
-        for date_pair in dates_array {
-            if let Some(pair) = date_pair.as_array() {
-                if pair.len() == 2 {
-                    let start = extract_date_from_tagged_value(&pair[0]);
-                    let end = extract_date_from_tagged_value(&pair[1]);
-                    dates.push(PeriodDate { start, end });
-                }
-            }
-        }
-    }
-
-    // Parse subreports
-    let subreports_json = obj.get("cbrSubreports").ok_or_else(|| {
-        HLedgerError::ParseError("Missing cbrSubreports in income statement".to_string())
-    })?;
-
+/// Convert hledger's compound income statement report
+fn parse_incomestatement_report(json: JsonCompoundReport) -> Result<IncomeStatementReport> {
     let mut subreports = Vec::new();
-    if let Some(subreports_array) = subreports_json.as_array() {
-        for subreport_entry in subreports_array {
-            if let Some(entry_array) = subreport_entry.as_array() {
-                if entry_array.len() >= 3 {
-                    let name = entry_array[0].as_str().unwrap_or("").to_string();
-                    let report_data = &entry_array[1];
-                    let increases_total = entry_array[2].as_bool().unwrap_or(false);
-
-                    let subreport =
-                        parse_incomestatement_subreport(name, report_data, increases_total)?;
-                    subreports.push(subreport);
-                }
-            }
-        }
+    for (name, report, increases_total) in json.subreports {
+        let report = report.into_periodic_balance()?;
+        subreports.push(IncomeStatementSubreport {
+            name,
+            dates: report.dates,
+            rows: report.rows,
+            totals: report.totals,
+            increases_total,
+        });
     }
 
-    // Parse totals
-    let totals = if let Some(totals_json) = obj.get("cbrTotals") {
-        Some(parse_periodic_row(totals_json)?)
-    } else {
-        None
-    };
-
     Ok(IncomeStatementReport {
-        title,
-        dates,
+        title: json.title.unwrap_or_else(|| "Income Statement".to_string()),
+        dates: period_dates(json.dates),
         subreports,
-        totals,
-    })
-}
-
-/// Parse an income statement subreport
-fn parse_incomestatement_subreport(
-    name: String,
-    value: &serde_json::Value,
-    increases_total: bool,
-) -> Result<IncomeStatementSubreport> {
-    use crate::commands::balance::extract_date_from_tagged_value;
-    let obj = value
-        .as_object()
-        .ok_or_else(|| HLedgerError::ParseError("Subreport should be an object".to_string()))?;
-
-    // Parse dates
-    let dates_json = obj
-        .get("prDates")
-        .ok_or_else(|| HLedgerError::ParseError("Missing prDates in subreport".to_string()))?;
-
-    let mut dates = Vec::new();
-    if let Some(dates_array) = dates_json.as_array() {
-        for date_pair in dates_array {
-            if let Some(pair) = date_pair.as_array() {
-                if pair.len() == 2 {
-                    let start = extract_date_from_tagged_value(&pair[0]);
-                    let end = extract_date_from_tagged_value(&pair[1]);
-                    dates.push(PeriodDate { start, end });
-                }
-            }
-        }
-    }
-
-    // Parse rows
-    let rows_json = obj
-        .get("prRows")
-        .ok_or_else(|| HLedgerError::ParseError("Missing prRows in subreport".to_string()))?;
-
-    let mut rows = Vec::new();
-    if let Some(rows_array) = rows_json.as_array() {
-        for row_json in rows_array {
-            let row = parse_periodic_row(row_json)?;
-            rows.push(row);
-        }
-    }
-
-    // Parse totals
-    let totals = if let Some(totals_json) = obj.get("prTotals") {
-        Some(parse_periodic_row(totals_json)?)
-    } else {
-        None
-    };
-
-    Ok(IncomeStatementSubreport {
-        name,
-        dates,
-        rows,
-        totals,
-        increases_total,
-    })
-}
-
-/// Parse a periodic balance row (reusing from balance.rs)
-fn parse_periodic_row(value: &serde_json::Value) -> Result<PeriodicBalanceRow> {
-    use crate::commands::balance::parse_amounts;
-
-    let obj = value
-        .as_object()
-        .ok_or_else(|| HLedgerError::ParseError("Periodic row should be an object".to_string()))?;
-
-    // Extract account name
-    let account = obj
-        .get("prrName")
-        .and_then(|n| {
-            if let Some(s) = n.as_str() {
-                Some(s.to_string())
-            } else if let Some(arr) = n.as_array() {
-                // Handle empty array case for totals
-                if arr.is_empty() {
-                    Some("".to_string())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
-        .unwrap_or_else(|| "".to_string());
-
-    let display_name = account.clone();
-
-    // Parse period amounts (prrAmounts is an array of arrays of amounts)
-    let mut amounts = Vec::new();
-    if let Some(amounts_array) = obj.get("prrAmounts").and_then(|a| a.as_array()) {
-        for period_amounts in amounts_array {
-            amounts.push(parse_amounts(period_amounts)?);
-        }
-    }
-
-    // Parse total
-    let total = if let Some(total_amounts) = obj.get("prrTotal") {
-        Some(parse_amounts(total_amounts)?)
-    } else {
-        None
-    };
-
-    // Parse average
-    let average = if let Some(avg_amounts) = obj.get("prrAverage") {
-        Some(parse_amounts(avg_amounts)?)
-    } else {
-        None
-    };
-
-    Ok(PeriodicBalanceRow {
-        account,
-        display_name,
-        amounts,
-        total,
-        average,
+        totals: totals_row(json.totals)?,
     })
 }
 
@@ -654,15 +575,29 @@ mod tests {
             .average()
             .query("expenses");
 
-        assert!(options.monthly);
-        assert!(options.tree);
-        assert!(!options.flat);
+        assert_eq!(options.interval, ReportInterval::Monthly);
+        assert_eq!(options.list_mode, Some(ListMode::Tree));
         assert_eq!(options.depth, Some(2));
         assert!(options.row_total);
         assert!(options.average);
         assert_eq!(options.queries, vec!["expenses"]);
     }
 
+    #[test]
+    fn test_list_mode_flag_only_when_chosen() {
+        let args = |options: &IncomeStatementOptions| -> Vec<String> {
+            incomestatement_command(None, None, options, "json")
+                .unwrap()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        };
+        let default = args(&IncomeStatementOptions::new());
+        assert!(!default.iter().any(|arg| arg == "--flat" || arg == "--tree"));
+        assert!(args(&IncomeStatementOptions::new().flat()).contains(&"--flat".to_string()));
+        assert!(args(&IncomeStatementOptions::new().tree()).contains(&"--tree".to_string()));
+    }
+
     #[test]
     fn test_incomestatement_options_accumulation_modes() {
         let options = IncomeStatementOptions::new().historical();
@@ -683,4 +618,20 @@ mod tests {
         let options = IncomeStatementOptions::new().gain();
         assert!(options.gain);
     }
+
+    #[test]
+    fn test_parse_requires_report_keys() {
+        use crate::commands::json::from_value;
+
+        let no_dates = serde_json::json!({"prRows": [], "prTotals": null});
+        for json in [
+            serde_json::json!({}),
+            serde_json::json!({"cbrDates": [], "cbrSubreports": [["Revenues", no_dates, true]]}),
+        ] {
+            assert!(matches!(
+                from_value::<JsonCompoundReport>(&json),
+                Err(crate::HLedgerError::ParseError(_))
+            ));
+        }
+    }
 }
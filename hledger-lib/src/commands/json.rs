@@ -0,0 +1,844 @@
+//! Parsers for the pieces of hledger's JSON output the report commands share:
+//! decimals, amounts and their prices, dates, and periodic report rows
+//!
+//! Each piece has a `Json*` type that deserializes straight from hledger's
+//! output, so a report is read off its stdout without building a
+//! `serde_json::Value` first, and is converted to the public type afterwards.
+//! The `parse_*` functions do the same for callers that already have a
+//! `Value`.
+
+use crate::commands::balance::{
+    Amount, PeriodDate, PeriodicBalance, PeriodicBalanceRow, Price, PriceKind,
+};
+use crate::{HLedgerError, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+/// Read `T` from part of an already parsed document, reporting a mismatch
+/// as a [`HLedgerError::ParseError`]
+pub(crate) fn from_value<'de, T: Deserialize<'de>>(value: &'de serde_json::Value) -> Result<T> {
+    T::deserialize(value).map_err(|e| HLedgerError::ParseError(e.to_string()))
+}
+
+/// The error for JSON that failed to deserialize: a
+/// [`HLedgerError::ParseError`] if it was well formed but not the expected
+/// shape, else [`HLedgerError::Json`]
+pub(crate) fn json_error(e: serde_json::Error) -> HLedgerError {
+    match e.classify() {
+        serde_json::error::Category::Data => HLedgerError::ParseError(e.to_string()),
+        _ => HLedgerError::Json(e),
+    }
+}
+
+/// A decimal as hledger writes it
+///
+/// hledger writes `{"decimalMantissa", "decimalPlaces", "floatingPoint"}`;
+/// plain numbers and numeric strings are accepted as well.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct JsonDecimal(pub(crate) Decimal);
+
+impl<'de> Deserialize<'de> for JsonDecimal {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+struct DecimalVisitor;
+
+impl<'de> Visitor<'de> for DecimalVisitor {
+    type Value = JsonDecimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal object, number or numeric string")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<JsonDecimal, E> {
+        Ok(JsonDecimal(Decimal::from(v)))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<JsonDecimal, E> {
+        Ok(JsonDecimal(Decimal::from(v)))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<JsonDecimal, E> {
+        Decimal::from_f64_retain(v)
+            .map(JsonDecimal)
+            .ok_or_else(|| E::custom("Invalid decimal number"))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<JsonDecimal, E> {
+        v.parse()
+            .map(JsonDecimal)
+            .map_err(|_| E::custom("Invalid decimal string"))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<JsonDecimal, A::Error> {
+        #[derive(Deserialize)]
+        enum Field {
+            #[serde(rename = "decimalMantissa")]
+            Mantissa,
+            #[serde(rename = "decimalPlaces")]
+            Places,
+            #[serde(rename = "floatingPoint")]
+            FloatingPoint,
+            #[serde(other)]
+            Other,
+        }
+
+        let (mut mantissa, mut places, mut floating_point) = (None, None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Mantissa => mantissa = Some(map.next_value::<Mantissa>()?),
+                Field::Places => places = map.next_value::<Option<u32>>()?,
+                Field::FloatingPoint => floating_point = map.next_value::<Option<f64>>()?,
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let mantissa = mantissa.ok_or_else(|| de::Error::missing_field("decimalMantissa"))?;
+        let places = places.unwrap_or(0);
+        decimal_from_mantissa(&mantissa, places, floating_point)
+            .map(JsonDecimal)
+            .ok_or_else(|| {
+                de::Error::custom(format!(
+                    "Decimal mantissa out of range: {} (places: {})",
+                    mantissa, places
+                ))
+            })
+    }
+}
+
+/// `decimalMantissa`, kept exact when serde_json reads it as an integer or
+/// hledger writes it as a string
+enum Mantissa {
+    Exact(i128),
+    Float(f64),
+    Text(String),
+}
+
+impl<'de> Deserialize<'de> for Mantissa {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MantissaVisitor;
+
+        impl Visitor<'_> for MantissaVisitor {
+            type Value = Mantissa;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a decimal mantissa")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Mantissa, E> {
+                Ok(Mantissa::Exact(i128::from(v)))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Mantissa, E> {
+                Ok(Mantissa::Exact(i128::from(v)))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Mantissa, E> {
+                Ok(Mantissa::Float(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Mantissa, E> {
+                Ok(v.parse()
+                    .map(Mantissa::Exact)
+                    .unwrap_or_else(|_| Mantissa::Text(v.to_string())))
+            }
+        }
+
+        deserializer.deserialize_any(MantissaVisitor)
+    }
+}
+
+impl fmt::Display for Mantissa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mantissa::Exact(m) => write!(f, "{}", m),
+            Mantissa::Float(m) => write!(f, "{}", m),
+            Mantissa::Text(m) => write!(f, "{:?}", m),
+        }
+    }
+}
+
+/// Build a Decimal from hledger's `decimalMantissa`/`decimalPlaces` pair
+///
+/// Mantissas that overflow `i64` are read exactly when serde_json keeps them as
+/// a `u64` or hledger emits them as a string. Larger mantissas only survive as
+/// an `f64`; in that case the value is rebuilt from `floatingPoint` (or the
+/// float mantissa) and rounded to `decimalPlaces`, which is lossy beyond
+/// roughly 15 significant digits.
+fn decimal_from_mantissa(
+    mantissa: &Mantissa,
+    places: u32,
+    floating_point: Option<f64>,
+) -> Option<Decimal> {
+    if let Mantissa::Exact(m) = *mantissa {
+        if let Ok(decimal) = Decimal::try_from_i128_with_scale(m, places) {
+            return Some(decimal);
+        }
+    }
+
+    // Lossy fallback for mantissas beyond what Decimal or serde_json can hold exactly
+    let float_mantissa = match *mantissa {
+        Mantissa::Exact(m) => Some(m as f64),
+        Mantissa::Float(m) => Some(m),
+        Mantissa::Text(_) => None,
+    };
+    floating_point
+        .or_else(|| float_mantissa.map(|m| m / 10f64.powi(places as i32)))
+        .and_then(Decimal::from_f64_retain)
+        .map(|decimal| decimal.round_dp(places))
+}
+
+/// An amount as hledger writes it (`acommodity`, `aquantity`, `aprice`)
+///
+/// A missing commodity is the empty one and a missing quantity is zero; the
+/// amount itself must be an object. Its style (`astyle`) is read as `S` for
+/// callers that need it, and skipped otherwise.
+#[derive(Deserialize)]
+pub(crate) struct JsonAmount<S = IgnoredAny> {
+    #[serde(default)]
+    acommodity: String,
+    #[serde(default)]
+    aquantity: JsonDecimal,
+    aprice: Option<JsonPrice>,
+    pub(crate) astyle: Option<S>,
+}
+
+impl<S> JsonAmount<S> {
+    pub(crate) fn into_amount(self) -> Result<Amount> {
+        Ok(Amount {
+            commodity: self.acommodity,
+            quantity: self.aquantity.0,
+            price: self.aprice.map(JsonPrice::into_price).transpose()?,
+        })
+    }
+}
+
+/// A list of amounts
+///
+/// A `null` list (which some report rows have) is empty; an entry that isn't
+/// an amount is an error rather than being skipped.
+#[derive(Default)]
+pub(crate) struct JsonAmounts(Vec<JsonAmount>);
+
+impl<'de> Deserialize<'de> for JsonAmounts {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<Vec<JsonAmount>>::deserialize(deserializer)
+            .map(|amounts| JsonAmounts(amounts.unwrap_or_default()))
+    }
+}
+
+impl JsonAmounts {
+    pub(crate) fn into_amounts(self) -> Result<Vec<Amount>> {
+        self.0.into_iter().map(JsonAmount::into_amount).collect()
+    }
+}
+
+/// A price as hledger writes it
+///
+/// hledger emits prices as `{"tag": "UnitPrice" | "TotalPrice", "contents": <amount>}`,
+/// and `null` for an amount without one. Older versions stored the amount
+/// under `priceAmount` instead of `contents`; both are read, with `contents`
+/// taking precedence.
+#[derive(Deserialize)]
+pub(crate) struct JsonPrice {
+    tag: Option<String>,
+    contents: Option<Box<JsonAmount>>,
+    #[serde(rename = "priceAmount")]
+    price_amount: Option<Box<JsonAmount>>,
+}
+
+impl JsonPrice {
+    /// The price, failing for an unknown kind or a price without an amount
+    pub(crate) fn into_price(self) -> Result<Price> {
+        let kind = match self.tag.as_deref() {
+            Some("UnitPrice") | None => PriceKind::Unit,
+            Some("TotalPrice") => PriceKind::Total,
+            Some(other) => {
+                return Err(HLedgerError::ParseError(format!(
+                    "Unknown price kind: {}",
+                    other
+                )))
+            }
+        };
+
+        let amount = self
+            .contents
+            .or(self.price_amount)
+            .ok_or_else(|| HLedgerError::ParseError("Price has no amount".to_string()))?;
+        let Amount {
+            commodity,
+            quantity,
+            ..
+        } = amount.into_amount()?;
+
+        Ok(Price {
+            kind,
+            commodity,
+            quantity,
+        })
+    }
+}
+
+/// Parse a list of amounts from an already parsed document
+pub(crate) fn parse_amounts(value: &serde_json::Value) -> Result<Vec<Amount>> {
+    from_value::<JsonAmounts>(value)?.into_amounts()
+}
+
+/// Parse an ISO `YYYY-MM-DD` date string
+pub(crate) fn parse_date(s: &str) -> Result<NaiveDate> {
+    parse_iso_date(s).map_err(HLedgerError::ParseError)
+}
+
+fn parse_iso_date(s: &str) -> std::result::Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", s, e))
+}
+
+/// A date as hledger writes it
+///
+/// hledger emits period boundaries as `{"tag": "Exact", "contents": "2024-01-01"}`,
+/// but plain date strings and nested tagged values are accepted as well.
+pub(crate) struct JsonDate(NaiveDate);
+
+impl<'de> Deserialize<'de> for JsonDate {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DateVisitor;
+
+        impl<'de> Visitor<'de> for DateVisitor {
+            type Value = JsonDate;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a date or tagged date")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<JsonDate, E> {
+                parse_iso_date(v).map(JsonDate).map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<JsonDate, A::Error> {
+                #[derive(Deserialize)]
+                #[serde(rename_all = "lowercase")]
+                enum Field {
+                    Contents,
+                    #[serde(other)]
+                    Other,
+                }
+
+                let mut date = None;
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::Contents => date = Some(map.next_value::<JsonDate>()?),
+                        Field::Other => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                date.ok_or_else(|| de::Error::missing_field("contents"))
+            }
+        }
+
+        deserializer.deserialize_any(DateVisitor)
+    }
+}
+
+/// Report periods as hledger writes them: a list of `[start, end]` pairs
+pub(crate) type JsonPeriodDates = Vec<(JsonDate, JsonDate)>;
+
+/// Convert report periods
+pub(crate) fn period_dates(dates: JsonPeriodDates) -> Vec<PeriodDate> {
+    dates
+        .into_iter()
+        .map(|(start, end)| PeriodDate {
+            start: start.0,
+            end: end.0,
+        })
+        .collect()
+}
+
+/// Parse a list of `[start, end]` report periods from an already parsed
+/// document; a missing list is empty
+pub(crate) fn parse_period_dates(value: Option<&serde_json::Value>) -> Result<Vec<PeriodDate>> {
+    let dates = value.map(from_value::<JsonPeriodDates>).transpose()?;
+    Ok(period_dates(dates.unwrap_or_default()))
+}
+
+/// Account name of a periodic row
+///
+/// Totals rows carry an empty array instead of a string.
+#[derive(Default)]
+pub(crate) struct JsonRowName(String);
+
+impl<'de> Deserialize<'de> for JsonRowName {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RowNameVisitor;
+
+        impl<'de> Visitor<'de> for RowNameVisitor {
+            type Value = JsonRowName;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an account name or a list of its parts")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<JsonRowName, E> {
+                Ok(JsonRowName(v.to_string()))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> std::result::Result<JsonRowName, E> {
+                Ok(JsonRowName::default())
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<JsonRowName, A::Error> {
+                let mut parts = Vec::new();
+                while let Some(part) = seq.next_element::<String>()? {
+                    parts.push(part);
+                }
+                Ok(JsonRowName(parts.join(":")))
+            }
+        }
+
+        deserializer.deserialize_any(RowNameVisitor)
+    }
+}
+
+/// Account name of a periodic row in an already parsed document, empty if
+/// it has none
+pub(crate) fn parse_row_name(value: Option<&serde_json::Value>) -> String {
+    value
+        .and_then(|value| from_value::<JsonRowName>(value).ok())
+        .unwrap_or_default()
+        .0
+}
+
+/// A periodic report row (`prrName`, `prrAmounts`, `prrTotal`, `prrAverage`)
+///
+/// Shared by the balance, balancesheet, incomestatement and cashflow parsers.
+#[derive(Deserialize)]
+pub(crate) struct JsonPeriodicRow {
+    #[serde(rename = "prrName", default)]
+    name: JsonRowName,
+    #[serde(rename = "prrAmounts", default)]
+    amounts: Vec<JsonAmounts>,
+    #[serde(rename = "prrTotal")]
+    total: Option<JsonAmounts>,
+    #[serde(rename = "prrAverage")]
+    average: Option<JsonAmounts>,
+}
+
+impl JsonPeriodicRow {
+    pub(crate) fn into_row(self) -> Result<PeriodicBalanceRow> {
+        let account = self.name.0;
+        Ok(PeriodicBalanceRow {
+            display_name: account.clone(), // For now, use same as account name
+            account,
+            indent: 0,
+            amounts: self
+                .amounts
+                .into_iter()
+                .map(JsonAmounts::into_amounts)
+                .collect::<Result<_>>()?,
+            total: self.total.map(JsonAmounts::into_amounts).transpose()?,
+            average: self.average.map(JsonAmounts::into_amounts).transpose()?,
+        })
+    }
+}
+
+/// Convert an optional totals row, treating a missing, null or empty row as absent
+///
+/// With `--no-total` some hledger versions still emit a totals object whose
+/// amount arrays are all empty; reporting that as `None` keeps the UI from
+/// rendering a blank totals row.
+pub(crate) fn totals_row(row: Option<JsonPeriodicRow>) -> Result<Option<PeriodicBalanceRow>> {
+    let Some(row) = row.map(JsonPeriodicRow::into_row).transpose()? else {
+        return Ok(None);
+    };
+
+    let is_empty = row.amounts.iter().all(|period| period.is_empty())
+        && row.total.as_ref().is_none_or(|t| t.is_empty())
+        && row.average.as_ref().is_none_or(|a| a.is_empty());
+
+    Ok(if is_empty { None } else { Some(row) })
+}
+
+/// A periodic report (`prDates`, `prRows`, `prTotals`)
+///
+/// hledger always writes dates and rows, empty on an empty journal, so an
+/// object without them is not a report and fails to parse.
+#[derive(Deserialize)]
+pub(crate) struct JsonPeriodicReport {
+    #[serde(rename = "prDates")]
+    dates: JsonPeriodDates,
+    #[serde(rename = "prRows")]
+    rows: Vec<JsonPeriodicRow>,
+    #[serde(rename = "prTotals")]
+    totals: Option<JsonPeriodicRow>,
+}
+
+impl JsonPeriodicReport {
+    pub(crate) fn into_periodic_balance(self) -> Result<PeriodicBalance> {
+        Ok(PeriodicBalance {
+            dates: period_dates(self.dates),
+            rows: self
+                .rows
+                .into_iter()
+                .map(JsonPeriodicRow::into_row)
+                .collect::<Result<_>>()?,
+            totals: totals_row(self.totals)?,
+        })
+    }
+}
+
+/// A compound report (`cbrTitle`, `cbrDates`, `cbrSubreports`, `cbrTotals`),
+/// as balancesheet, incomestatement and cashflow print; dates and
+/// subreports are required, as for [`JsonPeriodicReport`]
+#[derive(Deserialize)]
+pub(crate) struct JsonCompoundReport {
+    #[serde(rename = "cbrTitle")]
+    pub(crate) title: Option<String>,
+    #[serde(rename = "cbrDates")]
+    pub(crate) dates: JsonPeriodDates,
+    /// Each subreport's name, report, and whether it adds to the totals
+    #[serde(rename = "cbrSubreports")]
+    pub(crate) subreports: Vec<(String, JsonPeriodicReport, bool)>,
+    #[serde(rename = "cbrTotals")]
+    pub(crate) totals: Option<JsonPeriodicRow>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn read_amount(value: &serde_json::Value) -> Result<Amount> {
+        from_value::<JsonAmount>(value)?.into_amount()
+    }
+
+    fn read_decimal(value: &serde_json::Value) -> Result<Decimal> {
+        from_value::<JsonDecimal>(value).map(|decimal| decimal.0)
+    }
+
+    fn read_price(value: &serde_json::Value) -> Result<Option<Price>> {
+        from_value::<Option<JsonPrice>>(value)?
+            .map(JsonPrice::into_price)
+            .transpose()
+    }
+
+    fn read_date(value: &serde_json::Value) -> Result<NaiveDate> {
+        from_value::<JsonDate>(value).map(|date| date.0)
+    }
+
+    fn read_row(value: &serde_json::Value) -> Result<PeriodicBalanceRow> {
+        from_value::<JsonPeriodicRow>(value)?.into_row()
+    }
+
+    fn read_totals(value: Option<&serde_json::Value>) -> Result<Option<PeriodicBalanceRow>> {
+        let row = value
+            .map(from_value::<Option<JsonPeriodicRow>>)
+            .transpose()?;
+        totals_row(row.flatten())
+    }
+
+    fn dollars(mantissa: i64) -> serde_json::Value {
+        json!({"acommodity": "$", "aquantity": {"decimalMantissa": mantissa, "decimalPlaces": 2}})
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        // Test decimal object format
+        let json = json!({
+            "decimalMantissa": 2000,
+            "decimalPlaces": 2
+        });
+        let decimal = read_decimal(&json).unwrap();
+        assert_eq!(decimal, Decimal::new(2000, 2));
+
+        // Test simple number
+        let json = json!(20.5);
+        let decimal = read_decimal(&json).unwrap();
+        assert_eq!(decimal.to_string(), "20.5");
+
+        // Test string number
+        let json = json!("30.25");
+        let decimal = read_decimal(&json).unwrap();
+        assert_eq!(decimal.to_string(), "30.25");
+
+        assert!(read_decimal(&json!(null)).is_err());
+        assert!(read_decimal(&json!({"decimalPlaces": 2})).is_err());
+    }
+
+    #[test]
+    fn test_parse_decimal_mantissa_overflowing_i64() {
+        // 12.345678901234567890 ETH: the mantissa is above i64::MAX but fits in u64
+        let json: serde_json::Value = serde_json::from_str(
+            r#"{"decimalMantissa": 12345678901234567890, "decimalPlaces": 18, "floatingPoint": 12.345678901234567}"#,
+        )
+        .unwrap();
+        let decimal = read_decimal(&json).unwrap();
+        assert_eq!(decimal.to_string(), "12.345678901234567890");
+
+        // Mantissa emitted as a string is read exactly as well
+        let json = json!({
+            "decimalMantissa": "123456789012345678901",
+            "decimalPlaces": 18
+        });
+        let decimal = read_decimal(&json).unwrap();
+        assert_eq!(decimal.to_string(), "123.456789012345678901");
+    }
+
+    #[test]
+    fn test_parse_decimal_mantissa_overflowing_u64_is_lossy() {
+        // 123.456789012345678901 ETH: serde_json can only hold this mantissa as an f64
+        let json: serde_json::Value = serde_json::from_str(
+            r#"{"decimalMantissa": 123456789012345678901, "decimalPlaces": 18, "floatingPoint": 123.45678901234568}"#,
+        )
+        .unwrap();
+        let decimal = read_decimal(&json).unwrap();
+        assert_eq!(decimal.round_dp(8).to_string(), "123.45678901");
+
+        // Without floatingPoint the float mantissa is scaled instead
+        let json: serde_json::Value = serde_json::from_str(
+            r#"{"decimalMantissa": 123456789012345678901, "decimalPlaces": 18}"#,
+        )
+        .unwrap();
+        let decimal = read_decimal(&json).unwrap();
+        assert_eq!(decimal.round_dp(8).to_string(), "123.45678901");
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        let json = json!([{
+            "acommodity": "$",
+            "aquantity": {
+                "decimalMantissa": 10000,
+                "decimalPlaces": 2
+            },
+            "astyle": {
+                "ascommodityside": "L",
+                "ascommodityspaced": false,
+                "asdecimalmark": ".",
+                "asdigitgroups": null,
+                "asprecision": 2
+            }
+        }]);
+
+        let amounts = parse_amounts(&json).unwrap();
+        assert_eq!(amounts.len(), 1);
+        assert_eq!(amounts[0].commodity, "$");
+        assert_eq!(amounts[0].quantity, Decimal::new(10000, 2));
+        assert!(amounts[0].price.is_none());
+
+        // Missing fields default rather than fail
+        let amount = read_amount(&json!({})).unwrap();
+        assert_eq!(amount.commodity, "");
+        assert_eq!(amount.quantity, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_parse_amounts_shapes() {
+        assert!(parse_amounts(&json!(null)).unwrap().is_empty());
+        assert!(parse_amounts(&json!([])).unwrap().is_empty());
+
+        // Malformed entries fail instead of vanishing from a total
+        assert!(parse_amounts(&json!([dollars(100), "oops"])).is_err());
+        assert!(parse_amounts(&json!({"acommodity": "$"})).is_err());
+        assert!(read_amount(&json!(null)).is_err());
+    }
+
+    #[test]
+    fn test_parse_price_kinds() {
+        // hledger -f tests/fixtures/prices.journal print -O json
+        let json = json!([
+            {
+                "acommodity": "AAPL",
+                "aquantity": {"decimalMantissa": 2, "decimalPlaces": 0},
+                "aprice": {
+                    "contents": {
+                        "acommodity": "USD",
+                        "aquantity": {"decimalMantissa": 150, "decimalPlaces": 0}
+                    },
+                    "tag": "UnitPrice"
+                }
+            },
+            {
+                "acommodity": "AAPL",
+                "aquantity": {"decimalMantissa": 2, "decimalPlaces": 0},
+                "aprice": {
+                    "contents": {
+                        "acommodity": "USD",
+                        "aquantity": {"decimalMantissa": 300, "decimalPlaces": 0}
+                    },
+                    "tag": "TotalPrice"
+                }
+            },
+            {
+                "acommodity": "USD",
+                "aquantity": {"decimalMantissa": -300, "decimalPlaces": 0},
+                "aprice": null
+            }
+        ]);
+
+        let amounts = parse_amounts(&json).unwrap();
+        let unit = amounts[0].price.as_ref().unwrap();
+        assert_eq!(unit.kind, PriceKind::Unit);
+        assert_eq!(unit.commodity, "USD");
+        assert_eq!(unit.quantity, Decimal::new(150, 0));
+
+        let total = amounts[1].price.as_ref().unwrap();
+        assert_eq!(total.kind, PriceKind::Total);
+        assert_eq!(total.quantity, Decimal::new(300, 0));
+
+        assert!(amounts[2].price.is_none());
+    }
+
+    #[test]
+    fn test_parse_price_legacy_and_malformed() {
+        // Older hledger: untagged, amount under `priceAmount`
+        let price = read_price(&json!({"priceAmount": dollars(250)}))
+            .unwrap()
+            .unwrap();
+        assert_eq!(price.kind, PriceKind::Unit);
+        assert_eq!(price.quantity, Decimal::new(250, 2));
+
+        // `contents` wins when both are present
+        let price = read_price(&json!({
+            "tag": "TotalPrice",
+            "contents": dollars(100),
+            "priceAmount": dollars(999)
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(price.quantity, Decimal::new(100, 2));
+
+        assert!(read_price(&json!(null)).unwrap().is_none());
+        assert!(read_price(&json!({"tag": "UnitPrice"})).is_err());
+        assert!(read_price(&json!("@ $2.50")).is_err());
+        assert!(read_price(&json!({
+            "contents": {"acommodity": "USD", "aquantity": 1},
+            "tag": "SomePrice"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_extract_date_from_tagged_value() {
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        let json = json!({"contents": "2024-01-01", "tag": "Exact"});
+        assert_eq!(read_date(&json).unwrap(), expected);
+
+        let json = json!("2024-01-01");
+        assert_eq!(read_date(&json).unwrap(), expected);
+
+        let json = json!({"tag": "Flex", "contents": {"tag": "Exact", "contents": "2024-01-01"}});
+        assert_eq!(read_date(&json).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_extract_date_from_tagged_value_malformed() {
+        let json = json!({"contents": "2024-13-45", "tag": "Exact"});
+        assert!(matches!(read_date(&json), Err(HLedgerError::ParseError(_))));
+
+        let json = json!({"tag": "Exact"});
+        assert!(read_date(&json).is_err());
+
+        let json = json!(null);
+        assert!(read_date(&json).is_err());
+    }
+
+    #[test]
+    fn test_parse_period_dates() {
+        let json = json!([
+            [{"contents": "2024-01-01", "tag": "Exact"}, {"contents": "2024-02-01", "tag": "Exact"}],
+            ["2024-02-01", "2024-03-01"]
+        ]);
+        let dates = parse_period_dates(Some(&json)).unwrap();
+        assert_eq!(dates.len(), 2);
+        assert_eq!(dates[1].end, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+
+        assert!(parse_period_dates(None).unwrap().is_empty());
+        assert!(parse_period_dates(Some(&json!([["2024-13-01", "2024-02-01"]]))).is_err());
+    }
+
+    #[test]
+    fn test_parse_periodic_row() {
+        let json = json!({
+            "prrName": "expenses:groceries",
+            "prrAmounts": [[dollars(2000)], []],
+            "prrTotal": [dollars(2000)],
+            "prrAverage": [dollars(1000)]
+        });
+        let row = read_row(&json).unwrap();
+        assert_eq!(row.account, "expenses:groceries");
+        assert_eq!(row.display_name, row.account);
+        assert_eq!(row.amounts.len(), 2);
+        assert!(row.amounts[1].is_empty());
+        assert_eq!(row.total.unwrap()[0].quantity, Decimal::new(2000, 2));
+        assert_eq!(row.average.unwrap()[0].quantity, Decimal::new(1000, 2));
+
+        let row = read_row(&json!({"prrName": ["assets", "bank"]})).unwrap();
+        assert_eq!(row.account, "assets:bank");
+        assert!(row.total.is_none() && row.average.is_none());
+
+        assert!(read_row(&json!([])).is_err());
+    }
+
+    #[test]
+    fn test_parse_totals_row_suppressed() {
+        assert!(read_totals(None).unwrap().is_none());
+        assert!(read_totals(Some(&json!(null))).unwrap().is_none());
+
+        // --no-total: hledger still emits a totals object with empty amounts
+        let json = json!({
+            "prrName": [],
+            "prrAmounts": [[], []],
+            "prrTotal": [],
+            "prrAverage": []
+        });
+        assert!(read_totals(Some(&json)).unwrap().is_none());
+
+        let json = json!({"prrName": [], "prrAmounts": []});
+        assert!(read_totals(Some(&json)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_totals_row_present() {
+        let json = json!({
+            "prrName": [],
+            "prrAmounts": [[], [{"acommodity": "$", "aquantity": {"decimalMantissa": 0, "decimalPlaces": 0}}]],
+            "prrTotal": [],
+            "prrAverage": []
+        });
+        let totals = read_totals(Some(&json)).unwrap().unwrap();
+        assert_eq!(totals.amounts.len(), 2);
+        assert_eq!(totals.amounts[1][0].quantity, Decimal::ZERO);
+    }
+}
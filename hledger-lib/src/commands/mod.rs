@@ -1,13 +1,44 @@
 pub mod accounts;
 pub mod balance;
 pub mod balancesheet;
+pub mod budget;
 pub mod cashflow;
+pub mod check;
+pub mod files;
+pub mod flow;
+pub mod import;
 pub mod incomestatement;
+pub(crate) mod json;
+pub mod networth;
+pub mod prices;
 pub mod print;
+pub mod spending;
+pub mod stats;
+pub mod tags;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod timeseries;
 
-pub use accounts::{get_accounts, AccountsOptions};
+pub use accounts::{
+    find_account, get_account_tree, get_accounts, get_accounts_detailed, AccountInfo, AccountNode,
+    AccountType, AccountsOptions,
+};
 pub use balance::{get_balance, BalanceOptions, BalanceReport};
 pub use balancesheet::{get_balancesheet, BalanceSheetOptions, BalanceSheetReport};
+pub use budget::{get_budget_report, BudgetOptions, BudgetReport};
 pub use cashflow::{get_cashflow, CashflowOptions, CashflowReport};
+pub use check::{verify_assertions, AssertionFailure};
+pub use files::get_files;
+pub use flow::{get_flow_data, FlowGraph, FlowLink, FlowNode, FlowNodeKind, FlowOptions};
+pub use import::{import_files, preview_import, ImportOptions};
 pub use incomestatement::{get_incomestatement, IncomeStatementOptions, IncomeStatementReport};
-pub use print::{get_print, PrintOptions, PrintReport, PrintTransaction};
+pub use networth::{get_networth_series, NetworthOptions, NetworthPoint};
+pub use prices::{get_prices, MarketPrice, PricesOptions};
+pub use print::{
+    get_print, get_print_paged, PageOrder, PageSpec, PrintOptions, PrintPage, PrintReport,
+    PrintTransaction,
+};
+pub use spending::{top_spending_accounts, AccountTotal, TopSpendingOptions};
+pub use stats::{get_stats, JournalStats};
+pub use tags::{get_tag_usage, get_tags, TagUsage, TagValueUsage, TagsOptions};
+pub use timeseries::{get_timeseries, Accumulation, Series, SeriesPoint, TimeseriesSpec};
@@ -0,0 +1,357 @@
+use crate::amount::MultiAmount;
+use crate::commands::balancesheet::{get_balancesheet, BalanceSheetOptions, BalanceSheetReport};
+use crate::interval::{LegacyInterval, ReportInterval};
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::Result;
+use chrono::NaiveDate;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for the net worth series
+///
+/// Balances are always historical (from the start of the journal). With no
+/// interval chosen the series is monthly.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+// Derived as inherent functions, which the trait impls below wrap to fold in
+// the deprecated fields
+#[serde(rename_all = "camelCase", remote = "Self")]
+#[ts(rename_all = "camelCase")]
+pub struct NetworthOptions {
+    // Period selection
+    /// How the series is split into periods (monthly when `None`)
+    #[serde(default)]
+    pub interval: ReportInterval,
+
+    // Date filters
+    /// Begin date (inclusive: transactions on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: transactions before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    // Valuation options
+    /// Convert to cost basis
+    pub cost: bool,
+    /// Convert to market value at period end
+    pub market: bool,
+    /// Convert to specific commodity
+    pub exchange: Option<String>,
+
+    // Query patterns
+    pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+impl Serialize for NetworthOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        NetworthOptions::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NetworthOptions {
+    /// Reads the options, folding the deprecated interval fields into
+    /// `interval`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        let mut options = NetworthOptions::deserialize(&json).map_err(de::Error::custom)?;
+        options.interval = LegacyInterval::resolve(options.interval, &json)?;
+        Ok(options)
+    }
+}
+
+/// Net worth at the end of one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct NetworthPoint {
+    /// Last day of the period (inclusive)
+    #[ts(type = "string")]
+    pub date: NaiveDate,
+    /// Total assets
+    pub assets: MultiAmount,
+    /// Total liabilities, positive when money is owed
+    pub liabilities: MultiAmount,
+    /// Assets minus liabilities
+    pub net_worth: MultiAmount,
+}
+
+// Implementation for builder pattern
+impl NetworthOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn daily(mut self) -> Self {
+        self.interval = ReportInterval::Daily;
+        self
+    }
+
+    pub fn weekly(mut self) -> Self {
+        self.interval = ReportInterval::Weekly;
+        self
+    }
+
+    pub fn monthly(mut self) -> Self {
+        self.interval = ReportInterval::Monthly;
+        self
+    }
+
+    pub fn quarterly(mut self) -> Self {
+        self.interval = ReportInterval::Quarterly;
+        self
+    }
+
+    pub fn yearly(mut self) -> Self {
+        self.interval = ReportInterval::Yearly;
+        self
+    }
+
+    pub fn period(mut self, period: impl Into<String>) -> Self {
+        self.interval = ReportInterval::Custom(period.into());
+        self
+    }
+
+    pub fn interval(mut self, interval: ReportInterval) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn cost(mut self) -> Self {
+        self.cost = true;
+        self
+    }
+
+    pub fn market(mut self) -> Self {
+        self.market = true;
+        self
+    }
+
+    pub fn exchange(mut self, commodity: impl Into<String>) -> Self {
+        self.exchange = Some(commodity.into());
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.queries.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    /// The balance sheet options that produce this series
+    fn balancesheet_options(&self) -> BalanceSheetOptions {
+        let interval = match self.interval.clone() {
+            ReportInterval::None => ReportInterval::Monthly,
+            interval => interval,
+        };
+
+        BalanceSheetOptions {
+            historical: true,
+            interval,
+            begin: self.begin.clone(),
+            end: self.end.clone(),
+            unchecked_dates: self.unchecked_dates,
+            cost: self.cost,
+            market: self.market,
+            exchange: self.exchange.clone(),
+            queries: self.queries.clone(),
+            timeout: self.timeout,
+            ..BalanceSheetOptions::default()
+        }
+    }
+}
+
+/// Get assets, liabilities and net worth at the end of each period
+///
+/// Runs `hledger balancesheet --historical` and combines its subreport totals.
+pub fn get_networth_series(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &NetworthOptions,
+) -> Result<Vec<NetworthPoint>> {
+    let report = get_balancesheet(hledger_path, journal_file, &options.balancesheet_options())?;
+    Ok(networth_points(&report))
+}
+
+/// One point per report period; subreports without a total for a period
+/// count as zero
+pub(crate) fn networth_points(report: &BalanceSheetReport) -> Vec<NetworthPoint> {
+    report
+        .dates
+        .iter()
+        .enumerate()
+        .map(|(i, period)| {
+            let mut point = NetworthPoint {
+                date: period.end.pred_opt().unwrap_or(period.end),
+                assets: MultiAmount::new(),
+                liabilities: MultiAmount::new(),
+                net_worth: MultiAmount::new(),
+            };
+            for subreport in &report.subreports {
+                let total: MultiAmount = subreport
+                    .totals
+                    .as_ref()
+                    .and_then(|totals| totals.amounts.get(i))
+                    .map(|amounts| amounts.iter().collect())
+                    .unwrap_or_default();
+
+                // hledger shows liabilities with their sign flipped and
+                // marks them as decreasing the total
+                if subreport.increases_total {
+                    point.net_worth += &total;
+                } else {
+                    point.net_worth -= &total;
+                }
+                match subreport.name.as_str() {
+                    "Assets" => point.assets += &total,
+                    "Liabilities" => point.liabilities += &total,
+                    _ => {}
+                }
+            }
+            point
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::{Amount, PeriodDate, PeriodicBalanceRow};
+    use crate::commands::balancesheet::BalanceSheetSubreport;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn export_bindings() {
+        NetworthOptions::export_all().unwrap();
+        NetworthPoint::export_all().unwrap();
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn amounts(pairs: &[(&str, &str)]) -> Vec<Amount> {
+        pairs
+            .iter()
+            .map(|(commodity, quantity)| Amount {
+                commodity: commodity.to_string(),
+                quantity: quantity.parse().unwrap(),
+                price: None,
+            })
+            .collect()
+    }
+
+    fn subreport(
+        name: &str,
+        increases_total: bool,
+        totals: Vec<Vec<Amount>>,
+    ) -> BalanceSheetSubreport {
+        BalanceSheetSubreport {
+            name: name.to_string(),
+            dates: vec![],
+            rows: vec![],
+            totals: Some(PeriodicBalanceRow {
+                account: String::new(),
+                display_name: String::new(),
+                indent: 0,
+                amounts: totals,
+                total: None,
+                average: None,
+            }),
+            increases_total,
+        }
+    }
+
+    #[test]
+    fn test_balancesheet_options_default_to_monthly_historical() {
+        let options = NetworthOptions::new().balancesheet_options();
+        assert!(options.historical);
+        assert_eq!(options.interval, ReportInterval::Monthly);
+
+        let options = NetworthOptions::new().yearly().balancesheet_options();
+        assert_eq!(options.interval, ReportInterval::Yearly);
+    }
+
+    #[test]
+    fn test_networth_points_combine_subreports() {
+        let report = BalanceSheetReport {
+            title: "Balance Sheet".to_string(),
+            dates: vec![
+                PeriodDate {
+                    start: date("2024-01-01"),
+                    end: date("2024-02-01"),
+                },
+                PeriodDate {
+                    start: date("2024-02-01"),
+                    end: date("2024-03-01"),
+                },
+            ],
+            subreports: vec![
+                subreport(
+                    "Assets",
+                    true,
+                    vec![
+                        amounts(&[("$", "1000"), ("EUR", "50")]),
+                        amounts(&[("$", "1200")]),
+                    ],
+                ),
+                // No total for February, e.g. a credit card paid off in January
+                subreport("Liabilities", false, vec![amounts(&[("$", "300")])]),
+            ],
+            totals: None,
+        };
+
+        let points = networth_points(&report);
+        assert_eq!(points.len(), 2);
+
+        assert_eq!(points[0].date, date("2024-01-31"));
+        assert_eq!(points[0].assets.get("$"), Decimal::from(1000));
+        assert_eq!(points[0].liabilities.get("$"), Decimal::from(300));
+        assert_eq!(points[0].net_worth.get("$"), Decimal::from(700));
+        assert_eq!(points[0].net_worth.get("EUR"), Decimal::from(50));
+
+        assert_eq!(points[1].date, date("2024-02-29"));
+        assert!(points[1].liabilities.is_zero());
+        assert_eq!(points[1].net_worth.get("$"), Decimal::from(1200));
+    }
+
+    #[test]
+    fn test_networth_points_empty_report() {
+        let report = BalanceSheetReport {
+            title: "Balance Sheet".to_string(),
+            dates: vec![],
+            subreports: vec![],
+            totals: None,
+        };
+        assert!(networth_points(&report).is_empty());
+    }
+}
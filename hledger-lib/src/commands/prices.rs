@@ -0,0 +1,182 @@
+use crate::amount::parse_amount_text;
+use crate::commands::balance::Amount;
+use crate::config::{add_journal_args, add_query_args, check_success, run_command};
+use crate::dates::validate_date_options;
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, HLedgerError, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for the prices command
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PricesOptions {
+    /// Also list prices inferred from transaction costs
+    pub infer_market_prices: bool,
+
+    // Date filters
+    /// Begin date (inclusive: prices on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: prices before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    // Query patterns
+    /// Commodity patterns, e.g. `cur:GOOG`
+    pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+/// A market price: one unit of `commodity` was worth `price` on `date`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct MarketPrice {
+    #[ts(type = "string")]
+    pub date: NaiveDate,
+    pub commodity: String,
+    pub price: Amount,
+}
+
+// Implementation for builder pattern
+impl PricesOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn infer_market_prices(mut self) -> Self {
+        self.infer_market_prices = true;
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.queries.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+}
+
+/// Get market prices from the journal
+///
+/// `hledger prices` has no JSON output, so its `P DATE COMMODITY AMOUNT`
+/// lines are parsed.
+pub fn get_prices(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &PricesOptions,
+) -> Result<Vec<MarketPrice>> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
+    }
+
+    let mut cmd = get_hledger_command(hledger_path);
+    add_journal_args(&mut cmd, journal_file);
+    cmd.arg("prices");
+
+    if options.infer_market_prices {
+        cmd.arg("--infer-market-prices");
+    }
+    if let Some(begin) = &options.begin {
+        cmd.arg("--begin").arg(begin);
+    }
+    if let Some(end) = &options.end {
+        cmd.arg("--end").arg(end);
+    }
+    add_query_args(&mut cmd, &options.queries);
+
+    let output = run_command(&mut cmd, options.timeout)?;
+    check_success(&cmd, output.status, &output.stderr)?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+    stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            parse_price_line(line)
+                .ok_or_else(|| HLedgerError::ParseError(format!("Unexpected price line: {}", line)))
+        })
+        .collect()
+}
+
+/// Parse `P 2024-01-15 GOOG $150.00`; the commodity may be quoted
+fn parse_price_line(line: &str) -> Option<MarketPrice> {
+    let rest = line.trim().strip_prefix("P ")?.trim_start();
+    let (date, rest) = rest.split_once(char::is_whitespace)?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+
+    let rest = rest.trim_start();
+    let (commodity, rest) = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split_once('"')?,
+        None => rest.split_once(char::is_whitespace)?,
+    };
+
+    Some(MarketPrice {
+        date,
+        commodity: commodity.to_string(),
+        price: parse_amount_text(rest)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn export_bindings() {
+        PricesOptions::export_all().unwrap();
+        MarketPrice::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_parse_price_line() {
+        let price = parse_price_line("P 2024-01-15 GOOG $150.25").unwrap();
+        assert_eq!(price.date, NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+        assert_eq!(price.commodity, "GOOG");
+        assert_eq!(price.price.commodity, "$");
+        assert_eq!(price.price.quantity, Decimal::new(15025, 2));
+
+        let price = parse_price_line("P 2024-02-01 \"ACME Inc\" 12,50 EUR").unwrap();
+        assert_eq!(price.commodity, "ACME Inc");
+        assert_eq!(price.price.commodity, "EUR");
+        assert_eq!(price.price.quantity, Decimal::new(1250, 2));
+    }
+
+    #[test]
+    fn test_parse_price_line_rejects_other_lines() {
+        assert!(parse_price_line("2024-01-15 GOOG $150").is_none());
+        assert!(parse_price_line("P 2024-13-01 GOOG $150").is_none());
+        assert!(parse_price_line("P 2024-01-15 GOOG").is_none());
+    }
+}
@@ -1,6 +1,23 @@
+use crate::commands::balance::Price;
+use crate::commands::json::{parse_date, JsonAmount};
+use crate::config::{
+    add_journal_args, add_query_args, check_success, run_command_piped, run_json_command,
+    skip_json_preamble,
+};
+use crate::csv_report::{run_csv_command, CsvReport};
+use crate::dates::validate_date_options;
+use crate::query::Query;
+use crate::timeout::Timeout;
 use crate::{get_hledger_command, HLedgerError, Result};
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
+use serde::de::{Error as _, IgnoredAny, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::process::Command;
+use std::time::Duration;
 use ts_rs::TS;
 
 /// Custom serde module for Decimal to/from string
@@ -26,7 +43,9 @@ mod decimal_string_serde {
 
 /// Options for the print command
 #[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct PrintOptions {
     /// Show all amounts explicitly
     pub explicit: bool,
@@ -38,12 +57,20 @@ pub struct PrintOptions {
     pub new: bool,
     /// Fuzzy search for transaction by description
     pub match_desc: Option<String>,
+    /// Include transactions generated by periodic rules (`~ monthly ...`),
+    /// up to `end` or six months after today
+    pub forecast: bool,
+    /// Date hledger takes as today (`YYYY-MM-DD`), for forecasts and
+    /// relative dates; the system date when `None`
+    pub today: Option<String>,
 
     // Date filters
     /// Begin date (inclusive)
     pub begin: Option<String>,
     /// End date (exclusive)
     pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
 
     // Status filters
     /// Include only unmarked transactions
@@ -58,47 +85,83 @@ pub struct PrintOptions {
     pub real: bool,
     /// Show empty accounts
     pub empty: bool,
+    /// Skip balance assertions, e.g. to see the report while one fails
+    pub ignore_assertions: bool,
 
     // Query patterns
     pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
 }
 
 /// Source position information
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct SourcePosition {
     pub line: u32,
     pub column: u32,
     pub file: String,
 }
 
+/// Where a transaction sits in its source file
+///
+/// The end position is exclusive: hledger reports it as the start of the
+/// line following the transaction's last posting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct SourceSpan {
+    pub file: String,
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
 /// Amount display style
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct AmountStyle {
     pub commodity_side: String,
     pub commodity_spaced: bool,
     pub decimal_mark: Option<String>,
-    pub digit_groups: Option<String>,
-    pub precision: u16,
+    pub digit_groups: Option<DigitGroups>,
+    pub precision: Precision,
     pub rounding: String,
 }
 
-/// Price information (reused from balance module)
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
-pub struct Price {
-    /// Price commodity
-    pub commodity: String,
-    /// Price quantity
-    #[serde(with = "decimal_string_serde")]
-    #[ts(type = "string")]
-    pub quantity: Decimal,
+/// Digit group style, e.g. `1,000,000` or the Indian `10,00,000`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DigitGroups {
+    /// Character placed between digit groups
+    pub separator: char,
+    /// Group sizes counting from the decimal mark leftwards; the last size repeats
+    pub sizes: Vec<u8>,
+}
+
+/// Number of decimal digits an amount is displayed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type", content = "digits")]
+pub enum Precision {
+    /// Show exactly this many decimal digits
+    Digits(u16),
+    /// Show as many decimal digits as the quantity needs
+    Natural,
 }
 
 /// Amount with inline style information
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct PrintAmount {
     pub commodity: String,
     #[serde(with = "decimal_string_serde")]
@@ -110,7 +173,9 @@ pub struct PrintAmount {
 
 /// Balance assertion information
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct BalanceAssertion {
     pub amount: PrintAmount,
     pub inclusive: bool,
@@ -120,7 +185,9 @@ pub struct BalanceAssertion {
 
 /// Posting structure
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct PrintPosting {
     pub account: String,
     pub amounts: Vec<PrintAmount>,
@@ -128,8 +195,10 @@ pub struct PrintPosting {
     pub comment: String,
     pub tags: Vec<(String, String)>,
     pub posting_type: String,
-    pub date: Option<String>,
-    pub date2: Option<String>,
+    #[ts(type = "string | null")]
+    pub date: Option<NaiveDate>,
+    #[ts(type = "string | null")]
+    pub date2: Option<NaiveDate>,
     pub balance_assertion: Option<BalanceAssertion>,
     pub original: Option<Box<PrintPosting>>,
     pub transaction_index: String,
@@ -137,11 +206,15 @@ pub struct PrintPosting {
 
 /// Transaction structure
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
-#[ts(export)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
 pub struct PrintTransaction {
     pub index: u32,
-    pub date: String,
-    pub date2: Option<String>,
+    #[ts(type = "string")]
+    pub date: NaiveDate,
+    #[ts(type = "string | null")]
+    pub date2: Option<NaiveDate>,
     pub status: String,
     pub code: String,
     pub description: String,
@@ -150,17 +223,80 @@ pub struct PrintTransaction {
     pub postings: Vec<PrintPosting>,
     pub preceding_comment: String,
     pub source_positions: Vec<SourcePosition>,
+    /// Span from the first to the last source position, if hledger gave any
+    pub source_span: Option<SourceSpan>,
 }
 
 /// Print report - array of transactions
 pub type PrintReport = Vec<PrintTransaction>;
 
+/// Which transactions of a print report to return, for loading long
+/// journals a page at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PageSpec {
+    /// Transactions to skip, counted in `order`
+    pub offset: usize,
+    /// Most transactions to return
+    pub limit: usize,
+    pub order: PageOrder,
+}
+
+/// Order a [`PageSpec`] counts transactions in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum PageOrder {
+    /// As hledger prints them: by date, oldest first
+    #[default]
+    OldestFirst,
+    /// Most recent first, as transaction tables usually show them
+    NewestFirst,
+}
+
+/// One page of a print report
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PrintPage {
+    /// How many transactions the whole report has
+    pub total: usize,
+    /// The page's transactions, in the page's order
+    pub transactions: PrintReport,
+}
+
+// Implementation for builder pattern
+impl PageSpec {
+    /// Up to `limit` transactions after the first `offset`, oldest first
+    pub fn new(offset: usize, limit: usize) -> Self {
+        Self {
+            offset,
+            limit,
+            order: PageOrder::OldestFirst,
+        }
+    }
+
+    pub fn newest_first(mut self) -> Self {
+        self.order = PageOrder::NewestFirst;
+        self
+    }
+}
+
 // Implementation for builder pattern
 impl PrintOptions {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Options for reading every posting's amount, as the reports built on
+    /// print do: inferred amounts are only in the report when shown
+    /// explicitly
+    pub(crate) fn with_inferred_amounts() -> Self {
+        Self::new().explicit()
+    }
+
     pub fn explicit(mut self) -> Self {
         self.explicit = true;
         self
@@ -196,6 +332,11 @@ impl PrintOptions {
         self
     }
 
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
     pub fn unmarked(mut self) -> Self {
         self.unmarked = true;
         self
@@ -221,15 +362,40 @@ impl PrintOptions {
         self
     }
 
+    pub fn ignore_assertions(mut self) -> Self {
+        self.ignore_assertions = true;
+        self
+    }
+
+    pub fn forecast(mut self) -> Self {
+        self.forecast = true;
+        self
+    }
+
+    pub fn today(mut self, date: impl Into<String>) -> Self {
+        self.today = Some(date.into());
+        self
+    }
+
     pub fn query(mut self, query: impl Into<String>) -> Self {
         self.queries.push(query.into());
         self
     }
 
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
     pub fn queries(mut self, queries: Vec<String>) -> Self {
         self.queries = queries;
         self
     }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
 }
 
 /// Get print report from hledger
@@ -238,16 +404,102 @@ pub fn get_print(
     journal_file: Option<&str>,
     options: &PrintOptions,
 ) -> Result<PrintReport> {
-    let mut cmd = get_hledger_command(hledger_path);
+    let mut cmd = print_command(hledger_path, journal_file, options, "json")?;
+    print_report(&mut cmd, None, options.timeout)
+}
+
+/// Get print report for journal text that isn't on disk
+///
+/// The text is piped to `hledger -f -`, as for [`crate::get_balance_from_text`].
+/// Source positions name the file `-`, with lines counted in the text.
+pub fn get_print_from_text(
+    hledger_path: Option<&str>,
+    journal_text: &str,
+    options: &PrintOptions,
+) -> Result<PrintReport> {
+    let mut cmd = print_command(hledger_path, Some("-"), options, "json")?;
+    print_report(&mut cmd, Some(journal_text), options.timeout)
+}
+
+/// Get one page of the print report
+///
+/// Transactions outside the page are counted but never converted, so memory
+/// grows with the page rather than the journal; with
+/// [`PageOrder::NewestFirst`] it grows with `offset + limit`, since the
+/// newest transactions come last. Only the page's transactions are checked
+/// for parse errors.
+pub fn get_print_paged(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &PrintOptions,
+    page: PageSpec,
+) -> Result<PrintPage> {
+    let mut cmd = print_command(hledger_path, journal_file, options, "json")?;
+    let output = run_command_piped(&mut cmd, None, options.timeout, move |stdout| {
+        parse_print_page_reader(BufReader::new(skip_json_preamble(stdout)?), page)
+    })?;
+    check_success(&cmd, output.status, &output.stderr)?;
+
+    output.stdout
+}
+
+/// Run a print command, parsing transactions straight off its stdout
+fn print_report(
+    cmd: &mut Command,
+    stdin: Option<&str>,
+    timeout: Option<Timeout>,
+) -> Result<PrintReport> {
+    let output = run_command_piped(cmd, stdin, timeout, |stdout| {
+        parse_print_reader(BufReader::new(skip_json_preamble(stdout)?))
+    })?;
+    check_success(cmd, output.status, &output.stderr)?;
+
+    output.stdout
+}
+
+/// Get the print report as hledger's JSON, untouched
+///
+/// For fields the typed [`PrintReport`] doesn't model yet. The command is built
+/// exactly as for [`get_print`].
+pub fn get_print_raw(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &PrintOptions,
+) -> Result<serde_json::Value> {
+    let mut cmd = print_command(hledger_path, journal_file, options, "json")?;
+    run_json_command(&mut cmd, options.timeout)
+}
+
+/// Get the print report as hledger's CSV (`-O csv`), one row per posting
+///
+/// The command is built exactly as for [`get_print`].
+pub fn get_print_csv(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &PrintOptions,
+) -> Result<CsvReport> {
+    let mut cmd = print_command(hledger_path, journal_file, options, "csv")?;
+    run_csv_command(&mut cmd, options.timeout)
+}
 
-    if let Some(file) = journal_file {
-        cmd.arg("-f").arg(file);
+/// Build the hledger command for the print report
+fn print_command(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &PrintOptions,
+    output_format: &str,
+) -> Result<Command> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
     }
 
+    let mut cmd = get_hledger_command(hledger_path);
+
+    add_journal_args(&mut cmd, journal_file);
+
     cmd.arg("print");
 
-    // Always output JSON
-    cmd.arg("--output-format").arg("json");
+    cmd.arg("--output-format").arg(output_format);
 
     // Add option flags
     if options.explicit {
@@ -265,6 +517,12 @@ pub fn get_print(
     if let Some(desc) = &options.match_desc {
         cmd.arg("--match").arg(desc);
     }
+    if options.forecast {
+        cmd.arg("--forecast");
+    }
+    if let Some(today) = &options.today {
+        cmd.arg(format!("--today={}", today));
+    }
 
     // Date filters
     if let Some(begin) = &options.begin {
@@ -292,335 +550,409 @@ pub fn get_print(
     if options.empty {
         cmd.arg("--empty");
     }
-
-    // Query patterns
-    for query in &options.queries {
-        cmd.arg(query);
+    if options.ignore_assertions {
+        cmd.arg("--ignore-assertions");
     }
 
-    let output = cmd.output().map_err(|e| {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            HLedgerError::HLedgerNotFound
-        } else {
-            HLedgerError::Io(e)
-        }
-    })?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(HLedgerError::CommandFailed {
-            code: output.status.code().unwrap_or(-1),
-            stderr: stderr.to_string(),
-        });
-    }
-
-    let stdout = String::from_utf8(output.stdout)?;
-
-    // Parse the JSON output
-    let json_value: serde_json::Value = serde_json::from_str(&stdout)?;
+    // Query patterns
+    add_query_args(&mut cmd, &options.queries);
 
-    parse_print_output(&json_value)
+    Ok(cmd)
 }
 
-/// Parse print output from JSON
-fn parse_print_output(value: &serde_json::Value) -> Result<PrintReport> {
-    let array = value
-        .as_array()
-        .ok_or_else(|| HLedgerError::ParseError("Expected array for print output".to_string()))?;
-
-    let mut transactions = Vec::new();
-    for transaction_json in array {
-        let transaction = parse_transaction(transaction_json)?;
-        transactions.push(transaction);
+/// Parse print output from a JSON stream
+///
+/// Each transaction is deserialized straight into a [`JsonTransaction`] and
+/// converted as soon as it is read, so no `serde_json::Value` is built for
+/// the report or any part of it.
+pub(crate) fn parse_print_reader(reader: impl Read) -> Result<PrintReport> {
+    let mut failure = None;
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let result = deserializer.deserialize_seq(TransactionsVisitor {
+        failure: &mut failure,
+    });
+    if let Some(e) = failure {
+        return Err(e);
     }
-
+    let transactions = result?;
+    deserializer.end()?;
     Ok(transactions)
 }
 
-/// Parse a transaction from JSON
-fn parse_transaction(value: &serde_json::Value) -> Result<PrintTransaction> {
-    let obj = value
-        .as_object()
-        .ok_or_else(|| HLedgerError::ParseError("Transaction should be an object".to_string()))?;
-
-    let index = obj.get("tindex").and_then(|i| i.as_u64()).unwrap_or(0) as u32;
-
-    let date = obj
-        .get("tdate")
-        .and_then(|d| d.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let date2 = obj
-        .get("tdate2")
-        .and_then(|d| d.as_str())
-        .map(|s| s.to_string());
-
-    let status = obj
-        .get("tstatus")
-        .and_then(|s| s.as_str())
-        .unwrap_or("Unmarked")
-        .to_string();
-
-    let code = obj
-        .get("tcode")
-        .and_then(|c| c.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let description = obj
-        .get("tdescription")
-        .and_then(|d| d.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let comment = obj
-        .get("tcomment")
-        .and_then(|c| c.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let preceding_comment = obj
-        .get("tprecedingcomment")
-        .and_then(|c| c.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    // Parse tags
-    let mut tags = Vec::new();
-    if let Some(tags_array) = obj.get("ttags").and_then(|t| t.as_array()) {
-        for tag in tags_array {
-            if let Some(tag_pair) = tag.as_array() {
-                if tag_pair.len() == 2 {
-                    let name = tag_pair[0].as_str().unwrap_or("").to_string();
-                    let value = tag_pair[1].as_str().unwrap_or("").to_string();
-                    tags.push((name, value));
-                }
-            }
-        }
-    }
+/// Visits the top-level print array, parsing each transaction as it arrives
+struct TransactionsVisitor<'a> {
+    /// The parse error that aborted deserialization, kept so it can be
+    /// returned as-is instead of wrapped in a `serde_json::Error`
+    failure: &'a mut Option<HLedgerError>,
+}
 
-    // Parse postings
-    let mut postings = Vec::new();
-    if let Some(postings_array) = obj.get("tpostings").and_then(|p| p.as_array()) {
-        for posting_json in postings_array {
-            let posting = parse_posting(posting_json)?;
-            postings.push(posting);
-        }
+impl<'de> Visitor<'de> for TransactionsVisitor<'_> {
+    type Value = PrintReport;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of transactions")
     }
 
-    // Parse source positions
-    let mut source_positions = Vec::new();
-    if let Some(positions_array) = obj.get("tsourcepos").and_then(|s| s.as_array()) {
-        for pos_json in positions_array {
-            if let Some(pos) = parse_source_position(pos_json) {
-                source_positions.push(pos);
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<PrintReport, A::Error> {
+        let mut transactions = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(json) = seq.next_element::<JsonTransaction>()? {
+            match json.into_transaction() {
+                Ok(transaction) => transactions.push(transaction),
+                Err(e) => {
+                    let message = e.to_string();
+                    *self.failure = Some(e);
+                    return Err(A::Error::custom(message));
+                }
             }
         }
+        Ok(transactions)
     }
+}
 
-    Ok(PrintTransaction {
-        index,
-        date,
-        date2,
-        status,
-        code,
-        description,
-        comment,
-        tags,
-        postings,
-        preceding_comment,
-        source_positions,
+/// Parse one page of print output from a JSON stream
+pub(crate) fn parse_print_page_reader(reader: impl Read, page: PageSpec) -> Result<PrintPage> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let (total, kept) = deserializer.deserialize_seq(PageVisitor { page })?;
+    deserializer.end()?;
+    let transactions = kept
+        .into_iter()
+        .map(JsonTransaction::into_transaction)
+        .collect::<Result<PrintReport>>()?;
+    Ok(PrintPage {
+        total,
+        transactions,
     })
 }
 
-/// Parse a posting from JSON
-fn parse_posting(value: &serde_json::Value) -> Result<PrintPosting> {
-    let obj = value
-        .as_object()
-        .ok_or_else(|| HLedgerError::ParseError("Posting should be an object".to_string()))?;
-
-    let account = obj
-        .get("paccount")
-        .and_then(|a| a.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let status = obj
-        .get("pstatus")
-        .and_then(|s| s.as_str())
-        .unwrap_or("Unmarked")
-        .to_string();
-
-    let comment = obj
-        .get("pcomment")
-        .and_then(|c| c.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let posting_type = obj
-        .get("ptype")
-        .and_then(|t| t.as_str())
-        .unwrap_or("RegularPosting")
-        .to_string();
-
-    let date = obj
-        .get("pdate")
-        .and_then(|d| d.as_str())
-        .map(|s| s.to_string());
-
-    let date2 = obj
-        .get("pdate2")
-        .and_then(|d| d.as_str())
-        .map(|s| s.to_string());
-
-    let transaction_index = obj
-        .get("ptransaction_")
-        .and_then(|t| t.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    // Parse tags
-    let mut tags = Vec::new();
-    if let Some(tags_array) = obj.get("ptags").and_then(|t| t.as_array()) {
-        for tag in tags_array {
-            if let Some(tag_pair) = tag.as_array() {
-                if tag_pair.len() == 2 {
-                    let name = tag_pair[0].as_str().unwrap_or("").to_string();
-                    let value = tag_pair[1].as_str().unwrap_or("").to_string();
-                    tags.push((name, value));
+/// Visits the top-level print array, counting every transaction but keeping
+/// only those in the page
+struct PageVisitor {
+    page: PageSpec,
+}
+
+impl<'de> Visitor<'de> for PageVisitor {
+    type Value = (usize, Vec<JsonTransaction>);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of transactions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let PageSpec {
+            offset,
+            limit,
+            order,
+        } = self.page;
+        // The newest transactions come last, so newest-first pages keep a
+        // rolling window of the latest `offset + limit` and drop the offset
+        // once the array ends
+        let (skip, keep) = match order {
+            PageOrder::OldestFirst => (offset, limit),
+            PageOrder::NewestFirst => (0, offset.saturating_add(limit)),
+        };
+
+        let mut total = 0;
+        let mut kept = VecDeque::new();
+        loop {
+            let wanted = match order {
+                PageOrder::OldestFirst => total >= skip && total - skip < keep,
+                PageOrder::NewestFirst => keep > 0,
+            };
+            if wanted {
+                let Some(json) = seq.next_element::<JsonTransaction>()? else {
+                    break;
+                };
+                if kept.len() == keep {
+                    kept.pop_front();
                 }
+                kept.push_back(json);
+            } else if seq.next_element::<IgnoredAny>()?.is_none() {
+                break;
             }
+            total += 1;
         }
-    }
 
-    // Parse amounts
-    let amounts = if let Some(amounts_json) = obj.get("pamount") {
-        parse_print_amounts(amounts_json)?
-    } else {
-        Vec::new()
-    };
+        if order == PageOrder::NewestFirst {
+            kept.truncate(kept.len().saturating_sub(offset));
+            return Ok((total, kept.into_iter().rev().collect()));
+        }
+        Ok((total, kept.into()))
+    }
+}
 
-    // Parse balance assertion
-    let balance_assertion = if let Some(ba_json) = obj.get("pbalanceassertion") {
-        parse_balance_assertion(ba_json)?
-    } else {
-        None
-    };
+/// A transaction as hledger's print writes it, read straight off its stdout
+///
+/// Dates are kept as text until [`JsonTransaction::into_transaction`], so a
+/// malformed one is a [`HLedgerError::ParseError`] rather than a JSON error.
+#[derive(Deserialize)]
+struct JsonTransaction {
+    #[serde(default)]
+    tindex: u32,
+    tdate: Option<String>,
+    tdate2: Option<String>,
+    tstatus: Option<String>,
+    #[serde(default)]
+    tcode: String,
+    #[serde(default)]
+    tdescription: String,
+    #[serde(default)]
+    tcomment: String,
+    #[serde(default)]
+    tprecedingcomment: String,
+    #[serde(default)]
+    ttags: Vec<(String, String)>,
+    #[serde(default)]
+    tpostings: Vec<JsonPosting>,
+    #[serde(default)]
+    tsourcepos: Vec<JsonSourcePosition>,
+}
 
-    // Parse original posting (for auto postings)
-    let original = if let Some(orig_json) = obj.get("poriginal") {
-        if !orig_json.is_null() {
-            Some(Box::new(parse_posting(orig_json)?))
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+impl JsonTransaction {
+    fn into_transaction(self) -> Result<PrintTransaction> {
+        let date = self
+            .tdate
+            .as_deref()
+            .ok_or_else(|| HLedgerError::ParseError("Transaction is missing tdate".to_string()))
+            .and_then(parse_date)?;
+        let source_positions: Vec<SourcePosition> = self
+            .tsourcepos
+            .into_iter()
+            .map(JsonSourcePosition::into_position)
+            .collect();
+
+        Ok(PrintTransaction {
+            index: self.tindex,
+            date,
+            date2: self.tdate2.as_deref().map(parse_date).transpose()?,
+            status: self.tstatus.unwrap_or_else(|| "Unmarked".to_string()),
+            code: self.tcode,
+            description: self.tdescription,
+            comment: self.tcomment,
+            tags: self.ttags,
+            postings: self
+                .tpostings
+                .into_iter()
+                .map(JsonPosting::into_posting)
+                .collect::<Result<_>>()?,
+            preceding_comment: self.tprecedingcomment,
+            source_span: source_span(&source_positions),
+            source_positions,
+        })
+    }
+}
 
-    Ok(PrintPosting {
-        account,
-        amounts,
-        status,
-        comment,
-        tags,
-        posting_type,
-        date,
-        date2,
-        balance_assertion,
-        original,
-        transaction_index,
+/// Build a span from the first and last of a transaction's source positions
+fn source_span(positions: &[SourcePosition]) -> Option<SourceSpan> {
+    let (start, end) = (positions.first()?, positions.last()?);
+    Some(SourceSpan {
+        file: start.file.clone(),
+        start_line: start.line,
+        start_column: start.column,
+        end_line: end.line,
+        end_column: end.column,
     })
 }
 
-/// Parse print amounts from JSON
-fn parse_print_amounts(value: &serde_json::Value) -> Result<Vec<PrintAmount>> {
-    let mut amounts = Vec::new();
-
-    if let Some(amounts_array) = value.as_array() {
-        for amount_json in amounts_array {
-            if let Some(amount_obj) = amount_json.as_object() {
-                let commodity = amount_obj
-                    .get("acommodity")
-                    .and_then(|c| c.as_str())
-                    .unwrap_or("")
-                    .to_string();
+/// A posting as hledger's print writes it
+#[derive(Deserialize)]
+struct JsonPosting {
+    #[serde(default)]
+    paccount: String,
+    pstatus: Option<String>,
+    #[serde(default)]
+    pcomment: String,
+    ptype: Option<String>,
+    pdate: Option<String>,
+    pdate2: Option<String>,
+    #[serde(default)]
+    ptransaction_: String,
+    #[serde(default)]
+    ptags: Vec<(String, String)>,
+    #[serde(default)]
+    pamount: JsonPrintAmounts,
+    pbalanceassertion: Option<JsonBalanceAssertion>,
+    /// The posting before an auto posting rule changed it
+    poriginal: Option<Box<JsonPosting>>,
+}
 
-                let quantity = if let Some(q) = amount_obj.get("aquantity") {
-                    parse_decimal_from_json(q)?
-                } else {
-                    Decimal::ZERO
-                };
+impl JsonPosting {
+    fn into_posting(self) -> Result<PrintPosting> {
+        Ok(PrintPosting {
+            account: self.paccount,
+            amounts: self.pamount.into_amounts()?,
+            status: self.pstatus.unwrap_or_else(|| "Unmarked".to_string()),
+            comment: self.pcomment,
+            tags: self.ptags,
+            posting_type: self.ptype.unwrap_or_else(|| "RegularPosting".to_string()),
+            date: self.pdate.as_deref().map(parse_date).transpose()?,
+            date2: self.pdate2.as_deref().map(parse_date).transpose()?,
+            balance_assertion: match self.pbalanceassertion {
+                Some(assertion) => assertion.into_assertion()?,
+                None => None,
+            },
+            original: self
+                .poriginal
+                .map(|original| original.into_posting().map(Box::new))
+                .transpose()?,
+            transaction_index: self.ptransaction_,
+        })
+    }
+}
 
-                let price = if let Some(price_obj) = amount_obj.get("aprice") {
-                    parse_price(price_obj)?
-                } else {
-                    None
-                };
+/// An amount with its display style
+type JsonPrintAmount = JsonAmount<JsonAmountStyle>;
 
-                let style = if let Some(style_obj) = amount_obj.get("astyle") {
-                    parse_amount_style(style_obj)?
-                } else {
-                    AmountStyle::default()
-                };
+/// Print amounts; as with `JsonAmounts`, `null` is empty and a malformed
+/// entry is an error
+#[derive(Default)]
+struct JsonPrintAmounts(Vec<JsonPrintAmount>);
 
-                amounts.push(PrintAmount {
-                    commodity,
-                    quantity,
-                    price,
-                    style,
-                });
-            }
-        }
+impl<'de> Deserialize<'de> for JsonPrintAmounts {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<Vec<JsonPrintAmount>>::deserialize(deserializer)
+            .map(|amounts| JsonPrintAmounts(amounts.unwrap_or_default()))
     }
+}
 
-    Ok(amounts)
+impl JsonPrintAmounts {
+    fn into_amounts(self) -> Result<Vec<PrintAmount>> {
+        self.0.into_iter().map(print_amount).collect()
+    }
 }
 
-/// Parse amount style from JSON
-fn parse_amount_style(value: &serde_json::Value) -> Result<AmountStyle> {
-    let obj = value
-        .as_object()
-        .ok_or_else(|| HLedgerError::ParseError("Amount style should be an object".to_string()))?;
+/// Convert a print amount: the shared amount fields plus its display style
+fn print_amount(mut amount: JsonPrintAmount) -> Result<PrintAmount> {
+    let style = match amount.astyle.take() {
+        Some(style) => style.into_style()?,
+        None => AmountStyle::default(),
+    };
+    let amount = amount.into_amount()?;
 
-    let commodity_side = obj
-        .get("ascommodityside")
-        .and_then(|s| s.as_str())
-        .unwrap_or("L")
-        .to_string();
+    Ok(PrintAmount {
+        commodity: amount.commodity,
+        quantity: amount.quantity,
+        price: amount.price,
+        style,
+    })
+}
 
-    let commodity_spaced = obj
-        .get("ascommodityspaced")
-        .and_then(|s| s.as_bool())
-        .unwrap_or(false);
+/// An amount's display style as hledger writes it
+#[derive(Deserialize)]
+struct JsonAmountStyle {
+    ascommodityside: Option<String>,
+    #[serde(default)]
+    ascommodityspaced: bool,
+    asdecimalmark: Option<String>,
+    asdigitgroups: Option<JsonDigitGroups>,
+    asprecision: Option<JsonPrecision>,
+    asrounding: Option<String>,
+}
 
-    let decimal_mark = obj
-        .get("asdecimalmark")
-        .and_then(|d| d.as_str())
-        .map(|s| s.to_string());
+impl JsonAmountStyle {
+    fn into_style(self) -> Result<AmountStyle> {
+        Ok(AmountStyle {
+            commodity_side: self.ascommodityside.unwrap_or_else(|| "L".to_string()),
+            commodity_spaced: self.ascommodityspaced,
+            decimal_mark: self.asdecimalmark,
+            digit_groups: self
+                .asdigitgroups
+                .map(JsonDigitGroups::into_digit_groups)
+                .transpose()?,
+            precision: match self.asprecision {
+                Some(precision) => precision.into_precision()?,
+                None => Precision::Digits(2),
+            },
+            rounding: self.asrounding.unwrap_or_else(|| "NoRounding".to_string()),
+        })
+    }
+}
 
-    let digit_groups = obj
-        .get("asdigitgroups")
-        .and_then(|d| d.as_str())
-        .map(|s| s.to_string());
+/// Digit group style as hledger writes it
+///
+/// hledger emits `[",", [3]]` (separator and group sizes), possibly wrapped
+/// in a `{"tag": "DigitGroups", "contents": ...}` object.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonDigitGroups {
+    Tagged { contents: Box<JsonDigitGroups> },
+    Pair(String, Vec<u64>),
+}
 
-    let precision = obj.get("asprecision").and_then(|p| p.as_u64()).unwrap_or(2) as u16;
+impl JsonDigitGroups {
+    fn into_digit_groups(self) -> Result<DigitGroups> {
+        let (separator, sizes) = match self {
+            JsonDigitGroups::Tagged { contents } => return contents.into_digit_groups(),
+            JsonDigitGroups::Pair(separator, sizes) => (separator, sizes),
+        };
+        let invalid = || {
+            HLedgerError::ParseError(format!(
+                "Unknown digit groups format: [{:?}, {:?}]",
+                separator, sizes
+            ))
+        };
+
+        let mut separator_chars = separator.chars();
+        let separator = match (separator_chars.next(), separator_chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(invalid()),
+        };
+        let sizes = sizes
+            .iter()
+            .map(|&size| u8::try_from(size).map_err(|_| invalid()))
+            .collect::<Result<Vec<u8>>>()?;
+
+        Ok(DigitGroups { separator, sizes })
+    }
+}
 
-    let rounding = obj
-        .get("asrounding")
-        .and_then(|r| r.as_str())
-        .unwrap_or("NoRounding")
-        .to_string();
+/// Amount precision as hledger writes it
+///
+/// hledger emits a plain integer for a fixed precision and `"NaturalPrecision"`
+/// (or its tagged-object form) when the amount is shown with all its digits.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonPrecision {
+    Digits(u64),
+    Name(String),
+    Tagged {
+        tag: String,
+        contents: Option<Box<JsonPrecision>>,
+    },
+}
 
-    Ok(AmountStyle {
-        commodity_side,
-        commodity_spaced,
-        decimal_mark,
-        digit_groups,
-        precision,
-        rounding,
-    })
+impl JsonPrecision {
+    fn into_precision(self) -> Result<Precision> {
+        let (tag, contents) = match self {
+            JsonPrecision::Digits(digits) => {
+                return u16::try_from(digits).map(Precision::Digits).map_err(|_| {
+                    HLedgerError::ParseError(format!("Precision out of range: {}", digits))
+                })
+            }
+            JsonPrecision::Name(tag) => (tag, None),
+            JsonPrecision::Tagged { tag, contents } => (tag, contents),
+        };
+
+        match (tag.as_str(), contents) {
+            ("NaturalPrecision", _) => Ok(Precision::Natural),
+            ("Precision", Some(contents)) => contents.into_precision(),
+            ("Precision", None) => Err(HLedgerError::ParseError(
+                "Precision is missing contents".to_string(),
+            )),
+            _ => Err(HLedgerError::ParseError(format!(
+                "Unknown precision format: {}",
+                tag
+            ))),
+        }
+    }
 }
 
 /// Default implementation for AmountStyle
@@ -631,189 +963,100 @@ impl Default for AmountStyle {
             commodity_spaced: false,
             decimal_mark: Some(".".to_string()),
             digit_groups: None,
-            precision: 2,
+            precision: Precision::Digits(2),
             rounding: "NoRounding".to_string(),
         }
     }
 }
 
-/// Parse balance assertion from JSON
-fn parse_balance_assertion(value: &serde_json::Value) -> Result<Option<BalanceAssertion>> {
-    if value.is_null() {
-        return Ok(None);
-    }
+/// A balance assertion as hledger writes it
+#[derive(Deserialize)]
+struct JsonBalanceAssertion {
+    baamount: Option<JsonPrintAmount>,
+    #[serde(default)]
+    bainclusive: bool,
+    #[serde(default)]
+    batotal: bool,
+    baposition: Option<JsonSourcePosition>,
+}
 
-    let obj = value.as_object().ok_or_else(|| {
-        HLedgerError::ParseError("Balance assertion should be an object".to_string())
-    })?;
+impl JsonBalanceAssertion {
+    /// The assertion, or `None` if it has no amount
+    fn into_assertion(self) -> Result<Option<BalanceAssertion>> {
+        let Some(amount) = self.baamount else {
+            return Ok(None);
+        };
+
+        Ok(Some(BalanceAssertion {
+            amount: print_amount(amount)?,
+            inclusive: self.bainclusive,
+            total: self.batotal,
+            position: self.baposition.unwrap_or_default().into_position(),
+        }))
+    }
+}
 
-    let amount = if let Some(amount_json) = obj.get("baamount") {
-        parse_single_print_amount(amount_json)?
-    } else {
-        return Ok(None);
-    };
+/// A source position as hledger writes it
+#[derive(Default, Deserialize)]
+struct JsonSourcePosition {
+    #[serde(rename = "sourceLine", default)]
+    line: u32,
+    #[serde(rename = "sourceColumn", default)]
+    column: u32,
+    #[serde(rename = "sourceName", default)]
+    file: String,
+}
 
-    let inclusive = obj
-        .get("bainclusive")
-        .and_then(|i| i.as_bool())
-        .unwrap_or(false);
-
-    let total = obj
-        .get("batotal")
-        .and_then(|t| t.as_bool())
-        .unwrap_or(false);
-
-    let position = if let Some(pos_json) = obj.get("baposition") {
-        parse_source_position(pos_json).unwrap_or_else(|| SourcePosition {
-            line: 0,
-            column: 0,
-            file: String::new(),
-        })
-    } else {
+impl JsonSourcePosition {
+    fn into_position(self) -> SourcePosition {
         SourcePosition {
-            line: 0,
-            column: 0,
-            file: String::new(),
+            line: self.line,
+            column: self.column,
+            file: self.file,
         }
-    };
-
-    Ok(Some(BalanceAssertion {
-        amount,
-        inclusive,
-        total,
-        position,
-    }))
-}
-
-/// Parse a single print amount from JSON
-fn parse_single_print_amount(value: &serde_json::Value) -> Result<PrintAmount> {
-    let obj = value
-        .as_object()
-        .ok_or_else(|| HLedgerError::ParseError("Amount should be an object".to_string()))?;
-
-    let commodity = obj
-        .get("acommodity")
-        .and_then(|c| c.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let quantity = if let Some(q) = obj.get("aquantity") {
-        parse_decimal_from_json(q)?
-    } else {
-        Decimal::ZERO
-    };
-
-    let price = if let Some(price_obj) = obj.get("aprice") {
-        parse_price(price_obj)?
-    } else {
-        None
-    };
-
-    let style = if let Some(style_obj) = obj.get("astyle") {
-        parse_amount_style(style_obj)?
-    } else {
-        AmountStyle::default()
-    };
-
-    Ok(PrintAmount {
-        commodity,
-        quantity,
-        price,
-        style,
-    })
+    }
 }
 
-/// Parse source position from JSON
-fn parse_source_position(value: &serde_json::Value) -> Option<SourcePosition> {
-    let obj = value.as_object()?;
-
-    let line = obj.get("sourceLine").and_then(|l| l.as_u64()).unwrap_or(0) as u32;
-
-    let column = obj
-        .get("sourceColumn")
-        .and_then(|c| c.as_u64())
-        .unwrap_or(0) as u32;
-
-    let file = obj
-        .get("sourceName")
-        .and_then(|n| n.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    Some(SourcePosition { line, column, file })
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::json::from_value;
 
-/// Parse price from JSON (reused from balance module pattern)
-fn parse_price(value: &serde_json::Value) -> Result<Option<Price>> {
-    if value.is_null() {
-        return Ok(None);
+    fn parse_transaction(value: &serde_json::Value) -> Result<PrintTransaction> {
+        from_value::<JsonTransaction>(value)?.into_transaction()
     }
 
-    if let Some(price_obj) = value.as_object() {
-        // Handle the tagged price format with "contents" field
-        if let Some(amount_obj) = price_obj.get("contents").and_then(|a| a.as_object()) {
-            let commodity = amount_obj
-                .get("acommodity")
-                .and_then(|c| c.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let quantity = if let Some(q) = amount_obj.get("aquantity") {
-                parse_decimal_from_json(q)?
-            } else {
-                Decimal::ZERO
-            };
-
-            return Ok(Some(Price {
-                commodity,
-                quantity,
-            }));
-        }
+    fn parse_source_position(value: &serde_json::Value) -> Result<SourcePosition> {
+        from_value::<JsonSourcePosition>(value).map(JsonSourcePosition::into_position)
     }
-    Ok(None)
-}
 
-/// Parse decimal from JSON value (reused from balance module)
-fn parse_decimal_from_json(value: &serde_json::Value) -> Result<Decimal> {
-    if let Some(obj) = value.as_object() {
-        // Handle decimal object format
-        if let Some(mantissa) = obj.get("decimalMantissa").and_then(|m| m.as_i64()) {
-            let places = obj
-                .get("decimalPlaces")
-                .and_then(|p| p.as_u64())
-                .unwrap_or(0) as u32;
-            return Ok(Decimal::new(mantissa, places));
-        }
-    } else if let Some(num) = value.as_f64() {
-        // Handle simple number
-        return Decimal::from_f64_retain(num)
-            .ok_or_else(|| HLedgerError::ParseError("Invalid decimal number".to_string()));
-    } else if let Some(s) = value.as_str() {
-        // Handle string number
-        return s
-            .parse()
-            .map_err(|_| HLedgerError::ParseError("Invalid decimal string".to_string()));
+    fn parse_amount_style(value: &serde_json::Value) -> Result<AmountStyle> {
+        from_value::<JsonAmountStyle>(value)?.into_style()
     }
 
-    Err(HLedgerError::ParseError(
-        "Unknown decimal format".to_string(),
-    ))
-}
+    fn parse_digit_groups(value: &serde_json::Value) -> Result<DigitGroups> {
+        from_value::<JsonDigitGroups>(value)?.into_digit_groups()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn parse_precision(value: &serde_json::Value) -> Result<Precision> {
+        from_value::<JsonPrecision>(value)?.into_precision()
+    }
 
     #[test]
     fn export_bindings() {
         PrintOptions::export_all().unwrap();
         SourcePosition::export_all().unwrap();
         AmountStyle::export_all().unwrap();
-        Price::export_all().unwrap();
+        DigitGroups::export_all().unwrap();
+        Precision::export_all().unwrap();
         PrintAmount::export_all().unwrap();
         BalanceAssertion::export_all().unwrap();
         PrintPosting::export_all().unwrap();
         PrintTransaction::export_all().unwrap();
+        SourceSpan::export_all().unwrap();
+        PageSpec::export_all().unwrap();
+        PageOrder::export_all().unwrap();
+        PrintPage::export_all().unwrap();
     }
 
     #[test]
@@ -837,19 +1080,18 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_decimal() {
-        // Test decimal object format
-        let json = serde_json::json!({
-            "decimalMantissa": 2000,
-            "decimalPlaces": 2
-        });
-        let decimal = parse_decimal_from_json(&json).unwrap();
-        assert_eq!(decimal, Decimal::new(2000, 2));
-
-        // Test floating point format
-        let json = serde_json::json!(20.5);
-        let decimal = parse_decimal_from_json(&json).unwrap();
-        assert_eq!(decimal.to_string(), "20.5");
+    fn test_forecast_and_today_args() {
+        let options = PrintOptions::new().forecast().today("2024-06-10");
+        let cmd = print_command(None, None, &options, "json").unwrap();
+        let args: Vec<_> = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert!(args.contains(&"--forecast".to_string()));
+        assert!(args.contains(&"--today=2024-06-10".to_string()));
+
+        let cmd = print_command(None, None, &PrintOptions::new(), "json").unwrap();
+        assert!(!cmd.get_args().any(|arg| arg == "--forecast"));
     }
 
     #[test]
@@ -865,13 +1107,189 @@ mod tests {
         assert_eq!(pos.file, "test.journal");
     }
 
+    #[test]
+    fn test_parse_transaction_source_span() {
+        // First transaction of tests/fixtures/spans.journal: a comment line
+        // and three postings, followed by a blank line
+        let json = serde_json::json!({
+            "tindex": 1,
+            "tdate": "2024-01-05",
+            "tdescription": "Grocery store",
+            "tcomment": "weekly shop\n",
+            "tpostings": [],
+            "tsourcepos": [
+                {"sourceName": "tests/fixtures/spans.journal", "sourceLine": 3, "sourceColumn": 1},
+                {"sourceName": "tests/fixtures/spans.journal", "sourceLine": 8, "sourceColumn": 1}
+            ]
+        });
+        let txn = parse_transaction(&json).unwrap();
+        assert_eq!(txn.source_positions.len(), 2);
+        assert_eq!(
+            txn.source_span,
+            Some(SourceSpan {
+                file: "tests/fixtures/spans.journal".to_string(),
+                start_line: 3,
+                start_column: 1,
+                end_line: 8,
+                end_column: 1,
+            })
+        );
+
+        let serialized = serde_json::to_value(&txn).unwrap();
+        assert_eq!(serialized["sourceSpan"]["endLine"], 8);
+
+        let json = serde_json::json!({"tindex": 1, "tdate": "2024-01-05"});
+        assert_eq!(parse_transaction(&json).unwrap().source_span, None);
+    }
+
+    #[test]
+    fn test_parse_transaction_dates() {
+        let json = serde_json::json!({
+            "tindex": 1,
+            "tdate": "2024-01-05",
+            "tdate2": "2024-01-07",
+            "tdescription": "groceries",
+            "tpostings": [{
+                "paccount": "expenses:groceries",
+                "pdate": "2024-01-06",
+                "pdate2": null,
+                "pamount": []
+            }]
+        });
+        let txn = parse_transaction(&json).unwrap();
+        assert_eq!(txn.date, NaiveDate::from_ymd_opt(2024, 1, 5).unwrap());
+        assert_eq!(txn.date2, NaiveDate::from_ymd_opt(2024, 1, 7));
+        assert_eq!(txn.postings[0].date, NaiveDate::from_ymd_opt(2024, 1, 6));
+        assert_eq!(txn.postings[0].date2, None);
+
+        let serialized = serde_json::to_value(&txn).unwrap();
+        assert_eq!(serialized["date"], "2024-01-05");
+        assert_eq!(serialized["date2"], "2024-01-07");
+    }
+
+    #[test]
+    fn test_parse_transaction_malformed_date() {
+        let json = serde_json::json!({"tindex": 1, "tdate": "01/05/2024"});
+        assert!(matches!(
+            parse_transaction(&json),
+            Err(HLedgerError::ParseError(_))
+        ));
+
+        let json = serde_json::json!({"tindex": 1});
+        assert!(parse_transaction(&json).is_err());
+    }
+
+    #[test]
+    fn test_parse_print_reader() {
+        let json = br#"[
+            {"tindex": 1, "tdate": "2024-01-05", "tdescription": "groceries", "tpostings": []},
+            {"tindex": 2, "tdate": "2024-01-06", "tdescription": "rent", "tpostings": []}
+        ]"#;
+        let transactions = parse_print_reader(&json[..]).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[1].index, 2);
+        assert_eq!(transactions[1].description, "rent");
+
+        assert!(parse_print_reader(&b"[]"[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_print_reader_errors() {
+        // Parse errors inside a transaction surface unchanged
+        let json = br#"[{"tindex": 1, "tdate": "2024-01-05"}, {"tindex": 2, "tdate": "bad"}]"#;
+        assert!(matches!(
+            parse_print_reader(&json[..]),
+            Err(HLedgerError::ParseError(_))
+        ));
+
+        // Anything but a single array is a JSON error
+        assert!(matches!(
+            parse_print_reader(&b"{}"[..]),
+            Err(HLedgerError::Json(_))
+        ));
+        assert!(matches!(
+            parse_print_reader(&b"[] []"[..]),
+            Err(HLedgerError::Json(_))
+        ));
+    }
+
+    /// Print JSON for transactions indexed 1 to `count`, one per day
+    fn numbered_transactions(count: u32) -> Vec<u8> {
+        let transactions: Vec<_> = (1..=count)
+            .map(|i| {
+                serde_json::json!({
+                    "tindex": i,
+                    "tdate": format!("2024-01-{:02}", i),
+                    "tdescription": format!("t{}", i),
+                    "tpostings": []
+                })
+            })
+            .collect();
+        serde_json::to_vec(&transactions).unwrap()
+    }
+
+    fn page_indices(json: &[u8], page: PageSpec) -> (usize, Vec<u32>) {
+        let page = parse_print_page_reader(json, page).unwrap();
+        let indices = page.transactions.iter().map(|t| t.index).collect();
+        (page.total, indices)
+    }
+
+    #[test]
+    fn test_parse_print_page_reader() {
+        let json = numbered_transactions(5);
+
+        assert_eq!(page_indices(&json, PageSpec::new(1, 2)), (5, vec![2, 3]));
+        assert_eq!(page_indices(&json, PageSpec::new(3, 10)), (5, vec![4, 5]));
+        assert_eq!(page_indices(&json, PageSpec::new(7, 2)), (5, vec![]));
+        assert_eq!(page_indices(&json, PageSpec::new(0, 0)), (5, vec![]));
+
+        let newest = |offset, limit| PageSpec::new(offset, limit).newest_first();
+        assert_eq!(page_indices(&json, newest(0, 2)), (5, vec![5, 4]));
+        assert_eq!(page_indices(&json, newest(1, 2)), (5, vec![4, 3]));
+        assert_eq!(page_indices(&json, newest(3, 10)), (5, vec![2, 1]));
+        assert_eq!(page_indices(&json, newest(5, 2)), (5, vec![]));
+        assert_eq!(page_indices(&json, newest(0, 0)), (5, vec![]));
+
+        assert_eq!(page_indices(b"[]", PageSpec::new(0, 10)), (0, vec![]));
+    }
+
+    #[test]
+    fn test_parse_print_page_reader_errors() {
+        // Only transactions in the page are parsed
+        let json = br#"[{"tindex": 1, "tdate": "bad"}, {"tindex": 2, "tdate": "2024-01-06"}]"#;
+        assert_eq!(page_indices(json, PageSpec::new(1, 1)), (2, vec![2]));
+        assert!(matches!(
+            parse_print_page_reader(&json[..], PageSpec::new(0, 1)),
+            Err(HLedgerError::ParseError(_))
+        ));
+
+        // Malformed JSON fails wherever it is
+        assert!(matches!(
+            parse_print_page_reader(&b"[{}, {"[..], PageSpec::new(0, 1)),
+            Err(HLedgerError::Json(_))
+        ));
+        assert!(matches!(
+            parse_print_page_reader(&b"{}"[..], PageSpec::new(0, 1)),
+            Err(HLedgerError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_page_spec_serialization() {
+        let json = serde_json::to_value(PageSpec::new(50, 25).newest_first()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"offset": 50, "limit": 25, "order": "NewestFirst"})
+        );
+    }
+
     #[test]
     fn test_parse_amount_style() {
         let json = serde_json::json!({
             "ascommodityside": "R",
             "ascommodityspaced": true,
             "asdecimalmark": ",",
-            "asdigitgroups": "3",
+            "asdigitgroups": [".", [3]],
             "asprecision": 2,
             "asrounding": "HardRounding"
         });
@@ -879,8 +1297,118 @@ mod tests {
         assert_eq!(style.commodity_side, "R");
         assert!(style.commodity_spaced);
         assert_eq!(style.decimal_mark, Some(",".to_string()));
-        assert_eq!(style.digit_groups, Some("3".to_string()));
-        assert_eq!(style.precision, 2);
+        assert_eq!(
+            style.digit_groups,
+            Some(DigitGroups {
+                separator: '.',
+                sizes: vec![3]
+            })
+        );
+        assert_eq!(style.precision, Precision::Digits(2));
         assert_eq!(style.rounding, "HardRounding");
     }
+
+    #[test]
+    fn test_parse_digit_groups_us() {
+        // hledger -f - print -O json for `$1,234,567.89`
+        let json = serde_json::json!({
+            "ascommodityside": "L",
+            "ascommodityspaced": false,
+            "asdecimalmark": ".",
+            "asdigitgroups": [",", [3]],
+            "asprecision": 2,
+            "asrounding": "NoRounding"
+        });
+        let style = parse_amount_style(&json).unwrap();
+        assert_eq!(
+            style.digit_groups,
+            Some(DigitGroups {
+                separator: ',',
+                sizes: vec![3]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_digit_groups_indian() {
+        // hledger -f - print -O json for `INR 10,00,000.00`
+        let json = serde_json::json!({
+            "ascommodityside": "L",
+            "ascommodityspaced": true,
+            "asdecimalmark": ".",
+            "asdigitgroups": [",", [3, 2]],
+            "asprecision": 2,
+            "asrounding": "NoRounding"
+        });
+        let style = parse_amount_style(&json).unwrap();
+        assert_eq!(
+            style.digit_groups,
+            Some(DigitGroups {
+                separator: ',',
+                sizes: vec![3, 2]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_digit_groups_forms() {
+        let json = serde_json::json!({"tag": "DigitGroups", "contents": [" ", [3]]});
+        assert_eq!(
+            parse_digit_groups(&json).unwrap(),
+            DigitGroups {
+                separator: ' ',
+                sizes: vec![3]
+            }
+        );
+
+        let json = serde_json::json!({"asdigitgroups": null});
+        assert_eq!(parse_amount_style(&json).unwrap().digit_groups, None);
+
+        assert!(parse_digit_groups(&serde_json::json!("3")).is_err());
+        assert!(parse_digit_groups(&serde_json::json!([",,", [3]])).is_err());
+        assert!(parse_digit_groups(&serde_json::json!([",", [300]])).is_err());
+    }
+
+    #[test]
+    fn test_parse_natural_precision() {
+        let json = serde_json::json!({
+            "ascommodityside": "L",
+            "ascommodityspaced": false,
+            "asdecimalmark": ".",
+            "asdigitgroups": null,
+            "asprecision": "NaturalPrecision",
+            "asrounding": "NoRounding"
+        });
+        let style = parse_amount_style(&json).unwrap();
+        assert_eq!(style.precision, Precision::Natural);
+
+        let json = serde_json::json!({"tag": "NaturalPrecision"});
+        assert_eq!(parse_precision(&json).unwrap(), Precision::Natural);
+    }
+
+    #[test]
+    fn test_parse_precision_forms() {
+        assert_eq!(
+            parse_precision(&serde_json::json!(0)).unwrap(),
+            Precision::Digits(0)
+        );
+        assert_eq!(
+            parse_precision(&serde_json::json!({"tag": "Precision", "contents": 4})).unwrap(),
+            Precision::Digits(4)
+        );
+        assert!(parse_precision(&serde_json::json!("Bogus")).is_err());
+        assert!(parse_precision(&serde_json::json!(-1)).is_err());
+    }
+
+    #[test]
+    fn test_precision_serialization() {
+        assert_eq!(
+            serde_json::to_value(Precision::Digits(2)).unwrap(),
+            serde_json::json!({"type": "Digits", "digits": 2})
+        );
+        assert_eq!(
+            serde_json::to_value(Precision::Natural).unwrap(),
+            serde_json::json!({"type": "Natural"})
+        );
+    }
 }
@@ -0,0 +1,410 @@
+use crate::commands::balance::{get_balance, Amount, BalanceOptions, BalanceReport};
+use crate::conversion::ConversionTable;
+use crate::interval::ReportInterval;
+use crate::list_mode::ListMode;
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for [`top_spending_accounts`]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TopSpendingOptions {
+    /// Account query selecting the spending accounts (`^expenses` when `None`)
+    pub accounts: Option<String>,
+    /// Account depth to rank at; deeper accounts roll up (2 when `None`)
+    pub depth: Option<u32>,
+
+    // Period selection
+    /// Period expression, e.g. `2024-03` or `lastmonth`
+    pub period: Option<String>,
+
+    // Date filters
+    /// Begin date (inclusive: transactions on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: transactions before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    // Ranking
+    /// Commodity to rank in; the one used by the most accounts when `None`
+    pub commodity: Option<String>,
+    /// Convert other commodities with the journal's market prices instead of
+    /// leaving them out
+    pub convert: bool,
+
+    // Query patterns
+    pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+/// One account's spending, or the remainder after the top accounts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct AccountTotal {
+    /// Full account name, or `other` for the remainder
+    pub account: String,
+    /// Total in the ranking commodity
+    pub amount: Amount,
+    /// Whether this is the aggregated remainder of the accounts below the top N
+    pub is_other: bool,
+}
+
+// Implementation for builder pattern
+impl TopSpendingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn accounts(mut self, query: impl Into<String>) -> Self {
+        self.accounts = Some(query.into());
+        self
+    }
+
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn period(mut self, period: impl Into<String>) -> Self {
+        self.period = Some(period.into());
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn commodity(mut self, commodity: impl Into<String>) -> Self {
+        self.commodity = Some(commodity.into());
+        self
+    }
+
+    pub fn convert(mut self) -> Self {
+        self.convert = true;
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.queries.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    /// The flat, depth-limited balance report the ranking is built from
+    fn balance_options(&self) -> BalanceOptions {
+        let mut queries = vec![self
+            .accounts
+            .clone()
+            .unwrap_or_else(|| "^expenses".to_string())];
+        queries.extend(self.queries.iter().cloned());
+
+        BalanceOptions {
+            list_mode: Some(ListMode::Flat),
+            depth: Some(self.depth.unwrap_or(2)),
+            interval: self
+                .period
+                .clone()
+                .map_or(ReportInterval::None, ReportInterval::Custom),
+            begin: self.begin.clone(),
+            end: self.end.clone(),
+            unchecked_dates: self.unchecked_dates,
+            queries,
+            timeout: self.timeout,
+            ..BalanceOptions::default()
+        }
+    }
+}
+
+/// The `n` accounts with the largest spending, followed by an `other` entry
+/// totalling the rest when there are more than `n`
+///
+/// Runs a flat `hledger balance` limited to `depth` and ranks by absolute
+/// amount in one commodity. Amounts in other commodities are left out unless
+/// `convert` is set, in which case they are converted with the journal's
+/// latest market prices; amounts with no known rate are still left out.
+pub fn top_spending_accounts(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    n: usize,
+    options: &TopSpendingOptions,
+) -> Result<Vec<AccountTotal>> {
+    let report = get_balance(hledger_path, journal_file, &options.balance_options())?;
+    let table = if options.convert {
+        Some(ConversionTable::load(hledger_path, journal_file, None)?)
+    } else {
+        None
+    };
+    Ok(rank_accounts(
+        &report_rows(&report),
+        n,
+        options.commodity.as_deref(),
+        table.as_ref(),
+    ))
+}
+
+/// Each account with all of its amounts; periodic reports are summed over
+/// their periods
+fn report_rows(report: &BalanceReport) -> Vec<(String, Vec<Amount>)> {
+    match report {
+        BalanceReport::Simple(balance) => balance
+            .accounts
+            .iter()
+            .map(|account| (account.name.clone(), account.amounts.clone()))
+            .collect(),
+        BalanceReport::Periodic(balance) => balance
+            .rows
+            .iter()
+            .map(|row| (row.account.clone(), row.amounts.concat()))
+            .collect(),
+    }
+}
+
+/// The commodity appearing in the most rows; ties go to the first seen
+pub(crate) fn most_common_commodity(rows: &[(String, Vec<Amount>)]) -> Option<String> {
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (_, amounts) in rows {
+        let mut seen: Vec<&str> = Vec::new();
+        for amount in amounts {
+            if !seen.contains(&amount.commodity.as_str()) {
+                seen.push(&amount.commodity);
+            }
+        }
+        for commodity in seen {
+            let first_seen = counts.len();
+            counts.entry(commodity).or_insert((0, first_seen)).0 += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by(|(_, (a, a_first)), (_, (b, b_first))| a.cmp(b).then(b_first.cmp(a_first)))
+        .map(|(commodity, _)| commodity.to_string())
+}
+
+/// Sum of the amounts in the target commodity; other commodities are
+/// converted with the table, or left out when there is none or no rate
+pub(crate) fn quantity_in(
+    amounts: &[Amount],
+    target: &str,
+    table: Option<&ConversionTable>,
+) -> Decimal {
+    amounts
+        .iter()
+        .filter_map(|amount| {
+            if amount.commodity == target {
+                Some(amount.quantity)
+            } else {
+                table?.convert(amount, target).map(|a| a.quantity)
+            }
+        })
+        .sum()
+}
+
+fn rank_accounts(
+    rows: &[(String, Vec<Amount>)],
+    n: usize,
+    commodity: Option<&str>,
+    table: Option<&ConversionTable>,
+) -> Vec<AccountTotal> {
+    let Some(target) = commodity
+        .map(str::to_string)
+        .or_else(|| most_common_commodity(rows))
+    else {
+        return Vec::new();
+    };
+    let total = |quantity: Decimal, account: &str, is_other: bool| AccountTotal {
+        account: account.to_string(),
+        amount: Amount {
+            commodity: target.clone(),
+            quantity,
+            price: None,
+        },
+        is_other,
+    };
+
+    let mut totals: Vec<AccountTotal> = rows
+        .iter()
+        .map(|(account, amounts)| total(quantity_in(amounts, &target, table), account, false))
+        .filter(|account| !account.amount.quantity.is_zero())
+        .collect();
+    totals.sort_by_key(|total| Reverse(total.amount.quantity.abs()));
+
+    if totals.len() > n {
+        let rest: Decimal = totals.drain(n..).map(|a| a.amount.quantity).sum();
+        totals.push(total(rest, "other", true));
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::prices::MarketPrice;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn export_bindings() {
+        TopSpendingOptions::export_all().unwrap();
+        AccountTotal::export_all().unwrap();
+    }
+
+    fn amount(commodity: &str, quantity: &str) -> Amount {
+        Amount {
+            commodity: commodity.to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+        }
+    }
+
+    fn row(account: &str, amounts: &[(&str, &str)]) -> (String, Vec<Amount>) {
+        (
+            account.to_string(),
+            amounts.iter().map(|(c, q)| amount(c, q)).collect(),
+        )
+    }
+
+    fn summary(totals: &[AccountTotal]) -> Vec<(&str, String, bool)> {
+        totals
+            .iter()
+            .map(|t| {
+                (
+                    t.account.as_str(),
+                    t.amount.quantity.to_string(),
+                    t.is_other,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_balance_options_defaults() {
+        let options = TopSpendingOptions::new().balance_options();
+        assert_eq!(options.list_mode, Some(ListMode::Flat));
+        assert_eq!(options.depth, Some(2));
+        assert_eq!(options.interval, ReportInterval::None);
+        assert_eq!(options.queries, vec!["^expenses"]);
+
+        let options = TopSpendingOptions::new()
+            .accounts("^expenses:food")
+            .depth(3)
+            .period("2024-02")
+            .query("not:tag:reimbursed")
+            .balance_options();
+        assert_eq!(options.depth, Some(3));
+        assert_eq!(options.interval, ReportInterval::Custom("2024-02".into()));
+        assert_eq!(
+            options.queries,
+            vec!["^expenses:food", "not:tag:reimbursed"]
+        );
+    }
+
+    #[test]
+    fn test_rank_accounts_top_n_and_other() {
+        let rows = vec![
+            row("expenses:books", &[("$", "30")]),
+            row("expenses:food", &[("$", "450")]),
+            row("expenses:rent", &[("$", "1200")]),
+            row("expenses:travel", &[("$", "80")]),
+        ];
+        let totals = rank_accounts(&rows, 2, None, None);
+        assert_eq!(
+            summary(&totals),
+            vec![
+                ("expenses:rent", "1200".to_string(), false),
+                ("expenses:food", "450".to_string(), false),
+                ("other", "110".to_string(), true),
+            ]
+        );
+
+        // No other bucket when everything fits
+        assert_eq!(rank_accounts(&rows, 4, None, None).len(), 4);
+    }
+
+    #[test]
+    fn test_rank_accounts_ranks_by_absolute_amount() {
+        let rows = vec![
+            row("expenses:food", &[("$", "50")]),
+            row("expenses:refunds", &[("$", "-200")]),
+        ];
+        let totals = rank_accounts(&rows, 1, Some("$"), None);
+        assert_eq!(totals[0].account, "expenses:refunds");
+        assert_eq!(totals[1].amount.quantity, Decimal::from(50));
+    }
+
+    #[test]
+    fn test_rank_accounts_picks_most_common_commodity() {
+        let rows = vec![
+            row("expenses:food", &[("EUR", "40"), ("$", "10")]),
+            row("expenses:rent", &[("EUR", "900")]),
+            row("expenses:software", &[("$", "500")]),
+        ];
+        let totals = rank_accounts(&rows, 5, None, None);
+        assert_eq!(
+            summary(&totals),
+            vec![
+                ("expenses:rent", "900".to_string(), false),
+                ("expenses:food", "40".to_string(), false),
+            ]
+        );
+        assert!(totals.iter().all(|t| t.amount.commodity == "EUR"));
+    }
+
+    #[test]
+    fn test_rank_accounts_converts_other_commodities() {
+        let prices = vec![MarketPrice {
+            date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            commodity: "EUR".to_string(),
+            price: amount("$", "1.10"),
+        }];
+        let table = ConversionTable::from_prices(prices, None);
+        let rows = vec![
+            row("expenses:food", &[("EUR", "100"), ("$", "5")]),
+            row("expenses:rent", &[("$", "90")]),
+            row("expenses:misc", &[("GBP", "70")]),
+        ];
+        let totals = rank_accounts(&rows, 5, Some("$"), Some(&table));
+        assert_eq!(
+            summary(&totals),
+            vec![
+                ("expenses:food", "115.00".to_string(), false),
+                ("expenses:rent", "90".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rank_accounts_empty_report() {
+        assert!(rank_accounts(&[], 3, None, None).is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::config::{add_journal_args, check_success, run_command};
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, HLedgerError, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Summary figures for a journal, from `hledger stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct JournalStats {
+    pub transaction_count: u32,
+    /// Date of the first transaction; `None` without transactions
+    #[ts(type = "string | null")]
+    pub first_date: Option<NaiveDate>,
+    /// Date of the last transaction; `None` without transactions
+    #[ts(type = "string | null")]
+    pub last_date: Option<NaiveDate>,
+    pub account_count: u32,
+    pub payee_count: u32,
+    pub commodities: Vec<String>,
+}
+
+/// Get a journal's transaction count, date span, accounts and commodities
+///
+/// Runs `hledger stats` and reads its text output, which has no JSON form.
+/// Both the current labels and the older abbreviated ones (`Txns`, `Last
+/// txn`) are understood.
+pub fn get_stats(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<JournalStats> {
+    let mut cmd = get_hledger_command(hledger_path);
+    add_journal_args(&mut cmd, journal_file);
+    cmd.arg("stats");
+
+    let output = run_command(&mut cmd, timeout.map(Timeout))?;
+    check_success(&cmd, output.status, &output.stderr)?;
+    parse_stats(&String::from_utf8(output.stdout)?)
+}
+
+/// Read the `Label : value` lines of `hledger stats`; unknown labels and
+/// continuation lines are skipped
+fn parse_stats(text: &str) -> Result<JournalStats> {
+    let mut stats = JournalStats::default();
+    let mut found_count = false;
+    for line in text.lines() {
+        let Some((label, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match label.trim().to_lowercase().as_str() {
+            "transactions span" | "txns span" => {
+                stats.first_date = value
+                    .split_whitespace()
+                    .next()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+            }
+            "last transaction" | "last txn" => {
+                stats.last_date = value
+                    .split_whitespace()
+                    .next()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+            }
+            "transactions" | "txns" => {
+                stats.transaction_count = leading_number(value)?;
+                found_count = true;
+            }
+            "accounts" => stats.account_count = leading_number(value)?,
+            "payees/descriptions" => stats.payee_count = leading_number(value)?,
+            "commodities" => {
+                // `2 ($, EUR)`
+                stats.commodities = value
+                    .split_once('(')
+                    .and_then(|(_, list)| list.rsplit_once(')'))
+                    .map(|(list, _)| {
+                        list.split(", ")
+                            .map(str::trim)
+                            .filter(|commodity| !commodity.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+    if !found_count {
+        return Err(HLedgerError::ParseError(
+            "hledger stats printed no transaction count".to_string(),
+        ));
+    }
+    Ok(stats)
+}
+
+/// The number a stats value starts with, as in `5 (0.0 per day)`
+fn leading_number(value: &str) -> Result<u32> {
+    let number = value.split_whitespace().next().unwrap_or_default();
+    number
+        .parse()
+        .map_err(|_| HLedgerError::ParseError(format!("Expected a number in stats: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        JournalStats::export_all().unwrap();
+    }
+
+    fn date(s: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let text = "\
+Main file                : /books/main.journal
+Included files           : /books/2023.journal
+                           /books/2024.journal
+Transactions span        : 2023-01-02 to 2024-03-01 (424 days)
+Last transaction         : 2024-02-29 (230 days ago)
+Transactions             : 412 (1.0 per day)
+Transactions last 30 days: 0 (0.0 per day)
+Transactions last 7 days : 0 (0.0 per day)
+Payees/descriptions      : 57
+Accounts                 : 31 (depth 4)
+Commodities              : 2 ($, EUR)
+Market prices            : 12 (EUR)
+";
+        let stats = parse_stats(text).unwrap();
+        assert_eq!(stats.transaction_count, 412);
+        assert_eq!(stats.first_date, date("2023-01-02"));
+        assert_eq!(stats.last_date, date("2024-02-29"));
+        assert_eq!(stats.account_count, 31);
+        assert_eq!(stats.payee_count, 57);
+        assert_eq!(stats.commodities, ["$", "EUR"]);
+    }
+
+    #[test]
+    fn test_parse_stats_older_labels_and_empty_journal() {
+        let text = "\
+Main file                : /books/empty.journal
+Included files           :
+Txns span                :  to  (0 days)
+Last txn                 : none
+Txns                     : 0 (0.0 per day)
+Payees/descriptions      : 0
+Accounts                 : 0 (depth 0)
+Commodities              : 0 ()
+";
+        let stats = parse_stats(text).unwrap();
+        assert_eq!(stats, JournalStats::default());
+
+        assert!(matches!(
+            parse_stats("Main file : x.journal\n"),
+            Err(HLedgerError::ParseError(_))
+        ));
+    }
+}
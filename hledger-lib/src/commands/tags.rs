@@ -0,0 +1,380 @@
+use crate::amount::MultiAmount;
+use crate::commands::print::{get_print, PrintOptions, PrintPosting, PrintTransaction};
+use crate::config::{add_journal_args, check_success, run_command};
+use crate::dates::validate_date_options;
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::{get_hledger_command, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for the tags command
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TagsOptions {
+    /// Only tags whose name matches this regular expression
+    pub pattern: Option<String>,
+
+    // Date filters
+    /// Begin date (inclusive: transactions on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: transactions before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    /// Query patterns selecting the transactions whose tags are listed
+    pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+// Implementation for builder pattern
+impl TagsOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.queries.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    /// The print report the usage counts are read from
+    fn print_options(&self) -> PrintOptions {
+        PrintOptions {
+            begin: self.begin.clone(),
+            end: self.end.clone(),
+            unchecked_dates: self.unchecked_dates,
+            queries: self.queries.clone(),
+            timeout: self.timeout,
+            ..PrintOptions::with_inferred_amounts()
+        }
+    }
+}
+
+/// How much one tag value is used
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TagValueUsage {
+    /// Empty for the tag used without a value
+    pub value: String,
+    pub transaction_count: u32,
+    pub posting_count: u32,
+    /// Sum of the postings with this value
+    pub total: MultiAmount,
+}
+
+/// How much a tag is used, overall and per value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TagUsage {
+    pub name: String,
+    /// Transactions with the tag on themselves or any of their postings
+    pub transaction_count: u32,
+    /// Postings with the tag, their own or their transaction's
+    pub posting_count: u32,
+    /// Sum of the postings with the tag
+    pub total: MultiAmount,
+    /// Sorted by value
+    pub values: Vec<TagValueUsage>,
+}
+
+/// Get the names of the tags used in the journal
+///
+/// Runs `hledger tags`, which prints one name per line and also lists tags
+/// declared with `tag` directives or used on account declarations.
+pub fn get_tags(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &TagsOptions,
+) -> Result<Vec<String>> {
+    if !options.unchecked_dates {
+        validate_date_options(options.begin.as_deref(), options.end.as_deref())?;
+    }
+
+    let mut cmd = get_hledger_command(hledger_path);
+    add_journal_args(&mut cmd, journal_file);
+    cmd.arg("tags");
+    if let Some(begin) = &options.begin {
+        cmd.arg("--begin").arg(begin);
+    }
+    if let Some(end) = &options.end {
+        cmd.arg("--end").arg(end);
+    }
+    // The first argument is the tag name pattern, so queries need one too
+    if options.pattern.is_some() || !options.queries.is_empty() {
+        cmd.arg("--")
+            .arg(options.pattern.as_deref().unwrap_or("."))
+            .args(&options.queries);
+    }
+
+    let output = run_command(&mut cmd, options.timeout)?;
+    check_success(&cmd, output.status, &output.stderr)?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Every tag with its transaction and posting counts and the sum of its
+/// postings, overall and per value, sorted by name
+///
+/// The names come from [`get_tags`], so tags that are declared but unused
+/// in the selected transactions are listed with zero counts; the counts
+/// come from one `hledger print` pass. A posting carries its own tags and
+/// its transaction's, so a tag on a whole balanced transaction totals zero:
+/// the totals tell most for posting tags.
+pub fn get_tag_usage(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &TagsOptions,
+) -> Result<Vec<TagUsage>> {
+    let names = get_tags(hledger_path, journal_file, options)?;
+    let transactions = get_print(hledger_path, journal_file, &options.print_options())?;
+    Ok(tag_usage(&names, &transactions))
+}
+
+#[derive(Default)]
+struct Tally {
+    transactions: u32,
+    postings: u32,
+    total: MultiAmount,
+}
+
+/// Usage of each of `names` across `transactions`; other tags are ignored
+fn tag_usage(names: &[String], transactions: &[PrintTransaction]) -> Vec<TagUsage> {
+    let mut tallies: BTreeMap<&str, (Tally, BTreeMap<&str, Tally>)> = names
+        .iter()
+        .map(|name| (name.as_str(), Default::default()))
+        .collect();
+
+    for transaction in transactions {
+        let own: BTreeSet<(&str, &str)> = pairs(&transaction.tags).collect();
+        let mut in_transaction = own.clone();
+        for posting in &transaction.postings {
+            let tags: BTreeSet<(&str, &str)> =
+                pairs(&posting.tags).chain(own.iter().copied()).collect();
+            // A tag with two values on one posting still counts once
+            let names: BTreeSet<&str> = tags.iter().map(|&(name, _)| name).collect();
+            for name in names {
+                if let Some((tally, _)) = tallies.get_mut(name) {
+                    tally.postings += 1;
+                    add_amounts(&mut tally.total, posting);
+                }
+            }
+            for &(name, value) in &tags {
+                if let Some((_, values)) = tallies.get_mut(name) {
+                    let tally = values.entry(value).or_default();
+                    tally.postings += 1;
+                    add_amounts(&mut tally.total, posting);
+                }
+            }
+            in_transaction.extend(tags);
+        }
+
+        let mut counted = BTreeSet::new();
+        for (name, value) in in_transaction {
+            let Some((tally, values)) = tallies.get_mut(name) else {
+                continue;
+            };
+            values.entry(value).or_default().transactions += 1;
+            if counted.insert(name) {
+                tally.transactions += 1;
+            }
+        }
+    }
+
+    tallies
+        .into_iter()
+        .map(|(name, (tally, values))| TagUsage {
+            name: name.to_string(),
+            transaction_count: tally.transactions,
+            posting_count: tally.postings,
+            total: tally.total,
+            values: values
+                .into_iter()
+                .map(|(value, tally)| TagValueUsage {
+                    value: value.to_string(),
+                    transaction_count: tally.transactions,
+                    posting_count: tally.postings,
+                    total: tally.total,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn add_amounts(total: &mut MultiAmount, posting: &PrintPosting) {
+    for amount in &posting.amounts {
+        total.add_quantity(&amount.commodity, amount.quantity);
+    }
+}
+
+fn pairs(tags: &[(String, String)]) -> impl Iterator<Item = (&str, &str)> {
+    tags.iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+
+    #[test]
+    fn export_bindings() {
+        TagsOptions::export_all().unwrap();
+        TagUsage::export_all().unwrap();
+    }
+
+    /// Tag names and values
+    type Pairs<'a> = &'a [(&'a str, &'a str)];
+
+    fn transaction(transaction_tags: Pairs, postings: &[(&str, &str, Pairs)]) -> PrintTransaction {
+        let txn = transaction_tags.iter().fold(
+            PrintTransactionBuilder::new(1, "2024-03-01"),
+            |txn, (name, value)| txn.tag(name, value),
+        );
+        postings
+            .iter()
+            .fold(txn, |txn, (account, dollars, posting_tags)| {
+                let posting = PrintPostingBuilder::new(account).amount("$", dollars);
+                txn.posting(
+                    posting_tags
+                        .iter()
+                        .fold(posting, |posting, (name, value)| posting.tag(name, value)),
+                )
+            })
+            .build()
+    }
+
+    fn dollars(quantity: &str) -> MultiAmount {
+        let mut total = MultiAmount::new();
+        total.add_quantity("$", quantity.parse().unwrap());
+        total
+    }
+
+    #[test]
+    fn test_tag_usage() {
+        let transactions = [
+            transaction(
+                &[("trip", "paris")],
+                &[
+                    ("expenses:hotel", "300", &[("reimbursable", "")]),
+                    ("expenses:food", "80", &[]),
+                    ("assets:cash", "-380", &[]),
+                ],
+            ),
+            transaction(
+                &[],
+                &[
+                    ("expenses:food", "20", &[("trip", "rome")]),
+                    ("expenses:taxi", "15", &[("reimbursable", "")]),
+                    ("assets:cash", "-35", &[]),
+                ],
+            ),
+        ];
+        let names = ["reimbursable", "trip", "unused"].map(String::from);
+        let usage = tag_usage(&names, &transactions);
+
+        let names: Vec<&str> = usage.iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, ["reimbursable", "trip", "unused"]);
+
+        let reimbursable = &usage[0];
+        assert_eq!(reimbursable.transaction_count, 2);
+        assert_eq!(reimbursable.posting_count, 2);
+        assert_eq!(reimbursable.total, dollars("315"));
+        assert_eq!(reimbursable.values.len(), 1);
+        assert_eq!(reimbursable.values[0].value, "");
+
+        // Inherited by every posting of the first transaction, so it nets
+        // to zero there
+        let trip = &usage[1];
+        assert_eq!(trip.transaction_count, 2);
+        assert_eq!(trip.posting_count, 4);
+        assert_eq!(trip.total, dollars("20"));
+        let values: Vec<(&str, u32, u32)> = trip
+            .values
+            .iter()
+            .map(|v| (v.value.as_str(), v.transaction_count, v.posting_count))
+            .collect();
+        assert_eq!(values, [("paris", 1, 3), ("rome", 1, 1)]);
+        assert!(trip.values[0].total.is_zero());
+
+        assert_eq!(
+            usage[2],
+            TagUsage {
+                name: "unused".to_string(),
+                ..TagUsage::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_inherited_tag_counts_once_per_posting() {
+        let transactions = [transaction(
+            &[("project", "a")],
+            &[
+                ("expenses:tools", "10", &[("project", "a")]),
+                ("assets:cash", "-10", &[]),
+            ],
+        )];
+        let usage = tag_usage(&["project".to_string()], &transactions);
+        assert_eq!(usage[0].transaction_count, 1);
+        assert_eq!(usage[0].posting_count, 2);
+        assert_eq!(usage[0].values[0].posting_count, 2);
+    }
+
+    #[test]
+    fn test_print_options() {
+        let options = TagsOptions::new()
+            .pattern("trip")
+            .begin("2024-01-01")
+            .query("^expenses");
+        let print = options.print_options();
+        assert!(print.explicit);
+        assert_eq!(print.begin.as_deref(), Some("2024-01-01"));
+        assert_eq!(print.queries, ["^expenses"]);
+    }
+}
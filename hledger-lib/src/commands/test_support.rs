@@ -0,0 +1,127 @@
+//! Builders for the print report structs that other modules' tests take as
+//! input, so each test spells out only the fields it cares about
+
+use crate::amount::default_style_for;
+use crate::commands::print::{PrintAmount, PrintPosting, PrintTransaction, SourceSpan};
+use chrono::NaiveDate;
+
+fn parse_date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+/// Builds a [`PrintTransaction`]: unmarked, with no description, comment,
+/// tags, postings or source span until given some
+pub(crate) struct PrintTransactionBuilder(PrintTransaction);
+
+impl PrintTransactionBuilder {
+    /// Transaction number `index`, dated `date` (`YYYY-MM-DD`)
+    pub(crate) fn new(index: u32, date: &str) -> Self {
+        Self(PrintTransaction {
+            index,
+            date: parse_date(date),
+            date2: None,
+            status: String::new(),
+            code: String::new(),
+            description: String::new(),
+            comment: String::new(),
+            tags: Vec::new(),
+            postings: Vec::new(),
+            preceding_comment: String::new(),
+            source_positions: Vec::new(),
+            source_span: None,
+        })
+    }
+
+    pub(crate) fn description(mut self, description: &str) -> Self {
+        self.0.description = description.to_string();
+        self
+    }
+
+    pub(crate) fn comment(mut self, comment: &str) -> Self {
+        self.0.comment = comment.to_string();
+        self
+    }
+
+    pub(crate) fn tag(mut self, name: &str, value: &str) -> Self {
+        self.0.tags.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add a posting, numbered with this transaction's index
+    pub(crate) fn posting(mut self, posting: PrintPostingBuilder) -> Self {
+        let mut posting = posting.build();
+        posting.transaction_index = self.0.index.to_string();
+        self.0.postings.push(posting);
+        self
+    }
+
+    /// Place the transaction in `file`, from the start of `start_line` up to
+    /// the start of `end_line`
+    pub(crate) fn span(mut self, file: &str, start_line: u32, end_line: u32) -> Self {
+        self.0.source_span = Some(SourceSpan {
+            file: file.to_string(),
+            start_line,
+            start_column: 1,
+            end_line,
+            end_column: 1,
+        });
+        self
+    }
+
+    pub(crate) fn build(self) -> PrintTransaction {
+        self.0
+    }
+}
+
+/// Builds a [`PrintPosting`]: a regular posting with no amounts, comment or
+/// tags until given some
+pub(crate) struct PrintPostingBuilder(PrintPosting);
+
+impl PrintPostingBuilder {
+    pub(crate) fn new(account: &str) -> Self {
+        Self(PrintPosting {
+            account: account.to_string(),
+            amounts: Vec::new(),
+            status: String::new(),
+            comment: String::new(),
+            tags: Vec::new(),
+            posting_type: "RegularPosting".to_string(),
+            date: None,
+            date2: None,
+            balance_assertion: None,
+            original: None,
+            transaction_index: String::new(),
+        })
+    }
+
+    /// Add an amount of `commodity`, styled as hledger would by default
+    pub(crate) fn amount(mut self, commodity: &str, quantity: &str) -> Self {
+        self.0.amounts.push(PrintAmount {
+            commodity: commodity.to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+            style: default_style_for(commodity),
+        });
+        self
+    }
+
+    pub(crate) fn comment(mut self, comment: &str) -> Self {
+        self.0.comment = comment.to_string();
+        self
+    }
+
+    pub(crate) fn tag(mut self, name: &str, value: &str) -> Self {
+        self.0.tags.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Date the posting itself, apart from its transaction
+    pub(crate) fn date(mut self, date: &str) -> Self {
+        self.0.date = Some(parse_date(date));
+        self
+    }
+
+    pub(crate) fn build(self) -> PrintPosting {
+        self.0
+    }
+}
@@ -0,0 +1,401 @@
+use crate::commands::balance::{get_balance, BalanceOptions, BalanceReport, PeriodicBalance};
+use crate::commands::prices::{get_prices, MarketPrice, PricesOptions};
+use crate::commands::spending::quantity_in;
+use crate::conversion::ConversionTable;
+use crate::defaults::ValueMode;
+use crate::interval::ReportInterval;
+use crate::list_mode::ListMode;
+use crate::query::Query;
+use crate::timeout::Timeout;
+use crate::Result;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// What each point of a series adds up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum Accumulation {
+    /// The change during the period
+    #[default]
+    Change,
+    /// The change from the start of the series to the end of the period
+    Cumulative,
+    /// The balance at the end of the period, from the start of the journal
+    Historical,
+}
+
+/// What [`get_timeseries`] charts
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TimeseriesSpec {
+    /// Query terms as hledger takes them, e.g. `^expenses` `depth:2`; each
+    /// account matched is a series
+    pub query: Vec<String>,
+    /// How the series is split into periods (monthly when `None`)
+    #[serde(default)]
+    pub interval: ReportInterval,
+    #[serde(default)]
+    pub accumulation: Accumulation,
+    /// How amounts are valued; as recorded when `None`
+    pub value_mode: Option<ValueMode>,
+    /// Commodity of every series, other commodities being converted with the
+    /// journal's market prices; one series per commodity when `None`
+    pub commodity: Option<String>,
+
+    // Date filters
+    /// Begin date (inclusive: transactions on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: transactions before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    pub timeout: Option<Timeout>,
+}
+
+/// One account's value in one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct SeriesPoint {
+    /// First day of the period
+    #[ts(type = "string")]
+    pub date: NaiveDate,
+    #[ts(type = "string")]
+    pub value: Decimal,
+}
+
+/// An account's values in one commodity, with a point for every period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct Series {
+    /// Full account name
+    pub account: String,
+    pub commodity: String,
+    pub points: Vec<SeriesPoint>,
+}
+
+// Implementation for builder pattern
+impl TimeseriesSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.query.push(query.to_arg());
+        self
+    }
+
+    pub fn interval(mut self, interval: ReportInterval) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
+
+    pub fn value_mode(mut self, mode: ValueMode) -> Self {
+        self.value_mode = Some(mode);
+        self
+    }
+
+    pub fn commodity(mut self, commodity: impl Into<String>) -> Self {
+        self.commodity = Some(commodity.into());
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    /// The flat, periodic balance report the series are read from
+    fn balance_options(&self) -> BalanceOptions {
+        let interval = match &self.interval {
+            ReportInterval::None => ReportInterval::Monthly,
+            interval => interval.clone(),
+        };
+
+        BalanceOptions {
+            cumulative: self.accumulation == Accumulation::Cumulative,
+            historical: self.accumulation == Accumulation::Historical,
+            list_mode: Some(ListMode::Flat),
+            no_total: true,
+            interval,
+            begin: self.begin.clone(),
+            end: self.end.clone(),
+            unchecked_dates: self.unchecked_dates,
+            cost: self.value_mode == Some(ValueMode::Cost),
+            market: self.value_mode == Some(ValueMode::Market),
+            queries: self.query.clone(),
+            timeout: self.timeout,
+            ..BalanceOptions::default()
+        }
+    }
+}
+
+/// A series per account (and per commodity, unless one is chosen), ready to
+/// chart
+///
+/// Runs a flat, periodic `hledger balance`. Every series has a point for
+/// every period, zero where the account had nothing. With `commodity` set,
+/// other commodities are converted at each period's end with the journal's
+/// market prices, and left out where no rate is known. A custom interval
+/// that hledger reports as a single column gives no series.
+pub fn get_timeseries(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    spec: &TimeseriesSpec,
+) -> Result<Vec<Series>> {
+    let BalanceReport::Periodic(report) =
+        get_balance(hledger_path, journal_file, &spec.balance_options())?
+    else {
+        return Ok(Vec::new());
+    };
+    let Some(commodity) = &spec.commodity else {
+        return Ok(timeseries(&report, None));
+    };
+
+    let options = PricesOptions {
+        infer_market_prices: true,
+        timeout: spec.timeout,
+        ..PricesOptions::default()
+    };
+    let prices = get_prices(hledger_path, journal_file, &options)?;
+    let tables = period_tables(&report, &prices);
+    Ok(timeseries(&report, Some((commodity, &tables))))
+}
+
+/// A conversion table for each period, with the prices known at its end
+fn period_tables(report: &PeriodicBalance, prices: &[MarketPrice]) -> Vec<ConversionTable> {
+    report
+        .dates
+        .iter()
+        .map(|period| {
+            let last_day = period.end.pred_opt().unwrap_or(period.end);
+            ConversionTable::from_prices(prices.iter().cloned(), Some(last_day))
+        })
+        .collect()
+}
+
+/// The report's rows as series; `conversion` is the target commodity and a
+/// table for each period
+fn timeseries(
+    report: &PeriodicBalance,
+    conversion: Option<(&str, &[ConversionTable])>,
+) -> Vec<Series> {
+    let points = |value: &dyn Fn(usize) -> Decimal| -> Vec<SeriesPoint> {
+        report
+            .dates
+            .iter()
+            .enumerate()
+            .map(|(i, period)| SeriesPoint {
+                date: period.start,
+                value: value(i),
+            })
+            .collect()
+    };
+
+    let mut series = Vec::new();
+    for row in &report.rows {
+        let cell = |i: usize| row.amounts.get(i).map(Vec::as_slice).unwrap_or_default();
+        if let Some((target, tables)) = conversion {
+            series.push(Series {
+                account: row.account.clone(),
+                commodity: target.to_string(),
+                points: points(&|i| quantity_in(cell(i), target, tables.get(i))),
+            });
+            continue;
+        }
+
+        let mut commodities: Vec<&str> = Vec::new();
+        for amount in row.amounts.iter().flatten() {
+            if !commodities.contains(&amount.commodity.as_str()) {
+                commodities.push(&amount.commodity);
+            }
+        }
+        for commodity in commodities {
+            series.push(Series {
+                account: row.account.clone(),
+                commodity: commodity.to_string(),
+                points: points(&|i| quantity_in(cell(i), commodity, None)),
+            });
+        }
+    }
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::{Amount, PeriodDate, PeriodicBalanceRow};
+
+    #[test]
+    fn export_bindings() {
+        TimeseriesSpec::export_all().unwrap();
+        Series::export_all().unwrap();
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn amount(commodity: &str, quantity: &str) -> Amount {
+        Amount {
+            commodity: commodity.to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+        }
+    }
+
+    fn row(account: &str, amounts: Vec<Vec<Amount>>) -> PeriodicBalanceRow {
+        PeriodicBalanceRow {
+            account: account.to_string(),
+            display_name: account.to_string(),
+            indent: 0,
+            amounts,
+            total: None,
+            average: None,
+        }
+    }
+
+    /// January to March 2024, monthly
+    fn report(rows: Vec<PeriodicBalanceRow>) -> PeriodicBalance {
+        let months = ["2024-01-01", "2024-02-01", "2024-03-01", "2024-04-01"];
+        PeriodicBalance {
+            dates: months
+                .windows(2)
+                .map(|pair| PeriodDate {
+                    start: date(pair[0]),
+                    end: date(pair[1]),
+                })
+                .collect(),
+            rows,
+            totals: None,
+        }
+    }
+
+    fn values(series: &Series) -> Vec<String> {
+        series
+            .points
+            .iter()
+            .map(|point| point.value.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_missing_periods_are_zero() {
+        let report = report(vec![
+            row(
+                "expenses:food",
+                vec![vec![amount("$", "10")], vec![], vec![amount("$", "7.50")]],
+            ),
+            row(
+                "expenses:travel",
+                vec![vec![amount("EUR", "200")], vec![amount("$", "40")], vec![]],
+            ),
+        ]);
+        let series = timeseries(&report, None);
+
+        let summary: Vec<_> = series
+            .iter()
+            .map(|s| (s.account.as_str(), s.commodity.as_str(), values(s)))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "expenses:food",
+                    "$",
+                    vec!["10".into(), "0".into(), "7.50".into()]
+                ),
+                (
+                    "expenses:travel",
+                    "EUR",
+                    vec!["200".into(), "0".into(), "0".into()]
+                ),
+                (
+                    "expenses:travel",
+                    "$",
+                    vec!["0".into(), "40".into(), "0".into()]
+                ),
+            ]
+        );
+        let dates: Vec<_> = series[0].points.iter().map(|p| p.date).collect();
+        assert_eq!(
+            dates,
+            [date("2024-01-01"), date("2024-02-01"), date("2024-03-01")]
+        );
+    }
+
+    #[test]
+    fn test_conversion_uses_each_periods_prices() {
+        let price = |day: &str, rate: &str| MarketPrice {
+            date: date(day),
+            commodity: "EUR".to_string(),
+            price: amount("$", rate),
+        };
+        let report = report(vec![row(
+            "assets:savings",
+            vec![
+                vec![amount("EUR", "100")],
+                vec![amount("EUR", "100"), amount("$", "5")],
+                vec![amount("GBP", "10")],
+            ],
+        )]);
+        let tables = period_tables(
+            &report,
+            &[price("2024-01-15", "1.10"), price("2024-02-10", "1.20")],
+        );
+        let series = timeseries(&report, Some(("$", &tables)));
+
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].commodity, "$");
+        // GBP has no rate, so March is left at zero
+        assert_eq!(values(&series[0]), ["110.00", "125.00", "0"]);
+    }
+
+    #[test]
+    fn test_balance_options() {
+        let spec = TimeseriesSpec::new()
+            .query("^assets")
+            .accumulation(Accumulation::Historical)
+            .value_mode(ValueMode::Market);
+        let options = spec.balance_options();
+        assert!(options.historical && !options.cumulative);
+        assert!(options.market && !options.cost);
+        assert_eq!(options.list_mode, Some(ListMode::Flat));
+        assert_eq!(options.interval, ReportInterval::Monthly);
+        assert_eq!(options.queries, ["^assets"]);
+    }
+}
@@ -0,0 +1,264 @@
+//! Two periods of the income statement side by side, e.g. this month
+//! against last month
+//!
+//! Both income statements run at once through
+//! [`run_reports`](crate::run_reports) and their rows are lined up by
+//! account.
+
+use crate::amount::MultiAmount;
+use crate::batch::{run_reports, ReportRequest, ReportResponse};
+use crate::commands::incomestatement::{IncomeStatementOptions, IncomeStatementReport};
+use crate::interval::ReportInterval;
+use crate::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// One account's change in each period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct ComparisonRow {
+    pub account: String,
+    /// The change in the first period; zero if the account wasn't in it
+    pub a: MultiAmount,
+    /// The change in the second period; zero if the account wasn't in it
+    pub b: MultiAmount,
+    /// `b` less `a`
+    pub delta: MultiAmount,
+    /// `delta` as a percentage of `a`, to two decimal places; `None` when
+    /// `a` is zero or either period holds more than one commodity
+    #[ts(type = "string | null")]
+    pub pct_change: Option<Decimal>,
+}
+
+/// Compare the income statement for `period_a` with the one for `period_b`
+///
+/// Periods are hledger period expressions such as `2024-05` or `last
+/// month`. Each report covers its whole period, in change mode; the
+/// interval and date filters in `options` are replaced, the rest (query,
+/// depth, valuation, ...) apply to both. Rows come in the first report's
+/// order, subreport by subreport, with accounts only in the second period
+/// after those of their subreport.
+pub fn compare_periods(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    period_a: &str,
+    period_b: &str,
+    options: &IncomeStatementOptions,
+) -> Result<Vec<ComparisonRow>> {
+    let requests = vec![
+        ReportRequest::IncomeStatement(period_options(options, period_a)),
+        ReportRequest::IncomeStatement(period_options(options, period_b)),
+    ];
+    let mut reports = Vec::with_capacity(2);
+    for result in run_reports(hledger_path, journal_file, requests) {
+        match result? {
+            ReportResponse::IncomeStatement(report) => reports.push(report),
+            _ => unreachable!("each request gets a response of its kind"),
+        }
+    }
+    Ok(align_rows(&reports[0], &reports[1]))
+}
+
+/// `options` narrowed to the change over the whole of `period`
+fn period_options(options: &IncomeStatementOptions, period: &str) -> IncomeStatementOptions {
+    IncomeStatementOptions {
+        interval: ReportInterval::Custom(period.to_string()),
+        change: true,
+        cumulative: false,
+        historical: false,
+        begin: None,
+        end: None,
+        ..options.clone()
+    }
+}
+
+/// The rows of both reports matched by account; an account missing from
+/// one report is zero there
+fn align_rows(a: &IncomeStatementReport, b: &IncomeStatementReport) -> Vec<ComparisonRow> {
+    let mut rows = Vec::new();
+    let subreports = a.subreports.len().max(b.subreports.len());
+    for i in 0..subreports {
+        let mut totals: Vec<(String, MultiAmount, MultiAmount)> = Vec::new();
+        for row in a.subreports.get(i).iter().flat_map(|s| &s.rows) {
+            let total: MultiAmount = row.amounts.iter().flatten().collect();
+            totals.push((row.account.clone(), total, MultiAmount::new()));
+        }
+        for row in b.subreports.get(i).iter().flat_map(|s| &s.rows) {
+            let total: MultiAmount = row.amounts.iter().flatten().collect();
+            match totals
+                .iter_mut()
+                .find(|(account, ..)| *account == row.account)
+            {
+                Some((_, _, b)) => *b = total,
+                None => totals.push((row.account.clone(), MultiAmount::new(), total)),
+            }
+        }
+
+        rows.extend(totals.into_iter().map(|(account, a, b)| ComparisonRow {
+            delta: b.clone() - a.clone(),
+            pct_change: pct_change(&a, &b),
+            account,
+            a,
+            b,
+        }));
+    }
+    rows
+}
+
+/// The change from `a` to `b` in percent of `a`, when both are in the same
+/// single commodity (or `b` is zero) and `a` isn't zero
+fn pct_change(a: &MultiAmount, b: &MultiAmount) -> Option<Decimal> {
+    let mut commodities = a.iter();
+    let (commodity, base) = commodities.next()?;
+    if commodities.next().is_some() || b.iter().any(|(other, _)| other != commodity) {
+        return None;
+    }
+    let change = (b.get(commodity) - base) / base.abs() * Decimal::ONE_HUNDRED;
+    Some(change.round_dp(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::{Amount, PeriodDate, PeriodicBalanceRow};
+    use crate::commands::incomestatement::IncomeStatementSubreport;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn export_bindings() {
+        ComparisonRow::export_all().unwrap();
+    }
+
+    fn amounts(pairs: &[(&str, &str)]) -> Vec<Amount> {
+        pairs
+            .iter()
+            .map(|(commodity, quantity)| Amount {
+                commodity: commodity.to_string(),
+                quantity: quantity.parse().unwrap(),
+                price: None,
+            })
+            .collect()
+    }
+
+    fn multi(pairs: &[(&str, &str)]) -> MultiAmount {
+        amounts(pairs).iter().collect()
+    }
+
+    fn row(account: &str, pairs: &[(&str, &str)]) -> PeriodicBalanceRow {
+        PeriodicBalanceRow {
+            account: account.to_string(),
+            display_name: account.to_string(),
+            indent: 0,
+            amounts: vec![amounts(pairs)],
+            total: None,
+            average: None,
+        }
+    }
+
+    fn report(
+        revenues: Vec<PeriodicBalanceRow>,
+        expenses: Vec<PeriodicBalanceRow>,
+    ) -> IncomeStatementReport {
+        let dates = vec![PeriodDate {
+            start: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+        }];
+        let subreport = |name: &str, rows, increases_total| IncomeStatementSubreport {
+            name: name.to_string(),
+            dates: dates.clone(),
+            rows,
+            totals: None,
+            increases_total,
+        };
+        IncomeStatementReport {
+            title: String::new(),
+            dates: dates.clone(),
+            subreports: vec![
+                subreport("Revenues", revenues, true),
+                subreport("Expenses", expenses, false),
+            ],
+            totals: None,
+        }
+    }
+
+    #[test]
+    fn test_rows_aligned_by_account() {
+        let may = report(
+            vec![row("income:salary", &[("$", "3000")])],
+            vec![
+                row("expenses:food", &[("$", "400")]),
+                row("expenses:rent", &[("$", "1200")]),
+            ],
+        );
+        let june = report(
+            vec![row("income:salary", &[("$", "3000")])],
+            vec![
+                row("expenses:food", &[("$", "500")]),
+                row("expenses:travel", &[("$", "250")]),
+            ],
+        );
+
+        let rows = align_rows(&may, &june);
+        let accounts: Vec<&str> = rows.iter().map(|row| row.account.as_str()).collect();
+        assert_eq!(
+            accounts,
+            [
+                "income:salary",
+                "expenses:food",
+                "expenses:rent",
+                "expenses:travel"
+            ]
+        );
+
+        let food = &rows[1];
+        assert_eq!(food.delta, multi(&[("$", "100")]));
+        assert_eq!(food.pct_change, Some("25".parse().unwrap()));
+
+        // Only in May: gone entirely in June
+        let rent = &rows[2];
+        assert!(rent.b.is_zero());
+        assert_eq!(rent.delta, multi(&[("$", "-1200")]));
+        assert_eq!(rent.pct_change, Some("-100".parse().unwrap()));
+
+        // Only in June: no base to take a percentage of
+        let travel = &rows[3];
+        assert!(travel.a.is_zero());
+        assert_eq!(travel.delta, multi(&[("$", "250")]));
+        assert_eq!(travel.pct_change, None);
+
+        assert_eq!(rows[0].delta, MultiAmount::new());
+        assert_eq!(rows[0].pct_change, Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_pct_change() {
+        let pct = |a: &[(&str, &str)], b: &[(&str, &str)]| pct_change(&multi(a), &multi(b));
+        assert_eq!(
+            pct(&[("$", "300")], &[("$", "400")]),
+            Some("33.33".parse().unwrap())
+        );
+        // Negative bases (revenues in some reports) still read as growth
+        assert_eq!(
+            pct(&[("$", "-200")], &[("$", "-100")]),
+            Some("50".parse().unwrap())
+        );
+        assert_eq!(pct(&[], &[("$", "5")]), None);
+        assert_eq!(pct(&[("$", "5")], &[("EUR", "5")]), None);
+        assert_eq!(pct(&[("$", "5"), ("EUR", "1")], &[("$", "5")]), None);
+    }
+
+    #[test]
+    fn test_period_options() {
+        let options = IncomeStatementOptions::new()
+            .monthly()
+            .historical()
+            .query("^expenses");
+        let options = period_options(&options, "2024-06");
+        assert_eq!(options.interval, ReportInterval::Custom("2024-06".into()));
+        assert!(options.change && !options.historical);
+        assert_eq!(options.queries, ["^expenses"]);
+    }
+}
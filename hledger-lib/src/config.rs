@@ -1,7 +1,1187 @@
-use std::process::Command;
+use crate::binary::{clean_input, resolve_hledger_binary, usable_sidecar};
+use crate::cancel::{check_cancelled, current_token};
+use crate::commands::json::json_error;
+use crate::journals::journal_file_paths;
+use crate::timeout::Timeout;
+use crate::version::check_flag_support;
+use crate::warnings::{collect_warnings, warn};
+use crate::{HLedgerError, Result};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Output, Stdio};
+use std::sync::{PoisonError, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// Get a Command instance for hledger with the specified binary path
+/// How often a running hledger is polled while waiting on a timeout or a
+/// cancel token
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static COMMAND_CONFIG: RwLock<Option<CommandConfig>> = RwLock::new(None);
+
+/// Environment hledger runs in, and how its output is read
+///
+/// A GUI app inherits a sparse environment (on macOS, almost none of the
+/// shell's), so by default hledger gets a fixed UTF-8 locale rather than
+/// whatever the app started with, and `LEDGER_FILE` is dropped when the
+/// command names its journal with `-f`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandConfig {
+    /// Variables set for hledger, overriding inherited ones
+    pub env_overrides: HashMap<String, String>,
+    /// Remove an inherited `LEDGER_FILE` when the command has `-f`
+    pub clear_ledger_file: bool,
+    /// Directory hledger runs in, which relative paths resolve against;
+    /// inherited when `None`
+    pub working_dir: Option<PathBuf>,
+    /// Replace invalid UTF-8 in hledger's output (e.g. Latin-1 text in the
+    /// journal) with U+FFFD and raise a warning (see [`crate::warnings`]),
+    /// instead of failing with `InvalidUtf8`
+    pub lossy_utf8: bool,
+    /// hledger shipped with the app, run when no path is given; `hledger`
+    /// from `PATH` runs instead if it isn't there
+    pub sidecar: Option<PathBuf>,
+}
+
+impl Default for CommandConfig {
+    fn default() -> Self {
+        Self {
+            env_overrides: HashMap::from([("LC_ALL".to_string(), "C.UTF-8".to_string())]),
+            clear_ledger_file: true,
+            working_dir: None,
+            lossy_utf8: false,
+            sidecar: None,
+        }
+    }
+}
+
+// Implementation for builder pattern
+impl CommandConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env_overrides.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn keep_ledger_file(mut self) -> Self {
+        self.clear_ledger_file = false;
+        self
+    }
+
+    pub fn working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    pub fn lossy_utf8(mut self) -> Self {
+        self.lossy_utf8 = true;
+        self
+    }
+
+    pub fn sidecar(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sidecar = Some(path.into());
+        self
+    }
+}
+
+/// Set the environment every later hledger run gets (see [`CommandConfig`])
+pub fn set_command_config(config: CommandConfig) {
+    *COMMAND_CONFIG
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(config);
+}
+
+/// The environment hledger runs get, as last set with [`set_command_config`]
+pub fn command_config() -> CommandConfig {
+    COMMAND_CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Get a Command instance for hledger with the specified binary path, set
+/// up with the current [`command_config`]
 pub fn get_hledger_command(hledger_path: Option<&str>) -> Command {
-    let binary = hledger_path.unwrap_or("hledger");
-    Command::new(binary)
+    get_hledger_command_with(hledger_path, &command_config())
+}
+
+/// Get a Command instance for hledger with the specified binary path, set
+/// up with `config` rather than the current [`command_config`]
+///
+/// An explicit path is resolved with [`resolve_hledger_binary`], so quoted
+/// paths and Windows paths without `.exe` work; `None` runs the config's
+/// sidecar if it's there, else `hledger` from `PATH`.
+///
+/// `clear_ledger_file` is applied when the command runs, once its arguments
+/// are known.
+pub fn get_hledger_command_with(hledger_path: Option<&str>, config: &CommandConfig) -> Command {
+    // An unresolvable path is still tried, so spawning reports the failure
+    let binary = match hledger_path {
+        Some(path) => {
+            resolve_hledger_binary(path).unwrap_or_else(|_| PathBuf::from(clean_input(path)))
+        }
+        None => usable_sidecar(config.sidecar.as_deref())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("hledger")),
+    };
+    let mut cmd = Command::new(binary);
+    cmd.envs(&config.env_overrides);
+    if let Some(dir) = &config.working_dir {
+        cmd.current_dir(dir);
+    }
+    cmd
+}
+
+/// Drop an inherited `LEDGER_FILE` if `config` asks to and the command names
+/// its journal with `-f`; one set explicitly on the command is kept
+fn apply_ledger_file_rule(cmd: &mut Command, config: &CommandConfig) {
+    let names_journal = cmd.get_args().any(|arg| arg == "-f");
+    let set_explicitly = cmd.get_envs().any(|(key, _)| key == "LEDGER_FILE");
+    if config.clear_ledger_file && names_journal && !set_explicitly {
+        cmd.env_remove("LEDGER_FILE");
+    }
+}
+
+/// Add `-f` for each file `journal_file` names: one, or several joined by
+/// [`crate::combined_journal`]
+pub(crate) fn add_journal_args(cmd: &mut Command, journal_file: Option<&str>) {
+    for file in journal_file.into_iter().flat_map(journal_file_paths) {
+        cmd.arg("-f").arg(file);
+    }
+}
+
+/// Append query patterns as positional arguments
+///
+/// A `--` separator goes first so patterns starting with a dash (user search
+/// text like `-salary`, or `--version`) are read as queries, not flags.
+pub(crate) fn add_query_args(cmd: &mut Command, queries: &[String]) {
+    if !queries.is_empty() {
+        cmd.arg("--").args(queries);
+    }
+}
+
+/// Fail with the matching error if hledger exited unsuccessfully
+///
+/// After a successful run, anything hledger printed to stderr (e.g. notices
+/// about deprecated flags) is raised as warnings instead.
+pub(crate) fn check_success(cmd: &Command, status: ExitStatus, stderr: &[u8]) -> Result<()> {
+    if status.success() {
+        for warning in stderr_warnings(&String::from_utf8_lossy(stderr)) {
+            warn(warning);
+        }
+        return Ok(());
+    }
+    Err(HLedgerError::from_failure(
+        command_line(cmd),
+        status.code().unwrap_or(-1),
+        String::from_utf8_lossy(stderr).to_string(),
+    ))
+}
+
+/// Split stderr into warnings: a line starting with whitespace continues the
+/// warning before it, blank lines are dropped
+fn stderr_warnings(stderr: &str) -> Vec<String> {
+    let mut warnings: Vec<String> = Vec::new();
+    for line in stderr.lines().map(str::trim_end) {
+        if line.is_empty() {
+            continue;
+        }
+        match warnings.last_mut() {
+            Some(warning) if line.starts_with(char::is_whitespace) => {
+                warning.push('\n');
+                warning.push_str(line);
+            }
+            _ => warnings.push(line.to_string()),
+        }
+    }
+    warnings
+}
+
+/// Run a command that prints a JSON report, deserializing it straight from
+/// the child's stdout
+///
+/// `T` is a `serde_json::Value` for the untouched JSON, or one of the
+/// `commands::json` types to skip building one.
+pub(crate) fn run_json_command<T: DeserializeOwned + Send + 'static>(
+    cmd: &mut Command,
+    timeout: Option<Timeout>,
+) -> Result<T> {
+    run_json_command_piped(cmd, None, timeout)
+}
+
+/// [`run_json_command`], writing `stdin` to the child, e.g. for `-f -`
+pub(crate) fn run_json_command_piped<T: DeserializeOwned + Send + 'static>(
+    cmd: &mut Command,
+    stdin: Option<&str>,
+    timeout: Option<Timeout>,
+) -> Result<T> {
+    let output = run_command_piped(cmd, stdin, timeout, |stdout| {
+        let json = skip_json_preamble(stdout).map_err(serde_json::Error::io)?;
+        serde_json::from_reader(io::BufReader::new(json))
+    })?;
+    check_success(cmd, output.status, &output.stderr)?;
+    output.stdout.map_err(json_error)
+}
+
+/// How far into hledger's stdout [`skip_json_preamble`] looks for the JSON
+const JSON_SEARCH_LIMIT: u64 = 64 * 1024;
+
+/// `stdout` from the start of its JSON document, skipping lines printed
+/// before it (e.g. by a wrapper script, or notices from `hledger.conf`)
+///
+/// The document starts at the first line, within [`JSON_SEARCH_LIMIT`]
+/// bytes, that begins with `{` or `[` and parses as JSON from there to as
+/// far as was read. Skipped text is raised as a warning. Without such a
+/// line nothing is skipped, so the parser reports what's wrong.
+pub(crate) fn skip_json_preamble<R: Read>(mut stdout: R) -> io::Result<impl Read> {
+    let mut head = Vec::new();
+    (&mut stdout)
+        .take(JSON_SEARCH_LIMIT)
+        .read_to_end(&mut head)?;
+
+    let start = json_start(&head).unwrap_or(0);
+    if start > 0 {
+        let skipped = String::from_utf8_lossy(&head[..start]);
+        let skipped = skipped.trim();
+        let preview = match skipped.char_indices().nth(200) {
+            Some((end, _)) => format!("{}...", &skipped[..end]),
+            None => skipped.to_string(),
+        };
+        warn(format!("Ignored output before hledger's JSON: {}", preview));
+    }
+
+    let mut head = io::Cursor::new(head);
+    head.set_position(start as u64);
+    Ok(head.chain(stdout))
+}
+
+/// Where the JSON document in `head` starts, if any line starts one
+fn json_start(head: &[u8]) -> Option<usize> {
+    let mut line_start = 0;
+    let mut candidates = Vec::new();
+    for (i, &byte) in head.iter().enumerate() {
+        match byte {
+            b'\n' => line_start = i + 1,
+            b'{' | b'[' if head[line_start..i].iter().all(u8::is_ascii_whitespace) => {
+                candidates.push(i)
+            }
+            _ => {}
+        }
+    }
+    candidates
+        .into_iter()
+        .find(|&start| parses_to_end(&head[start..]))
+}
+
+/// Whether `text` is one JSON value, possibly cut off, and nothing else
+fn parses_to_end(text: &[u8]) -> bool {
+    let mut values = serde_json::Deserializer::from_slice(text).into_iter::<IgnoredAny>();
+    match values.next() {
+        Some(Ok(_)) => text[values.byte_offset()..]
+            .iter()
+            .all(u8::is_ascii_whitespace),
+        Some(Err(e)) => e.is_eof(),
+        None => false,
+    }
+}
+
+/// Output of a finished hledger run whose stdout was consumed by a reader
+pub(crate) struct CommandOutput<T> {
+    pub status: ExitStatus,
+    pub stdout: T,
+    pub stderr: Vec<u8>,
+}
+
+/// Run a prepared hledger command and collect its output
+pub(crate) fn run_command(cmd: &mut Command, timeout: Option<Timeout>) -> Result<Output> {
+    let output = run_command_with(cmd, timeout, |stdout| {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    })?;
+    Ok(Output {
+        status: output.status,
+        stdout: output.stdout?,
+        stderr: output.stderr,
+    })
+}
+
+/// A child's stdout that counts the bytes read from it, optionally
+/// repairing invalid UTF-8 on the way
+pub(crate) struct CountingStdout {
+    inner: StdoutReader,
+    bytes: u64,
+}
+
+enum StdoutReader {
+    Strict(ChildStdout),
+    Lossy(LossyUtf8<ChildStdout>),
+}
+
+impl CountingStdout {
+    /// Invalid UTF-8 sequences replaced so far
+    fn replaced(&self) -> usize {
+        match &self.inner {
+            StdoutReader::Strict(_) => 0,
+            StdoutReader::Lossy(lossy) => lossy.replaced,
+        }
+    }
+}
+
+impl Read for CountingStdout {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match &mut self.inner {
+            StdoutReader::Strict(stdout) => stdout.read(buf)?,
+            StdoutReader::Lossy(stdout) => stdout.read(buf)?,
+        };
+        self.bytes += n as u64;
+        Ok(n)
+    }
+}
+
+/// Reads `inner`, replacing each invalid UTF-8 sequence with U+FFFD as
+/// `String::from_utf8_lossy` would, without buffering the whole stream
+struct LossyUtf8<R> {
+    inner: R,
+    /// Bytes read but not yet decoded: the start of a character split
+    /// across reads
+    pending: Vec<u8>,
+    /// Decoded bytes not yet handed out, from `pos`
+    decoded: Vec<u8>,
+    pos: usize,
+    eof: bool,
+    replaced: usize,
+}
+
+impl<R: Read> LossyUtf8<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            decoded: Vec::new(),
+            pos: 0,
+            eof: false,
+            replaced: 0,
+        }
+    }
+
+    /// Read another chunk and decode as much of it as is complete
+    fn fill(&mut self) -> io::Result<()> {
+        self.decoded.clear();
+        self.pos = 0;
+        if !self.eof {
+            let mut chunk = [0; 8192];
+            let n = self.inner.read(&mut chunk)?;
+            self.eof = n == 0;
+            self.pending.extend_from_slice(&chunk[..n]);
+        }
+
+        let mut rest = &self.pending[..];
+        while !rest.is_empty() {
+            let e = match std::str::from_utf8(rest) {
+                Ok(_) => {
+                    self.decoded.extend_from_slice(rest);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => e,
+            };
+            let (valid, invalid) = rest.split_at(e.valid_up_to());
+            self.decoded.extend_from_slice(valid);
+            match e.error_len() {
+                Some(len) => rest = &invalid[len..],
+                // A character cut off by the end of this read may complete
+                // in the next one
+                None if !self.eof => {
+                    rest = invalid;
+                    break;
+                }
+                None => rest = &[],
+            }
+            self.decoded
+                .extend_from_slice(char::REPLACEMENT_CHARACTER.to_string().as_bytes());
+            self.replaced += 1;
+        }
+        let undecoded = rest.len();
+        self.pending.drain(..self.pending.len() - undecoded);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for LossyUtf8<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.decoded.len() {
+            if self.eof && self.pending.is_empty() {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let n = buf.len().min(self.decoded.len() - self.pos);
+        buf[..n].copy_from_slice(&self.decoded[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Run a prepared hledger command, handing its stdout to `read_stdout`
+///
+/// The reader runs on a helper thread as the child writes, so large reports
+/// can be parsed straight off the pipe instead of being buffered first.
+/// Whatever the reader leaves unread is discarded, and stderr is drained on
+/// its own thread, so the child can never block on a full pipe.
+///
+/// Flags the installed hledger is too old for fail early with
+/// `HLedgerError::UnsupportedFeature` (see [`crate::version`]).
+///
+/// With a timeout the child is polled until it exits or the deadline passes,
+/// in which case it is killed and `HLedgerError::Timeout` is returned. Under
+/// a cancel token (see [`crate::cancel`]) it is polled the same way and
+/// killed with `HLedgerError::Cancelled` once the token is cancelled. On any
+/// early return the child is killed and reaped rather than left running.
+///
+/// With the `tracing` feature each run is wrapped in a debug-level `hledger`
+/// span recording the program, arguments, duration, exit code and stdout
+/// size. A failed run also logs its stderr at warn level, minus the journal
+/// excerpt lines; report output itself is never logged.
+pub(crate) fn run_command_with<T, F>(
+    cmd: &mut Command,
+    timeout: Option<Timeout>,
+    read_stdout: F,
+) -> Result<CommandOutput<T>>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut CountingStdout) -> T + Send + 'static,
+{
+    run_command_piped(cmd, None, timeout, read_stdout)
+}
+
+/// [`run_command_with`], writing `stdin` to the child and then closing it
+///
+/// The write happens on its own thread while stdout and stderr are read, so
+/// a large journal can't deadlock against a child blocked on a full output
+/// pipe. Without `stdin` the child's stdin is null. A child that exits
+/// without reading everything (e.g. on a parse error) ends the write early;
+/// its exit status reports the failure.
+pub(crate) fn run_command_piped<T, F>(
+    cmd: &mut Command,
+    stdin: Option<&str>,
+    timeout: Option<Timeout>,
+    read_stdout: F,
+) -> Result<CommandOutput<T>>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut CountingStdout) -> T + Send + 'static,
+{
+    let timeout = timeout.map(Duration::from);
+    let token = current_token();
+    check_cancelled(token.as_ref())?;
+    check_flag_support(cmd)?;
+    let config = command_config();
+    apply_ledger_file_rule(cmd, &config);
+
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!(
+        "hledger",
+        program = %cmd.get_program().to_string_lossy(),
+        args = %command_args(cmd),
+        duration_ms = tracing::field::Empty,
+        exit_code = tracing::field::Empty,
+        stdout_bytes = tracing::field::Empty,
+    );
+    #[cfg(feature = "tracing")]
+    let _entered = span.enter();
+
+    let start = Instant::now();
+    let mut child = ChildGuard::spawn(cmd, stdin.is_some())?;
+
+    let stdin = stdin.map(|text| {
+        let mut pipe = child.child.stdin.take().expect("stdin is piped");
+        let text = text.as_bytes().to_vec();
+        // Dropping the pipe at the end of the thread closes the child's stdin
+        thread::spawn(move || {
+            let _ = pipe.write_all(&text);
+        })
+    });
+
+    let stdout = child.child.stdout.take().expect("stdout is piped");
+    let stdout = thread::spawn(move || {
+        let inner = if config.lossy_utf8 {
+            StdoutReader::Lossy(LossyUtf8::new(stdout))
+        } else {
+            StdoutReader::Strict(stdout)
+        };
+        let mut stdout = CountingStdout { inner, bytes: 0 };
+        // Warnings are per thread, so the reader's are raised again below
+        let (value, warnings) = collect_warnings(|| read_stdout(&mut stdout));
+        let _ = io::copy(&mut stdout, &mut io::sink());
+        (value, warnings, stdout.bytes, stdout.replaced())
+    });
+    let mut stderr = child.child.stderr.take().expect("stderr is piped");
+    let stderr = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stderr.read_to_end(&mut buf).map(|_| buf)
+    });
+
+    let status = match (timeout, &token) {
+        (None, None) => child.wait()?,
+        _ => loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+                #[cfg(feature = "tracing")]
+                {
+                    span.record("duration_ms", start.elapsed().as_millis() as u64);
+                    tracing::warn!("hledger timed out");
+                }
+                // Dropping the guard kills and reaps the child
+                return Err(HLedgerError::Timeout {
+                    elapsed: start.elapsed(),
+                });
+            }
+            check_cancelled(token.as_ref()).inspect_err(|_| {
+                #[cfg(feature = "tracing")]
+                {
+                    span.record("duration_ms", start.elapsed().as_millis() as u64);
+                    tracing::debug!("hledger cancelled");
+                }
+            })?;
+            let remaining = timeout.map_or(POLL_INTERVAL, |timeout| {
+                timeout.saturating_sub(start.elapsed())
+            });
+            thread::sleep(POLL_INTERVAL.min(remaining));
+        },
+    };
+
+    let (stdout, reader_warnings, stdout_bytes, replaced) = join_reader(stdout)?;
+    reader_warnings.into_iter().for_each(warn);
+    if replaced > 0 {
+        warn(format!(
+            "`{}` printed {} invalid UTF-8 sequence{}, shown as {}",
+            command_line(cmd),
+            replaced,
+            if replaced == 1 { "" } else { "s" },
+            char::REPLACEMENT_CHARACTER
+        ));
+    }
+    let stderr = join_reader(stderr)??;
+    if let Some(stdin) = stdin {
+        join_reader(stdin)?;
+    }
+
+    #[cfg(feature = "tracing")]
+    {
+        span.record("duration_ms", start.elapsed().as_millis() as u64);
+        span.record("stdout_bytes", stdout_bytes);
+        if let Some(code) = status.code() {
+            span.record("exit_code", code);
+        }
+        if status.success() {
+            tracing::debug!("hledger finished");
+        } else {
+            let stderr = crate::error::without_excerpts(&String::from_utf8_lossy(&stderr));
+            tracing::warn!(stderr = %stderr, "hledger failed");
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = stdout_bytes;
+
+    Ok(CommandOutput {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Owns a spawned hledger process and makes sure it never outlives the run
+///
+/// If the guard is dropped before the child has been waited on (a timeout, an
+/// error, or a panic unwinding through the caller) the child is killed and
+/// reaped, so it neither keeps running nor lingers as a zombie.
+struct ChildGuard {
+    child: Child,
+    reaped: bool,
+}
+
+impl ChildGuard {
+    fn spawn(cmd: &mut Command, piped_stdin: bool) -> Result<Self> {
+        let child = cmd
+            .stdin(if piped_stdin {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error(e, cmd))?;
+        Ok(Self {
+            child,
+            reaped: false,
+        })
+    }
+
+    fn wait(&mut self) -> io::Result<ExitStatus> {
+        let status = self.child.wait()?;
+        self.reaped = true;
+        Ok(status)
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        let status = self.child.try_wait()?;
+        self.reaped = status.is_some();
+        Ok(status)
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if !self.reaped {
+            // Fails only if the child already exited, which wait handles
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+fn spawn_error(e: io::Error, cmd: &Command) -> HLedgerError {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    match e.kind() {
+        io::ErrorKind::NotFound => HLedgerError::HLedgerNotFound { attempted: program },
+        io::ErrorKind::PermissionDenied => HLedgerError::HLedgerNotExecutable { path: program },
+        _ => HLedgerError::Io(e),
+    }
+}
+
+fn join_reader<T>(handle: thread::JoinHandle<T>) -> Result<T> {
+    handle
+        .join()
+        .map_err(|_| HLedgerError::ParseError("Output reader thread panicked".to_string()))
+}
+
+/// Render a Command as a shell-like command line for error messages and logs
+///
+/// Arguments containing whitespace or quotes are single-quoted so the result
+/// can be pasted back into a shell.
+pub fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| quote_arg(&part.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The arguments of a Command, quoted as in [`command_line`]
+#[cfg(feature = "tracing")]
+fn command_args(cmd: &Command) -> String {
+    cmd.get_args()
+        .map(|arg| quote_arg(&arg.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '\'' | '"' | '\\' | '$' | '`'))
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn env_of<'a>(cmd: &'a Command, key: &str) -> Option<Option<&'a OsStr>> {
+        cmd.get_envs().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn test_get_hledger_command_with_applies_config() {
+        let cmd = get_hledger_command_with(None, &CommandConfig::default());
+        assert_eq!(env_of(&cmd, "LC_ALL"), Some(Some(OsStr::new("C.UTF-8"))));
+        assert_eq!(cmd.get_current_dir(), None);
+
+        let config = CommandConfig::new()
+            .env("NO_COLOR", "1")
+            .env("LC_ALL", "de_DE.UTF-8")
+            .working_dir("/tmp");
+        let cmd = get_hledger_command_with(Some("/opt/hledger"), &config);
+        assert_eq!(cmd.get_program(), "/opt/hledger");
+        assert_eq!(env_of(&cmd, "NO_COLOR"), Some(Some(OsStr::new("1"))));
+        assert_eq!(
+            env_of(&cmd, "LC_ALL"),
+            Some(Some(OsStr::new("de_DE.UTF-8")))
+        );
+        assert_eq!(cmd.get_current_dir(), Some(Path::new("/tmp")));
+    }
+
+    #[test]
+    fn test_get_hledger_command_with_sidecar() {
+        // Any executable stands in for the bundled hledger
+        let sidecar = std::env::current_exe().unwrap();
+        let config = CommandConfig::new().sidecar(&sidecar);
+        assert_eq!(
+            get_hledger_command_with(None, &config).get_program(),
+            sidecar
+        );
+        assert_eq!(
+            get_hledger_command_with(Some("/opt/hledger"), &config).get_program(),
+            "/opt/hledger"
+        );
+
+        let missing = CommandConfig::new().sidecar("/nonexistent/hledger");
+        assert_eq!(
+            get_hledger_command_with(None, &missing).get_program(),
+            "hledger"
+        );
+    }
+
+    #[test]
+    fn test_command_config_survives_poisoning() {
+        // A thread panicking while it holds the lock must not break every
+        // later hledger run
+        let _ = thread::spawn(|| {
+            let _guard = COMMAND_CONFIG.write().unwrap();
+            panic!("poison the command config");
+        })
+        .join();
+        assert!(COMMAND_CONFIG.is_poisoned());
+
+        let config = command_config();
+        set_command_config(config.clone());
+        assert_eq!(command_config().lossy_utf8, config.lossy_utf8);
+    }
+
+    #[test]
+    fn test_apply_ledger_file_rule() {
+        let config = CommandConfig::default();
+
+        // Removed only when the journal is named with -f
+        let mut cmd = get_hledger_command_with(None, &config);
+        cmd.args(["-f", "main.journal", "print"]);
+        apply_ledger_file_rule(&mut cmd, &config);
+        assert_eq!(env_of(&cmd, "LEDGER_FILE"), Some(None));
+
+        let mut cmd = get_hledger_command_with(None, &config);
+        cmd.arg("print");
+        apply_ledger_file_rule(&mut cmd, &config);
+        assert_eq!(env_of(&cmd, "LEDGER_FILE"), None);
+
+        // An explicit override and keep_ledger_file both win
+        let explicit = CommandConfig::new().env("LEDGER_FILE", "other.journal");
+        let mut cmd = get_hledger_command_with(None, &explicit);
+        cmd.args(["-f", "main.journal", "print"]);
+        apply_ledger_file_rule(&mut cmd, &explicit);
+        assert_eq!(
+            env_of(&cmd, "LEDGER_FILE"),
+            Some(Some(OsStr::new("other.journal")))
+        );
+
+        let keep = CommandConfig::new().keep_ledger_file();
+        let mut cmd = get_hledger_command_with(None, &keep);
+        cmd.args(["-f", "main.journal", "print"]);
+        apply_ledger_file_rule(&mut cmd, &keep);
+        assert_eq!(env_of(&cmd, "LEDGER_FILE"), None);
+    }
+
+    /// A reader handing out at most `chunk` bytes per read
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn decode_lossy(data: &[u8], chunk: usize) -> (String, usize) {
+        let mut reader = LossyUtf8::new(Chunked { data, chunk });
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        (text, reader.replaced)
+    }
+
+    #[test]
+    fn test_lossy_utf8_matches_from_utf8_lossy() {
+        let samples: [&[u8]; 5] = [
+            "Café €5 🍕".as_bytes(),
+            b"Caf\xe9 au lait",
+            b"\xff\xfe\xfd",
+            b"ok \xe2\x82",
+            b"",
+        ];
+        for data in samples {
+            // One byte at a time splits every multi-byte character
+            for chunk in [1, 2, 3, 8192] {
+                let (text, _) = decode_lossy(data, chunk);
+                assert_eq!(text, String::from_utf8_lossy(data), "chunk {}", chunk);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lossy_utf8_counts_replacements() {
+        assert_eq!(decode_lossy("Café".as_bytes(), 1).1, 0);
+        assert_eq!(decode_lossy(b"Caf\xe9 cr\xe8me", 1).1, 2);
+        // A character cut off by the end of the stream is replaced too
+        assert_eq!(
+            decode_lossy(b"ok \xe2\x82", 1),
+            ("ok \u{FFFD}".to_string(), 1)
+        );
+    }
+
+    #[test]
+    fn test_stderr_warnings() {
+        assert!(stderr_warnings("").is_empty());
+        assert!(stderr_warnings("\n  \n").is_empty());
+        assert_eq!(
+            stderr_warnings(
+                "Warning: --flat is deprecated\n\nbalance assertion noted\n  at main.journal:4\n"
+            ),
+            vec![
+                "Warning: --flat is deprecated",
+                "balance assertion noted\n  at main.journal:4"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_start() {
+        assert_eq!(json_start(b"[]"), Some(0));
+        assert_eq!(json_start(b"  {\"a\": 1}\n"), Some(2));
+        assert_eq!(json_start(b"using ~/.hledger.conf\n[1, 2]\n"), Some(22));
+        // Bracketed text that isn't JSON, and JSON that isn't at a line start
+        assert_eq!(
+            json_start(b"[info] reading config\nsee [1]\n  [[\"assets\""),
+            Some(32)
+        );
+        // Trailing text means the document hasn't started yet
+        assert_eq!(json_start(b"[1] of 2\n{}"), Some(9));
+        assert_eq!(json_start(b"no json here\n"), None);
+        assert_eq!(json_start(b""), None);
+    }
+
+    #[test]
+    fn test_skip_json_preamble() {
+        let read = |stdout: &[u8]| {
+            let mut text = String::new();
+            skip_json_preamble(stdout)
+                .unwrap()
+                .read_to_string(&mut text)
+                .unwrap();
+            text
+        };
+
+        let (text, warnings) = collect_warnings(|| read(b"[\"assets\"]\n"));
+        assert_eq!(text, "[\"assets\"]\n");
+        assert!(warnings.is_empty());
+
+        let (text, warnings) = collect_warnings(|| read(b"hledger-shim: 1.2\n\n{\"a\": []}"));
+        assert_eq!(text, "{\"a\": []}");
+        assert_eq!(
+            warnings,
+            vec!["Ignored output before hledger's JSON: hledger-shim: 1.2"]
+        );
+
+        // JSON past the search limit isn't found, so nothing is skipped
+        let long = format!("{}\n[1]", "x".repeat(JSON_SEARCH_LIMIT as usize));
+        assert_eq!(read(long.as_bytes()), long);
+        assert_eq!(read(b"no json"), "no json");
+    }
+
+    #[test]
+    fn test_add_query_args_separates_queries() {
+        let mut cmd = get_hledger_command(None);
+        cmd.arg("print");
+        add_query_args(&mut cmd, &[]);
+        assert_eq!(command_line(&cmd), "hledger print");
+
+        add_query_args(&mut cmd, &["-salary".to_string(), "--version".to_string()]);
+        assert_eq!(command_line(&cmd), "hledger print -- -salary --version");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_times_out() {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("5");
+        let start = Instant::now();
+        match run_command(&mut cmd, Some(Timeout(Duration::from_millis(100)))) {
+            Err(HLedgerError::Timeout { elapsed }) => {
+                assert!(elapsed >= Duration::from_millis(100));
+                assert!(start.elapsed() < Duration::from_secs(4));
+            }
+            other => panic!("Expected Timeout, got {:?}", other),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_within_timeout_collects_output() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo out; echo err >&2; exit 3");
+        let output = run_command(&mut cmd, Some(Timeout(Duration::from_secs(10)))).unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_with_discards_unread_stdout() {
+        // Far more than a pipe buffer, so the child would block if the
+        // remainder weren't drained
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("head -c 1000000 /dev/zero");
+        let output = run_command_with(&mut cmd, Some(Timeout(Duration::from_secs(10))), |stdout| {
+            let mut first = [0u8; 16];
+            stdout.read_exact(&mut first).map(|_| first)
+        })
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout.unwrap(), [0u8; 16]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_piped_echoes_large_input() {
+        // Far more than a pipe buffer in both directions; writing all of
+        // stdin before reading stdout would deadlock
+        let text = "2024-01-01 x\n    a  $1\n    b\n".repeat(40_000);
+        let mut cmd = Command::new("cat");
+        let output = run_command_piped(
+            &mut cmd,
+            Some(&text),
+            Some(Timeout(Duration::from_secs(10))),
+            |stdout| {
+                let mut buf = String::new();
+                stdout.read_to_string(&mut buf).map(|_| buf)
+            },
+        )
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout.unwrap(), text);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_piped_child_ignoring_stdin() {
+        let text = "x".repeat(1_000_000);
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo done; exit 1");
+        let output = run_command_piped(&mut cmd, Some(&text), None, |stdout| {
+            let mut buf = String::new();
+            stdout.read_to_string(&mut buf).map(|_| buf)
+        })
+        .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(output.stdout.unwrap(), "done\n");
+    }
+
+    /// Whether a process still exists, including as an unreaped zombie
+    #[cfg(target_os = "linux")]
+    fn process_exists(pid: u32) -> bool {
+        std::path::Path::new(&format!("/proc/{}", pid)).exists()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_dropped_child_guard_kills_and_reaps() {
+        // Dropping the guard mid-run is what a caller abandoning the report does
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        let guard = ChildGuard::spawn(&mut cmd, false).unwrap();
+        let pid = guard.child.id();
+        assert!(process_exists(pid));
+
+        drop(guard);
+        assert!(!process_exists(pid));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_timed_out_child_is_reaped() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo $$; exec sleep 30");
+        let result = run_command_with(
+            &mut cmd,
+            Some(Timeout(Duration::from_millis(200))),
+            move |stdout| {
+                let mut line = String::new();
+                io::BufRead::read_line(&mut io::BufReader::new(stdout), &mut line).unwrap();
+                tx.send(line.trim().parse::<u32>().unwrap()).unwrap();
+            },
+        );
+        assert!(matches!(result, Err(HLedgerError::Timeout { .. })));
+
+        let pid = rx.recv().unwrap();
+        assert!(!process_exists(pid));
+    }
+
+    #[test]
+    fn test_run_command_missing_binary() {
+        let mut cmd = get_hledger_command(Some("/nonexistent/hledger"));
+        for timeout in [None, Some(Timeout(Duration::from_secs(1)))] {
+            match run_command(&mut cmd, timeout) {
+                Err(HLedgerError::HLedgerNotFound { attempted }) => {
+                    assert_eq!(attempted, "/nonexistent/hledger");
+                }
+                other => panic!("Expected HLedgerNotFound, got {:?}", other),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_non_executable_binary() {
+        let path = std::env::temp_dir().join(format!("noexec-hledger-{}", std::process::id()));
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+
+        let mut cmd = get_hledger_command(path.to_str());
+        let result = run_command(&mut cmd, None);
+        std::fs::remove_file(&path).ok();
+
+        match result {
+            Err(HLedgerError::HLedgerNotExecutable { path: reported }) => {
+                assert_eq!(reported, path.to_string_lossy());
+            }
+            other => panic!("Expected HLedgerNotExecutable, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    type Fields = Vec<(String, String)>;
+
+    /// Collects span fields and events, for checking what gets traced
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct Recorder {
+        /// (field, value) pairs given to or recorded on any span
+        span_fields: std::sync::Arc<std::sync::Mutex<Fields>>,
+        /// (level, fields) of each event
+        events: std::sync::Arc<std::sync::Mutex<Vec<(tracing::Level, Fields)>>>,
+    }
+
+    #[cfg(feature = "tracing")]
+    struct FieldVisitor<'a>(&'a mut Fields);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut fields = self.span_fields.lock().unwrap();
+            // Declared fields, recorded or not
+            for field in span.metadata().fields() {
+                fields.push((field.name().to_string(), String::new()));
+            }
+            span.record(&mut FieldVisitor(&mut fields));
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            values.record(&mut FieldVisitor(&mut self.span_fields.lock().unwrap()));
+        }
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = Vec::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            let level = *event.metadata().level();
+            self.events.lock().unwrap().push((level, fields));
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(all(unix, feature = "tracing"))]
+    #[test]
+    fn test_run_command_traces_span_fields() {
+        let recorder = Recorder::default();
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg("printf hello; echo '12 | 2024-01-05 secret payee' >&2; echo 'balance assertion failed' >&2; exit 2");
+        let output = tracing::subscriber::with_default(recorder.clone(), || {
+            run_command(&mut cmd, None).unwrap()
+        });
+        assert_eq!(output.status.code(), Some(2));
+
+        let fields = recorder.span_fields.lock().unwrap();
+        let value = |name: &str| {
+            fields
+                .iter()
+                .rev()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone())
+        };
+        assert_eq!(value("program").as_deref(), Some("sh"));
+        assert!(value("args").unwrap().starts_with("-c "));
+        assert!(value("duration_ms").is_some_and(|ms| ms.parse::<u64>().is_ok()));
+        assert_eq!(value("exit_code").as_deref(), Some("2"));
+        assert_eq!(value("stdout_bytes").as_deref(), Some("5"));
+
+        let events = recorder.events.lock().unwrap();
+        let (level, fields) = events.last().unwrap();
+        assert_eq!(*level, tracing::Level::WARN);
+        let stderr = &fields.iter().find(|(name, _)| name == "stderr").unwrap().1;
+        assert!(stderr.contains("balance assertion failed"));
+        assert!(!stderr.contains("secret payee"));
+    }
+
+    #[test]
+    fn test_command_line_plain_args() {
+        let mut cmd = get_hledger_command(None);
+        cmd.arg("-f")
+            .arg("main.journal")
+            .arg("bal")
+            .arg("--output-format=json");
+        assert_eq!(
+            command_line(&cmd),
+            "hledger -f main.journal bal --output-format=json"
+        );
+    }
+
+    #[test]
+    fn test_command_line_quotes_args() {
+        let mut cmd = get_hledger_command(Some("/opt/hledger/bin/hledger"));
+        cmd.arg("-f")
+            .arg("My Documents/main.journal")
+            .arg("desc:Joe's Diner")
+            .arg("");
+        assert_eq!(
+            command_line(&cmd),
+            "/opt/hledger/bin/hledger -f 'My Documents/main.journal' 'desc:Joe'\\''s Diner' ''"
+        );
+    }
 }
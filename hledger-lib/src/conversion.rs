@@ -0,0 +1,231 @@
+use crate::commands::balance::Amount;
+use crate::commands::prices::{get_prices, MarketPrice, PricesOptions};
+use crate::Result;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Exchange rates between commodities, for converting amounts after the fact
+///
+/// For each pair of commodities only the most recent price on or before the
+/// as-of date is kept; of several prices on the same day the last one wins,
+/// as in hledger. A conversion uses the direct rate, else the inverse of the
+/// reverse rate, else a chain through one other commodity (e.g. GOOG to EUR
+/// via USD).
+#[derive(Debug, Clone, Default)]
+pub struct ConversionTable {
+    rates: BTreeMap<(String, String), (NaiveDate, Decimal)>,
+}
+
+impl ConversionTable {
+    /// Build a table from the journal's market prices, including those
+    /// inferred from transaction costs
+    pub fn load(
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        as_of: Option<NaiveDate>,
+    ) -> Result<Self> {
+        let options = PricesOptions::new().infer_market_prices();
+        let prices = get_prices(hledger_path, journal_file, &options)?;
+        Ok(Self::from_prices(prices, as_of))
+    }
+
+    /// Build a table from prices already fetched; prices after `as_of` are
+    /// ignored
+    pub fn from_prices(
+        prices: impl IntoIterator<Item = MarketPrice>,
+        as_of: Option<NaiveDate>,
+    ) -> Self {
+        let mut rates: BTreeMap<(String, String), (NaiveDate, Decimal)> = BTreeMap::new();
+        for price in prices {
+            if as_of.is_some_and(|as_of| price.date > as_of)
+                || price.commodity == price.price.commodity
+            {
+                continue;
+            }
+            let key = (price.commodity, price.price.commodity);
+            match rates.get(&key) {
+                Some((date, _)) if *date > price.date => {}
+                _ => {
+                    rates.insert(key, (price.date, price.price.quantity));
+                }
+            }
+        }
+        Self { rates }
+    }
+
+    /// Convert an amount to the target commodity, or `None` if no rate
+    /// connects them. The result carries no cost.
+    pub fn convert(&self, amount: &Amount, target: &str) -> Option<Amount> {
+        let rate = self.rate(&amount.commodity, target)?;
+        Some(Amount {
+            commodity: target.to_string(),
+            quantity: amount.quantity.checked_mul(rate)?,
+            price: None,
+        })
+    }
+
+    /// Value of one unit of `from` in `to`
+    pub fn rate(&self, from: &str, to: &str) -> Option<Decimal> {
+        if from == to {
+            return Some(Decimal::ONE);
+        }
+        self.single_rate(from, to).or_else(|| {
+            self.commodities()
+                .into_iter()
+                .filter(|via| *via != from && *via != to)
+                .find_map(|via| {
+                    self.single_rate(from, via)?
+                        .checked_mul(self.single_rate(via, to)?)
+                })
+        })
+    }
+
+    /// Direct rate, or the inverse of the reverse rate
+    fn single_rate(&self, from: &str, to: &str) -> Option<Decimal> {
+        let key = |a: &str, b: &str| (a.to_string(), b.to_string());
+        if let Some((_, rate)) = self.rates.get(&key(from, to)) {
+            return Some(*rate);
+        }
+        let (_, reverse) = self.rates.get(&key(to, from))?;
+        Decimal::ONE.checked_div(*reverse)
+    }
+
+    fn commodities(&self) -> BTreeSet<&str> {
+        self.rates
+            .keys()
+            .flat_map(|(from, to)| [from.as_str(), to.as_str()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn amount(commodity: &str, quantity: &str) -> Amount {
+        Amount {
+            commodity: commodity.to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+        }
+    }
+
+    fn price(day: &str, commodity: &str, quantity: &str, unit: &str) -> MarketPrice {
+        MarketPrice {
+            date: date(day),
+            commodity: commodity.to_string(),
+            price: amount(unit, quantity),
+        }
+    }
+
+    fn sample_table(as_of: Option<&str>) -> ConversionTable {
+        ConversionTable::from_prices(
+            vec![
+                price("2024-01-01", "GOOG", "100", "USD"),
+                price("2024-02-01", "GOOG", "120", "USD"),
+                price("2024-03-01", "GOOG", "125", "USD"),
+                price("2024-01-01", "USD", "0.90", "EUR"),
+                price("2024-02-15", "USD", "0.80", "EUR"),
+            ],
+            as_of.map(date),
+        )
+    }
+
+    #[test]
+    fn test_direct_rate_uses_latest_price() {
+        let table = sample_table(None);
+        assert_eq!(
+            table.convert(&amount("GOOG", "2"), "USD"),
+            Some(amount("USD", "250"))
+        );
+    }
+
+    #[test]
+    fn test_as_of_ignores_later_prices() {
+        let table = sample_table(Some("2024-02-10"));
+        assert_eq!(
+            table.convert(&amount("GOOG", "2"), "USD"),
+            Some(amount("USD", "240"))
+        );
+        assert_eq!(
+            table.convert(&amount("USD", "10"), "EUR"),
+            Some(amount("EUR", "9"))
+        );
+
+        let table = sample_table(Some("2023-12-31"));
+        assert_eq!(table.convert(&amount("GOOG", "2"), "USD"), None);
+    }
+
+    #[test]
+    fn test_reverse_rate() {
+        let table = sample_table(None);
+        assert_eq!(
+            table.convert(&amount("EUR", "8"), "USD"),
+            Some(amount("USD", "10"))
+        );
+    }
+
+    #[test]
+    fn test_chained_conversion() {
+        // GOOG -> USD -> EUR
+        let table = sample_table(None);
+        assert_eq!(
+            table.convert(&amount("GOOG", "1"), "EUR"),
+            Some(amount("EUR", "100"))
+        );
+        // EUR -> USD -> GOOG, both legs reversed
+        assert_eq!(
+            table.convert(&amount("EUR", "200"), "GOOG"),
+            Some(amount("GOOG", "2"))
+        );
+    }
+
+    #[test]
+    fn test_missing_rate() {
+        let table = ConversionTable::from_prices(
+            vec![
+                price("2024-01-01", "GOOG", "100", "USD"),
+                price("2024-01-01", "EUR", "1.1", "CHF"),
+                price("2024-01-01", "BTC", "40000", "GBP"),
+                price("2024-01-01", "GBP", "1.2", "CHF"),
+            ],
+            None,
+        );
+        assert_eq!(table.convert(&amount("GOOG", "1"), "EUR"), None);
+        // Two hops (BTC -> GBP -> CHF -> EUR) are not followed
+        assert_eq!(table.convert(&amount("BTC", "1"), "EUR"), None);
+        assert_eq!(table.convert(&amount("JPY", "1"), "USD"), None);
+    }
+
+    #[test]
+    fn test_same_commodity_and_costs() {
+        let table = sample_table(None);
+        let mut shares = amount("GOOG", "3");
+        assert_eq!(table.convert(&shares, "GOOG"), Some(shares.clone()));
+
+        shares.price = Some(crate::Price {
+            kind: crate::PriceKind::Unit,
+            commodity: "USD".to_string(),
+            quantity: "90".parse().unwrap(),
+        });
+        assert_eq!(table.convert(&shares, "USD"), Some(amount("USD", "375")));
+    }
+
+    #[test]
+    fn test_direct_rate_preferred_over_reverse() {
+        let table = ConversionTable::from_prices(
+            vec![
+                price("2024-01-01", "USD", "0.9", "EUR"),
+                price("2024-01-02", "EUR", "1.25", "USD"),
+            ],
+            None,
+        );
+        assert_eq!(table.rate("USD", "EUR"), Some("0.9".parse().unwrap()));
+        assert_eq!(table.rate("EUR", "USD"), Some("1.25".parse().unwrap()));
+    }
+}
@@ -0,0 +1,616 @@
+//! The steps of a CSV import wizard: a look at the file, a guess at which
+//! columns hold what, and an import that checks the dates before hledger
+//! reads them
+//!
+//! Files are read in whatever encoding they're in, as
+//! [`detect_journal_encoding`](crate::detect_journal_encoding) tells it.
+
+use crate::commands::import::{import_files, preview_import, ImportOptions};
+use crate::commands::print::PrintReport;
+use crate::csv_rules::CsvRules;
+use crate::encoding::{decode, detect_encoding};
+use crate::{HLedgerError, Result};
+use chrono::format::{parse, Parsed, StrftimeItems};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use ts_rs::TS;
+
+/// Separators suggested when the chosen one doesn't split the file
+const COMMON_DELIMITERS: &[char] = &[',', ';', '\t', '|'];
+
+/// How many records are looked at to tell whether a delimiter fits
+const SNIFF_RECORDS: usize = 10;
+
+/// Date formats tried on sample values, in order: day-first comes before
+/// month-first when both read every sample
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%d/%m/%Y", "%m/%d/%Y", "%d.%m.%Y", "%d-%m-%Y", "%m-%d-%Y",
+    "%d/%m/%y", "%m/%d/%y", "%d.%m.%y",
+];
+
+/// What hledger reads dates as without a `date-format` rule
+const HLEDGER_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"];
+
+/// Words in a header naming a column, most telling first
+const DATE_HEADERS: &[&str] = &["date", "datum", "fecha", "booked", "posted"];
+const DESCRIPTION_HEADERS: &[&str] = &[
+    "description",
+    "payee",
+    "memo",
+    "narrative",
+    "details",
+    "verwendungszweck",
+    "beschreibung",
+    "reference",
+    "name",
+    "text",
+];
+const AMOUNT_HEADERS: &[&str] = &["amount", "betrag", "value", "sum"];
+const AMOUNT_IN_HEADERS: &[&str] = &["credit", "deposit", "paid in", "money in"];
+const AMOUNT_OUT_HEADERS: &[&str] = &["debit", "withdrawal", "paid out", "money out"];
+/// Running balances look like amounts but aren't one
+const BALANCE_HEADERS: &[&str] = &["balance", "saldo"];
+
+/// The start of a CSV file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct CsvPreview {
+    /// The first record when the file has a header, otherwise empty
+    pub headers: Vec<String>,
+    /// Up to the requested number of records after the header; rows may
+    /// differ in length
+    pub rows: Vec<Vec<String>>,
+    /// Records in the whole file, not counting the header
+    pub total_rows: u32,
+}
+
+/// Which columns (counted from 0) hold what, as guessed from a preview
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct CsvMapping {
+    pub date: Option<u32>,
+    /// strptime-style format every sample date reads with, for the
+    /// `date-format` rule
+    pub date_format: Option<String>,
+    pub description: Option<u32>,
+    /// A single signed amount column
+    pub amount: Option<u32>,
+    /// Money in, when amounts are split over two columns
+    pub amount_in: Option<u32>,
+    /// Money out, when amounts are split over two columns
+    pub amount_out: Option<u32>,
+    /// `,` when the amounts use a decimal comma
+    pub decimal_mark: Option<char>,
+}
+
+/// What [`import_csv`] did
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type", content = "value")]
+pub enum CsvImportOutcome {
+    /// A dry run: the transactions an import would add
+    Preview(PrintReport),
+    /// The number of transactions added to the journal
+    Imported(u32),
+}
+
+/// The header and first `max_rows` records of the CSV file at `path`,
+/// split on `delimiter`
+///
+/// Fails with `HLedgerError::CsvDelimiter` for a delimiter the csv format
+/// can't have (a quote, a line break or a non-ASCII character), and for one
+/// that doesn't split the first records into the same number of fields
+/// when another common delimiter does, naming that one.
+pub fn preview_csv(
+    path: &Path,
+    delimiter: char,
+    has_header: bool,
+    max_rows: usize,
+) -> Result<CsvPreview> {
+    let bad_delimiter = |hint: String| HLedgerError::CsvDelimiter {
+        file: path.to_string_lossy().into_owned(),
+        delimiter: delimiter_name(delimiter),
+        hint,
+    };
+    if !delimiter.is_ascii() || matches!(delimiter, '"' | '\n' | '\r') {
+        return Err(bad_delimiter(
+            "it must be a single ASCII character other than a quote or line break".to_string(),
+        ));
+    }
+
+    let text = read_text(path)?;
+    if !splits_evenly(&text, delimiter) {
+        let other = COMMON_DELIMITERS
+            .iter()
+            .find(|other| **other != delimiter && splits_evenly(&text, **other));
+        if let Some(other) = other {
+            return Err(bad_delimiter(format!(
+                "the rows don't split evenly on it; the file looks {}-separated",
+                delimiter_name(*other)
+            )));
+        }
+    }
+
+    let mut records = records(&text, delimiter);
+    let header = if has_header {
+        records.next().transpose()?
+    } else {
+        None
+    };
+    let headers = header
+        .map(|record| record.iter().map(str::to_string).collect())
+        .unwrap_or_default();
+    let mut rows = Vec::new();
+    let mut total_rows = 0;
+    for record in records {
+        let record = record?;
+        if rows.len() < max_rows {
+            rows.push(record.iter().map(str::to_string).collect::<Vec<_>>());
+        }
+        total_rows += 1;
+    }
+    Ok(CsvPreview {
+        headers,
+        rows,
+        total_rows,
+    })
+}
+
+/// Guess the date, description and amount columns of a CSV file from its
+/// headers (empty when it has none) and some of its rows
+///
+/// Header names decide where they're telling (`Date`, `Payee`, `Credit`,
+/// ...), as long as the samples agree; otherwise the first column of dates
+/// and the first of numbers are taken, and the description is the column
+/// with the longest text. Running balance columns are never the amount.
+pub fn guess_csv_mapping(headers: &[String], rows: &[Vec<String>]) -> CsvMapping {
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .chain([headers.len()])
+        .max()
+        .unwrap_or(0);
+    let header = |column: usize| {
+        headers
+            .get(column)
+            .map(|h| h.trim().to_lowercase())
+            .unwrap_or_default()
+    };
+    let samples = |column: usize| -> Vec<&str> {
+        rows.iter()
+            .filter_map(|row| row.get(column))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .collect()
+    };
+    let named = |words: &[&str], taken: &[usize]| -> Option<usize> {
+        words.iter().find_map(|word| {
+            (0..columns).find(|column| !taken.contains(column) && header(*column).contains(word))
+        })
+    };
+    let is_amounts = |column: usize| samples(column).iter().all(|v| number_mark(v).is_some());
+
+    let mut mapping = CsvMapping::default();
+    let mut taken = Vec::new();
+
+    // Dates: a named column that reads as dates, else any that does, else
+    // a named one whose format is left to the user
+    let dated = |column: usize| date_format_of(&samples(column));
+    let date = named(DATE_HEADERS, &taken)
+        .filter(|column| dated(*column).is_some())
+        .or_else(|| (0..columns).find(|column| dated(*column).is_some()))
+        .or_else(|| named(DATE_HEADERS, &taken));
+    if let Some(column) = date {
+        mapping.date = Some(column as u32);
+        mapping.date_format = dated(column).map(str::to_string);
+        taken.push(column);
+    }
+
+    let is_balance = |column: usize| {
+        BALANCE_HEADERS
+            .iter()
+            .any(|word| header(column).contains(word))
+    };
+    let amount = named(AMOUNT_HEADERS, &taken).filter(|column| is_amounts(*column));
+    let split = (
+        named(AMOUNT_IN_HEADERS, &taken).filter(|column| is_amounts(*column)),
+        named(AMOUNT_OUT_HEADERS, &taken).filter(|column| is_amounts(*column)),
+    );
+    let amount_columns = match (amount, split) {
+        (Some(column), _) => {
+            mapping.amount = Some(column as u32);
+            vec![column]
+        }
+        (None, (Some(amount_in), Some(amount_out))) => {
+            mapping.amount_in = Some(amount_in as u32);
+            mapping.amount_out = Some(amount_out as u32);
+            vec![amount_in, amount_out]
+        }
+        _ => {
+            let column = (0..columns).find(|column| {
+                !taken.contains(column)
+                    && !is_balance(*column)
+                    && !samples(*column).is_empty()
+                    && is_amounts(*column)
+            });
+            mapping.amount = column.map(|column| column as u32);
+            column.into_iter().collect()
+        }
+    };
+    mapping.decimal_mark = amount_columns
+        .iter()
+        .flat_map(|column| samples(*column))
+        .filter_map(number_mark)
+        .find_map(|mark| mark.filter(|mark| *mark == ','));
+    taken.extend(amount_columns);
+
+    // Numbers, balances included, are never the description
+    let is_text = |column: usize| !samples(column).iter().all(|v| number_mark(v).is_some());
+    mapping.description = named(DESCRIPTION_HEADERS, &taken)
+        .or_else(|| {
+            (0..columns)
+                .filter(|column| !taken.contains(column) && is_text(*column))
+                .max_by_key(|column| {
+                    let samples = samples(*column);
+                    // The first of the longest
+                    let length: usize = samples.iter().map(|v| v.chars().count()).sum();
+                    (length / samples.len().max(1), std::cmp::Reverse(*column))
+                })
+        })
+        .map(|column| column as u32);
+    mapping
+}
+
+/// Check that every row of the CSV file at `path` has a date `rules` can
+/// read, so a wrong `date-format` is reported at its row rather than as
+/// hledger's parse error
+///
+/// Fails with `HLedgerError::CsvDate` at the first row that doesn't.
+/// Nothing is checked without a `date` field, or when conditional rules
+/// could skip rows, since which ones isn't known here.
+pub fn check_csv_dates(path: &Path, rules: &CsvRules) -> Result<()> {
+    let Some(column) = rules.fields.iter().position(|field| field.trim() == "date") else {
+        return Ok(());
+    };
+    let skips_rows = rules.conditions.iter().any(|condition| {
+        condition
+            .assignments
+            .iter()
+            .any(|a| matches!(a.field.trim(), "skip" | "end"))
+    });
+    let Some(delimiter) = separator_char(rules.separator.as_deref()) else {
+        return Ok(());
+    };
+    if skips_rows {
+        return Ok(());
+    }
+    let formats: Vec<&str> = match &rules.date_format {
+        Some(format) => vec![format.as_str()],
+        None => HLEDGER_DATE_FORMATS.to_vec(),
+    };
+
+    let text = read_text(path)?;
+    let skip = rules.skip.unwrap_or(0) as usize;
+    for record in records(&text, delimiter).skip(skip) {
+        let record = record?;
+        let value = record.get(column).unwrap_or("").trim();
+        if !formats
+            .iter()
+            .any(|format| parse_date(value, format).is_some())
+        {
+            return Err(HLedgerError::CsvDate {
+                file: path.to_string_lossy().into_owned(),
+                line: record.position().map_or(0, |p| p.line() as u32),
+                column: column as u32 + 1,
+                value: value.to_string(),
+                format: rules
+                    .date_format
+                    .clone()
+                    .unwrap_or_else(|| "YYYY-MM-DD".to_string()),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Import `csv_file` into the journal with `rules`, or only preview it when
+/// `options.dry_run` is set
+///
+/// The dates are checked with [`check_csv_dates`] first, and the import is
+/// previewed before anything is written, so the count is of the
+/// transactions hledger said it would add. `options.rules` is replaced by
+/// `rules`; the rest of the options apply as for [`import_files`].
+pub fn import_csv(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    csv_file: &str,
+    rules: &CsvRules,
+    options: &ImportOptions,
+) -> Result<CsvImportOutcome> {
+    check_csv_dates(Path::new(csv_file), rules)?;
+    let options = ImportOptions {
+        rules: Some(rules.clone()),
+        ..options.clone()
+    };
+    let transactions = preview_import(hledger_path, journal_file, &[csv_file], &options)?;
+    if options.dry_run {
+        return Ok(CsvImportOutcome::Preview(transactions));
+    }
+    if !transactions.is_empty() {
+        import_files(hledger_path, journal_file, &[csv_file], &options)?;
+    }
+    Ok(CsvImportOutcome::Imported(transactions.len() as u32))
+}
+
+fn read_text(path: &Path) -> Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(decode(&bytes, &detect_encoding(&bytes)))
+}
+
+fn records(text: &str, delimiter: char) -> csv::StringRecordsIntoIter<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .into_records()
+}
+
+/// Whether the first records of `text` all split into the same number of
+/// fields, more than one, on `delimiter`
+fn splits_evenly(text: &str, delimiter: char) -> bool {
+    let counts: Vec<usize> = records(text, delimiter)
+        .take(SNIFF_RECORDS)
+        .map_while(|record| record.ok().map(|record| record.len()))
+        .collect();
+    counts.first().is_some_and(|first| *first > 1) && counts.windows(2).all(|w| w[0] == w[1])
+}
+
+/// The delimiter a `separator` rule means, as hledger reads it
+fn separator_char(separator: Option<&str>) -> Option<char> {
+    match separator.map(str::trim) {
+        None => Some(','),
+        Some("TAB") => Some('\t'),
+        Some("SPACE") => Some(' '),
+        Some(s) => {
+            let mut chars = s.chars();
+            chars
+                .next()
+                .filter(|c| c.is_ascii() && chars.next().is_none())
+        }
+    }
+}
+
+fn delimiter_name(delimiter: char) -> String {
+    match delimiter {
+        '\t' => "TAB".to_string(),
+        ' ' => "SPACE".to_string(),
+        c => c.to_string(),
+    }
+}
+
+/// `value` as a date in `format`, ignoring any time of day in it
+fn parse_date(value: &str, format: &str) -> Option<NaiveDate> {
+    let mut parsed = Parsed::new();
+    parse(&mut parsed, value, StrftimeItems::new(format)).ok()?;
+    parsed.to_naive_date().ok()
+}
+
+/// The first of [`DATE_FORMATS`] every sample reads with; years outside
+/// 1900–2100 don't count, so `%Y` doesn't take two-digit years
+fn date_format_of(samples: &[&str]) -> Option<&'static str> {
+    if samples.is_empty() {
+        return None;
+    }
+    DATE_FORMATS.iter().copied().find(|format| {
+        samples.iter().all(|value| {
+            parse_date(value, format).is_some_and(|date| (1900..=2100).contains(&date.year()))
+        })
+    })
+}
+
+/// The decimal mark of `value` if it reads as an amount (`None` inside
+/// when it has no decimals or they're ambiguous, as in `1,234`), ignoring
+/// signs, parentheses and commodity symbols around the number
+fn number_mark(value: &str) -> Option<Option<char>> {
+    let number = value.trim_matches(|c: char| !c.is_ascii_digit());
+    let grouping = |c: char| matches!(c, '.' | ',' | '\'' | ' ');
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || grouping(c)) {
+        return None;
+    }
+
+    let (whole, mark) = match number.rfind(['.', ',']) {
+        Some(i) => {
+            let mark = number[i..].chars().next()?;
+            let (whole, decimals) = (&number[..i], &number[i + 1..]);
+            let other = if mark == '.' { ',' } else { '.' };
+            if !decimals.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            if whole.contains(mark) || (decimals.len() == 3 && !whole.contains(other)) {
+                (number, None)
+            } else {
+                (whole, Some(mark))
+            }
+        }
+        None => (number, None),
+    };
+    // Digit groups after the first are of three
+    let mut groups = whole.split(|c: char| grouping(c) && Some(c) != mark);
+    let first = groups.next()?;
+    let grouped = whole.contains(|c: char| grouping(c) && Some(c) != mark);
+    if first.is_empty() || (grouped && first.len() > 3) || groups.any(|g| g.len() != 3) {
+        return None;
+    }
+    Some(mark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_rules::CsvCondition;
+
+    const BANK_CSV: &str = "tests/fixtures/bank.csv";
+
+    #[test]
+    fn export_bindings() {
+        CsvPreview::export_all().unwrap();
+        CsvMapping::export_all().unwrap();
+        CsvImportOutcome::export_all().unwrap();
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_preview_csv() {
+        let preview = preview_csv(Path::new(BANK_CSV), ';', true, 1).unwrap();
+        assert_eq!(
+            preview.headers,
+            strings(&["Date", "Description", "Ref", "Credit", "Debit"])
+        );
+        assert_eq!(
+            preview.rows,
+            [strings(&[
+                "05/03/2024",
+                "SUPERMARKT CENTRUM",
+                "A1",
+                "",
+                "23,40"
+            ])]
+        );
+        assert_eq!(preview.total_rows, 2);
+
+        let headless = preview_csv(Path::new(BANK_CSV), ';', false, 10).unwrap();
+        assert!(headless.headers.is_empty());
+        assert_eq!(headless.rows.len(), 3);
+        assert_eq!(headless.total_rows, 3);
+    }
+
+    #[test]
+    fn test_preview_bad_delimiter() {
+        let err = preview_csv(Path::new(BANK_CSV), ',', true, 10).unwrap_err();
+        match err {
+            HLedgerError::CsvDelimiter {
+                delimiter, hint, ..
+            } => {
+                assert_eq!(delimiter, ",");
+                assert!(hint.ends_with("looks ;-separated"), "{}", hint);
+            }
+            other => panic!("Expected CsvDelimiter, got {:?}", other),
+        }
+        assert!(matches!(
+            preview_csv(Path::new(BANK_CSV), '"', true, 10),
+            Err(HLedgerError::CsvDelimiter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_guess_named_columns() {
+        let preview = preview_csv(Path::new(BANK_CSV), ';', true, 10).unwrap();
+        let mapping = guess_csv_mapping(&preview.headers, &preview.rows);
+        assert_eq!(
+            mapping,
+            CsvMapping {
+                date: Some(0),
+                date_format: Some("%d/%m/%Y".to_string()),
+                description: Some(1),
+                amount: None,
+                amount_in: Some(3),
+                amount_out: Some(4),
+                decimal_mark: Some(','),
+            }
+        );
+    }
+
+    #[test]
+    fn test_guess_without_headers() {
+        let rows = vec![
+            strings(&[
+                "1",
+                "12/31/2023",
+                "$-4.50",
+                "COFFEE SHOP DOWNTOWN",
+                "1,020.00",
+            ]),
+            strings(&["2", "01/02/2024", "(12.00)", "BOOKSTORE", "1,008.00"]),
+        ];
+        let mapping = guess_csv_mapping(&[], &rows);
+        assert_eq!(mapping.date, Some(1));
+        // 12/31 can only be month first
+        assert_eq!(mapping.date_format.as_deref(), Some("%m/%d/%Y"));
+        // The first column of numbers, not the running balance
+        assert_eq!(mapping.amount, Some(0));
+        assert_eq!(mapping.description, Some(3));
+        assert_eq!(mapping.decimal_mark, None);
+
+        let headers = strings(&["Id", "Date", "Amount", "Memo", "Balance"]);
+        let mapping = guess_csv_mapping(&headers, &rows);
+        assert_eq!(mapping.amount, Some(2));
+        assert_eq!(mapping.description, Some(3));
+    }
+
+    #[test]
+    fn test_number_mark() {
+        let cases = [
+            ("23,40", Some(Some(','))),
+            ("-1.234,56 EUR", Some(Some(','))),
+            ("$1,234.56", Some(Some('.'))),
+            ("1,234", Some(None)),
+            ("1.234.567", Some(None)),
+            ("1'000.5", Some(Some('.'))),
+            ("(12)", Some(None)),
+            ("05.01.2024", None),
+            ("2024-01-05", None),
+            ("12,34,56", None),
+            ("COFFEE", None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(number_mark(value), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_check_csv_dates() {
+        let rules = CsvRules::new().skip(1).separator(";").fields([
+            "date",
+            "description",
+            "",
+            "credit",
+            "debit",
+        ]);
+        let path = Path::new(BANK_CSV);
+        assert!(check_csv_dates(path, &rules.clone().date_format("%d/%m/%Y")).is_ok());
+
+        match check_csv_dates(path, &rules.clone().date_format("%Y-%m-%d")) {
+            Err(HLedgerError::CsvDate {
+                line,
+                column,
+                value,
+                format,
+                ..
+            }) => {
+                assert_eq!((line, column), (2, 1));
+                assert_eq!(value, "05/03/2024");
+                assert_eq!(format, "%Y-%m-%d");
+            }
+            other => panic!("Expected CsvDate, got {:?}", other),
+        }
+
+        // Without the skip the header is read as a date
+        let mut unskipped = rules.clone().date_format("%d/%m/%Y");
+        unskipped.skip = None;
+        assert!(check_csv_dates(path, &unskipped).is_err());
+
+        // Rows a rule may skip can't be checked
+        let skipping = unskipped.condition(CsvCondition::new("^Date").assign("skip", ""));
+        assert!(check_csv_dates(path, &skipping).is_ok());
+    }
+}
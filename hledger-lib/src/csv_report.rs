@@ -0,0 +1,534 @@
+//! Reports as CSV tables, either read from hledger's `-O csv` output or laid
+//! out from the typed reports for export
+
+use crate::commands::balance::{Amount, BalanceReport, PeriodDate, PeriodicBalanceRow};
+use crate::commands::balancesheet::BalanceSheetReport;
+use crate::commands::cashflow::CashflowReport;
+use crate::commands::incomestatement::IncomeStatementReport;
+use crate::config::{check_success, run_command_with};
+use crate::timeout::Timeout;
+use crate::{HLedgerError, Result};
+use chrono::{Datelike, Months};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::process::Command;
+use ts_rs::TS;
+
+/// A report as a table of text cells
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct CsvReport {
+    /// The first record
+    pub headers: Vec<String>,
+    /// Every other record; rows may differ in length from the headers
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvReport {
+    /// Parse CSV whose first record is the header
+    pub fn from_reader(reader: impl Read) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = reader.headers()?.iter().map(str::to_string).collect();
+        let rows = reader
+            .records()
+            .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+            .collect::<Result<_>>()?;
+        Ok(Self { headers, rows })
+    }
+
+    /// Write the table as CSV text, headers first
+    pub fn to_csv_string(&self) -> Result<String> {
+        self.to_delimited_string(b',')
+    }
+
+    /// Write the table as tab-separated text, headers first
+    pub fn to_tsv_string(&self) -> Result<String> {
+        self.to_delimited_string(b'\t')
+    }
+
+    fn to_delimited_string(&self, delimiter: u8) -> Result<String> {
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .delimiter(delimiter)
+            .from_writer(Vec::new());
+        writer.write_record(&self.headers)?;
+        for row in &self.rows {
+            writer.write_record(row)?;
+        }
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| HLedgerError::Io(e.into_error()))?;
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+/// File format for exporting a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    /// The report as the app receives it, rather than a table
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// A file name for an exported report, e.g. `incomestatement-2024-06.csv`
+/// for a report on June 2024
+///
+/// The period covers all of `dates` and is named as hledger names periods;
+/// without dates the name is just the kind.
+pub fn export_file_name(kind: &str, dates: &[PeriodDate], format: ExportFormat) -> String {
+    let span = match (dates.first(), dates.last()) {
+        (Some(first), Some(last)) => Some(period_label(&PeriodDate {
+            start: first.start,
+            end: last.end,
+        })),
+        _ => None,
+    };
+    match span {
+        Some(span) => format!(
+            "{}-{}.{}",
+            kind,
+            span.replace("..", "_"),
+            format.extension()
+        ),
+        None => format!("{}.{}", kind, format.extension()),
+    }
+}
+
+/// Run a command built with `--output-format csv` and parse its output
+pub(crate) fn run_csv_command(cmd: &mut Command, timeout: Option<Timeout>) -> Result<CsvReport> {
+    let output = run_command_with(cmd, timeout, |stdout| CsvReport::from_reader(stdout))?;
+    check_success(cmd, output.status, &output.stderr)?;
+    output.stdout
+}
+
+impl BalanceReport {
+    /// The report laid out as `balance -O csv` lays it out, for exporting a
+    /// report already fetched as JSON
+    pub fn to_csv_report(&self) -> CsvReport {
+        match self {
+            BalanceReport::Simple(simple) => {
+                let mut rows: Vec<Vec<String>> = simple
+                    .accounts
+                    .iter()
+                    .map(|account| vec![account.name.clone(), cell(&account.amounts)])
+                    .collect();
+                rows.push(vec!["total".to_string(), cell(&simple.totals)]);
+                CsvReport {
+                    headers: vec!["account".to_string(), "balance".to_string()],
+                    rows,
+                }
+            }
+            BalanceReport::Periodic(periodic) => {
+                let columns = Columns::of(periodic.rows.iter().chain(&periodic.totals));
+                let mut rows: Vec<Vec<String>> = periodic
+                    .rows
+                    .iter()
+                    .map(|row| columns.row(&row.account, row))
+                    .collect();
+                if let Some(totals) = &periodic.totals {
+                    rows.push(columns.row("total", totals));
+                }
+                CsvReport {
+                    headers: columns.headers("account", &periodic.dates),
+                    rows,
+                }
+            }
+        }
+    }
+
+    /// [`BalanceReport::to_csv_report`] as CSV text
+    pub fn to_csv_string(&self) -> Result<String> {
+        self.to_csv_report().to_csv_string()
+    }
+}
+
+impl BalanceSheetReport {
+    /// The report laid out as `balancesheet -O csv` lays it out
+    pub fn to_csv_report(&self) -> CsvReport {
+        let sections = self
+            .subreports
+            .iter()
+            .map(|s| (s.name.as_str(), s.rows.as_slice(), s.totals.as_ref()));
+        compound_csv(&self.title, &self.dates, sections, self.totals.as_ref())
+    }
+
+    /// [`BalanceSheetReport::to_csv_report`] as CSV text
+    pub fn to_csv_string(&self) -> Result<String> {
+        self.to_csv_report().to_csv_string()
+    }
+}
+
+impl IncomeStatementReport {
+    /// The report laid out as `incomestatement -O csv` lays it out
+    pub fn to_csv_report(&self) -> CsvReport {
+        let sections = self
+            .subreports
+            .iter()
+            .map(|s| (s.name.as_str(), s.rows.as_slice(), s.totals.as_ref()));
+        compound_csv(&self.title, &self.dates, sections, self.totals.as_ref())
+    }
+
+    /// [`IncomeStatementReport::to_csv_report`] as CSV text
+    pub fn to_csv_string(&self) -> Result<String> {
+        self.to_csv_report().to_csv_string()
+    }
+}
+
+impl CashflowReport {
+    /// The report laid out as `cashflow -O csv` lays it out
+    pub fn to_csv_report(&self) -> CsvReport {
+        let sections = self.subreports.iter().map(|s| {
+            (
+                s.name.as_str(),
+                s.data.rows.as_slice(),
+                s.data.totals.as_ref(),
+            )
+        });
+        compound_csv(&self.title, &self.dates, sections, self.totals.as_ref())
+    }
+
+    /// [`CashflowReport::to_csv_report`] as CSV text
+    pub fn to_csv_string(&self) -> Result<String> {
+        self.to_csv_report().to_csv_string()
+    }
+}
+
+/// A compound report: a title row of period headers, then each section's
+/// name, account rows and `Total:`, then `Net:`
+fn compound_csv<'a>(
+    title: &str,
+    dates: &[PeriodDate],
+    sections: impl Iterator<
+        Item = (
+            &'a str,
+            &'a [PeriodicBalanceRow],
+            Option<&'a PeriodicBalanceRow>,
+        ),
+    >,
+    totals: Option<&PeriodicBalanceRow>,
+) -> CsvReport {
+    let sections: Vec<_> = sections.collect();
+    let columns = Columns::of(
+        sections
+            .iter()
+            .flat_map(|(_, rows, totals)| rows.iter().chain(*totals))
+            .chain(totals),
+    );
+
+    let mut rows = Vec::new();
+    for (name, section_rows, section_totals) in sections {
+        let mut heading = vec![String::new(); columns.width(dates)];
+        heading[0] = name.to_string();
+        rows.push(heading);
+        rows.extend(
+            section_rows
+                .iter()
+                .map(|row| columns.row(&row.account, row)),
+        );
+        if let Some(section_totals) = section_totals {
+            rows.push(columns.row("Total:", section_totals));
+        }
+    }
+    if let Some(totals) = totals {
+        rows.push(columns.row("Net:", totals));
+    }
+
+    CsvReport {
+        headers: columns.headers(title, dates),
+        rows,
+    }
+}
+
+/// Which optional columns a periodic report has
+struct Columns {
+    total: bool,
+    average: bool,
+}
+
+impl Columns {
+    fn of<'a>(rows: impl Iterator<Item = &'a PeriodicBalanceRow>) -> Self {
+        let mut columns = Columns {
+            total: false,
+            average: false,
+        };
+        for row in rows {
+            columns.total |= row.total.is_some();
+            columns.average |= row.average.is_some();
+        }
+        columns
+    }
+
+    fn width(&self, dates: &[PeriodDate]) -> usize {
+        1 + dates.len() + usize::from(self.total) + usize::from(self.average)
+    }
+
+    fn headers(&self, first: &str, dates: &[PeriodDate]) -> Vec<String> {
+        let mut headers = vec![first.to_string()];
+        headers.extend(dates.iter().map(period_label));
+        if self.total {
+            headers.push("total".to_string());
+        }
+        if self.average {
+            headers.push("average".to_string());
+        }
+        headers
+    }
+
+    fn row(&self, name: &str, row: &PeriodicBalanceRow) -> Vec<String> {
+        let mut cells = vec![name.to_string()];
+        cells.extend(row.amounts.iter().map(|amounts| cell(amounts)));
+        if self.total {
+            cells.push(row.total.as_deref().map(cell).unwrap_or_default());
+        }
+        if self.average {
+            cells.push(row.average.as_deref().map(cell).unwrap_or_default());
+        }
+        cells
+    }
+}
+
+/// Amounts in one cell, comma separated as hledger writes them; `0` when
+/// there are none
+fn cell(amounts: &[Amount]) -> String {
+    if amounts.is_empty() {
+        return "0".to_string();
+    }
+    amounts
+        .iter()
+        .map(|amount| amount.format(None))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A period the way hledger names it: `2024`, `2024Q1`, `2024-01`, a single
+/// date, or `start..end` with an inclusive end
+fn period_label(period: &PeriodDate) -> String {
+    let start = period.start;
+    let after = |months: u32| start.checked_add_months(Months::new(months));
+    let last = period.end.pred_opt().unwrap_or(period.end);
+
+    if start.day() == 1 && start.month() == 1 && after(12) == Some(period.end) {
+        start.format("%Y").to_string()
+    } else if start.day() == 1 && start.month() % 3 == 1 && after(3) == Some(period.end) {
+        format!("{}Q{}", start.year(), start.month() / 3 + 1)
+    } else if start.day() == 1 && after(1) == Some(period.end) {
+        start.format("%Y-%m").to_string()
+    } else if last == start {
+        start.to_string()
+    } else {
+        format!("{}..{}", start, last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::{BalanceAccount, PeriodicBalance, SimpleBalance};
+    use crate::commands::balancesheet::BalanceSheetSubreport;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn export_bindings() {
+        CsvReport::export_all().unwrap();
+    }
+
+    fn usd(quantity: &str) -> Vec<Amount> {
+        vec![Amount {
+            commodity: "$".to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+        }]
+    }
+
+    fn period(start: &str, end: &str) -> PeriodDate {
+        PeriodDate {
+            start: NaiveDate::parse_from_str(start, "%Y-%m-%d").unwrap(),
+            end: NaiveDate::parse_from_str(end, "%Y-%m-%d").unwrap(),
+        }
+    }
+
+    fn row(account: &str, amounts: &[&str], total: Option<&str>) -> PeriodicBalanceRow {
+        PeriodicBalanceRow {
+            account: account.to_string(),
+            display_name: account.to_string(),
+            indent: 0,
+            amounts: amounts.iter().map(|q| usd(q)).collect(),
+            total: total.map(usd),
+            average: None,
+        }
+    }
+
+    #[test]
+    fn test_from_reader() {
+        // hledger -f tests/fixtures/test.journal balance -O csv
+        let text = "\"account\",\"balance\"\n\"assets:checking\",\"$79.50\"\n\"expenses:food:groceries\",\"$20.00\"\n\"total\",\"0\"\n";
+        let report = CsvReport::from_reader(text.as_bytes()).unwrap();
+        assert_eq!(report.headers, vec!["account", "balance"]);
+        assert_eq!(report.rows.len(), 3);
+        assert_eq!(report.rows[1], vec!["expenses:food:groceries", "$20.00"]);
+
+        assert!(CsvReport::from_reader("".as_bytes())
+            .unwrap()
+            .rows
+            .is_empty());
+    }
+
+    #[test]
+    fn test_csv_string_round_trip() {
+        let report = CsvReport {
+            headers: vec!["account".to_string(), "balance".to_string()],
+            rows: vec![
+                vec!["assets:\"quoted\"".to_string(), "$1, 2 EUR".to_string()],
+                vec!["total".to_string()],
+            ],
+        };
+        let text = report.to_csv_string().unwrap();
+        assert!(text.starts_with("account,balance\n"));
+        assert_eq!(CsvReport::from_reader(text.as_bytes()).unwrap(), report);
+    }
+
+    #[test]
+    fn test_simple_balance_to_csv() {
+        let report = BalanceReport::Simple(SimpleBalance {
+            accounts: vec![BalanceAccount {
+                name: "assets:checking".to_string(),
+                display_name: "assets:checking".to_string(),
+                indent: 0,
+                amounts: usd("79.50"),
+            }],
+            totals: vec![],
+        });
+        let csv = report.to_csv_report();
+        assert_eq!(csv.headers, vec!["account", "balance"]);
+        assert_eq!(
+            csv.rows,
+            vec![vec!["assets:checking", "$79.50"], vec!["total", "0"]]
+        );
+    }
+
+    #[test]
+    fn test_periodic_balance_to_csv() {
+        let report = BalanceReport::Periodic(PeriodicBalance {
+            dates: vec![
+                period("2024-01-01", "2024-02-01"),
+                period("2024-02-01", "2024-03-01"),
+            ],
+            rows: vec![row("expenses:food", &["20", "5"], Some("25"))],
+            totals: Some(row("", &["20", "5"], Some("25"))),
+        });
+        let csv = report.to_csv_report();
+        assert_eq!(csv.headers, vec!["account", "2024-01", "2024-02", "total"]);
+        assert_eq!(
+            csv.rows,
+            vec![
+                vec!["expenses:food", "$20", "$5", "$25"],
+                vec!["total", "$20", "$5", "$25"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compound_report_to_csv() {
+        let subreport = |name: &str, account: &str, amount: &str| BalanceSheetSubreport {
+            name: name.to_string(),
+            dates: vec![],
+            rows: vec![row(account, &[amount], None)],
+            totals: Some(row("", &[amount], None)),
+            increases_total: true,
+        };
+        let report = BalanceSheetReport {
+            title: "Balance Sheet 2024-01-31".to_string(),
+            dates: vec![period("2024-01-31", "2024-02-01")],
+            subreports: vec![
+                subreport("Assets", "assets:checking", "100"),
+                subreport("Liabilities", "liabilities:card", "40"),
+            ],
+            totals: Some(row("", &["60"], None)),
+        };
+        let csv = report.to_csv_report();
+        assert_eq!(csv.headers, vec!["Balance Sheet 2024-01-31", "2024-01-31"]);
+        assert_eq!(
+            csv.rows,
+            vec![
+                vec!["Assets", ""],
+                vec!["assets:checking", "$100"],
+                vec!["Total:", "$100"],
+                vec!["Liabilities", ""],
+                vec!["liabilities:card", "$40"],
+                vec!["Total:", "$40"],
+                vec!["Net:", "$60"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsv_string() {
+        let table = CsvReport {
+            headers: vec!["account".to_string(), "balance".to_string()],
+            rows: vec![vec!["assets:cash".to_string(), "$1, 2 EUR".to_string()]],
+        };
+        // Commas need no quoting between tabs
+        assert_eq!(
+            table.to_tsv_string().unwrap(),
+            "account\tbalance\nassets:cash\t$1, 2 EUR\n"
+        );
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        let months = [
+            period("2024-06-01", "2024-07-01"),
+            period("2024-07-01", "2024-08-01"),
+            period("2024-08-01", "2024-09-01"),
+        ];
+        assert_eq!(
+            export_file_name("incomestatement", &months[..1], ExportFormat::Csv),
+            "incomestatement-2024-06.csv"
+        );
+        assert_eq!(
+            export_file_name("balance", &months, ExportFormat::Tsv),
+            "balance-2024-06-01_2024-08-31.tsv"
+        );
+        assert_eq!(
+            export_file_name(
+                "cashflow",
+                &[period("2024-01-01", "2025-01-01")],
+                ExportFormat::Json
+            ),
+            "cashflow-2024.json"
+        );
+        assert_eq!(
+            export_file_name("print", &[], ExportFormat::Csv),
+            "print.csv"
+        );
+    }
+
+    #[test]
+    fn test_period_label() {
+        assert_eq!(period_label(&period("2024-01-01", "2025-01-01")), "2024");
+        assert_eq!(period_label(&period("2024-04-01", "2024-07-01")), "2024Q2");
+        assert_eq!(period_label(&period("2024-12-01", "2025-01-01")), "2024-12");
+        assert_eq!(
+            period_label(&period("2024-03-05", "2024-03-06")),
+            "2024-03-05"
+        );
+        assert_eq!(
+            period_label(&period("2024-01-01", "2024-01-08")),
+            "2024-01-01..2024-01-07"
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{HLedgerError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use ts_rs::TS;
+
+/// An hledger CSV rules file, describing how rows of a bank's CSV export
+/// become transactions
+///
+/// Values like `amount_in` are hledger field assignments, so they may refer
+/// to CSV fields by name or number (`%credit`, `%4`) and mix in literal text.
+/// Render with [`CsvRules::to_rules_string`]; see the hledger manual's CSV
+/// section for what each rule does.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct CsvRules {
+    /// Header lines to skip at the top of the file
+    pub skip: Option<u32>,
+    /// Field separator when it isn't a comma, e.g. `;` or `TAB`
+    pub separator: Option<String>,
+    /// A name for each CSV column, in order; empty for unused columns.
+    /// Names hledger knows (`date`, `description`, `amount`, ...) are
+    /// assigned directly
+    pub fields: Vec<String>,
+    /// strptime-style format of the date field, e.g. `%d/%m/%Y`
+    pub date_format: Option<String>,
+    /// Decimal mark of amounts, when it's `,`
+    pub decimal_mark: Option<char>,
+    /// Rows are newest first, so same-day transactions should be reversed
+    pub newest_first: bool,
+    /// Commodity for amounts that have none
+    pub currency: Option<String>,
+    /// The account the CSV is for, e.g. `assets:bank:checking`
+    pub account1: Option<String>,
+    /// The other account, when no conditional rule sets one
+    pub account2: Option<String>,
+    /// Amount flowing into account1
+    pub amount_in: Option<String>,
+    /// Amount flowing out of account1
+    pub amount_out: Option<String>,
+    /// `if` blocks, applied in order
+    pub conditions: Vec<CsvCondition>,
+}
+
+/// An `if` block: assignments applied to rows any of the matchers match
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct CsvCondition {
+    /// Regexes matched against the whole row, or against one field when
+    /// written `%field REGEX`
+    pub matchers: Vec<String>,
+    /// Field assignments for matching rows
+    pub assignments: Vec<CsvAssignment>,
+}
+
+/// A field assignment such as `account2 expenses:food`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct CsvAssignment {
+    pub field: String,
+    pub value: String,
+}
+
+// Implementation for builder pattern
+impl CsvRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn skip(mut self, lines: u32) -> Self {
+        self.skip = Some(lines);
+        self
+    }
+
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = Some(separator.into());
+        self
+    }
+
+    pub fn fields<S: Into<String>>(mut self, fields: impl IntoIterator<Item = S>) -> Self {
+        self.fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn date_format(mut self, format: impl Into<String>) -> Self {
+        self.date_format = Some(format.into());
+        self
+    }
+
+    pub fn decimal_mark(mut self, mark: char) -> Self {
+        self.decimal_mark = Some(mark);
+        self
+    }
+
+    pub fn newest_first(mut self) -> Self {
+        self.newest_first = true;
+        self
+    }
+
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    pub fn account1(mut self, account: impl Into<String>) -> Self {
+        self.account1 = Some(account.into());
+        self
+    }
+
+    pub fn account2(mut self, account: impl Into<String>) -> Self {
+        self.account2 = Some(account.into());
+        self
+    }
+
+    pub fn amount_in(mut self, value: impl Into<String>) -> Self {
+        self.amount_in = Some(value.into());
+        self
+    }
+
+    pub fn amount_out(mut self, value: impl Into<String>) -> Self {
+        self.amount_out = Some(value.into());
+        self
+    }
+
+    pub fn condition(mut self, condition: CsvCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+}
+
+// Implementation for builder pattern
+impl CsvCondition {
+    pub fn new(matcher: impl Into<String>) -> Self {
+        Self {
+            matchers: vec![matcher.into()],
+            assignments: vec![],
+        }
+    }
+
+    /// Also apply to rows this matches
+    pub fn or(mut self, matcher: impl Into<String>) -> Self {
+        self.matchers.push(matcher.into());
+        self
+    }
+
+    pub fn assign(mut self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.assignments.push(CsvAssignment {
+            field: field.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn account2(self, account: impl Into<String>) -> Self {
+        self.assign("account2", account)
+    }
+}
+
+impl CsvRules {
+    /// Render the rules file
+    ///
+    /// Fails with `HLedgerError::InvalidCsvRules` for values hledger would
+    /// misread: line breaks anywhere, commas in field names, or an `if`
+    /// block without matchers or assignments.
+    pub fn to_rules_string(&self) -> Result<String> {
+        let mut lines = Vec::new();
+
+        if let Some(skip) = self.skip {
+            push_rule(&mut lines, "skip", &skip.to_string())?;
+        }
+        if let Some(separator) = &self.separator {
+            push_rule(&mut lines, "separator", separator)?;
+        }
+        if !self.fields.is_empty() {
+            if let Some(field) = self.fields.iter().find(|f| f.contains(',')) {
+                return Err(invalid(format!("field name '{}' contains a comma", field)));
+            }
+            push_rule(&mut lines, "fields", &self.fields.join(", "))?;
+        }
+        if let Some(format) = &self.date_format {
+            push_rule(&mut lines, "date-format", format)?;
+        }
+        if let Some(mark) = self.decimal_mark {
+            push_rule(&mut lines, "decimal-mark", &mark.to_string())?;
+        }
+        if self.newest_first {
+            lines.push("newest-first".to_string());
+        }
+        let assignments = [
+            ("currency", &self.currency),
+            ("account1", &self.account1),
+            ("account2", &self.account2),
+            ("amount-in", &self.amount_in),
+            ("amount-out", &self.amount_out),
+        ];
+        for (field, value) in assignments {
+            if let Some(value) = value {
+                push_rule(&mut lines, field, value)?;
+            }
+        }
+
+        for condition in &self.conditions {
+            if condition.matchers.is_empty() || condition.assignments.is_empty() {
+                return Err(invalid(
+                    "an if block needs at least one matcher and one assignment".to_string(),
+                ));
+            }
+            lines.push(String::new());
+            for (i, matcher) in condition.matchers.iter().enumerate() {
+                single_line("if", matcher)?;
+                lines.push(if i == 0 {
+                    format!("if {}", matcher)
+                } else {
+                    matcher.clone()
+                });
+            }
+            for assignment in &condition.assignments {
+                single_line(&assignment.field, &assignment.value)?;
+                lines.push(format!("  {} {}", assignment.field, assignment.value));
+            }
+        }
+
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// Write the rules file to `path`, replacing any file there
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_rules_string()?)?;
+        Ok(())
+    }
+}
+
+/// Append a `NAME VALUE` line
+fn push_rule(lines: &mut Vec<String>, name: &str, value: &str) -> Result<()> {
+    single_line(name, value)?;
+    lines.push(format!("{} {}", name, value));
+    Ok(())
+}
+
+fn single_line(name: &str, value: &str) -> Result<()> {
+    if name.contains(['\n', '\r']) || value.contains(['\n', '\r']) {
+        return Err(invalid(format!("{} contains a line break", name.trim())));
+    }
+    Ok(())
+}
+
+fn invalid(message: String) -> HLedgerError {
+    HLedgerError::InvalidCsvRules(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        CsvRules::export_all().unwrap();
+        CsvCondition::export_all().unwrap();
+        CsvAssignment::export_all().unwrap();
+    }
+
+    fn bank_rules() -> CsvRules {
+        CsvRules::new()
+            .skip(1)
+            .separator(";")
+            .fields(["date", "description", "", "credit", "debit"])
+            .date_format("%d/%m/%Y")
+            .decimal_mark(',')
+            .newest_first()
+            .currency("EUR")
+            .account1("assets:bank:checking")
+            .account2("expenses:unknown")
+            .amount_in("%credit")
+            .amount_out("%debit")
+            .condition(
+                CsvCondition::new("SUPERMARKT")
+                    .or("%description BAKERY")
+                    .account2("expenses:food"),
+            )
+            .condition(
+                CsvCondition::new("SALARY")
+                    .account2("income:salary")
+                    .assign("comment", "payroll"),
+            )
+    }
+
+    #[test]
+    fn test_to_rules_string() {
+        assert_eq!(
+            bank_rules().to_rules_string().unwrap(),
+            "skip 1
+separator ;
+fields date, description, , credit, debit
+date-format %d/%m/%Y
+decimal-mark ,
+newest-first
+currency EUR
+account1 assets:bank:checking
+account2 expenses:unknown
+amount-in %credit
+amount-out %debit
+
+if SUPERMARKT
+%description BAKERY
+  account2 expenses:food
+
+if SALARY
+  account2 income:salary
+  comment payroll
+"
+        );
+        assert_eq!(CsvRules::new().to_rules_string().unwrap(), "\n");
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let cases = [
+            CsvRules::new().account1("assets\nskip 5"),
+            CsvRules::new().fields(["date", "amount,fee"]),
+            CsvRules::new().condition(CsvCondition::new("SHOP")),
+            CsvRules::new().condition(CsvCondition::new("SHOP\r").account2("expenses")),
+            CsvRules::new().condition(CsvCondition::new("SHOP").assign("comment", "a\nb")),
+        ];
+        for rules in cases {
+            assert!(
+                matches!(
+                    rules.to_rules_string(),
+                    Err(HLedgerError::InvalidCsvRules(_))
+                ),
+                "{:?} should be rejected",
+                rules
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_to() {
+        let path = std::env::temp_dir().join(format!("csv-rules-{}.rules", std::process::id()));
+        let rules = bank_rules();
+        rules.write_to(&path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(written, rules.to_rules_string().unwrap());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let json = serde_json::to_value(bank_rules()).unwrap();
+        assert_eq!(json["dateFormat"], "%d/%m/%Y");
+        assert_eq!(json["amountIn"], "%credit");
+        assert_eq!(json["conditions"][0]["matchers"][1], "%description BAKERY");
+        let parsed: CsvRules = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, bank_rules());
+    }
+}
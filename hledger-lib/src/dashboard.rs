@@ -0,0 +1,283 @@
+//! The figures a home screen shows for one month, computed from a single
+//! batch of reports
+//!
+//! Each figure comes from its own report, run at once by
+//! [`run_reports`](crate::run_reports); a report that fails only leaves its
+//! own figures out, with the error in [`DashboardSummary::unavailable`].
+
+use crate::amount::MultiAmount;
+use crate::batch::{run_reports, ReportRequest, ReportResponse};
+use crate::commands::incomestatement::IncomeStatementReport;
+use crate::commands::networth::networth_points;
+use crate::defaults::{merge_options, ReportDefaults};
+use crate::error::AppError;
+use crate::interval::ReportInterval;
+use crate::{HLedgerError, Result};
+use chrono::{Datelike, Local, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use ts_rs::TS;
+
+/// Income and expenses over one month
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct MonthTotals {
+    /// Revenues, positive as hledger shows them
+    pub income: MultiAmount,
+    pub expenses: MultiAmount,
+    /// Income less expenses
+    pub net: MultiAmount,
+}
+
+/// A part of the dashboard that comes from one report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum DashboardSection {
+    /// `net_worth`, from the balance sheet
+    NetWorth,
+    /// `this_month`, `last_month` and `net_change`, from the income statement
+    IncomeExpenses,
+    /// `transaction_count`, from print
+    Transactions,
+}
+
+/// Why a section of the dashboard is missing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct UnavailableSection {
+    pub section: DashboardSection,
+    pub error: AppError,
+}
+
+/// Summary of one month; a figure is `None` when its section is
+/// unavailable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DashboardSummary {
+    /// The month summarised, as `YYYY-MM`
+    pub month: String,
+    /// Net worth at the end of the month
+    pub net_worth: Option<MultiAmount>,
+    pub this_month: Option<MonthTotals>,
+    pub last_month: Option<MonthTotals>,
+    /// This month's net less last month's
+    pub net_change: Option<MultiAmount>,
+    /// Transactions dated in the month
+    pub transaction_count: Option<u32>,
+    pub unavailable: Vec<UnavailableSection>,
+}
+
+/// Summarise `month` (`YYYY-MM`, the current month when `None`)
+///
+/// `defaults` apply to each report as they would to the app's own, so the
+/// valuation and status filters match the other screens. Only a malformed
+/// `month` fails the call; a failing report marks its section unavailable.
+pub fn dashboard_summary(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    month: Option<&str>,
+    defaults: &ReportDefaults,
+) -> Result<DashboardSummary> {
+    let start = month_start(month)?;
+    let previous = start - Months::new(1);
+    let next = start + Months::new(1);
+
+    let requests = vec![
+        ReportRequest::BalanceSheet(merge_options(
+            defaults,
+            json!({ "historical": true, "end": next.to_string() }),
+        )?),
+        ReportRequest::IncomeStatement(merge_options(
+            defaults,
+            json!({
+                "interval": ReportInterval::Monthly,
+                "begin": previous.to_string(),
+                "end": next.to_string(),
+            }),
+        )?),
+        ReportRequest::Print(merge_options(
+            defaults,
+            json!({ "begin": start.to_string(), "end": next.to_string() }),
+        )?),
+    ];
+
+    let mut summary = DashboardSummary {
+        month: start.format("%Y-%m").to_string(),
+        net_worth: None,
+        this_month: None,
+        last_month: None,
+        net_change: None,
+        transaction_count: None,
+        unavailable: Vec::new(),
+    };
+    let sections = [
+        DashboardSection::NetWorth,
+        DashboardSection::IncomeExpenses,
+        DashboardSection::Transactions,
+    ];
+    let results = run_reports(hledger_path, journal_file, requests);
+    for (section, result) in sections.into_iter().zip(results) {
+        match result {
+            Ok(ReportResponse::BalanceSheet(report)) => {
+                let points = networth_points(&report);
+                summary.net_worth = Some(
+                    points
+                        .last()
+                        .map(|point| point.net_worth.clone())
+                        .unwrap_or_default(),
+                );
+            }
+            Ok(ReportResponse::IncomeStatement(report)) => {
+                let this_month = month_totals(&report, start);
+                let last_month = month_totals(&report, previous);
+                summary.net_change = Some(this_month.net.clone() - last_month.net.clone());
+                summary.this_month = Some(this_month);
+                summary.last_month = Some(last_month);
+            }
+            Ok(ReportResponse::Print(transactions)) => {
+                summary.transaction_count = Some(transactions.len() as u32);
+            }
+            Ok(_) => unreachable!("each request gets a response of its kind"),
+            Err(e) => summary.unavailable.push(UnavailableSection {
+                section,
+                error: e.into(),
+            }),
+        }
+    }
+    Ok(summary)
+}
+
+/// First day of `month`, or of the current month
+fn month_start(month: Option<&str>) -> Result<NaiveDate> {
+    let Some(month) = month else {
+        let today = Local::now().date_naive();
+        return Ok(today.with_day(1).unwrap_or(today));
+    };
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d").map_err(|_| {
+        HLedgerError::InvalidDate {
+            input: month.to_string(),
+            hint: "Expected a month as YYYY-MM".to_string(),
+        }
+    })
+}
+
+/// Totals of the income statement period starting on `start`; zero when
+/// hledger left the period out
+fn month_totals(report: &IncomeStatementReport, start: NaiveDate) -> MonthTotals {
+    let mut totals = MonthTotals::default();
+    let Some(i) = report.dates.iter().position(|period| period.start == start) else {
+        return totals;
+    };
+    for subreport in &report.subreports {
+        let total: MultiAmount = subreport
+            .totals
+            .as_ref()
+            .and_then(|totals| totals.amounts.get(i))
+            .map(|amounts| amounts.iter().collect())
+            .unwrap_or_default();
+        // Revenues add to the total and expenses take from it, both shown
+        // positive
+        if subreport.increases_total {
+            totals.income += &total;
+        } else {
+            totals.expenses += &total;
+        }
+    }
+    totals.net = totals.income.clone() - totals.expenses.clone();
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::{Amount, PeriodDate, PeriodicBalanceRow};
+    use crate::commands::incomestatement::IncomeStatementSubreport;
+    use rust_decimal::Decimal;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn usd(quantity: &str) -> Vec<Amount> {
+        vec![Amount {
+            commodity: "$".to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+        }]
+    }
+
+    fn subreport(
+        name: &str,
+        increases_total: bool,
+        totals: Vec<Vec<Amount>>,
+    ) -> IncomeStatementSubreport {
+        IncomeStatementSubreport {
+            name: name.to_string(),
+            dates: vec![],
+            rows: vec![],
+            totals: Some(PeriodicBalanceRow {
+                account: String::new(),
+                display_name: String::new(),
+                indent: 0,
+                amounts: totals,
+                total: None,
+                average: None,
+            }),
+            increases_total,
+        }
+    }
+
+    #[test]
+    fn export_bindings() {
+        DashboardSummary::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_month_start() {
+        assert_eq!(month_start(Some("2024-03")).unwrap(), date("2024-03-01"));
+        assert!(matches!(
+            month_start(Some("March")),
+            Err(HLedgerError::InvalidDate { .. })
+        ));
+        assert_eq!(month_start(None).unwrap().day(), 1);
+    }
+
+    #[test]
+    fn test_month_totals() {
+        let report = IncomeStatementReport {
+            title: "Income Statement".to_string(),
+            dates: vec![
+                PeriodDate {
+                    start: date("2024-02-01"),
+                    end: date("2024-03-01"),
+                },
+                PeriodDate {
+                    start: date("2024-03-01"),
+                    end: date("2024-04-01"),
+                },
+            ],
+            subreports: vec![
+                subreport("Revenues", true, vec![usd("1000"), usd("1200.50")]),
+                subreport("Expenses", false, vec![usd("400"), usd("900.25")]),
+            ],
+            totals: None,
+        };
+
+        let march = month_totals(&report, date("2024-03-01"));
+        assert_eq!(march.income.get("$"), Decimal::new(120050, 2));
+        assert_eq!(march.expenses.get("$"), Decimal::new(90025, 2));
+        assert_eq!(march.net.get("$"), Decimal::new(30025, 2));
+
+        let february = month_totals(&report, date("2024-02-01"));
+        assert_eq!(february.net.get("$"), Decimal::from(600));
+
+        // A month hledger didn't report is all zero
+        assert!(month_totals(&report, date("2024-01-01")).net.is_zero());
+    }
+}
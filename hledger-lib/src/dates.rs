@@ -0,0 +1,251 @@
+use crate::{HLedgerError, Result};
+use chrono::NaiveDate;
+
+const PERIOD_UNITS: [&str; 5] = ["day", "week", "month", "quarter", "year"];
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Check that a `begin`/`end` value is a date hledger will understand
+///
+/// Accepts the common smart-date forms: `YYYY-MM-DD`, `YYYY-MM`, `YYYY` (with
+/// `-`, `/` or `.` separators), `YYYYMMDD`, `M/D`, quarters like `2024q1`,
+/// month names, and relative dates such as `today`, `last month`, `in 3 days`
+/// or `2 weeks ago`. Anything else returns `HLedgerError::InvalidDate` with a
+/// hint suitable for showing next to the input.
+pub fn validate_smart_date(input: &str) -> Result<()> {
+    let date = input.trim().to_lowercase();
+    let numeric = date
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '-' | '/' | '.'));
+    if numeric && !date.is_empty() {
+        return validate_numeric_date(input, &date);
+    }
+
+    if is_relative_date(&date) || is_quarter(&date) || is_month_name(&date) {
+        Ok(())
+    } else {
+        Err(invalid_date(
+            input,
+            "Expected YYYY-MM-DD, YYYY-MM, YYYY or a relative date like 'last month'",
+        ))
+    }
+}
+
+/// Validate the `begin` and `end` options of a report
+pub(crate) fn validate_date_options(begin: Option<&str>, end: Option<&str>) -> Result<()> {
+    begin
+        .into_iter()
+        .chain(end)
+        .try_for_each(validate_smart_date)
+}
+
+fn validate_numeric_date(input: &str, date: &str) -> Result<()> {
+    let separators: Vec<char> = date.chars().filter(|c| !c.is_ascii_digit()).collect();
+    if separators.iter().any(|&c| c != separators[0]) {
+        return Err(invalid_date(
+            input,
+            "Use a single kind of separator, e.g. 2024-01-31",
+        ));
+    }
+    let parts: Vec<&str> = match separators.first() {
+        Some(&sep) => date.split(sep).collect(),
+        None => vec![date],
+    };
+    if parts.iter().any(|p| p.is_empty()) {
+        return Err(invalid_date(input, "Expected YYYY-MM-DD, YYYY-MM or YYYY"));
+    }
+
+    let number = |s: &str| s.parse::<u32>().unwrap_or(u32::MAX);
+    let (year, month, day) = match parts.as_slice() {
+        [y] if y.len() == 4 => (Some(number(y)), None, None),
+        [ym] if ym.len() == 6 => (Some(number(&ym[..4])), Some(number(&ym[4..])), None),
+        [ymd] if ymd.len() == 8 => (
+            Some(number(&ymd[..4])),
+            Some(number(&ymd[4..6])),
+            Some(number(&ymd[6..])),
+        ),
+        [y, m] if y.len() == 4 && m.len() <= 2 => (Some(number(y)), Some(number(m)), None),
+        [m, d] if m.len() <= 2 && d.len() <= 2 => (None, Some(number(m)), Some(number(d))),
+        [y, m, d] if y.len() == 4 && m.len() <= 2 && d.len() <= 2 => {
+            (Some(number(y)), Some(number(m)), Some(number(d)))
+        }
+        [_, _, y] if y.len() == 4 => {
+            return Err(invalid_date(
+                input,
+                "Dates are written year first, e.g. 2024-01-31",
+            ));
+        }
+        _ => {
+            return Err(invalid_date(input, "Expected YYYY-MM-DD, YYYY-MM or YYYY"));
+        }
+    };
+
+    let month = month.unwrap_or(1);
+    if !(1..=12).contains(&month) {
+        return Err(invalid_date(input, "Month must be between 1 and 12"));
+    }
+    // A leap year lets month/day forms without a year accept Feb 29
+    let year = year.map_or(Some(2000), |y| i32::try_from(y).ok());
+    let exists = year
+        .and_then(|y| NaiveDate::from_ymd_opt(y, month, day.unwrap_or(1)))
+        .is_some();
+    if !exists {
+        return Err(invalid_date(
+            input,
+            "That day doesn't exist in the given month",
+        ));
+    }
+    Ok(())
+}
+
+fn is_relative_date(date: &str) -> bool {
+    let words: Vec<&str> = date.split_whitespace().collect();
+    match words.as_slice() {
+        ["today" | "yesterday" | "tomorrow"] => true,
+        ["this" | "last" | "next", unit] => PERIOD_UNITS.contains(unit),
+        ["in", n, unit] => is_count(n) && is_period_unit(unit),
+        [n, unit, "ago" | "ahead"] => is_count(n) && is_period_unit(unit),
+        _ => false,
+    }
+}
+
+fn is_count(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_period_unit(word: &str) -> bool {
+    let singular = word.strip_suffix('s').unwrap_or(word);
+    PERIOD_UNITS.contains(&singular)
+}
+
+/// `q1`..`q4`, optionally preceded by a year (`2024q1`, `2024-q1`)
+fn is_quarter(date: &str) -> bool {
+    let (year, quarter) = match date.find('q') {
+        Some(i) => (&date[..i], &date[i..]),
+        None => return false,
+    };
+    let year = year.strip_suffix('-').unwrap_or(year);
+    matches!(quarter, "q1" | "q2" | "q3" | "q4")
+        && (year.is_empty() || (year.len() == 4 && is_count(year)))
+}
+
+/// Full or three-letter month names, e.g. `jan` or `january`
+fn is_month_name(date: &str) -> bool {
+    MONTHS
+        .iter()
+        .any(|m| date == *m || (date.len() == 3 && m.starts_with(date)))
+}
+
+fn invalid_date(input: &str, hint: &str) -> HLedgerError {
+    HLedgerError::InvalidDate {
+        input: input.to_string(),
+        hint: hint.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_smart_dates() {
+        for date in [
+            "2024-01-31",
+            "2024/1/5",
+            "2024.12.01",
+            "2024-02-29",
+            "2024-02",
+            "2024/2",
+            "2024",
+            "20240131",
+            "202401",
+            "1/31",
+            "2/29",
+            "today",
+            "Yesterday",
+            "tomorrow",
+            "this month",
+            "last year",
+            "next quarter",
+            "in 3 days",
+            "2 weeks ago",
+            "1 month ahead",
+            "q3",
+            "2024q1",
+            "2024-Q4",
+            "jan",
+            "February",
+            " 2024-01-01 ",
+        ] {
+            assert!(
+                validate_smart_date(date).is_ok(),
+                "{:?} should be accepted",
+                date
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_malformed_dates() {
+        for date in [
+            "",
+            "2024-13-01",
+            "2024-02-30",
+            "2023-02-29",
+            "2024-00",
+            "01/02/2024",
+            "2024-01/31",
+            "2024--01",
+            "24-01-01",
+            "13/1",
+            "last fortnight",
+            "in a week",
+            "q5",
+            "sometime",
+        ] {
+            assert!(
+                matches!(
+                    validate_smart_date(date),
+                    Err(HLedgerError::InvalidDate { .. })
+                ),
+                "{:?} should be rejected",
+                date
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_date_hints() {
+        let hint = |date| match validate_smart_date(date) {
+            Err(HLedgerError::InvalidDate { input, hint }) => {
+                assert_eq!(input, date);
+                hint
+            }
+            other => panic!("Expected InvalidDate, got {:?}", other),
+        };
+        assert!(hint("2024-13-01").contains("Month"));
+        assert!(hint("01/02/2024").contains("year first"));
+        assert!(hint("2024-02-30").contains("day"));
+    }
+
+    #[test]
+    fn test_validate_date_options() {
+        assert!(validate_date_options(None, None).is_ok());
+        assert!(validate_date_options(Some("2024-01-01"), Some("2024-02")).is_ok());
+        assert!(validate_date_options(Some("2024-01-01"), Some("2024-13")).is_err());
+        assert!(validate_date_options(Some("Jan 5th"), None).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//! Preferred report settings, applied to whatever options a caller leaves
+//! out
+//!
+//! The app keeps one [`ReportDefaults`] and each report's options arrive as
+//! JSON naming only what the caller chose; [`merge_options`] fills in the
+//! rest, so the frontend needn't repeat the preferences on every call and
+//! equal requests still make equal cache keys.
+
+use crate::interval::ReportInterval;
+use crate::list_mode::ListMode;
+use crate::query::Status;
+use crate::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use ts_rs::TS;
+
+/// How amounts are valued when the caller doesn't say
+///
+/// The lowercase names it once had are still read, as saved settings may
+/// hold them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum ValueMode {
+    /// `--cost`
+    #[serde(alias = "cost")]
+    Cost,
+    /// `--market`
+    #[serde(alias = "market")]
+    Market,
+}
+
+/// Report settings to use unless a call sets them; `None` leaves the
+/// report's own default
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase", default)]
+#[ts(rename_all = "camelCase")]
+pub struct ReportDefaults {
+    pub interval: Option<ReportInterval>,
+    /// Accounts as a tree or a flat list; the `tree` boolean it replaced is
+    /// still read from saved settings
+    #[serde(alias = "tree", deserialize_with = "list_mode_or_tree")]
+    pub list_mode: Option<ListMode>,
+    pub depth: Option<u32>,
+    /// Only postings with one of these statuses; empty means all
+    pub statuses: Vec<Status>,
+    pub value_mode: Option<ValueMode>,
+    /// Convert amounts to this commodity (`--exchange`)
+    pub commodity: Option<String>,
+}
+
+/// A list mode, or the `tree` boolean that stood for one
+fn list_mode_or_tree<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<ListMode>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Setting {
+        Mode(ListMode),
+        Tree(bool),
+    }
+    Ok(match Option::<Setting>::deserialize(deserializer)? {
+        None => None,
+        Some(Setting::Mode(mode)) => Some(mode),
+        Some(Setting::Tree(true)) => Some(ListMode::Tree),
+        Some(Setting::Tree(false)) => Some(ListMode::Flat),
+    })
+}
+
+/// Interval flags older frontends send instead of `interval`
+const LEGACY_INTERVAL_KEYS: [&str; 5] = ["daily", "weekly", "monthly", "quarterly", "yearly"];
+
+/// The option fields a setting covers, and the values it gives them
+type Setting = (Vec<&'static str>, Vec<(&'static str, Value)>);
+
+impl ReportDefaults {
+    /// Each setting made; one is only applied when the caller set none of
+    /// its fields
+    fn settings(&self) -> Vec<Setting> {
+        let mut settings = Vec::new();
+        if let Some(interval) = &self.interval {
+            let mut keys = vec!["interval"];
+            keys.extend(LEGACY_INTERVAL_KEYS);
+            let value = serde_json::to_value(interval).unwrap_or(Value::Null);
+            settings.push((keys, vec![("interval", value)]));
+        }
+        if let Some(mode) = self.list_mode {
+            let value = serde_json::to_value(mode).unwrap_or(Value::Null);
+            settings.push((vec!["listMode", "tree", "flat"], vec![("listMode", value)]));
+        }
+        if let Some(depth) = self.depth {
+            settings.push((vec!["depth"], vec![("depth", depth.into())]));
+        }
+        if !self.statuses.is_empty() {
+            let status = |name, status| (name, Value::Bool(self.statuses.contains(&status)));
+            settings.push((
+                vec!["unmarked", "pending", "cleared"],
+                vec![
+                    status("unmarked", Status::Unmarked),
+                    status("pending", Status::Pending),
+                    status("cleared", Status::Cleared),
+                ],
+            ));
+        }
+        let valuation = ["cost", "market", "exchange", "value"];
+        if let Some(mode) = self.value_mode {
+            settings.push((
+                valuation.to_vec(),
+                vec![
+                    ("cost", Value::Bool(mode == ValueMode::Cost)),
+                    ("market", Value::Bool(mode == ValueMode::Market)),
+                ],
+            ));
+        }
+        if let Some(commodity) = &self.commodity {
+            settings.push((
+                valuation.to_vec(),
+                vec![("exchange", Value::String(commodity.clone()))],
+            ));
+        }
+        settings
+    }
+}
+
+/// Options of type `O` from the fields in `explicit`, then `defaults`, then
+/// `O::default()`
+///
+/// A default only applies to options that have its fields (print has no
+/// `listMode`, say) and that the caller left alone: sending `listMode` as
+/// flat keeps the default tree from applying too.
+pub fn merge_options<O>(defaults: &ReportDefaults, explicit: Value) -> Result<O>
+where
+    O: Default + Serialize + DeserializeOwned,
+{
+    let Value::Object(mut base) = serde_json::to_value(O::default())? else {
+        return Ok(serde_json::from_value(explicit)?);
+    };
+    let explicit = match explicit {
+        Value::Object(explicit) => explicit,
+        Value::Null => Map::new(),
+        other => return Ok(serde_json::from_value(other)?),
+    };
+
+    for (keys, values) in defaults.settings() {
+        // `period` is a legacy interval field only where it isn't an option
+        // of its own
+        let legacy_period = keys.contains(&"interval") && !base.contains_key("period");
+        let set_by_caller = keys.iter().any(|key| explicit.contains_key(*key))
+            || (legacy_period && explicit.contains_key("period"));
+        if set_by_caller {
+            continue;
+        }
+        for (key, value) in values {
+            if base.contains_key(key) {
+                base.insert(key.to_string(), value);
+            }
+        }
+    }
+    base.extend(explicit);
+    Ok(serde_json::from_value(Value::Object(base))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AccountsOptions, BalanceOptions, BalanceSheetOptions, BudgetOptions, CashflowOptions,
+        FlowOptions, ImportOptions, IncomeStatementOptions, NetworthOptions, PayeeStatsOptions,
+        PricesOptions, PrintOptions, TagsOptions, TopSpendingOptions, WriteOptions,
+    };
+    use serde_json::json;
+
+    fn preferred() -> ReportDefaults {
+        ReportDefaults {
+            interval: Some(ReportInterval::Monthly),
+            list_mode: Some(ListMode::Tree),
+            depth: Some(2),
+            statuses: vec![Status::Cleared],
+            value_mode: None,
+            commodity: None,
+        }
+    }
+
+    #[test]
+    fn export_bindings() {
+        ReportDefaults::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_defaults_fill_unset_options() {
+        let options: BalanceOptions = merge_options(&preferred(), json!({})).unwrap();
+        assert_eq!(options.interval, ReportInterval::Monthly);
+        assert_eq!(options.list_mode, Some(ListMode::Tree));
+        assert_eq!(options.depth, Some(2));
+        assert!(options.cleared && !options.pending && !options.unmarked);
+
+        let none: BalanceOptions = merge_options(&ReportDefaults::default(), json!(null)).unwrap();
+        assert_eq!(
+            serde_json::to_value(none).unwrap(),
+            serde_json::to_value(BalanceOptions::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_explicit_options_take_precedence() {
+        let options: BalanceOptions = merge_options(
+            &preferred(),
+            json!({ "listMode": "Flat", "depth": null, "interval": { "type": "Yearly" }, "pending": true }),
+        )
+        .unwrap();
+        // Each setting the call touched is left entirely to the call
+        assert_eq!(options.list_mode, Some(ListMode::Flat));
+        assert_eq!(options.depth, None);
+        assert_eq!(options.interval, ReportInterval::Yearly);
+        assert!(options.pending && !options.cleared);
+
+        // A legacy interval flag counts as setting the interval
+        let options: BalanceOptions =
+            merge_options(&preferred(), json!({ "quarterly": true })).unwrap();
+        assert_eq!(options.interval, ReportInterval::Quarterly);
+
+        // So does a legacy `flat`, which then decides the list mode
+        let options: BalanceOptions = merge_options(&preferred(), json!({ "flat": true })).unwrap();
+        assert_eq!(options.list_mode, Some(ListMode::Flat));
+    }
+
+    #[test]
+    fn test_defaults_only_touch_existing_fields() {
+        let defaults = ReportDefaults {
+            value_mode: Some(ValueMode::Market),
+            commodity: Some("EUR".to_string()),
+            ..preferred()
+        };
+        let print: PrintOptions = merge_options(&defaults, json!({ "explicit": true })).unwrap();
+        assert!(print.explicit && print.cleared);
+
+        let balance: BalanceOptions = merge_options(&defaults, json!({})).unwrap();
+        assert!(balance.market && !balance.cost);
+        assert_eq!(balance.exchange.as_deref(), Some("EUR"));
+
+        // Cashflow has no status filters
+        let cashflow: CashflowOptions = merge_options(&defaults, json!({})).unwrap();
+        assert_eq!(cashflow.depth, Some(2));
+    }
+
+    #[test]
+    fn test_list_mode_reads_saved_tree_setting() {
+        let read = |json| {
+            serde_json::from_value::<ReportDefaults>(json)
+                .unwrap()
+                .list_mode
+        };
+        assert_eq!(read(json!({ "tree": true })), Some(ListMode::Tree));
+        assert_eq!(read(json!({ "tree": false })), Some(ListMode::Flat));
+        assert_eq!(read(json!({ "listMode": "Flat" })), Some(ListMode::Flat));
+        assert_eq!(read(json!({ "listMode": "flat" })), Some(ListMode::Flat));
+        assert_eq!(read(json!({})), None);
+
+        let json = serde_json::to_value(ReportDefaults {
+            list_mode: Some(ListMode::Tree),
+            ..ReportDefaults::default()
+        })
+        .unwrap();
+        assert_eq!(json["listMode"], "Tree");
+        assert!(json.get("tree").is_none());
+    }
+
+    #[test]
+    fn test_new_options_match_default() {
+        // Options built in Rust and options filled in from JSON must mean the same
+        macro_rules! assert_new_is_default {
+            ($($ty:ty),* $(,)?) => {$(
+                assert_eq!(
+                    serde_json::to_value(<$ty>::new()).unwrap(),
+                    serde_json::to_value(<$ty>::default()).unwrap(),
+                    stringify!($ty)
+                );
+            )*};
+        }
+        assert_new_is_default!(
+            AccountsOptions,
+            BalanceOptions,
+            BalanceSheetOptions,
+            BudgetOptions,
+            CashflowOptions,
+            FlowOptions,
+            ImportOptions,
+            IncomeStatementOptions,
+            NetworthOptions,
+            PayeeStatsOptions,
+            PricesOptions,
+            PrintOptions,
+            TagsOptions,
+            TopSpendingOptions,
+            WriteOptions,
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::amount::MultiAmount;
+use crate::commands::print::{PrintTransaction, SourceSpan};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// How closely two transactions must match to be reported as duplicates
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DuplicateTolerance {
+    /// Days the dates may differ by; 0 requires the same date
+    pub date_days: u32,
+    /// Character edits the normalized descriptions may differ by; `None`
+    /// requires them to be equal
+    pub max_edit_distance: Option<u32>,
+}
+
+/// A transaction that may duplicate the others in its group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DuplicateCandidate {
+    /// The transaction's index in the journal
+    pub index: u32,
+    #[ts(type = "string")]
+    pub date: NaiveDate,
+    pub description: String,
+    /// Where the transaction is written, for deleting it
+    pub source_span: Option<SourceSpan>,
+}
+
+/// Transactions that look like copies of each other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// At least two transactions, in date then index order
+    pub transactions: Vec<DuplicateCandidate>,
+}
+
+// Implementation for builder pattern
+impl DuplicateTolerance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn date_days(mut self, days: u32) -> Self {
+        self.date_days = days;
+        self
+    }
+
+    pub fn max_edit_distance(mut self, edits: u32) -> Self {
+        self.max_edit_distance = Some(edits);
+        self
+    }
+}
+
+/// Group transactions that are probably the same one entered twice, e.g. by
+/// overlapping CSV imports
+///
+/// Two transactions match when their dates are within `date_days` of each
+/// other, they move the same amount in every commodity (the sum of their
+/// positive postings), and their descriptions are equal after lowercasing and
+/// collapsing whitespace, or within `max_edit_distance` edits. Matches chain,
+/// so a group may span more than `date_days` from end to end. Pass print
+/// output with `explicit` set so inferred amounts are counted; transactions
+/// without amounts are never grouped. Groups come in order of their first
+/// transaction.
+pub fn find_duplicate_candidates(
+    transactions: &[PrintTransaction],
+    tolerance: DuplicateTolerance,
+) -> Vec<DuplicateGroup> {
+    let keys: Vec<(MultiAmount, String)> = transactions
+        .iter()
+        .map(|txn| (gross_amount(txn), normalize_description(&txn.description)))
+        .collect();
+
+    let mut order: Vec<usize> = (0..transactions.len()).collect();
+    order.sort_by_key(|&i| (transactions[i].date, transactions[i].index));
+
+    // Union-find over positions in `order`
+    let mut parent: Vec<usize> = (0..order.len()).collect();
+    for a in 0..order.len() {
+        let (txn_a, (amount_a, desc_a)) = (&transactions[order[a]], &keys[order[a]]);
+        if amount_a.is_zero() {
+            continue;
+        }
+        for b in a + 1..order.len() {
+            let (txn_b, (amount_b, desc_b)) = (&transactions[order[b]], &keys[order[b]]);
+            if (txn_b.date - txn_a.date).num_days() > i64::from(tolerance.date_days) {
+                break;
+            }
+            if amount_a == amount_b && descriptions_match(desc_a, desc_b, tolerance) {
+                let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+                parent[root_b.max(root_a)] = root_a.min(root_b);
+            }
+        }
+    }
+
+    // Positions come in date order and roots are the earliest position of
+    // their group, so groups are created in order of their first transaction
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    let mut group_of_root: Vec<Option<usize>> = vec![None; order.len()];
+    for position in 0..order.len() {
+        let root = find(&mut parent, position);
+        let txn = &transactions[order[position]];
+        let candidate = DuplicateCandidate {
+            index: txn.index,
+            date: txn.date,
+            description: txn.description.clone(),
+            source_span: txn.source_span.clone(),
+        };
+        match group_of_root[root] {
+            Some(group) => groups[group].transactions.push(candidate),
+            None => {
+                group_of_root[root] = Some(groups.len());
+                groups.push(DuplicateGroup {
+                    transactions: vec![candidate],
+                });
+            }
+        }
+    }
+    groups.retain(|group| group.transactions.len() > 1);
+    groups
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// What the transaction moves: its positive postings summed per commodity
+fn gross_amount(txn: &PrintTransaction) -> MultiAmount {
+    let mut total = MultiAmount::new();
+    for posting in &txn.postings {
+        for amount in &posting.amounts {
+            if amount.quantity.is_sign_positive() {
+                total.add_quantity(&amount.commodity, amount.quantity);
+            }
+        }
+    }
+    total
+}
+
+/// Lowercase with runs of whitespace collapsed to one space
+fn normalize_description(description: &str) -> String {
+    description
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn descriptions_match(a: &str, b: &str, tolerance: DuplicateTolerance) -> bool {
+    match tolerance.max_edit_distance {
+        None => a == b,
+        Some(max) => a == b || levenshtein(a, b) <= max as usize,
+    }
+}
+
+/// Number of single-character insertions, deletions and substitutions
+/// turning `a` into `b`
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+
+    #[test]
+    fn export_bindings() {
+        DuplicateTolerance::export_all().unwrap();
+        DuplicateCandidate::export_all().unwrap();
+        DuplicateGroup::export_all().unwrap();
+    }
+
+    fn transaction(index: u32, date: &str, description: &str, dollars: &str) -> PrintTransaction {
+        PrintTransactionBuilder::new(index, date)
+            .description(description)
+            .posting(PrintPostingBuilder::new("expenses:food").amount("$", dollars))
+            .posting(
+                PrintPostingBuilder::new("assets:checking").amount("$", &format!("-{}", dollars)),
+            )
+            .span("import.journal", index * 3, index * 3 + 3)
+            .build()
+    }
+
+    fn indices(groups: &[DuplicateGroup]) -> Vec<Vec<u32>> {
+        groups
+            .iter()
+            .map(|g| g.transactions.iter().map(|t| t.index).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_exact_duplicates() {
+        let transactions = vec![
+            transaction(1, "2024-03-01", "Grocer", "42.10"),
+            transaction(2, "2024-03-02", "Coffee", "3.50"),
+            transaction(3, "2024-03-01", "Grocer", "42.10"),
+        ];
+        let groups = find_duplicate_candidates(&transactions, DuplicateTolerance::new());
+        assert_eq!(indices(&groups), vec![vec![1, 3]]);
+
+        let spans: Vec<u32> = groups[0]
+            .transactions
+            .iter()
+            .map(|t| t.source_span.as_ref().unwrap().start_line)
+            .collect();
+        assert_eq!(spans, vec![3, 9]);
+    }
+
+    #[test]
+    fn test_near_date_duplicates() {
+        let transactions = vec![
+            transaction(1, "2024-03-01", "ACME  Corp", "100"),
+            transaction(2, "2024-03-03", "acme corp", "100"),
+            transaction(3, "2024-03-10", "Acme Corp", "100"),
+        ];
+        assert!(find_duplicate_candidates(&transactions, DuplicateTolerance::new()).is_empty());
+
+        let tolerance = DuplicateTolerance::new().date_days(2);
+        assert_eq!(
+            indices(&find_duplicate_candidates(&transactions, tolerance)),
+            vec![vec![1, 2]]
+        );
+    }
+
+    #[test]
+    fn test_similar_descriptions() {
+        let transactions = vec![
+            transaction(1, "2024-03-01", "AMAZON MKTPLACE", "19.99"),
+            transaction(2, "2024-03-01", "Amazon Mktplce", "19.99"),
+        ];
+        assert!(find_duplicate_candidates(&transactions, DuplicateTolerance::new()).is_empty());
+
+        let tolerance = DuplicateTolerance::new().max_edit_distance(1);
+        assert_eq!(
+            indices(&find_duplicate_candidates(&transactions, tolerance)),
+            vec![vec![1, 2]]
+        );
+    }
+
+    #[test]
+    fn test_false_positive_guards() {
+        let tolerance = DuplicateTolerance::new().date_days(3).max_edit_distance(2);
+        let transactions = vec![
+            // Same payee and day, different amounts
+            transaction(1, "2024-03-01", "Coffee", "3.50"),
+            transaction(2, "2024-03-01", "Coffee", "4.50"),
+            // Same amount and day, unrelated descriptions
+            transaction(3, "2024-03-05", "Rent", "1200"),
+            transaction(4, "2024-03-05", "Tuition", "1200"),
+            // Same amount and description, a month apart
+            transaction(5, "2024-03-15", "Gym", "30"),
+            transaction(6, "2024-04-15", "Gym", "30"),
+        ];
+        assert!(find_duplicate_candidates(&transactions, tolerance).is_empty());
+    }
+
+    #[test]
+    fn test_groups_chain_and_keep_order() {
+        let transactions = vec![
+            transaction(5, "2024-03-07", "Bakery", "8"),
+            transaction(2, "2024-03-01", "Bakery", "8"),
+            transaction(9, "2024-03-04", "Bakery", "8"),
+            transaction(7, "2024-02-01", "Cinema", "12"),
+            transaction(8, "2024-02-01", "Cinema", "12"),
+        ];
+        let tolerance = DuplicateTolerance::new().date_days(3);
+        assert_eq!(
+            indices(&find_duplicate_candidates(&transactions, tolerance)),
+            vec![vec![7, 8], vec![2, 9, 5]]
+        );
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+}
@@ -0,0 +1,221 @@
+//! Which text encoding a journal is in, and converting it to the UTF-8
+//! hledger reads
+//!
+//! hledger fails on anything else with a parse error that doesn't say why,
+//! so files are checked before they're handed to it.
+
+use crate::write::{
+    ensure_unchanged, ensure_writable, resolve_path, write_atomically, WriteHook, WriteOptions,
+};
+use crate::Result;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use ts_rs::TS;
+
+/// How much of a file without a byte order mark is looked at for UTF-16
+const UTF16_SAMPLE: usize = 4096;
+
+/// The text encoding of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct JournalEncoding {
+    /// The encoding's WHATWG name: `UTF-8`, `UTF-16LE`, `UTF-16BE` or
+    /// `windows-1252` (which covers Latin-1)
+    pub name: String,
+    /// Whether the file starts with a byte order mark
+    pub bom: bool,
+}
+
+impl JournalEncoding {
+    fn new(encoding: &'static Encoding, bom: bool) -> Self {
+        Self {
+            name: encoding.name().to_string(),
+            bom,
+        }
+    }
+
+    /// Whether hledger can't read the file as it is; it skips a UTF-8 byte
+    /// order mark
+    pub fn needs_conversion(&self) -> bool {
+        self.name != UTF_8.name()
+    }
+
+    /// UTF-16 text is full of NUL bytes without being binary
+    pub(crate) fn is_utf16(&self) -> bool {
+        self.name == UTF_16LE.name() || self.name == UTF_16BE.name()
+    }
+
+    fn encoding(&self) -> &'static Encoding {
+        Encoding::for_label(self.name.as_bytes()).unwrap_or(UTF_8)
+    }
+}
+
+/// The encoding `path` is in
+///
+/// A byte order mark decides it. Without one, a file that is valid UTF-8 is
+/// UTF-8; one with NUL bytes in every other position is UTF-16; anything
+/// else is taken for windows-1252, the usual encoding of older journals.
+pub fn detect_journal_encoding(path: &Path) -> Result<JournalEncoding> {
+    Ok(detect_encoding(&fs::read(path)?))
+}
+
+pub(crate) fn detect_encoding(bytes: &[u8]) -> JournalEncoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return JournalEncoding::new(encoding, true);
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return JournalEncoding::new(UTF_8, false);
+    }
+    if let Some(encoding) = utf16_without_bom(&bytes[..bytes.len().min(UTF16_SAMPLE)]) {
+        return JournalEncoding::new(encoding, false);
+    }
+    JournalEncoding::new(WINDOWS_1252, false)
+}
+
+/// UTF-16 if most two-byte units of `sample` have a NUL on one side only,
+/// as mostly-ASCII text does
+fn utf16_without_bom(sample: &[u8]) -> Option<&'static Encoding> {
+    let units = sample.len() / 2;
+    if units == 0 {
+        return None;
+    }
+    let (mut high_nul, mut low_nul) = (0, 0);
+    for unit in sample.chunks_exact(2) {
+        match (unit[0] == 0, unit[1] == 0) {
+            (true, false) => high_nul += 1,
+            (false, true) => low_nul += 1,
+            _ => {}
+        }
+    }
+    if low_nul * 2 > units {
+        Some(UTF_16LE)
+    } else if high_nul * 2 > units {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Rewrite `path` in UTF-8 without a byte order mark, returning the
+/// encoding it was in
+///
+/// Line endings are kept. A file hledger can already read is left alone.
+/// Otherwise the write goes through a temporary file and a rename like the
+/// other writes, with `options.before_write` (e.g. a backup) called first;
+/// `expect_unchanged` is honoured, the other options don't apply.
+pub fn convert_journal_to_utf8(path: &Path, options: &WriteOptions) -> Result<JournalEncoding> {
+    let path = resolve_path(path)?;
+    let original = fs::read(&path)?;
+    let detected = detect_encoding(&original);
+    if !detected.needs_conversion() {
+        return Ok(detected);
+    }
+    ensure_unchanged(&path, &original, options)?;
+    ensure_writable(&path)?;
+
+    let text = decode(&original, &detected);
+    WriteHook::call(options.before_write.as_ref(), &path)?;
+    write_atomically(&path, text.as_bytes())?;
+    Ok(detected)
+}
+
+/// `bytes` as text, without the byte order mark
+pub(crate) fn decode(bytes: &[u8], detected: &JournalEncoding) -> String {
+    let bom = match Encoding::for_bom(bytes) {
+        Some((_, len)) if detected.bom => len,
+        _ => 0,
+    };
+    let (text, _) = detected
+        .encoding()
+        .decode_without_bom_handling(&bytes[bom..]);
+    text.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "; Café expenses, in the old encoding\n\n2023-03-04 Crêperie\n    \
+                        expenses:food  EUR 12.50\n    assets:cash\n";
+
+    #[test]
+    fn export_bindings() {
+        JournalEncoding::export_all().unwrap();
+    }
+
+    fn detect(fixture: &str) -> JournalEncoding {
+        detect_journal_encoding(Path::new(&format!("tests/fixtures/{}", fixture))).unwrap()
+    }
+
+    #[test]
+    fn test_detect_fixtures() {
+        let plain = detect("test.journal");
+        assert_eq!((plain.name.as_str(), plain.bom), ("UTF-8", false));
+        assert!(!plain.needs_conversion());
+
+        let bom = detect("utf8_bom.journal");
+        assert_eq!((bom.name.as_str(), bom.bom), ("UTF-8", true));
+        assert!(!bom.needs_conversion());
+
+        let latin1 = detect("latin1.journal");
+        assert_eq!((latin1.name.as_str(), latin1.bom), ("windows-1252", false));
+        assert!(latin1.needs_conversion());
+
+        let utf16 = detect("utf16.journal");
+        assert_eq!((utf16.name.as_str(), utf16.bom), ("UTF-16LE", true));
+        assert!(utf16.needs_conversion() && utf16.is_utf16());
+    }
+
+    #[test]
+    fn test_detect_utf16_without_bom() {
+        let le: Vec<u8> = TEXT.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = TEXT.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(detect_encoding(&le).name, "UTF-16LE");
+        assert_eq!(detect_encoding(&be).name, "UTF-16BE");
+        assert_eq!(decode(&be, &detect_encoding(&be)), TEXT);
+    }
+
+    #[test]
+    fn test_decode_fixtures() {
+        for fixture in ["latin1.journal", "utf8_bom.journal", "utf16.journal"] {
+            let bytes = fs::read(format!("tests/fixtures/{}", fixture)).unwrap();
+            assert_eq!(
+                decode(&bytes, &detect_encoding(&bytes)),
+                TEXT,
+                "{}",
+                fixture
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_with_hook() {
+        let path = std::env::temp_dir().join(format!("convert-{}.journal", std::process::id()));
+        let latin1 = fs::read("tests/fixtures/latin1.journal").unwrap();
+        fs::write(&path, &latin1).unwrap();
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hook = {
+            let seen = seen.clone();
+            WriteHook::new(move |path| {
+                seen.lock().unwrap().push(fs::read(path)?);
+                Ok(())
+            })
+        };
+        let options = WriteOptions::new().before_write(hook);
+        let converted = convert_journal_to_utf8(&path, &options).unwrap();
+        assert_eq!(converted.name, "windows-1252");
+        assert_eq!(fs::read_to_string(&path).unwrap(), TEXT);
+        assert_eq!(*seen.lock().unwrap(), [latin1]);
+
+        // Already UTF-8: nothing to write
+        let again = convert_journal_to_utf8(&path, &options).unwrap();
+        assert!(!again.needs_conversion());
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+}
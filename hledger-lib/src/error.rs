@@ -1,15 +1,83 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use ts_rs::TS;
 
 #[derive(Error, Debug)]
 pub enum HLedgerError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
-    #[error("HLedger command failed with exit code {code}: {stderr}")]
-    CommandFailed { code: i32, stderr: String },
+    #[error("HLedger command `{command}` failed with exit code {code}: {stderr}")]
+    CommandFailed {
+        command: String,
+        code: i32,
+        stderr: String,
+    },
 
-    #[error("HLedger executable not found")]
-    HLedgerNotFound,
+    #[error("Journal error at {file}:{line}: {message}")]
+    JournalError {
+        file: String,
+        line: u32,
+        column: Option<u32>,
+        message: String,
+        excerpt: Option<String>,
+    },
+
+    #[error("Invalid date '{input}': {hint}")]
+    InvalidDate { input: String, hint: String },
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("Invalid CSV rules: {0}")]
+    InvalidCsvRules(String),
+
+    #[error("Delimiter '{delimiter}' doesn't work for {file}: {hint}")]
+    CsvDelimiter {
+        file: String,
+        delimiter: String,
+        hint: String,
+    },
+
+    #[error("Can't read '{value}' at {file}:{line} as a date in the format {format}")]
+    CsvDate {
+        file: String,
+        line: u32,
+        column: u32,
+        value: String,
+        format: String,
+    },
+
+    #[error("{file} changed on disk since it was read")]
+    FileChanged { file: String },
+
+    #[error("{flag} requires hledger {required} or newer, but {installed} is installed")]
+    UnsupportedFeature {
+        flag: String,
+        required: String,
+        installed: String,
+    },
+
+    #[error("{operation} is not supported by the {backend} backend")]
+    UnsupportedByBackend { backend: String, operation: String },
+
+    #[error("HTTP request to {url} failed: {message}")]
+    Http { url: String, message: String },
+
+    #[error("SQLite error in {path}: {message}")]
+    Sqlite { path: String, message: String },
+
+    #[error("HLedger command timed out after {elapsed:?}")]
+    Timeout { elapsed: std::time::Duration },
+
+    #[error("HLedger command was cancelled")]
+    Cancelled,
+
+    #[error("HLedger executable not found: {}", describe_missing(.attempted))]
+    HLedgerNotFound { attempted: String },
+
+    #[error("HLedger at '{path}' is not executable (permission denied)")]
+    HLedgerNotExecutable { path: String },
 
     #[error("Invalid UTF-8 in hledger output: {0}")]
     InvalidUtf8(#[from] std::string::FromUtf8Error),
@@ -17,6 +85,630 @@ pub enum HLedgerError {
     #[error("JSON parsing error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
     #[error("Parse error: {0}")]
     ParseError(String),
 }
+
+impl HLedgerError {
+    /// Build the error for a failed hledger run
+    ///
+    /// Journal errors are reported by hledger as
+    /// `hledger: Error: FILE:LINE[:COLUMN][-ENDLINE]:` followed by an optional
+    /// source excerpt (lines with a `|` gutter) and the message. Those become
+    /// [`HLedgerError::JournalError`]; anything else is `CommandFailed`.
+    pub fn from_failure(command: String, code: i32, stderr: String) -> Self {
+        parse_journal_error(&stderr).unwrap_or(HLedgerError::CommandFailed {
+            command,
+            code,
+            stderr,
+        })
+    }
+}
+
+/// Say where a missing binary was looked for: at the given path, or on PATH
+/// for a bare program name
+fn describe_missing(attempted: &str) -> String {
+    if std::path::Path::new(attempted).components().count() > 1 {
+        format!("no file at '{}'", attempted)
+    } else {
+        format!("'{}' is not on PATH", attempted)
+    }
+}
+
+fn parse_journal_error(stderr: &str) -> Option<HLedgerError> {
+    let mut lines = stderr.lines();
+    let header = lines.by_ref().find(|l| l.starts_with("hledger"))?;
+    let header = header
+        .strip_prefix("hledger: Error: ")
+        .or_else(|| header.strip_prefix("hledger: "))?;
+
+    let (file, line, column, inline_message) = parse_location_header(header)?;
+
+    let mut excerpt = Vec::new();
+    let mut message = Vec::new();
+    if !inline_message.is_empty() {
+        message.push(inline_message.to_string());
+    }
+    for l in lines {
+        if message.is_empty() && is_excerpt_line(l) {
+            excerpt.push(l);
+        } else if !l.trim().is_empty() || !message.is_empty() {
+            message.push(l.to_string());
+        }
+    }
+    while message.last().is_some_and(|l| l.trim().is_empty()) {
+        message.pop();
+    }
+
+    Some(HLedgerError::JournalError {
+        file: file.to_string(),
+        line,
+        column,
+        message: message.join("\n"),
+        excerpt: (!excerpt.is_empty()).then(|| excerpt.join("\n")),
+    })
+}
+
+/// Split `FILE:LINE[:COL][-END]:[ MESSAGE]` into its parts
+pub(crate) fn parse_location_header(header: &str) -> Option<(&str, u32, Option<u32>, &str)> {
+    for (i, _) in header.match_indices(':') {
+        let file = &header[..i];
+        if file.is_empty() {
+            continue;
+        }
+        // A colon not followed by a line number is part of the path
+        // (e.g. a Windows drive letter), so keep scanning
+        let Some((line, rest)) = take_number(&header[i + 1..]) else {
+            continue;
+        };
+        let (column, rest) = match rest.strip_prefix(':').and_then(take_number) {
+            Some((column, rest)) => (Some(column), rest),
+            None => (None, rest),
+        };
+        // Ranges (`8-10` or `5:3-9`) only keep their start
+        let rest = match rest.strip_prefix('-').and_then(take_number) {
+            Some((_, rest)) => rest,
+            None => rest,
+        };
+
+        if let Some(message) = rest.strip_prefix(':') {
+            return Some((file, line, column, message.trim()));
+        }
+        if rest.is_empty() {
+            return Some((file, line, column, ""));
+        }
+    }
+    None
+}
+
+fn take_number(s: &str) -> Option<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let number = s[..end].parse().ok()?;
+    Some((number, &s[end..]))
+}
+
+/// stderr without the journal excerpt lines hledger quotes in errors, so it
+/// can be logged without leaking journal content
+#[cfg(feature = "tracing")]
+pub(crate) fn without_excerpts(stderr: &str) -> String {
+    stderr
+        .lines()
+        .filter(|line| !is_excerpt_line(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether a journal error's message is hledger's for a failing balance
+/// assertion
+pub(crate) fn is_assertion_failure(message: &str) -> bool {
+    message.to_lowercase().contains("balance assertion failed")
+}
+
+/// Excerpt lines look like `5 | 2024-01-05 expenses` or `  |     ^^^`
+fn is_excerpt_line(line: &str) -> bool {
+    match line.split_once('|') {
+        Some((gutter, _)) => gutter.trim().chars().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// What kind of failure an [`AppError`] is, one per [`HLedgerError`]
+/// variant plus the app's own failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum ErrorCode {
+    /// A file, usually a journal, doesn't exist
+    FileNotFound,
+    Io,
+    CommandFailed,
+    /// hledger rejected the journal at a known line
+    JournalError,
+    /// A balance assertion in the journal didn't hold; the report can be
+    /// run again with `ignore_assertions`
+    AssertionFailed,
+    InvalidDate,
+    InvalidTransaction,
+    InvalidCsvRules,
+    /// The CSV delimiter is wrong for the file
+    CsvDelimiter,
+    /// A CSV row's date doesn't read with the date format, at a known line
+    /// and column
+    CsvDate,
+    FileChanged,
+    UnsupportedFeature,
+    UnsupportedByBackend,
+    Http,
+    Sqlite,
+    Timeout,
+    Cancelled,
+    HledgerNotFound,
+    HledgerNotExecutable,
+    InvalidUtf8,
+    Json,
+    Csv,
+    ParseError,
+    /// Anything that didn't come from the library, e.g. a failed dialog
+    Other,
+}
+
+/// Where and how an [`AppError`] happened, as far as the error knows
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct ErrorDetails {
+    /// The journal, or other file, the error is about
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    /// Journal lines hledger quoted around the error
+    pub excerpt: Option<String>,
+    /// The hledger command line that failed
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+    pub stderr: Option<String>,
+    /// The hledger binary that was looked for or couldn't run
+    pub hledger_path: Option<String>,
+    /// How long the command ran before it was stopped
+    pub elapsed_ms: Option<u64>,
+}
+
+/// An error as the app's frontend receives it: a code to decide what to
+/// offer (the hledger path setting, a jump to the journal line), a message
+/// to show and whatever details the error carried
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<ErrorDetails>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// The same error with `prefix` before its message, e.g. to say what
+    /// was being done
+    pub fn context(mut self, prefix: impl std::fmt::Display) -> Self {
+        self.message = format!("{}: {}", prefix, self.message);
+        self
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Other, message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        Self::new(ErrorCode::Other, message)
+    }
+}
+
+impl From<HLedgerError> for AppError {
+    fn from(e: HLedgerError) -> Self {
+        use ErrorCode::*;
+        let message = e.to_string();
+        let (code, details) = match e {
+            HLedgerError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => (FileNotFound, None),
+            HLedgerError::Io(_) => (Io, None),
+            HLedgerError::CommandFailed {
+                command,
+                code,
+                stderr,
+            } => {
+                // hledger's own message for a journal it can't open
+                let missing = stderr.contains("openFile:") && stderr.contains("does not exist");
+                let details = ErrorDetails {
+                    command: Some(command),
+                    exit_code: Some(code),
+                    stderr: Some(stderr),
+                    ..ErrorDetails::default()
+                };
+                (
+                    if missing { FileNotFound } else { CommandFailed },
+                    Some(details),
+                )
+            }
+            HLedgerError::JournalError {
+                file,
+                line,
+                column,
+                message,
+                excerpt,
+            } => (
+                if is_assertion_failure(&message) {
+                    AssertionFailed
+                } else {
+                    JournalError
+                },
+                Some(ErrorDetails {
+                    file: Some(file),
+                    line: Some(line),
+                    column,
+                    excerpt,
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::InvalidDate { .. } => (InvalidDate, None),
+            HLedgerError::InvalidTransaction(_) => (InvalidTransaction, None),
+            HLedgerError::InvalidCsvRules(_) => (InvalidCsvRules, None),
+            HLedgerError::CsvDelimiter { file, .. } => (
+                CsvDelimiter,
+                Some(ErrorDetails {
+                    file: Some(file),
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::CsvDate {
+                file, line, column, ..
+            } => (
+                CsvDate,
+                Some(ErrorDetails {
+                    file: Some(file),
+                    line: Some(line),
+                    column: Some(column),
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::FileChanged { file } => (
+                FileChanged,
+                Some(ErrorDetails {
+                    file: Some(file),
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::UnsupportedFeature { .. } => (UnsupportedFeature, None),
+            HLedgerError::UnsupportedByBackend { .. } => (UnsupportedByBackend, None),
+            HLedgerError::Http { .. } => (Http, None),
+            HLedgerError::Sqlite { path, .. } => (
+                Sqlite,
+                Some(ErrorDetails {
+                    file: Some(path),
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::Timeout { elapsed } => (
+                Timeout,
+                Some(ErrorDetails {
+                    elapsed_ms: Some(elapsed.as_millis() as u64),
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::Cancelled => (Cancelled, None),
+            HLedgerError::HLedgerNotFound { attempted } => (
+                HledgerNotFound,
+                Some(ErrorDetails {
+                    hledger_path: Some(attempted),
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::HLedgerNotExecutable { path } => (
+                HledgerNotExecutable,
+                Some(ErrorDetails {
+                    hledger_path: Some(path),
+                    ..ErrorDetails::default()
+                }),
+            ),
+            HLedgerError::InvalidUtf8(_) => (InvalidUtf8, None),
+            HLedgerError::Json(_) => (Json, None),
+            HLedgerError::Csv(_) => (Csv, None),
+            HLedgerError::ParseError(_) => (ParseError, None),
+        };
+        Self {
+            code,
+            message,
+            details,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMAND: &str = "hledger -f main.journal print --output-format=json";
+
+    #[test]
+    fn test_parse_unbalanced_transaction_error() {
+        // hledger -f tests/fixtures/unbalanced.journal balance
+        let stderr = "hledger: Error: /tmp/unbalanced.journal:1-3:
+1 | 2024-01-05 groceries
+  |     expenses:groceries             $20
+  |     assets:bank:checking          $-15
+
+This transaction is unbalanced.
+The real postings' sum should be 0 but is: $5
+";
+        match HLedgerError::from_failure(COMMAND.to_string(), 1, stderr.to_string()) {
+            HLedgerError::JournalError {
+                file,
+                line,
+                column,
+                message,
+                excerpt,
+            } => {
+                assert_eq!(file, "/tmp/unbalanced.journal");
+                assert_eq!(line, 1);
+                assert_eq!(column, None);
+                assert_eq!(
+                    message,
+                    "This transaction is unbalanced.\nThe real postings' sum should be 0 but is: $5"
+                );
+                let excerpt = excerpt.unwrap();
+                assert_eq!(excerpt.lines().count(), 3);
+                assert!(excerpt.starts_with("1 | 2024-01-05 groceries"));
+            }
+            other => panic!("Expected JournalError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_bad_date_error() {
+        // hledger -f tests/fixtures/bad_date.journal print
+        let stderr = "hledger: Error: /tmp/bad_date.journal:1:6:
+  |
+1 | 2024-13-01 bad date
+  |      ^^^^^
+invalid date: month must be 1-12
+";
+        match HLedgerError::from_failure(COMMAND.to_string(), 1, stderr.to_string()) {
+            HLedgerError::JournalError {
+                file,
+                line,
+                column,
+                message,
+                excerpt,
+            } => {
+                assert_eq!(file, "/tmp/bad_date.journal");
+                assert_eq!(line, 1);
+                assert_eq!(column, Some(6));
+                assert_eq!(message, "invalid date: month must be 1-12");
+                assert_eq!(excerpt.unwrap().lines().count(), 3);
+            }
+            other => panic!("Expected JournalError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_windows_path_with_inline_message() {
+        let stderr = "hledger: Error: C:\\Users\\me\\main.journal:12:3: unexpected 'x'\n";
+        match HLedgerError::from_failure(COMMAND.to_string(), 1, stderr.to_string()) {
+            HLedgerError::JournalError {
+                file,
+                line,
+                column,
+                message,
+                excerpt,
+            } => {
+                assert_eq!(file, "C:\\Users\\me\\main.journal");
+                assert_eq!(line, 12);
+                assert_eq!(column, Some(3));
+                assert_eq!(message, "unexpected 'x'");
+                assert!(excerpt.is_none());
+            }
+            other => panic!("Expected JournalError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unrecognized_stderr_falls_back_to_command_failed() {
+        let stderr = "hledger: Error: Unknown flag: --bogus\n";
+        match HLedgerError::from_failure(COMMAND.to_string(), 2, stderr.to_string()) {
+            HLedgerError::CommandFailed {
+                command,
+                code,
+                stderr,
+            } => {
+                assert_eq!(command, COMMAND);
+                assert_eq!(code, 2);
+                assert!(stderr.contains("--bogus"));
+            }
+            other => panic!("Expected CommandFailed, got {:?}", other),
+        }
+
+        assert!(matches!(
+            HLedgerError::from_failure(COMMAND.to_string(), 1, String::new()),
+            HLedgerError::CommandFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_command_failed_display_includes_command() {
+        let err = HLedgerError::from_failure(
+            COMMAND.to_string(),
+            1,
+            "hledger: Error: main.journal: openFile: does not exist".to_string(),
+        );
+        assert_eq!(
+            err.to_string(),
+            "HLedger command `hledger -f main.journal print --output-format=json` failed with exit code 1: hledger: Error: main.journal: openFile: does not exist"
+        );
+    }
+
+    #[test]
+    fn test_not_found_display_distinguishes_path_lookup() {
+        let err = HLedgerError::HLedgerNotFound {
+            attempted: "hledger".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "HLedger executable not found: 'hledger' is not on PATH"
+        );
+
+        let err = HLedgerError::HLedgerNotFound {
+            attempted: "/opt/hledger/bin/hledger".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "HLedger executable not found: no file at '/opt/hledger/bin/hledger'"
+        );
+    }
+
+    #[test]
+    fn export_bindings() {
+        AppError::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_journal_error_keeps_location() {
+        let e = AppError::from(HLedgerError::JournalError {
+            file: "/books/main.journal".to_string(),
+            line: 120,
+            column: Some(4),
+            message: "unbalanced".to_string(),
+            excerpt: None,
+        });
+        assert_eq!(e.code, ErrorCode::JournalError);
+        assert_eq!(
+            e.message,
+            "Journal error at /books/main.journal:120: unbalanced"
+        );
+        let details = e.details.unwrap();
+        assert_eq!(details.file.as_deref(), Some("/books/main.journal"));
+        assert_eq!((details.line, details.column), (Some(120), Some(4)));
+    }
+
+    #[test]
+    fn test_assertion_failure_has_own_code() {
+        let stderr = "hledger: Error: /books/main.journal:11:35:
+   | 2024-01-31 * Statement
+11 |     assets:bank:checking         $0 = $800.00
+   |                                     ^^^^^^^^^
+
+Balance assertion failed in assets:bank:checking
+the asserted balance is:        $800.00
+but the calculated balance is:  $750.00
+";
+        let e = AppError::from(HLedgerError::from_failure(
+            COMMAND.to_string(),
+            1,
+            stderr.to_string(),
+        ));
+        assert_eq!(e.code, ErrorCode::AssertionFailed);
+        let details = e.details.unwrap();
+        assert_eq!(details.file.as_deref(), Some("/books/main.journal"));
+        assert_eq!((details.line, details.column), (Some(11), Some(35)));
+    }
+
+    #[test]
+    fn test_csv_date_error_has_location() {
+        let e = AppError::from(HLedgerError::CsvDate {
+            file: "bank.csv".to_string(),
+            line: 2,
+            column: 1,
+            value: "05/03/2024".to_string(),
+            format: "%Y-%m-%d".to_string(),
+        });
+        assert_eq!(e.code, ErrorCode::CsvDate);
+        assert_eq!(
+            e.message,
+            "Can't read '05/03/2024' at bank.csv:2 as a date in the format %Y-%m-%d"
+        );
+        let details = e.details.unwrap();
+        assert_eq!((details.line, details.column), (Some(2), Some(1)));
+    }
+
+    #[test]
+    fn test_missing_files_and_binaries() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        assert_eq!(
+            AppError::from(HLedgerError::Io(io)).code,
+            ErrorCode::FileNotFound
+        );
+
+        let e = AppError::from(HLedgerError::CommandFailed {
+            command: "hledger -f gone.journal print".to_string(),
+            code: 1,
+            stderr: "hledger: gone.journal: openFile: does not exist (No such file or directory)"
+                .to_string(),
+        });
+        assert_eq!(e.code, ErrorCode::FileNotFound);
+        assert_eq!(e.details.unwrap().exit_code, Some(1));
+
+        let e = AppError::from(HLedgerError::HLedgerNotFound {
+            attempted: "/opt/hledger".to_string(),
+        });
+        assert_eq!(e.code, ErrorCode::HledgerNotFound);
+        assert_eq!(
+            e.details.unwrap().hledger_path.as_deref(),
+            Some("/opt/hledger")
+        );
+    }
+
+    #[test]
+    fn test_other_codes() {
+        let e = AppError::from(HLedgerError::Timeout {
+            elapsed: std::time::Duration::from_secs(3),
+        });
+        assert_eq!(e.code, ErrorCode::Timeout);
+        assert_eq!(e.details.unwrap().elapsed_ms, Some(3000));
+        assert_eq!(
+            AppError::from(HLedgerError::Cancelled).code,
+            ErrorCode::Cancelled
+        );
+        assert_eq!(
+            AppError::from("Unknown report kind: x".to_string()).code,
+            ErrorCode::Other
+        );
+    }
+
+    #[test]
+    fn test_context_and_serialized_form() {
+        let e = AppError::from(HLedgerError::ParseError("bad amount".to_string()))
+            .context("Failed to get balance");
+        assert_eq!(
+            e.to_string(),
+            "Failed to get balance: Parse error: bad amount"
+        );
+        assert_eq!(
+            serde_json::to_value(&e).unwrap(),
+            serde_json::json!({
+                "code": "ParseError",
+                "message": "Failed to get balance: Parse error: bad amount",
+                "details": null,
+            })
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Display strings for amounts, in the styles the journal itself uses
+//!
+//! Balance reports carry no amount styles, so the styles come from one
+//! `hledger print --explicit` pass, in which hledger has already applied
+//! each commodity's canonical style to every amount.
+
+use crate::commands::balance::Amount;
+use crate::commands::print::{get_print, AmountStyle, PrintAmount, PrintOptions};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// Display style per commodity
+pub type CommodityStyles = BTreeMap<String, AmountStyle>;
+
+/// How [`format_amounts`] renders amounts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase", default)]
+#[ts(rename_all = "camelCase")]
+pub struct FormatOptions {
+    /// Abbreviate thousands, millions and billions (`$1.2k`), e.g. for chart
+    /// axis labels
+    pub compact: bool,
+}
+
+/// The style of each commodity the journal uses
+pub fn get_commodity_styles(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+) -> Result<CommodityStyles> {
+    let report = get_print(hledger_path, journal_file, &PrintOptions::new().explicit())?;
+    let amounts = report
+        .iter()
+        .flat_map(|transaction| &transaction.postings)
+        .flat_map(|posting| &posting.amounts);
+    Ok(commodity_styles(amounts))
+}
+
+/// The first style seen for each commodity
+fn commodity_styles<'a>(amounts: impl IntoIterator<Item = &'a PrintAmount>) -> CommodityStyles {
+    let mut styles = CommodityStyles::new();
+    for amount in amounts {
+        if !styles.contains_key(&amount.commodity) {
+            styles.insert(amount.commodity.clone(), amount.style.clone());
+        }
+    }
+    styles
+}
+
+/// Each amount as a display string, in its commodity's style; commodities
+/// without one get a default style
+pub fn format_amounts(
+    amounts: &[Amount],
+    styles: &CommodityStyles,
+    options: &FormatOptions,
+) -> Vec<String> {
+    amounts
+        .iter()
+        .map(|amount| {
+            let style = styles.get(&amount.commodity);
+            if options.compact {
+                amount.format_compact(style)
+            } else {
+                amount.format(style)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::print::{DigitGroups, Precision};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn export_bindings() {
+        FormatOptions::export_all().unwrap();
+    }
+
+    fn amount(commodity: &str, quantity: &str) -> Amount {
+        Amount {
+            commodity: commodity.to_string(),
+            quantity: quantity.parse().unwrap(),
+            price: None,
+        }
+    }
+
+    fn euro_style() -> AmountStyle {
+        AmountStyle {
+            commodity_side: "R".to_string(),
+            commodity_spaced: true,
+            decimal_mark: Some(",".to_string()),
+            digit_groups: Some(DigitGroups {
+                separator: ' ',
+                sizes: vec![3],
+            }),
+            precision: Precision::Digits(2),
+            rounding: "NoRounding".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_first_style_wins() {
+        let print_amount = |style: AmountStyle| PrintAmount {
+            commodity: "€".to_string(),
+            quantity: Decimal::ONE,
+            price: None,
+            style,
+        };
+        let amounts = [
+            print_amount(euro_style()),
+            print_amount(AmountStyle::default()),
+        ];
+
+        let styles = commodity_styles(&amounts);
+        assert_eq!(styles.len(), 1);
+        assert_eq!(styles["€"].decimal_mark.as_deref(), Some(","));
+    }
+
+    #[test]
+    fn test_format_amounts() {
+        let styles = CommodityStyles::from([("€".to_string(), euro_style())]);
+        let amounts = [amount("€", "1234.5"), amount("XYZ", "3")];
+
+        assert_eq!(
+            format_amounts(&amounts, &styles, &FormatOptions::default()),
+            ["1 234,50 €", "3 XYZ"]
+        );
+        assert_eq!(
+            format_amounts(&amounts, &styles, &FormatOptions { compact: true }),
+            ["1,2k €", "3 XYZ"]
+        );
+    }
+}
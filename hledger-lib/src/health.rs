@@ -0,0 +1,323 @@
+//! One check of everything reports depend on, so the app can show a single
+//! status at startup instead of failing report by report
+
+use crate::binary::{resolve_hledger, HledgerSource};
+use crate::cancel::{with_cancel_token, CancelToken};
+use crate::commands::check::{get_check, CheckIssueKind, CheckSeverity};
+use crate::config::command_config;
+use crate::version::{get_version, HLedgerVersion, MIN_SUPPORTED_VERSION};
+use crate::HLedgerError;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// What a [`HealthItem`] found, for the app to act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum HealthCode {
+    HledgerFound,
+    HledgerNotFound,
+    /// Older than [`MIN_SUPPORTED_VERSION`]
+    HledgerTooOld,
+    /// hledger ran but its version couldn't be read
+    HledgerVersionUnknown,
+    NoJournalSelected,
+    JournalMissing,
+    JournalUnreadable,
+    JournalParses,
+    /// hledger couldn't read the journal, or a basic check failed
+    JournalInvalid,
+    /// The journal check didn't finish within the time allowed
+    JournalCheckTimedOut,
+    /// Saved settings were read; reported by the app
+    ConfigLoaded,
+    /// Saved settings couldn't be read; reported by the app
+    ConfigFailed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum HealthStatus {
+    Ok,
+    /// Reports may work, but something needs attention
+    Warning,
+    /// Reports won't work until this is fixed
+    Error,
+}
+
+/// One finding of a health check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct HealthItem {
+    pub code: HealthCode,
+    pub status: HealthStatus,
+    pub message: String,
+    /// The journal the item is about, if any
+    pub file: Option<String>,
+}
+
+impl HealthItem {
+    pub fn new(code: HealthCode, status: HealthStatus, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            status,
+            message: message.into(),
+            file: None,
+        }
+    }
+
+    fn about(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+}
+
+/// Result of [`health_check`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// Whether no item is an error
+    pub ok: bool,
+    /// The binary that would run, if one was found
+    pub hledger_path: Option<String>,
+    /// Where `hledger_path` comes from
+    pub hledger_source: Option<HledgerSource>,
+    pub hledger_version: Option<HLedgerVersion>,
+    pub meets_minimum_version: bool,
+    pub items: Vec<HealthItem>,
+}
+
+impl HealthReport {
+    /// Add a finding, e.g. one only the app can make
+    pub fn push(&mut self, item: HealthItem) {
+        self.ok &= item.status != HealthStatus::Error;
+        self.items.push(item);
+    }
+}
+
+/// Check that hledger runs and is new enough, and that each journal exists
+/// and passes `hledger check`
+///
+/// The pieces run at once, and whatever hasn't finished after `budget` is
+/// stopped and reported as timed out, so a huge journal can't hold up
+/// startup.
+pub fn health_check(
+    hledger_path: Option<&str>,
+    journal_files: &[String],
+    budget: Duration,
+) -> HealthReport {
+    let deadline = Instant::now() + budget;
+    let token = CancelToken::new();
+    let (done, finished) = mpsc::channel::<()>();
+
+    let (version, journals) = thread::scope(|scope| {
+        let version = {
+            let (token, done) = (&token, done.clone());
+            scope.spawn(move || {
+                let version = with_cancel_token(token, || get_version(hledger_path));
+                let _ = done.send(());
+                version
+            })
+        };
+        let journals: Vec<_> = journal_files
+            .iter()
+            .map(|file| {
+                let (token, done) = (&token, done.clone());
+                scope.spawn(move || {
+                    let items = with_cancel_token(token, || journal_items(hledger_path, file));
+                    let _ = done.send(());
+                    items
+                })
+            })
+            .collect();
+
+        // Stop whatever is still running once the budget is spent
+        for _ in 0..=journal_files.len() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if finished.recv_timeout(left).is_err() {
+                token.cancel();
+                break;
+            }
+        }
+
+        let version = version.join().expect("version check panicked");
+        let journals: Vec<Vec<HealthItem>> = journals
+            .into_iter()
+            .map(|handle| handle.join().expect("journal check panicked"))
+            .collect();
+        (version, journals)
+    });
+
+    let hledger_version = version.as_ref().ok().copied();
+    let resolved = resolve_hledger(hledger_path, command_config().sidecar.as_deref()).ok();
+    let mut report = HealthReport {
+        ok: true,
+        hledger_path: resolved
+            .as_ref()
+            .map(|resolved| resolved.path.to_string_lossy().into_owned()),
+        hledger_source: resolved.map(|resolved| resolved.source),
+        hledger_version,
+        meets_minimum_version: hledger_version.is_some_and(|v| v >= MIN_SUPPORTED_VERSION),
+        items: Vec::new(),
+    };
+    report.push(version_item(version));
+    if journal_files.is_empty() {
+        report.push(HealthItem::new(
+            HealthCode::NoJournalSelected,
+            HealthStatus::Warning,
+            "No journal file is selected",
+        ));
+    }
+    for item in journals.into_iter().flatten() {
+        report.push(item);
+    }
+    report
+}
+
+/// The finding for the installed hledger's version, or why there is none
+fn version_item(version: crate::Result<HLedgerVersion>) -> HealthItem {
+    use HealthCode::*;
+    match version {
+        Ok(version) if version >= MIN_SUPPORTED_VERSION => HealthItem::new(
+            HledgerFound,
+            HealthStatus::Ok,
+            format!("hledger {} is installed", version),
+        ),
+        Ok(version) => HealthItem::new(
+            HledgerTooOld,
+            HealthStatus::Error,
+            format!(
+                "hledger {} is installed, but {} or newer is needed",
+                version, MIN_SUPPORTED_VERSION
+            ),
+        ),
+        Err(HLedgerError::ParseError(message)) => {
+            HealthItem::new(HledgerVersionUnknown, HealthStatus::Warning, message)
+        }
+        Err(HLedgerError::Cancelled) => HealthItem::new(
+            HledgerVersionUnknown,
+            HealthStatus::Warning,
+            "hledger didn't report its version in time",
+        ),
+        Err(e) => HealthItem::new(HledgerNotFound, HealthStatus::Error, e.to_string()),
+    }
+}
+
+/// Findings for one journal: whether it can be opened and, if so, whether
+/// hledger accepts it
+fn journal_items(hledger_path: Option<&str>, file: &str) -> Vec<HealthItem> {
+    use HealthCode::*;
+    if let Err(e) = std::fs::File::open(file) {
+        let code = match e.kind() {
+            std::io::ErrorKind::NotFound => JournalMissing,
+            _ => JournalUnreadable,
+        };
+        return vec![HealthItem::new(code, HealthStatus::Error, e.to_string()).about(file)];
+    }
+
+    let item = match get_check(hledger_path, Some(file), &[]) {
+        Ok(issues) => match issues
+            .into_iter()
+            .find(|issue| issue.severity == CheckSeverity::Error)
+        {
+            None => HealthItem::new(JournalParses, HealthStatus::Ok, "The journal is valid"),
+            Some(issue) => {
+                let code = match issue.kind {
+                    CheckIssueKind::Unreadable => JournalUnreadable,
+                    _ => JournalInvalid,
+                };
+                let message = match (&issue.file, issue.line) {
+                    (Some(at), Some(line)) => format!("{}:{}: {}", at, line, issue.message),
+                    _ => issue.message,
+                };
+                HealthItem::new(code, HealthStatus::Error, message)
+            }
+        },
+        Err(HLedgerError::Cancelled) => HealthItem::new(
+            JournalCheckTimedOut,
+            HealthStatus::Warning,
+            "Checking the journal took too long; it will be read when a report runs",
+        ),
+        // A missing hledger is reported once, by the version check
+        Err(HLedgerError::HLedgerNotFound { .. } | HLedgerError::HLedgerNotExecutable { .. }) => {
+            return Vec::new()
+        }
+        Err(e) => HealthItem::new(JournalInvalid, HealthStatus::Error, e.to_string()),
+    };
+    vec![item.about(file)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        HealthReport::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_version_item() {
+        let item = version_item(Ok(HLedgerVersion::new(1, 40, 0)));
+        assert_eq!(
+            (item.code, item.status),
+            (HealthCode::HledgerFound, HealthStatus::Ok)
+        );
+
+        let item = version_item(Ok(HLedgerVersion::new(1, 10, 0)));
+        assert_eq!(
+            (item.code, item.status),
+            (HealthCode::HledgerTooOld, HealthStatus::Error)
+        );
+        assert!(item.message.contains("1.10"));
+
+        let item = version_item(Err(HLedgerError::HLedgerNotFound {
+            attempted: "/opt/hledger".to_string(),
+        }));
+        assert_eq!(
+            (item.code, item.status),
+            (HealthCode::HledgerNotFound, HealthStatus::Error)
+        );
+
+        let item = version_item(Err(HLedgerError::Cancelled));
+        assert_eq!(item.status, HealthStatus::Warning);
+    }
+
+    #[test]
+    fn test_journal_items_missing_file() {
+        let items = journal_items(Some("/nonexistent/hledger"), "/nonexistent/main.journal");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].code, HealthCode::JournalMissing);
+        assert_eq!(items[0].file.as_deref(), Some("/nonexistent/main.journal"));
+    }
+
+    #[test]
+    fn test_push_tracks_ok() {
+        let mut report = HealthReport {
+            ok: true,
+            hledger_path: None,
+            hledger_source: None,
+            hledger_version: None,
+            meets_minimum_version: false,
+            items: Vec::new(),
+        };
+        report.push(HealthItem::new(
+            HealthCode::NoJournalSelected,
+            HealthStatus::Warning,
+            "none",
+        ));
+        assert!(report.ok);
+        report.push(HealthItem::new(
+            HealthCode::ConfigFailed,
+            HealthStatus::Error,
+            "bad store",
+        ));
+        assert!(!report.ok);
+    }
+}
@@ -0,0 +1,185 @@
+use serde::{de, Deserialize, Serialize};
+use serde_json::Value;
+use std::process::Command;
+use ts_rs::TS;
+
+/// How a report is split into periods
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type", content = "value")]
+pub enum ReportInterval {
+    /// A single period covering the whole report
+    #[default]
+    None,
+    /// `--daily`
+    Daily,
+    /// `--weekly`
+    Weekly,
+    /// `--monthly`
+    Monthly,
+    /// `--quarterly`
+    Quarterly,
+    /// `--yearly`
+    Yearly,
+    /// `--period PERIODEXPR`, e.g. `every 2 weeks from 2024-01-01` or `2024`
+    Custom(String),
+}
+
+/// The period fields options structs had before [`ReportInterval`]
+///
+/// Deprecated: options still accept them from older frontend code, folding
+/// them into `interval` as they are read, so they are never held or
+/// serialized. Set `interval` instead.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LegacyInterval {
+    pub daily: bool,
+    pub weekly: bool,
+    pub monthly: bool,
+    pub quarterly: bool,
+    pub yearly: bool,
+    pub period: Option<String>,
+}
+
+impl ReportInterval {
+    /// Add the hledger flags for this interval
+    pub(crate) fn add_args(&self, cmd: &mut Command) {
+        match self {
+            ReportInterval::None => {}
+            ReportInterval::Daily => {
+                cmd.arg("--daily");
+            }
+            ReportInterval::Weekly => {
+                cmd.arg("--weekly");
+            }
+            ReportInterval::Monthly => {
+                cmd.arg("--monthly");
+            }
+            ReportInterval::Quarterly => {
+                cmd.arg("--quarterly");
+            }
+            ReportInterval::Yearly => {
+                cmd.arg("--yearly");
+            }
+            ReportInterval::Custom(period) => {
+                cmd.arg("--period").arg(period);
+            }
+        }
+    }
+}
+
+impl LegacyInterval {
+    /// `interval`, or when it is `None` the one the legacy fields in the
+    /// options JSON `json` give
+    pub(crate) fn resolve<E: de::Error>(
+        interval: ReportInterval,
+        json: &Value,
+    ) -> Result<ReportInterval, E> {
+        match interval {
+            ReportInterval::None => Ok(LegacyInterval::deserialize(json)
+                .map_err(E::custom)?
+                .to_interval()),
+            interval => Ok(interval),
+        }
+    }
+
+    /// The shortest interval set, restricted to the custom period if there is
+    /// one (hledger accepts `monthly in 2024`)
+    fn to_interval(&self) -> ReportInterval {
+        let flag = [
+            (self.daily, ReportInterval::Daily, "daily"),
+            (self.weekly, ReportInterval::Weekly, "weekly"),
+            (self.monthly, ReportInterval::Monthly, "monthly"),
+            (self.quarterly, ReportInterval::Quarterly, "quarterly"),
+            (self.yearly, ReportInterval::Yearly, "yearly"),
+        ]
+        .into_iter()
+        .find(|(set, _, _)| *set);
+
+        match (flag, &self.period) {
+            (Some((_, _, name)), Some(period)) => {
+                ReportInterval::Custom(format!("{} in {}", name, period))
+            }
+            (None, Some(period)) => ReportInterval::Custom(period.clone()),
+            (Some((_, interval, _)), None) => interval,
+            (None, None) => ReportInterval::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(interval: &ReportInterval) -> Vec<String> {
+        let mut cmd = Command::new("hledger");
+        interval.add_args(&mut cmd);
+        cmd.get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn export_bindings() {
+        ReportInterval::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_interval_args() {
+        assert!(args(&ReportInterval::None).is_empty());
+        assert_eq!(args(&ReportInterval::Daily), ["--daily"]);
+        assert_eq!(args(&ReportInterval::Weekly), ["--weekly"]);
+        assert_eq!(args(&ReportInterval::Monthly), ["--monthly"]);
+        assert_eq!(args(&ReportInterval::Quarterly), ["--quarterly"]);
+        assert_eq!(args(&ReportInterval::Yearly), ["--yearly"]);
+        assert_eq!(
+            args(&ReportInterval::Custom("every 2 weeks".into())),
+            ["--period", "every 2 weeks"]
+        );
+    }
+
+    #[test]
+    fn test_interval_serde() {
+        let json = serde_json::to_value(ReportInterval::Monthly).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "Monthly"}));
+
+        let custom = ReportInterval::Custom("2024q1".into());
+        let json = serde_json::to_value(&custom).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "Custom", "value": "2024q1"})
+        );
+        assert_eq!(
+            serde_json::from_value::<ReportInterval>(json).unwrap(),
+            custom
+        );
+    }
+
+    #[test]
+    fn test_legacy_fields_map_to_interval() {
+        let resolve =
+            |interval, json| LegacyInterval::resolve::<serde_json::Error>(interval, &json).unwrap();
+        let json = serde_json::json!({"weekly": true, "yearly": true});
+        assert_eq!(
+            resolve(ReportInterval::None, json.clone()),
+            ReportInterval::Weekly
+        );
+        assert_eq!(resolve(ReportInterval::Daily, json), ReportInterval::Daily);
+
+        assert_eq!(
+            resolve(
+                ReportInterval::None,
+                serde_json::json!({"monthly": true, "period": "2024"})
+            ),
+            ReportInterval::Custom("monthly in 2024".into())
+        );
+        assert_eq!(
+            resolve(ReportInterval::None, serde_json::json!({"period": "2024"})),
+            ReportInterval::Custom("2024".into())
+        );
+        assert_eq!(
+            resolve(ReportInterval::None, serde_json::json!({"depth": 2})),
+            ReportInterval::None
+        );
+    }
+}
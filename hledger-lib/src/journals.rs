@@ -0,0 +1,210 @@
+//! Running reports on several journals at once, or on hledger's default
+//! one
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{self, PathBuf};
+use ts_rs::TS;
+
+/// A journal file name standing for hledger's default journal, which
+/// reports read by leaving out `-f`
+///
+/// Angle brackets can't appear in Windows file names and hardly ever do
+/// elsewhere, so it can't be mistaken for a real journal.
+pub const DEFAULT_JOURNAL: &str = "<default>";
+
+/// Where hledger's default journal was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum DefaultJournalSource {
+    /// The `LEDGER_FILE` environment variable
+    LedgerFile,
+    /// `.hledger.journal` in the home directory
+    Home,
+}
+
+/// The journal hledger reads when it isn't given `-f`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DefaultJournal {
+    pub path: String,
+    pub source: DefaultJournalSource,
+    /// Whether the file exists and can be opened
+    pub readable: bool,
+}
+
+/// Separates the files of a [`combined_journal`]; no path can contain it
+const FILE_SEPARATOR: char = '\0';
+
+/// The `journal_file` to pass to reports that should read all of `files`
+///
+/// No files, or just [`DEFAULT_JOURNAL`], gives `None`, so hledger reads
+/// its default journal (see [`default_journal`]), and a single file is
+/// passed as it is. Several are joined into one value, by absolute path,
+/// that the report commands split again into one `-f` per file (see
+/// [`journal_file_paths`]); a [`DEFAULT_JOURNAL`] among them stands for the
+/// default journal's path. The same selection always gives the same value,
+/// so cached reports keep their key.
+pub fn combined_journal(files: &[String]) -> Result<Option<String>> {
+    match files {
+        [] => return Ok(None),
+        [file] if file == DEFAULT_JOURNAL => return Ok(None),
+        [file] => return Ok(Some(file.clone())),
+        _ => {}
+    }
+
+    let mut paths = Vec::with_capacity(files.len());
+    for file in files {
+        let file = if file == DEFAULT_JOURNAL {
+            match default_journal() {
+                Some(default) => default.path,
+                None => continue,
+            }
+        } else {
+            file.clone()
+        };
+        paths.push(path::absolute(file)?.to_string_lossy().into_owned());
+    }
+    Ok(Some(paths.join(&FILE_SEPARATOR.to_string())))
+}
+
+/// The files a `journal_file` names: itself, or each file of a
+/// [`combined_journal`]
+pub fn journal_file_paths(journal_file: &str) -> impl Iterator<Item = &str> {
+    journal_file.split(FILE_SEPARATOR)
+}
+
+/// The journal hledger reads without `-f`: `LEDGER_FILE` if it's set, else
+/// `~/.hledger.journal`
+///
+/// A `LEDGER_FILE` that can't be read is still the one returned, flagged
+/// unreadable, since hledger would fail on it rather than fall back. `None`
+/// only if neither is set and there's no home directory.
+pub fn default_journal() -> Option<DefaultJournal> {
+    let mut journal = default_journal_path(|key| std::env::var_os(key), cfg!(windows))?;
+    journal.readable = fs::File::open(&journal.path)
+        .is_ok_and(|file| file.metadata().is_ok_and(|metadata| metadata.is_file()));
+    Some(journal)
+}
+
+/// Where the default journal is, given the environment `env`; not yet
+/// checked for readability
+fn default_journal_path(
+    env: impl Fn(&str) -> Option<OsString>,
+    windows: bool,
+) -> Option<DefaultJournal> {
+    let home = env(if windows { "USERPROFILE" } else { "HOME" })
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from);
+    let (path, source) = match env("LEDGER_FILE").filter(|file| !file.is_empty()) {
+        Some(file) => {
+            let file = file.to_string_lossy().into_owned();
+            // hledger expands a leading `~` itself
+            let path = match (file.strip_prefix("~/").or(file.strip_prefix("~\\")), &home) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(file),
+            };
+            (path, DefaultJournalSource::LedgerFile)
+        }
+        None => (home?.join(".hledger.journal"), DefaultJournalSource::Home),
+    };
+    Some(DefaultJournal {
+        path: path.to_string_lossy().into_owned(),
+        source,
+        readable: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        DefaultJournal::export_all().unwrap();
+    }
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| OsString::from(v))
+        }
+    }
+
+    #[test]
+    fn test_combined_journal_single_and_none() {
+        assert_eq!(combined_journal(&[]).unwrap(), None);
+        assert_eq!(
+            combined_journal(&[DEFAULT_JOURNAL.to_string()]).unwrap(),
+            None
+        );
+        assert_eq!(
+            combined_journal(&["main.journal".to_string()]).unwrap(),
+            Some("main.journal".to_string())
+        );
+    }
+
+    #[test]
+    fn test_combined_journal_lists_each_file() {
+        let files = [
+            "tests/fixtures/test.journal".to_string(),
+            "tests/fixtures/prices.journal".to_string(),
+        ];
+        let combined = combined_journal(&files).unwrap().unwrap();
+        let paths: Vec<PathBuf> = journal_file_paths(&combined).map(PathBuf::from).collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|path| path.is_absolute()));
+        assert!(paths[0].ends_with("tests/fixtures/test.journal"));
+        assert!(paths[1].ends_with("tests/fixtures/prices.journal"));
+
+        // Each file gets its own `-f`, in order
+        let mut cmd = std::process::Command::new("hledger");
+        crate::config::add_journal_args(&mut cmd, Some(&combined));
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args.len(), 4);
+        assert_eq!((args[0], args[2]), ("-f".as_ref(), "-f".as_ref()));
+        assert_eq!(PathBuf::from(args[3]), paths[1]);
+
+        // The same selection gives the same key; another order another one
+        assert_eq!(combined_journal(&files).unwrap().unwrap(), combined);
+        let reversed = [files[1].clone(), files[0].clone()];
+        assert_ne!(combined_journal(&reversed).unwrap().unwrap(), combined);
+        assert_eq!(journal_file_paths("main.journal").count(), 1);
+    }
+
+    #[test]
+    fn test_default_journal_path() {
+        let vars = [
+            ("LEDGER_FILE", "~/finance/2024.journal"),
+            ("HOME", "/home/ana"),
+        ];
+        let journal = default_journal_path(env(&vars), false).unwrap();
+        assert_eq!(
+            PathBuf::from(&journal.path),
+            PathBuf::from("/home/ana/finance/2024.journal")
+        );
+        assert_eq!(journal.source, DefaultJournalSource::LedgerFile);
+
+        let vars = [("LEDGER_FILE", ""), ("HOME", "/home/ana")];
+        let journal = default_journal_path(env(&vars), false).unwrap();
+        assert_eq!(
+            PathBuf::from(&journal.path),
+            PathBuf::from("/home/ana/.hledger.journal")
+        );
+        assert_eq!(journal.source, DefaultJournalSource::Home);
+
+        let vars = [("USERPROFILE", r"C:\Users\ana")];
+        let journal = default_journal_path(env(&vars), true).unwrap();
+        assert_eq!(
+            PathBuf::from(&journal.path),
+            PathBuf::from(r"C:\Users\ana").join(".hledger.journal")
+        );
+
+        assert_eq!(default_journal_path(env(&[]), false), None);
+    }
+}
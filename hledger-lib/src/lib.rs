@@ -1,19 +1,161 @@
+pub mod amount;
+pub mod backend;
+pub mod batch;
+pub mod binary;
+#[cfg(test)]
+mod bindings;
+pub mod cache;
+pub mod cancel;
 pub mod commands;
+pub mod compare;
 pub mod config;
+pub mod conversion;
+pub mod csv_import;
+pub mod csv_report;
+pub mod csv_rules;
+pub mod dashboard;
+pub mod dates;
+pub mod defaults;
+pub mod duplicates;
+pub mod encoding;
 pub mod error;
+pub mod format;
+pub mod health;
+pub mod interval;
+pub mod journals;
+pub mod list_mode;
+pub mod metadata;
+pub mod payees;
+pub mod preview;
+pub mod query;
+pub mod register;
+pub mod scaffold;
+pub mod search;
+pub mod sqlite;
+pub mod suggest;
+pub mod summary;
+pub mod timeout;
+pub mod transaction;
+pub mod upcoming;
+pub mod version;
+pub mod warnings;
+pub mod write;
 
-pub use commands::accounts::{get_accounts, AccountsOptions};
-pub use commands::balance::{get_balance, BalanceOptions, BalanceReport};
-pub use commands::balancesheet::{get_balancesheet, BalanceSheetOptions, BalanceSheetReport};
-pub use commands::cashflow::{get_cashflow, CashflowOptions, CashflowReport};
+pub use amount::{sum_amounts, MultiAmount};
+pub use backend::{Backend, SubprocessBackend};
+#[cfg(feature = "http")]
+pub use backend::{HttpAuth, HttpBackend};
+#[cfg(feature = "tokio")]
+pub use batch::run_reports_async;
+pub use batch::{
+    default_parallelism, run_reports, run_reports_with_parallelism, ReportRequest, ReportResponse,
+};
+pub use binary::{
+    detect_hledger, resolve_hledger, resolve_hledger_binary, DetectedHledger, HledgerSource,
+    ResolvedHledger,
+};
+pub use cache::{with_refresh, ReportCache};
+pub use cancel::{with_cancel_token, CancelToken};
+pub use commands::accounts::{
+    account_balance_tree, find_account, get_account_balance_tree, get_account_tree, get_accounts,
+    get_accounts_detailed, get_accounts_from_text, get_accounts_with_balances, AccountBalanceNode,
+    AccountInfo, AccountNode, AccountType, AccountWithBalance, AccountsOptions,
+};
+pub use commands::balance::{
+    get_balance, get_balance_csv, get_balance_from_text, get_balance_raw, BalanceOptions,
+    BalanceReport, Price, PriceKind,
+};
+pub use commands::balancesheet::{
+    get_balancesheet, get_balancesheet_csv, get_balancesheet_from_text, get_balancesheet_raw,
+    BalanceSheetOptions, BalanceSheetReport,
+};
+pub use commands::budget::{get_budget_report, BudgetCell, BudgetOptions, BudgetReport, BudgetRow};
+pub use commands::cashflow::{
+    get_cashflow, get_cashflow_csv, get_cashflow_from_text, get_cashflow_raw, CashflowOptions,
+    CashflowReport,
+};
+pub use commands::check::{
+    get_check, get_journal_problems, verify_assertions, AssertionFailure, CheckIssue,
+    CheckIssueKind, CheckSeverity, JournalProblem,
+};
+pub use commands::files::get_files;
+pub use commands::flow::{get_flow_data, FlowGraph, FlowLink, FlowNode, FlowNodeKind, FlowOptions};
+pub use commands::import::{import_files, preview_import, ImportOptions};
 pub use commands::incomestatement::{
-    get_incomestatement, IncomeStatementOptions, IncomeStatementReport,
+    get_incomestatement, get_incomestatement_csv, get_incomestatement_from_text,
+    get_incomestatement_raw, IncomeStatementOptions, IncomeStatementReport,
 };
+pub use commands::networth::{get_networth_series, NetworthOptions, NetworthPoint};
+pub use commands::prices::{get_prices, MarketPrice, PricesOptions};
 pub use commands::print::{
-    get_print, AmountStyle, BalanceAssertion, Price, PrintAmount, PrintOptions, PrintPosting,
-    PrintReport, PrintTransaction, SourcePosition,
+    get_print, get_print_csv, get_print_from_text, get_print_paged, get_print_raw, AmountStyle,
+    BalanceAssertion, DigitGroups, PageOrder, PageSpec, Precision, PrintAmount, PrintOptions,
+    PrintPage, PrintPosting, PrintReport, PrintTransaction, SourcePosition, SourceSpan,
+};
+pub use commands::spending::{top_spending_accounts, AccountTotal, TopSpendingOptions};
+pub use commands::stats::{get_stats, JournalStats};
+pub use commands::tags::{get_tag_usage, get_tags, TagUsage, TagValueUsage, TagsOptions};
+pub use commands::timeseries::{get_timeseries, Accumulation, Series, SeriesPoint, TimeseriesSpec};
+pub use compare::{compare_periods, ComparisonRow};
+pub use config::{
+    command_config, get_hledger_command, get_hledger_command_with, set_command_config,
+    CommandConfig,
+};
+pub use conversion::ConversionTable;
+pub use csv_import::{
+    check_csv_dates, guess_csv_mapping, import_csv, preview_csv, CsvImportOutcome, CsvMapping,
+    CsvPreview,
+};
+pub use csv_report::{export_file_name, CsvReport, ExportFormat};
+pub use csv_rules::{CsvAssignment, CsvCondition, CsvRules};
+pub use dashboard::{
+    dashboard_summary, DashboardSection, DashboardSummary, MonthTotals, UnavailableSection,
+};
+pub use dates::validate_smart_date;
+pub use defaults::{merge_options, ReportDefaults, ValueMode};
+pub use duplicates::{
+    find_duplicate_candidates, DuplicateCandidate, DuplicateGroup, DuplicateTolerance,
+};
+pub use encoding::{convert_journal_to_utf8, detect_journal_encoding, JournalEncoding};
+pub use error::{AppError, ErrorCode, ErrorDetails, HLedgerError};
+pub use format::{format_amounts, get_commodity_styles, CommodityStyles, FormatOptions};
+pub use health::{health_check, HealthCode, HealthItem, HealthReport, HealthStatus};
+pub use interval::ReportInterval;
+pub use journals::{
+    combined_journal, default_journal, journal_file_paths, DefaultJournal, DefaultJournalSource,
+    DEFAULT_JOURNAL,
+};
+pub use list_mode::ListMode;
+pub use metadata::{
+    journal_metadata, validate_dropped_file, DroppedFile, JournalMetadata, JournalMetadataCache,
+    JOURNAL_EXTENSIONS, STATS_SIZE_LIMIT,
+};
+pub use payees::{get_payee_stats, PayeeStats, PayeeStatsOptions};
+pub use preview::overlay_journal;
+pub use query::{AmountOp, Query, Status};
+pub use register::{compute_running_balances, RunningBalanceEntry};
+pub use scaffold::{create_journal, JournalLayout, JournalTemplate, OpeningBalance};
+pub use search::{
+    search_transactions, SearchField, SearchHighlight, SearchIndex, SearchMatch, SearchResults,
+};
+#[cfg(feature = "sqlite")]
+pub use sqlite::export_to_sqlite;
+pub use sqlite::ExportStats;
+pub use suggest::{
+    SuggestionCache, SuggestionIndex, SuggestionKind, Suggestions, TransactionSuggestion,
+};
+pub use summary::{
+    group_by_month, group_by_payee, summarize_transactions, MonthSummary, PayeeSummary,
+    TransactionSummary,
+};
+pub use timeout::Timeout;
+pub use transaction::{JournalStyle, PostingAssertion, PostingBuilder, TransactionBuilder};
+pub use upcoming::{get_upcoming_transactions, UpcomingTransaction};
+pub use version::{get_version, set_version_check, HLedgerVersion, MIN_SUPPORTED_VERSION};
+pub use warnings::{collect_warnings, with_warnings, ReportOutput};
+pub use write::{
+    append_transaction, file_fingerprint, replace_transaction, FileFingerprint, Replacement,
+    WriteHook, WriteOptions, WriteReceipt,
 };
-pub use config::get_hledger_command;
-pub use error::HLedgerError;
 
 pub type Result<T> = std::result::Result<T, HLedgerError>;
@@ -0,0 +1,116 @@
+use serde::{de, Deserialize, Serialize};
+use serde_json::Value;
+use std::process::Command;
+use ts_rs::TS;
+
+/// How a balance report lists its accounts
+///
+/// Options hold an `Option<ListMode>`: `None` passes neither flag, leaving it
+/// to hledger (flat, unless a config file says otherwise). The lowercase
+/// names it once had are still read, as saved settings may hold them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum ListMode {
+    /// `--flat`
+    #[serde(alias = "flat")]
+    Flat,
+    /// `--tree`
+    #[serde(alias = "tree")]
+    Tree,
+}
+
+/// The `tree`/`flat` fields options structs had before [`ListMode`]
+///
+/// Deprecated: options still accept them from older frontend code, folding
+/// them into `list_mode` as they are read, so they are never held or
+/// serialized. Set `list_mode` instead.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LegacyListMode {
+    pub tree: bool,
+    pub flat: bool,
+}
+
+impl LegacyListMode {
+    /// `mode`, or when it is `None` the one the legacy fields in the options
+    /// JSON `json` give
+    pub(crate) fn resolve<E: de::Error>(
+        mode: Option<ListMode>,
+        json: &Value,
+    ) -> Result<Option<ListMode>, E> {
+        if mode.is_some() {
+            return Ok(mode);
+        }
+        let legacy = LegacyListMode::deserialize(json).map_err(E::custom)?;
+        Ok(if legacy.tree {
+            Some(ListMode::Tree)
+        } else if legacy.flat {
+            Some(ListMode::Flat)
+        } else {
+            None
+        })
+    }
+}
+
+impl ListMode {
+    /// Add the hledger flag for `mode`, if one was chosen
+    pub(crate) fn add_args(mode: Option<ListMode>, cmd: &mut Command) {
+        match mode {
+            None => {}
+            Some(ListMode::Flat) => {
+                cmd.arg("--flat");
+            }
+            Some(ListMode::Tree) => {
+                cmd.arg("--tree");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(mode: Option<ListMode>) -> Vec<String> {
+        let mut cmd = Command::new("hledger");
+        ListMode::add_args(mode, &mut cmd);
+        cmd.get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn export_bindings() {
+        ListMode::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_list_mode_args() {
+        assert!(args(None).is_empty());
+        assert_eq!(args(Some(ListMode::Flat)), ["--flat"]);
+        assert_eq!(args(Some(ListMode::Tree)), ["--tree"]);
+        assert_eq!(
+            serde_json::to_value(ListMode::Tree).unwrap(),
+            serde_json::json!("Tree")
+        );
+        assert_eq!(
+            serde_json::from_value::<ListMode>(serde_json::json!("tree")).unwrap(),
+            ListMode::Tree
+        );
+    }
+
+    #[test]
+    fn test_legacy_fields_map_to_list_mode() {
+        let resolve =
+            |mode, json| LegacyListMode::resolve::<serde_json::Error>(mode, &json).unwrap();
+        let legacy = |tree: bool, flat: bool| serde_json::json!({"tree": tree, "flat": flat});
+        assert_eq!(resolve(None, legacy(false, false)), None);
+        assert_eq!(resolve(None, legacy(true, false)), Some(ListMode::Tree));
+        assert_eq!(resolve(None, legacy(false, true)), Some(ListMode::Flat));
+        assert_eq!(
+            resolve(Some(ListMode::Flat), legacy(true, false)),
+            Some(ListMode::Flat)
+        );
+        assert_eq!(resolve(None, serde_json::json!({"depth": 2})), None);
+    }
+}
@@ -0,0 +1,345 @@
+//! What the journal picker shows about each file, gathered quickly enough
+//! to list several at once, and whether a file dropped on it is a journal
+
+use crate::commands::check::{issue_from_error, CheckIssue};
+use crate::commands::files::get_files_within;
+use crate::commands::stats::{get_stats, JournalStats};
+use crate::encoding::{detect_journal_encoding, JournalEncoding};
+use crate::{HLedgerError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use ts_rs::TS;
+
+/// Journals larger than this aren't read by hledger for their metadata
+pub const STATS_SIZE_LIMIT: u64 = 10 * 1024 * 1024;
+
+/// How long each hledger run gets before its figures are left unknown
+const METADATA_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Extensions of files hledger reads as journals
+pub const JOURNAL_EXTENSIONS: &[&str] = &[
+    "journal",
+    "hledger",
+    "j",
+    "ledger",
+    "dat",
+    "timeclock",
+    "timedot",
+];
+
+/// How long hledger gets to read a dropped file before it's let through
+/// unchecked
+const DROP_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A journal file's size and age, and what hledger finds in it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct JournalMetadata {
+    pub path: String,
+    #[ts(type = "number")]
+    pub size: u64,
+    /// Last modification, in milliseconds since the Unix epoch
+    #[ts(type = "number | null")]
+    pub modified_ms: Option<u64>,
+    /// `None` when unknown: see `note`
+    pub stats: Option<JournalStats>,
+    /// Files the journal includes, directly or through others; `None` when
+    /// unknown
+    pub included_files: Option<Vec<String>>,
+    /// Why `stats` or `included_files` is unknown
+    pub note: Option<String>,
+    /// The file's encoding when hledger can't read it, for the app to offer
+    /// converting it to UTF-8; `None` for UTF-8
+    pub encoding: Option<JournalEncoding>,
+}
+
+/// Size and modification time of `path`, plus its stats and included files
+/// unless it's larger than `stats_size_limit`
+///
+/// The two hledger runs happen at once and are cut off after a few
+/// seconds; either failing only leaves its figures unknown, with the
+/// reason in `note`. Only a file that can't be stat'ed is an error.
+pub fn journal_metadata(
+    hledger_path: Option<&str>,
+    path: &str,
+    stats_size_limit: u64,
+) -> Result<JournalMetadata> {
+    let file = fs::metadata(path)?;
+    let mut metadata = JournalMetadata {
+        path: path.to_string(),
+        size: file.len(),
+        modified_ms: modified_ms(&file),
+        stats: None,
+        included_files: None,
+        note: None,
+        encoding: None,
+    };
+    let encoding = detect_journal_encoding(Path::new(path))?;
+    if encoding.needs_conversion() {
+        metadata.note = Some(not_utf8(&encoding));
+        metadata.encoding = Some(encoding);
+        return Ok(metadata);
+    }
+    if metadata.size > stats_size_limit {
+        metadata.note = Some(format!(
+            "Too large to summarise quickly ({} MB)",
+            metadata.size / (1024 * 1024)
+        ));
+        return Ok(metadata);
+    }
+
+    let (stats, files) = thread::scope(|scope| {
+        let files =
+            scope.spawn(|| get_files_within(hledger_path, Some(path), Some(METADATA_TIMEOUT)));
+        let stats = get_stats(hledger_path, Some(path), Some(METADATA_TIMEOUT));
+        (stats, files.join().expect("hledger files panicked"))
+    });
+    let mut notes = Vec::new();
+    match stats {
+        Ok(stats) => metadata.stats = Some(stats),
+        Err(e) => notes.push(e.to_string()),
+    }
+    match files {
+        // The journal itself comes first
+        Ok(files) => metadata.included_files = Some(files.into_iter().skip(1).collect()),
+        Err(e) => notes.push(e.to_string()),
+    }
+    if !notes.is_empty() {
+        notes.dedup();
+        metadata.note = Some(notes.join("\n"));
+    }
+    Ok(metadata)
+}
+
+/// Why hledger isn't asked about a file in `encoding`
+fn not_utf8(encoding: &JournalEncoding) -> String {
+    format!("Encoded in {}; hledger only reads UTF-8", encoding.name)
+}
+
+fn modified_ms(file: &fs::Metadata) -> Option<u64> {
+    let modified = file.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+/// [`journal_metadata`] results, reused while the file's size and
+/// modification time are unchanged
+#[derive(Debug, Default)]
+pub struct JournalMetadataCache {
+    entries: Mutex<HashMap<(Option<String>, String), JournalMetadata>>,
+}
+
+impl JournalMetadataCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The metadata for `path`, from the cache if the file looks unchanged
+    ///
+    /// A change to an included file alone isn't noticed.
+    pub fn get(&self, hledger_path: Option<&str>, path: &str) -> Result<JournalMetadata> {
+        let key = (hledger_path.map(str::to_string), path.to_string());
+        let file = fs::metadata(path)?;
+        if let Some(cached) = self.entries.lock().unwrap().get(&key) {
+            if cached.size == file.len() && cached.modified_ms == modified_ms(&file) {
+                return Ok(cached.clone());
+            }
+        }
+
+        let metadata = journal_metadata(hledger_path, path, STATS_SIZE_LIMIT)?;
+        self.entries.lock().unwrap().insert(key, metadata.clone());
+        Ok(metadata)
+    }
+}
+
+/// Whether a file dropped on the window can be added as a journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct DroppedFile {
+    pub accepted: bool,
+    /// Why the file was turned away, or why an accepted one wasn't checked
+    pub reason: Option<String>,
+    /// Transactions hledger read from it; `None` when it didn't read it
+    pub transaction_count: Option<u32>,
+    /// hledger's complaint about the file, with its line and column when
+    /// known
+    pub issue: Option<CheckIssue>,
+    /// The file's encoding when hledger can't read it, for the app to offer
+    /// converting it to UTF-8; `None` for UTF-8
+    pub encoding: Option<JournalEncoding>,
+}
+
+impl DroppedFile {
+    fn rejected(reason: impl Into<String>) -> Self {
+        Self {
+            accepted: false,
+            reason: Some(reason.into()),
+            transaction_count: None,
+            issue: None,
+            encoding: None,
+        }
+    }
+
+    fn unchecked(reason: impl Into<String>) -> Self {
+        Self {
+            accepted: true,
+            ..Self::rejected(reason)
+        }
+    }
+}
+
+/// Check that `path` looks like a journal before it's added as one
+///
+/// The extension must be one of [`JOURNAL_EXTENSIONS`] and the start of the
+/// file must be text; then hledger reads it for a few seconds at most.
+/// Files larger than [`STATS_SIZE_LIMIT`], and ones hledger doesn't finish
+/// in time, are accepted unchecked. Only a failure to run hledger at all is
+/// an error.
+pub fn validate_dropped_file(hledger_path: Option<&str>, path: &str) -> Result<DroppedFile> {
+    let extension = Path::new(path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    if !extension
+        .as_deref()
+        .is_some_and(|extension| JOURNAL_EXTENSIONS.contains(&extension))
+    {
+        return Ok(DroppedFile::rejected(format!(
+            "Not a journal file: expected one ending in .{}",
+            JOURNAL_EXTENSIONS.join(", .")
+        )));
+    }
+
+    let file = match fs::metadata(path) {
+        Ok(file) if file.is_file() => file,
+        Ok(_) => return Ok(DroppedFile::rejected("Not a file")),
+        Err(e) => return Ok(DroppedFile::rejected(e.to_string())),
+    };
+    let encoding = match detect_journal_encoding(Path::new(path)) {
+        Ok(encoding) => encoding,
+        Err(e) => return Ok(DroppedFile::rejected(e.to_string())),
+    };
+    if !encoding.is_utf16() && looks_binary(path)? {
+        return Ok(DroppedFile::rejected("Not a text file"));
+    }
+    if encoding.needs_conversion() {
+        return Ok(DroppedFile {
+            encoding: Some(encoding.clone()),
+            ..DroppedFile::rejected(not_utf8(&encoding))
+        });
+    }
+    if file.len() > STATS_SIZE_LIMIT {
+        return Ok(DroppedFile::unchecked(format!(
+            "Too large to check quickly ({} MB)",
+            file.len() / (1024 * 1024)
+        )));
+    }
+
+    match get_stats(hledger_path, Some(path), Some(DROP_CHECK_TIMEOUT)) {
+        Ok(stats) => Ok(DroppedFile {
+            accepted: true,
+            reason: None,
+            transaction_count: Some(stats.transaction_count),
+            issue: None,
+            encoding: None,
+        }),
+        Err(HLedgerError::Timeout { .. }) => Ok(DroppedFile::unchecked(
+            "hledger took too long to read it; it will be read when a report runs",
+        )),
+        Err(e) => {
+            let issue = issue_from_error(e)?;
+            Ok(DroppedFile {
+                issue: Some(issue.clone()),
+                ..DroppedFile::rejected(issue.message)
+            })
+        }
+    }
+}
+
+/// Whether the first few kilobytes of `path` hold a NUL byte, which text
+/// never does
+fn looks_binary(path: &str) -> Result<bool> {
+    let mut start = Vec::with_capacity(8192);
+    fs::File::open(path)?.take(8192).read_to_end(&mut start)?;
+    Ok(start.contains(&0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        JournalMetadata::export_all().unwrap();
+        DroppedFile::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_dropped_files_rejected_before_hledger() {
+        let hledger = Some("/nonexistent/hledger");
+        let dropped = validate_dropped_file(hledger, "tests/fixtures/statement.pdf").unwrap();
+        assert!(!dropped.accepted);
+        assert!(dropped.reason.unwrap().contains(".journal"));
+
+        let dropped = validate_dropped_file(hledger, "/nonexistent/main.journal").unwrap();
+        assert!(!dropped.accepted);
+
+        let binary = std::env::temp_dir().join(format!("dropped-{}.journal", std::process::id()));
+        fs::write(&binary, b"%PDF-1.7\0\x01\x02").unwrap();
+        let dropped = validate_dropped_file(hledger, binary.to_str().unwrap()).unwrap();
+        fs::remove_file(&binary).unwrap();
+        assert_eq!(dropped.reason.as_deref(), Some("Not a text file"));
+    }
+
+    #[test]
+    fn test_files_hledger_cant_read_are_flagged() {
+        let hledger = Some("/nonexistent/hledger");
+        for fixture in ["latin1.journal", "utf16.journal"] {
+            let path = format!("tests/fixtures/{}", fixture);
+            let dropped = validate_dropped_file(hledger, &path).unwrap();
+            assert!(!dropped.accepted);
+            let encoding = dropped.encoding.expect(fixture);
+            assert!(dropped.reason.unwrap().contains(&encoding.name));
+
+            let metadata = journal_metadata(hledger, &path, u64::MAX).unwrap();
+            assert_eq!(metadata.encoding, Some(encoding));
+            assert!(metadata.note.unwrap().contains("UTF-8"));
+        }
+
+        // A UTF-8 byte order mark is fine, so hledger is run
+        let path = "tests/fixtures/utf8_bom.journal";
+        let metadata = journal_metadata(hledger, path, u64::MAX).unwrap();
+        assert_eq!(metadata.encoding, None);
+        assert!(!metadata.note.unwrap().contains("UTF-8"));
+    }
+
+    #[test]
+    fn test_large_journals_skip_hledger() {
+        let path = "tests/fixtures/test.journal";
+        let metadata = journal_metadata(Some("/nonexistent/hledger"), path, 0).unwrap();
+        assert_eq!(metadata.size, fs::metadata(path).unwrap().len());
+        assert!(metadata.modified_ms.is_some());
+        assert_eq!(metadata.stats, None);
+        assert_eq!(metadata.included_files, None);
+        assert!(metadata.note.unwrap().contains("Too large"));
+    }
+
+    #[test]
+    fn test_failing_hledger_leaves_figures_unknown() {
+        let path = "tests/fixtures/test.journal";
+        let metadata = journal_metadata(Some("/nonexistent/hledger"), path, u64::MAX).unwrap();
+        assert_eq!(metadata.stats, None);
+        assert_eq!(metadata.included_files, None);
+        assert!(metadata.note.is_some());
+
+        assert!(journal_metadata(None, "/nonexistent/main.journal", u64::MAX).is_err());
+    }
+}
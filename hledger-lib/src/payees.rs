@@ -0,0 +1,329 @@
+//! Figures per payee for a merchants screen, computed from the print
+//! report so the transactions themselves stay on the Rust side
+
+use crate::amount::MultiAmount;
+use crate::commands::accounts::{get_accounts_detailed, AccountType, AccountsOptions};
+use crate::commands::print::{get_print, PrintOptions, PrintTransaction};
+use crate::query::Query;
+use crate::summary::payee;
+use crate::timeout::Timeout;
+use crate::Result;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for [`get_payee_stats`]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PayeeStatsOptions {
+    /// Leave out payees with fewer transactions than this
+    pub min_count: u32,
+
+    // Date filters
+    /// Begin date (inclusive: transactions on or after this date)
+    pub begin: Option<String>,
+    /// End date (exclusive: transactions before this date)
+    pub end: Option<String>,
+    /// Pass `begin`/`end` to hledger without validating them first
+    pub unchecked_dates: bool,
+
+    // Query patterns
+    pub queries: Vec<String>,
+    pub timeout: Option<Timeout>,
+}
+
+/// What one payee's transactions add up to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PayeeStats {
+    pub payee: String,
+    pub transaction_count: u32,
+    #[ts(type = "string")]
+    pub first_date: NaiveDate,
+    #[ts(type = "string")]
+    pub last_date: NaiveDate,
+    /// Sum of the expense and income postings, signed as hledger shows them
+    /// (spending positive, income negative)
+    pub total: MultiAmount,
+    /// `total` per transaction, rounded to the total's decimal places
+    pub average: MultiAmount,
+    /// The expense or income account posted to most often; `None` when the
+    /// payee has no such postings
+    pub top_account: Option<String>,
+}
+
+// Implementation for builder pattern
+impl PayeeStatsOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min_count(mut self, count: u32) -> Self {
+        self.min_count = count;
+        self
+    }
+
+    pub fn begin(mut self, date: impl Into<String>) -> Self {
+        self.begin = Some(date.into());
+        self
+    }
+
+    pub fn end(mut self, date: impl Into<String>) -> Self {
+        self.end = Some(date.into());
+        self
+    }
+
+    pub fn unchecked_dates(mut self) -> Self {
+        self.unchecked_dates = true;
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.queries.push(query.into());
+        self
+    }
+
+    pub fn query_typed(mut self, query: Query) -> Self {
+        self.queries.push(query.to_arg());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    fn print_options(&self) -> PrintOptions {
+        PrintOptions {
+            begin: self.begin.clone(),
+            end: self.end.clone(),
+            unchecked_dates: self.unchecked_dates,
+            queries: self.queries.clone(),
+            timeout: self.timeout,
+            ..PrintOptions::with_inferred_amounts()
+        }
+    }
+}
+
+/// Transaction count, date span, totals and usual account of each payee,
+/// most transactions first, ties in name order
+///
+/// Runs `hledger print` for the transactions and `hledger accounts --types`
+/// to tell expense and income accounts (declared or inferred from their
+/// names) from the rest. The payee is the description up to a `|`, as in
+/// hledger.
+pub fn get_payee_stats(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    options: &PayeeStatsOptions,
+) -> Result<Vec<PayeeStats>> {
+    let accounts = AccountsOptions {
+        types: true,
+        timeout: options.timeout,
+        ..AccountsOptions::default()
+    };
+    let flow_accounts: HashSet<String> =
+        get_accounts_detailed(hledger_path, journal_file, &accounts)?
+            .into_iter()
+            .filter(|account| {
+                matches!(
+                    account.account_type,
+                    Some(AccountType::Expense | AccountType::Revenue)
+                )
+            })
+            .map(|account| account.name)
+            .collect();
+    let transactions = get_print(hledger_path, journal_file, &options.print_options())?;
+    Ok(payee_stats(
+        &transactions,
+        |account| flow_accounts.contains(account),
+        options.min_count,
+    ))
+}
+
+struct Tally<'a> {
+    transactions: u32,
+    first_date: NaiveDate,
+    last_date: NaiveDate,
+    total: MultiAmount,
+    accounts: BTreeMap<&'a str, u32>,
+}
+
+fn payee_stats(
+    transactions: &[PrintTransaction],
+    is_flow: impl Fn(&str) -> bool,
+    min_count: u32,
+) -> Vec<PayeeStats> {
+    let mut payees: BTreeMap<&str, Tally> = BTreeMap::new();
+    for txn in transactions {
+        let tally = payees
+            .entry(payee(&txn.description))
+            .or_insert_with(|| Tally {
+                transactions: 0,
+                first_date: txn.date,
+                last_date: txn.date,
+                total: MultiAmount::new(),
+                accounts: BTreeMap::new(),
+            });
+        tally.transactions += 1;
+        tally.first_date = tally.first_date.min(txn.date);
+        tally.last_date = tally.last_date.max(txn.date);
+        for posting in txn.postings.iter().filter(|p| is_flow(&p.account)) {
+            *tally.accounts.entry(&posting.account).or_default() += 1;
+            for amount in &posting.amounts {
+                tally.total.add_quantity(&amount.commodity, amount.quantity);
+            }
+        }
+    }
+
+    let mut stats: Vec<PayeeStats> = payees
+        .into_iter()
+        .filter(|(_, tally)| tally.transactions >= min_count)
+        .map(|(name, tally)| {
+            let count = Decimal::from(tally.transactions);
+            let mut average = MultiAmount::new();
+            for (commodity, quantity) in tally.total.iter() {
+                average.add_quantity(commodity, (quantity / count).round_dp(quantity.scale()));
+            }
+            // The first of the most used, in name order
+            let top_account = tally
+                .accounts
+                .iter()
+                .rev()
+                .max_by_key(|(_, uses)| **uses)
+                .map(|(account, _)| account.to_string());
+            PayeeStats {
+                payee: name.to_string(),
+                transaction_count: tally.transactions,
+                first_date: tally.first_date,
+                last_date: tally.last_date,
+                total: tally.total,
+                average,
+                top_account,
+            }
+        })
+        .collect();
+    // Stable, so ties stay in name order
+    stats.sort_by_key(|stats| std::cmp::Reverse(stats.transaction_count));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+
+    #[test]
+    fn export_bindings() {
+        PayeeStatsOptions::export_all().unwrap();
+        PayeeStats::export_all().unwrap();
+    }
+
+    fn transaction(date: &str, description: &str, postings: &[(&str, &str)]) -> PrintTransaction {
+        postings
+            .iter()
+            .fold(
+                PrintTransactionBuilder::new(1, date).description(description),
+                |txn, (account, dollars)| {
+                    txn.posting(PrintPostingBuilder::new(account).amount("$", dollars))
+                },
+            )
+            .build()
+    }
+
+    fn dollars(quantity: &str) -> MultiAmount {
+        let mut amount = MultiAmount::new();
+        amount.add_quantity("$", quantity.parse::<Decimal>().unwrap());
+        amount
+    }
+
+    fn is_flow(account: &str) -> bool {
+        account.starts_with("expenses") || account.starts_with("income")
+    }
+
+    fn sample() -> Vec<PrintTransaction> {
+        vec![
+            transaction(
+                "2024-02-02",
+                "Grocer",
+                &[("expenses:food", "25.25"), ("assets:cash", "-25.25")],
+            ),
+            transaction(
+                "2024-01-03",
+                "Grocer | weekly shop",
+                &[
+                    ("expenses:food", "40.10"),
+                    ("expenses:household", "9.90"),
+                    ("assets:cash", "-50.00"),
+                ],
+            ),
+            transaction(
+                "2024-03-09",
+                "Grocer",
+                &[("expenses:household", "10"), ("assets:cash", "-10")],
+            ),
+            transaction(
+                "2024-01-31",
+                "Employer",
+                &[("assets:bank", "1000"), ("income:salary", "-1000")],
+            ),
+            transaction(
+                "2024-02-10",
+                "Savings transfer",
+                &[("assets:savings", "100"), ("assets:bank", "-100")],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_payee_stats() {
+        let stats = payee_stats(&sample(), is_flow, 0);
+        let payees: Vec<&str> = stats.iter().map(|s| s.payee.as_str()).collect();
+        assert_eq!(payees, ["Grocer", "Employer", "Savings transfer"]);
+
+        let grocer = &stats[0];
+        assert_eq!(grocer.transaction_count, 3);
+        assert_eq!(grocer.first_date.to_string(), "2024-01-03");
+        assert_eq!(grocer.last_date.to_string(), "2024-03-09");
+        assert_eq!(grocer.total, dollars("85.25"));
+        // 28.41666… rounded to the total's cents
+        assert_eq!(grocer.average, dollars("28.42"));
+        // Two uses each: the first by name wins
+        assert_eq!(grocer.top_account.as_deref(), Some("expenses:food"));
+
+        let employer = &stats[1];
+        assert_eq!(employer.total, dollars("-1000"));
+        assert_eq!(employer.top_account.as_deref(), Some("income:salary"));
+
+        // Transfers have no expense or income postings
+        let transfer = &stats[2];
+        assert!(transfer.total.is_zero() && transfer.average.is_zero());
+        assert_eq!(transfer.top_account, None);
+    }
+
+    #[test]
+    fn test_min_count() {
+        let stats = payee_stats(&sample(), is_flow, 2);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].payee, "Grocer");
+    }
+
+    #[test]
+    fn test_print_options() {
+        let options = PayeeStatsOptions::new()
+            .begin("2024-01-01")
+            .end("2024-04-01")
+            .query("not:desc:transfer");
+        let print = options.print_options();
+        assert!(print.explicit);
+        assert_eq!(print.end.as_deref(), Some("2024-04-01"));
+        assert_eq!(print.queries, ["not:desc:transfer"]);
+    }
+}
@@ -0,0 +1,34 @@
+//! Journal text for running reports on changes that haven't been saved,
+//! with the `_from_text` report functions
+
+use crate::Result;
+use std::path::{self, Path};
+
+/// Journal text reading the saved `journal_file` followed by `pending`,
+/// usually transactions rendered with
+/// [`crate::TransactionBuilder::to_journal_string`]
+///
+/// The saved journal is pulled in with an `include` of its absolute path
+/// rather than pasted in, so its own relative includes still resolve and its
+/// directives apply to the pending text as they would after saving.
+pub fn overlay_journal(journal_file: impl AsRef<Path>, pending: &str) -> Result<String> {
+    let journal_file = path::absolute(journal_file)?;
+    Ok(format!("include {}\n\n{}", journal_file.display(), pending))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_journal_includes_absolute_path() {
+        let text = overlay_journal("tests/fixtures/test.journal", "2024-02-01 pending\n").unwrap();
+        let (include, rest) = text.split_once('\n').unwrap();
+
+        let included = Path::new(include.strip_prefix("include ").unwrap());
+        assert!(included.is_absolute());
+        assert!(included.ends_with("tests/fixtures/test.journal"));
+        assert!(included.is_file());
+        assert_eq!(rest, "\n2024-02-01 pending\n");
+    }
+}
@@ -0,0 +1,222 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// A single hledger query term, rendered to a command-line argument with
+/// [`Query::to_arg`]
+///
+/// Text values of `Account`, `Description`, `Payee`, `Note` and `Code` are
+/// regular expressions, as in hledger. `Currency` matches a commodity symbol
+/// literally.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type", content = "value")]
+pub enum Query {
+    /// `acct:REGEX`
+    Account(String),
+    /// `desc:REGEX`
+    Description(String),
+    /// `payee:REGEX`
+    Payee(String),
+    /// `note:REGEX`
+    Note(String),
+    /// `tag:NAME` or `tag:NAME=VALUE`
+    Tag { name: String, value: Option<String> },
+    /// `amt:OPVALUE`, e.g. `amt:>100`
+    Amount {
+        op: AmountOp,
+        #[ts(type = "string")]
+        value: Decimal,
+    },
+    /// `cur:SYMBOL`
+    Currency(String),
+    /// `status:`, `status:!` or `status:*`
+    Status(Status),
+    /// `date:PERIODEXPR`, e.g. `date:2024` or `date:2024-01..2024-04`
+    Date(String),
+    /// `code:REGEX`
+    Code(String),
+    /// `not:QUERY`
+    Not(Box<Query>),
+    /// A query passed through unchanged
+    Expr(String),
+}
+
+/// Comparison used by an amount query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum AmountOp {
+    /// `=`
+    Eq,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+}
+
+/// Clearing status of a transaction or posting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum Status {
+    Unmarked,
+    Pending,
+    Cleared,
+}
+
+impl Query {
+    /// The argument to pass to hledger for this query
+    pub fn to_arg(&self) -> String {
+        match self {
+            Query::Account(pattern) => format!("acct:{}", pattern),
+            Query::Description(pattern) => format!("desc:{}", pattern),
+            Query::Payee(pattern) => format!("payee:{}", pattern),
+            Query::Note(pattern) => format!("note:{}", pattern),
+            Query::Tag { name, value: None } => format!("tag:{}", name),
+            Query::Tag {
+                name,
+                value: Some(value),
+            } => format!("tag:{}={}", name, value),
+            Query::Amount { op, value } => format!("amt:{}{}", op.symbol(), value),
+            Query::Currency(symbol) => format!("cur:{}", escape_regex(symbol)),
+            Query::Status(status) => format!("status:{}", status.mark()),
+            Query::Date(period) => format!("date:{}", period),
+            Query::Code(pattern) => format!("code:{}", pattern),
+            Query::Not(query) => format!("not:{}", query.to_arg()),
+            Query::Expr(expr) => expr.clone(),
+        }
+    }
+}
+
+impl AmountOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            AmountOp::Eq => "=",
+            AmountOp::Lt => "<",
+            AmountOp::Le => "<=",
+            AmountOp::Gt => ">",
+            AmountOp::Ge => ">=",
+        }
+    }
+}
+
+impl Status {
+    /// The status mark as written in a journal: nothing, `!` or `*`
+    pub(crate) fn mark(self) -> &'static str {
+        match self {
+            Status::Unmarked => "",
+            Status::Pending => "!",
+            Status::Cleared => "*",
+        }
+    }
+}
+
+/// Backslash-escape regex metacharacters so a symbol like `$` matches itself
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        Query::export_all().unwrap();
+        AmountOp::export_all().unwrap();
+        Status::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_text_queries() {
+        assert_eq!(Query::Account("assets".into()).to_arg(), "acct:assets");
+        assert_eq!(
+            Query::Description("whole foods".into()).to_arg(),
+            "desc:whole foods"
+        );
+        assert_eq!(Query::Payee("amazon".into()).to_arg(), "payee:amazon");
+        assert_eq!(Query::Note("refund".into()).to_arg(), "note:refund");
+        assert_eq!(Query::Code("^12".into()).to_arg(), "code:^12");
+        assert_eq!(
+            Query::Date("2024-01..2024-04".into()).to_arg(),
+            "date:2024-01..2024-04"
+        );
+    }
+
+    #[test]
+    fn test_tag_queries() {
+        let tag = Query::Tag {
+            name: "trip".into(),
+            value: None,
+        };
+        assert_eq!(tag.to_arg(), "tag:trip");
+
+        let tag = Query::Tag {
+            name: "trip".into(),
+            value: Some("paris".into()),
+        };
+        assert_eq!(tag.to_arg(), "tag:trip=paris");
+    }
+
+    #[test]
+    fn test_amount_queries() {
+        let amount = |op, value: &str| Query::Amount {
+            op,
+            value: value.parse().unwrap(),
+        };
+        assert_eq!(amount(AmountOp::Eq, "50").to_arg(), "amt:=50");
+        assert_eq!(amount(AmountOp::Lt, "-10.5").to_arg(), "amt:<-10.5");
+        assert_eq!(amount(AmountOp::Le, "0").to_arg(), "amt:<=0");
+        assert_eq!(amount(AmountOp::Gt, "100").to_arg(), "amt:>100");
+        assert_eq!(amount(AmountOp::Ge, "99.99").to_arg(), "amt:>=99.99");
+    }
+
+    #[test]
+    fn test_currency_query_escapes_symbol() {
+        assert_eq!(Query::Currency("USD".into()).to_arg(), "cur:USD");
+        assert_eq!(Query::Currency("$".into()).to_arg(), "cur:\\$");
+    }
+
+    #[test]
+    fn test_status_queries() {
+        assert_eq!(Query::Status(Status::Unmarked).to_arg(), "status:");
+        assert_eq!(Query::Status(Status::Pending).to_arg(), "status:!");
+        assert_eq!(Query::Status(Status::Cleared).to_arg(), "status:*");
+    }
+
+    #[test]
+    fn test_not_and_expr_queries() {
+        let not_usd = Query::Not(Box::new(Query::Currency("USD".into())));
+        assert_eq!(not_usd.to_arg(), "not:cur:USD");
+        assert_eq!(Query::Expr("real:1".into()).to_arg(), "real:1");
+    }
+
+    #[test]
+    fn test_query_serde_round_trip() {
+        let query = Query::Not(Box::new(Query::Tag {
+            name: "trip".into(),
+            value: Some("paris".into()),
+        }));
+        let json = serde_json::to_value(&query).unwrap();
+        assert_eq!(json["type"], "Not");
+        assert_eq!(json["value"]["type"], "Tag");
+        assert_eq!(json["value"]["value"]["name"], "trip");
+        assert_eq!(serde_json::from_value::<Query>(json).unwrap(), query);
+
+        let json = serde_json::json!({"type": "Amount", "value": {"op": "Gt", "value": "100"}});
+        assert_eq!(
+            serde_json::from_value::<Query>(json).unwrap().to_arg(),
+            "amt:>100"
+        );
+    }
+}
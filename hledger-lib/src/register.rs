@@ -0,0 +1,218 @@
+use crate::amount::MultiAmount;
+use crate::commands::print::PrintTransaction;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// One posting in a register view, with the running total after it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct RunningBalanceEntry {
+    /// Posting date (the transaction's unless the posting has its own)
+    #[ts(type = "string")]
+    pub date: NaiveDate,
+    /// Index of the transaction in the journal
+    pub transaction_index: u32,
+    pub description: String,
+    pub account: String,
+    /// The posting's amount
+    pub change: MultiAmount,
+    /// Sum of this and all earlier matching postings
+    pub balance: MultiAmount,
+}
+
+/// Running balances of the postings whose account matches `account_query`
+///
+/// The query is matched like hledger's account queries, as a case-insensitive
+/// infix; `^` and `$` anchor it to the start and end of the account name.
+/// Other regex syntax is not supported. Postings are ordered by date, keeping
+/// journal order for the same date.
+///
+/// Postings with inferred amounts only carry them in print output fetched
+/// with [`PrintOptions::explicit`](crate::PrintOptions::explicit); otherwise
+/// they count as zero.
+pub fn compute_running_balances(
+    transactions: &[PrintTransaction],
+    account_query: &str,
+) -> Vec<RunningBalanceEntry> {
+    let mut postings: Vec<_> = transactions
+        .iter()
+        .flat_map(|txn| {
+            txn.postings
+                .iter()
+                .filter(|posting| account_matches(&posting.account, account_query))
+                .map(move |posting| (posting.date.unwrap_or(txn.date), txn, posting))
+        })
+        .collect();
+    // Stable, so same-date postings keep their order
+    postings.sort_by_key(|(date, txn, _)| (*date, txn.index));
+
+    let mut balance = MultiAmount::new();
+    postings
+        .into_iter()
+        .map(|(date, txn, posting)| {
+            let mut change = MultiAmount::new();
+            for amount in &posting.amounts {
+                change.add_quantity(&amount.commodity, amount.quantity);
+            }
+            balance += &change;
+            RunningBalanceEntry {
+                date,
+                transaction_index: txn.index,
+                description: txn.description.clone(),
+                account: posting.account.clone(),
+                change,
+                balance: balance.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Match an account name against a query as described on
+/// [`compute_running_balances`]
+pub(crate) fn account_matches(account: &str, query: &str) -> bool {
+    let account = account.to_lowercase();
+    let query = query.to_lowercase();
+    let (query, anchored_start) = match query.strip_prefix('^') {
+        Some(rest) => (rest, true),
+        None => (query.as_str(), false),
+    };
+    let (query, anchored_end) = match query.strip_suffix('$') {
+        Some(rest) => (rest, true),
+        None => (query, false),
+    };
+
+    match (anchored_start, anchored_end) {
+        (true, true) => account == query,
+        (true, false) => account.starts_with(query),
+        (false, true) => account.ends_with(query),
+        (false, false) => account.contains(query),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn export_bindings() {
+        RunningBalanceEntry::export_all().unwrap();
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn posting(account: &str, amounts: &[(&str, &str)]) -> PrintPostingBuilder {
+        amounts.iter().fold(
+            PrintPostingBuilder::new(account),
+            |posting, (commodity, quantity)| posting.amount(commodity, quantity),
+        )
+    }
+
+    fn transaction(index: u32, day: &str, postings: Vec<PrintPostingBuilder>) -> PrintTransaction {
+        postings
+            .into_iter()
+            .fold(
+                PrintTransactionBuilder::new(index, day).description(&format!("txn {}", index)),
+                PrintTransactionBuilder::posting,
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_running_balance_orders_by_date() {
+        let transactions = vec![
+            transaction(
+                1,
+                "2024-01-10",
+                vec![
+                    posting("expenses:food", &[("$", "20")]),
+                    posting("assets:checking", &[("$", "-20")]),
+                ],
+            ),
+            // Listed later in the journal but dated earlier
+            transaction(
+                2,
+                "2024-01-01",
+                vec![
+                    posting("assets:checking", &[("$", "100")]),
+                    posting("income:salary", &[("$", "-100")]),
+                ],
+            ),
+            transaction(
+                3,
+                "2024-01-10",
+                vec![
+                    posting("assets:Checking", &[("$", "-5")]),
+                    posting("expenses:fees", &[("$", "5")]),
+                ],
+            ),
+        ];
+
+        let entries = compute_running_balances(&transactions, "checking");
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|e| (e.transaction_index, e.balance.get("$")))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (2, Decimal::from(100)),
+                (1, Decimal::from(80)),
+                (3, Decimal::from(75))
+            ]
+        );
+        assert_eq!(entries[0].date, date("2024-01-01"));
+        assert_eq!(entries[0].description, "txn 2");
+        assert_eq!(entries[1].change.get("$"), Decimal::from(-20));
+    }
+
+    #[test]
+    fn test_running_balance_multi_commodity() {
+        let dated = posting("assets:broker", &[("AAPL", "2")]).date("2024-03-01");
+        let transactions = vec![
+            transaction(
+                1,
+                "2024-02-01",
+                vec![
+                    posting("assets:broker", &[("$", "1000")]),
+                    posting("assets:checking", &[("$", "-1000")]),
+                ],
+            ),
+            transaction(
+                2,
+                "2024-02-15",
+                vec![
+                    dated,
+                    posting("assets:broker", &[("$", "-300"), ("EUR", "5")]),
+                ],
+            ),
+        ];
+
+        let entries = compute_running_balances(&transactions, "^assets:broker$");
+        assert_eq!(entries.len(), 3);
+
+        // The posting dated 2024-03-01 moves after its sibling
+        assert_eq!(entries[1].balance.get("$"), Decimal::from(700));
+        assert_eq!(entries[1].balance.get("EUR"), Decimal::from(5));
+        assert_eq!(entries[2].date, date("2024-03-01"));
+        assert_eq!(entries[2].balance.get("$"), Decimal::from(700));
+        assert_eq!(entries[2].balance.get("AAPL"), Decimal::from(2));
+    }
+
+    #[test]
+    fn test_account_matching() {
+        assert!(account_matches("assets:bank:checking", "BANK"));
+        assert!(account_matches("assets:bank:checking", "^assets"));
+        assert!(!account_matches("liabilities:assets", "^assets"));
+        assert!(account_matches("assets:bank:checking", "checking$"));
+        assert!(!account_matches("assets:bank:checking", "^assets:bank$"));
+        assert!(account_matches("assets:bank", "^assets:bank$"));
+        assert!(compute_running_balances(&[], "assets").is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+//! A starter journal for someone who doesn't have one yet: a default
+//! commodity, a standard chart of accounts and their opening balances
+
+use crate::amount::{default_style_for, format_amount};
+use crate::commands::print::{AmountStyle, DigitGroups, Precision};
+use crate::transaction::{JournalStyle, PostingBuilder, TransactionBuilder};
+use crate::write::{check_journal, WriteOptions};
+use crate::{HLedgerError, Result};
+use chrono::{Datelike, Local, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Account the opening balances are balanced against
+pub const OPENING_BALANCES_ACCOUNT: &str = "equity:opening balances";
+
+/// The accounts a new journal declares, each with its hledger account type
+/// (`A`ssets, `L`iabilities, `E`quity, `R`evenue, e`X`penses)
+const CHART_OF_ACCOUNTS: &[(&str, &str)] = &[
+    ("assets", "A"),
+    ("assets:bank:checking", "A"),
+    ("assets:bank:savings", "A"),
+    ("assets:cash", "A"),
+    ("liabilities", "L"),
+    ("liabilities:credit card", "L"),
+    ("equity", "E"),
+    (OPENING_BALANCES_ACCOUNT, "E"),
+    ("income", "R"),
+    ("income:salary", "R"),
+    ("income:interest", "R"),
+    ("expenses", "X"),
+    ("expenses:food", "X"),
+    ("expenses:housing", "X"),
+    ("expenses:transport", "X"),
+    ("expenses:utilities", "X"),
+    ("expenses:health", "X"),
+    ("expenses:entertainment", "X"),
+];
+
+/// How a new journal is split into files
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum JournalLayout {
+    /// Everything in the one file
+    #[default]
+    SingleFile,
+    /// Directives in the main file, which includes a `YEAR.journal` next
+    /// to it for the transactions of the opening year
+    PerYear,
+}
+
+/// An account's balance when the journal starts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct OpeningBalance {
+    pub account: String,
+    #[ts(type = "string")]
+    pub quantity: Decimal,
+    /// The template's currency when `None`
+    pub commodity: Option<String>,
+}
+
+/// What goes into a new journal
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct JournalTemplate {
+    /// Commodity amounts are in unless they say otherwise, e.g. `$` or
+    /// `EUR`; declared with a `commodity` directive
+    pub currency: String,
+    /// Balances to start with, booked against
+    /// [`OPENING_BALANCES_ACCOUNT`]; none leaves out the transaction
+    pub opening_balances: Vec<OpeningBalance>,
+    /// Date of the opening balances; today when `None`
+    #[ts(type = "string | null")]
+    pub opening_date: Option<NaiveDate>,
+    pub layout: JournalLayout,
+}
+
+// Implementation for builder pattern
+impl JournalTemplate {
+    pub fn new(currency: impl Into<String>) -> Self {
+        Self {
+            currency: currency.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn opening_balance(mut self, account: impl Into<String>, quantity: Decimal) -> Self {
+        self.opening_balances.push(OpeningBalance {
+            account: account.into(),
+            quantity,
+            commodity: None,
+        });
+        self
+    }
+
+    pub fn opening_date(mut self, date: NaiveDate) -> Self {
+        self.opening_date = Some(date);
+        self
+    }
+
+    pub fn per_year(mut self) -> Self {
+        self.layout = JournalLayout::PerYear;
+        self
+    }
+}
+
+/// Write a new journal at `path` from `template`, returning the files
+/// written, `path` first
+///
+/// Existing files are never overwritten: if any of the files is already
+/// there the call fails with an `AlreadyExists` I/O error before writing.
+/// The journal is checked with `hledger check` afterwards (with the
+/// options' hledger path and timeout), and removed again if it fails.
+/// Amounts are laid out with the options' style.
+pub fn create_journal(
+    path: &Path,
+    template: &JournalTemplate,
+    options: &WriteOptions,
+) -> Result<Vec<String>> {
+    let files = render(path, template, &options.style)?;
+    for (file, _) in &files {
+        if file.exists() {
+            return Err(HLedgerError::Io(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", file.display()),
+            )));
+        }
+    }
+
+    let mut written: Vec<&Path> = Vec::new();
+    let result = files.iter().try_for_each(|(file, contents)| {
+        let mut out = OpenOptions::new().write(true).create_new(true).open(file)?;
+        written.push(file);
+        out.write_all(contents.as_bytes())?;
+        out.sync_all()?;
+        Ok(())
+    });
+    let result = result.and_then(|()| check_journal(path, options));
+    if let Err(e) = result {
+        for file in written {
+            let _ = fs::remove_file(file);
+        }
+        return Err(e);
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(file, _)| file.to_string_lossy().into_owned())
+        .collect())
+}
+
+/// Each file of the new journal with its contents
+fn render(
+    path: &Path,
+    template: &JournalTemplate,
+    style: &JournalStyle,
+) -> Result<Vec<(PathBuf, String)>> {
+    let currency = template.currency.trim();
+    if currency.is_empty() {
+        return Err(HLedgerError::InvalidTransaction(
+            "the journal needs a currency".to_string(),
+        ));
+    }
+    let date = template
+        .opening_date
+        .unwrap_or_else(|| Local::now().date_naive());
+
+    let mut directives = format!(
+        "; Default commodity, and how its amounts are shown\ncommodity {}\n\n",
+        format_amount(
+            currency,
+            Decimal::new(100000, 2),
+            &currency_style(currency),
+            None
+        )
+    );
+    directives.push_str("; Chart of accounts\n");
+    for (account, account_type) in CHART_OF_ACCOUNTS {
+        directives.push_str(&format!("account {}  ; type: {}\n", account, account_type));
+    }
+
+    let opening = opening_transaction(template, currency, date, style)?;
+    match (template.layout, opening) {
+        (_, None) => Ok(vec![(path.to_path_buf(), directives)]),
+        (JournalLayout::SingleFile, Some(opening)) => Ok(vec![(
+            path.to_path_buf(),
+            format!("{}\n{}", directives, opening),
+        )]),
+        (JournalLayout::PerYear, Some(opening)) => {
+            let name = format!("{}.journal", date.year());
+            let year_file = path.with_file_name(&name);
+            if year_file == path {
+                return Err(HLedgerError::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("the main journal can't be named {}", name),
+                )));
+            }
+            Ok(vec![
+                (
+                    path.to_path_buf(),
+                    format!(
+                        "{}\n; Transactions, a file per year\ninclude {}\n",
+                        directives, name
+                    ),
+                ),
+                (year_file, opening),
+            ])
+        }
+    }
+}
+
+/// Two decimals and thousands separators, the commodity placed as hledger
+/// would by default
+fn currency_style(currency: &str) -> AmountStyle {
+    AmountStyle {
+        digit_groups: Some(DigitGroups {
+            separator: ',',
+            sizes: vec![3],
+        }),
+        precision: Precision::Digits(2),
+        ..default_style_for(currency)
+    }
+}
+
+/// The opening balances as a transaction, if there are any
+fn opening_transaction(
+    template: &JournalTemplate,
+    currency: &str,
+    date: NaiveDate,
+    style: &JournalStyle,
+) -> Result<Option<String>> {
+    if template.opening_balances.is_empty() {
+        return Ok(None);
+    }
+    let mut txn = TransactionBuilder::new(date, "Opening balances");
+    for balance in &template.opening_balances {
+        let commodity = balance.commodity.as_deref().unwrap_or(currency);
+        txn =
+            txn.posting(PostingBuilder::new(&balance.account).amount(balance.quantity, commodity));
+    }
+    txn = txn.posting(PostingBuilder::new(OPENING_BALANCES_ACCOUNT));
+
+    let mut style = style.clone();
+    style
+        .commodity_styles
+        .entry(currency.to_string())
+        .or_insert_with(|| currency_style(currency));
+    txn.to_journal_string(&style).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        JournalTemplate::export_all().unwrap();
+    }
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+    }
+
+    fn template() -> JournalTemplate {
+        JournalTemplate::new("$")
+            .opening_date(date())
+            .opening_balance("assets:bank:checking", Decimal::new(250000, 2))
+            .opening_balance("liabilities:credit card", Decimal::new(-4000, 2))
+    }
+
+    #[test]
+    fn test_single_file() {
+        let path = Path::new("/books/main.journal");
+        let files = render(path, &template(), &JournalStyle::default()).unwrap();
+        assert_eq!(files.len(), 1);
+        let (file, contents) = &files[0];
+        assert_eq!(file, path);
+        assert!(contents.starts_with("; Default commodity"));
+        assert!(contents.contains("commodity $1,000.00\n"));
+        assert!(contents.contains("account assets:bank:checking  ; type: A\n"));
+        assert!(contents.contains("account equity:opening balances  ; type: E\n"));
+        assert!(contents.ends_with(
+            "\n2024-01-01 Opening balances\n    \
+             assets:bank:checking   $2,500.00\n    \
+             liabilities:credit card  $-40.00\n    \
+             equity:opening balances\n"
+        ));
+    }
+
+    #[test]
+    fn test_per_year_files() {
+        let path = Path::new("/books/main.journal");
+        let files = render(path, &template().per_year(), &JournalStyle::default()).unwrap();
+        let names: Vec<&Path> = files.iter().map(|(file, _)| file.as_path()).collect();
+        assert_eq!(
+            names,
+            [
+                Path::new("/books/main.journal"),
+                Path::new("/books/2024.journal")
+            ]
+        );
+        assert!(files[0].1.ends_with("include 2024.journal\n"));
+        assert!(!files[0].1.contains("Opening balances"));
+        assert!(files[1].1.starts_with("2024-01-01 Opening balances\n"));
+
+        // Nothing to put in a year file without opening balances
+        let bare = JournalTemplate::new("EUR").opening_date(date()).per_year();
+        let files = render(path, &bare, &JournalStyle::default()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].1.contains("commodity 1,000.00 EUR\n"));
+    }
+
+    #[test]
+    fn test_rejected_templates() {
+        let path = Path::new("/books/main.journal");
+        let style = JournalStyle::default();
+        assert!(render(path, &JournalTemplate::new(" "), &style).is_err());
+
+        let bad_account = JournalTemplate::new("$").opening_balance("assets:  cash", Decimal::ONE);
+        assert!(matches!(
+            render(path, &bad_account, &style),
+            Err(HLedgerError::InvalidTransaction(_))
+        ));
+
+        let clash = template().per_year();
+        assert!(render(Path::new("/books/2024.journal"), &clash, &style).is_err());
+    }
+
+    #[test]
+    fn test_existing_files_are_left_alone() {
+        let path = std::env::temp_dir().join(format!("scaffold-{}.journal", std::process::id()));
+        fs::write(&path, "; mine\n").unwrap();
+        let result = create_journal(&path, &template(), &WriteOptions::new());
+        assert!(matches!(
+            result,
+            Err(HLedgerError::Io(e)) if e.kind() == io::ErrorKind::AlreadyExists
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "; mine\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_check_removes_files() {
+        let dir = std::env::temp_dir().join(format!("scaffold-check-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("main.journal");
+        let options = WriteOptions::new().hledger_path("/nonexistent/hledger");
+        assert!(create_journal(&path, &template().per_year(), &options).is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,376 @@
+//! Full-text search over a journal's transactions
+//!
+//! A [`SearchIndex`] keeps each transaction's searchable text folded once,
+//! to lowercase and without diacritics, so a query only scans strings.
+//! [`ReportCache::search_transactions_cached`](crate::ReportCache::search_transactions_cached)
+//! keeps the index until one of the journal's files changes.
+
+use crate::commands::print::{get_print, PrintOptions, PrintReport, PrintTransaction};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+/// The part of a transaction a query matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum SearchField {
+    Description,
+    /// The transaction's own comment
+    Comment,
+    /// A posting's comment
+    PostingComment,
+    /// A transaction tag, as `name:value` (or `name` without a value)
+    Tag,
+    /// A posting's account
+    Account,
+}
+
+/// Where a query term was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct SearchHighlight {
+    pub field: SearchField,
+    /// Which posting or tag, for fields a transaction has several of; 0
+    /// otherwise
+    pub item: u32,
+    /// Byte offset of the first matched byte in the field's text
+    pub start: u32,
+    /// Byte offset just past the match
+    pub end: u32,
+}
+
+/// A transaction that matched, with every place its terms were found
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct SearchMatch {
+    /// The transaction, with its source span for jumping to it
+    pub transaction: PrintTransaction,
+    /// Non-overlapping ranges, in field order
+    pub highlights: Vec<SearchHighlight>,
+}
+
+/// One page of matches
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub matches: Vec<SearchMatch>,
+    /// Matches across all pages
+    pub total: u32,
+}
+
+/// Text folded for matching, with the original byte range each folded
+/// byte came from
+#[derive(Debug, Clone)]
+struct Folded {
+    text: String,
+    origins: Vec<(u32, u32)>,
+}
+
+/// Lowercase `text` and strip its diacritics, so `Crème` matches `creme`
+fn fold(text: &str) -> Folded {
+    let mut folded = Folded {
+        text: String::with_capacity(text.len()),
+        origins: Vec::with_capacity(text.len()),
+    };
+    for (start, c) in text.char_indices() {
+        let origin = (start as u32, (start + c.len_utf8()) as u32);
+        for lower in c.to_lowercase() {
+            decompose_canonical(lower, |part| {
+                if !is_combining_mark(part) {
+                    folded.text.push(part);
+                    folded
+                        .origins
+                        .extend(std::iter::repeat_n(origin, part.len_utf8()));
+                }
+            });
+        }
+    }
+    folded
+}
+
+#[derive(Debug, Clone)]
+struct SearchText {
+    field: SearchField,
+    item: u32,
+    folded: Folded,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    transaction: PrintTransaction,
+    texts: Vec<SearchText>,
+}
+
+impl Entry {
+    fn new(transaction: PrintTransaction) -> Self {
+        let mut texts = vec![
+            text(SearchField::Description, 0, &transaction.description),
+            text(SearchField::Comment, 0, &transaction.comment),
+        ];
+        for (i, (name, value)) in transaction.tags.iter().enumerate() {
+            let tag = if value.is_empty() {
+                name.clone()
+            } else {
+                format!("{}:{}", name, value)
+            };
+            texts.push(text(SearchField::Tag, i, &tag));
+        }
+        for (i, posting) in transaction.postings.iter().enumerate() {
+            texts.push(text(SearchField::Account, i, &posting.account));
+            texts.push(text(SearchField::PostingComment, i, &posting.comment));
+        }
+        texts.retain(|text| !text.folded.text.is_empty());
+        Self { transaction, texts }
+    }
+
+    fn contains(&self, term: &str) -> bool {
+        self.texts
+            .iter()
+            .any(|text| text.folded.text.contains(term))
+    }
+
+    /// Every occurrence of the terms, overlapping ones merged
+    fn highlights(&self, terms: &[&str]) -> Vec<SearchHighlight> {
+        let mut highlights = Vec::new();
+        for text in &self.texts {
+            let folded = &text.folded;
+            let mut ranges: Vec<(usize, usize)> = terms
+                .iter()
+                .flat_map(|term| {
+                    folded
+                        .text
+                        .match_indices(term)
+                        .map(|(start, found)| (start, start + found.len()))
+                })
+                .collect();
+            ranges.sort_unstable();
+
+            let mut merged: Vec<(usize, usize)> = Vec::new();
+            for (start, end) in ranges {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+            highlights.extend(merged.into_iter().map(|(start, end)| SearchHighlight {
+                field: text.field,
+                item: text.item,
+                start: folded.origins[start].0,
+                end: folded.origins[end - 1].1,
+            }));
+        }
+        highlights
+    }
+}
+
+fn text(field: SearchField, item: usize, text: &str) -> SearchText {
+    SearchText {
+        field,
+        item: item as u32,
+        folded: fold(text),
+    }
+}
+
+/// A journal's transactions, ready to search, latest first
+#[derive(Debug, Clone)]
+pub struct SearchIndex {
+    entries: Vec<Entry>,
+}
+
+impl SearchIndex {
+    pub fn new(transactions: PrintReport) -> Self {
+        let mut entries: Vec<Entry> = transactions.into_iter().map(Entry::new).collect();
+        entries.sort_by(|a, b| {
+            (b.transaction.date, b.transaction.index)
+                .cmp(&(a.transaction.date, a.transaction.index))
+        });
+        Self { entries }
+    }
+
+    /// Transactions containing every word of `query` in their description,
+    /// comments, tags or accounts, ignoring case and diacritics
+    ///
+    /// Transactions where the whole query appears as written come first,
+    /// then the rest; each group is latest first. `offset` and `limit` pick
+    /// the page; an empty query matches nothing.
+    pub fn search(&self, query: &str, limit: usize, offset: usize) -> SearchResults {
+        let query = fold(query).text;
+        let words: Vec<&str> = query.split_whitespace().collect();
+        let phrase = words.join(" ");
+        let mut terms = words.clone();
+        terms.sort_unstable();
+        terms.dedup();
+        if terms.is_empty() {
+            return SearchResults::default();
+        }
+
+        let mut found: Vec<(bool, &Entry)> = self
+            .entries
+            .iter()
+            .filter(|entry| terms.iter().all(|term| entry.contains(term)))
+            .map(|entry| (words.len() > 1 && entry.contains(&phrase), entry))
+            .collect();
+        // Stable, so each group keeps the index's latest-first order
+        found.sort_by_key(|(exact, _)| !exact);
+
+        SearchResults {
+            total: found.len() as u32,
+            matches: found
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(|(_, entry)| SearchMatch {
+                    transaction: entry.transaction.clone(),
+                    highlights: entry.highlights(&terms),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Search the journal's transactions as [`SearchIndex::search`] does
+///
+/// Builds the index from `hledger print` each time; use
+/// [`ReportCache::search_transactions_cached`](crate::ReportCache::search_transactions_cached)
+/// to keep it between queries.
+pub fn search_transactions(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    query: &str,
+    limit: usize,
+    offset: usize,
+) -> Result<SearchResults> {
+    let transactions = get_print(hledger_path, journal_file, &PrintOptions::new())?;
+    Ok(SearchIndex::new(transactions).search(query, limit, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+
+    #[test]
+    fn export_bindings() {
+        SearchField::export_all().unwrap();
+        SearchHighlight::export_all().unwrap();
+        SearchMatch::export_all().unwrap();
+        SearchResults::export_all().unwrap();
+    }
+
+    fn transaction(index: u32, date: &str, description: &str, account: &str) -> PrintTransaction {
+        PrintTransactionBuilder::new(index, date)
+            .description(description)
+            .comment("trip: Zürich\n")
+            .tag("trip", "Zürich")
+            .posting(PrintPostingBuilder::new(account).comment("paid by Ana"))
+            .posting(PrintPostingBuilder::new("assets:cash"))
+            .span("main.journal", index * 3, index * 3 + 3)
+            .build()
+    }
+
+    fn indices(results: &SearchResults) -> Vec<u32> {
+        results
+            .matches
+            .iter()
+            .map(|found| found.transaction.index)
+            .collect()
+    }
+
+    #[test]
+    fn test_fold_keeps_original_offsets() {
+        let folded = fold("Crème BRÛLÉE");
+        assert_eq!(folded.text, "creme brulee");
+        assert_eq!(folded.origins.len(), folded.text.len());
+        // The `e` of `è` maps back to both of its bytes
+        assert_eq!(folded.origins[2], (2, 4));
+    }
+
+    #[test]
+    fn test_diacritics_match_either_way() {
+        let index = SearchIndex::new(vec![
+            transaction(1, "2024-01-05", "Café Crème", "expenses:food"),
+            transaction(2, "2024-01-06", "Bakery", "expenses:food"),
+        ]);
+
+        let results = index.search("cafe CREME", 10, 0);
+        assert_eq!(indices(&results), [1]);
+        let description = &results.matches[0].transaction.description;
+        let highlighted: Vec<&str> = results.matches[0]
+            .highlights
+            .iter()
+            .filter(|h| h.field == SearchField::Description)
+            .map(|h| &description[h.start as usize..h.end as usize])
+            .collect();
+        assert_eq!(highlighted, ["Café", "Crème"]);
+
+        // Tags and comments fold too
+        let results = index.search("zurich", 10, 0);
+        assert_eq!(results.total, 2);
+        let fields: Vec<SearchField> = results.matches[0]
+            .highlights
+            .iter()
+            .map(|h| h.field)
+            .collect();
+        assert_eq!(fields, [SearchField::Comment, SearchField::Tag]);
+        assert_eq!(
+            (
+                results.matches[0].highlights[1].start,
+                results.matches[0].highlights[1].end
+            ),
+            (5, 12)
+        );
+    }
+
+    #[test]
+    fn test_every_word_must_match_somewhere() {
+        let index = SearchIndex::new(vec![
+            transaction(1, "2024-01-05", "Coffee shop", "expenses:food"),
+            transaction(2, "2024-02-01", "Coffee beans", "expenses:groceries"),
+            transaction(3, "2024-03-01", "Petrol", "expenses:car"),
+        ]);
+
+        // Words may come from different fields
+        let results = index.search("coffee FOOD", 10, 0);
+        assert_eq!(indices(&results), [1]);
+        let highlights: Vec<(SearchField, u32, u32, u32)> = results.matches[0]
+            .highlights
+            .iter()
+            .map(|h| (h.field, h.item, h.start, h.end))
+            .collect();
+        assert_eq!(
+            highlights,
+            [
+                (SearchField::Description, 0, 0, 6),
+                (SearchField::Account, 0, 9, 13),
+            ]
+        );
+
+        assert_eq!(indices(&index.search("ana coffee", 10, 0)), [2, 1]);
+        assert_eq!(index.search("coffee tea", 10, 0).total, 0);
+        assert_eq!(index.search("   ", 10, 0).total, 0);
+    }
+
+    #[test]
+    fn test_exact_phrase_ranks_first_then_latest() {
+        let index = SearchIndex::new(vec![
+            transaction(1, "2023-06-01", "Coffee shop", "expenses:food"),
+            transaction(2, "2024-01-01", "Shop for coffee", "expenses:food"),
+            transaction(3, "2024-02-01", "Shop: coffee", "expenses:food"),
+            transaction(4, "2022-01-01", "The coffee shop", "expenses:food"),
+        ]);
+
+        let results = index.search("coffee shop", 10, 0);
+        assert_eq!(indices(&results), [1, 4, 3, 2]);
+
+        let page = index.search("coffee shop", 2, 1);
+        assert_eq!(page.total, 4);
+        assert_eq!(indices(&page), [4, 3]);
+    }
+}
@@ -0,0 +1,418 @@
+//! Copying a journal's transactions into SQLite, for ad-hoc SQL
+//!
+//! [`export_to_sqlite`] (behind the `sqlite` feature) writes four tables:
+//!
+//! - `transactions`: one row per transaction, unique on the `file` and
+//!   `line` it starts at
+//! - `postings`: each transaction's postings, in order (`position`)
+//! - `amounts`: one row per commodity of a posting's amount, with the exact
+//!   `quantity` as decimal TEXT and `quantity_real` as a REAL for quick
+//!   arithmetic
+//! - `tags`: transaction tags, and posting tags with their `posting_id`
+//!
+//! Dates are ISO 8601 TEXT (`2024-01-31`), so they sort and compare as
+//! dates. Exporting again updates transactions at the same source position
+//! in place and leaves the rest of the database alone.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Rows written by [`export_to_sqlite`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct ExportStats {
+    pub transactions: usize,
+    pub postings: usize,
+    pub amounts: usize,
+    pub tags: usize,
+}
+
+#[cfg(feature = "sqlite")]
+pub use export::export_to_sqlite;
+
+#[cfg(feature = "sqlite")]
+mod export {
+    use super::ExportStats;
+    use crate::commands::print::{PrintPosting, PrintTransaction};
+    use crate::{HLedgerError, Result};
+    use rusqlite::{params, Connection, Transaction};
+    use rust_decimal::prelude::ToPrimitive;
+    use std::path::Path;
+
+    const SCHEMA: &str = "
+        PRAGMA foreign_keys = ON;
+
+        CREATE TABLE IF NOT EXISTS transactions (
+            id INTEGER PRIMARY KEY,
+            file TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            date2 TEXT,
+            status TEXT NOT NULL,
+            code TEXT NOT NULL,
+            description TEXT NOT NULL,
+            comment TEXT NOT NULL,
+            UNIQUE (file, line)
+        );
+
+        CREATE TABLE IF NOT EXISTS postings (
+            id INTEGER PRIMARY KEY,
+            transaction_id INTEGER NOT NULL REFERENCES transactions (id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            account TEXT NOT NULL,
+            status TEXT NOT NULL,
+            comment TEXT NOT NULL,
+            posting_type TEXT NOT NULL,
+            date TEXT,
+            date2 TEXT
+        );
+        CREATE INDEX IF NOT EXISTS postings_transaction_id ON postings (transaction_id);
+        CREATE INDEX IF NOT EXISTS postings_account ON postings (account);
+
+        CREATE TABLE IF NOT EXISTS amounts (
+            id INTEGER PRIMARY KEY,
+            posting_id INTEGER NOT NULL REFERENCES postings (id) ON DELETE CASCADE,
+            commodity TEXT NOT NULL,
+            quantity TEXT NOT NULL,
+            quantity_real REAL NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS amounts_posting_id ON amounts (posting_id);
+
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY,
+            transaction_id INTEGER NOT NULL REFERENCES transactions (id) ON DELETE CASCADE,
+            posting_id INTEGER REFERENCES postings (id) ON DELETE CASCADE,
+            name TEXT NOT NULL,
+            value TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS tags_transaction_id ON tags (transaction_id);
+        CREATE INDEX IF NOT EXISTS tags_name ON tags (name);
+    ";
+
+    /// Write `transactions` into the SQLite database at `db_path`, creating
+    /// it and its tables if needed
+    ///
+    /// Everything is written in one SQLite transaction, so a failed export
+    /// leaves the database as it was. A transaction already in the database
+    /// at the same file and line is replaced, postings, amounts and tags
+    /// included; transactions without a source position are keyed on their
+    /// index under an empty file name.
+    pub fn export_to_sqlite(
+        transactions: &[PrintTransaction],
+        db_path: &Path,
+    ) -> Result<ExportStats> {
+        let sqlite_error = |e: rusqlite::Error| HLedgerError::Sqlite {
+            path: db_path.display().to_string(),
+            message: e.to_string(),
+        };
+
+        let mut conn = Connection::open(db_path).map_err(sqlite_error)?;
+        conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        let tx = conn.transaction().map_err(sqlite_error)?;
+        let mut stats = ExportStats::default();
+        for transaction in transactions {
+            write_transaction(&tx, transaction, &mut stats).map_err(sqlite_error)?;
+        }
+        tx.commit().map_err(sqlite_error)?;
+        Ok(stats)
+    }
+
+    /// The file and line a transaction starts at, which it is stored under
+    fn source_key(transaction: &PrintTransaction) -> (&str, u32) {
+        match (
+            &transaction.source_span,
+            transaction.source_positions.first(),
+        ) {
+            (Some(span), _) => (&span.file, span.start_line),
+            (None, Some(position)) => (&position.file, position.line),
+            (None, None) => ("", transaction.index),
+        }
+    }
+
+    fn write_transaction(
+        tx: &Transaction,
+        transaction: &PrintTransaction,
+        stats: &mut ExportStats,
+    ) -> rusqlite::Result<()> {
+        let (file, line) = source_key(transaction);
+        let transaction_id: i64 = tx
+            .prepare_cached(
+                "INSERT INTO transactions
+                    (file, line, date, date2, status, code, description, comment)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (file, line) DO UPDATE SET
+                    date = excluded.date,
+                    date2 = excluded.date2,
+                    status = excluded.status,
+                    code = excluded.code,
+                    description = excluded.description,
+                    comment = excluded.comment
+                 RETURNING id",
+            )?
+            .query_row(
+                params![
+                    file,
+                    line,
+                    transaction.date.to_string(),
+                    transaction.date2.map(|d| d.to_string()),
+                    transaction.status,
+                    transaction.code,
+                    transaction.description,
+                    transaction.comment,
+                ],
+                |row| row.get(0),
+            )?;
+        stats.transactions += 1;
+
+        // Replacing a transaction replaces everything under it; postings
+        // take their amounts and tags with them
+        tx.prepare_cached("DELETE FROM postings WHERE transaction_id = ?1")?
+            .execute([transaction_id])?;
+        tx.prepare_cached("DELETE FROM tags WHERE transaction_id = ?1")?
+            .execute([transaction_id])?;
+
+        write_tags(tx, transaction_id, None, &transaction.tags, stats)?;
+        for (position, posting) in transaction.postings.iter().enumerate() {
+            write_posting(tx, transaction_id, position, posting, stats)?;
+        }
+        Ok(())
+    }
+
+    fn write_posting(
+        tx: &Transaction,
+        transaction_id: i64,
+        position: usize,
+        posting: &PrintPosting,
+        stats: &mut ExportStats,
+    ) -> rusqlite::Result<()> {
+        tx.prepare_cached(
+            "INSERT INTO postings
+                (transaction_id, position, account, status, comment, posting_type, date, date2)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?
+        .execute(params![
+            transaction_id,
+            position,
+            posting.account,
+            posting.status,
+            posting.comment,
+            posting.posting_type,
+            posting.date.map(|d| d.to_string()),
+            posting.date2.map(|d| d.to_string()),
+        ])?;
+        let posting_id = tx.last_insert_rowid();
+        stats.postings += 1;
+
+        let mut insert_amount = tx.prepare_cached(
+            "INSERT INTO amounts (posting_id, commodity, quantity, quantity_real)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for amount in &posting.amounts {
+            insert_amount.execute(params![
+                posting_id,
+                amount.commodity,
+                amount.quantity.to_string(),
+                amount.quantity.to_f64().unwrap_or(f64::NAN),
+            ])?;
+            stats.amounts += 1;
+        }
+
+        write_tags(tx, transaction_id, Some(posting_id), &posting.tags, stats)
+    }
+
+    fn write_tags(
+        tx: &Transaction,
+        transaction_id: i64,
+        posting_id: Option<i64>,
+        tags: &[(String, String)],
+        stats: &mut ExportStats,
+    ) -> rusqlite::Result<()> {
+        let mut insert_tag = tx.prepare_cached(
+            "INSERT INTO tags (transaction_id, posting_id, name, value) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (name, value) in tags {
+            insert_tag.execute(params![transaction_id, posting_id, name, value])?;
+            stats.tags += 1;
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+        use rust_decimal::Decimal;
+        use std::path::PathBuf;
+
+        /// A database path under the system temp dir, removed on drop
+        struct TempDb(PathBuf);
+
+        impl TempDb {
+            fn new(name: &str) -> Self {
+                let path =
+                    std::env::temp_dir().join(format!("{}-{}.sqlite", name, std::process::id()));
+                let _ = std::fs::remove_file(&path);
+                Self(path)
+            }
+        }
+
+        impl Drop for TempDb {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+
+        fn transaction(line: u32, description: &str, dollars: &str) -> PrintTransaction {
+            let mut txn = PrintTransactionBuilder::new(line, "2024-03-01")
+                .description(description)
+                .tag("trip", "lisbon")
+                .posting(
+                    PrintPostingBuilder::new("expenses:food")
+                        .amount("$", dollars)
+                        .tag("card", "visa"),
+                )
+                .posting(
+                    PrintPostingBuilder::new("assets:checking")
+                        .amount("$", &format!("-{}", dollars)),
+                )
+                .span("main.journal", line, line + 3)
+                .build();
+            txn.status = "*".to_string();
+            txn
+        }
+
+        fn count(conn: &Connection, table: &str) -> usize {
+            conn.query_row(&format!("SELECT count(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        }
+
+        #[test]
+        fn test_export_round_trip() {
+            let db = TempDb::new("export-round-trip");
+            let transactions = vec![
+                transaction(1, "Grocer", "42.10"),
+                transaction(5, "Cafe", "3.55"),
+            ];
+            let stats = export_to_sqlite(&transactions, &db.0).unwrap();
+            assert_eq!(
+                stats,
+                ExportStats {
+                    transactions: 2,
+                    postings: 4,
+                    amounts: 4,
+                    tags: 4,
+                }
+            );
+
+            let conn = Connection::open(&db.0).unwrap();
+            assert_eq!(count(&conn, "transactions"), 2);
+            assert_eq!(count(&conn, "postings"), 4);
+            assert_eq!(count(&conn, "amounts"), 4);
+            assert_eq!(count(&conn, "tags"), 4);
+
+            // Exact sums come from the TEXT column, quick ones from the REAL
+            let quantities: Vec<String> = conn
+                .prepare(
+                    "SELECT quantity FROM amounts JOIN postings ON postings.id = posting_id
+                     WHERE account = 'expenses:food'",
+                )
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap();
+            let exact: Decimal = quantities
+                .iter()
+                .map(|q| q.parse::<Decimal>().unwrap())
+                .sum();
+            assert_eq!(exact, "45.65".parse::<Decimal>().unwrap());
+            let real: f64 = conn
+                .query_row("SELECT sum(quantity_real) FROM amounts", [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert!(real.abs() < 1e-9);
+
+            let (date, description): (String, String) = conn
+                .query_row(
+                    "SELECT date, description FROM transactions WHERE file = 'main.journal' AND line = 5",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap();
+            assert_eq!(date, "2024-03-01");
+            assert_eq!(description, "Cafe");
+
+            let posting_tag: (String, String) = conn
+                .query_row(
+                    "SELECT name, value FROM tags WHERE posting_id IS NOT NULL LIMIT 1",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap();
+            assert_eq!(posting_tag, ("card".to_string(), "visa".to_string()));
+        }
+
+        #[test]
+        fn test_export_upserts_by_source_position() {
+            let db = TempDb::new("export-upsert");
+            export_to_sqlite(
+                &[
+                    transaction(1, "Grocer", "42.10"),
+                    transaction(5, "Cafe", "3.55"),
+                ],
+                &db.0,
+            )
+            .unwrap();
+            // The transaction at line 5 was edited, one was added at line 9
+            export_to_sqlite(
+                &[
+                    transaction(5, "Cafe Central", "4.00"),
+                    transaction(9, "Rent", "900"),
+                ],
+                &db.0,
+            )
+            .unwrap();
+
+            let conn = Connection::open(&db.0).unwrap();
+            assert_eq!(count(&conn, "transactions"), 3);
+            assert_eq!(count(&conn, "postings"), 6);
+            assert_eq!(count(&conn, "amounts"), 6);
+            assert_eq!(count(&conn, "tags"), 6);
+            let description: String = conn
+                .query_row(
+                    "SELECT description FROM transactions WHERE line = 5",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(description, "Cafe Central");
+        }
+
+        #[test]
+        fn test_export_error_names_database() {
+            let db = TempDb::new("export-not-a-db");
+            std::fs::write(&db.0, "not a database, just text that is long enough").unwrap();
+            match export_to_sqlite(&[transaction(1, "Grocer", "1")], &db.0) {
+                Err(HLedgerError::Sqlite { path, .. }) => {
+                    assert_eq!(path, db.0.display().to_string())
+                }
+                other => panic!("Expected Sqlite error, got {:?}", other),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        ExportStats::export_all().unwrap();
+    }
+}
@@ -0,0 +1,548 @@
+//! Completions for the transaction form: account, payee, commodity and tag
+//! names from a journal, most used first
+//!
+//! A [`SuggestionIndex`] holds one journal's names and is costly to build
+//! (two hledger runs), so [`SuggestionCache`] keeps one per journal and
+//! rebuilds it on a background thread, answering from the previous index
+//! meanwhile. The same index proposes a whole transaction from the latest
+//! one with a similar description.
+
+use crate::commands::accounts::{get_accounts, AccountsOptions};
+use crate::commands::print::{get_print, PrintOptions, PrintTransaction};
+use crate::summary::payee;
+use crate::transaction::{PostingBuilder, TransactionBuilder};
+use crate::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use ts_rs::TS;
+
+/// What a suggestion completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum SuggestionKind {
+    Account,
+    Payee,
+    Commodity,
+    /// Tag names, without values
+    Tag,
+}
+
+/// Suggestions for one query
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct Suggestions {
+    pub values: Vec<String>,
+    /// The index is being rebuilt, so `values` may be out of date; asking
+    /// again shortly gives the fresh ones
+    pub refreshing: bool,
+}
+
+/// A transaction to start the form from, copied from the latest one with
+/// a similar description
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TransactionSuggestion {
+    /// Its description, tags, and postings with their accounts, amounts and
+    /// tags; `None` when no description matches
+    pub template: Option<TransactionBuilder>,
+    /// Date of the transaction the template was copied from
+    #[ts(type = "string | null")]
+    pub last_date: Option<NaiveDate>,
+    /// The index is being rebuilt, so the template may be out of date
+    pub refreshing: bool,
+}
+
+/// The names in one journal, each with how many times it's used
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SuggestionIndex {
+    accounts: Vec<(String, u32)>,
+    payees: Vec<(String, u32)>,
+    commodities: Vec<(String, u32)>,
+    tags: Vec<(String, u32)>,
+    /// The latest transaction of each payee as a template, by normalized
+    /// payee, latest first
+    templates: Vec<(String, TransactionBuilder)>,
+}
+
+impl SuggestionIndex {
+    /// Read the journal's accounts, declared ones included, and count uses
+    /// in its transactions
+    pub fn build(hledger_path: Option<&str>, journal_file: Option<&str>) -> Result<Self> {
+        let accounts = get_accounts(hledger_path, journal_file, &AccountsOptions::new())?;
+        let transactions = get_print(hledger_path, journal_file, &PrintOptions::new())?;
+        Ok(Self::from_transactions(&accounts, &transactions))
+    }
+
+    /// An index of `accounts` and the names in `transactions`
+    ///
+    /// Accounts count once per posting, payees and tags once per
+    /// transaction, and commodities once per amount. Accounts without
+    /// postings are kept with no uses.
+    pub fn from_transactions(accounts: &[String], transactions: &[PrintTransaction]) -> Self {
+        let mut counts: [HashMap<&str, u32>; 4] = Default::default();
+        let [account_counts, payee_counts, commodity_counts, tag_counts] = &mut counts;
+        let mut latest: HashMap<String, &PrintTransaction> = HashMap::new();
+        for account in accounts {
+            account_counts.entry(account).or_default();
+        }
+        for txn in transactions {
+            *payee_counts.entry(payee(&txn.description)).or_default() += 1;
+            let previous = latest
+                .entry(normalize(payee(&txn.description)))
+                .or_insert(txn);
+            if (txn.date, txn.index) > (previous.date, previous.index) {
+                *previous = txn;
+            }
+            let mut tags: BTreeSet<&str> = txn.tags.iter().map(|(name, _)| name.as_str()).collect();
+            for posting in &txn.postings {
+                *account_counts.entry(&posting.account).or_default() += 1;
+                for amount in &posting.amounts {
+                    *commodity_counts.entry(&amount.commodity).or_default() += 1;
+                }
+                tags.extend(posting.tags.iter().map(|(name, _)| name.as_str()));
+            }
+            for tag in tags {
+                *tag_counts.entry(tag).or_default() += 1;
+            }
+        }
+
+        let [accounts, payees, commodities, tags] = counts.map(|counts| {
+            let mut names: Vec<(String, u32)> = counts
+                .into_iter()
+                .filter(|(name, _)| !name.is_empty())
+                .map(|(name, count)| (name.to_string(), count))
+                .collect();
+            names.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+            names
+        });
+        let mut templates: Vec<_> = latest
+            .into_iter()
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, txn)| (name, template(txn)))
+            .collect();
+        templates.sort_by(|(a, a_txn), (b, b_txn)| b_txn.date.cmp(&a_txn.date).then(a.cmp(b)));
+        Self {
+            accounts,
+            payees,
+            commodities,
+            tags,
+            templates,
+        }
+    }
+
+    /// The latest transaction whose payee matches `description`, as a
+    /// template
+    ///
+    /// Descriptions are compared ignoring case, punctuation and spacing
+    /// (`trader joes` finds `Trader Joe's`). An exact match wins, then as
+    /// for [`SuggestionIndex::suggest`] a prefix, a substring and a
+    /// subsequence; within each, the most recent.
+    pub fn suggest_transaction(&self, description: &str) -> Option<&TransactionBuilder> {
+        let query = normalize(payee(description));
+        if query.is_empty() {
+            return None;
+        }
+        self.templates
+            .iter()
+            .filter_map(|(name, template)| {
+                let rank = match match_rank(name, &query)? {
+                    0 if *name == query => 0,
+                    rank => rank + 1,
+                };
+                Some((rank, template))
+            })
+            // The first of the best, being latest first
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, template)| template)
+    }
+
+    /// At most `limit` names of `kind` matching `query`, ignoring case
+    ///
+    /// Names starting with the query come first, then ones containing it,
+    /// then ones containing its characters in order (`exfo` for
+    /// `expenses:food`); within each, the most used first. An empty query
+    /// matches everything.
+    pub fn suggest(&self, kind: SuggestionKind, query: &str, limit: usize) -> Vec<String> {
+        let names = match kind {
+            SuggestionKind::Account => &self.accounts,
+            SuggestionKind::Payee => &self.payees,
+            SuggestionKind::Commodity => &self.commodities,
+            SuggestionKind::Tag => &self.tags,
+        };
+        let query = query.trim().to_lowercase();
+        let mut matches: Vec<(u8, &str)> = names
+            .iter()
+            .filter_map(|(name, _)| {
+                Some((match_rank(&name.to_lowercase(), &query)?, name.as_str()))
+            })
+            .collect();
+        // Stable, so each rank stays in order of use
+        matches.sort_by_key(|(rank, _)| *rank);
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+}
+
+/// A transaction to copy: its payee and description, tags and postings, but
+/// not its code, status, comments or balance assertions
+fn template(txn: &PrintTransaction) -> TransactionBuilder {
+    let visible = |tags: &[(String, String)]| -> Vec<(String, String)> {
+        tags.iter()
+            .filter(|(name, _)| !name.starts_with('_'))
+            .cloned()
+            .collect()
+    };
+    let mut template = TransactionBuilder::new(txn.date, txn.description.clone());
+    template.tags = visible(&txn.tags);
+    for posting in &txn.postings {
+        let mut builder = PostingBuilder::new(posting.account.clone());
+        // A posting of several commodities is left for the user to fill in
+        if let [amount] = posting.amounts.as_slice() {
+            builder = builder.amount(amount.quantity, amount.commodity.clone());
+            builder.price = amount.price.clone();
+        }
+        builder.tags = visible(&posting.tags);
+        template = template.posting(builder);
+    }
+    template
+}
+
+/// `description` lowercased, with apostrophes dropped and other runs of
+/// punctuation and spaces made one space
+fn normalize(description: &str) -> String {
+    description
+        .to_lowercase()
+        .replace(['\'', '’'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// How well `name` matches `query`, both lowercase: 0 for a prefix, 1 for
+/// a substring, 2 for a subsequence, `None` for no match
+fn match_rank(name: &str, query: &str) -> Option<u8> {
+    if name.starts_with(query) {
+        return Some(0);
+    }
+    if name.contains(query) {
+        return Some(1);
+    }
+    let mut chars = name.chars();
+    query.chars().all(|q| chars.any(|c| c == q)).then_some(2)
+}
+
+/// Which journal, read by which hledger
+type JournalKey = (Option<String>, Option<String>);
+
+#[derive(Default)]
+struct Slot {
+    index: Option<Arc<SuggestionIndex>>,
+    /// A change was seen since the index was built
+    stale: bool,
+    building: bool,
+}
+
+/// A [`SuggestionIndex`] per journal, built on first use and rebuilt after
+/// [`SuggestionCache::mark_stale`]
+///
+/// Neither ever waits for hledger: while an index is being built, queries
+/// are answered from the previous one (or with nothing) and flagged as
+/// refreshing. A build that fails keeps the previous index and isn't
+/// retried until the journal changes again, since retrying on every
+/// keystroke would fail the same way.
+#[derive(Default)]
+pub struct SuggestionCache {
+    slots: Arc<Mutex<HashMap<JournalKey, Slot>>>,
+}
+
+impl std::fmt::Debug for SuggestionCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuggestionCache")
+            .field("journals", &self.slots.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl SuggestionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Suggestions from the journal's index, starting a build in the
+    /// background if there is none yet or it's stale
+    pub fn suggest(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        kind: SuggestionKind,
+        query: &str,
+        limit: usize,
+    ) -> Suggestions {
+        let (index, refreshing) = self.index(hledger_path, journal_file);
+        Suggestions {
+            values: index
+                .map(|index| index.suggest(kind, query, limit))
+                .unwrap_or_default(),
+            refreshing,
+        }
+    }
+
+    /// A template from the latest transaction with a description like
+    /// `description`, from the journal's index as for
+    /// [`SuggestionCache::suggest`]
+    pub fn suggest_transaction(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+        description: &str,
+    ) -> TransactionSuggestion {
+        let (index, refreshing) = self.index(hledger_path, journal_file);
+        let template = index.and_then(|index| index.suggest_transaction(description).cloned());
+        TransactionSuggestion {
+            last_date: template.as_ref().map(|template| template.date),
+            template,
+            refreshing,
+        }
+    }
+
+    /// The journal's index if there is one yet, and whether it's being
+    /// rebuilt; a build is started if it's missing or stale
+    fn index(
+        &self,
+        hledger_path: Option<&str>,
+        journal_file: Option<&str>,
+    ) -> (Option<Arc<SuggestionIndex>>, bool) {
+        let key = (
+            hledger_path.map(str::to_string),
+            journal_file.map(str::to_string),
+        );
+        let mut slots = self.slots.lock().unwrap();
+        let slot = slots.entry(key.clone()).or_insert_with(|| Slot {
+            stale: true,
+            ..Slot::default()
+        });
+        if slot.stale && !slot.building {
+            slot.stale = false;
+            slot.building = true;
+            self.spawn_build(key);
+        }
+        (slot.index.clone(), slot.building)
+    }
+
+    /// Rebuild every index on its next query, e.g. when a journal changed
+    ///
+    /// Included files aren't tracked per journal, so all of them go stale.
+    pub fn mark_stale(&self) {
+        for slot in self.slots.lock().unwrap().values_mut() {
+            slot.stale = true;
+        }
+    }
+
+    fn spawn_build(&self, key: JournalKey) {
+        let slots = self.slots.clone();
+        thread::spawn(move || {
+            let built = SuggestionIndex::build(key.0.as_deref(), key.1.as_deref());
+            let mut slots = slots.lock().unwrap();
+            let Some(slot) = slots.get_mut(&key) else {
+                return;
+            };
+            slot.building = false;
+            match built {
+                Ok(index) => slot.index = Some(Arc::new(index)),
+                Err(e) => eprintln!("Warning: couldn't index the journal for suggestions: {}", e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+
+    #[test]
+    fn export_bindings() {
+        Suggestions::export_all().unwrap();
+        TransactionSuggestion::export_all().unwrap();
+    }
+
+    fn transaction(
+        description: &str,
+        tags: &[&str],
+        postings: &[(&str, &str, &[&str])],
+    ) -> PrintTransaction {
+        let txn = tags.iter().fold(
+            PrintTransactionBuilder::new(1, "2024-01-01").description(description),
+            |txn, name| txn.tag(name, ""),
+        );
+        postings
+            .iter()
+            .fold(txn, |txn, (account, commodity, posting_tags)| {
+                let posting = PrintPostingBuilder::new(account).amount(commodity, "1");
+                txn.posting(
+                    posting_tags
+                        .iter()
+                        .fold(posting, |posting, name| posting.tag(name, "")),
+                )
+            })
+            .build()
+    }
+
+    fn index() -> SuggestionIndex {
+        let accounts = [
+            "assets:cash".to_string(),
+            "expenses:food".to_string(),
+            "expenses:fuel".to_string(),
+            "equity:opening".to_string(),
+        ];
+        let transactions = [
+            transaction(
+                "Grocer | weekly shop",
+                &["trip"],
+                &[("expenses:food", "$", &["trip"]), ("assets:cash", "$", &[])],
+            ),
+            transaction(
+                "Grocer",
+                &[],
+                &[("expenses:food", "$", &[]), ("assets:cash", "$", &[])],
+            ),
+            transaction(
+                "Petrol station",
+                &[],
+                &[
+                    ("expenses:fuel", "EUR", &["car"]),
+                    ("assets:cash", "EUR", &[]),
+                ],
+            ),
+        ];
+        SuggestionIndex::from_transactions(&accounts, &transactions)
+    }
+
+    #[test]
+    fn test_names_ranked_by_use() {
+        let index = index();
+        assert_eq!(
+            index.suggest(SuggestionKind::Account, "", 10),
+            [
+                "assets:cash",
+                "expenses:food",
+                "expenses:fuel",
+                "equity:opening"
+            ]
+        );
+        assert_eq!(
+            index.suggest(SuggestionKind::Payee, "", 10),
+            ["Grocer", "Petrol station"]
+        );
+        assert_eq!(
+            index.suggest(SuggestionKind::Commodity, "", 10),
+            ["$", "EUR"]
+        );
+        // A tag on a transaction and its posting counts once
+        assert_eq!(index.suggest(SuggestionKind::Tag, "", 10), ["car", "trip"]);
+        assert_eq!(index.suggest(SuggestionKind::Account, "", 2).len(), 2);
+    }
+
+    #[test]
+    fn test_matching() {
+        let index = index();
+        // Prefix matches before substring matches before subsequences
+        assert_eq!(
+            index.suggest(SuggestionKind::Account, "e", 10),
+            [
+                "expenses:food",
+                "expenses:fuel",
+                "equity:opening",
+                "assets:cash"
+            ]
+        );
+        assert_eq!(
+            index.suggest(SuggestionKind::Account, "FOOD", 10),
+            ["expenses:food"]
+        );
+        assert_eq!(
+            index.suggest(SuggestionKind::Account, "exfu", 10),
+            ["expenses:fuel"]
+        );
+        assert_eq!(
+            index.suggest(SuggestionKind::Payee, "station", 10),
+            ["Petrol station"]
+        );
+        assert!(index.suggest(SuggestionKind::Account, "xyz", 10).is_empty());
+    }
+
+    #[test]
+    fn test_transaction_templates() {
+        let mut older = transaction(
+            "Trader Joe's",
+            &["_generated", "trip"],
+            &[("expenses:food", "$", &[]), ("assets:cash", "$", &[])],
+        );
+        older.postings[1].amounts.clear();
+        let mut latest = older.clone();
+        latest.date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        latest.index = 2;
+        latest.postings[0].account = "expenses:groceries".to_string();
+        let other = transaction(
+            "Traders' market",
+            &[],
+            &[("expenses:food", "EUR", &[]), ("assets:cash", "EUR", &[])],
+        );
+        let index = SuggestionIndex::from_transactions(&[], &[latest, older, other]);
+
+        let template = index.suggest_transaction("trader joes").unwrap();
+        assert_eq!(template.date, NaiveDate::from_ymd_opt(2024, 3, 9).unwrap());
+        assert_eq!(template.description, "Trader Joe's");
+        assert_eq!(template.tags, [("trip".to_string(), String::new())]);
+        assert_eq!(template.postings[0].account, "expenses:groceries");
+        assert_eq!(
+            template.postings[0].quantity,
+            Some(rust_decimal::Decimal::ONE)
+        );
+        assert_eq!(template.postings[0].commodity, "$");
+        // The amount hledger inferred is left out
+        assert_eq!(template.postings[1].quantity, None);
+
+        // An exact match beats a more recent prefix match
+        let template = index
+            .suggest_transaction("TRADERS MARKET | weekly")
+            .unwrap();
+        assert_eq!(template.description, "Traders' market");
+        // Otherwise the most recent of the closest
+        assert_eq!(
+            index.suggest_transaction("trad").unwrap().description,
+            "Trader Joe's"
+        );
+        assert!(index.suggest_transaction("petrol").is_none());
+        assert!(index.suggest_transaction(" | ").is_none());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("  Trader Joe’s #552, NYC "),
+            "trader joes 552 nyc"
+        );
+        assert_eq!(normalize("--"), "");
+    }
+
+    #[test]
+    fn test_match_rank() {
+        assert_eq!(match_rank("expenses:food", "exp"), Some(0));
+        assert_eq!(match_rank("expenses:food", "food"), Some(1));
+        assert_eq!(match_rank("expenses:food", "efd"), Some(2));
+        assert_eq!(match_rank("expenses:food", "dfe"), None);
+        assert_eq!(match_rank("anything", ""), Some(0));
+    }
+}
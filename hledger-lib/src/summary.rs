@@ -0,0 +1,268 @@
+use crate::amount::MultiAmount;
+use crate::commands::print::{PrintPosting, PrintTransaction};
+use crate::register::account_matches;
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// Activity for one payee
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PayeeSummary {
+    pub payee: String,
+    /// Number of transactions with a matching posting
+    pub txn_count: u32,
+    /// Sum of the matching postings
+    pub total: MultiAmount,
+}
+
+/// Activity in one calendar month
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct MonthSummary {
+    pub year: i32,
+    /// 1 to 12
+    pub month: u32,
+    /// Number of transactions with a matching posting in the month
+    pub txn_count: u32,
+    /// Sum of the matching postings
+    pub total: MultiAmount,
+}
+
+/// Both groupings of the same transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TransactionSummary {
+    pub by_payee: Vec<PayeeSummary>,
+    pub by_month: Vec<MonthSummary>,
+}
+
+/// Totals per payee of the postings matching `account_filter`
+///
+/// The filter is an account query as for
+/// [`compute_running_balances`](crate::compute_running_balances), e.g.
+/// `^expenses` for spending; `None` counts every posting. The payee is the
+/// description up to a `|`, as in hledger. Payees with the most transactions
+/// come first, ties in name order; transactions without a matching posting
+/// are left out.
+pub fn group_by_payee(
+    transactions: &[PrintTransaction],
+    account_filter: Option<&str>,
+) -> Vec<PayeeSummary> {
+    let mut payees: BTreeMap<&str, PayeeSummary> = BTreeMap::new();
+    for txn in transactions {
+        let postings = matching_postings(txn, account_filter);
+        if postings.is_empty() {
+            continue;
+        }
+        let payee = payee(&txn.description);
+        let summary = payees.entry(payee).or_insert_with(|| PayeeSummary {
+            payee: payee.to_string(),
+            txn_count: 0,
+            total: MultiAmount::new(),
+        });
+        summary.txn_count += 1;
+        for posting in postings {
+            add_posting(&mut summary.total, posting);
+        }
+    }
+
+    let mut summaries: Vec<_> = payees.into_values().collect();
+    // Stable, so ties stay in name order
+    summaries.sort_by_key(|summary| std::cmp::Reverse(summary.txn_count));
+    summaries
+}
+
+/// Totals per calendar month of the postings matching `account_filter`
+///
+/// Postings count in the month of their own date when they have one. Months
+/// come in date order; months without matching postings are left out.
+pub fn group_by_month(
+    transactions: &[PrintTransaction],
+    account_filter: Option<&str>,
+) -> Vec<MonthSummary> {
+    let mut months: BTreeMap<(i32, u32), (MonthSummary, Option<u32>)> = BTreeMap::new();
+    for txn in transactions {
+        for posting in matching_postings(txn, account_filter) {
+            let date = posting.date.unwrap_or(txn.date);
+            let (summary, last_txn) =
+                months
+                    .entry((date.year(), date.month()))
+                    .or_insert_with(|| {
+                        let summary = MonthSummary {
+                            year: date.year(),
+                            month: date.month(),
+                            txn_count: 0,
+                            total: MultiAmount::new(),
+                        };
+                        (summary, None)
+                    });
+            // Count each transaction once per month, however many postings
+            if *last_txn != Some(txn.index) {
+                summary.txn_count += 1;
+                *last_txn = Some(txn.index);
+            }
+            add_posting(&mut summary.total, posting);
+        }
+    }
+    months.into_values().map(|(summary, _)| summary).collect()
+}
+
+/// Group the same transactions by payee and by month
+pub fn summarize_transactions(
+    transactions: &[PrintTransaction],
+    account_filter: Option<&str>,
+) -> TransactionSummary {
+    TransactionSummary {
+        by_payee: group_by_payee(transactions, account_filter),
+        by_month: group_by_month(transactions, account_filter),
+    }
+}
+
+fn matching_postings<'a>(
+    txn: &'a PrintTransaction,
+    account_filter: Option<&str>,
+) -> Vec<&'a PrintPosting> {
+    txn.postings
+        .iter()
+        .filter(|posting| account_filter.is_none_or(|q| account_matches(&posting.account, q)))
+        .collect()
+}
+
+fn add_posting(total: &mut MultiAmount, posting: &PrintPosting) {
+    for amount in &posting.amounts {
+        total.add_quantity(&amount.commodity, amount.quantity);
+    }
+}
+
+/// hledger's payee: the description before a `|`, or all of it
+pub(crate) fn payee(description: &str) -> &str {
+    match description.split_once('|') {
+        Some((payee, _)) => payee.trim(),
+        None => description.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{PrintPostingBuilder, PrintTransactionBuilder};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn export_bindings() {
+        PayeeSummary::export_all().unwrap();
+        MonthSummary::export_all().unwrap();
+        TransactionSummary::export_all().unwrap();
+    }
+
+    fn transaction(
+        index: u32,
+        date: &str,
+        description: &str,
+        postings: &[(&str, &str)],
+    ) -> PrintTransaction {
+        postings
+            .iter()
+            .fold(
+                PrintTransactionBuilder::new(index, date).description(description),
+                |txn, (account, dollars)| {
+                    txn.posting(PrintPostingBuilder::new(account).amount("$", dollars))
+                },
+            )
+            .build()
+    }
+
+    fn sample() -> Vec<PrintTransaction> {
+        vec![
+            transaction(
+                1,
+                "2024-01-03",
+                "Grocer | weekly shop",
+                &[("expenses:food", "40.10"), ("assets:cash", "-40.10")],
+            ),
+            transaction(
+                2,
+                "2024-01-20",
+                "Employer",
+                &[("assets:cash", "1000"), ("income:salary", "-1000")],
+            ),
+            transaction(
+                3,
+                "2024-02-02",
+                "Grocer",
+                &[
+                    ("expenses:food", "25.25"),
+                    ("expenses:household", "4.75"),
+                    ("assets:cash", "-30"),
+                ],
+            ),
+            transaction(
+                4,
+                "2024-02-10",
+                "Bookshop",
+                &[("expenses:books", "12"), ("assets:cash", "-12")],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_group_by_payee() {
+        let payees = group_by_payee(&sample(), Some("^expenses"));
+        let summary: Vec<_> = payees
+            .iter()
+            .map(|p| (p.payee.as_str(), p.txn_count, p.total.get("$")))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Grocer", 2, Decimal::new(7010, 2)),
+                ("Bookshop", 1, Decimal::from(12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_payee_without_filter() {
+        let payees = group_by_payee(&sample(), None);
+        assert_eq!(payees.len(), 3);
+        // Every transaction balances, so unfiltered totals are zero
+        assert!(payees.iter().all(|p| p.total.is_zero()));
+        assert!(group_by_payee(&sample(), Some("^liabilities")).is_empty());
+    }
+
+    #[test]
+    fn test_group_by_month() {
+        let months = group_by_month(&sample(), Some("^expenses"));
+        let summary: Vec<_> = months
+            .iter()
+            .map(|m| (m.year, m.month, m.txn_count, m.total.get("$")))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (2024, 1, 1, Decimal::new(4010, 2)),
+                // Transaction 3 has two expense postings but counts once
+                (2024, 2, 2, Decimal::from(42)),
+            ]
+        );
+
+        let income = group_by_month(&sample(), Some("^income"));
+        assert_eq!(income.len(), 1);
+        assert_eq!(income[0].total.get("$"), Decimal::from(-1000));
+    }
+
+    #[test]
+    fn test_summarize_transactions() {
+        let summary = summarize_transactions(&sample(), Some("expenses"));
+        assert_eq!(summary.by_payee.len(), 2);
+        assert_eq!(summary.by_month.len(), 2);
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::Duration;
+use ts_rs::TS;
+
+/// How long hledger may run before it is killed and the call fails with
+/// `HLedgerError::Timeout`
+///
+/// Options hold an `Option<Timeout>`: `None` lets hledger run as long as it
+/// needs. Serialized as whole milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct Timeout(#[ts(type = "number")] pub Duration);
+
+impl From<Duration> for Timeout {
+    fn from(duration: Duration) -> Self {
+        Timeout(duration)
+    }
+}
+
+impl From<Timeout> for Duration {
+    fn from(timeout: Timeout) -> Self {
+        timeout.0
+    }
+}
+
+impl Serialize for Timeout {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(self.0.as_millis()).unwrap_or(u64::MAX))
+    }
+}
+
+impl<'de> Deserialize<'de> for Timeout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(|millis| Timeout(Duration::from_millis(millis)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_bindings() {
+        Timeout::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_timeout_is_milliseconds() {
+        let timeout = Timeout(Duration::from_millis(1500));
+        assert_eq!(
+            serde_json::to_value(timeout).unwrap(),
+            serde_json::json!(1500)
+        );
+        assert_eq!(
+            serde_json::from_value::<Option<Timeout>>(serde_json::json!(250)).unwrap(),
+            Some(Timeout(Duration::from_millis(250)))
+        );
+        assert_eq!(
+            serde_json::from_value::<Option<Timeout>>(serde_json::Value::Null).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,564 @@
+use crate::amount::{default_style_for, format_amount};
+use crate::commands::balance::Price;
+use crate::commands::print::AmountStyle;
+use crate::query::Status;
+use crate::{HLedgerError, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// Layout used when rendering transactions as journal text
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct JournalStyle {
+    /// Spaces before each posting
+    pub indent: u32,
+    /// Minimum spaces between an account name and its amount
+    pub min_gap: u32,
+    /// Right-align amounts to end at least at this column (hledger's own
+    /// output aligns them just past the longest account name)
+    pub amount_column: Option<u32>,
+    /// Display style per commodity, e.g. taken from a print report; other
+    /// commodities get a default style
+    pub commodity_styles: BTreeMap<String, AmountStyle>,
+}
+
+impl Default for JournalStyle {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            min_gap: 2,
+            amount_column: None,
+            commodity_styles: BTreeMap::new(),
+        }
+    }
+}
+
+/// A balance assertion on a posting, e.g. `= $100` or `==* $100`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PostingAssertion {
+    pub commodity: String,
+    #[ts(type = "string")]
+    pub quantity: Decimal,
+    /// Assert the balance of all commodities (`==`), not just this one
+    pub total: bool,
+    /// Include subaccounts in the asserted balance (`=*`)
+    pub inclusive: bool,
+}
+
+/// A posting of a [`TransactionBuilder`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct PostingBuilder {
+    pub account: String,
+    pub status: Status,
+    /// Amount quantity; `None` leaves the amount for hledger to infer
+    #[ts(type = "string | null")]
+    pub quantity: Option<Decimal>,
+    pub commodity: String,
+    pub price: Option<Price>,
+    pub assertion: Option<PostingAssertion>,
+    pub comment: Option<String>,
+    pub tags: Vec<(String, String)>,
+}
+
+/// A transaction to be written as journal text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct TransactionBuilder {
+    #[ts(type = "string")]
+    pub date: NaiveDate,
+    pub status: Status,
+    pub code: Option<String>,
+    pub description: String,
+    pub comment: Option<String>,
+    pub tags: Vec<(String, String)>,
+    pub postings: Vec<PostingBuilder>,
+}
+
+impl PostingBuilder {
+    pub fn new(account: impl Into<String>) -> Self {
+        Self {
+            account: account.into(),
+            status: Status::Unmarked,
+            quantity: None,
+            commodity: String::new(),
+            price: None,
+            assertion: None,
+            comment: None,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn amount(mut self, quantity: Decimal, commodity: impl Into<String>) -> Self {
+        self.quantity = Some(quantity);
+        self.commodity = commodity.into();
+        self
+    }
+
+    pub fn price(mut self, price: Price) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn assertion(mut self, assertion: PostingAssertion) -> Self {
+        self.assertion = Some(assertion);
+        self
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn tag(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl TransactionBuilder {
+    pub fn new(date: NaiveDate, description: impl Into<String>) -> Self {
+        Self {
+            date,
+            status: Status::Unmarked,
+            code: None,
+            description: description.into(),
+            comment: None,
+            tags: Vec::new(),
+            postings: Vec::new(),
+        }
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn tag(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn posting(mut self, posting: PostingBuilder) -> Self {
+        self.postings.push(posting);
+        self
+    }
+
+    /// Render the transaction as journal text, ending with a newline
+    ///
+    /// Fails with `HLedgerError::InvalidTransaction` if the result would not
+    /// read back as the same transaction, e.g. an account name containing two
+    /// spaces or more than one posting without an amount.
+    pub fn to_journal_string(&self, style: &JournalStyle) -> Result<String> {
+        self.validate()?;
+
+        let mut header = self.date.format("%Y-%m-%d").to_string();
+        if self.status != Status::Unmarked {
+            header.push(' ');
+            header.push_str(self.status.mark());
+        }
+        if let Some(code) = &self.code {
+            header.push_str(&format!(" ({})", code));
+        }
+        if !self.description.is_empty() {
+            header.push(' ');
+            header.push_str(&self.description);
+        }
+
+        let indent = " ".repeat(style.indent as usize);
+        let mut out = String::new();
+        push_with_comment(
+            &mut out,
+            header,
+            &comment_lines(self.comment.as_deref(), &self.tags),
+            &indent,
+        );
+
+        let lines: Vec<(String, String)> = self
+            .postings
+            .iter()
+            .map(|posting| {
+                let account = match posting.status {
+                    Status::Unmarked => posting.account.clone(),
+                    status => format!("{} {}", status.mark(), posting.account),
+                };
+                (account, posting.amount_text(style))
+            })
+            .collect();
+
+        // Right-align amounts on a common column
+        let width = |s: &str| s.chars().count();
+        let widest = lines
+            .iter()
+            .filter(|(_, amount)| !amount.is_empty())
+            .map(|(account, amount)| width(account) + style.min_gap as usize + width(amount))
+            .max()
+            .unwrap_or(0);
+        let amount_end = widest.max(
+            style
+                .amount_column
+                .map_or(0, |column| (column as usize).saturating_sub(indent.len())),
+        );
+
+        let posting_indent = format!("{}  ", indent);
+        for ((account, amount), posting) in lines.iter().zip(&self.postings) {
+            let mut line = format!("{}{}", indent, account);
+            if !amount.is_empty() {
+                let pad = amount_end - width(account) - width(amount);
+                line.push_str(&" ".repeat(pad));
+                line.push_str(amount);
+            }
+            if let Some(assertion) = &posting.assertion {
+                if amount.is_empty() {
+                    line.push_str(&" ".repeat(style.min_gap as usize));
+                } else {
+                    line.push(' ');
+                }
+                line.push_str(&assertion_text(assertion, style));
+            }
+            push_with_comment(
+                &mut out,
+                line,
+                &comment_lines(posting.comment.as_deref(), &posting.tags),
+                &posting_indent,
+            );
+        }
+
+        Ok(out)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(HLedgerError::InvalidTransaction(message));
+
+        if self.description.contains('\n') {
+            return invalid("description must be a single line".to_string());
+        }
+        if let Some(code) = &self.code {
+            if code.contains(['(', ')', '\n']) {
+                return invalid(format!("code '{}' can't contain parentheses", code));
+            }
+        }
+        validate_tags(&self.tags)?;
+
+        let mut elided = 0;
+        for posting in &self.postings {
+            let account = posting.account.as_str();
+            if account.trim().is_empty() {
+                return invalid("posting account can't be empty".to_string());
+            }
+            if account != account.trim() || account.contains("  ") || account.contains(['\t', '\n'])
+            {
+                return invalid(format!(
+                    "account '{}' can't contain tabs, newlines, two spaces in a row, or surrounding spaces",
+                    account
+                ));
+            }
+            if posting.quantity.is_none() {
+                elided += 1;
+                if posting.price.is_some() {
+                    return invalid(format!(
+                        "posting to '{}' has a price but no amount",
+                        account
+                    ));
+                }
+            }
+            validate_tags(&posting.tags)?;
+        }
+        if elided > 1 {
+            return invalid("only one posting can leave its amount to be inferred".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl PostingBuilder {
+    fn amount_text(&self, style: &JournalStyle) -> String {
+        match self.quantity {
+            Some(quantity) => {
+                let amount_style = style_for(style, &self.commodity);
+                format_amount(
+                    &self.commodity,
+                    quantity,
+                    &amount_style,
+                    self.price.as_ref(),
+                )
+            }
+            None => String::new(),
+        }
+    }
+}
+
+fn style_for(style: &JournalStyle, commodity: &str) -> AmountStyle {
+    style
+        .commodity_styles
+        .get(commodity)
+        .cloned()
+        .unwrap_or_else(|| default_style_for(commodity))
+}
+
+fn assertion_text(assertion: &PostingAssertion, style: &JournalStyle) -> String {
+    let operator = match (assertion.total, assertion.inclusive) {
+        (false, false) => "=",
+        (false, true) => "=*",
+        (true, false) => "==",
+        (true, true) => "==*",
+    };
+    let amount_style = style_for(style, &assertion.commodity);
+    format!(
+        "{} {}",
+        operator,
+        format_amount(
+            &assertion.commodity,
+            assertion.quantity,
+            &amount_style,
+            None
+        )
+    )
+}
+
+/// Comment text split into lines, with tags as a final `name:value, ...` line
+fn comment_lines(comment: Option<&str>, tags: &[(String, String)]) -> Vec<String> {
+    let mut lines: Vec<String> = comment
+        .into_iter()
+        .flat_map(str::lines)
+        .map(|line| line.trim_end().to_string())
+        .collect();
+    if !tags.is_empty() {
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|(name, value)| format!("{}:{}", name, value))
+            .collect();
+        lines.push(tags.join(", "));
+    }
+    lines
+}
+
+/// Write `line`, putting the first comment line after it and any further
+/// ones on their own indented lines
+fn push_with_comment(out: &mut String, line: String, comments: &[String], indent: &str) {
+    out.push_str(&line);
+    let mut comments = comments.iter();
+    if let Some(first) = comments.next() {
+        out.push_str(format!("  ; {}", first).trim_end());
+    }
+    out.push('\n');
+    for comment in comments {
+        out.push_str(format!("{}; {}", indent, comment).trim_end());
+        out.push('\n');
+    }
+}
+
+fn validate_tags(tags: &[(String, String)]) -> Result<()> {
+    for (name, value) in tags {
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == ':' || c == ',') {
+            return Err(HLedgerError::InvalidTransaction(format!(
+                "tag name '{}' must be a single word without ':' or ','",
+                name
+            )));
+        }
+        if value.contains([',', '\n']) {
+            return Err(HLedgerError::InvalidTransaction(format!(
+                "value of tag '{}' can't contain commas or newlines",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::PriceKind;
+
+    #[test]
+    fn export_bindings() {
+        JournalStyle::export_all().unwrap();
+        PostingAssertion::export_all().unwrap();
+        PostingBuilder::export_all().unwrap();
+        TransactionBuilder::export_all().unwrap();
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_render_simple_transaction() {
+        let txn = TransactionBuilder::new(date("2024-01-05"), "Grocery store")
+            .posting(PostingBuilder::new("expenses:food:groceries").amount(dec("45.20"), "$"))
+            .posting(PostingBuilder::new("assets:bank:checking"));
+
+        assert_eq!(
+            txn.to_journal_string(&JournalStyle::default()).unwrap(),
+            "2024-01-05 Grocery store\n    expenses:food:groceries  $45.20\n    assets:bank:checking\n"
+        );
+    }
+
+    #[test]
+    fn test_render_aligns_amounts_and_commodity_sides() {
+        let txn = TransactionBuilder::new(date("2024-01-10"), "Investment purchase")
+            .status(Status::Cleared)
+            .code("42")
+            .posting(
+                PostingBuilder::new("assets:investments:goog")
+                    .amount(dec("2"), "GOOG")
+                    .price(Price {
+                        kind: PriceKind::Unit,
+                        commodity: "$".to_string(),
+                        quantity: dec("150.00"),
+                    }),
+            )
+            .posting(PostingBuilder::new("expenses:fees").amount(dec("0.50"), "$"))
+            .posting(PostingBuilder::new("assets:cash").amount(dec("-300.50"), "$"));
+
+        let expected = "\
+2024-01-10 * (42) Investment purchase
+    assets:investments:goog  2 GOOG @ $150.00
+    expenses:fees                       $0.50
+    assets:cash                      $-300.50
+";
+        assert_eq!(
+            txn.to_journal_string(&JournalStyle::default()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_render_uses_commodity_styles_and_amount_column() {
+        let mut style = JournalStyle {
+            amount_column: Some(40),
+            ..JournalStyle::default()
+        };
+        style.commodity_styles.insert(
+            "EUR".to_string(),
+            AmountStyle {
+                commodity_side: "R".to_string(),
+                commodity_spaced: true,
+                decimal_mark: Some(",".to_string()),
+                digit_groups: Some(crate::commands::print::DigitGroups {
+                    separator: '.',
+                    sizes: vec![3],
+                }),
+                precision: crate::commands::print::Precision::Digits(2),
+                rounding: "NoRounding".to_string(),
+            },
+        );
+
+        let txn = TransactionBuilder::new(date("2024-03-01"), "Rent")
+            .posting(PostingBuilder::new("expenses:rent").amount(dec("1200"), "EUR"))
+            .posting(PostingBuilder::new("assets:bank"));
+        let text = txn.to_journal_string(&style).unwrap();
+        let first_posting = text.lines().nth(1).unwrap();
+        assert!(first_posting.ends_with("1.200,00 EUR"));
+        assert_eq!(first_posting.chars().count(), 40);
+    }
+
+    #[test]
+    fn test_render_comments_tags_and_assertions() {
+        let txn = TransactionBuilder::new(date("2024-02-01"), "Paycheck")
+            .comment("monthly salary\nsecond line")
+            .tag("employer", "acme")
+            .tag("reviewed", "")
+            .posting(
+                PostingBuilder::new("assets:bank")
+                    .status(Status::Pending)
+                    .amount(dec("3000"), "$")
+                    .assertion(PostingAssertion {
+                        commodity: "$".to_string(),
+                        quantity: dec("5000"),
+                        total: false,
+                        inclusive: false,
+                    })
+                    .comment("direct deposit"),
+            )
+            .posting(PostingBuilder::new("income:salary").tag("payroll", "jan"));
+
+        let expected = "\
+2024-02-01 Paycheck  ; monthly salary
+    ; second line
+    ; employer:acme, reviewed:
+    ! assets:bank  $3000 = $5000  ; direct deposit
+    income:salary  ; payroll:jan
+";
+        assert_eq!(
+            txn.to_journal_string(&JournalStyle::default()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_assertion_operators() {
+        let assertion = |total, inclusive| PostingAssertion {
+            commodity: "$".to_string(),
+            quantity: dec("10"),
+            total,
+            inclusive,
+        };
+        let style = JournalStyle::default();
+        assert_eq!(assertion_text(&assertion(false, false), &style), "= $10");
+        assert_eq!(assertion_text(&assertion(false, true), &style), "=* $10");
+        assert_eq!(assertion_text(&assertion(true, false), &style), "== $10");
+        assert_eq!(assertion_text(&assertion(true, true), &style), "==* $10");
+    }
+
+    #[test]
+    fn test_rejects_unparseable_transactions() {
+        let style = JournalStyle::default();
+        let txn = |account: &str| {
+            TransactionBuilder::new(date("2024-01-01"), "test")
+                .posting(PostingBuilder::new(account).amount(dec("1"), "$"))
+                .posting(PostingBuilder::new("assets:cash"))
+        };
+        for account in ["", "expenses  food", "expenses\tfood", " expenses"] {
+            assert!(
+                matches!(
+                    txn(account).to_journal_string(&style),
+                    Err(HLedgerError::InvalidTransaction(_))
+                ),
+                "{:?} should be rejected",
+                account
+            );
+        }
+
+        let two_elided = txn("expenses:food").posting(PostingBuilder::new("assets:bank"));
+        assert!(two_elided.to_journal_string(&style).is_err());
+
+        let bad_tag = txn("expenses:food").tag("trip", "paris, france");
+        assert!(bad_tag.to_journal_string(&style).is_err());
+
+        let bad_description = TransactionBuilder::new(date("2024-01-01"), "two\nlines");
+        assert!(bad_description.to_journal_string(&style).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! The next transactions due: future-dated ones in the journal and those
+//! its periodic rules (`~ monthly ...`) generate
+
+use crate::commands::print::{get_print, PrintOptions, PrintTransaction};
+use crate::Result;
+use chrono::{Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Tags hledger puts on forecast transactions, with the generating rule's
+/// period expression as value; the hidden one is always there, the visible
+/// one with `--verbose-tags`
+const GENERATED_TAGS: &[&str] = &["_generated-transaction", "generated-transaction"];
+
+/// A transaction dated after today
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct UpcomingTransaction {
+    pub transaction: PrintTransaction,
+    /// Period expression of the periodic rule that generated it, e.g.
+    /// `monthly from 2024-01-01`; `None` for one recorded in the journal
+    pub rule: Option<String>,
+}
+
+/// Transactions dated from tomorrow until `horizon_days` after `today`,
+/// inclusive, in date order
+///
+/// Runs `hledger print --forecast`, with `today` (the local date when
+/// `None`) passed on so forecasts don't depend on hledger's clock.
+/// Generated transactions carry their rule; future-dated ones written in
+/// the journal come too, without one.
+pub fn get_upcoming_transactions(
+    hledger_path: Option<&str>,
+    journal_file: Option<&str>,
+    today: Option<NaiveDate>,
+    horizon_days: u32,
+) -> Result<Vec<UpcomingTransaction>> {
+    let today = today.unwrap_or_else(|| Local::now().date_naive());
+    let transactions = get_print(
+        hledger_path,
+        journal_file,
+        &upcoming_options(today, horizon_days),
+    )?;
+    Ok(upcoming(transactions, today))
+}
+
+fn upcoming_options(today: NaiveDate, horizon_days: u32) -> PrintOptions {
+    let tomorrow = today + Days::new(1);
+    PrintOptions {
+        forecast: true,
+        today: Some(today.to_string()),
+        begin: Some(tomorrow.to_string()),
+        end: Some((tomorrow + Days::new(horizon_days.into())).to_string()),
+        ..PrintOptions::with_inferred_amounts()
+    }
+}
+
+/// The transactions after `today`, each with its generating rule if it has
+/// one
+fn upcoming(transactions: Vec<PrintTransaction>, today: NaiveDate) -> Vec<UpcomingTransaction> {
+    let mut upcoming: Vec<UpcomingTransaction> = transactions
+        .into_iter()
+        .filter(|transaction| transaction.date > today)
+        .map(|transaction| UpcomingTransaction {
+            rule: generating_rule(&transaction),
+            transaction,
+        })
+        .collect();
+    upcoming.sort_by_key(|upcoming| upcoming.transaction.date);
+    upcoming
+}
+
+/// The rule's period expression, from the tag hledger adds to what a
+/// periodic rule generates (`~ monthly`, or `~monthly` in older versions)
+fn generating_rule(transaction: &PrintTransaction) -> Option<String> {
+    transaction
+        .tags
+        .iter()
+        .find(|(name, _)| GENERATED_TAGS.contains(&name.as_str()))
+        .map(|(_, value)| value.trim().trim_start_matches('~').trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::PrintTransactionBuilder;
+
+    #[test]
+    fn export_bindings() {
+        UpcomingTransaction::export_all().unwrap();
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn transaction(day: &str, description: &str, tags: &[(&str, &str)]) -> PrintTransaction {
+        tags.iter()
+            .fold(
+                PrintTransactionBuilder::new(0, day).description(description),
+                |txn, (name, value)| txn.tag(name, value),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_upcoming_keeps_future_with_rules() {
+        let rent_tags = [("_generated-transaction", "~ monthly from 2024-01-01")];
+        let transactions = vec![
+            transaction("2024-07-01", "Rent", &rent_tags),
+            transaction("2024-06-15", "Dentist", &[]),
+            transaction("2024-06-10", "Groceries", &[]),
+            transaction("2024-06-20", "Gym", &[("generated-transaction", "~weekly")]),
+        ];
+
+        let upcoming = upcoming(transactions, date("2024-06-10"));
+        let summary: Vec<(&str, Option<&str>)> = upcoming
+            .iter()
+            .map(|u| (u.transaction.description.as_str(), u.rule.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Dentist", None),
+                ("Gym", Some("weekly")),
+                ("Rent", Some("monthly from 2024-01-01")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_upcoming_options() {
+        let options = upcoming_options(date("2024-06-10"), 30);
+        assert!(options.forecast && options.explicit);
+        assert_eq!(options.today.as_deref(), Some("2024-06-10"));
+        assert_eq!(options.begin.as_deref(), Some("2024-06-11"));
+        // Exclusive, so the 30th day after today is included
+        assert_eq!(options.end.as_deref(), Some("2024-07-11"));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::config::run_command;
+use crate::{get_hledger_command, HLedgerError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use ts_rs::TS;
+
+/// An installed hledger's version, e.g. 1.32.3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct HLedgerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl HLedgerVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse `hledger --version` output such as
+    /// `hledger 1.32.3, linux-x86_64` or `hledger 1.40-gd4a5c8d 20240902`
+    pub fn parse(output: &str) -> Option<Self> {
+        let word = output.split_whitespace().nth(1)?;
+        let numeric: String = word
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let mut parts = numeric.split('.').map(|p| p.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for HLedgerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.patch == 0 {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        }
+    }
+}
+
+/// Oldest hledger release the reports are supported on
+pub const MIN_SUPPORTED_VERSION: HLedgerVersion = HLedgerVersion::new(1, 19, 0);
+
+/// Flags that older hledger releases reject, with the release that added them
+const FLAG_MIN_VERSIONS: &[(&str, HLedgerVersion)] = &[
+    ("--summary-only", HLedgerVersion::new(1, 19, 0)),
+    ("--layout", HLedgerVersion::new(1, 19, 0)),
+    ("--infer-market-prices", HLedgerVersion::new(1, 24, 0)),
+    ("--round", HLedgerVersion::new(1, 32, 0)),
+];
+
+static VERSION_CHECK: AtomicBool = AtomicBool::new(true);
+
+/// Turn the pre-flight flag/version check on or off (it is on by default)
+///
+/// When disabled, commands go straight to hledger and an unsupported flag
+/// surfaces as hledger's own usage error.
+pub fn set_version_check(enabled: bool) {
+    VERSION_CHECK.store(enabled, Ordering::Relaxed);
+}
+
+/// Get the version of the hledger binary at `hledger_path` (or on PATH)
+pub fn get_version(hledger_path: Option<&str>) -> Result<HLedgerVersion> {
+    let mut cmd = get_hledger_command(hledger_path);
+    cmd.arg("--version");
+    let output = run_command(&mut cmd, None)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    HLedgerVersion::parse(&stdout).ok_or_else(|| {
+        HLedgerError::ParseError(format!("Unrecognized hledger version: {}", stdout.trim()))
+    })
+}
+
+/// Fail with `UnsupportedFeature` if the command uses a flag the installed
+/// hledger is too old for
+///
+/// hledger is only asked for its version when a gated flag is present, and
+/// the answer is remembered per binary.
+pub(crate) fn check_flag_support(cmd: &Command) -> Result<()> {
+    if !VERSION_CHECK.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let args: Vec<String> = cmd
+        .get_args()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    if gated_flags(&args).next().is_none() {
+        return Ok(());
+    }
+
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    // If the version can't be read, let hledger itself be the judge
+    let Some(installed) = cached_version(&program) else {
+        return Ok(());
+    };
+    match unsupported_flag(&args, installed) {
+        Some((flag, required)) => Err(HLedgerError::UnsupportedFeature {
+            flag: flag.to_string(),
+            required: required.to_string(),
+            installed: installed.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn cached_version(program: &str) -> Option<HLedgerVersion> {
+    static VERSIONS: OnceLock<Mutex<HashMap<String, HLedgerVersion>>> = OnceLock::new();
+    let versions = VERSIONS.get_or_init(Default::default);
+    if let Some(version) = versions.lock().ok()?.get(program) {
+        return Some(*version);
+    }
+    let version = get_version(Some(program)).ok()?;
+    versions.lock().ok()?.insert(program.to_string(), version);
+    Some(version)
+}
+
+/// Gated flags among the arguments, stopping at the `--` before queries
+fn gated_flags(args: &[String]) -> impl Iterator<Item = (&'static str, HLedgerVersion)> + '_ {
+    args.iter()
+        .take_while(|arg| *arg != "--")
+        .filter_map(|arg| {
+            let flag = arg.split_once('=').map_or(arg.as_str(), |(flag, _)| flag);
+            FLAG_MIN_VERSIONS.iter().find(|(gated, _)| *gated == flag)
+        })
+        .copied()
+}
+
+/// The first flag in `args` that `installed` is too old for
+fn unsupported_flag(
+    args: &[String],
+    installed: HLedgerVersion,
+) -> Option<(&'static str, HLedgerVersion)> {
+    gated_flags(args).find(|(_, required)| installed < *required)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn export_bindings() {
+        HLedgerVersion::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            HLedgerVersion::parse("hledger 1.32.3, linux-x86_64\n"),
+            Some(HLedgerVersion::new(1, 32, 3))
+        );
+        assert_eq!(
+            HLedgerVersion::parse("hledger 1.40-gd4a5c8d 20240902"),
+            Some(HLedgerVersion::new(1, 40, 0))
+        );
+        assert_eq!(
+            HLedgerVersion::parse("hledger 1.19"),
+            Some(HLedgerVersion::new(1, 19, 0))
+        );
+        assert_eq!(HLedgerVersion::parse("not hledger"), None);
+        assert_eq!(HLedgerVersion::parse(""), None);
+    }
+
+    #[test]
+    fn test_version_ordering_and_display() {
+        assert!(HLedgerVersion::new(1, 9, 9) < HLedgerVersion::new(1, 19, 0));
+        assert!(HLedgerVersion::new(1, 32, 3) > HLedgerVersion::new(1, 32, 0));
+        assert_eq!(HLedgerVersion::new(1, 32, 0).to_string(), "1.32");
+        assert_eq!(HLedgerVersion::new(1, 32, 3).to_string(), "1.32.3");
+    }
+
+    #[test]
+    fn test_unsupported_flag_against_old_version() {
+        let old = HLedgerVersion::new(1, 18, 1);
+        let command = args(&["bal", "--output-format", "json", "--summary-only"]);
+        assert_eq!(
+            unsupported_flag(&command, old),
+            Some(("--summary-only", HLedgerVersion::new(1, 19, 0)))
+        );
+
+        // Flags given as --flag=value are matched by name
+        let command = args(&["print", "--round=soft"]);
+        assert_eq!(
+            unsupported_flag(&command, HLedgerVersion::new(1, 31, 0)).map(|(flag, _)| flag),
+            Some("--round")
+        );
+    }
+
+    #[test]
+    fn test_supported_flags_pass() {
+        let new = HLedgerVersion::new(1, 32, 0);
+        let command = args(&["print", "--round=soft", "--layout=bare", "--summary-only"]);
+        assert_eq!(unsupported_flag(&command, new), None);
+
+        let command = args(&["bal", "--monthly"]);
+        assert_eq!(
+            unsupported_flag(&command, HLedgerVersion::new(1, 0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_queries_after_separator_are_not_flags() {
+        let command = args(&["print", "--", "--round"]);
+        assert_eq!(
+            unsupported_flag(&command, HLedgerVersion::new(1, 0, 0)),
+            None
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Problems worth telling the user about that don't fail the report, e.g.
+//! output that had to be repaired to be read
+//!
+//! hledger can succeed while printing warnings to stderr; those, and
+//! problems found reading its output, are raised here rather than failing
+//! the report. Warnings raised while [`collect_warnings`] or
+//! [`with_warnings`] runs its closure are handed back with the closure's
+//! result. hledger runs on the calling thread are seen, as are reports run
+//! by [`crate::run_reports`] on its worker threads; outside a collector
+//! warnings are dropped (and, with the `tracing` feature, logged).
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use ts_rs::TS;
+
+thread_local! {
+    static COLLECTED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, returning its result with the warnings raised meanwhile
+///
+/// Collectors nest: an outer collector also receives what an inner one saw.
+pub fn collect_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let outer = COLLECTED.with(|c| c.borrow_mut().replace(Vec::new()));
+    // Restores the outer collector even if `f` panics
+    struct Restore(Option<Option<Vec<String>>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            if let Some(outer) = self.0.take() {
+                COLLECTED.with(|c| *c.borrow_mut() = outer);
+            }
+        }
+    }
+    let restore = Restore(Some(outer));
+
+    let value = f();
+
+    let warnings = COLLECTED
+        .with(|c| c.borrow_mut().take())
+        .unwrap_or_default();
+    drop(restore);
+    COLLECTED.with(|c| {
+        if let Some(outer) = c.borrow_mut().as_mut() {
+            outer.extend(warnings.iter().cloned());
+        }
+    });
+    (value, warnings)
+}
+
+/// A report with the warnings raised while producing it, so the app can
+/// show them without treating the report as failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct ReportOutput<T> {
+    pub value: T,
+    pub warnings: Vec<String>,
+}
+
+/// Run `f`, attaching the warnings raised meanwhile to its result
+///
+/// A failure is returned as is: what hledger printed is in the error.
+pub fn with_warnings<T>(f: impl FnOnce() -> Result<T>) -> Result<ReportOutput<T>> {
+    let (result, warnings) = collect_warnings(f);
+    result.map(|value| ReportOutput { value, warnings })
+}
+
+/// Record a warning for the innermost collector on this thread
+pub(crate) fn warn(message: String) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(warning = %message, "hledger warning");
+    COLLECTED.with(|c| {
+        if let Some(warnings) = c.borrow_mut().as_mut() {
+            warnings.push(message);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_warnings() {
+        warn("before".to_string());
+        let (value, warnings) = collect_warnings(|| {
+            warn("first".to_string());
+            let ((), inner) = collect_warnings(|| warn("inner".to_string()));
+            assert_eq!(inner, vec!["inner"]);
+            42
+        });
+        assert_eq!(value, 42);
+        assert_eq!(warnings, vec!["first", "inner"]);
+
+        let ((), warnings) = collect_warnings(|| ());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn export_bindings() {
+        ReportOutput::<()>::export_all().unwrap();
+    }
+
+    #[test]
+    fn test_with_warnings() {
+        let output = with_warnings(|| {
+            warn("deprecated flag".to_string());
+            Ok(1)
+        })
+        .unwrap();
+        assert_eq!(
+            output,
+            ReportOutput {
+                value: 1,
+                warnings: vec!["deprecated flag".to_string()],
+            }
+        );
+
+        let failed = with_warnings(|| -> Result<()> {
+            warn("deprecated flag".to_string());
+            Err(crate::HLedgerError::ParseError("bad".to_string()))
+        });
+        assert!(failed.is_err());
+    }
+
+    #[test]
+    fn test_collector_is_restored_after_panic() {
+        let ((), warnings) = collect_warnings(|| {
+            let panicked = std::panic::catch_unwind(|| {
+                collect_warnings(|| -> () { panic!("boom") });
+            });
+            assert!(panicked.is_err());
+            warn("after".to_string());
+        });
+        assert_eq!(warnings, vec!["after"]);
+    }
+}
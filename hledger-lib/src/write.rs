@@ -0,0 +1,729 @@
+use crate::commands::print::{get_print, PrintOptions, SourceSpan};
+use crate::config::{check_success, run_command};
+use crate::timeout::Timeout;
+use crate::transaction::{JournalStyle, TransactionBuilder};
+use crate::{get_hledger_command, HLedgerError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use ts_rs::TS;
+
+/// Options for writing transactions to a journal file
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct WriteOptions {
+    /// Run `hledger check` on the journal after writing, restoring the
+    /// previous contents if it fails
+    pub validate: bool,
+    /// hledger binary used for validation (found on PATH when unset)
+    pub hledger_path: Option<String>,
+    /// Layout of the rendered transaction
+    pub style: JournalStyle,
+    /// Refuse to write if the file no longer matches this fingerprint, taken
+    /// with [`file_fingerprint`] when the journal was last read
+    pub expect_unchanged: Option<FileFingerprint>,
+    /// Limit on the hledger run validating the changed journal
+    pub timeout: Option<Timeout>,
+    /// Called before the file is changed, e.g. to back it up
+    #[serde(skip)]
+    #[ts(skip)]
+    pub before_write: Option<WriteHook>,
+}
+
+/// A function called with a file's path just before a write changes it;
+/// an error stops the write with the file untouched
+///
+/// Restoring a file after a failed validation doesn't call it again.
+#[derive(Clone)]
+pub struct WriteHook(Arc<HookFn>);
+
+type HookFn = dyn Fn(&Path) -> Result<()> + Send + Sync;
+
+impl WriteHook {
+    pub fn new(hook: impl Fn(&Path) -> Result<()> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(hook: Option<&WriteHook>, path: &Path) -> Result<()> {
+        match hook {
+            Some(hook) => (hook.0)(path),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Debug for WriteHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WriteHook")
+    }
+}
+
+/// Where a write put the new text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct WriteReceipt {
+    /// The file that was written (symlinks resolved)
+    pub file: String,
+    /// Byte offset of the first written byte
+    pub start_offset: u64,
+    /// Byte offset just past the last written byte
+    pub end_offset: u64,
+    /// First line of the written text (1-based)
+    pub start_line: u32,
+    /// Last line of the written text (1-based, inclusive)
+    pub end_line: u32,
+}
+
+/// Identifies a file's contents, to detect edits made behind our back
+///
+/// Content is compared by length and hash rather than modification time,
+/// which editors and sync tools don't always update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "camelCase")]
+#[ts(rename_all = "camelCase")]
+pub struct FileFingerprint {
+    #[ts(type = "number")]
+    pub len: u64,
+    /// FNV-1a hash of the contents, in hex
+    pub hash: String,
+}
+
+impl FileFingerprint {
+    fn of(contents: &[u8]) -> Self {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for &byte in contents {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        Self {
+            len: contents.len() as u64,
+            hash: format!("{:016x}", hash),
+        }
+    }
+}
+
+/// New content for [`replace_transaction`]
+#[derive(Debug, Clone, Copy)]
+pub enum Replacement<'a> {
+    /// Journal text, used as is apart from line endings (a final newline is
+    /// added if missing)
+    Text(&'a str),
+    /// A transaction rendered with the write options' style
+    Transaction(&'a TransactionBuilder),
+}
+
+// Implementation for builder pattern
+impl WriteOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn validate(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    pub fn hledger_path(mut self, path: impl Into<String>) -> Self {
+        self.hledger_path = Some(path.into());
+        self
+    }
+
+    pub fn style(mut self, style: JournalStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn expect_unchanged(mut self, fingerprint: FileFingerprint) -> Self {
+        self.expect_unchanged = Some(fingerprint);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout(timeout));
+        self
+    }
+
+    pub fn before_write(mut self, hook: WriteHook) -> Self {
+        self.before_write = Some(hook);
+        self
+    }
+}
+
+/// Fingerprint a file's current contents
+pub fn file_fingerprint(path: &Path) -> Result<FileFingerprint> {
+    Ok(FileFingerprint::of(&fs::read(path)?))
+}
+
+/// Append a transaction to the end of a journal file
+///
+/// The file is rewritten through a temporary file and a rename, so readers
+/// never see a half-written journal. The transaction is separated from
+/// existing content by a blank line and uses the file's line endings (CRLF
+/// if the file already has any). A missing file is created. Read-only files
+/// are refused with a `PermissionDenied` I/O error.
+pub fn append_transaction(
+    journal_path: &Path,
+    txn: &TransactionBuilder,
+    options: &WriteOptions,
+) -> Result<WriteReceipt> {
+    let text = txn.to_journal_string(&options.style)?;
+    let path = resolve_path(journal_path)?;
+
+    let original = match fs::read(&path) {
+        Ok(bytes) => Some(bytes),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    ensure_unchanged(&path, original.as_deref().unwrap_or_default(), options)?;
+    ensure_writable(&path)?;
+
+    let mut contents = original.clone().unwrap_or_default();
+    let newline = line_ending(&contents);
+    if !contents.is_empty() {
+        if !contents.ends_with(b"\n") {
+            contents.extend_from_slice(newline.as_bytes());
+        }
+        if !ends_with_blank_line(&contents) {
+            contents.extend_from_slice(newline.as_bytes());
+        }
+    }
+
+    let start_offset = contents.len();
+    let start_line = count_lines(&contents) + 1;
+    contents.extend_from_slice(text.replace('\n', newline).as_bytes());
+    let end_line = count_lines(&contents);
+
+    WriteHook::call(options.before_write.as_ref(), &path)?;
+    write_atomically(&path, &contents)?;
+
+    if options.validate {
+        if let Err(e) = check_journal(&path, options) {
+            match &original {
+                Some(bytes) => write_atomically(&path, bytes)?,
+                None => fs::remove_file(&path)?,
+            }
+            return Err(e);
+        }
+    }
+
+    Ok(WriteReceipt {
+        file: path.to_string_lossy().into_owned(),
+        start_offset: start_offset as u64,
+        end_offset: contents.len() as u64,
+        start_line,
+        end_line,
+    })
+}
+
+/// Replace the transaction covering `span` with new text
+///
+/// Only the bytes inside the span change. The span's file may be one
+/// included by `journal_path`; `journal_path` is the journal hledger reads
+/// to verify the edit. The previous contents are kept in a `.bak` file next
+/// to the edited one. If the journal no longer parses afterwards, or the
+/// edit changed its number of transactions, the file is restored and the
+/// error returned.
+pub fn replace_transaction(
+    journal_path: &Path,
+    span: &SourceSpan,
+    replacement: Replacement,
+    options: &WriteOptions,
+) -> Result<()> {
+    let path = resolve_path(Path::new(&span.file))?;
+    let original = fs::read(&path)?;
+    ensure_unchanged(&path, &original, options)?;
+    ensure_writable(&path)?;
+
+    let text = match replacement {
+        Replacement::Text(text) => text.to_string(),
+        Replacement::Transaction(txn) => txn.to_journal_string(&options.style)?,
+    };
+    let contents = splice_span(&original, span, &text)?;
+
+    let count_transactions = || {
+        let print_options = PrintOptions {
+            timeout: options.timeout,
+            ..PrintOptions::default()
+        };
+        get_print(
+            options.hledger_path.as_deref(),
+            Some(&journal_path.to_string_lossy()),
+            &print_options,
+        )
+        .map(|transactions| transactions.len())
+    };
+    let before = count_transactions()?;
+
+    WriteHook::call(options.before_write.as_ref(), &path)?;
+    let mut backup = path.clone().into_os_string();
+    backup.push(".bak");
+    fs::write(&backup, &original)?;
+    write_atomically(&path, &contents)?;
+
+    let verified = count_transactions().and_then(|after| {
+        if after == before {
+            Ok(())
+        } else {
+            Err(HLedgerError::InvalidTransaction(format!(
+                "the edit changed the number of transactions from {} to {}",
+                before, after
+            )))
+        }
+    });
+    if let Err(e) = verified {
+        write_atomically(&path, &original)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Replace the bytes covered by `span` with `text`, converted to the file's
+/// line endings
+fn splice_span(contents: &[u8], span: &SourceSpan, text: &str) -> Result<Vec<u8>> {
+    let out_of_range = || {
+        HLedgerError::InvalidTransaction(format!(
+            "span {}:{}-{} is outside the file",
+            span.file, span.start_line, span.end_line
+        ))
+    };
+    let start =
+        position_offset(contents, span.start_line, span.start_column).ok_or_else(out_of_range)?;
+    let end = position_offset(contents, span.end_line, span.end_column).ok_or_else(out_of_range)?;
+    if end <= start {
+        return Err(out_of_range());
+    }
+
+    let mut text = text.to_string();
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    let text = text
+        .replace("\r\n", "\n")
+        .replace('\n', line_ending(contents));
+
+    let mut spliced = Vec::with_capacity(contents.len() + text.len());
+    spliced.extend_from_slice(&contents[..start]);
+    spliced.extend_from_slice(text.as_bytes());
+    spliced.extend_from_slice(&contents[end..]);
+    Ok(spliced)
+}
+
+/// Byte offset of a 1-based line and column (counted in characters)
+///
+/// The line just past the end of the file maps to the end of the file, as
+/// hledger ends the span of a file's last transaction there.
+fn position_offset(contents: &[u8], line: u32, column: u32) -> Option<usize> {
+    let mut offset = 0;
+    for _ in 1..line.max(1) {
+        let newline = contents[offset..].iter().position(|&b| b == b'\n')?;
+        offset += newline + 1;
+    }
+    if offset == contents.len() {
+        return Some(offset);
+    }
+
+    let line_end = contents[offset..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(contents.len(), |i| offset + i);
+    let line_text = String::from_utf8_lossy(&contents[offset..line_end]);
+    let skipped: usize = line_text
+        .chars()
+        .take(column.saturating_sub(1) as usize)
+        .map(char::len_utf8)
+        .sum();
+    Some((offset + skipped).min(line_end))
+}
+
+pub(crate) fn ensure_unchanged(path: &Path, contents: &[u8], options: &WriteOptions) -> Result<()> {
+    match &options.expect_unchanged {
+        Some(expected) if *expected != FileFingerprint::of(contents) => {
+            Err(HLedgerError::FileChanged {
+                file: path.to_string_lossy().into_owned(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Follow symlinks so the rename replaces the real file, not the link
+pub(crate) fn resolve_path(path: &Path) -> Result<PathBuf> {
+    match fs::canonicalize(path) {
+        Ok(path) => Ok(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(path.to_path_buf()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Renaming over a read-only file would succeed, so check for it explicitly
+pub(crate) fn ensure_writable(path: &Path) -> Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.permissions().readonly() => Err(HLedgerError::Io(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is read-only", path.display()),
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Write `contents` to a temporary file next to `path`, flush it to disk and
+/// rename it into place, keeping the original file's permissions
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let result = (|| -> io::Result<()> {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&tmp, metadata.permissions())?;
+        }
+        fs::rename(&tmp, path)?;
+        sync_parent_dir(path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(result?)
+}
+
+/// Make the rename itself durable
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => File::open(dir)?.sync_all(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Run `hledger check`, which parses the journal and verifies that
+/// transactions balance and balance assertions hold
+pub(crate) fn check_journal(path: &Path, options: &WriteOptions) -> Result<()> {
+    let mut cmd = get_hledger_command(options.hledger_path.as_deref());
+    cmd.arg("-f").arg(path).arg("check");
+    let output = run_command(&mut cmd, options.timeout)?;
+    check_success(&cmd, output.status, &output.stderr)
+}
+
+/// `"\r\n"` if the text already uses CRLF line endings, otherwise `"\n"`
+pub(crate) fn line_ending(contents: &[u8]) -> &'static str {
+    if contents.windows(2).any(|pair| pair == b"\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn ends_with_blank_line(contents: &[u8]) -> bool {
+    contents.ends_with(b"\n\n") || contents.ends_with(b"\r\n\r\n")
+}
+
+fn count_lines(contents: &[u8]) -> u32 {
+    contents.iter().filter(|&&b| b == b'\n').count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::PostingBuilder;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn export_bindings() {
+        WriteOptions::export_all().unwrap();
+        WriteReceipt::export_all().unwrap();
+        FileFingerprint::export_all().unwrap();
+    }
+
+    /// A fresh path in the temp dir, removed when dropped
+    struct TempJournal(PathBuf);
+
+    impl TempJournal {
+        fn new(name: &str, contents: Option<&[u8]>) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "hledger-lib-write-{}-{}.journal",
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_file(&path);
+            if let Some(contents) = contents {
+                fs::write(&path, contents).unwrap();
+            }
+            TempJournal(path)
+        }
+
+        fn read(&self) -> String {
+            fs::read_to_string(&self.0).unwrap()
+        }
+    }
+
+    impl Drop for TempJournal {
+        fn drop(&mut self) {
+            if let Ok(metadata) = fs::metadata(&self.0) {
+                let mut permissions = metadata.permissions();
+                #[allow(clippy::permissions_set_readonly_false)]
+                permissions.set_readonly(false);
+                let _ = fs::set_permissions(&self.0, permissions);
+            }
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn groceries() -> TransactionBuilder {
+        TransactionBuilder::new(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), "groceries")
+            .posting(PostingBuilder::new("expenses:food").amount("20".parse().unwrap(), "$"))
+            .posting(PostingBuilder::new("assets:cash"))
+    }
+
+    const RENDERED: &str = "2024-02-01 groceries\n    expenses:food  $20\n    assets:cash\n";
+
+    #[test]
+    fn test_append_after_existing_transaction() {
+        let existing = "2024-01-01 opening\n    assets:cash  $100\n    equity\n";
+        let journal = TempJournal::new("lf", Some(existing.as_bytes()));
+
+        let receipt = append_transaction(&journal.0, &groceries(), &WriteOptions::new()).unwrap();
+
+        let contents = journal.read();
+        assert_eq!(contents, format!("{}\n{}", existing, RENDERED));
+        assert_eq!(receipt.start_offset as usize, existing.len() + 1);
+        assert_eq!(receipt.end_offset as usize, contents.len());
+        assert_eq!((receipt.start_line, receipt.end_line), (5, 7));
+        assert_eq!(
+            &contents[receipt.start_offset as usize..receipt.end_offset as usize],
+            RENDERED
+        );
+    }
+
+    #[test]
+    fn test_append_to_file_without_trailing_newline() {
+        let journal = TempJournal::new("no-newline", Some(b"; budget below"));
+
+        let receipt = append_transaction(&journal.0, &groceries(), &WriteOptions::new()).unwrap();
+
+        assert_eq!(journal.read(), format!("; budget below\n\n{}", RENDERED));
+        assert_eq!(receipt.start_line, 3);
+    }
+
+    #[test]
+    fn test_append_keeps_existing_blank_line() {
+        let journal = TempJournal::new("blank", Some(b"; notes\n\n"));
+        append_transaction(&journal.0, &groceries(), &WriteOptions::new()).unwrap();
+        assert_eq!(journal.read(), format!("; notes\n\n{}", RENDERED));
+    }
+
+    #[test]
+    fn test_append_uses_crlf_line_endings() {
+        let existing = "2024-01-01 opening\r\n    assets:cash  $100\r\n    equity\r\n";
+        let journal = TempJournal::new("crlf", Some(existing.as_bytes()));
+
+        let receipt = append_transaction(&journal.0, &groceries(), &WriteOptions::new()).unwrap();
+
+        let contents = journal.read();
+        assert_eq!(
+            contents,
+            format!("{}\r\n{}", existing, RENDERED.replace('\n', "\r\n"))
+        );
+        assert!(!contents.replace("\r\n", "").contains('\n'));
+        assert_eq!(receipt.end_offset as usize, contents.len());
+        assert_eq!((receipt.start_line, receipt.end_line), (5, 7));
+    }
+
+    #[test]
+    fn test_append_creates_missing_file() {
+        let journal = TempJournal::new("missing", None);
+        let receipt = append_transaction(&journal.0, &groceries(), &WriteOptions::new()).unwrap();
+        assert_eq!(journal.read(), RENDERED);
+        assert_eq!((receipt.start_offset, receipt.start_line), (0, 1));
+    }
+
+    #[test]
+    fn test_append_refuses_read_only_file() {
+        let journal = TempJournal::new("read-only", Some(b"; locked\n"));
+        let mut permissions = fs::metadata(&journal.0).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&journal.0, permissions).unwrap();
+
+        match append_transaction(&journal.0, &groceries(), &WriteOptions::new()) {
+            Err(HLedgerError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::PermissionDenied),
+            other => panic!("Expected a permission error, got {:?}", other),
+        }
+        assert_eq!(journal.read(), "; locked\n");
+    }
+
+    #[test]
+    fn test_append_rejects_invalid_transaction_without_touching_file() {
+        let journal = TempJournal::new("invalid", Some(b"; untouched\n"));
+        let txn = groceries().posting(PostingBuilder::new("assets:bank"));
+
+        assert!(matches!(
+            append_transaction(&journal.0, &txn, &WriteOptions::new()),
+            Err(HLedgerError::InvalidTransaction(_))
+        ));
+        assert_eq!(journal.read(), "; untouched\n");
+    }
+
+    fn span(start_line: u32, end_line: u32) -> SourceSpan {
+        SourceSpan {
+            file: "main.journal".to_string(),
+            start_line,
+            start_column: 1,
+            end_line,
+            end_column: 1,
+        }
+    }
+
+    const TWO_TRANSACTIONS: &str = "\
+; header comment
+
+2024-01-01 opening
+    assets:cash  $100
+    equity
+
+; between
+2024-01-05 lunch
+    expenses:food  $12
+    assets:cash
+";
+
+    #[test]
+    fn test_splice_preserves_surrounding_bytes() {
+        let spliced = splice_span(TWO_TRANSACTIONS.as_bytes(), &span(3, 6), RENDERED).unwrap();
+        let spliced = String::from_utf8(spliced).unwrap();
+        assert_eq!(
+            spliced,
+            TWO_TRANSACTIONS.replace(
+                "2024-01-01 opening\n    assets:cash  $100\n    equity\n",
+                RENDERED
+            )
+        );
+    }
+
+    #[test]
+    fn test_splice_last_transaction_ending_at_eof() {
+        // hledger ends the last span on the line after the file's last line
+        let spliced = splice_span(TWO_TRANSACTIONS.as_bytes(), &span(8, 11), RENDERED).unwrap();
+        let spliced = String::from_utf8(spliced).unwrap();
+        assert!(spliced.starts_with("; header comment\n\n2024-01-01 opening"));
+        assert!(spliced.ends_with(&format!("; between\n{}", RENDERED)));
+
+        // Text without a final newline still ends the transaction's last line
+        let spliced = splice_span(
+            b"2024-01-01 x\n    a  1\n    b\n",
+            &span(1, 4),
+            "2024-01-02 y\n    c  1\n    d",
+        )
+        .unwrap();
+        assert_eq!(spliced, b"2024-01-02 y\n    c  1\n    d\n");
+    }
+
+    #[test]
+    fn test_splice_keeps_crlf_line_endings() {
+        let crlf = TWO_TRANSACTIONS.replace('\n', "\r\n");
+        let spliced = splice_span(crlf.as_bytes(), &span(3, 6), RENDERED).unwrap();
+        let spliced = String::from_utf8(spliced).unwrap();
+        assert_eq!(
+            spliced,
+            TWO_TRANSACTIONS
+                .replace(
+                    "2024-01-01 opening\n    assets:cash  $100\n    equity\n",
+                    RENDERED
+                )
+                .replace('\n', "\r\n")
+        );
+    }
+
+    #[test]
+    fn test_splice_rejects_spans_outside_file() {
+        assert!(splice_span(TWO_TRANSACTIONS.as_bytes(), &span(20, 23), RENDERED).is_err());
+        assert!(splice_span(TWO_TRANSACTIONS.as_bytes(), &span(6, 3), RENDERED).is_err());
+    }
+
+    #[test]
+    fn test_position_offset_counts_characters() {
+        let contents = "2024-01-01 café\n    €  5\n".as_bytes();
+        assert_eq!(position_offset(contents, 1, 1), Some(0));
+        assert_eq!(position_offset(contents, 2, 1), Some(17));
+        // Column 6 is just past the euro sign, which takes three bytes
+        assert_eq!(position_offset(contents, 2, 6), Some(17 + 4 + 3));
+        assert_eq!(position_offset(contents, 3, 1), Some(contents.len()));
+        assert_eq!(position_offset(contents, 4, 1), None);
+    }
+
+    #[test]
+    fn test_fingerprint_detects_changes() {
+        let a = FileFingerprint::of(b"2024-01-01 x\n");
+        assert_eq!(a, FileFingerprint::of(b"2024-01-01 x\n"));
+        assert_ne!(a, FileFingerprint::of(b"2024-01-01 y\n"));
+        assert_eq!(a.hash.len(), 16);
+    }
+
+    #[test]
+    fn test_refuses_to_write_changed_file() {
+        let journal = TempJournal::new("changed", Some(TWO_TRANSACTIONS.as_bytes()));
+        let fingerprint = file_fingerprint(&journal.0).unwrap();
+        fs::write(
+            &journal.0,
+            format!("{}; edited elsewhere\n", TWO_TRANSACTIONS),
+        )
+        .unwrap();
+
+        let options = WriteOptions::new().expect_unchanged(fingerprint);
+        assert!(matches!(
+            append_transaction(&journal.0, &groceries(), &options),
+            Err(HLedgerError::FileChanged { .. })
+        ));
+
+        let mut stale = span(3, 6);
+        stale.file = journal.0.to_string_lossy().into_owned();
+        assert!(matches!(
+            replace_transaction(&journal.0, &stale, Replacement::Text(RENDERED), &options),
+            Err(HLedgerError::FileChanged { .. })
+        ));
+        assert!(journal.read().ends_with("; edited elsewhere\n"));
+    }
+
+    #[test]
+    fn test_hook_runs_before_write() {
+        let existing = "2024-01-01 opening\n    assets:cash  $100\n    equity\n";
+        let journal = TempJournal::new("hook", Some(existing.as_bytes()));
+
+        // The hook sees the file as it was
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hook = {
+            let seen = seen.clone();
+            WriteHook::new(move |path| {
+                seen.lock().unwrap().push(fs::read_to_string(path)?);
+                Ok(())
+            })
+        };
+        let options = WriteOptions::new().before_write(hook);
+        append_transaction(&journal.0, &groceries(), &options).unwrap();
+        assert_eq!(*seen.lock().unwrap(), [existing]);
+
+        // and a failing one stops the write
+        let refuse = WriteHook::new(|_| Err(HLedgerError::ParseError("no backup".into())));
+        let options = WriteOptions::new().before_write(refuse);
+        let before = journal.read();
+        assert!(append_transaction(&journal.0, &groceries(), &options).is_err());
+        assert_eq!(journal.read(), before);
+    }
+}
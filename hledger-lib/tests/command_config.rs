@@ -0,0 +1,110 @@
+//! The environment hledger runs in and how its output is read, checked
+//! against a mock hledger (see `tests/support`)
+//!
+//! The command config and `LEDGER_FILE` are process-wide, so these tests
+//! take turns.
+
+mod support;
+
+use hledger_lib::{
+    collect_warnings, get_accounts, set_command_config, AccountsOptions, CommandConfig,
+    HLedgerError,
+};
+use std::sync::Mutex;
+use support::{Canned, MockHledger};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+fn accounts_mock() -> MockHledger {
+    MockHledger::new().on(&["accounts"], Canned::stdout("assets\n"))
+}
+
+#[test]
+fn test_default_environment() {
+    let _lock = ENV_LOCK.lock().unwrap();
+    set_command_config(CommandConfig::default());
+    std::env::set_var("LEDGER_FILE", "/home/me/inherited.journal");
+
+    let mock = accounts_mock();
+    let options = AccountsOptions::new();
+    get_accounts(Some(mock.path()), Some("main.journal"), &options).unwrap();
+    get_accounts(Some(mock.path()), None, &options).unwrap();
+    std::env::remove_var("LEDGER_FILE");
+
+    let calls = mock.invocations();
+    assert_eq!(calls.len(), 2);
+    for call in &calls {
+        assert_eq!(call.env.get("LC_ALL").map(String::as_str), Some("C.UTF-8"));
+    }
+    // Dropped when the journal is named with -f, otherwise hledger needs it
+    assert_eq!(calls[0].env.get("LEDGER_FILE"), None);
+    assert_eq!(
+        calls[1].env.get("LEDGER_FILE").map(String::as_str),
+        Some("/home/me/inherited.journal")
+    );
+}
+
+#[test]
+fn test_custom_environment() {
+    let _lock = ENV_LOCK.lock().unwrap();
+    let dir = std::env::temp_dir().canonicalize().unwrap();
+    set_command_config(
+        CommandConfig::new()
+            .env("NO_COLOR", "1")
+            .keep_ledger_file()
+            .working_dir(&dir),
+    );
+    std::env::set_var("LEDGER_FILE", "/home/me/inherited.journal");
+
+    let mock = accounts_mock();
+    let result = get_accounts(
+        Some(mock.path()),
+        Some("main.journal"),
+        &AccountsOptions::new(),
+    );
+    std::env::remove_var("LEDGER_FILE");
+    set_command_config(CommandConfig::default());
+    result.unwrap();
+
+    let call = &mock.invocations()[0];
+    assert_eq!(call.env.get("NO_COLOR").map(String::as_str), Some("1"));
+    assert_eq!(call.env.get("LC_ALL").map(String::as_str), Some("C.UTF-8"));
+    assert_eq!(
+        call.env.get("LEDGER_FILE").map(String::as_str),
+        Some("/home/me/inherited.journal")
+    );
+    assert_eq!(call.cwd.as_deref(), Some(dir.as_path()));
+}
+
+#[test]
+fn test_lossy_utf8_output() {
+    let _lock = ENV_LOCK.lock().unwrap();
+    // Latin-1 account names, as from a journal that isn't UTF-8
+    let mock = MockHledger::new().on(
+        &["accounts"],
+        Canned::bytes(b"assets\nexpenses:caf\xe9\n".to_vec()),
+    );
+    let options = AccountsOptions::new();
+
+    set_command_config(CommandConfig::default());
+    let (strict, warnings) = collect_warnings(|| get_accounts(Some(mock.path()), None, &options));
+    assert!(
+        matches!(strict, Err(HLedgerError::InvalidUtf8(_))),
+        "got {:?}",
+        strict
+    );
+    assert!(warnings.is_empty());
+
+    set_command_config(CommandConfig::new().lossy_utf8());
+    let (lossy, warnings) = collect_warnings(|| get_accounts(Some(mock.path()), None, &options));
+    set_command_config(CommandConfig::default());
+
+    assert_eq!(lossy.unwrap(), vec!["assets", "expenses:caf\u{FFFD}"]);
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0].contains("1 invalid UTF-8 sequence"),
+        "{}",
+        warnings[0]
+    );
+    assert!(warnings[0].contains("accounts"), "{}", warnings[0]);
+}
@@ -0,0 +1,731 @@
+//! Error handling against a mock hledger (see `tests/support`), so these run
+//! without hledger installed and don't depend on its exact messages
+
+mod support;
+
+use hledger_lib::{
+    dashboard_summary, get_accounts, get_balance, get_balance_raw, get_balancesheet, get_cashflow,
+    get_check, get_incomestatement, get_journal_problems, get_print, get_print_paged, get_version,
+    health_check, run_reports, validate_dropped_file, with_cancel_token, with_warnings,
+    AccountsOptions, AppError, BalanceOptions, BalanceSheetOptions, CancelToken, CashflowOptions,
+    CheckIssueKind, DashboardSection, ErrorCode, HLedgerError, HealthCode, HealthStatus,
+    HledgerSource, IncomeStatementOptions, JournalMetadataCache, PageSpec, PrintOptions,
+    ReportCache, ReportDefaults, ReportRequest, SuggestionCache, SuggestionKind,
+};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use support::{Canned, MockHledger};
+
+const MISSING_FILE: &str =
+    "hledger: Error: nonexistent.journal: openFile: does not exist (No such file or directory)\n";
+
+/// A mock that fails like hledger does when the journal file is missing
+fn missing_journal() -> MockHledger {
+    MockHledger::new().on(
+        &["-f", "nonexistent.journal"],
+        Canned::failure(1, MISSING_FILE),
+    )
+}
+
+fn assert_command_failed<T: std::fmt::Debug>(result: hledger_lib::Result<T>) {
+    match result {
+        Err(HLedgerError::CommandFailed {
+            command,
+            code,
+            stderr,
+        }) => {
+            assert!(command.contains("nonexistent.journal"));
+            assert_eq!(code, 1);
+            assert!(stderr.contains("does not exist"));
+        }
+        other => panic!("Expected CommandFailed error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_accounts_error_nonexistent_file() {
+    let mock = missing_journal();
+    let result = get_accounts(
+        Some(mock.path()),
+        Some("nonexistent.journal"),
+        &AccountsOptions::default(),
+    );
+    assert_command_failed(result);
+
+    let calls = mock.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0][..3], ["-f", "nonexistent.journal", "accounts"]);
+}
+
+#[test]
+fn test_get_balancesheet_error_nonexistent_file() {
+    let mock = missing_journal();
+    assert_command_failed(get_balancesheet(
+        Some(mock.path()),
+        Some("nonexistent.journal"),
+        &BalanceSheetOptions::default(),
+    ));
+}
+
+#[test]
+fn test_get_incomestatement_error_nonexistent_file() {
+    let mock = missing_journal();
+    assert_command_failed(get_incomestatement(
+        Some(mock.path()),
+        Some("nonexistent.journal"),
+        &IncomeStatementOptions::default(),
+    ));
+}
+
+#[test]
+fn test_get_cashflow_error_nonexistent_file() {
+    let mock = missing_journal();
+    assert_command_failed(get_cashflow(
+        Some(mock.path()),
+        Some(Path::new("nonexistent.journal")),
+        CashflowOptions::default(),
+    ));
+}
+
+#[test]
+fn test_get_print_error_nonexistent_file() {
+    let mock = missing_journal();
+    assert_command_failed(get_print(
+        Some(mock.path()),
+        Some("nonexistent.journal"),
+        &PrintOptions::new(),
+    ));
+}
+
+#[test]
+fn test_malformed_json_is_a_json_error() {
+    // Cut off partway through an otherwise well-formed simple report
+    let mock = MockHledger::new().on(
+        &["balance"],
+        Canned::stdout("[[[\"assets\", \"assets\", 0,"),
+    );
+    let result = get_balance(Some(mock.path()), None, &BalanceOptions::new());
+    assert!(
+        matches!(result, Err(HLedgerError::Json(_))),
+        "got {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_unexpected_json_shape_is_a_parse_error() {
+    let mock = MockHledger::new().on(&["balancesheet"], Canned::stdout("[]"));
+    let result = get_balancesheet(Some(mock.path()), None, &BalanceSheetOptions::new());
+    assert!(
+        matches!(result, Err(HLedgerError::ParseError(_))),
+        "got {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_unrecognized_version_is_a_parse_error() {
+    let mock = MockHledger::new().on(&["--version"], Canned::stdout("hledger-ish\n"));
+    match get_version(Some(mock.path())) {
+        Err(HLedgerError::ParseError(message)) => assert!(message.contains("hledger-ish")),
+        other => panic!("Expected ParseError, got {:?}", other),
+    }
+
+    let mock = MockHledger::new().on(
+        &["--version"],
+        Canned::stdout("hledger 1.32.3, linux-x86_64\n"),
+    );
+    let version = get_version(Some(mock.path())).expect("Failed to parse version");
+    assert_eq!(version.to_string(), "1.32.3");
+}
+
+#[test]
+fn test_non_utf8_output_is_an_error() {
+    let mock = MockHledger::new().on(&["accounts"], Canned::bytes(b"assets\n\xff\xfe\n".to_vec()));
+    let result = get_accounts(Some(mock.path()), None, &AccountsOptions::new());
+    assert!(
+        matches!(result, Err(HLedgerError::InvalidUtf8(_))),
+        "got {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_missing_binary_is_not_found() {
+    let mock = MockHledger::new();
+    let missing = Path::new(mock.path()).with_file_name("no-such-hledger");
+    let result = get_accounts(missing.to_str(), None, &AccountsOptions::new());
+    match result {
+        Err(HLedgerError::HLedgerNotFound { attempted }) => {
+            assert!(attempted.contains("no-such-hledger"))
+        }
+        other => panic!("Expected HLedgerNotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_accounts_timeout_with_slow_binary() {
+    let mock = MockHledger::new().on(
+        &["accounts"],
+        Canned::stdout("assets\n").delay(Duration::from_secs(10)),
+    );
+
+    let options = AccountsOptions::new().timeout(Duration::from_millis(200));
+    let start = Instant::now();
+    let result = get_accounts(
+        Some(mock.path()),
+        Some("tests/fixtures/test.journal"),
+        &options,
+    );
+
+    match result {
+        Err(HLedgerError::Timeout { elapsed }) => {
+            assert!(elapsed >= Duration::from_millis(200));
+            assert!(start.elapsed() < Duration::from_secs(5));
+        }
+        other => panic!("Expected Timeout error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_cancel_kills_running_reports() {
+    let mock = MockHledger::new().on(
+        &["accounts"],
+        Canned::stdout("assets\n").delay(Duration::from_secs(10)),
+    );
+    let token = CancelToken::new();
+    let canceller = {
+        let token = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            token.cancel();
+        })
+    };
+
+    // Both reports run on worker threads, which watch the caller's token
+    let start = Instant::now();
+    let results = with_cancel_token(&token, || {
+        run_reports(
+            Some(mock.path()),
+            Some("tests/fixtures/test.journal"),
+            vec![
+                ReportRequest::Accounts(AccountsOptions::new()),
+                ReportRequest::Accounts(AccountsOptions::new().used()),
+            ],
+        )
+    });
+    canceller.join().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    for result in results {
+        assert!(matches!(result, Err(HLedgerError::Cancelled)));
+    }
+
+    // A cancelled token stops later runs before they start
+    let result = with_cancel_token(&token, || {
+        get_accounts(Some(mock.path()), None, &AccountsOptions::new())
+    });
+    assert!(matches!(result, Err(HLedgerError::Cancelled)));
+    assert_eq!(mock.calls().len(), 2);
+}
+
+#[test]
+fn test_mock_replays_canned_output() {
+    let mock = MockHledger::new()
+        .on(&["accounts", "--depth=1"], Canned::stdout("assets\n"))
+        .on(&["accounts"], Canned::stdout("assets\nassets:bank\n"));
+
+    let shallow = get_accounts(Some(mock.path()), None, &AccountsOptions::new().depth(1));
+    assert_eq!(shallow.unwrap(), vec!["assets"]);
+    let all = get_accounts(Some(mock.path()), None, &AccountsOptions::new());
+    assert_eq!(all.unwrap(), vec!["assets", "assets:bank"]);
+    assert_eq!(mock.calls().len(), 2);
+
+    // Calls nothing was registered for fail like a usage error
+    let result = get_print(Some(mock.path()), None, &PrintOptions::new());
+    assert!(matches!(
+        result,
+        Err(HLedgerError::CommandFailed { code: 2, .. })
+    ));
+}
+
+const DEPRECATION: &str = "Warning: --flat is deprecated, use --flat=yes\n";
+
+#[test]
+fn test_stderr_on_success_is_a_warning() {
+    let mock = MockHledger::new().on(
+        &["accounts"],
+        Canned::stdout("assets\n").with_stderr(DEPRECATION),
+    );
+    let output = with_warnings(|| get_accounts(Some(mock.path()), None, &AccountsOptions::new()))
+        .expect("A warning shouldn't fail the report");
+    assert_eq!(output.value, vec!["assets"]);
+    assert_eq!(
+        output.warnings,
+        vec!["Warning: --flat is deprecated, use --flat=yes"]
+    );
+
+    // Also from reports run on worker threads
+    let requests = vec![
+        ReportRequest::Accounts(AccountsOptions::new()),
+        ReportRequest::Accounts(AccountsOptions::new()),
+    ];
+    let output = with_warnings(|| {
+        run_reports(Some(mock.path()), None, requests)
+            .into_iter()
+            .collect::<hledger_lib::Result<Vec<_>>>()
+    })
+    .unwrap();
+    assert_eq!(output.value.len(), 2);
+    assert_eq!(output.warnings.len(), 2);
+}
+
+#[test]
+fn test_stderr_on_failure_stays_in_the_error() {
+    let mock = MockHledger::new().on(
+        &["accounts"],
+        Canned::failure(
+            1,
+            format!("{}hledger: Error: something broke\n", DEPRECATION),
+        ),
+    );
+    let (result, warnings) = hledger_lib::collect_warnings(|| {
+        get_accounts(Some(mock.path()), None, &AccountsOptions::new())
+    });
+    match result {
+        Err(HLedgerError::CommandFailed { stderr, .. }) => {
+            assert!(stderr.contains("deprecated"));
+            assert!(stderr.contains("something broke"));
+        }
+        other => panic!("Expected CommandFailed error, got {:?}", other),
+    }
+    assert!(warnings.is_empty());
+}
+
+const CONFIG_NOTICE: &str = "Using extra options from ~/.hledger.conf\n[info] --pretty\n";
+
+#[test]
+fn test_preamble_before_json_is_skipped() {
+    let mock = MockHledger::new()
+        .on(
+            &["balance"],
+            Canned::stdout(format!("{}{{\"rows\": []}}\n", CONFIG_NOTICE)),
+        )
+        .on(&["print"], Canned::stdout(format!("{}[]\n", CONFIG_NOTICE)));
+
+    let output = with_warnings(|| get_balance_raw(Some(mock.path()), None, &BalanceOptions::new()))
+        .expect("A preamble shouldn't fail the report");
+    assert_eq!(output.value, serde_json::json!({"rows": []}));
+    assert_eq!(output.warnings.len(), 1);
+    assert!(output.warnings[0].contains("~/.hledger.conf"));
+    assert!(output.warnings[0].contains("[info] --pretty"));
+
+    let output =
+        with_warnings(|| get_print(Some(mock.path()), None, &PrintOptions::new())).unwrap();
+    assert!(output.value.is_empty());
+    assert_eq!(output.warnings.len(), 1);
+
+    let page = get_print_paged(
+        Some(mock.path()),
+        None,
+        &PrintOptions::new(),
+        PageSpec::new(0, 10),
+    )
+    .unwrap();
+    assert_eq!(page.total, 0);
+}
+
+#[test]
+fn test_output_without_json_is_a_json_error() {
+    let mock = MockHledger::new().on(&["balance"], Canned::stdout(CONFIG_NOTICE));
+    let result = get_balance_raw(Some(mock.path()), None, &BalanceOptions::new());
+    assert!(
+        matches!(result, Err(HLedgerError::Json(_))),
+        "got {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_get_check_reports_issues() {
+    let journal = std::env::temp_dir().join(format!("check-{}.journal", std::process::id()));
+    std::fs::write(&journal, "2024-01-01 opening\n").unwrap();
+    let file = journal.to_str().unwrap();
+
+    let undeclared = format!(
+        "hledger: Error: {}:2:\nStrict account checking is enabled, and\naccount \"assets:cash\" has not been declared.\n",
+        file
+    );
+    let mock = MockHledger::new()
+        .on(&["check", "accounts"], Canned::failure(1, &undeclared))
+        .on(
+            &["check"],
+            Canned::stdout("").with_stderr("Warning: deprecated option\n"),
+        );
+
+    let issues = get_check(Some(mock.path()), Some(file), &["accounts".to_string()]).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, CheckIssueKind::CheckFailed);
+    assert_eq!(issues[0].line, Some(2));
+
+    let issues = get_check(Some(mock.path()), Some(file), &[]).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, CheckIssueKind::Warning);
+    assert_eq!(issues[0].message, "Warning: deprecated option");
+
+    let calls = mock.calls();
+    assert_eq!(calls[0], ["-f", file, "check", "accounts"]);
+    assert_eq!(calls[1], ["-f", file, "check"]);
+    std::fs::remove_file(&journal).unwrap();
+}
+
+#[test]
+fn test_failing_assertion_offers_ignoring_it() {
+    let journal = std::env::temp_dir().join(format!("assert-{}.journal", std::process::id()));
+    std::fs::write(&journal, "2024-01-01 opening\n").unwrap();
+    let file = journal.to_str().unwrap();
+
+    let failed = format!(
+        "hledger: Error: {}:12:35:\nBalance assertion failed in assets:bank:checking\n\
+         In commodity \"$\" at this point, excluding subaccounts, ignoring costs,\n\
+         the asserted balance is:        $800.00\n\
+         but the calculated balance is:  $750.00\n",
+        file
+    );
+    let mock = MockHledger::new()
+        .on(&["--ignore-assertions"], Canned::stdout("assets\n"))
+        .on(&["accounts"], Canned::failure(1, &failed))
+        .on(&["check"], Canned::failure(1, &failed));
+
+    let err = get_accounts(Some(mock.path()), Some(file), &AccountsOptions::new()).unwrap_err();
+    let err = AppError::from(err);
+    assert_eq!(err.code, ErrorCode::AssertionFailed);
+    let details = err.details.unwrap();
+    assert_eq!(details.file.as_deref(), Some(file));
+    assert_eq!(details.line, Some(12));
+
+    let accounts = get_accounts(
+        Some(mock.path()),
+        Some(file),
+        &AccountsOptions::new().ignore_assertions(),
+    );
+    assert_eq!(accounts.unwrap(), vec!["assets"]);
+
+    let problems = get_journal_problems(Some(mock.path()), Some(file)).unwrap();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].kind, CheckIssueKind::CheckFailed);
+    assert_eq!(problems[0].line, Some(12));
+    assert_eq!(problems[0].expected.as_ref().unwrap().commodity, "$");
+    assert_eq!(
+        problems[0].actual.as_ref().unwrap().quantity.to_string(),
+        "750.00"
+    );
+    std::fs::remove_file(&journal).unwrap();
+}
+
+#[test]
+fn test_health_check() {
+    let journal = "tests/fixtures/test.journal".to_string();
+    let missing = "/nonexistent/main.journal".to_string();
+    let mock = MockHledger::new()
+        .on(
+            &["--version"],
+            Canned::stdout("hledger 1.32.3, linux-x86_64\n"),
+        )
+        .on(&["check"], Canned::stdout(""));
+
+    let report = health_check(
+        Some(mock.path()),
+        &[journal.clone(), missing.clone()],
+        Duration::from_secs(5),
+    );
+    assert!(!report.ok);
+    assert!(report.meets_minimum_version);
+    assert_eq!(report.hledger_version.unwrap().to_string(), "1.32.3");
+    assert_eq!(report.hledger_source, Some(HledgerSource::Custom));
+    let codes: Vec<_> = report.items.iter().map(|item| item.code).collect();
+    assert_eq!(
+        codes,
+        [
+            HealthCode::HledgerFound,
+            HealthCode::JournalParses,
+            HealthCode::JournalMissing
+        ]
+    );
+    assert_eq!(report.items[2].file.as_deref(), Some(missing.as_str()));
+
+    let report = health_check(Some(mock.path()), &[journal], Duration::from_secs(5));
+    assert!(report.ok);
+}
+
+#[test]
+fn test_health_check_stops_at_budget() {
+    let mock = MockHledger::new()
+        .on(
+            &["--version"],
+            Canned::stdout("hledger 1.18, linux-x86_64\n"),
+        )
+        .on(
+            &["check"],
+            Canned::stdout("").delay(Duration::from_secs(10)),
+        );
+
+    let start = Instant::now();
+    let report = health_check(
+        Some(mock.path()),
+        &["tests/fixtures/test.journal".to_string()],
+        Duration::from_millis(500),
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(!report.meets_minimum_version);
+    assert_eq!(report.items[0].code, HealthCode::HledgerTooOld);
+    assert_eq!(report.items[1].code, HealthCode::JournalCheckTimedOut);
+    assert_eq!(report.items[1].status, HealthStatus::Warning);
+}
+
+#[test]
+fn test_reports_on_separate_threads_overlap() {
+    let mock = MockHledger::new().on(
+        &["accounts"],
+        Canned::stdout("assets\n").delay(Duration::from_millis(500)),
+    );
+
+    // As the app runs each report command on its own blocking thread
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        let reports: Vec<_> = (0..2)
+            .map(|_| {
+                scope.spawn(|| {
+                    get_accounts(
+                        Some(mock.path()),
+                        Some("tests/fixtures/test.journal"),
+                        &AccountsOptions::new(),
+                    )
+                })
+            })
+            .collect();
+        for report in reports {
+            assert_eq!(report.join().unwrap().unwrap(), ["assets"]);
+        }
+    });
+    assert!(start.elapsed() < Duration::from_millis(900));
+}
+
+#[test]
+fn test_dashboard_summary_tolerates_a_failing_report() {
+    let empty_subreport = serde_json::json!({
+        "prDates": [],
+        "prRows": [],
+        "prTotals": {"prrName": [], "prrAmounts": [], "prrTotal": [], "prrAverage": []}
+    });
+    let balancesheet = serde_json::json!({
+        "cbrTitle": "Balance Sheet",
+        "cbrDates": [],
+        "cbrSubreports": [["Assets", empty_subreport, true]],
+        "cbrTotals": {"prrName": [], "prrAmounts": [], "prrTotal": [], "prrAverage": []}
+    });
+    let mock = MockHledger::new()
+        .on(&["balancesheet"], Canned::stdout(balancesheet.to_string()))
+        .on(
+            &["incomestatement"],
+            Canned::failure(1, "hledger: Error: something broke\n"),
+        )
+        .on(&["print"], Canned::stdout("[]"));
+
+    let summary = dashboard_summary(
+        Some(mock.path()),
+        None,
+        Some("2024-03"),
+        &ReportDefaults::default(),
+    )
+    .expect("One failing report shouldn't fail the dashboard");
+    assert_eq!(summary.month, "2024-03");
+    assert!(summary
+        .net_worth
+        .is_some_and(|net_worth| net_worth.is_zero()));
+    assert_eq!(summary.transaction_count, Some(0));
+    assert_eq!(summary.this_month, None);
+    assert_eq!(summary.net_change, None);
+    assert_eq!(summary.unavailable.len(), 1);
+    assert_eq!(
+        summary.unavailable[0].section,
+        DashboardSection::IncomeExpenses
+    );
+    assert_eq!(summary.unavailable[0].error.code, ErrorCode::CommandFailed);
+
+    // Each report covers the month asked for
+    let print = mock
+        .calls()
+        .into_iter()
+        .find(|call| call.contains(&"print".to_string()))
+        .unwrap();
+    assert!(print
+        .windows(2)
+        .any(|args| args == ["--begin", "2024-03-01"]));
+    assert!(print.windows(2).any(|args| args == ["--end", "2024-04-01"]));
+
+    assert!(matches!(
+        dashboard_summary(
+            Some(mock.path()),
+            None,
+            Some("soon"),
+            &ReportDefaults::default()
+        ),
+        Err(HLedgerError::InvalidDate { .. })
+    ));
+}
+
+#[test]
+fn test_suggestions_build_in_the_background() {
+    let print = r#"[
+        {"tindex": 1, "tdate": "2024-01-05", "tdescription": "Grocer", "tpostings": []},
+        {"tindex": 2, "tdate": "2024-01-06", "tdescription": "Landlord", "tpostings": []}
+    ]"#;
+    let mock = MockHledger::new()
+        .on(
+            &["accounts"],
+            Canned::stdout("assets:cash\nexpenses:food\n"),
+        )
+        .on(
+            &["print"],
+            Canned::stdout(print).delay(Duration::from_millis(300)),
+        );
+    let cache = SuggestionCache::new();
+    let suggest =
+        |query: &str| cache.suggest(Some(mock.path()), None, SuggestionKind::Payee, query, 10);
+
+    // The first query starts the build and doesn't wait for it
+    let start = Instant::now();
+    let first = suggest("g");
+    assert!(start.elapsed() < Duration::from_millis(200));
+    assert!(first.values.is_empty() && first.refreshing);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let ready = loop {
+        let suggestions = suggest("g");
+        if !suggestions.refreshing || Instant::now() > deadline {
+            break suggestions;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    assert_eq!(ready.values, ["Grocer"]);
+    assert_eq!(mock.calls().len(), 2);
+
+    // A change rebuilds once, answering from the old index meanwhile
+    cache.mark_stale();
+    let stale = suggest("lord");
+    assert_eq!(stale.values, ["Landlord"]);
+    assert!(stale.refreshing);
+    assert!(suggest("lord").refreshing);
+    while suggest("lord").refreshing && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(mock.calls().len(), 4);
+}
+
+#[test]
+fn test_journal_metadata_from_stats_and_files() {
+    let stats = "\
+Main file                : tests/fixtures/test.journal
+Transactions span        : 2024-01-01 to 2024-02-01 (31 days)
+Last transaction         : 2024-01-31 (260 days ago)
+Transactions             : 3 (0.1 per day)
+Payees/descriptions      : 3
+Accounts                 : 4 (depth 2)
+Commodities              : 1 ($)
+";
+    let mock = MockHledger::new().on(&["stats"], Canned::stdout(stats)).on(
+        &["files"],
+        Canned::stdout("tests/fixtures/test.journal\ntests/fixtures/prices.journal\n"),
+    );
+    let cache = JournalMetadataCache::new();
+    let metadata = cache
+        .get(Some(mock.path()), "tests/fixtures/test.journal")
+        .unwrap();
+    let stats = metadata.stats.unwrap();
+    assert_eq!(stats.transaction_count, 3);
+    assert_eq!(stats.commodities, ["$"]);
+    assert_eq!(
+        metadata.included_files.unwrap(),
+        ["tests/fixtures/prices.journal"]
+    );
+    assert_eq!(metadata.note, None);
+
+    // An unchanged file is answered from the cache
+    cache
+        .get(Some(mock.path()), "tests/fixtures/test.journal")
+        .unwrap();
+    assert_eq!(mock.calls().len(), 2);
+}
+
+#[test]
+fn test_validate_dropped_file() {
+    let stats = "Transactions             : 3 (0.1 per day)\n";
+    let mock = MockHledger::new().on(&["stats"], Canned::stdout(stats));
+    let dropped = validate_dropped_file(Some(mock.path()), "tests/fixtures/test.journal").unwrap();
+    assert!(dropped.accepted);
+    assert_eq!(dropped.transaction_count, Some(3));
+
+    let journal = std::env::temp_dir().join(format!("dropped-{}.journal", std::process::id()));
+    std::fs::write(&journal, "2024-01-01 opening\n  assets  $1\n").unwrap();
+    let file = journal.to_str().unwrap();
+    let unbalanced = format!(
+        "hledger: Error: {}:1-2:\n1 | 2024-01-01 opening\n  |     assets  $1\nThis transaction is unbalanced.\n",
+        file
+    );
+    let mock = MockHledger::new().on(&["stats"], Canned::failure(1, &unbalanced));
+    let dropped = validate_dropped_file(Some(mock.path()), file).unwrap();
+    std::fs::remove_file(&journal).unwrap();
+    assert!(!dropped.accepted);
+    assert_eq!(dropped.transaction_count, None);
+    let issue = dropped.issue.unwrap();
+    assert_eq!(issue.line, Some(1));
+    assert_eq!(dropped.reason.unwrap(), issue.message);
+
+    // hledger missing can't be told from a bad file
+    assert!(
+        validate_dropped_file(Some("/nonexistent/hledger"), "tests/fixtures/test.journal").is_err()
+    );
+}
+
+#[test]
+fn test_identical_cached_reports_run_once() {
+    let mock = MockHledger::new().on(
+        &["accounts"],
+        Canned::stdout("assets\n").delay(Duration::from_millis(300)),
+    );
+    // The journal alone, so the only hledger runs are the reports'
+    let cache = ReportCache::new(8)
+        .file_lister(|_, file| Ok(file.into_iter().map(str::to_string).collect()));
+    let journal = Some("tests/fixtures/test.journal");
+    let options = AccountsOptions::new();
+    let accounts = || cache.get_accounts_cached(Some(mock.path()), journal, &options);
+
+    // Two tabs asking at once share one run
+    let (first, second) = std::thread::scope(|scope| {
+        let first = scope.spawn(accounts);
+        std::thread::sleep(Duration::from_millis(50));
+        (first.join().unwrap(), accounts())
+    });
+    assert_eq!(first.unwrap(), ["assets"]);
+    assert_eq!(second.unwrap(), ["assets"]);
+    assert_eq!(mock.calls().len(), 1);
+
+    // A run cancelled by its caller leaves the one waiting on it to run the
+    // report itself
+    cache.clear();
+    let token = CancelToken::new();
+    let (cancelled, waiting) = std::thread::scope(|scope| {
+        let cancelled = scope.spawn(|| with_cancel_token(&token, accounts));
+        std::thread::sleep(Duration::from_millis(50));
+        let canceller = scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            token.cancel();
+        });
+        let waiting = accounts();
+        canceller.join().unwrap();
+        (cancelled.join().unwrap(), waiting)
+    });
+    assert!(matches!(cancelled, Err(HLedgerError::Cancelled)));
+    assert_eq!(waiting.unwrap(), ["assets"]);
+    assert_eq!(mock.calls().len(), 3);
+}
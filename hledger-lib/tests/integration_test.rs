@@ -1,11 +1,12 @@
 use hledger_lib::{
     get_accounts, get_balancesheet, get_cashflow, get_incomestatement, AccountsOptions,
-    BalanceSheetOptions, CashflowOptions, HLedgerError, IncomeStatementOptions,
+    BalanceSheetOptions, CashflowOptions, HLedgerError, IncomeStatementOptions, ListMode,
 };
 
 #[test]
 fn test_get_accounts_with_journal() {
     let accounts = get_accounts(
+        None,
         Some("tests/fixtures/test.journal"),
         &AccountsOptions::default(),
     )
@@ -24,7 +25,7 @@ fn test_get_accounts_with_journal() {
 #[test]
 fn test_get_accounts_no_journal() {
     // This should work if there's a default journal file or fail gracefully
-    let result = get_accounts(None, &AccountsOptions::default());
+    let result = get_accounts(None, None, &AccountsOptions::default());
     // We don't assert success/failure since it depends on the environment
     // Just ensure it doesn't panic
     match result {
@@ -40,7 +41,7 @@ fn test_get_accounts_no_journal() {
 #[test]
 fn test_get_accounts_depth_filter() {
     let options = AccountsOptions::new().depth(1);
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // With depth 1, we should only see top-level accounts
@@ -61,7 +62,7 @@ fn test_get_accounts_depth_filter() {
 #[test]
 fn test_get_accounts_with_query_filter() {
     let options = AccountsOptions::new().query("assets");
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should only include accounts that match the "assets" query
@@ -80,7 +81,7 @@ fn test_get_accounts_with_query_filter() {
 fn test_get_accounts_with_date_filter() {
     let options = AccountsOptions::new().begin("2024-01-01").end("2024-01-06"); // End is exclusive, so this includes 2024-01-05
 
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should get accounts from transactions in the date range
@@ -98,7 +99,7 @@ fn test_get_accounts_with_date_filter() {
 #[test]
 fn test_get_accounts_query_filter_negative() {
     let options = AccountsOptions::new().query("assets");
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should only include accounts that match the "assets" query
@@ -119,7 +120,7 @@ fn test_get_accounts_query_filter_negative() {
 #[test]
 fn test_get_accounts_depth_filter_negative() {
     let options = AccountsOptions::new().depth(2);
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // With depth 2, should not see accounts with more than 2 levels
@@ -142,7 +143,7 @@ fn test_get_accounts_depth_filter_negative() {
 fn test_get_accounts_multiple_queries_negative() {
     let options = AccountsOptions::new().query("assets").query("bank");
 
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Multiple queries work as OR - should include accounts matching "assets" OR "bank"
@@ -159,7 +160,7 @@ fn test_get_accounts_multiple_queries_negative() {
 #[test]
 fn test_get_accounts_empty_result() {
     let options = AccountsOptions::new().query("nonexistent");
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should return empty result for non-matching query
@@ -171,7 +172,7 @@ fn test_get_accounts_invalid_date_range() {
     // End date before begin date
     let options = AccountsOptions::new().begin("2024-01-10").end("2024-01-01");
 
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should return empty result for invalid date range
@@ -182,31 +183,17 @@ fn test_get_accounts_invalid_date_range() {
 fn test_get_accounts_future_date_range() {
     let options = AccountsOptions::new().begin("2025-01-01").end("2025-01-31");
 
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should return empty result for future dates with no transactions
     assert!(accounts.is_empty());
 }
 
-#[test]
-fn test_get_accounts_error_nonexistent_file() {
-    let result = get_accounts(Some("nonexistent.journal"), &AccountsOptions::default());
-
-    // Should return an error for non-existent file
-    assert!(result.is_err());
-    match result {
-        Err(HLedgerError::CommandFailed { code, stderr: _ }) => {
-            assert_ne!(code, 0);
-        }
-        _ => panic!("Expected CommandFailed error"),
-    }
-}
-
 #[test]
 fn test_get_accounts_find_exact_match() {
     let options = AccountsOptions::new().find("assets:bank:checking");
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should return exactly one account that matches
@@ -217,7 +204,7 @@ fn test_get_accounts_find_exact_match() {
 #[test]
 fn test_get_accounts_find_partial_match() {
     let options = AccountsOptions::new().find("bank");
-    let accounts = get_accounts(Some("tests/fixtures/test.journal"), &options)
+    let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get accounts");
 
     // Should return the first account containing "bank"
@@ -228,18 +215,31 @@ fn test_get_accounts_find_partial_match() {
 }
 
 #[test]
-fn test_get_accounts_find_no_match() {
-    let options = AccountsOptions::new().find("nonexistent");
-    let result = get_accounts(Some("tests/fixtures/test.journal"), &options);
+fn test_find_account_no_match() {
+    use hledger_lib::find_account;
 
-    // Should fail with non-zero exit code when no match is found
+    let options = AccountsOptions::new();
+    let found = find_account(
+        None,
+        Some("tests/fixtures/test.journal"),
+        "nonexistent",
+        &options,
+    )
+    .expect("No match should not be an error");
+    assert_eq!(found, None);
+
+    let found = find_account(None, Some("tests/fixtures/test.journal"), "bank", &options)
+        .expect("Failed to find account");
+    assert_eq!(found.as_deref(), Some("assets:bank"));
+
+    // Real failures are still errors
+    let result = find_account(
+        None,
+        Some("tests/fixtures/nonexistent.journal"),
+        "bank",
+        &options,
+    );
     assert!(result.is_err());
-    match result {
-        Err(HLedgerError::CommandFailed { code, stderr: _ }) => {
-            assert_ne!(code, 0);
-        }
-        _ => panic!("Expected CommandFailed error for no match"),
-    }
 }
 
 // ================================
@@ -249,6 +249,7 @@ fn test_get_accounts_find_no_match() {
 #[test]
 fn test_get_balancesheet_simple() {
     let report = get_balancesheet(
+        None,
         Some("tests/fixtures/test.journal"),
         &BalanceSheetOptions::default(),
     )
@@ -281,7 +282,7 @@ fn test_get_balancesheet_simple() {
 #[test]
 fn test_get_balancesheet_monthly() {
     let options = BalanceSheetOptions::new().monthly();
-    let report = get_balancesheet(Some("tests/fixtures/test.journal"), &options)
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get monthly balance sheet");
 
     // Should have monthly periods
@@ -297,7 +298,7 @@ fn test_get_balancesheet_monthly() {
 #[test]
 fn test_get_balancesheet_tree_mode() {
     let options = BalanceSheetOptions::new().tree().depth(2);
-    let report = get_balancesheet(Some("tests/fixtures/test.journal"), &options)
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get tree mode balance sheet");
 
     // Should still have subreports
@@ -318,7 +319,7 @@ fn test_get_balancesheet_tree_mode() {
 #[test]
 fn test_get_balancesheet_with_query() {
     let options = BalanceSheetOptions::new().query("bank");
-    let report = get_balancesheet(Some("tests/fixtures/test.journal"), &options)
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get filtered balance sheet");
 
     // Should still have subreports structure
@@ -337,7 +338,7 @@ fn test_get_balancesheet_with_query() {
 #[test]
 fn test_get_balancesheet_historical_mode() {
     let options = BalanceSheetOptions::new().historical();
-    let report = get_balancesheet(Some("tests/fixtures/test.journal"), &options)
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get historical balance sheet");
 
     // Historical mode should work (it's the default for balance sheet anyway)
@@ -352,7 +353,7 @@ fn test_get_balancesheet_with_dates() {
         .begin("2024-01-01")
         .end("2024-01-06");
 
-    let report = get_balancesheet(Some("tests/fixtures/test.journal"), &options)
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get balance sheet with date filter");
 
     // Should have subreports
@@ -376,7 +377,7 @@ fn test_get_balancesheet_with_dates() {
 #[test]
 fn test_get_balancesheet_depth_limit() {
     let options = BalanceSheetOptions::new().depth(1);
-    let report = get_balancesheet(Some("tests/fixtures/test.journal"), &options)
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get balance sheet with depth limit");
 
     // With depth 1, should only see top-level accounts
@@ -392,7 +393,7 @@ fn test_get_balancesheet_depth_limit() {
 #[test]
 fn test_get_balancesheet_with_totals() {
     let options = BalanceSheetOptions::new().row_total().average();
-    let report = get_balancesheet(Some("tests/fixtures/test.journal"), &options)
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get balance sheet with totals");
 
     // Should have subreports
@@ -406,24 +407,27 @@ fn test_get_balancesheet_with_totals() {
             let has_averages = subreport.rows.iter().any(|r| r.average.is_some());
             // Note: hledger might not always include totals/averages for single-period reports
             // so we don't assert these, just verify the structure is preserved
-            assert!(has_totals || !has_totals); // Always true, just checking the field exists
-            assert!(has_averages || !has_averages); // Always true, just checking the field exists
+            // Only checking the fields exist
+            let _ = (has_totals, has_averages);
         }
     }
 }
 
 #[test]
-fn test_get_balancesheet_error_nonexistent_file() {
-    let result = get_balancesheet(Some("nonexistent.journal"), &BalanceSheetOptions::default());
+fn test_get_balancesheet_no_total() {
+    let options = BalanceSheetOptions::new().monthly().no_total();
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
+        .expect("Failed to get balance sheet without totals");
 
-    // Should return an error for non-existent file
-    assert!(result.is_err());
-    match result {
-        Err(HLedgerError::CommandFailed { code, stderr: _ }) => {
-            assert_ne!(code, 0);
-        }
-        _ => panic!("Expected CommandFailed error"),
+    assert!(report.totals.is_none());
+    for subreport in &report.subreports {
+        assert!(subreport.totals.is_none());
     }
+
+    let options = BalanceSheetOptions::new().monthly();
+    let report = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options)
+        .expect("Failed to get balance sheet with totals");
+    assert!(report.totals.is_some());
 }
 
 #[test]
@@ -440,9 +444,8 @@ fn test_get_balancesheet_options_builder() {
         .historical();
 
     // Verify builder pattern works
-    assert!(options.monthly);
-    assert!(options.tree);
-    assert!(!options.flat);
+    assert_eq!(options.interval, hledger_lib::ReportInterval::Monthly);
+    assert_eq!(options.list_mode, Some(ListMode::Tree));
     assert_eq!(options.depth, Some(3));
     assert!(options.row_total);
     assert!(options.average);
@@ -456,13 +459,13 @@ fn test_get_balancesheet_options_builder() {
 fn test_get_balancesheet_calculation_modes() {
     // Test valuechange mode
     let options = BalanceSheetOptions::new().valuechange();
-    let result = get_balancesheet(Some("tests/fixtures/test.journal"), &options);
+    let result = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options);
     // Should not error (though results may vary)
     assert!(result.is_ok());
 
     // Test gain mode
     let options = BalanceSheetOptions::new().gain();
-    let result = get_balancesheet(Some("tests/fixtures/test.journal"), &options);
+    let result = get_balancesheet(None, Some("tests/fixtures/test.journal"), &options);
     // Should not error (though results may vary)
     assert!(result.is_ok());
 
@@ -477,6 +480,7 @@ fn test_get_balancesheet_calculation_modes() {
 #[test]
 fn test_get_incomestatement_simple() {
     let report = get_incomestatement(
+        None,
         Some("tests/fixtures/test.journal"),
         &IncomeStatementOptions::default(),
     )
@@ -521,7 +525,7 @@ fn test_get_incomestatement_simple() {
 #[test]
 fn test_get_incomestatement_monthly() {
     let options = IncomeStatementOptions::new().monthly();
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get monthly income statement");
 
     // Should have monthly periods
@@ -537,7 +541,7 @@ fn test_get_incomestatement_monthly() {
 #[test]
 fn test_get_incomestatement_tree_mode() {
     let options = IncomeStatementOptions::new().tree().depth(2);
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get tree mode income statement");
 
     // Should still have subreports
@@ -558,7 +562,7 @@ fn test_get_incomestatement_tree_mode() {
 #[test]
 fn test_get_incomestatement_with_query() {
     let options = IncomeStatementOptions::new().query("groceries");
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get filtered income statement");
 
     // Should still have subreports structure
@@ -580,7 +584,7 @@ fn test_get_incomestatement_with_dates() {
         .begin("2024-01-01")
         .end("2024-01-06");
 
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get income statement with date filter");
 
     // Should have subreports
@@ -606,7 +610,7 @@ fn test_get_incomestatement_with_dates() {
 #[test]
 fn test_get_incomestatement_depth_limit() {
     let options = IncomeStatementOptions::new().depth(1);
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get income statement with depth limit");
 
     // With depth 1, should only see top-level accounts
@@ -626,7 +630,7 @@ fn test_get_incomestatement_with_totals() {
         .monthly()
         .row_total()
         .average();
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get income statement with totals");
 
     // Should have subreports
@@ -640,26 +644,9 @@ fn test_get_incomestatement_with_totals() {
             let has_totals = subreport.rows.iter().any(|r| r.total.is_some());
             let has_averages = subreport.rows.iter().any(|r| r.average.is_some());
             // Just verify the structure is preserved
-            assert!(has_totals || !has_totals); // Always true, just checking field exists
-            assert!(has_averages || !has_averages); // Always true, just checking field exists
-        }
-    }
-}
-
-#[test]
-fn test_get_incomestatement_error_nonexistent_file() {
-    let result = get_incomestatement(
-        Some("nonexistent.journal"),
-        &IncomeStatementOptions::default(),
-    );
-
-    // Should return an error for non-existent file
-    assert!(result.is_err());
-    match result {
-        Err(HLedgerError::CommandFailed { code, stderr: _ }) => {
-            assert_ne!(code, 0);
+            // Only checking the fields exist
+            let _ = (has_totals, has_averages);
         }
-        _ => panic!("Expected CommandFailed error"),
     }
 }
 
@@ -677,9 +664,8 @@ fn test_get_incomestatement_options_builder() {
         .change();
 
     // Verify builder pattern works
-    assert!(options.monthly);
-    assert!(options.tree);
-    assert!(!options.flat);
+    assert_eq!(options.interval, hledger_lib::ReportInterval::Monthly);
+    assert_eq!(options.list_mode, Some(ListMode::Tree));
     assert_eq!(options.depth, Some(3));
     assert!(options.row_total);
     assert!(options.average);
@@ -693,13 +679,13 @@ fn test_get_incomestatement_options_builder() {
 fn test_get_incomestatement_calculation_modes() {
     // Test valuechange mode
     let options = IncomeStatementOptions::new().valuechange();
-    let result = get_incomestatement(Some("tests/fixtures/test.journal"), &options);
+    let result = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options);
     // Should not error (though results may vary)
     assert!(result.is_ok());
 
     // Test gain mode
     let options = IncomeStatementOptions::new().gain();
-    let result = get_incomestatement(Some("tests/fixtures/test.journal"), &options);
+    let result = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options);
     // Should not error (though results may vary)
     assert!(result.is_ok());
 
@@ -711,24 +697,24 @@ fn test_get_incomestatement_calculation_modes() {
 fn test_get_incomestatement_accumulation_modes() {
     // Test change mode (default for income statement)
     let options = IncomeStatementOptions::new().change();
-    let result = get_incomestatement(Some("tests/fixtures/test.journal"), &options);
+    let result = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 
     // Test cumulative mode
     let options = IncomeStatementOptions::new().cumulative();
-    let result = get_incomestatement(Some("tests/fixtures/test.journal"), &options);
+    let result = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 
     // Test historical mode
     let options = IncomeStatementOptions::new().historical();
-    let result = get_incomestatement(Some("tests/fixtures/test.journal"), &options);
+    let result = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 }
 
 #[test]
 fn test_get_incomestatement_quarterly() {
     let options = IncomeStatementOptions::new().quarterly();
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get quarterly income statement");
 
     // Should have quarterly periods
@@ -738,14 +724,14 @@ fn test_get_incomestatement_quarterly() {
     // Should have appropriate period range
     if let Some(first_date) = report.dates.first() {
         // Q1 should start on Jan 1
-        assert!(first_date.start.starts_with("2024-01"));
+        assert!(first_date.start.to_string().starts_with("2024-01"));
     }
 }
 
 #[test]
 fn test_get_incomestatement_sort_amount() {
     let options = IncomeStatementOptions::new().sort_amount();
-    let report = get_incomestatement(Some("tests/fixtures/test.journal"), &options)
+    let report = get_incomestatement(None, Some("tests/fixtures/test.journal"), &options)
         .expect("Failed to get income statement sorted by amount");
 
     // Should work without error
@@ -763,6 +749,7 @@ fn test_get_incomestatement_sort_amount() {
 #[test]
 fn test_get_cashflow_simple() {
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         CashflowOptions::default(),
     )
@@ -802,6 +789,7 @@ fn test_get_cashflow_simple() {
 fn test_get_cashflow_monthly() {
     let options = CashflowOptions::new().monthly();
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -821,6 +809,7 @@ fn test_get_cashflow_monthly() {
 fn test_get_cashflow_tree_mode() {
     let options = CashflowOptions::new().tree().depth(2);
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -839,13 +828,14 @@ fn test_get_cashflow_tree_mode() {
         .collect();
 
     // Should have aggregated accounts like "assets"
-    assert!(account_names.iter().any(|&name| name == "assets"));
+    assert!(account_names.contains(&"assets"));
 }
 
 #[test]
 fn test_get_cashflow_with_query() {
     let options = CashflowOptions::new().query("bank");
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -867,6 +857,7 @@ fn test_get_cashflow_with_dates() {
     let options = CashflowOptions::new().begin("2024-01-01").end("2024-01-06");
 
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -894,6 +885,7 @@ fn test_get_cashflow_with_dates() {
 fn test_get_cashflow_depth_limit() {
     let options = CashflowOptions::new().depth(1);
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -911,6 +903,7 @@ fn test_get_cashflow_depth_limit() {
 fn test_get_cashflow_with_totals() {
     let options = CashflowOptions::new().row_total().average();
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -926,25 +919,8 @@ fn test_get_cashflow_with_totals() {
         let has_totals = cashflows.data.rows.iter().any(|r| r.total.is_some());
         let has_averages = cashflows.data.rows.iter().any(|r| r.average.is_some());
         // Verify the structure is preserved
-        assert!(has_totals || !has_totals); // Always true, just checking field exists
-        assert!(has_averages || !has_averages); // Always true, just checking field exists
-    }
-}
-
-#[test]
-fn test_get_cashflow_error_nonexistent_file() {
-    let result = get_cashflow(
-        Some(std::path::Path::new("nonexistent.journal")),
-        CashflowOptions::default(),
-    );
-
-    // Should return an error for non-existent file
-    assert!(result.is_err());
-    match result {
-        Err(HLedgerError::CommandFailed { code, stderr: _ }) => {
-            assert_ne!(code, 0);
-        }
-        _ => panic!("Expected CommandFailed error"),
+        // Only checking the fields exist
+        let _ = (has_totals, has_averages);
     }
 }
 
@@ -962,9 +938,8 @@ fn test_get_cashflow_options_builder() {
         .historical();
 
     // Verify builder pattern works
-    assert!(options.monthly);
-    assert!(options.tree);
-    assert!(!options.flat);
+    assert_eq!(options.interval, hledger_lib::ReportInterval::Monthly);
+    assert_eq!(options.list_mode, Some(ListMode::Tree));
     assert_eq!(options.depth, Some(3));
     assert!(options.row_total);
     assert!(options.average);
@@ -979,6 +954,7 @@ fn test_get_cashflow_calculation_modes() {
     // Test valuechange mode
     let options = CashflowOptions::new().valuechange();
     let result = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     );
@@ -988,6 +964,7 @@ fn test_get_cashflow_calculation_modes() {
     // Test gain mode
     let options = CashflowOptions::new().gain();
     let result = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     );
@@ -997,6 +974,7 @@ fn test_get_cashflow_calculation_modes() {
     // Test budget mode
     let options = CashflowOptions::new().budget();
     let result = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     );
@@ -1009,6 +987,7 @@ fn test_get_cashflow_accumulation_modes() {
     // Test change mode (default)
     let options = CashflowOptions::new();
     let result = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     );
@@ -1017,6 +996,7 @@ fn test_get_cashflow_accumulation_modes() {
     // Test cumulative mode
     let options = CashflowOptions::new().cumulative();
     let result = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     );
@@ -1025,6 +1005,7 @@ fn test_get_cashflow_accumulation_modes() {
     // Test historical mode
     let options = CashflowOptions::new().historical();
     let result = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     );
@@ -1035,6 +1016,7 @@ fn test_get_cashflow_accumulation_modes() {
 fn test_get_cashflow_quarterly() {
     let options = CashflowOptions::new().quarterly();
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -1047,7 +1029,7 @@ fn test_get_cashflow_quarterly() {
     // Should have appropriate period range
     if let Some(first_date) = report.dates.first() {
         // Q1 should start on Jan 1
-        assert!(first_date.start.starts_with("2024-01"));
+        assert!(first_date.start.to_string().starts_with("2024-01"));
     }
 }
 
@@ -1055,6 +1037,7 @@ fn test_get_cashflow_quarterly() {
 fn test_get_cashflow_sort_amount() {
     let options = CashflowOptions::new().sort_amount();
     let report = get_cashflow(
+        None,
         Some(std::path::Path::new("tests/fixtures/test.journal")),
         options,
     )
@@ -1077,7 +1060,7 @@ fn test_get_print_basic() {
     use hledger_lib::{get_print, PrintOptions};
 
     let options = PrintOptions::new();
-    let result = get_print(Some("tests/fixtures/test.journal"), &options);
+    let result = get_print(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 
     let transactions = result.unwrap();
@@ -1085,7 +1068,7 @@ fn test_get_print_basic() {
 
     // Check first transaction
     let first = &transactions[0];
-    assert_eq!(first.date, "2024-01-01");
+    assert_eq!(first.date.to_string(), "2024-01-01");
     assert_eq!(first.description, "income");
     assert_eq!(first.status, "Unmarked");
     assert_eq!(first.postings.len(), 2);
@@ -1097,7 +1080,7 @@ fn test_get_print_with_date_filter() {
 
     let options = PrintOptions::new().begin("2024-01-01").end("2024-01-06");
 
-    let result = get_print(Some("tests/fixtures/test.journal"), &options);
+    let result = get_print(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 
     let transactions = result.unwrap();
@@ -1110,7 +1093,7 @@ fn test_get_print_with_query() {
 
     let options = PrintOptions::new().query("expenses");
 
-    let result = get_print(Some("tests/fixtures/test.journal"), &options);
+    let result = get_print(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 
     let transactions = result.unwrap();
@@ -1131,7 +1114,7 @@ fn test_get_print_transaction_details() {
     use hledger_lib::{get_print, PrintOptions};
 
     let options = PrintOptions::new();
-    let result = get_print(Some("tests/fixtures/test.journal"), &options);
+    let result = get_print(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 
     let transactions = result.unwrap();
@@ -1158,12 +1141,68 @@ fn test_get_print_transaction_details() {
     assert_eq!(price.quantity.to_string(), "150.00");
 }
 
+#[test]
+fn test_get_balance_tree_monthly_layout() {
+    use hledger_lib::{get_balance, BalanceOptions, BalanceReport};
+
+    let options = BalanceOptions::new().monthly().tree();
+    let report = get_balance(None, Some("tests/fixtures/test.journal"), &options)
+        .expect("Failed to get tree-mode monthly balance");
+
+    let BalanceReport::Periodic(balance) = report else {
+        panic!("Expected a periodic balance report");
+    };
+
+    let row = |account: &str| {
+        balance
+            .rows
+            .iter()
+            .find(|r| r.account == account)
+            .unwrap_or_else(|| panic!("Missing row for {}", account))
+    };
+
+    assert_eq!(row("assets").indent, 0);
+    assert_eq!(row("assets").display_name, "assets");
+    assert_eq!(row("expenses:groceries").indent, 1);
+    assert_eq!(row("expenses:groceries").display_name, "groceries");
+    assert_eq!(
+        row("expenses:fees:brokerage").display_name,
+        "fees:brokerage"
+    );
+}
+
+#[test]
+fn test_get_print_unit_and_total_prices() {
+    use hledger_lib::{get_print, PriceKind, PrintOptions};
+
+    let options = PrintOptions::new();
+    let transactions = get_print(None, Some("tests/fixtures/prices.journal"), &options)
+        .expect("Failed to get print report");
+    assert_eq!(transactions.len(), 2);
+
+    let price_of = |txn: &hledger_lib::PrintTransaction| {
+        txn.postings
+            .iter()
+            .find(|p| p.account == "assets:investments:aapl")
+            .and_then(|p| p.amounts[0].price.clone())
+            .expect("AAPL posting should carry a price")
+    };
+
+    let unit = price_of(&transactions[0]);
+    assert_eq!(unit.kind, PriceKind::Unit);
+    assert_eq!(unit.quantity.to_string(), "150.00");
+
+    let total = price_of(&transactions[1]);
+    assert_eq!(total.kind, PriceKind::Total);
+    assert_eq!(total.quantity.to_string(), "300.00");
+}
+
 #[test]
 fn test_get_print_posting_types() {
     use hledger_lib::{get_print, PrintOptions};
 
     let options = PrintOptions::new();
-    let result = get_print(Some("tests/fixtures/test.journal"), &options);
+    let result = get_print(None, Some("tests/fixtures/test.journal"), &options);
     assert!(result.is_ok());
 
     let transactions = result.unwrap();
@@ -1187,7 +1226,7 @@ fn test_get_print_empty_journal() {
     writeln!(file, "; Empty journal").unwrap();
 
     let options = PrintOptions::new();
-    let result = get_print(Some(temp_file), &options);
+    let result = get_print(None, Some(temp_file), &options);
     assert!(result.is_ok());
 
     let transactions = result.unwrap();
@@ -1197,18 +1236,9 @@ fn test_get_print_empty_journal() {
     fs::remove_file(temp_file).ok();
 }
 
-#[test]
-fn test_get_print_error_nonexistent_file() {
-    use hledger_lib::{get_print, PrintOptions};
-
-    let options = PrintOptions::new();
-    let result = get_print(Some("nonexistent.journal"), &options);
-    assert!(result.is_err());
-}
-
 #[test]
 fn test_get_print_options_builder() {
-    use hledger_lib::{get_print, PrintOptions};
+    use hledger_lib::PrintOptions;
 
     let options = PrintOptions::new()
         .explicit()
@@ -1227,3 +1257,1087 @@ fn test_get_print_options_builder() {
     assert_eq!(options.end, Some("2024-12-31".to_string()));
     assert_eq!(options.queries, vec!["assets"]);
 }
+
+#[test]
+fn test_get_print_unbalanced_journal_error() {
+    use hledger_lib::{get_print, PrintOptions};
+
+    let result = get_print(
+        None,
+        Some("tests/fixtures/unbalanced.journal"),
+        &PrintOptions::new(),
+    );
+    match result {
+        Err(HLedgerError::JournalError {
+            file,
+            line,
+            message,
+            ..
+        }) => {
+            assert!(file.ends_with("unbalanced.journal"));
+            assert_eq!(line, 1);
+            assert!(message.contains("unbalanced"));
+        }
+        other => panic!("Expected JournalError, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_print_bad_date_journal_error() {
+    use hledger_lib::{get_print, PrintOptions};
+
+    let result = get_print(
+        None,
+        Some("tests/fixtures/bad_date.journal"),
+        &PrintOptions::new(),
+    );
+    match result {
+        Err(HLedgerError::JournalError { file, line, .. }) => {
+            assert!(file.ends_with("bad_date.journal"));
+            assert_eq!(line, 1);
+        }
+        other => panic!("Expected JournalError, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_accounts_dash_leading_query_is_not_a_flag() {
+    // Without a `--` separator hledger would print its version or reject an
+    // unknown flag; as queries these simply match no accounts
+    for query in ["--version", "-salary"] {
+        let options = AccountsOptions::new().query(query);
+        let accounts = get_accounts(None, Some("tests/fixtures/test.journal"), &options)
+            .unwrap_or_else(|e| panic!("query {:?} failed: {}", query, e));
+        assert!(
+            accounts.is_empty(),
+            "query {:?} matched {:?}",
+            query,
+            accounts
+        );
+    }
+}
+
+#[test]
+fn test_get_print_dash_leading_query_is_not_a_flag() {
+    use hledger_lib::{get_print, PrintOptions};
+
+    let options = PrintOptions::new().query("-x");
+    let transactions = get_print(None, Some("tests/fixtures/test.journal"), &options).unwrap();
+    assert!(transactions.is_empty());
+}
+
+#[test]
+fn test_get_accounts_rejects_invalid_dates() {
+    let options = AccountsOptions::new().begin("2024-13-01");
+    match get_accounts(None, Some("tests/fixtures/test.journal"), &options) {
+        Err(HLedgerError::InvalidDate { input, .. }) => assert_eq!(input, "2024-13-01"),
+        other => panic!("Expected InvalidDate error, got {:?}", other),
+    }
+
+    let options = AccountsOptions::new().end("01/02/2024");
+    assert!(matches!(
+        get_accounts(None, Some("tests/fixtures/test.journal"), &options),
+        Err(HLedgerError::InvalidDate { .. })
+    ));
+}
+
+#[test]
+fn test_get_accounts_unchecked_dates_reach_hledger() {
+    // The validator would reject this; with the escape hatch it's left to hledger
+    let options = AccountsOptions::new()
+        .begin("2024-01-01T00:00")
+        .unchecked_dates();
+    assert!(!matches!(
+        get_accounts(None, Some("tests/fixtures/test.journal"), &options),
+        Err(HLedgerError::InvalidDate { .. })
+    ));
+}
+
+const EMPTY_JOURNAL: &str = "tests/fixtures/empty.journal";
+
+#[test]
+fn test_empty_journal_accounts() {
+    let accounts = get_accounts(None, Some(EMPTY_JOURNAL), &AccountsOptions::new()).unwrap();
+    assert!(accounts.is_empty());
+}
+
+#[test]
+fn test_empty_journal_balance() {
+    use hledger_lib::{get_balance, BalanceOptions, BalanceReport};
+
+    match get_balance(None, Some(EMPTY_JOURNAL), &BalanceOptions::new()).unwrap() {
+        BalanceReport::Simple(report) => {
+            assert!(report.accounts.is_empty());
+            assert!(report.totals.is_empty());
+        }
+        other => panic!("Expected simple report, got {:?}", other),
+    }
+
+    match get_balance(None, Some(EMPTY_JOURNAL), &BalanceOptions::new().monthly()).unwrap() {
+        BalanceReport::Periodic(report) => {
+            assert!(report.dates.is_empty());
+            assert!(report.rows.is_empty());
+        }
+        other => panic!("Expected periodic report, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_empty_journal_balancesheet() {
+    let report = get_balancesheet(None, Some(EMPTY_JOURNAL), &BalanceSheetOptions::new()).unwrap();
+    assert!(report
+        .subreports
+        .iter()
+        .all(|subreport| subreport.rows.is_empty()));
+}
+
+#[test]
+fn test_empty_journal_incomestatement() {
+    let report =
+        get_incomestatement(None, Some(EMPTY_JOURNAL), &IncomeStatementOptions::new()).unwrap();
+    assert!(report
+        .subreports
+        .iter()
+        .all(|subreport| subreport.rows.is_empty()));
+}
+
+#[test]
+fn test_empty_journal_cashflow() {
+    let report = get_cashflow(
+        None,
+        Some(std::path::Path::new(EMPTY_JOURNAL)),
+        CashflowOptions::new(),
+    )
+    .unwrap();
+    assert!(report
+        .subreports
+        .iter()
+        .all(|subreport| subreport.data.rows.is_empty()));
+}
+
+#[test]
+fn test_empty_journal_print() {
+    use hledger_lib::{get_print, PrintOptions};
+
+    let transactions = get_print(None, Some(EMPTY_JOURNAL), &PrintOptions::new()).unwrap();
+    assert!(transactions.is_empty());
+}
+
+#[test]
+fn test_get_balance_raw_matches_typed_command() {
+    use hledger_lib::{get_balance_raw, get_print_raw, BalanceOptions, PrintOptions};
+
+    let raw = get_balance_raw(
+        None,
+        Some("tests/fixtures/test.journal"),
+        &BalanceOptions::new().monthly(),
+    )
+    .unwrap();
+    assert!(raw.get("prDates").is_some());
+    assert!(raw.get("prRows").is_some());
+
+    let raw = get_print_raw(
+        None,
+        Some("tests/fixtures/test.journal"),
+        &PrintOptions::new(),
+    )
+    .unwrap();
+    assert!(raw.as_array().is_some_and(|txns| !txns.is_empty()));
+}
+
+#[test]
+fn test_get_print_source_spans() {
+    use hledger_lib::{get_print, PrintOptions};
+
+    let transactions = get_print(
+        None,
+        Some("tests/fixtures/spans.journal"),
+        &PrintOptions::new(),
+    )
+    .unwrap();
+    assert_eq!(transactions.len(), 2);
+
+    // Spans end at the start of the line after the last posting
+    let span = transactions[0].source_span.as_ref().unwrap();
+    assert!(span.file.ends_with("spans.journal"));
+    assert_eq!((span.start_line, span.start_column), (3, 1));
+    assert_eq!((span.end_line, span.end_column), (8, 1));
+
+    let span = transactions[1].source_span.as_ref().unwrap();
+    assert_eq!(span.start_line, 10);
+    assert_eq!(span.end_line, 13);
+
+    // The raw positions are still exposed
+    assert_eq!(transactions[0].source_positions.len(), 2);
+}
+
+#[test]
+fn test_get_networth_series() {
+    use hledger_lib::{get_networth_series, NetworthOptions};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let points = get_networth_series(
+        None,
+        Some("tests/fixtures/test.journal"),
+        &NetworthOptions::new(),
+    )
+    .unwrap();
+    assert_eq!(points.len(), 1);
+
+    let point = &points[0];
+    assert_eq!(point.date.to_string(), "2024-01-31");
+    // $80 in checking, $-300.50 brokerage cash and 2 GOOG
+    assert_eq!(point.assets.get("$"), Decimal::from_str("-220.50").unwrap());
+    assert_eq!(point.assets.get("GOOG"), Decimal::from(2));
+    assert!(point.liabilities.is_zero());
+    assert_eq!(point.net_worth, point.assets);
+}
+
+#[test]
+fn test_get_budget_report() {
+    use hledger_lib::{get_budget_report, BudgetOptions};
+    use rust_decimal::Decimal;
+
+    let report = get_budget_report(
+        None,
+        Some("tests/fixtures/budget.journal"),
+        &BudgetOptions::new().monthly(),
+    )
+    .unwrap();
+    assert_eq!(report.dates.len(), 2);
+
+    let food = report
+        .rows
+        .iter()
+        .find(|row| row.account == "expenses:food")
+        .expect("expenses:food should have a budget row");
+    let january = &food.cells[0];
+    assert_eq!(january.actual.get("$"), Decimal::from(450));
+    assert_eq!(january.goal.as_ref().unwrap().get("$"), Decimal::from(500));
+    assert_eq!(
+        january.remaining.as_ref().unwrap().get("$"),
+        Decimal::from(50)
+    );
+    assert_eq!(january.percent, Some(Decimal::from(90)));
+
+    // February is over budget
+    let february = &food.cells[1];
+    assert_eq!(
+        february.remaining.as_ref().unwrap().get("$"),
+        Decimal::from(-120)
+    );
+    assert_eq!(february.percent, Some(Decimal::from(124)));
+
+    // Rent was paid in full in January only
+    let rent = report
+        .rows
+        .iter()
+        .find(|row| row.account == "expenses:rent")
+        .unwrap();
+    assert_eq!(rent.cells[0].percent, Some(Decimal::from(100)));
+    assert_eq!(rent.cells[1].percent, Some(Decimal::ZERO));
+}
+
+#[test]
+fn test_transaction_builder_round_trip() {
+    use chrono::NaiveDate;
+    use hledger_lib::{
+        get_print, JournalStyle, PostingAssertion, PostingBuilder, Price, PriceKind, PrintOptions,
+        Status, TransactionBuilder,
+    };
+    use rust_decimal::Decimal;
+
+    let dec = |s: &str| s.parse::<Decimal>().unwrap();
+    let txn = TransactionBuilder::new(
+        NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+        "Broker | buy shares",
+    )
+    .status(Status::Cleared)
+    .code("T-7")
+    .comment("quarterly rebalance")
+    .tag("trip", "none")
+    .posting(
+        PostingBuilder::new("assets:investments:goog")
+            .amount(dec("2"), "GOOG")
+            .price(Price {
+                kind: PriceKind::Unit,
+                commodity: "$".to_string(),
+                quantity: dec("150"),
+            }),
+    )
+    .posting(
+        PostingBuilder::new("assets:bank:checking")
+            .status(Status::Pending)
+            .amount(dec("-300"), "$")
+            .assertion(PostingAssertion {
+                commodity: "$".to_string(),
+                quantity: dec("-300"),
+                total: false,
+                inclusive: false,
+            })
+            .tag("account", "joint"),
+    );
+
+    let text = txn.to_journal_string(&JournalStyle::default()).unwrap();
+    let path = std::env::temp_dir().join(format!("builder-{}.journal", std::process::id()));
+    std::fs::write(&path, &text).unwrap();
+    let parsed = get_print(None, Some(path.to_str().unwrap()), &PrintOptions::new());
+    std::fs::remove_file(&path).unwrap();
+    let parsed = parsed.unwrap();
+
+    assert_eq!(parsed.len(), 1, "rendered:\n{}", text);
+    let parsed = &parsed[0];
+    assert_eq!(parsed.date, txn.date);
+    assert_eq!(parsed.status, "Cleared");
+    assert_eq!(parsed.code, "T-7");
+    assert_eq!(parsed.description, txn.description);
+    assert!(parsed.comment.contains("quarterly rebalance"));
+    assert!(parsed
+        .tags
+        .contains(&("trip".to_string(), "none".to_string())));
+
+    assert_eq!(parsed.postings.len(), 2);
+    let shares = &parsed.postings[0];
+    assert_eq!(shares.account, "assets:investments:goog");
+    assert_eq!(shares.amounts[0].commodity, "GOOG");
+    assert_eq!(shares.amounts[0].quantity, dec("2"));
+    assert_eq!(
+        shares.amounts[0].price.as_ref().unwrap().quantity,
+        dec("150")
+    );
+
+    let bank = &parsed.postings[1];
+    assert_eq!(bank.status, "Pending");
+    assert_eq!(bank.amounts[0].quantity, dec("-300"));
+    assert_eq!(
+        bank.balance_assertion.as_ref().unwrap().amount.quantity,
+        dec("-300")
+    );
+    assert!(bank
+        .tags
+        .contains(&("account".to_string(), "joint".to_string())));
+}
+
+#[test]
+fn test_append_transaction_rolls_back_when_check_fails() {
+    use chrono::NaiveDate;
+    use hledger_lib::{append_transaction, PostingBuilder, TransactionBuilder, WriteOptions};
+
+    let original = "2024-01-01 opening\n    assets:cash  $100\n    equity:opening\n";
+    let path = std::env::temp_dir().join(format!("append-{}.journal", std::process::id()));
+    std::fs::write(&path, original).unwrap();
+
+    // Both amounts are explicit and don't sum to zero
+    let unbalanced = TransactionBuilder::new(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap(), "oops")
+        .posting(PostingBuilder::new("expenses:food").amount("20".parse().unwrap(), "$"))
+        .posting(PostingBuilder::new("assets:cash").amount("-15".parse().unwrap(), "$"));
+    let result = append_transaction(&path, &unbalanced, &WriteOptions::new().validate());
+    let after_failure = std::fs::read_to_string(&path).unwrap();
+
+    let balanced = TransactionBuilder::new(NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(), "lunch")
+        .posting(PostingBuilder::new("expenses:food").amount("12".parse().unwrap(), "$"))
+        .posting(PostingBuilder::new("assets:cash"));
+    let receipt = append_transaction(&path, &balanced, &WriteOptions::new().validate());
+    let after_success = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(result, Err(HLedgerError::JournalError { .. })));
+    assert_eq!(after_failure, original);
+
+    let receipt = receipt.unwrap();
+    assert_eq!(receipt.start_line, 5);
+    assert!(after_success.ends_with("    assets:cash\n"));
+}
+
+#[test]
+fn test_replace_transaction_in_included_file() {
+    use hledger_lib::{
+        file_fingerprint, get_print, replace_transaction, PrintOptions, Replacement, WriteOptions,
+    };
+
+    let dir = std::env::temp_dir().join(format!("replace-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("main.journal");
+    let included = dir.join("spans.journal");
+    std::fs::write(&main, "; main file\ninclude spans.journal\n").unwrap();
+    std::fs::copy("tests/fixtures/spans.journal", &included).unwrap();
+    let original = std::fs::read_to_string(&included).unwrap();
+    let main_str = main.to_string_lossy().into_owned();
+
+    let report = get_print(None, Some(&main_str), &PrintOptions::default()).unwrap();
+    let span = report[1].source_span.clone().unwrap();
+    let options = WriteOptions::new().expect_unchanged(file_fingerprint(&included).unwrap());
+
+    let edit = "2024-02-01 * Landlord\n    expenses:rent                $1250.00\n    assets:bank:checking\n";
+    let result = replace_transaction(&main, &span, Replacement::Text(edit), &options);
+    let edited = std::fs::read_to_string(&included).unwrap();
+    let backup = std::fs::read_to_string(dir.join("spans.journal.bak"));
+
+    // Splitting one transaction into two changes the count and is rolled back
+    let options = WriteOptions::new().expect_unchanged(file_fingerprint(&included).unwrap());
+    let split = format!("{}\n{}", edit, edit.replace("02-01", "02-02"));
+    let rejected = replace_transaction(&main, &span, Replacement::Text(&split), &options);
+    let after_rejected = std::fs::read_to_string(&included).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    result.unwrap();
+    assert_eq!(edited, original.replace("$1200.00", "$1250.00"));
+    assert_eq!(backup.unwrap(), original);
+    assert!(matches!(rejected, Err(HLedgerError::InvalidTransaction(_))));
+    assert_eq!(after_rejected, edited);
+}
+
+#[test]
+fn test_verify_assertions_reports_failure() {
+    use hledger_lib::verify_assertions;
+    use rust_decimal::Decimal;
+
+    let failures = verify_assertions(None, Some("tests/fixtures/assertions.journal"))
+        .expect("Failed to verify assertions");
+    assert_eq!(failures.len(), 1);
+
+    let failure = &failures[0];
+    assert!(failure.file.ends_with("assertions.journal"));
+    assert_eq!(failure.line, 12);
+    assert_eq!(failure.account, "assets:bank:checking");
+    assert_eq!(failure.expected.commodity, "$");
+    assert_eq!(failure.expected.quantity, Decimal::new(800, 0));
+    assert_eq!(failure.actual.quantity, Decimal::new(750, 0));
+    assert_eq!(failure.diff.quantity, Decimal::new(50, 0));
+
+    let failures = verify_assertions(None, Some("tests/fixtures/test.journal"))
+        .expect("Failed to verify assertions");
+    assert!(failures.is_empty());
+}
+
+#[test]
+fn test_assertion_failure_surfaced() {
+    use hledger_lib::{
+        get_balance, get_journal_problems, get_print, AppError, BalanceOptions, CheckIssueKind,
+        ErrorCode, PrintOptions,
+    };
+    use rust_decimal::Decimal;
+
+    let journal = Some("tests/fixtures/assertions.journal");
+
+    // Reports fail with a code the app can offer to ignore assertions for
+    let err = get_balance(None, journal, &BalanceOptions::new()).unwrap_err();
+    let err = AppError::from(err);
+    assert_eq!(err.code, ErrorCode::AssertionFailed);
+    assert_eq!(err.details.unwrap().line, Some(12));
+
+    let transactions = get_print(None, journal, &PrintOptions::new().ignore_assertions())
+        .expect("Failed to print ignoring assertions");
+    assert_eq!(transactions.len(), 3);
+
+    let problems = get_journal_problems(None, journal).expect("Failed to get problems");
+    assert_eq!(problems.len(), 1);
+    let problem = &problems[0];
+    assert_eq!(problem.kind, CheckIssueKind::CheckFailed);
+    assert!(problem
+        .file
+        .as_deref()
+        .unwrap()
+        .ends_with("assertions.journal"));
+    assert_eq!(problem.line, Some(12));
+    assert_eq!(
+        problem.expected.as_ref().unwrap().quantity,
+        Decimal::new(800, 0)
+    );
+    assert_eq!(
+        problem.actual.as_ref().unwrap().quantity,
+        Decimal::new(750, 0)
+    );
+
+    let problems = get_journal_problems(None, Some("tests/fixtures/test.journal"))
+        .expect("Failed to get problems");
+    assert!(problems.is_empty());
+}
+
+#[test]
+fn test_conversion_table_from_inferred_prices() {
+    use hledger_lib::{ConversionTable, PricesOptions};
+    use rust_decimal::Decimal;
+
+    let prices = hledger_lib::get_prices(
+        None,
+        Some("tests/fixtures/prices.journal"),
+        &PricesOptions::new().infer_market_prices(),
+    )
+    .expect("Failed to get prices");
+    assert!(prices
+        .iter()
+        .any(|p| p.commodity == "AAPL" && p.price.commodity == "$"));
+
+    let table = ConversionTable::load(None, Some("tests/fixtures/prices.journal"), None)
+        .expect("Failed to load conversion table");
+    let shares = hledger_lib::commands::balance::Amount {
+        commodity: "AAPL".to_string(),
+        quantity: Decimal::new(2, 0),
+        price: None,
+    };
+    let dollars = table.convert(&shares, "$").unwrap();
+    assert_eq!(dollars.quantity, Decimal::new(300, 0));
+    assert!(table.convert(&shares, "EUR").is_none());
+}
+
+#[test]
+fn test_get_accounts_detailed_types_and_positions() {
+    use hledger_lib::{get_accounts_detailed, AccountType};
+
+    let options = AccountsOptions::new().types().positions();
+    let accounts = get_accounts_detailed(None, Some("tests/fixtures/accounts.journal"), &options)
+        .expect("Failed to get detailed accounts");
+    let find = |name: &str| {
+        accounts
+            .iter()
+            .find(|a| a.name == name)
+            .unwrap_or_else(|| panic!("missing account {}", name))
+    };
+
+    let checking = find("assets:bank:checking");
+    assert_eq!(checking.account_type, Some(AccountType::Cash));
+    let declared = checking.declared_at.as_ref().unwrap();
+    assert!(declared.file.ends_with("accounts.journal"));
+    assert_eq!(declared.line, 4);
+
+    assert_eq!(find("expenses").account_type, Some(AccountType::Expense));
+    // Undeclared, with its type inferred from the declared parent
+    let card = find("liabilities:credit card");
+    assert_eq!(card.account_type, Some(AccountType::Liability));
+    assert!(card.declared_at.is_none());
+
+    let accounts = get_accounts_detailed(
+        None,
+        Some("tests/fixtures/accounts.journal"),
+        &AccountsOptions::new().types(),
+    )
+    .expect("Failed to get account types");
+    assert!(accounts.iter().all(|a| a.declared_at.is_none()));
+    assert!(accounts.iter().any(|a| a.name == "income:salary"));
+}
+
+#[test]
+fn test_get_account_tree() {
+    use hledger_lib::get_account_tree;
+
+    let tree = get_account_tree(
+        None,
+        Some("tests/fixtures/test.journal"),
+        &AccountsOptions::new(),
+    )
+    .expect("Failed to get account tree");
+
+    let assets = tree.iter().find(|node| node.name == "assets").unwrap();
+    assert_eq!(assets.full_name, "assets");
+    // Only leaf accounts are used in the journal; the parents are synthesized
+    let investments = assets
+        .children
+        .iter()
+        .find(|node| node.name == "investments")
+        .unwrap();
+    assert_eq!(investments.full_name, "assets:investments");
+    assert_eq!(
+        investments.children[0].full_name,
+        "assets:investments:fidelity"
+    );
+}
+
+#[test]
+fn test_account_balance_tree_matches_accounts_and_balance() {
+    use hledger_lib::{get_account_balance_tree, get_accounts_with_balances, BalanceOptions};
+    use rust_decimal::Decimal;
+
+    let journal = Some("tests/fixtures/test.journal");
+    let accounts = get_accounts_with_balances(None, journal, &BalanceOptions::new().monthly())
+        .expect("Failed to get accounts with balances");
+    let names: Vec<String> = accounts.iter().map(|a| a.name.clone()).collect();
+    assert_eq!(
+        names,
+        get_accounts(None, journal, &AccountsOptions::new()).unwrap()
+    );
+
+    let tree = get_account_balance_tree(None, journal, &BalanceOptions::new())
+        .expect("Failed to get account balance tree");
+    let assets = tree.iter().find(|node| node.name == "assets").unwrap();
+    let checking = assets.children[0]
+        .children
+        .iter()
+        .find(|node| node.name == "checking")
+        .unwrap();
+    assert_eq!(checking.balance.get("$"), Decimal::from(80));
+    assert_eq!(assets.total.get("GOOG"), Decimal::from(2));
+}
+
+#[test]
+fn test_running_balances_match_register() {
+    use hledger_lib::{compute_running_balances, get_print, PrintOptions};
+    use rust_decimal::Decimal;
+
+    let journal = "tests/fixtures/test.journal";
+    let transactions = get_print(None, Some(journal), &PrintOptions::new().explicit())
+        .expect("Failed to get print report");
+    let entries = compute_running_balances(&transactions, "assets:bank:checking");
+
+    let output = hledger_lib::get_hledger_command(None)
+        .args([
+            "-f",
+            journal,
+            "register",
+            "assets:bank:checking",
+            "-O",
+            "csv",
+        ])
+        .output()
+        .expect("Failed to run hledger register");
+    let csv = String::from_utf8(output.stdout).unwrap();
+    // "txnidx","date","code","description","account","amount","total"
+    let totals: Vec<Decimal> = csv
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let total = line.rsplit(',').next().unwrap().trim_matches('"');
+            total.replace('$', "").parse().unwrap()
+        })
+        .collect();
+
+    assert!(!entries.is_empty());
+    assert_eq!(entries.len(), totals.len());
+    for (entry, total) in entries.iter().zip(&totals) {
+        assert_eq!(entry.balance.get("$"), *total);
+    }
+}
+
+#[test]
+fn test_top_spending_accounts() {
+    use hledger_lib::{top_spending_accounts, TopSpendingOptions};
+    use rust_decimal::Decimal;
+
+    let journal = Some("tests/fixtures/budget.journal");
+    let options = TopSpendingOptions::new().period("2024-01");
+    let totals =
+        top_spending_accounts(None, journal, 1, &options).expect("Failed to rank spending");
+
+    let ranked: Vec<(&str, Decimal, bool)> = totals
+        .iter()
+        .map(|t| (t.account.as_str(), t.amount.quantity, t.is_other))
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![
+            ("expenses:rent", Decimal::from(1200), false),
+            ("other", Decimal::from(480), true),
+        ]
+    );
+    assert!(totals.iter().all(|t| t.amount.commodity == "$"));
+}
+
+#[test]
+fn test_get_flow_data() {
+    use hledger_lib::{get_flow_data, FlowNodeKind, FlowOptions};
+    use rust_decimal::Decimal;
+
+    let graph = get_flow_data(
+        None,
+        Some("tests/fixtures/test.journal"),
+        &FlowOptions::new(),
+    )
+    .expect("Failed to get flow data");
+
+    let flow = |source: &str, target: &str| {
+        graph
+            .links
+            .iter()
+            .find(|l| l.source == source && l.target == target)
+            .map(|l| l.amount.quantity)
+    };
+    assert_eq!(flow("income:salary", "budget"), Some(Decimal::from(100)));
+    assert_eq!(
+        flow("budget", "expenses:groceries"),
+        Some(Decimal::from(20))
+    );
+    assert_eq!(
+        flow("budget", "expenses:fees"),
+        Some("0.50".parse().unwrap())
+    );
+    assert_eq!(flow("budget", "savings"), Some("79.50".parse().unwrap()));
+
+    assert!(graph
+        .links
+        .iter()
+        .all(|l| l.amount.quantity > Decimal::ZERO));
+    assert!(graph
+        .nodes
+        .iter()
+        .any(|n| n.id == "budget" && n.kind == FlowNodeKind::Budget));
+}
+
+#[test]
+fn test_balance_csv_matches_json() {
+    use hledger_lib::{get_balance, get_balance_csv, BalanceOptions, ReportInterval};
+
+    let journal = Some("tests/fixtures/test.journal");
+    for options in [
+        BalanceOptions::new(),
+        BalanceOptions::new().interval(ReportInterval::Monthly),
+    ] {
+        let json = get_balance(None, journal, &options).expect("Failed to get balance");
+        let csv = get_balance_csv(None, journal, &options).expect("Failed to get balance CSV");
+
+        let exported = json.to_csv_report();
+        assert_eq!(csv.rows.len(), exported.rows.len());
+        assert_eq!(csv.headers.len(), exported.headers.len());
+        assert_eq!(csv.rows.last().unwrap()[0], "total");
+    }
+}
+
+#[test]
+fn test_compound_reports_csv_match_json() {
+    use hledger_lib::{get_balancesheet_csv, get_cashflow_csv, get_incomestatement_csv};
+    use std::path::Path;
+
+    let file = "tests/fixtures/test.journal";
+
+    let json = get_balancesheet(None, Some(file), &BalanceSheetOptions::new())
+        .expect("Failed to get balance sheet");
+    let csv = get_balancesheet_csv(None, Some(file), &BalanceSheetOptions::new())
+        .expect("Failed to get balance sheet CSV");
+    assert_eq!(csv.rows.len(), json.to_csv_report().rows.len());
+
+    let json = get_incomestatement(None, Some(file), &IncomeStatementOptions::new())
+        .expect("Failed to get income statement");
+    let csv = get_incomestatement_csv(None, Some(file), &IncomeStatementOptions::new())
+        .expect("Failed to get income statement CSV");
+    assert_eq!(csv.rows.len(), json.to_csv_report().rows.len());
+
+    let json = get_cashflow(None, Some(Path::new(file)), CashflowOptions::new())
+        .expect("Failed to get cashflow");
+    let csv = get_cashflow_csv(None, Some(Path::new(file)), CashflowOptions::new())
+        .expect("Failed to get cashflow CSV");
+    assert_eq!(csv.rows.len(), json.to_csv_report().rows.len());
+}
+
+#[test]
+fn test_print_csv_has_a_row_per_posting() {
+    use hledger_lib::{get_print, get_print_csv, PrintOptions};
+
+    let journal = Some("tests/fixtures/test.journal");
+    let options = PrintOptions::new().explicit();
+    let transactions = get_print(None, journal, &options).expect("Failed to get print");
+    let csv = get_print_csv(None, journal, &options).expect("Failed to get print CSV");
+
+    let postings: usize = transactions.iter().map(|t| t.postings.len()).sum();
+    assert_eq!(csv.rows.len(), postings);
+    assert!(csv.headers.iter().any(|h| h == "txnidx"));
+}
+
+#[test]
+fn test_reports_from_text() {
+    use hledger_lib::{get_accounts_from_text, get_print_from_text, PrintOptions};
+
+    let text = "2024-03-01 Bookshop\n    expenses:books  $12\n    assets:cash\n";
+
+    let accounts = get_accounts_from_text(None, text, &AccountsOptions::new())
+        .expect("Failed to get accounts from text");
+    assert_eq!(accounts, vec!["assets:cash", "expenses:books"]);
+
+    let transactions =
+        get_print_from_text(None, text, &PrintOptions::new()).expect("Failed to print from text");
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].description, "Bookshop");
+}
+
+#[test]
+fn test_overlay_journal_adds_pending_transactions() {
+    use hledger_lib::{get_balance_from_text, overlay_journal, BalanceOptions, BalanceReport};
+
+    let file = "tests/fixtures/test.journal";
+    let pending = "2024-03-01 Bookshop\n    expenses:books  $12\n    assets:bank:checking\n";
+    let text = overlay_journal(file, pending).expect("Failed to build overlay");
+
+    let report = get_balance_from_text(None, &text, &BalanceOptions::new())
+        .expect("Failed to get balance from text");
+    let BalanceReport::Simple(simple) = report else {
+        panic!("Expected a simple balance report");
+    };
+    let quantity = |name: &str| {
+        simple
+            .accounts
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.amounts[0].quantity)
+    };
+    // Only in the pending text
+    assert_eq!(quantity("expenses:books"), Some("12".parse().unwrap()));
+    // On disk, less the pending purchase
+    assert_eq!(
+        quantity("assets:bank:checking"),
+        Some("68".parse().unwrap())
+    );
+
+    // Unsaved text isn't visible to the on-disk report
+    let accounts = get_accounts(None, Some(file), &AccountsOptions::new()).unwrap();
+    assert!(!accounts.iter().any(|a| a == "expenses:books"));
+}
+
+#[test]
+fn test_preview_import_with_generated_rules() {
+    use hledger_lib::{preview_import, CsvCondition, CsvRules, ImportOptions};
+
+    let rules = CsvRules::new()
+        .skip(1)
+        .separator(";")
+        .fields(["date", "description", "", "credit", "debit"])
+        .date_format("%d/%m/%Y")
+        .decimal_mark(',')
+        .newest_first()
+        .currency("EUR")
+        .account1("assets:bank:checking")
+        .account2("expenses:unknown")
+        .amount_in("%credit")
+        .amount_out("%debit")
+        .condition(CsvCondition::new("SUPERMARKT").account2("expenses:food"))
+        .condition(CsvCondition::new("SALARY").account2("income:salary"));
+
+    let transactions = preview_import(
+        None,
+        Some("tests/fixtures/empty.journal"),
+        &["tests/fixtures/bank.csv"],
+        &ImportOptions::new().rules(rules),
+    )
+    .expect("Failed to preview import");
+
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].description, "SALARY ACME");
+    assert_eq!(transactions[0].postings[1].account, "income:salary");
+    assert_eq!(transactions[1].description, "SUPERMARKT CENTRUM");
+    assert_eq!(transactions[1].postings[1].account, "expenses:food");
+
+    // A dry run leaves no .latest file behind
+    assert!(!std::path::Path::new("tests/fixtures/.latest.bank.csv").exists());
+}
+
+#[test]
+fn test_get_print_paged_matches_full_report() {
+    use hledger_lib::{get_print, get_print_paged, PageSpec, PrintOptions};
+
+    let journal = Some("tests/fixtures/test.journal");
+    let options = PrintOptions::new();
+    let all = get_print(None, journal, &options).expect("Failed to get print report");
+    let indices = |transactions: &[hledger_lib::PrintTransaction]| {
+        transactions.iter().map(|t| t.index).collect::<Vec<_>>()
+    };
+
+    let page = get_print_paged(None, journal, &options, PageSpec::new(1, 1))
+        .expect("Failed to get print page");
+    assert_eq!(page.total, all.len());
+    assert_eq!(indices(&page.transactions), indices(&all[1..2]));
+
+    let newest = get_print_paged(None, journal, &options, PageSpec::new(0, 2).newest_first())
+        .expect("Failed to get print page");
+    assert_eq!(newest.total, all.len());
+    let mut expected = indices(&all[all.len() - 2..]);
+    expected.reverse();
+    assert_eq!(indices(&newest.transactions), expected);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_export_to_sqlite_round_trip() {
+    use hledger_lib::{export_to_sqlite, get_print, PrintOptions};
+    use rust_decimal::Decimal;
+
+    let transactions = get_print(
+        None,
+        Some("tests/fixtures/test.journal"),
+        &PrintOptions::new(),
+    )
+    .expect("Failed to get print report");
+    let db = std::env::temp_dir().join(format!("hledger-export-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&db);
+    let stats = export_to_sqlite(&transactions, &db).expect("Failed to export");
+
+    let postings: Vec<_> = transactions.iter().flat_map(|t| &t.postings).collect();
+    assert_eq!(stats.transactions, transactions.len());
+    assert_eq!(stats.postings, postings.len());
+
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let count = |table: &str| -> usize {
+        conn.query_row(&format!("SELECT count(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    };
+    assert_eq!(count("transactions"), transactions.len());
+    assert_eq!(count("postings"), postings.len());
+    assert_eq!(count("amounts"), stats.amounts);
+
+    // Per-commodity sums match the report's, exactly
+    for commodity in ["$", "GOOG"] {
+        let expected: Decimal = postings
+            .iter()
+            .flat_map(|p| &p.amounts)
+            .filter(|a| a.commodity == commodity)
+            .map(|a| a.quantity)
+            .sum();
+        let quantities: Vec<String> = conn
+            .prepare("SELECT quantity FROM amounts WHERE commodity = ?1")
+            .unwrap()
+            .query_map([commodity], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        let actual: Decimal = quantities
+            .iter()
+            .map(|q| q.parse::<Decimal>().unwrap())
+            .sum();
+        assert_eq!(actual, expected, "sum of {}", commodity);
+    }
+    let _ = std::fs::remove_file(&db);
+}
+
+#[test]
+fn test_get_tag_usage() {
+    use hledger_lib::{get_tag_usage, MultiAmount, TagsOptions};
+    use rust_decimal::Decimal;
+
+    let usage = get_tag_usage(
+        None,
+        Some("tests/fixtures/tags.journal"),
+        &TagsOptions::new(),
+    )
+    .unwrap();
+    let names: Vec<&str> = usage.iter().map(|tag| tag.name.as_str()).collect();
+    assert_eq!(names, ["reimbursable", "trip", "unused"]);
+
+    let dollars = |quantity: i64| {
+        let mut total = MultiAmount::new();
+        total.add_quantity("$", Decimal::from(quantity));
+        total
+    };
+    let reimbursable = &usage[0];
+    assert_eq!(reimbursable.transaction_count, 2);
+    assert_eq!(reimbursable.posting_count, 2);
+    assert_eq!(reimbursable.total, dollars(315));
+
+    let trip = &usage[1];
+    assert_eq!(trip.transaction_count, 2);
+    let values: Vec<&str> = trip.values.iter().map(|v| v.value.as_str()).collect();
+    assert_eq!(values, ["paris", "rome"]);
+    assert_eq!(trip.values[1].total, dollars(20));
+
+    assert_eq!(usage[2].transaction_count, 0);
+}
+
+#[test]
+fn test_get_upcoming_transactions() {
+    use chrono::NaiveDate;
+    use hledger_lib::get_upcoming_transactions;
+
+    let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    let upcoming = get_upcoming_transactions(
+        None,
+        Some("tests/fixtures/periodic.journal"),
+        Some(today),
+        30,
+    )
+    .unwrap();
+
+    assert!(upcoming.iter().all(|u| u.transaction.date > today));
+    assert!(upcoming
+        .iter()
+        .all(|u| u.transaction.date <= NaiveDate::from_ymd_opt(2024, 7, 10).unwrap()));
+
+    let dentist = upcoming
+        .iter()
+        .find(|u| u.transaction.description == "Dentist")
+        .unwrap();
+    assert_eq!(dentist.rule, None);
+
+    let rent = upcoming
+        .iter()
+        .find(|u| u.transaction.description == "Rent")
+        .unwrap();
+    assert_eq!(rent.transaction.date.to_string(), "2024-07-01");
+    assert!(rent.rule.as_deref().unwrap().starts_with("monthly"));
+}
+
+#[test]
+fn test_create_journal_per_year() {
+    use hledger_lib::{
+        create_journal, get_accounts_detailed, get_print, AccountType, JournalTemplate,
+        PrintOptions, WriteOptions,
+    };
+    use rust_decimal::Decimal;
+
+    let dir = std::env::temp_dir().join(format!("scaffold-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("main.journal");
+    let template = JournalTemplate::new("$")
+        .opening_date(chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+        .opening_balance("assets:bank:checking", Decimal::new(250000, 2))
+        .opening_balance("liabilities:credit card", Decimal::new(-4000, 2))
+        .per_year();
+
+    let files =
+        create_journal(&path, &template, &WriteOptions::new()).expect("Failed to create journal");
+    assert_eq!(files.len(), 2);
+    assert!(files[1].ends_with("2024.journal"));
+
+    let journal = path.to_str();
+    let options = AccountsOptions {
+        types: true,
+        ..AccountsOptions::default()
+    };
+    let accounts = get_accounts_detailed(None, journal, &options).expect("Failed to get accounts");
+    let savings = accounts
+        .iter()
+        .find(|a| a.name == "assets:bank:savings")
+        .unwrap();
+    assert_eq!(savings.account_type, Some(AccountType::Asset));
+
+    let transactions = get_print(None, journal, &PrintOptions::new()).unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].postings.len(), 3);
+
+    // A second time would overwrite it
+    assert!(create_journal(&path, &template, &WriteOptions::new()).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_import_csv_previews_then_imports() {
+    use hledger_lib::{import_csv, CsvImportOutcome, CsvRules, ImportOptions};
+
+    let dir = std::env::temp_dir().join(format!("csv-import-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let csv = dir.join("bank.csv");
+    let journal = dir.join("main.journal");
+    std::fs::copy("tests/fixtures/bank.csv", &csv).unwrap();
+    std::fs::write(&journal, "").unwrap();
+    let (csv, journal) = (csv.to_str().unwrap(), journal.to_str());
+
+    let rules = CsvRules::new()
+        .skip(1)
+        .separator(";")
+        .fields(["date", "description", "", "credit", "debit"])
+        .date_format("%d/%m/%Y")
+        .decimal_mark(',')
+        .account1("assets:bank:checking")
+        .amount_in("%credit")
+        .amount_out("%debit");
+
+    let dry_run = ImportOptions::new().dry_run();
+    match import_csv(None, journal, csv, &rules, &dry_run).expect("Failed to preview") {
+        CsvImportOutcome::Preview(transactions) => assert_eq!(transactions.len(), 2),
+        other => panic!("Expected a preview, got {:?}", other),
+    }
+
+    let imported = import_csv(None, journal, csv, &rules, &ImportOptions::new()).unwrap();
+    assert!(matches!(imported, CsvImportOutcome::Imported(2)));
+    // hledger remembers what it imported
+    let again = import_csv(None, journal, csv, &rules, &ImportOptions::new()).unwrap();
+    assert!(matches!(again, CsvImportOutcome::Imported(0)));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
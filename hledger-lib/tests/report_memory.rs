@@ -0,0 +1,179 @@
+//! Heap use of the typed report parsers against a mock hledger (see
+//! `tests/support`): reports are deserialized as hledger's stdout is read,
+//! so parsing should need little beyond the report it returns, rather than
+//! a copy of the output or a `serde_json::Value` of it
+//!
+//! A counting global allocator measures every allocation in the process,
+//! including the threads reading hledger's stdout, so the measurements take
+//! turns.
+
+mod support;
+
+use hledger_lib::{get_balance, get_print, BalanceOptions, BalanceReport, PrintOptions};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use support::{Canned, MockHledger};
+
+struct Counting;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(now, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+static MEASURING: Mutex<()> = Mutex::new(());
+
+/// Run `f`, returning its result and the bytes the heap grew by: at its
+/// peak, and still held once `f` returned (by the result)
+fn heap_use<T>(f: impl FnOnce() -> T) -> (T, usize, usize) {
+    let _turn = MEASURING.lock().unwrap_or_else(|e| e.into_inner());
+    let start = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(start, Ordering::Relaxed);
+    let result = f();
+    let peak = PEAK.load(Ordering::Relaxed).saturating_sub(start);
+    let retained = CURRENT.load(Ordering::Relaxed).saturating_sub(start);
+    (result, peak, retained)
+}
+
+/// Check that parsing `output` took less than a quarter of its size on top
+/// of the report; buffering the output alone would take all of it
+fn assert_streamed(output: &str, peak: usize, retained: usize) {
+    let overhead = peak.saturating_sub(retained);
+    assert!(
+        overhead < output.len() / 4,
+        "parsing {} bytes of output peaked {} bytes above the {} byte report",
+        output.len(),
+        overhead,
+        retained
+    );
+}
+
+/// An amount as hledger writes it, style and all
+fn amount(cents: i64) -> serde_json::Value {
+    serde_json::json!({
+        "acommodity": "$",
+        "aquantity": {"decimalMantissa": cents, "decimalPlaces": 2, "floatingPoint": cents as f64 / 100.0},
+        "aprice": null,
+        "astyle": {
+            "ascommodityside": "L",
+            "ascommodityspaced": false,
+            "asdecimalmark": ".",
+            "asdigitgroups": [",", [3]],
+            "asprecision": 2,
+            "asrounding": "NoRounding"
+        }
+    })
+}
+
+/// `hledger print -O json` for `count` two-posting transactions
+fn print_json(count: usize) -> String {
+    let posting = |i: usize, account: String, cents: i64| {
+        serde_json::json!({
+            "paccount": account,
+            "pamount": [amount(cents)],
+            "pbalanceassertion": null,
+            "pcomment": "",
+            "pdate": null,
+            "pdate2": null,
+            "poriginal": null,
+            "pstatus": "Unmarked",
+            "ptags": [],
+            "ptransaction_": i.to_string(),
+            "ptype": "RegularPosting"
+        })
+    };
+    let position = |line: usize| serde_json::json!({"sourceColumn": 1, "sourceLine": line, "sourceName": "/tmp/large.journal"});
+    let transactions: Vec<_> = (1..=count)
+        .map(|i| {
+            let cents = (i % 50_000) as i64;
+            serde_json::json!({
+                "tcode": "",
+                "tcomment": "",
+                "tdate": format!("2024-{:02}-{:02}", i % 12 + 1, i % 28 + 1),
+                "tdate2": null,
+                "tdescription": format!("payee {}", i),
+                "tindex": i,
+                "tpostings": [
+                    posting(i, format!("expenses:item{}", i % 50), cents),
+                    posting(i, "assets:checking".to_string(), -cents)
+                ],
+                "tprecedingcomment": "",
+                "tsourcepos": [position(i * 4 - 3), position(i * 4)],
+                "tstatus": "Unmarked",
+                "ttags": []
+            })
+        })
+        .collect();
+    serde_json::to_string(&transactions).unwrap()
+}
+
+/// `hledger balance -M -O json` for `accounts` rows over `months` periods
+fn balance_json(accounts: usize, months: usize) -> String {
+    let date = |month: usize| {
+        serde_json::json!({
+            "tag": "Exact",
+            "contents": format!("{}-{:02}-01", 2000 + month / 12, month % 12 + 1)
+        })
+    };
+    let row = |name: serde_json::Value, cents: i64| {
+        serde_json::json!({
+            "prrName": name,
+            "prrAmounts": (0..months).map(|_| vec![amount(cents)]).collect::<Vec<_>>(),
+            "prrTotal": [amount(cents * months as i64)],
+            "prrAverage": [amount(cents)]
+        })
+    };
+    serde_json::json!({
+        "prDates": (0..months).map(|m| [date(m), date(m + 1)]).collect::<Vec<_>>(),
+        "prRows": (0..accounts)
+            .map(|i| row(format!("expenses:item{}", i).into(), i as i64))
+            .collect::<Vec<_>>(),
+        "prTotals": row(serde_json::json!([]), 1)
+    })
+    .to_string()
+}
+
+#[test]
+fn test_print_is_parsed_as_it_is_read() {
+    const TRANSACTIONS: usize = 2_000;
+    let output = print_json(TRANSACTIONS);
+    let mock = MockHledger::new().on(&["print"], Canned::stdout(output.as_str()));
+
+    let (report, peak, retained) =
+        heap_use(|| get_print(Some(mock.path()), None, &PrintOptions::new()));
+    assert_eq!(report.unwrap().len(), TRANSACTIONS);
+    assert_streamed(&output, peak, retained);
+}
+
+#[test]
+fn test_balance_is_parsed_as_it_is_read() {
+    const ACCOUNTS: usize = 300;
+    let output = balance_json(ACCOUNTS, 24);
+    let mock = MockHledger::new().on(&["balance"], Canned::stdout(output.as_str()));
+
+    let (report, peak, retained) =
+        heap_use(|| get_balance(Some(mock.path()), None, &BalanceOptions::new().monthly()));
+    let BalanceReport::Periodic(report) = report.unwrap() else {
+        panic!("Expected a periodic report");
+    };
+    assert_eq!(report.rows.len(), ACCOUNTS);
+    assert_streamed(&output, peak, retained);
+}
@@ -0,0 +1,76 @@
+//! A stand-in for the hledger executable, for tests that shouldn't depend on
+//! a real hledger install
+//!
+//! Canned responses are read from `<this executable>.json`, written by
+//! `MockHledger` in `tests/support/mod.rs`. The first response whose `args`
+//! all appear on the command line is replayed: after its delay, its stdout
+//! and stderr are written and its exit code returned. Each call is appended
+//! to `<this executable>.calls` as a line of JSON with its arguments,
+//! environment and working directory.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+struct Response {
+    args: Vec<String>,
+    #[serde(default)]
+    stdout: Vec<u8>,
+    #[serde(default)]
+    stderr: String,
+    #[serde(default)]
+    code: u8,
+    #[serde(default)]
+    delay_ms: u64,
+}
+
+fn main() -> ExitCode {
+    let exe = std::env::current_exe().expect("mock hledger: can't find own path");
+    let sidecar = |extension: &str| {
+        let mut path = OsString::from(exe.as_os_str());
+        path.push(extension);
+        PathBuf::from(path)
+    };
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Ok(mut calls) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(sidecar(".calls"))
+    {
+        let env: BTreeMap<String, String> = std::env::vars_os()
+            .map(|(k, v)| (k.to_string_lossy().into(), v.to_string_lossy().into()))
+            .collect();
+        let call = serde_json::json!({
+            "args": args,
+            "env": env,
+            "cwd": std::env::current_dir().ok(),
+        });
+        // One write per call, so calls made at once don't interleave
+        let _ = calls.write_all(format!("{}\n", call).as_bytes());
+    }
+
+    let responses: Vec<Response> = fs::read(sidecar(".json"))
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default();
+    let Some(response) = responses
+        .iter()
+        .find(|r| r.args.iter().all(|arg| args.contains(arg)))
+    else {
+        eprintln!("mock hledger: no canned response for {:?}", args);
+        return ExitCode::from(2);
+    };
+
+    thread::sleep(Duration::from_millis(response.delay_ms));
+    let _ = std::io::stdout().write_all(&response.stdout);
+    let _ = std::io::stderr().write_all(response.stderr.as_bytes());
+    ExitCode::from(response.code)
+}
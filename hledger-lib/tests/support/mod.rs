@@ -0,0 +1,154 @@
+//! Test helpers: a fake hledger that replays canned output, for exercising
+//! error paths deterministically and without hledger installed
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// What the mock prints and returns for a matching call
+#[derive(Debug, Clone, Default)]
+pub struct Canned {
+    stdout: Vec<u8>,
+    stderr: String,
+    code: u8,
+    delay: Duration,
+}
+
+impl Canned {
+    /// Succeed, printing `text`
+    pub fn stdout(text: impl Into<String>) -> Self {
+        Self::bytes(text.into().into_bytes())
+    }
+
+    /// Succeed, printing `bytes`, which needn't be UTF-8
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            stdout: bytes.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Exit with `code`, printing `stderr`
+    pub fn failure(code: u8, stderr: impl Into<String>) -> Self {
+        Self {
+            stderr: stderr.into(),
+            code,
+            ..Self::default()
+        }
+    }
+
+    /// Also print `stderr`, e.g. warnings alongside a successful report
+    pub fn with_stderr(mut self, stderr: impl Into<String>) -> Self {
+        self.stderr = stderr.into();
+        self
+    }
+
+    /// Wait this long before answering
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// One run of the mock, as it saw it
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct MockCall {
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<PathBuf>,
+}
+
+/// A private copy of the `mock-hledger` binary with its own canned
+/// responses, removed on drop
+///
+/// Pass [`MockHledger::path`] as `hledger_path`. Calls are answered by the
+/// first response registered with [`MockHledger::on`] whose arguments all
+/// appear on the command line; unmatched calls exit 2.
+pub struct MockHledger {
+    dir: PathBuf,
+    exe: PathBuf,
+    responses: Vec<serde_json::Value>,
+}
+
+impl MockHledger {
+    pub fn new() -> Self {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!(
+            "mock-hledger-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+
+        // A copy per mock keeps each test's responses and call log apart
+        let binary = PathBuf::from(env!("CARGO_BIN_EXE_mock-hledger"));
+        let exe = dir.join(binary.file_name().unwrap());
+        if fs::hard_link(&binary, &exe).is_err() {
+            fs::copy(&binary, &exe).unwrap();
+        }
+
+        let mock = Self {
+            dir,
+            exe,
+            responses: Vec::new(),
+        };
+        mock.write_responses();
+        mock
+    }
+
+    /// Answer calls containing all of `args` with `canned`
+    pub fn on(mut self, args: &[&str], canned: Canned) -> Self {
+        self.responses.push(serde_json::json!({
+            "args": args,
+            "stdout": canned.stdout,
+            "stderr": canned.stderr,
+            "code": canned.code,
+            "delay_ms": canned.delay.as_millis() as u64,
+        }));
+        self.write_responses();
+        self
+    }
+
+    /// Path of the mock executable, to pass as `hledger_path`
+    pub fn path(&self) -> &str {
+        self.exe.to_str().unwrap()
+    }
+
+    /// Arguments of each call so far, in order
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.invocations()
+            .into_iter()
+            .map(|call| call.args)
+            .collect()
+    }
+
+    /// Each call so far, in order
+    pub fn invocations(&self) -> Vec<MockCall> {
+        fs::read_to_string(self.sidecar(".calls"))
+            .unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn write_responses(&self) {
+        let json = serde_json::to_string(&self.responses).unwrap();
+        fs::write(self.sidecar(".json"), json).unwrap();
+    }
+
+    fn sidecar(&self, extension: &str) -> PathBuf {
+        let mut path = self.exe.clone().into_os_string();
+        path.push(extension);
+        PathBuf::from(path)
+    }
+}
+
+impl Drop for MockHledger {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}